@@ -0,0 +1,48 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use reversi_egui::game::{has_line, BoardConfig, GameState, Move};
+
+const ITERATIONS: u32 = 200_000;
+
+// Verificação antiga, percorrendo a grade casa a casa em todas as direções
+fn naive_check_winner(board: &[Vec<i32>], player_symbol: i32, win_length: usize) -> bool {
+    let (rows, cols) = (board.len() as isize, board[0].len() as isize);
+    for row in 0..rows {
+        for col in 0..cols {
+            for (d_row, d_col) in [(0, 1), (1, 0), (1, 1), (-1, 1)] {
+                let inside = (0..win_length as isize).all(|i| {
+                    let (r, c) = (row + i * d_row, col + i * d_col);
+                    (0..rows).contains(&r) && (0..cols).contains(&c) && board[r as usize][c as usize] == player_symbol
+                });
+                if inside {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+fn measure(mut f: impl FnMut() -> bool) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed()
+}
+
+fn main() {
+    // Meio de partida sem vencedor, o pior caso das duas versões
+    let mut game_state = GameState::with_config(BoardConfig::new(12, 10, 4).unwrap());
+    for col in [0, 1, 2, 4, 3, 5, 7, 6, 8, 9, 1, 0, 3, 2, 5, 4, 6, 7, 9, 8] {
+        game_state.apply_move(&Move { col }).unwrap();
+    }
+    let board = game_state.board();
+
+    let bitboard = measure(|| has_line(black_box(&game_state), 1));
+    let naive = measure(|| naive_check_winner(black_box(&board), 1, 4));
+    println!("has_line (bitboard):     {:?} por chamada", bitboard / ITERATIONS);
+    println!("has_line (grade):        {:?} por chamada", naive / ITERATIONS);
+    println!("ganho: {:.0}x", naive.as_secs_f64() / bitboard.as_secs_f64());
+}
@@ -0,0 +1,71 @@
+// Bot de exemplo: entra no servidor como um jogador qualquer e joga uma partida sozinho.
+//
+//   cargo run --example bot -- [endereço] [apelido] [sala]
+//
+// Sem sala, o bot entra na fila do /queue. Ele se apresenta com HELLO caps=bot,utf8, e daí em
+// diante o servidor só manda JSON, uma mensagem por linha. A cada your_turn o bot responde
+// com uma das casas da lista, a mais próxima do centro do tabuleiro.
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+use reversi_egui::protocol::{ErrorCode, Hello, LegalMove, ServerMessage, PROTOCOL_VERSION};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+const DEFAULT_NICK: &str = "robo";
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let nick = args.next().unwrap_or_else(|| DEFAULT_NICK.to_string());
+    let command = match args.next() {
+        Some(room) => format!("/join {}", room),
+        None => "/queue".to_string(),
+    };
+
+    let mut stream = TcpStream::connect(&address)?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let hello = Hello { version: PROTOCOL_VERSION, caps: vec!["bot".to_string(), "utf8".to_string()], lang: None };
+    stream.write_all(hello.to_line().as_bytes())?;
+    writeln!(stream, "NICK {}", nick)?;
+    writeln!(stream, "{}", command)?;
+
+    let mut board_size = (0, 0);
+    for line in reader.lines() {
+        // A ajuda do lobby chega antes do HELLO, ainda em texto; ela é ignorada
+        let Ok(message) = serde_json::from_str::<ServerMessage>(&line?) else {
+            continue;
+        };
+        match message {
+            ServerMessage::State { board, .. } => {
+                board_size = (board.len(), board.first().map_or(0, |row| row.len()));
+            }
+            ServerMessage::YourTurn { legal_moves, .. } => {
+                if let Some(choice) = pick_move(&legal_moves, board_size) {
+                    writeln!(stream, "{}", serde_json::to_string(&choice)?)?;
+                }
+            }
+            ServerMessage::Error { message, code } => {
+                eprintln!("erro ({:?}): {}", code.unwrap_or(ErrorCode::Error), message);
+            }
+            ServerMessage::Info { message } => println!("{}", message),
+            ServerMessage::GameOver { winner, you, .. } => {
+                let result = match winner {
+                    0 => "empate",
+                    winner if winner == you => "vitória",
+                    _ => "derrota",
+                };
+                println!("Fim de partida: {}.", result);
+                break;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Escolhe a coluna mais perto do centro e, nela, a linha mais central; no lig4, as colunas
+// do meio entram em mais sequências
+fn pick_move(legal_moves: &[LegalMove], (rows, cols): (usize, usize)) -> Option<LegalMove> {
+    let distance = |cell: &LegalMove| ((2 * cell.col + 1).abs_diff(cols), (2 * cell.row + 1).abs_diff(rows));
+    legal_moves.iter().copied().min_by_key(distance)
+}
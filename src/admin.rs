@@ -0,0 +1,220 @@
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::connection::Connection;
+use crate::fairplay;
+use crate::lobby::Lobby;
+use crate::moderation::{format_duration, format_sanctions, parse_duration, SanctionKind};
+use crate::room::GameMode;
+use crate::tournament;
+use crate::{read_line, ClientLines};
+
+pub const ADMIN_HELP: &str = "Console de administração:\n  rooms                  lista as salas abertas\n  players                lista os jogadores conectados e onde estão\n  kick <apelido>         derruba a conexão do jogador\n  ban <apelido|ip> [duração]\n                         bane o apelido ou o endereço, para sempre ou por 30s, 15m, 2h, 7d\n  unban <apelido|ip>     retira o banimento\n  mute <apelido> [duração]\n                         proíbe o jogador de usar o chat\n  unmute <apelido>       devolve o chat ao jogador\n  bans                   lista os banimentos e silêncios em vigor\n  close <sala>           fecha a sala e desconecta quem estiver nela\n  announce <mensagem>    envia um aviso para todas as conexões\n  tournament [lig4|reversi]\n                         abre agora as inscrições de um torneio\n  fairplay [partida]     lista os relatórios de jogo limpo das ranqueadas, ou mostra o de uma partida\n  motd reload            relê o arquivo da mensagem do dia\n  reload                 relê a configuração sem derrubar ninguém, como o SIGHUP\n  stats                  mostra os números do servidor\n  help                   mostra esta ajuda\n  quit                   sai do console\n";
+
+// Atende a conexão autenticada com ADMIN até ela sair. Os comandos valem com ou sem a
+// barra, como os da sala.
+pub async fn serve_admin(lines: &mut ClientLines, conn: &mut Connection, lobby: &Arc<Mutex<Lobby>>) {
+    conn.send_info(ADMIN_HELP).await;
+
+    loop {
+        let line = match read_line(lines, conn).await {
+            Some(line) => line,
+            None => return,
+        };
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        match (command.strip_prefix('/').unwrap_or(command).to_lowercase().as_str(), argument) {
+            ("rooms", "") => {
+                let rooms = lobby.lock().await.room_infos(true).await;
+                conn.send_rooms(rooms).await;
+            }
+            ("players", "") => {
+                let players = lobby.lock().await.players().await;
+                conn.send_info(&format_players(&players)).await;
+            }
+            ("kick", nick) if !nick.is_empty() => {
+                // O lobby é solto antes de responder, como nos outros comandos
+                let result = lobby.lock().await.kick(nick);
+                match result {
+                    Ok(()) => conn.send_info(&format!("{} foi desconectado.\n", nick)).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ("ban", target) if !target.is_empty() => {
+                let Some((target, duration)) = parse_sanction(target) else {
+                    conn.send_error("Duração inválida. Use, por exemplo, 30s, 15m, 2h ou 7d.\n").await;
+                    continue;
+                };
+                let sanction = {
+                    let mut lobby = lobby.lock().await;
+                    let sanction = lobby.moderation().ban(target, duration);
+                    // Quem está conectado com o apelido banido sai na hora
+                    if sanction.kind == SanctionKind::BanNick {
+                        let _ = lobby.kick(target);
+                    }
+                    sanction
+                };
+                match duration {
+                    Some(duration) => conn.send_info(&format!("{} está banido por {}.\n", sanction.target, format_duration(duration))).await,
+                    None => conn.send_info(&format!("{} está banido para sempre.\n", sanction.target)).await,
+                }
+            }
+            ("unban", target) if !target.is_empty() => {
+                let lifted = lobby.lock().await.moderation().unban(target);
+                if lifted {
+                    conn.send_info(&format!("O banimento de {} foi retirado.\n", target)).await;
+                } else {
+                    conn.send_error(&format!("{} não está banido.\n", target)).await;
+                }
+            }
+            ("mute", target) if !target.is_empty() => {
+                let Some((nick, duration)) = parse_sanction(target) else {
+                    conn.send_error("Duração inválida. Use, por exemplo, 30s, 15m, 2h ou 7d.\n").await;
+                    continue;
+                };
+                lobby.lock().await.moderation().mute(nick, duration);
+                match duration {
+                    Some(duration) => conn.send_info(&format!("{} está silenciado por {}.\n", nick, format_duration(duration))).await,
+                    None => conn.send_info(&format!("{} está silenciado para sempre.\n", nick)).await,
+                }
+            }
+            ("unmute", nick) if !nick.is_empty() => {
+                let lifted = lobby.lock().await.moderation().unmute(nick);
+                if lifted {
+                    conn.send_info(&format!("{} pode voltar a usar o chat.\n", nick)).await;
+                } else {
+                    conn.send_error(&format!("{} não está silenciado.\n", nick)).await;
+                }
+            }
+            ("bans", "") => {
+                let sanctions = lobby.lock().await.moderation().active();
+                conn.send_info(&format_sanctions(&sanctions)).await;
+            }
+            ("close", name) if !name.is_empty() => {
+                let result = lobby.lock().await.close_room(name).await;
+                match result {
+                    Ok(()) => conn.send_info(&format!("Sala '{}' fechada.\n", name)).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ("announce", text) if !text.is_empty() => {
+                let reached = lobby.lock().await.announce(text);
+                conn.send_info(&format!("Aviso enviado para {} conexões.\n", reached)).await;
+            }
+            ("tournament", mode) => {
+                let mode = match mode {
+                    "" => Some(GameMode::ConnectFour),
+                    mode => GameMode::parse(mode).filter(|mode| !matches!(mode, GameMode::Ring(_))),
+                };
+                let Some(mode) = mode else {
+                    conn.send_error("Modo desconhecido. Use tournament lig4 ou tournament reversi.\n").await;
+                    continue;
+                };
+                let opened = {
+                    let mut lobby = lobby.lock().await;
+                    let starts_at = tournament::unix_now() + lobby.tournament_registration().as_secs();
+                    lobby.open_tournament(mode, starts_at).map(|()| starts_at)
+                };
+                match opened {
+                    Ok(starts_at) => {
+                        info!(mode = mode.name(), "torneio aberto pelo administrador");
+                        tokio::spawn(tournament::play(Arc::clone(lobby), starts_at));
+                        let msg = format!("Inscrições abertas; o torneio começa {} (UTC).\n", tournament::describe(starts_at));
+                        conn.send_info(&msg).await;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ("fairplay", game) => {
+                let game = match game.trim_start_matches('#') {
+                    "" => None,
+                    game => match game.parse() {
+                        Ok(game) => Some(game),
+                        Err(_) => {
+                            conn.send_error("Número de partida inválido. Use fairplay ou fairplay <partida>.\n").await;
+                            continue;
+                        }
+                    },
+                };
+                match lobby.lock().await.fair_play_reports(game) {
+                    Ok(reports) if game.is_none() => conn.send_info(&fairplay::format_reports(&reports)).await,
+                    Ok(reports) => match reports.first() {
+                        Some(report) => conn.send_info(&fairplay::describe_report(report)).await,
+                        None => conn.send_error("Essa partida não tem relatório de jogo limpo. Só as ranqueadas têm.\n").await,
+                    },
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ("motd", "reload") => {
+                let result = lobby.lock().await.reload_motd();
+                match result {
+                    Ok(0) => conn.send_info("Mensagem do dia recarregada: o arquivo está vazio, e ela foi retirada.\n").await,
+                    Ok(lines) => conn.send_info(&format!("Mensagem do dia recarregada ({} linhas).\n", lines)).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ("reload", "") => {
+                // O lobby é solto antes de esperar, porque a recarga também o usa
+                let reloader = lobby.lock().await.reloader();
+                let result = match reloader {
+                    Some(reloader) => reloader.reload().await,
+                    None => Err("Este servidor não recarrega a configuração.\n".to_string()),
+                };
+                match result {
+                    Ok(report) => conn.send_info(&report).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ("stats", "") => {
+                let stats = lobby.lock().await.stats().await;
+                let uptime = stats.uptime.as_secs();
+                conn.send_info(&format!(
+                    "Jogadores conectados: {}\nSalas: {} ({} em andamento, {} aguardando, {} pausadas)\nNa fila: {}\nNo ar há {}h{:02}m{:02}s\n",
+                    stats.online,
+                    stats.rooms,
+                    stats.playing,
+                    stats.waiting,
+                    stats.paused,
+                    stats.queued,
+                    uptime / 3600,
+                    uptime / 60 % 60,
+                    uptime % 60
+                ))
+                .await;
+            }
+            ("help", "") => conn.send_info(ADMIN_HELP).await,
+            ("quit", "") => {
+                info!("console de administração fechado");
+                conn.send_info("Até logo!\n").await;
+                return;
+            }
+            _ => conn.send_error("Comando desconhecido ou incompleto. Digite help para ver os comandos.\n").await,
+        }
+    }
+}
+
+// Alvo e duração opcional de ban e mute; None se a duração não for entendida
+fn parse_sanction(argument: &str) -> Option<(&str, Option<std::time::Duration>)> {
+    match argument.split_once(' ') {
+        Some((target, duration)) => Some((target, Some(parse_duration(duration.trim())?))),
+        None => Some((argument, None)),
+    }
+}
+
+fn format_players(players: &[(String, Option<String>)]) -> String {
+    if players.is_empty() {
+        return "Nenhum jogador conectado.\n".to_string();
+    }
+
+    let mut list = String::from("Jogadores conectados:\n");
+    for (nick, room) in players {
+        match room {
+            Some(room) => list.push_str(&format!("  {} (sala {})\n", nick, room)),
+            None => list.push_str(&format!("  {} (no lobby)\n", nick)),
+        }
+    }
+    list
+}
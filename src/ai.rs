@@ -0,0 +1,351 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::book;
+use crate::solver::Solver;
+use crate::game::{check_draw, has_line, is_valid_move, GameState, Move};
+
+// Nível do computador: define quanto tempo a busca tem para cada jogada
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Impossible, // Joga com o solucionador completo: no lig4 padrão, nunca perde o que já era dele
+}
+
+impl Difficulty {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "easy" | "facil" | "fácil" => Some(Difficulty::Easy),
+            "medium" | "medio" | "médio" => Some(Difficulty::Medium),
+            "hard" | "dificil" | "difícil" => Some(Difficulty::Hard),
+            "impossible" | "impossivel" | "impossível" => Some(Difficulty::Impossible),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "fácil",
+            Difficulty::Medium => "médio",
+            Difficulty::Hard => "difícil",
+            Difficulty::Impossible => "impossível",
+        }
+    }
+
+    // Tempo de busca por jogada; a profundidade alcançada depende do tamanho do tabuleiro.
+    // No impossível é o prazo do solucionador, e a busca do difícil fica para quando ele não
+    // serve.
+    pub fn time_budget(&self) -> Duration {
+        match self {
+            Difficulty::Easy => Duration::from_millis(50),
+            Difficulty::Medium => Duration::from_millis(500),
+            Difficulty::Hard => Duration::from_secs(3),
+            Difficulty::Impossible => Duration::from_secs(15),
+        }
+    }
+}
+
+const WIN_SCORE: i32 = 1_000_000;
+
+// Acima disso a tabela de transposição é esvaziada, para a memória não crescer sem limite
+const MAX_TABLE_ENTRIES: usize = 1 << 20;
+
+// Colunas centrais primeiro: participam de mais linhas e melhoram os cortes alfa-beta
+fn column_order(cols: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..cols).collect();
+    order.sort_by_key(|col| (2 * *col as isize - (cols as isize - 1)).abs());
+    order
+}
+
+// Escolhe a coluna para o jogador da vez dentro do tempo do nível. Do médio para cima, as
+// aberturas do lig4 padrão saem do livro, na hora e sem busca.
+pub fn best_move(game_state: &GameState, difficulty: Difficulty) -> Option<usize> {
+    if difficulty != Difficulty::Easy {
+        // Na posição perdida qualquer jogada perde; a busca escolhe a que mais resiste
+        if let Some(book_move) = book::lookup(game_state).filter(|book_move| book_move.value >= 0) {
+            return Some(book_move.col);
+        }
+    }
+    if difficulty == Difficulty::Impossible {
+        // Fora do tabuleiro padrão, na posição perdida ou se o prazo acabar, joga como o difícil
+        let mut solver = Solver::new(Instant::now() + difficulty.time_budget());
+        if let Some((col, _)) = solver.best_move(game_state).filter(|(_, value)| *value >= 0) {
+            return Some(col);
+        }
+        return best_move_within(game_state, Difficulty::Hard.time_budget());
+    }
+    best_move_within(game_state, difficulty.time_budget())
+}
+
+// Aprofundamento iterativo: busca com profundidade 1, 2, 3... até o tempo acabar e fica
+// com a melhor coluna da última busca completa. A tabela de transposição guarda o que
+// as buscas anteriores descobriram e ordena as jogadas das seguintes.
+pub fn best_move_within(game_state: &GameState, budget: Duration) -> Option<usize> {
+    let mut search = Search {
+        deadline: Instant::now() + budget,
+        table: HashMap::new(),
+    };
+    let empty_cells = (game_state.rows() * game_state.cols() - game_state.piece_count()) as u32;
+    let mut best = column_order(game_state.cols())
+        .into_iter()
+        .find(|col| is_valid_move(game_state, &Move { col: *col }));
+
+    for depth in 1..=empty_cells.max(1) {
+        match search.root(game_state, depth, best) {
+            Some((col, score)) => {
+                best = Some(col);
+                // Vitória ou derrota garantidas: buscar mais fundo não muda a escolha
+                if score.abs() >= WIN_SCORE {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+    best
+}
+
+// Como a pontuação guardada se relaciona com a real, por causa dos cortes alfa-beta
+#[derive(Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower, // Houve corte: a pontuação real é pelo menos esta
+    Upper, // Nenhuma jogada superou alfa: a pontuação real é no máximo esta
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    depth: u32,
+    score: i32,
+    bound: Bound,
+    best_col: Option<usize>,
+}
+
+struct Search {
+    deadline: Instant,
+    table: HashMap<u64, Entry>,
+}
+
+impl Search {
+    // Busca completa na raiz; None se o tempo acabou antes de terminar
+    fn root(&mut self, game_state: &GameState, depth: u32, first: Option<usize>) -> Option<(usize, i32)> {
+        let mut best = None;
+        let mut alpha = -i32::MAX;
+
+        for col in move_order(game_state.cols(), first) {
+            let mut child = game_state.clone();
+            if !is_valid_move(&child, &Move { col }) || child.apply_move(&Move { col }).is_err() {
+                continue;
+            }
+
+            let score = -self.negamax(&child, depth - 1, -i32::MAX, -alpha)?;
+            if best.is_none() || score > alpha {
+                alpha = score;
+                best = Some(col);
+            }
+        }
+
+        best.map(|col| (col, alpha))
+    }
+
+    // Pontuação da posição do ponto de vista de quem joga agora (formulação negamax).
+    // None quando o tempo acabou no meio da busca.
+    fn negamax(&mut self, game_state: &GameState, depth: u32, mut alpha: i32, mut beta: i32) -> Option<i32> {
+        if Instant::now() >= self.deadline {
+            return None;
+        }
+        // Se quem acabou de jogar venceu, a posição está perdida; perder mais tarde é melhor
+        if has_line(game_state, -game_state.current_turn) {
+            return Some(-(WIN_SCORE + depth as i32));
+        }
+        if check_draw(game_state) {
+            return Some(0);
+        }
+        if depth == 0 {
+            return Some(evaluate(game_state, game_state.current_turn));
+        }
+
+        let key = position_key(game_state);
+        let entry = self.table.get(&key).copied();
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+            match entry.bound {
+                Bound::Exact => return Some(entry.score),
+                Bound::Lower => alpha = alpha.max(entry.score),
+                Bound::Upper => beta = beta.min(entry.score),
+            }
+            if alpha >= beta {
+                return Some(entry.score);
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best = -i32::MAX;
+        let mut best_col = None;
+        for col in move_order(game_state.cols(), entry.and_then(|entry| entry.best_col)) {
+            let mut child = game_state.clone();
+            if !is_valid_move(&child, &Move { col }) || child.apply_move(&Move { col }).is_err() {
+                continue;
+            }
+
+            let score = -self.negamax(&child, depth - 1, -beta, -alpha)?;
+            if score > best {
+                best = score;
+                best_col = Some(col);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        if self.table.len() >= MAX_TABLE_ENTRIES {
+            self.table.clear();
+        }
+        self.table.insert(key, Entry { depth, score: best, bound, best_col });
+        Some(best)
+    }
+}
+
+// Ordem das colunas com a melhor jogada conhecida na frente
+fn move_order(cols: usize, first: Option<usize>) -> Vec<usize> {
+    let mut order = column_order(cols);
+    if let Some(index) = first.and_then(|first| order.iter().position(|col| *col == first)) {
+        let col = order.remove(index);
+        order.insert(0, col);
+    }
+    order
+}
+
+// Identifica a posição na tabela de transposição: o tabuleiro e quem joga
+fn position_key(game_state: &GameState) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    game_state.pieces(1).hash(&mut hasher);
+    game_state.pieces(-1).hash(&mut hasher);
+    game_state.wild().hash(&mut hasher);
+    game_state.current_turn.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Avaliação posicional: peças no centro e janelas do tamanho da sequência ainda abertas
+fn evaluate(game_state: &GameState, player_symbol: i32) -> i32 {
+    let board = game_state.board();
+    let rows = game_state.rows() as isize;
+    let cols = game_state.cols() as isize;
+    let length = game_state.win_length as isize;
+    let mut score = 0;
+
+    for row in board.iter() {
+        if row[row.len() / 2] == player_symbol {
+            score += 3;
+        }
+    }
+
+    // Horizontais, verticais e as duas diagonais
+    let directions: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+    for (d_row, d_col) in directions {
+        for row in 0..rows {
+            for col in 0..cols {
+                let end_row = row + (length - 1) * d_row;
+                let end_col = col + (length - 1) * d_col;
+                if end_row < 0 || end_row >= rows || end_col >= cols {
+                    continue;
+                }
+
+                let window: Vec<i32> = (0..length)
+                    .map(|i| board[(row + i * d_row) as usize][(col + i * d_col) as usize])
+                    .collect();
+                score += score_window(&window, player_symbol);
+            }
+        }
+    }
+
+    score
+}
+
+fn score_window(window: &[i32], player_symbol: i32) -> i32 {
+    let mine = window.iter().filter(|cell| **cell == player_symbol).count();
+    let theirs = window.iter().filter(|cell| **cell == -player_symbol).count();
+    let empty = window.iter().filter(|cell| **cell == 0).count();
+    let length = window.len();
+
+    if mine == length {
+        100
+    } else if theirs == 0 && mine == length - 1 && empty == 1 {
+        5
+    } else if theirs == 0 && mine == length - 2 && empty == 2 {
+        2
+    } else if mine == 0 && theirs == length - 1 && empty == 1 {
+        -4
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::BoardConfig;
+
+    // Monta o tabuleiro a partir de linhas de texto, de cima para baixo
+    fn board_from(rows: &[&str], current_turn: i32) -> GameState {
+        let board: Vec<Vec<i32>> = rows
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|cell| match cell {
+                        'X' => 1,
+                        'O' => -1,
+                        _ => 0,
+                    })
+                    .collect()
+            })
+            .collect();
+        GameState::from_grid(&board, current_turn, 4)
+    }
+
+    #[test]
+    fn takes_the_winning_column() {
+        let game_state = board_from(
+            &[".......", ".......", ".......", ".......", "OO.....", "XXX...."],
+            1,
+        );
+        assert_eq!(best_move_within(&game_state, Duration::from_millis(50)), Some(3));
+    }
+
+    #[test]
+    fn blocks_the_opponent() {
+        let game_state = board_from(
+            &[".......", ".......", ".......", "X......", "X......", "OOO..X."],
+            1,
+        );
+        assert_eq!(best_move_within(&game_state, Duration::from_millis(50)), Some(3));
+    }
+
+    #[test]
+    fn openings_come_from_the_book_without_searching() {
+        let started = Instant::now();
+        assert_eq!(best_move(&GameState::new(), Difficulty::Hard), Some(3));
+        assert!(started.elapsed() < Difficulty::Hard.time_budget() / 10);
+    }
+
+    #[test]
+    fn stays_within_the_time_budget_on_big_boards() {
+        let config = BoardConfig::new(12, 10, 5).unwrap();
+        let game_state = GameState::with_config(config);
+        let started = Instant::now();
+        assert!(best_move_within(&game_state, Duration::from_millis(100)).is_some());
+        // Uma folga para a última posição avaliada e para máquinas lentas
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+}
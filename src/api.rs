@@ -0,0 +1,218 @@
+use std::future::Future;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+use tokio::time;
+use tracing::{debug, warn};
+
+use reversi_egui::protocol::{LegalMove, ServerMessage};
+
+use crate::health::HEALTH;
+use crate::http;
+use crate::lobby::Lobby;
+use crate::metrics::METRICS;
+use crate::room::{RoomEvent, RoomHandle, RoomStatus, RoomView};
+
+// Tempo máximo para ler o pedido e enviar a resposta; os fluxos de eventos não têm limite
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Intervalo dos comentários enviados em um fluxo parado, para notar quem fechou a página
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+// API HTTP só de leitura, em JSON, para painéis e páginas que mostram o movimento do
+// servidor sem falar o protocolo do jogo:
+//   GET /api/rooms                  salas abertas, como no /list (sem as privadas)
+//   GET /api/games/<número>         partida gravada no histórico, com as jogadas
+//   GET /api/leaderboard            ranking ELO, como no /top
+//   GET /api/players/<apelido>/stats vitórias, derrotas e empates do jogador
+//   GET /api/games/<sala>/stream    partida em andamento na sala, como Server-Sent Events
+//   GET /healthz e /readyz          saúde do servidor, para orquestradores (também na porta de métricas)
+pub async fn serve(listener: TcpListener, lobby: Arc<Mutex<Lobby>>) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                warn!(%err, "erro ao aceitar conexão da API");
+                continue;
+            }
+        };
+        let lobby = Arc::clone(&lobby);
+        tokio::spawn(async move {
+            if let Err(err) = respond(stream, &lobby).await {
+                debug!(%peer, %err, "erro ao responder a API");
+            }
+        });
+    }
+}
+
+// Resposta da API: o status HTTP e o corpo em JSON
+type Reply = (&'static str, String);
+
+async fn respond(stream: TcpStream, lobby: &Arc<Mutex<Lobby>>) -> io::Result<()> {
+    let (request, writer) = within(http::read_request(stream)).await?;
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let (status, body) = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "games", room_name, "stream"]) => {
+            let room = lobby.lock().await.public_room(room_name);
+            match room {
+                Ok(room) => return stream_game(writer, room).await,
+                Err(msg) => error("404 Not Found", &msg),
+            }
+        }
+        ("GET", ["healthz"]) => HEALTH.check(lobby, false).await,
+        ("GET", ["readyz"]) => HEALTH.check(lobby, true).await,
+        ("GET", segments) => route(segments, lobby).await,
+        _ => error("405 Method Not Allowed", "A API só aceita GET."),
+    };
+    within(http::respond(writer, status, "application/json; charset=utf-8", &body)).await
+}
+
+async fn within<T>(future: impl Future<Output = io::Result<T>>) -> io::Result<T> {
+    time::timeout(REQUEST_TIMEOUT, future)
+        .await
+        .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::TimedOut, "pedido da API demorou demais")))
+}
+
+async fn route(segments: &[&str], lobby: &Arc<Mutex<Lobby>>) -> Reply {
+    match segments {
+        ["api", "rooms"] => ok(&lobby.lock().await.room_infos(false).await),
+        ["api", "leaderboard"] => match lobby.lock().await.leaderboard() {
+            Ok(entries) => ok(&entries),
+            Err(msg) => error("503 Service Unavailable", &msg),
+        },
+        ["api", "games", id] => match id.parse::<i64>() {
+            Ok(id) => match lobby.lock().await.find_game(id) {
+                Ok(record) => ok(&record),
+                Err(msg) => error("404 Not Found", &msg),
+            },
+            Err(_) => error("400 Bad Request", "O número da partida precisa ser um inteiro."),
+        },
+        ["api", "players", nick, "stats"] => match lobby.lock().await.player_stats(nick) {
+            Ok(stats) => ok(&stats),
+            Err(msg) => error("404 Not Found", &msg),
+        },
+        _ => error("404 Not Found", "Use /api/rooms, /api/games/<número>, /api/games/<sala>/stream, /api/leaderboard ou /api/players/<apelido>/stats."),
+    }
+}
+
+// O que o fluxo já mandou da partida, para enviar só o que mudou
+struct Seen {
+    board: Vec<Vec<i32>>,
+    current_turn: i32,
+    legal_moves: Vec<LegalMove>,
+    last_move: Option<(usize, usize)>,
+}
+
+// Acompanha a sala como um espectador de fora do jogo. Os dados de cada evento são as mesmas
+// mensagens JSON do protocolo: "state" com o tabuleiro inteiro ao conectar, "move" com a casa
+// de cada jogada, "delta" para cada casa que mudou, "turn" na troca de vez e "game_over" no
+// fim, quando o fluxo fecha.
+async fn stream_game(mut writer: OwnedWriteHalf, room: RoomHandle) -> io::Result<()> {
+    let mut events = room.subscribe();
+    http::start_event_stream(&mut writer).await?;
+    let mut seen = None;
+    if let Some(view) = room.view(0).await {
+        send_changes(&mut writer, &view, &mut seen).await?;
+    }
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = time::sleep(KEEPALIVE_INTERVAL) => {
+                http::send_comment(&mut writer, "ping").await?;
+                continue;
+            }
+        };
+        match event {
+            Ok(RoomEvent::StateChanged | RoomEvent::OpponentJoined | RoomEvent::SidesSwapped) => {}
+            Ok(RoomEvent::GameOver) => {
+                if let Some(view) = room.view(0).await {
+                    send_changes(&mut writer, &view, &mut seen).await?;
+                    if let RoomStatus::Finished(winner) = view.status {
+                        send(&mut writer, "game_over", &view.game.game_over_message(winner, 0)).await?;
+                    }
+                }
+                break;
+            }
+            // Perdeu eventos: manda o tabuleiro inteiro de novo
+            Err(RecvError::Lagged(skipped)) => {
+                METRICS.events_lagged(skipped);
+                seen = None;
+            }
+            Ok(RoomEvent::Closed | RoomEvent::Aborted) | Err(RecvError::Closed) => break,
+            Ok(_) => continue,
+        }
+        match room.view(0).await {
+            Some(view) => send_changes(&mut writer, &view, &mut seen).await?,
+            None => break,
+        }
+    }
+    writer.shutdown().await
+}
+
+async fn send_changes(writer: &mut OwnedWriteHalf, view: &RoomView, seen: &mut Option<Seen>) -> io::Result<()> {
+    let message = view.game.state_message(0);
+    let ServerMessage::State { board, current_turn, legal_moves, .. } = &message else {
+        return Ok(());
+    };
+
+    let same_size = |last: &Seen| {
+        last.board.len() == board.len() && last.board.iter().zip(board).all(|(old, new)| old.len() == new.len())
+    };
+    match seen.take().filter(same_size) {
+        Some(last) => {
+            if let Some((row, col)) = view.last_move.filter(|played| Some(*played) != last.last_move) {
+                let played = serde_json::json!({ "row": row, "col": col, "val": board[row][col] });
+                http::send_event(writer, "move", &played.to_string()).await?;
+            }
+            for (row, (old, new)) in last.board.iter().zip(board).enumerate() {
+                for (col, (old, new)) in old.iter().zip(new).enumerate() {
+                    if old != new {
+                        send(writer, "delta", &ServerMessage::Delta { row, col, val: *new }).await?;
+                    }
+                }
+            }
+            // A vez pode continuar com o mesmo jogador (passe no reversi, jogada extra), mas as
+            // jogadas possíveis mudam
+            if last.current_turn != *current_turn || last.legal_moves != *legal_moves {
+                let turn = ServerMessage::Turn { current_turn: *current_turn, legal_moves: legal_moves.clone() };
+                send(writer, "turn", &turn).await?;
+            }
+        }
+        None => send(writer, "state", &message).await?,
+    }
+
+    *seen = Some(Seen {
+        board: board.clone(),
+        current_turn: *current_turn,
+        legal_moves: legal_moves.clone(),
+        last_move: view.last_move,
+    });
+    Ok(())
+}
+
+async fn send(writer: &mut OwnedWriteHalf, event: &str, message: &ServerMessage) -> io::Result<()> {
+    match serde_json::to_string(message) {
+        Ok(data) => http::send_event(writer, event, &data).await,
+        Err(_) => Ok(()),
+    }
+}
+
+fn ok<T: Serialize>(value: &T) -> Reply {
+    match serde_json::to_string(value) {
+        Ok(body) => ("200 OK", body),
+        Err(_) => error("500 Internal Server Error", "Não foi possível montar a resposta."),
+    }
+}
+
+// Erros vão como {"error": "..."}, com a mesma mensagem que o lobby daria
+fn error(status: &'static str, message: &str) -> Reply {
+    let body = serde_json::json!({ "error": message.trim_end() });
+    (status, body.to_string())
+}
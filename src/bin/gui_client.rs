@@ -0,0 +1,485 @@
+use eframe::egui;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reversi_egui::protocol::{ErrorCode, Hello, LegalMove, RoomInfo, ServerMessage, PROTOCOL_VERSION};
+
+const CELL_SIZE: f32 = 56.0;
+
+// Quanto tempo o título do tabuleiro pisca depois de um aviso do servidor
+const FLASH_DURATION: Duration = Duration::from_millis(1500);
+
+// Conexão com o servidor: a leitura acontece em uma thread separada
+struct ServerConnection {
+    stream: TcpStream,
+    incoming: mpsc::Receiver<ServerMessage>,
+}
+
+impl ServerConnection {
+    fn connect(address: &str, ctx: egui::Context) -> std::io::Result<Self> {
+        let mut stream = TcpStream::connect(address)?;
+        let hello = Hello {
+            version: PROTOCOL_VERSION,
+            caps: vec!["json".to_string(), "utf8".to_string(), "delta".to_string(), "confirm".to_string()],
+            lang: Some("pt".to_string()),
+        };
+        stream.write_all(hello.to_line().as_bytes())?;
+
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, incoming) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                // Linhas que não são JSON (como a ajuda inicial do lobby) são ignoradas
+                if let Ok(message) = serde_json::from_str::<ServerMessage>(&line) {
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                    ctx.request_repaint();
+                }
+            }
+            ctx.request_repaint();
+        });
+
+        Ok(ServerConnection { stream, incoming })
+    }
+
+    fn send(&mut self, line: &str) -> std::io::Result<()> {
+        self.stream.write_all(line.as_bytes())?;
+        self.stream.write_all(b"\n")
+    }
+}
+
+// Último estado de jogo recebido do servidor
+struct BoardView {
+    mode: String,
+    board: Vec<Vec<i32>>,
+    current_turn: i32,
+    you: i32,
+    legal_moves: Vec<LegalMove>, // Onde quem está com a vez pode jogar; o resto nem é enviado
+}
+
+impl BoardView {
+    // No lig4 e no anel basta a coluna; no reversi, a casa
+    fn is_legal(&self, row: usize, col: usize) -> bool {
+        self.legal_moves.iter().any(|legal| legal.col == col && (self.mode != "reversi" || legal.row == row))
+    }
+}
+
+// Jogada clicada que o servidor devolveu como prévia: a casa e o tabuleiro como ficaria
+struct MovePreview {
+    cell: (usize, usize),
+    board: Vec<Vec<i32>>,
+}
+
+struct GuiClient {
+    address: String,
+    nick: String,
+    resume_code: String, // Código de reconexão da última partida, vindo deste ou de outro cliente
+    room_name: String,
+    reversi: bool,
+    connection: Option<ServerConnection>,
+    rooms: Vec<RoomInfo>,
+    board: Option<BoardView>,
+    info: Option<String>,
+    error: Option<String>,
+    game_over: Option<String>,
+    clock: Option<([u64; 2], Instant)>, // Relógio de xadrez do X e do O, em ms, e quando chegou
+    preview: Option<MovePreview>, // Jogada proposta, à espera de confirmação
+    bell: bool,                   // Aviso da vez ligado com /bell on
+    flash_until: Option<Instant>, // O título pisca até aqui depois de um aviso
+    last_col: Option<usize>,      // Coluna da última jogada enviada, para entender o erro que voltar
+    full_column: Option<(usize, Instant)>, // Coluna recusada por estar cheia, destacada até o instante
+    winning_line: Vec<(usize, usize)>,     // Casas da sequência que decidiu a partida
+}
+
+impl GuiClient {
+    fn new() -> Self {
+        GuiClient {
+            address: "127.0.0.1:8080".to_string(),
+            nick: String::new(),
+            resume_code: String::new(),
+            room_name: String::new(),
+            reversi: false,
+            connection: None,
+            rooms: Vec::new(),
+            board: None,
+            info: None,
+            error: None,
+            game_over: None,
+            clock: None,
+            preview: None,
+            bell: false,
+            flash_until: None,
+            last_col: None,
+            full_column: None,
+            winning_line: Vec::new(),
+        }
+    }
+
+    fn send(&mut self, line: &str) {
+        if let Some(connection) = &mut self.connection {
+            if connection.send(line).is_err() {
+                self.error = Some("Conexão com o servidor perdida.".to_string());
+                self.connection = None;
+            }
+        }
+    }
+
+    // Processa as mensagens que chegaram desde o último quadro
+    fn poll_messages(&mut self) {
+        let mut messages = Vec::new();
+        if let Some(connection) = &self.connection {
+            while let Ok(message) = connection.incoming.try_recv() {
+                messages.push(message);
+            }
+        }
+
+        for message in messages {
+            match message {
+                ServerMessage::Info { message } => {
+                    // O código fica guardado para voltar à partida se a conexão cair
+                    if let Some(code) = message.strip_prefix("Código de reconexão: ").and_then(|rest| rest.split('.').next()) {
+                        self.resume_code = code.to_string();
+                    }
+                    self.info = Some(message);
+                }
+                ServerMessage::Error { message, code } => {
+                    // O código diz o motivo sem depender do idioma do texto
+                    if code == Some(ErrorCode::ColumnFull) {
+                        self.full_column = self.last_col.map(|col| (col, Instant::now() + FLASH_DURATION));
+                    }
+                    self.error = Some(message);
+                }
+                ServerMessage::Rooms { rooms } => {
+                    self.rooms = rooms;
+                    self.clock = None;
+                }
+                ServerMessage::State { mode, board, current_turn, you, legal_moves } => {
+                    self.error = None;
+                    self.preview = None;
+                    self.winning_line.clear();
+                    self.board = Some(BoardView { mode, board, current_turn, you, legal_moves });
+                }
+                ServerMessage::GameOver { winner, you, line } => {
+                    self.winning_line = line.iter().map(|cell| (cell.row, cell.col)).collect();
+                    let text = if winner == you {
+                        "Você venceu!"
+                    } else if winner == 0 {
+                        "Empate!"
+                    } else {
+                        "Você perdeu!"
+                    };
+                    self.game_over = Some(text.to_string());
+                }
+                ServerMessage::Chat { from, message, time } => {
+                    self.info = Some(format!("[{}] {}: {}", time, from, message));
+                }
+                ServerMessage::History { player, games } => {
+                    self.info = Some(format!("{} partidas recentes de {}", games.len(), player));
+                }
+                ServerMessage::Leaderboard { entries } => {
+                    let top: Vec<String> = entries.iter().map(|entry| format!("{} ({})", entry.nick, entry.rating)).collect();
+                    self.info = Some(format!("Ranking: {}", top.join(", ")));
+                }
+                // A partida exportada é salva no diretório atual
+                ServerMessage::Export { id, text } => {
+                    let path = format!("partida-{}.pgn", id);
+                    match std::fs::write(&path, text) {
+                        Ok(()) => self.info = Some(format!("Partida #{} salva em {}", id, path)),
+                        Err(err) => self.error = Some(format!("Não foi possível salvar {}: {}", path, err)),
+                    }
+                }
+                // Só as casas que mudaram desde o último estado, pedidas com a capacidade "delta"
+                ServerMessage::Delta { row, col, val } => {
+                    self.preview = None;
+                    if let Some(cell) = self.board.as_mut().and_then(|view| view.board.get_mut(row)?.get_mut(col)) {
+                        *cell = val;
+                    }
+                }
+                ServerMessage::Turn { current_turn, legal_moves } => {
+                    self.error = None;
+                    if let Some(view) = &mut self.board {
+                        view.current_turn = current_turn;
+                        view.legal_moves = legal_moves;
+                    }
+                }
+                ServerMessage::Clock { x_ms, o_ms } => self.clock = Some(([x_ms, o_ms], Instant::now())),
+                // Resposta ao HELLO; a versão combinada só muda algo quando houver uma v2
+                ServerMessage::Hello { .. } => {}
+                ServerMessage::Bell => self.flash_until = Some(Instant::now() + FLASH_DURATION),
+                // Só vem para bots; a janela sabe a vez pelo estado
+                ServerMessage::YourTurn { .. } => {}
+                // A jogada clicada volta como prévia e só vale depois do botão de confirmar
+                ServerMessage::Preview { row, col, board } => {
+                    self.error = None;
+                    self.preview = Some(MovePreview { cell: (row, col), board });
+                }
+            }
+        }
+    }
+
+    fn connect_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.label("Servidor:");
+            ui.text_edit_singleline(&mut self.address);
+            ui.label("Apelido:");
+            ui.text_edit_singleline(&mut self.nick);
+            // Com o código, a conexão volta direto para a partida, mesmo que ela tenha começado
+            // pelo telnet ou pelo navegador
+            ui.label("Código de reconexão:");
+            ui.text_edit_singleline(&mut self.resume_code);
+            if ui.button("Conectar").clicked() {
+                match ServerConnection::connect(&self.address, ctx.clone()) {
+                    Ok(connection) => {
+                        self.connection = Some(connection);
+                        self.error = None;
+                        match self.resume_code.trim() {
+                            "" => {
+                                let command = format!("NICK {}", self.nick.trim());
+                                self.send(&command);
+                                self.send("/list");
+                            }
+                            code => {
+                                let command = format!("RESUME {}", code);
+                                self.send(&command);
+                            }
+                        }
+                    }
+                    Err(err) => self.error = Some(format!("Não foi possível conectar: {}", err)),
+                }
+            }
+        });
+    }
+
+    fn lobby_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Sala:");
+            ui.text_edit_singleline(&mut self.room_name);
+            ui.selectable_value(&mut self.reversi, false, "Lig 4");
+            ui.selectable_value(&mut self.reversi, true, "Reversi");
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Criar").clicked() {
+                let mode = if self.reversi { "reversi" } else { "lig4" };
+                let command = format!("/create {} {}", self.room_name.trim(), mode);
+                self.send(&command);
+            }
+            if ui.button("Entrar").clicked() {
+                let command = format!("/join {}", self.room_name.trim());
+                self.send(&command);
+            }
+            if ui.button("Atualizar lista").clicked() {
+                self.send("/list");
+            }
+        });
+
+        ui.separator();
+        if self.rooms.is_empty() {
+            ui.label("Nenhuma sala aberta.");
+        }
+        let mut selected = None;
+        for room in &self.rooms {
+            let label = format!("{} [{}] ({}/{}, {})", room.name, room.mode, room.players.len(), room.seats, room.status);
+            if ui.selectable_label(self.room_name == room.name, label).clicked() {
+                selected = Some(room.name.clone());
+            }
+        }
+        if let Some(name) = selected {
+            self.room_name = name;
+        }
+    }
+
+    fn board_panel(&mut self, ui: &mut egui::Ui) {
+        let view = match &self.board {
+            Some(view) => view,
+            None => return,
+        };
+
+        let status = if let Some(game_over) = &self.game_over {
+            game_over.clone()
+        } else if view.current_turn == view.you {
+            "Sua vez".to_string()
+        } else {
+            "Vez do oponente".to_string()
+        };
+        // Depois de um aviso do servidor, o título alterna de cor até o fim do piscar
+        match self.flash_until.and_then(|until| until.checked_duration_since(Instant::now())) {
+            Some(left) => {
+                let color = if left.as_millis() / 250 % 2 == 0 { egui::Color32::YELLOW } else { egui::Color32::WHITE };
+                ui.heading(egui::RichText::new(status).color(color));
+                ui.ctx().request_repaint();
+            }
+            None => {
+                ui.heading(status);
+            }
+        }
+        let mut bell = self.bell;
+        let bell_toggled = ui.checkbox(&mut bell, "Avisar quando for minha vez").changed();
+
+        // O relógio de quem está com a vez corre desde a última mensagem do servidor; o valor
+        // é recalculado a cada quadro que a interface desenha
+        if let Some((remaining, received)) = self.clock {
+            let elapsed = if self.game_over.is_none() { received.elapsed().as_millis() as u64 } else { 0 };
+            let side = |slot: usize, symbol: i32| {
+                let ms = if view.current_turn == symbol { remaining[slot].saturating_sub(elapsed) } else { remaining[slot] };
+                format!("{}:{:02}", ms / 60_000, ms / 1000 % 60)
+            };
+            ui.label(format!("Relógio: X {} | O {}", side(0, 1), side(1, -1)));
+        }
+
+        // Com uma jogada proposta, o tabuleiro mostra como ele ficaria depois dela
+        let (proposed, board) = match &self.preview {
+            Some(preview) => (Some(preview.cell), &preview.board),
+            None => (None, &view.board),
+        };
+        let rows = board.len();
+        let cols = board.first().map_or(0, |row| row.len());
+        let size = egui::vec2(cols as f32 * CELL_SIZE, rows as f32 * CELL_SIZE);
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click());
+        let origin = response.rect.min;
+
+        let background = if view.mode == "reversi" {
+            egui::Color32::from_rgb(0, 110, 50)
+        } else {
+            egui::Color32::from_rgb(30, 60, 160)
+        };
+        painter.rect_filled(response.rect, 4.0, background);
+
+        // Destaca a coluna sob o mouse quando é a vez do jogador
+        let hovered = response.hover_pos().map(|pos| cell_at(pos - origin));
+        if let Some((_, col)) = hovered {
+            if view.mode != "reversi" && view.current_turn == view.you && view.is_legal(0, col) {
+                let rect = egui::Rect::from_min_size(
+                    origin + egui::vec2(col as f32 * CELL_SIZE, 0.0),
+                    egui::vec2(CELL_SIZE, size.y),
+                );
+                painter.rect_filled(rect, 4.0, egui::Color32::from_white_alpha(30));
+            }
+        }
+
+        // A coluna que o servidor recusou por estar cheia fica em vermelho por um instante
+        if let Some((col, _)) = self.full_column.filter(|(col, until)| *col < cols && Instant::now() < *until) {
+            let rect = egui::Rect::from_min_size(origin + egui::vec2(col as f32 * CELL_SIZE, 0.0), egui::vec2(CELL_SIZE, size.y));
+            painter.rect_filled(rect, 4.0, egui::Color32::from_rgba_unmultiplied(220, 40, 40, 70));
+            ui.ctx().request_repaint();
+        }
+
+        for (row, cells) in board.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let center = origin + egui::vec2((col as f32 + 0.5) * CELL_SIZE, (row as f32 + 0.5) * CELL_SIZE);
+                let color = match cell {
+                    // No anel, os jogadores 3 e 4 ganham cores próprias
+                    3 if view.mode == "anel" => egui::Color32::from_rgb(40, 200, 220),
+                    4 if view.mode == "anel" => egui::Color32::from_rgb(60, 200, 80),
+                    2 if view.mode == "anel" => egui::Color32::from_rgb(240, 210, 40),
+                    1 => egui::Color32::from_rgb(220, 40, 40),
+                    -1 => egui::Color32::from_rgb(240, 210, 40),
+                    // Coringa do Power Up
+                    2 | -2 => egui::Color32::from_rgb(190, 60, 220),
+                    _ => egui::Color32::from_gray(30),
+                };
+                painter.circle_filled(center, CELL_SIZE * 0.4, color);
+                if self.winning_line.contains(&(row, col)) {
+                    painter.circle_stroke(center, CELL_SIZE * 0.45, egui::Stroke::new(4.0, egui::Color32::from_rgb(60, 220, 90)));
+                }
+                // No reversi, as casas possíveis ganham um ponto na vez do jogador
+                if view.mode == "reversi" && view.current_turn == view.you && proposed.is_none() && view.is_legal(row, col) {
+                    painter.circle_filled(center, CELL_SIZE * 0.1, egui::Color32::from_white_alpha(90));
+                }
+                if proposed == Some((row, col)) {
+                    painter.circle_stroke(center, CELL_SIZE * 0.45, egui::Stroke::new(3.0, egui::Color32::WHITE));
+                }
+            }
+        }
+
+        let mut answer = None;
+        if proposed.is_some() {
+            ui.horizontal(|ui| {
+                if ui.button("Confirmar jogada").clicked() {
+                    answer = Some("/confirm");
+                }
+                if ui.button("Cancelar").clicked() {
+                    answer = Some("/cancel");
+                }
+            });
+        }
+
+        let clicked = if response.clicked() && self.game_over.is_none() && view.current_turn == view.you {
+            response.interact_pointer_pos().map(|pos| cell_at(pos - origin))
+        } else {
+            None
+        };
+
+        // Jogadas fora da lista nem vão ao servidor
+        if let Some((row, col)) = clicked {
+            if row < rows && col < cols && view.is_legal(row, col) {
+                let line = if view.mode == "reversi" {
+                    format!("{{\"row\": {}, \"col\": {}}}", row, col)
+                } else {
+                    format!("{{\"col\": {}}}", col)
+                };
+                self.last_col = Some(col);
+                self.send(&line);
+            }
+        }
+        if let Some(line) = answer {
+            if line == "/cancel" {
+                self.preview = None;
+            }
+            self.send(line);
+        }
+        if bell_toggled {
+            self.bell = bell;
+            self.send(if bell { "/bell on" } else { "/bell off" });
+        }
+    }
+}
+
+// Converte uma posição relativa ao tabuleiro em (linha, coluna)
+fn cell_at(offset: egui::Vec2) -> (usize, usize) {
+    let row = (offset.y / CELL_SIZE).max(0.0) as usize;
+    let col = (offset.x / CELL_SIZE).max(0.0) as usize;
+    (row, col)
+}
+
+impl eframe::App for GuiClient {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_messages();
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if self.connection.is_none() {
+                self.connect_panel(ui, ctx);
+            } else if self.board.is_none() {
+                self.lobby_panel(ui);
+            } else {
+                self.board_panel(ui);
+            }
+
+            ui.separator();
+            if let Some(info) = &self.info {
+                ui.label(info);
+            }
+            if let Some(error) = &self.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        });
+    }
+}
+
+fn main() {
+    let options = eframe::NativeOptions::default();
+    eframe::run_native(
+        "Reversi / Lig 4",
+        options,
+        Box::new(|_cc| Box::new(GuiClient::new())),
+    );
+}
@@ -0,0 +1,688 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use reversi_egui::game::{self, GameState, Move};
+use reversi_egui::protocol::{Hello, LegalMove, RoomInfo, ServerMessage, PROTOCOL_VERSION};
+use reversi_egui::reversi::{ReversiMove, ReversiState};
+use reversi_egui::ring;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+// Mensagens recentes mostradas abaixo do tabuleiro
+const LOG_LINES: usize = 6;
+
+// Intervalo entre verificações de mensagens do servidor enquanto não há teclas
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+const HELP: &str = "Setas escolhem a casa, Enter joga. Digite comandos (/list, /create, /join, /say...). Esc limpa, Ctrl+C sai.";
+
+const LOCAL_HELP: &str = "Setas escolhem a casa, Enter joga. /new recomeça a partida, Ctrl+C sai.";
+
+// O que chega da thread de leitura
+enum Incoming {
+    Message(ServerMessage),
+    Disconnected,
+}
+
+// Conexão com o servidor: a leitura acontece em uma thread separada
+struct ServerConnection {
+    stream: TcpStream,
+    incoming: mpsc::Receiver<Incoming>,
+}
+
+impl ServerConnection {
+    fn connect(address: &str) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(address)?;
+        let hello = Hello {
+            version: PROTOCOL_VERSION,
+            caps: vec!["json".to_string(), "utf8".to_string(), "delta".to_string()],
+            lang: Some("pt".to_string()),
+        };
+        stream.write_all(hello.to_line().as_bytes())?;
+
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, incoming) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                // Linhas que não são JSON (como a ajuda inicial do lobby) são ignoradas
+                if let Ok(message) = serde_json::from_str::<ServerMessage>(&line) {
+                    if sender.send(Incoming::Message(message)).is_err() {
+                        return;
+                    }
+                }
+            }
+            let _ = sender.send(Incoming::Disconnected);
+        });
+
+        Ok(ServerConnection { stream, incoming })
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        self.stream.write_all(line.as_bytes())?;
+        self.stream.write_all(b"\n")
+    }
+}
+
+// Regras do jogo local, as mesmas da biblioteca usada pelo servidor
+enum LocalGame {
+    ConnectFour(GameState),
+    Reversi(Box<ReversiState>),
+}
+
+// Partida para duas pessoas no mesmo teclado, sem servidor. Responde às jogadas com as
+// mesmas mensagens do protocolo JSON, para que a tela funcione igual nos dois modos.
+struct LocalSession {
+    reversi: bool,
+    game: LocalGame,
+    finished: bool,
+    outbox: VecDeque<ServerMessage>,
+}
+
+impl LocalSession {
+    fn new(reversi: bool) -> Self {
+        let mut session = LocalSession {
+            reversi,
+            game: LocalGame::ConnectFour(GameState::new()),
+            finished: false,
+            outbox: VecDeque::new(),
+        };
+        session.restart();
+        session
+    }
+
+    fn restart(&mut self) {
+        self.game = if self.reversi {
+            LocalGame::Reversi(Box::new(ReversiState::new()))
+        } else {
+            LocalGame::ConnectFour(GameState::new())
+        };
+        self.finished = false;
+        self.push_state();
+    }
+
+    // Quem joga agora é sempre "você": os dois jogadores se revezam no teclado
+    fn push_state(&mut self) {
+        let (mode, board, current_turn, legal_moves) = match &self.game {
+            LocalGame::ConnectFour(game_state) => {
+                let legal_moves = game_state
+                    .legal_moves()
+                    .iter()
+                    .filter_map(|player_move| game_state.landing_row(player_move.col).map(|row| LegalMove { row, col: player_move.col }))
+                    .collect();
+                ("lig4", game_state.board(), game_state.current_turn, legal_moves)
+            }
+            LocalGame::Reversi(reversi_state) => (
+                "reversi",
+                reversi_state.board.iter().map(|row| row.to_vec()).collect(),
+                reversi_state.current_turn,
+                reversi_state
+                    .legal_moves(reversi_state.current_turn)
+                    .iter()
+                    .map(|player_move| LegalMove { row: player_move.row, col: player_move.col })
+                    .collect(),
+            ),
+        };
+        let state = ServerMessage::State { mode: mode.to_string(), board, current_turn, you: current_turn, legal_moves };
+        self.outbox.push_back(state);
+    }
+
+    fn push_error(&mut self, message: &str) {
+        self.outbox.push_back(ServerMessage::Error { message: message.trim_end().to_string(), code: None });
+    }
+
+    // Trata uma linha como o servidor trataria: uma jogada em JSON ou um comando
+    fn handle(&mut self, line: &str) {
+        if line == "/new" {
+            self.restart();
+            return;
+        }
+        if !line.starts_with('{') {
+            self.push_error("No modo local só existe o comando /new.");
+            return;
+        }
+        if self.finished {
+            self.push_error("Partida encerrada. Digite /new para jogar de novo.");
+            return;
+        }
+
+        let result = match &mut self.game {
+            LocalGame::ConnectFour(game_state) => serde_json::from_str::<Move>(line)
+                .map_err(|err| err.to_string())
+                .and_then(|player_move| game_state.apply_move(&player_move).map_err(String::from))
+                .map(|_| (None, game_state.result())),
+            LocalGame::Reversi(reversi_state) => serde_json::from_str::<ReversiMove>(line)
+                .map_err(|err| err.to_string())
+                .and_then(|player_move| {
+                    let symbol = reversi_state.current_turn;
+                    let passed = reversi_state.apply_move(&player_move)?;
+                    let pass_notice = passed.then(|| format!("O {} não tem jogadas e passa a vez.", if symbol == 1 { 'O' } else { 'X' }));
+                    Ok((pass_notice, reversi_state.final_result()))
+                }),
+        };
+
+        match result {
+            Ok((pass_notice, winner)) => {
+                self.push_state();
+                if let Some(notice) = pass_notice {
+                    self.outbox.push_back(ServerMessage::Info { message: notice });
+                }
+                if let Some(winner) = winner {
+                    self.finished = true;
+                    let line = match &self.game {
+                        LocalGame::ConnectFour(game_state) => game::check_winner(game_state).map(|outcome| outcome.line).unwrap_or_default(),
+                        LocalGame::Reversi(_) => Vec::new(),
+                    };
+                    let line = line.into_iter().map(|(row, col)| LegalMove { row, col }).collect();
+                    self.outbox.push_back(ServerMessage::GameOver { winner, you: 0, line });
+                }
+            }
+            Err(msg) => self.push_error(&msg),
+        }
+    }
+}
+
+// De onde vêm as mensagens: um servidor ou a partida local
+enum Backend {
+    Server(ServerConnection),
+    Local(LocalSession),
+}
+
+// Último estado de jogo recebido do servidor
+struct BoardView {
+    mode: String,
+    board: Vec<Vec<i32>>,
+    current_turn: i32,
+    you: i32,
+    legal_moves: Vec<LegalMove>, // Onde quem está com a vez pode jogar
+}
+
+impl BoardView {
+    fn reversi(&self) -> bool {
+        self.mode == "reversi"
+    }
+
+    // No lig4 e no anel basta a coluna; no reversi, a casa
+    fn is_legal(&self, row: usize, col: usize) -> bool {
+        self.legal_moves.iter().any(|legal| legal.col == col && (!self.reversi() || legal.row == row))
+    }
+
+    fn ring(&self) -> bool {
+        self.mode == "anel"
+    }
+
+    fn rows(&self) -> usize {
+        self.board.len()
+    }
+
+    fn cols(&self) -> usize {
+        self.board.first().map_or(0, |row| row.len())
+    }
+}
+
+struct TerminalClient {
+    address: String,
+    nick: String,
+    connection: Backend,
+    connected: bool,
+    rooms: Vec<RoomInfo>,
+    board: Option<BoardView>,
+    cursor: (usize, usize), // Casa selecionada; no lig4 só a coluna importa
+    input: String,          // Comando sendo digitado
+    log: Vec<String>,
+    error: Option<String>,
+    game_over: Option<String>,
+    winning_line: Vec<(usize, usize)>, // Casas da sequência que decidiu a partida, piscando no tabuleiro
+    clock: Option<(u64, u64)>, // Relógio de xadrez do X e do O, em milissegundos, quando a sala usa um
+    bell: bool,                // O servidor pediu o aviso sonoro (/bell on); toca depois do próximo quadro
+}
+
+impl TerminalClient {
+    fn new(address: String, nick: String, connection: Backend) -> Self {
+        TerminalClient {
+            address,
+            nick,
+            connection,
+            connected: true,
+            rooms: Vec::new(),
+            board: None,
+            cursor: (0, 0),
+            input: String::new(),
+            log: Vec::new(),
+            error: None,
+            game_over: None,
+            winning_line: Vec::new(),
+            clock: None,
+            bell: false,
+        }
+    }
+
+    fn send(&mut self, line: &str) {
+        match &mut self.connection {
+            Backend::Server(connection) => {
+                if self.connected && connection.send(line).is_err() {
+                    self.disconnected();
+                }
+            }
+            Backend::Local(session) => session.handle(line),
+        }
+    }
+
+    fn local(&self) -> bool {
+        matches!(self.connection, Backend::Local(_))
+    }
+
+    fn next_incoming(&mut self) -> Option<Incoming> {
+        match &mut self.connection {
+            Backend::Server(connection) => connection.incoming.try_recv().ok(),
+            Backend::Local(session) => session.outbox.pop_front().map(Incoming::Message),
+        }
+    }
+
+    fn disconnected(&mut self) {
+        self.connected = false;
+        self.error = Some("Conexão com o servidor perdida. Ctrl+C para sair.".to_string());
+    }
+
+    fn push_log(&mut self, text: String) {
+        self.log.extend(text.lines().map(str::to_string));
+        let excess = self.log.len().saturating_sub(LOG_LINES);
+        self.log.drain(..excess);
+    }
+
+    // Processa as mensagens que chegaram; retorna true se algo mudou
+    fn poll_messages(&mut self) -> bool {
+        let mut changed = false;
+        while let Some(incoming) = self.next_incoming() {
+            changed = true;
+            let message = match incoming {
+                Incoming::Message(message) => message,
+                Incoming::Disconnected => {
+                    self.disconnected();
+                    continue;
+                }
+            };
+
+            match message {
+                ServerMessage::Info { message } => self.push_log(message),
+                ServerMessage::Error { message, .. } => self.error = Some(message),
+                ServerMessage::Rooms { rooms } => {
+                    self.rooms = rooms;
+                    self.board = None;
+                    self.game_over = None;
+                    self.clock = None;
+                }
+                ServerMessage::State { mode, board, current_turn, you, legal_moves } => {
+                    self.error = None;
+                    self.game_over = None;
+                    self.winning_line.clear();
+                    let view = BoardView { mode, board, current_turn, you, legal_moves };
+                    // Mantém o cursor dentro do tabuleiro, que pode ter outro tamanho
+                    self.cursor.0 = self.cursor.0.min(view.rows().saturating_sub(1));
+                    self.cursor.1 = self.cursor.1.min(view.cols().saturating_sub(1));
+                    self.board = Some(view);
+                }
+                ServerMessage::GameOver { winner, you, line } => {
+                    self.winning_line = line.iter().map(|cell| (cell.row, cell.col)).collect();
+                    let text = if self.local() {
+                        match winner {
+                            1 => "Vitória do X!",
+                            -1 => "Vitória do O!",
+                            _ => "Empate!",
+                        }
+                    } else if winner == you {
+                        "Você venceu!"
+                    } else if winner == 0 {
+                        "Empate!"
+                    } else {
+                        "Você perdeu!"
+                    };
+                    self.game_over = Some(text.to_string());
+                }
+                ServerMessage::Chat { from, message, time } => {
+                    self.push_log(format!("[{}] {}: {}", time, from, message));
+                }
+                ServerMessage::History { player, games } => {
+                    self.push_log(format!("Últimas partidas de {}:", player));
+                    for game in games {
+                        let result = match game.winner {
+                            1 => format!("venceu {}", game.player_x),
+                            -1 => format!("venceu {}", game.player_o),
+                            _ => "empate".to_string(),
+                        };
+                        let result = if game.forfeit { format!("{} por W.O.", result) } else { result };
+                        let result = match game.series {
+                            Some(series) => format!("{} (série {} x {})", result, series.wins_x, series.wins_o),
+                            None => result,
+                        };
+                        self.push_log(format!(
+                            "  #{} {} {} x {}, {}, {} jogadas",
+                            game.id, game.mode, game.player_x, game.player_o, result, game.moves
+                        ));
+                    }
+                }
+                ServerMessage::Leaderboard { entries } => {
+                    let top: Vec<String> = entries.iter().map(|entry| format!("{} ({})", entry.nick, entry.rating)).collect();
+                    self.push_log(format!("Ranking: {}", top.join(", ")));
+                }
+                // A partida exportada é salva no diretório atual
+                ServerMessage::Export { id, text } => {
+                    let path = format!("partida-{}.pgn", id);
+                    match std::fs::write(&path, text) {
+                        Ok(()) => self.push_log(format!("Partida #{} salva em {}", id, path)),
+                        Err(err) => self.error = Some(format!("Não foi possível salvar {}: {}", path, err)),
+                    }
+                }
+                // Só as casas que mudaram desde o último estado, pedidas com a capacidade "delta"
+                ServerMessage::Delta { row, col, val } => {
+                    if let Some(cell) = self.board.as_mut().and_then(|view| view.board.get_mut(row)?.get_mut(col)) {
+                        *cell = val;
+                    }
+                }
+                ServerMessage::Turn { current_turn, legal_moves } => {
+                    self.error = None;
+                    if let Some(view) = &mut self.board {
+                        view.current_turn = current_turn;
+                        view.legal_moves = legal_moves;
+                    }
+                }
+                ServerMessage::Clock { x_ms, o_ms } => self.clock = Some((x_ms, o_ms)),
+                // Resposta ao HELLO; a versão combinada só muda algo quando houver uma v2
+                ServerMessage::Hello { .. } => {}
+                // O terminal joga em um passo só e não pede as capacidades "confirm" e "bot"
+                ServerMessage::Preview { .. } | ServerMessage::YourTurn { .. } => {}
+                ServerMessage::Bell => self.bell = true,
+            }
+        }
+        changed
+    }
+
+    // Trata uma tecla; retorna false quando o jogador pede para sair
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d')) {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Left => self.move_cursor(0, -1),
+            KeyCode::Right => self.move_cursor(0, 1),
+            KeyCode::Up => self.move_cursor(-1, 0),
+            KeyCode::Down => self.move_cursor(1, 0),
+            KeyCode::Esc => self.input.clear(),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(ch) => self.input.push(ch),
+            KeyCode::Enter => {
+                if self.input.trim().is_empty() {
+                    self.play_selected();
+                } else {
+                    let line = std::mem::take(&mut self.input);
+                    self.error = None;
+                    self.send(line.trim());
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let view = match &self.board {
+            Some(view) => view,
+            None => return,
+        };
+        // No lig4 a peça cai sozinha: só a coluna é escolhida
+        let rows = if view.reversi() { rows } else { 0 };
+        let row = self.cursor.0 as isize + rows;
+        let col = self.cursor.1 as isize + cols;
+        if row >= 0 && (row as usize) < view.rows() {
+            self.cursor.0 = row as usize;
+        }
+        if col >= 0 && (col as usize) < view.cols() {
+            self.cursor.1 = col as usize;
+        }
+    }
+
+    fn play_selected(&mut self) {
+        let line = match &self.board {
+            // Jogadas fora da lista do servidor nem são enviadas
+            Some(view) if self.game_over.is_none() && view.current_turn == view.you && !view.is_legal(self.cursor.0, self.cursor.1) => {
+                self.error = Some(if view.reversi() { "Essa casa não vira nenhuma peça." } else { "Não é possível jogar nessa coluna." }.to_string());
+                return;
+            }
+            Some(view) if self.game_over.is_none() && view.current_turn == view.you => {
+                if view.reversi() {
+                    format!("{{\"row\": {}, \"col\": {}}}", self.cursor.0, self.cursor.1)
+                } else {
+                    format!("{{\"col\": {}}}", self.cursor.1)
+                }
+            }
+            Some(_) => {
+                self.error = Some("Aguarde a sua vez.".to_string());
+                return;
+            }
+            None => return,
+        };
+        self.send(&line);
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        let mut lines = Vec::new();
+        if self.local() {
+            lines.push("Reversi / Lig 4 - partida local".to_string());
+        } else {
+            lines.push(format!("Reversi / Lig 4 - {} em {}", self.nick, self.address));
+        }
+        lines.push(String::new());
+
+        match &self.board {
+            Some(view) => self.draw_board(out, view, &mut lines)?,
+            None => {
+                if self.rooms.is_empty() {
+                    lines.push("Nenhuma sala aberta. Crie uma com /create [nome] [lig4|reversi].".to_string());
+                } else {
+                    lines.push("Salas:".to_string());
+                    for room in &self.rooms {
+                        lines.push(format!(
+                            "  {} [{}] {} ({})",
+                            room.name,
+                            room.mode,
+                            room.players.join(" x "),
+                            room.status
+                        ));
+                    }
+                }
+            }
+        }
+        lines.push(String::new());
+        lines.extend(self.log.iter().cloned());
+        for line in lines {
+            queue!(out, Print(line), Print("\r\n"))?;
+        }
+
+        if let Some(error) = &self.error {
+            queue!(out, SetForegroundColor(Color::Red), Print(error), ResetColor, Print("\r\n"))?;
+        }
+        queue!(
+            out,
+            SetAttribute(Attribute::Dim),
+            Print(if self.local() { LOCAL_HELP } else { HELP }),
+            SetAttribute(Attribute::Reset),
+            Print("\r\n> "),
+            Print(&self.input)
+        )?;
+        out.flush()
+    }
+
+    // Desenha o tabuleiro e a linha de status; as linhas acumuladas antes dele saem primeiro
+    fn draw_board(&self, out: &mut impl Write, view: &BoardView, lines: &mut Vec<String>) -> io::Result<()> {
+        let status = if let Some(game_over) = &self.game_over {
+            game_over.clone()
+        } else if self.local() {
+            format!("Vez do {}", if view.current_turn == 1 { 'X' } else { 'O' })
+        } else if view.current_turn == view.you {
+            "Sua vez".to_string()
+        } else if view.you == 0 {
+            "Assistindo".to_string()
+        } else {
+            "Vez do oponente".to_string()
+        };
+        lines.push(status);
+        if let Some((x_ms, o_ms)) = self.clock {
+            lines.push(format!("Relógio: X {} | O {}", clock_text(x_ms), clock_text(o_ms)));
+        }
+        lines.push(String::new());
+        for line in lines.drain(..) {
+            queue!(out, Print(line), Print("\r\n"))?;
+        }
+
+        // No lig4, uma seta acima da coluna escolhida
+        if !view.reversi() {
+            let marker = format!("{}v\r\n", "  ".repeat(self.cursor.1 + 1));
+            queue!(out, Print(marker))?;
+        }
+
+        for (row, cells) in view.board.iter().enumerate() {
+            queue!(out, Print("  "))?;
+            for (col, cell) in cells.iter().enumerate() {
+                // No anel, cada jogador da roda tem a sua letra e a sua cor
+                let ring_color = [Color::Red, Color::Yellow, Color::Cyan, Color::Green];
+                let (glyph, color) = match cell {
+                    1..=4 if view.ring() => (ring::glyph(*cell).to_string(), ring_color[*cell as usize - 1]),
+                    1 => ("X".to_string(), Color::Red),
+                    -1 => ("O".to_string(), Color::Yellow),
+                    // Coringa do Power Up
+                    2 | -2 => ("*".to_string(), Color::Magenta),
+                    // No reversi, as casas possíveis na vez do jogador
+                    _ if view.reversi() && view.current_turn == view.you && view.is_legal(row, col) => ("+".to_string(), Color::Green),
+                    _ => (".".to_string(), Color::DarkGrey),
+                };
+                let selected = view.reversi() && (row, col) == self.cursor;
+                if selected {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                if self.winning_line.contains(&(row, col)) {
+                    queue!(out, SetAttribute(Attribute::Reverse), SetAttribute(Attribute::SlowBlink))?;
+                }
+                queue!(out, SetForegroundColor(color), Print(glyph), ResetColor, SetAttribute(Attribute::Reset), Print(" "))?;
+            }
+            queue!(out, Print("\r\n"))?;
+        }
+        Ok(())
+    }
+}
+
+// Tempo do relógio como minutos:segundos
+fn clock_text(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+// Restaura o terminal ao sair, inclusive se o programa entrar em pânico
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen)?;
+        Ok(RawModeGuard)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn run(client: &mut TerminalClient) -> io::Result<()> {
+    let _guard = RawModeGuard::enable()?;
+    let mut stdout = io::stdout();
+    client.draw(&mut stdout)?;
+
+    loop {
+        let mut changed = client.poll_messages();
+        if event::poll(POLL_INTERVAL)? {
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    if !client.handle_key(key) {
+                        return Ok(());
+                    }
+                    changed = true;
+                }
+                Event::Resize(_, _) => changed = true,
+                _ => {}
+            }
+        }
+        if changed {
+            client.draw(&mut stdout)?;
+        }
+        // O BEL vai direto para o terminal, que toca o som ou pisca conforme a configuração dele
+        if std::mem::take(&mut client.bell) {
+            stdout.write_all(b"\x07")?;
+            stdout.flush()?;
+        }
+    }
+}
+
+// Uso: terminal_client [endereço] [apelido]
+//      terminal_client --local [lig4|reversi]
+fn main() {
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("--local") {
+        let reversi = args.nth(1).is_some_and(|mode| mode == "reversi");
+        let mut client = TerminalClient::new(String::new(), String::new(), Backend::Local(LocalSession::new(reversi)));
+        if let Err(err) = run(&mut client) {
+            eprintln!("Erro no terminal: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let address = args.next().unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let nick = match args.next() {
+        Some(nick) => nick,
+        None => {
+            print!("Apelido: ");
+            let _ = io::stdout().flush();
+            let mut nick = String::new();
+            if io::stdin().read_line(&mut nick).is_err() {
+                return;
+            }
+            nick.trim().to_string()
+        }
+    };
+
+    let connection = match ServerConnection::connect(&address) {
+        Ok(connection) => connection,
+        Err(err) => {
+            eprintln!("Não foi possível conectar a {}: {}", address, err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut client = TerminalClient::new(address, nick, Backend::Server(connection));
+    let command = format!("NICK {}", client.nick);
+    client.send(&command);
+    client.send("/list");
+
+    if let Err(err) = run(&mut client) {
+        eprintln!("Erro no terminal: {}", err);
+        std::process::exit(1);
+    }
+}
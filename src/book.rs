@@ -0,0 +1,317 @@
+use crate::game::{is_valid_move, BoardConfig, GameState, Move, COLS};
+
+// Livro de aberturas do lig4 padrão (7x6, quatro em linha). Cobre os primeiros lances das
+// partidas em que o computador segue o próprio livro, jogando de X ou de O, contra qualquer
+// resposta do oponente. Cada jogada do livro mantém o valor teórico da posição: quem está
+// ganhando continua ganhando e quem empata não perde. A tabela foi gerada por um
+// solucionador completo.
+pub const BOOK_PLIES: usize = 8;
+
+// Jogada do livro e o valor da posição para quem joga: 1 vence, 0 empata e -1 perde com
+// o melhor jogo dos dois lados. Nas posições perdidas qualquer jogada perde, e a do livro
+// é só a primeira pela ordem do centro para as bordas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookMove {
+    pub col: usize,
+    pub value: i32,
+}
+
+// Altura de uma coluna na chave: as 6 casas e um bit de folga, como nos solucionadores
+// clássicos do lig4
+pub(crate) const KEY_COLUMN_BITS: usize = 7;
+
+// Jogada do livro para a posição, se ela estiver nele. Só vale no tabuleiro padrão e sem
+// coringas; quem começou a partida não importa, só quem joga agora.
+pub fn lookup(game_state: &GameState) -> Option<BookMove> {
+    if game_state.config() != BoardConfig::default() || game_state.wild() != 0 || game_state.piece_count() >= BOOK_PLIES {
+        return None;
+    }
+    let mover = key_bits(game_state.pieces(game_state.current_turn), game_state.rows());
+    let occupied = key_bits(game_state.pieces(1) | game_state.pieces(-1), game_state.rows());
+
+    // A tabela guarda só uma das duas orientações de cada posição: a de menor chave
+    let key = mover + occupied;
+    let mirrored_key = mirror(mover) + mirror(occupied);
+    let (key, mirrored) = if mirrored_key < key { (mirrored_key, true) } else { (key, false) };
+    let index = BOOK.binary_search_by_key(&key, |(entry, _, _)| *entry).ok()?;
+    let (_, col, value) = BOOK[index];
+    let col = if mirrored { COLS - 1 - col as usize } else { col as usize };
+    is_valid_move(game_state, &Move { col }).then_some(BookMove { col, value: value as i32 })
+}
+
+// Converte um bitboard do GameState, com colunas de `rows` bits, para colunas de 7 bits
+pub(crate) fn key_bits(pieces: u128, rows: usize) -> u64 {
+    (0..COLS).fold(0, |bits, col| {
+        let column = (pieces >> (col * rows)) as u64 & ((1 << rows) - 1);
+        bits | column << (col * KEY_COLUMN_BITS)
+    })
+}
+
+// O mesmo tabuleiro visto no espelho, com a primeira coluna no lugar da última
+pub(crate) fn mirror(bits: u64) -> u64 {
+    (0..COLS).fold(0, |mirrored, col| {
+        let column = (bits >> (col * KEY_COLUMN_BITS)) & ((1 << KEY_COLUMN_BITS) - 1);
+        mirrored | column << ((COLS - 1 - col) * KEY_COLUMN_BITS)
+    })
+}
+
+// Chave da posição, coluna da jogada e valor da posição, em ordem de chave
+const BOOK: [(u64, u8, i8); 859] = [
+    (0, 3, 1), (1, 3, 1), (128, 2, 1), (16384, 3, 0),
+    (32897, 3, 1), (33152, 1, 1), (34177, 3, 1), (35200, 2, 1),
+    (65664, 2, 1), (66944, 2, 1), (100737, 3, 1), (102784, 3, 1),
+    (166272, 2, 1), (196737, 3, 1), (196992, 3, 1), (198017, 3, 1),
+    (199040, 3, 1), (327808, 1, 1), (328321, 1, 1), (328832, 3, 1),
+    (329088, 2, 1), (590464, 1, 1), (2097152, 3, -1), (2130048, 3, 1),
+    (2131328, 3, 1), (2197888, 3, 1), (2293888, 3, 1), (2295168, 3, 1),
+    (2425472, 3, 1), (4194305, 3, 1), (4194307, 3, 1), (4194432, 1, 1),
+    (4194433, 3, 1), (4194945, 2, 1), (4195456, 3, 1), (4210688, 5, 1),
+    (4210689, 3, 1), (4210816, 3, 1), (4211328, 4, 1), (4227203, 3, 1),
+    (4227457, 1, 1), (4227715, 2, 1), (4228225, 3, 1), (4228483, 3, 1),
+    (4229505, 3, 1), (4243456, 2, 0), (4259969, 3, 1), (4260481, 2, 1),
+    (4261249, 2, 1), (4374529, 3, 1), (4374656, 3, 1), (4391043, 3, 1),
+    (4391297, 3, 1), (4391808, 1, 1), (4505600, 3, 1), (4522113, 3, 1),
+    (4522368, 3, 1), (8388608, 3, 1), (8388609, 3, 1), (8389248, 3, 1),
+    (8404992, 3, 0), (8421505, 3, 1), (8422017, 3, 1), (8422785, 2, 1),
+    (8568832, 3, 0), (8585345, 2, 1), (8585600, 3, 1), (10485761, 3, 1),
+    (10485888, 2, 1), (10502144, 4, 1), (10518657, 3, 1), (10518912, 3, 1),
+    (10519937, 3, 1), (10520960, 3, 1), (10551424, 3, 1), (10552704, 3, 1),
+    (10682497, 3, 1), (10682752, 3, 1), (10813568, 3, 1), (12582915, 3, 1),
+    (12582919, 0, 1), (12582951, 3, 1), (12583041, 3, 1), (12583043, 3, 1),
+    (12583063, 3, 1), (12583297, 3, 1), (12584065, 3, 1), (12585088, 3, 1),
+    (12599297, 3, 1), (12599299, 3, 1), (12599319, 3, 1), (12599425, 3, 1),
+    (12599680, 3, 1), (12600448, 3, 1), (12615815, 0, 1), (12616067, 3, 1),
+    (12632065, 3, 1), (12632192, 3, 1), (12648579, 3, 1), (12648833, 3, 1),
+    (12714113, 3, 1), (12763139, 3, 1), (12763265, 3, 1), (12763520, 3, 1),
+    (12894209, 3, 1), (12894336, 3, 1), (13156352, 3, 1), (18874368, 3, -1),
+    (18907264, 3, 1), (18908544, 3, 1), (19071104, 3, 1), (20971521, 3, 1),
+    (20971523, 3, 1), (20971543, 3, 1), (20971649, 3, 1), (20972672, 3, 1),
+    (20987905, 3, 1), (20988032, 3, 1), (21004419, 3, 1), (21037185, 2, 1),
+    (21151745, 3, 1), (21151872, 3, 1), (21282816, 3, 1), (25165825, 3, 1),
+    (25165827, 3, 1), (25165952, 3, 1), (25165953, 3, 1), (25166465, 3, 1),
+    (25166976, 3, 1), (25182208, 3, 1), (25182209, 3, 1), (25182336, 3, 1),
+    (25182848, 3, 1), (25198723, 3, 1), (25198977, 3, 1), (25214976, 3, 1),
+    (25231489, 3, 1), (25346049, 3, 1), (25346176, 3, 1), (25477120, 3, 1),
+    (27262979, 3, 1), (27263105, 2, 1), (27279361, 1, 1), (27279619, 3, 1),
+    (27279873, 3, 1), (27295875, 3, 1), (27296129, 3, 1), (27296640, 1, 1),
+    (27312385, 3, 1), (27328641, 3, 1), (27328896, 3, 1), (27394176, 3, 1),
+    (29360135, 3, 1), (29360259, 3, 1), (29360263, 3, 1), (29360513, 3, 1),
+    (29360515, 3, 1), (29361025, 3, 1), (29376515, 3, 1), (29376519, 3, 1),
+    (29376641, 3, 1), (29376643, 3, 1), (29376897, 3, 1), (29377408, 3, 1),
+    (29409281, 3, 1), (29409283, 3, 1), (29409409, 3, 1), (29409664, 3, 1),
+    (29474817, 3, 1), (29474944, 3, 1), (41943040, 3, 1), (41943041, 3, 1),
+    (41943680, 3, 1), (41959424, 3, 0), (41975937, 1, 1), (42123264, 3, 0),
+    (44040193, 2, 1), (44056833, 3, 1), (44072963, 3, 1), (44073089, 3, 1),
+    (44073344, 3, 1), (44105729, 3, 1), (44105856, 3, 1), (46137347, 3, 1),
+    (46137473, 3, 1), (46137475, 3, 1), (46137729, 4, 1), (46153729, 3, 1),
+    (46153731, 3, 1), (46153857, 3, 1), (46154112, 3, 1), (46186497, 3, 1),
+    (46186624, 3, 1), (52428801, 3, 1), (52428928, 2, 1), (52445184, 4, 1),
+    (52461697, 3, 1), (52461952, 1, 1), (52494464, 2, 1), (54525955, 3, 1),
+    (54525959, 0, 1), (54526081, 3, 1), (54526083, 3, 1), (54526337, 4, 1),
+    (54542337, 3, 1), (54542339, 3, 1), (54542465, 3, 1), (54542720, 1, 1),
+    (54575105, 2, 1), (54575232, 2, 1), (58720259, 3, 1), (58720263, 0, 1),
+    (58720385, 3, 1), (58720387, 3, 1), (58720640, 3, 1), (58720641, 3, 1),
+    (58736641, 3, 1), (58736643, 3, 1), (58736768, 3, 1), (58736769, 3, 1),
+    (58737024, 3, 1), (58769408, 3, 1), (58769409, 3, 1), (58769536, 3, 1),
+    (58834944, 2, 1), (60817415, 3, 1), (60817539, 3, 1), (60833795, 3, 1),
+    (77627393, 3, 1), (85983232, 3, -1), (86016128, 2, 1), (88080385, 4, 1),
+    (88080387, 4, 1), (88080513, 3, 1), (88096769, 2, 1), (88096896, 3, 1),
+    (92274689, 2, 1), (92274691, 3, 1), (92274816, 3, 1), (92274817, 3, 1),
+    (92291072, 2, 1), (92291073, 3, 1), (92291200, 3, 1), (92323840, 3, 1),
+    (94371843, 3, 1), (109051905, 2, 1), (109051907, 3, 1), (109052032, 1, 1),
+    (109052033, 1, 1), (109068288, 2, 1), (109068289, 3, 1), (109068416, 1, 1),
+    (109101056, 3, 1), (119537667, 5, 1), (119537793, 2, 1), (119554049, 1, 1),
+    (176160768, 2, 1), (176160769, 5, 1), (176177152, 5, 1), (186646529, 2, 1),
+    (220200961, 1, 1), (220201088, 2, 0), (220217344, 2, -1), (268468352, 2, 1),
+    (268469632, 2, 1), (268533889, 2, 1), (268534144, 2, 1), (268535169, 3, 1),
+    (268536192, 2, 1), (268599424, 2, 1), (268600704, 2, 1), (268632192, 2, 1),
+    (268633472, 2, 1), (268664963, 3, 1), (268665217, 3, 1), (268665728, 2, 1),
+    (268763776, 4, 1), (268796033, 4, 1), (268796288, 4, 1), (268861569, 2, 1),
+    (268861824, 1, 1), (268894337, 3, 1), (268894592, 3, 1), (269123712, 2, 1),
+    (269156480, 3, 1), (270631040, 3, 1), (270632320, 3, 1), (270762113, 3, 1),
+    (270762368, 3, 1), (270958720, 3, 1), (270991488, 3, 1), (272629761, 3, 1),
+    (272629888, 3, 1), (272630017, 1, 1), (272630272, 0, 1), (272630276, 3, 1),
+    (272630400, 3, 1), (272630531, 4, 1), (272630786, 3, 1), (272631041, 3, 1),
+    (272646144, 3, 1), (272646400, 3, 1), (272646658, 4, 1), (272646913, 3, 1),
+    (272662657, 4, 1), (272663169, 3, 1), (272663937, 3, 1), (272809984, 3, 1),
+    (272826497, 3, 1), (272826752, 3, 1), (276824320, 3, 1), (276824578, 3, 1),
+    (276824833, 3, 1), (278953985, 3, 1), (278954112, 3, 1), (278955392, 3, 1),
+    (278986752, 3, 1), (279019649, 3, 1), (279019904, 3, 1), (279085184, 3, 1),
+    (279117952, 3, 1), (281018369, 3, 1), (281018371, 3, 1), (281018391, 3, 1),
+    (281018497, 3, 1), (281018752, 3, 1), (281019009, 3, 1), (281019520, 3, 1),
+    (281034753, 3, 1), (281034880, 3, 1), (281035009, 3, 1), (281035264, 3, 1),
+    (281035392, 3, 1), (281051267, 3, 1), (281067520, 3, 1), (281067776, 3, 1),
+    (281084033, 3, 1), (281198593, 3, 1), (281198720, 3, 1), (281329664, 3, 1),
+    (287342592, 3, 1), (287408256, 3, 1), (289406977, 3, 1), (289407104, 3, 1),
+    (289407616, 3, 1), (289423360, 3, 1), (289423616, 3, 1), (289587200, 3, 1),
+    (293601281, 3, 1), (293601408, 3, 1), (293601537, 3, 1), (293601792, 3, 1),
+    (293601920, 3, 1), (293617664, 3, 1), (293617920, 3, 1), (293634177, 3, 1),
+    (293781504, 3, 1), (295698433, 3, 1), (295715073, 5, 1), (295731203, 3, 1),
+    (295731329, 3, 1), (295731584, 3, 1), (295763969, 3, 1), (295764096, 3, 1),
+    (295829504, 3, 1), (297795587, 3, 1), (297795591, 3, 1), (297795713, 3, 1),
+    (297795715, 3, 1), (297795969, 3, 1), (297796480, 3, 1), (297811969, 3, 1),
+    (297811971, 3, 1), (297812097, 3, 1), (297812352, 3, 1), (297844737, 3, 1),
+    (297844864, 3, 1), (297910272, 3, 1), (310378752, 3, 1), (312508417, 3, 1),
+    (312508544, 3, 1), (312541184, 3, 1), (314572801, 3, 1), (314572803, 3, 1),
+    (314572929, 3, 1), (314573184, 3, 1), (314589185, 3, 1), (314589312, 3, 1),
+    (314621952, 3, 1), (320897025, 3, 1), (320897152, 3, 1), (320929792, 2, 1),
+    (322961409, 3, 1), (322961411, 3, 1), (322961537, 3, 1), (322961792, 1, 1),
+    (322977793, 3, 1), (322977920, 3, 1), (323010560, 2, 1), (327155713, 3, 1),
+    (327155715, 3, 1), (327155840, 3, 1), (327155841, 3, 1), (327156096, 3, 1),
+    (327172096, 3, 1), (327172097, 3, 1), (327172224, 3, 1), (327204864, 3, 1),
+    (329252867, 3, 1), (329252993, 3, 1), (329269249, 3, 1), (354451456, 2, 1),
+    (356515841, 3, 1), (356515968, 3, 1), (356532224, 2, 1), (360710145, 3, 1),
+    (360710272, 3, 1), (360726528, 3, 1), (362807297, 2, 1), (377487361, 3, 1),
+    (377487488, 4, 0), (377503744, 3, 1), (387973121, 2, 1), (541082241, 3, 1),
+    (541082752, 3, 1), (541115008, 2, 1), (545276544, 3, 1), (547373057, 1, 1),
+    (547373184, 0, 1), (547373188, 3, 1), (547373315, 3, 1), (547373442, 3, 1),
+    (547373569, 3, 1), (547405824, 3, 1), (547405954, 3, 1), (547406081, 3, 1),
+    (555761794, 3, 1), (555761921, 3, 1), (564183041, 1, 1), (564183168, 0, 1),
+    (564248576, 2, 1), (580960256, 2, 1), (589316097, 1, 1), (589316224, 0, 1),
+    (589348864, 2, 1), (805404800, 4, 1), (805406080, 4, 1), (805535873, 2, 1),
+    (805536128, 2, 1), (805732480, 4, 1), (805765248, 3, 1), (809500928, 4, 1),
+    (809501186, 3, 1), (809501441, 4, 1), (815890560, 3, 1), (817889281, 3, 1),
+    (817889408, 3, 1), (817889920, 3, 1), (817905920, 3, 1), (818069504, 3, 1),
+    (830472448, 3, 1), (832602113, 3, 1), (832602240, 3, 1), (832634880, 3, 1),
+    (834666497, 3, 1), (834666499, 3, 1), (834666625, 3, 1), (834666880, 3, 1),
+    (834682881, 3, 1), (834683008, 3, 1), (834715648, 3, 1), (851443713, 3, 1),
+    (851443840, 4, 1), (859832321, 4, 1), (859832448, 4, 1), (864026625, 3, 1),
+    (864026752, 3, 1), (866123777, 3, 1), (1077953152, 3, 1), (1084244098, 3, 1),
+    (1084244225, 3, 1), (1101021185, 1, 1), (1101021312, 0, 1), (1346404483, 3, 1),
+    (1346404737, 1, 1), (1346437249, 3, 1), (1350598785, 3, 1), (1908408321, 3, 1),
+    (1908408448, 3, 1), (2420146305, 3, 1), (2952888449, 2, 1), (2952888704, 2, 1),
+    (2952953984, 2, 1), (2954985600, 3, 1), (2956984321, 3, 1), (2956984448, 3, 1),
+    (2956984577, 1, 1), (2956984832, 2, 1), (2957000960, 3, 1), (2961178880, 3, 1),
+    (2965372931, 3, 1), (2965373057, 3, 1), (2965373312, 3, 1), (2965389313, 3, 1),
+    (2965389440, 3, 1), (2973761537, 3, 1), (2973761664, 3, 1), (2977955841, 3, 1),
+    (2977955968, 3, 1), (5100372096, 2, 1), (5104468224, 3, 1), (5112856577, 3, 1),
+    (5112856704, 3, 1), (34359771264, 2, 1), (34359772544, 2, 1), (34359836801, 3, 1),
+    (34359837056, 2, 1), (34359838081, 3, 1), (34359839104, 3, 1), (34359902336, 2, 1),
+    (34359903616, 2, 1), (34359935104, 3, 1), (34359936384, 3, 1), (34359968129, 3, 1),
+    (34359968640, 2, 1), (34360066688, 3, 1), (34360099200, 2, 1), (34360164481, 2, 1),
+    (34360164736, 1, 1), (34360426624, 2, 1), (34361933952, 3, 1), (34361935232, 3, 1),
+    (34362065280, 3, 1), (34362261632, 3, 1), (34363932673, 3, 1), (34363933312, 3, 1),
+    (34363965569, 3, 1), (34363966081, 2, 1), (34363966849, 3, 1), (34364031107, 3, 1),
+    (34364031361, 2, 1), (34364096641, 3, 1), (34364129409, 3, 1), (34364129664, 3, 1),
+    (34364260480, 3, 1), (34368225409, 3, 1), (34368323712, 2, 1), (34370257024, 3, 1),
+    (34370258304, 3, 1), (34370322561, 3, 1), (34370322816, 3, 1), (34370388096, 3, 1),
+    (34370420864, 3, 1), (34372321281, 3, 1), (34372321283, 3, 1), (34372321303, 3, 1),
+    (34372321409, 3, 1), (34372321921, 3, 1), (34372322432, 3, 1), (34372337665, 3, 1),
+    (34372337792, 3, 1), (34372338304, 3, 1), (34372354179, 3, 1), (34372354433, 3, 1),
+    (34372386945, 3, 1), (34372501505, 3, 1), (34372501632, 3, 1), (34378711168, 3, 1),
+    (34380709889, 3, 1), (34380710528, 3, 1), (34380742785, 3, 1), (34384904193, 3, 1),
+    (34384904832, 3, 1), (34384937089, 3, 1), (34387001345, 3, 1), (34387017985, 4, 1),
+    (34387034241, 3, 1), (34387034496, 3, 1), (34387067008, 3, 1), (34389098499, 3, 1),
+    (34389098503, 3, 1), (34389098625, 3, 1), (34389098627, 3, 1), (34389098881, 3, 1),
+    (34389114881, 3, 1), (34389114883, 3, 1), (34389115009, 3, 1), (34389115264, 3, 1),
+    (34389147649, 3, 1), (34389147776, 3, 1), (34403811329, 2, 1), (34403811456, 3, 1),
+    (34405875713, 3, 1), (34405875715, 3, 1), (34405875841, 3, 1), (34405892097, 3, 1),
+    (34405892224, 3, 1), (34412200064, 3, 1), (34414264321, 3, 1), (34414264323, 3, 1),
+    (34414264449, 3, 1), (34414280705, 3, 1), (34414280832, 3, 1), (34418458625, 3, 1),
+    (34418458627, 3, 1), (34418458752, 3, 1), (34418458753, 3, 1), (34418475009, 3, 1),
+    (34418475136, 3, 1), (34420555779, 3, 1), (34420555905, 3, 1), (34420572161, 3, 1),
+    (34447818753, 3, 1), (34452013057, 3, 1), (34454110209, 2, 1), (34468790273, 5, 1),
+    (34479276033, 2, 1), (34628272256, 2, 1), (34628273536, 2, 1), (34628403329, 3, 1),
+    (34628403584, 3, 1), (34628534400, 4, 1), (34628599936, 6, 1), (34628632704, 3, 1),
+    (34630500480, 2, 1), (34632368384, 3, 1), (34632368642, 3, 1), (34632368897, 3, 1),
+    (34632466561, 3, 1), (34632564864, 3, 1), (34638758016, 6, 1), (34640756737, 3, 1),
+    (34640756993, 3, 1), (34640757248, 3, 1), (34640757376, 3, 1), (34640773376, 3, 1),
+    (34640789633, 3, 1), (34649145600, 3, 1), (34653339904, 3, 1), (34655469569, 6, 1),
+    (34655469696, 6, 1), (34657533953, 3, 1), (34657533955, 3, 1), (34657534081, 3, 1),
+    (34657534336, 3, 1), (34657550337, 3, 1), (34657550464, 3, 1), (34674311169, 3, 1),
+    (34682699777, 3, 1), (34686894081, 3, 1), (34688991233, 3, 1), (34896609281, 4, 1),
+    (34900820608, 2, 1), (34907095041, 3, 1), (34907111554, 3, 1), (34907111681, 3, 1),
+    (34923872259, 3, 1), (34923872385, 2, 1), (34923872640, 3, 1), (34923888641, 1, 1),
+    (34940649473, 3, 1), (34949038081, 3, 1), (35165274240, 2, 1), (35177627904, 3, 1),
+    (35194404865, 3, 1), (35460743169, 3, 1), (35706142849, 3, 1), (35970351107, 3, 1),
+    (35970351233, 3, 1), (35970351488, 4, 1), (35970367489, 4, 1), (35970531329, 4, 1),
+    (35970531456, 3, 1), (35972448257, 3, 1), (35974545415, 0, 1), (35974545539, 3, 1),
+    (35974545793, 3, 1), (35974561795, 3, 1), (35974561921, 3, 1), (35978739715, 3, 1),
+    (35978739841, 3, 1), (35980836867, 3, 1), (35980836993, 2, 1), (35980837248, 3, 1),
+    (35980853249, 1, 1), (35989225473, 3, 1), (37044092929, 4, 1), (37048287235, 3, 1),
+    (37048287361, 3, 1), (37054578689, 3, 1), (37316722944, 3, 1), (37325111297, 3, 1),
+    (37580963841, 3, 1), (37585158147, 4, 1), (37585158273, 3, 1), (37585158528, 4, 1),
+    (37585174529, 3, 1), (37589352449, 3, 1), (37591449601, 3, 1), (38117835137, 3, 1),
+    (38117835648, 4, 1), (38117851139, 3, 1), (38117851265, 3, 1), (38117851520, 3, 1),
+    (38117883905, 4, 1), (38119932288, 3, 1), (38119948289, 4, 1), (39732641793, 3, 1),
+    (40265318784, 1, 1), (40265334785, 2, 1), (41339060227, 3, 1), (41339060353, 2, 1),
+    (41339060608, 5, 1), (41339076609, 1, 1), (41341157377, 3, 1), (41875947521, 3, 1),
+    (45634027521, 3, 1), (68723687425, 3, 1), (68723851265, 3, 1), (68732076035, 3, 1),
+    (68732076161, 3, 1), (68732076416, 3, 1), (68732108801, 3, 1), (68740464641, 3, 1),
+    (68744658945, 3, 1), (69000511489, 3, 1), (103079314816, 2, 1), (103079444609, 3, 1),
+    (103079444864, 3, 1), (103083507841, 3, 1), (103091798017, 3, 1), (103091798656, 3, 1),
+    (103091830913, 3, 1), (103108575233, 3, 1), (103108575235, 3, 1), (103108575361, 3, 1),
+    (103108591617, 3, 1), (103125352449, 2, 1), (103133741057, 5, 1), (103137935361, 3, 1),
+    (103140032513, 3, 1), (103377010689, 3, 1), (103643348993, 3, 1), (104689827841, 4, 1),
+    (104694022147, 3, 1), (104694022273, 4, 1), (104700313601, 3, 1), (106304634881, 3, 1),
+    (106837311491, 3, 1), (106837311617, 3, 1), (106837327873, 3, 1), (106839408641, 3, 1),
+    (108984795137, 4, 1), (110058536961, 5, 1), (137451552769, 3, 1), (171802886145, 3, 1),
+    (171811274755, 3, 1), (171811274881, 3, 1), (171811291137, 3, 1), (171819663361, 3, 1),
+    (171823857665, 3, 1), (172071354369, 4, 1), (172079710209, 3, 1), (177167400961, 3, 1),
+    (240547528705, 3, 1), (244276264961, 4, 1), (309250228225, 3, 1), (378493992961, 3, 1),
+    (378498187267, 3, 1), (378498187393, 3, 1), (378498203649, 3, 1), (378502381569, 4, 1),
+    (378504478721, 3, 1), (379035058177, 3, 1), (379567734913, 3, 1), (379567751169, 3, 1),
+    (381178347521, 3, 1), (653376094209, 3, 1), (654445641729, 3, 1), (4398046609537, 3, 1),
+    (4398046740865, 3, 1), (4398046937217, 3, 1), (4398050705409, 3, 1), (4398050738305, 3, 1),
+    (4398050738817, 3, 1), (4398050739585, 3, 1), (4398050803843, 3, 1), (4398050804097, 2, 1),
+    (4398050869377, 3, 1), (4398050902145, 3, 1), (4398054998145, 3, 1), (4398057095297, 3, 1),
+    (4398059094017, 3, 1), (4398059094019, 3, 1), (4398059094039, 3, 1), (4398059094145, 3, 1),
+    (4398059094657, 3, 1), (4398059110401, 3, 1), (4398059126915, 3, 1), (4398059127169, 3, 1),
+    (4398059159681, 3, 1), (4398059274241, 3, 1), (4398067482625, 3, 1), (4398067515521, 3, 1),
+    (4398071676929, 3, 1), (4398071709825, 3, 1), (4398073774081, 3, 1), (4398073790721, 3, 1),
+    (4398073806977, 3, 1), (4398075871235, 3, 1), (4398075871239, 3, 1), (4398075871361, 3, 1),
+    (4398075871363, 3, 1), (4398075871617, 3, 1), (4398075887617, 3, 1), (4398075887619, 3, 1),
+    (4398075887745, 3, 1), (4398075920385, 3, 1), (4398090584065, 2, 1), (4398092648449, 3, 1),
+    (4398092648451, 3, 1), (4398092648577, 2, 1), (4398092664833, 3, 1), (4398101037057, 3, 1),
+    (4398101037059, 3, 1), (4398101037185, 3, 1), (4398101053441, 3, 1), (4398105231361, 3, 1),
+    (4398105231363, 3, 1), (4398105231489, 3, 1), (4398105247745, 3, 1), (4398107328515, 3, 1),
+    (4398107328641, 3, 1), (4398107344897, 3, 1), (4398134591489, 3, 1), (4398138785793, 3, 1),
+    (4398140882945, 3, 1), (4398155563009, 3, 1), (4398166048769, 3, 1), (4398315176065, 3, 1),
+    (4398319141378, 3, 1), (4398319141633, 3, 1), (4398319239297, 3, 1), (4398327529729, 3, 1),
+    (4398327562369, 3, 1), (4398342242305, 5, 1), (4398344306691, 3, 1), (4398344306817, 3, 1),
+    (4398344323073, 3, 1), (4398593884290, 3, 1), (4398593884417, 3, 1), (4399392915585, 3, 1),
+    (4432410542209, 3, 1), (4432418865281, 3, 1), (4432435609603, 3, 1), (4432435609729, 3, 1),
+    (4432955703299, 3, 1), (4434021056515, 3, 1), (13194168893443, 3, 1),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(cols: &[usize]) -> GameState {
+        let mut game_state = GameState::new();
+        for col in cols {
+            game_state.apply_move(&Move { col: *col }).unwrap();
+        }
+        game_state
+    }
+
+    #[test]
+    fn table_is_sorted_for_the_binary_search() {
+        assert!(BOOK.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn opens_in_the_center_whoever_starts() {
+        let center = Some(BookMove { col: 3, value: 1 });
+        assert_eq!(lookup(&GameState::new()), center);
+        let mut o_starts = GameState::new();
+        o_starts.current_turn = -1;
+        assert_eq!(lookup(&o_starts), center);
+    }
+
+    #[test]
+    fn mirrored_positions_get_mirrored_moves() {
+        for first in 0..COLS {
+            let reply = lookup(&play(&[first])).unwrap();
+            let mirrored_reply = lookup(&play(&[COLS - 1 - first])).unwrap();
+            assert_eq!((COLS - 1 - reply.col, reply.value), (mirrored_reply.col, mirrored_reply.value));
+        }
+    }
+
+    #[test]
+    fn other_boards_are_left_to_the_search() {
+        let config = BoardConfig::new(7, 8, 4).unwrap();
+        assert_eq!(lookup(&GameState::with_config(config)), None);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::i18n::Lang;
+
+// Onde o comando é digitado: no lobby ou dentro de uma sala. Na sala a barra é opcional,
+// como em "help" e "/help".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Context {
+    Lobby,
+    Room,
+}
+
+// Uma linha da ajuda: os nomes que chamam o comando, como ele é usado e o que faz, em
+// português e em inglês. Um comando com várias formas, como o /create, ocupa várias linhas.
+pub struct Command {
+    pub names: &'static [&'static str],
+    usage: [&'static str; 2],
+    summary: [&'static str; 2],
+}
+
+const LOBBY_COMMANDS: &[Command] = &[
+    Command { names: &["/help"], usage: ["/help [comando]", "/help [command]"], summary: ["mostra esta ajuda, ou só a de um comando", "shows this help, or only a command's"] },
+    Command { names: &["NICK"], usage: ["NICK <apelido> [senha]", "NICK <nickname> [password]"], summary: ["escolhe seu apelido (obrigatório para jogar)", "chooses your nickname (required to play)"] },
+    Command { names: &["/list"], usage: ["/list", "/list"], summary: ["lista as salas abertas", "lists the open rooms"] },
+    Command { names: &["/create"], usage: ["/create [nome] [lig4|reversi]", "/create [name] [lig4|reversi]"], summary: ["cria uma sala e entra nela como X", "creates a room and joins it as X"] },
+    Command { names: &["/create"], usage: ["/create <nome> <linhas> <colunas> <sequência>", "/create <name> <rows> <columns> <line length>"], summary: ["cria uma sala de lig4 com tabuleiro personalizado", "creates a lig4 room with a custom board"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] bo3|bo5", "/create <name> [...] bo3|bo5"], summary: ["cria a sala para uma série melhor de 3 ou de 5", "creates the room for a best-of-3 or best-of-5 series"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] swap", "/create <name> [...] swap"], summary: ["usa a regra da troca: o O pode ficar com a primeira jogada", "uses the swap rule: O may take over the first move"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] popout", "/create <name> [...] popout"], summary: ["joga o Pop Out: vale retirar uma peça sua da base de uma coluna", "plays Pop Out: you may remove your own piece from the bottom of a column"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] powerup", "/create <name> [...] powerup"], summary: ["joga o Power Up: cada um tem um coringa, que conta para os dois", "plays Power Up: each player has a wildcard that counts for both"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] 5+3", "/create <name> [...] 5+3"], summary: ["usa relógio de xadrez: minutos por jogador + segundos de acréscimo", "uses a chess clock: minutes per player + seconds of increment"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] rated", "/create <name> [...] rated"], summary: ["partida ranqueada, que vale para o ELO; sem isso, é amistosa", "rated game, which counts for ELO; otherwise it is casual"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] block1|block2|extra", "/create <name> [...] block1|block2|extra"], summary: ["você dá vantagem: colunas do meio bloqueadas no início, ou uma jogada extra a quem entrar", "you give a handicap: middle columns blocked at the start, or an extra move for whoever joins"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] 2v2", "/create <name> [...] 2v2"], summary: ["sala de duplas: quatro jogadores, e os parceiros se alternam nas jogadas", "team room: four players, and teammates alternate moves"] },
+    Command { names: &["/create"], usage: ["/create <nome> [...] nofilter|filter|strict", "/create <name> [...] nofilter|filter|strict"], summary: ["filtro de palavras do chat: desligado, normal ou rigoroso; sem isso, o do servidor", "chat word filter: off, normal or strict; without it, the server's"] },
+    Command { names: &["/create"], usage: ["/create <nome> anel [3|4]", "/create <name> anel [3|4]"], summary: ["cria uma sala do modo anel (experimental) para 3 ou 4 jogadores", "creates a ring mode room (experimental) for 3 or 4 players"] },
+    Command { names: &["/create"], usage: ["/create private [nome] [...]", "/create private [name] [...]"], summary: ["cria uma sala privada, fora do /list, com código de entrada", "creates a private room, hidden from /list, with a join code"] },
+    Command { names: &["/join"], usage: ["/join <nome>", "/join <name>"], summary: ["entra em uma sala como O", "joins a room as O"] },
+    Command { names: &["/join"], usage: ["/join code <código>", "/join code <code>"], summary: ["entra em uma sala privada com o código recebido", "joins a private room with the code you received"] },
+    Command { names: &["/watch"], usage: ["/watch <nome>", "/watch <name>"], summary: ["assiste a uma partida", "watches a game"] },
+    Command { names: &["/tv", "/watch"], usage: ["/tv | /watch random", "/tv | /watch random"], summary: ["assiste às partidas em andamento, uma de cada vez", "watches the games in progress, one at a time"] },
+    Command { names: &["/queue"], usage: ["/queue [lig4|reversi]", "/queue [lig4|reversi]"], summary: ["entra na fila e joga uma ranqueada com o oponente de ELO mais próximo", "joins the queue and plays a rated game against the closest ELO"] },
+    Command { names: &["/tournament"], usage: ["/tournament [history]", "/tournament [history]"], summary: ["mostra o próximo torneio automático, ou os últimos campeões", "shows the next automatic tournament, or the latest champions"] },
+    Command { names: &["/tournament"], usage: ["/tournament join", "/tournament join"], summary: ["entra no torneio com inscrições abertas e espera as partidas do mata-mata", "joins the tournament open for registration and waits for the knockout games"] },
+    Command { names: &["/solo"], usage: ["/solo easy|medium|hard|impossible", "/solo easy|medium|hard|impossible"], summary: ["joga lig4 contra o computador; o impossível joga perfeito no 7x6", "plays lig4 against the computer; impossible plays perfectly on 7x6"] },
+    Command { names: &["/exhibition"], usage: ["/exhibition [nome] <X> <O> [segundos]", "/exhibition [name] <X> <O> [seconds]"], summary: ["exibição de lig4: easy, medium, hard, impossible ou bot em cada lugar, com atraso entre as jogadas", "lig4 exhibition: easy, medium, hard, impossible or bot in each seat, with a delay between moves"] },
+    Command { names: &["/crowd"], usage: ["/crowd [nome] <nível> [segundos]", "/crowd [name] <level> [seconds]"], summary: ["multidão contra o computador: quem assiste vota em cada jogada do X", "the crowd against the computer: spectators vote on each of X's moves"] },
+    Command { names: &["/challenge"], usage: ["/challenge <apelido> [modo]", "/challenge <nickname> [mode]"], summary: ["desafia um jogador conectado para uma partida em sala privada", "challenges a connected player to a game in a private room"] },
+    Command { names: &["/accept", "/decline"], usage: ["/accept | /decline <apelido>", "/accept | /decline <nickname>"], summary: ["aceita ou recusa o desafio do jogador", "accepts or declines the player's challenge"] },
+    Command { names: &["/friend"], usage: ["/friend add <apelido>", "/friend add <nickname>"], summary: ["adiciona um jogador à sua lista de amigos", "adds a player to your friend list"] },
+    Command { names: &["/friends"], usage: ["/friends", "/friends"], summary: ["mostra seus amigos e onde cada um está", "shows your friends and where each one is"] },
+    Command { names: &["/who"], usage: ["/who", "/who"], summary: ["mostra quem está conectado: no lobby, na fila ou em qual sala", "shows who is online: in the lobby, in the queue or in which room"] },
+    Command { names: &["/motd"], usage: ["/motd", "/motd"], summary: ["mostra a mensagem do dia, com os avisos e as regras do servidor", "shows the message of the day, with the server's notices and rules"] },
+    Command { names: &["RESUME"], usage: ["RESUME <código>", "RESUME <code>"], summary: ["volta para a partida depois de perder a conexão", "returns to the game after losing the connection"] },
+    Command { names: &["/history"], usage: ["/history [jogador]", "/history [player]"], summary: ["mostra as últimas partidas (as suas, por padrão)", "shows the latest games (yours, by default)"] },
+    Command { names: &["/top"], usage: ["/top", "/top"], summary: ["mostra o ranking ELO dos jogadores", "shows the players' ELO ranking"] },
+    Command { names: &["/replay"], usage: ["/replay <número>", "/replay <number>"], summary: ["revê uma partida gravada, jogada a jogada", "replays a recorded game, move by move"] },
+    Command { names: &["/puzzle"], usage: ["/puzzle [número]", "/puzzle [number]"], summary: ["resolve um quebra-cabeça de lig4: vença em poucas jogadas", "solves a lig4 puzzle: win in a few moves"] },
+    Command { names: &["/puzzle"], usage: ["/puzzle stats [jogador]", "/puzzle stats [player]"], summary: ["mostra os quebra-cabeças resolvidos", "shows the puzzles solved"] },
+    Command { names: &["/daily"], usage: ["/daily", "/daily"], summary: ["desafio do dia, o mesmo para todos; /daily top mostra quem resolveu", "the daily challenge, the same for everyone; /daily top shows who solved it"] },
+    Command { names: &["/export"], usage: ["/export [número]", "/export [number]"], summary: ["exporta uma partida como texto (a sua última, por padrão)", "exports a game as text (your latest, by default)"] },
+    Command { names: &["/say"], usage: ["/say <mensagem>", "/say <message>"], summary: ["envia uma mensagem no chat do lobby, ou no da sala durante a partida", "sends a message to the lobby chat, or to the room chat during a game"] },
+    Command { names: &["/undo"], usage: ["/undo", "/undo"], summary: ["pede ao oponente para desfazer sua última jogada", "asks the opponent to undo your last move"] },
+    Command { names: &["/abort"], usage: ["/abort", "/abort"], summary: ["anula a partida sem resultado; depois da primeira jogada de cada lado, se o oponente aceitar", "cancels the game with no result; after each side's first move, if the opponent accepts"] },
+    Command { names: &["/draw"], usage: ["/draw", "/draw"], summary: ["propõe empate ao oponente; a proposta vale até a próxima jogada", "offers the opponent a draw; the offer stands until the next move"] },
+    Command { names: &["/accept", "/decline"], usage: ["/accept | /decline", "/accept | /decline"], summary: ["responde ao pedido para desfazer ou anular, ou à proposta de empate", "answers the undo or abort request, or the draw offer"] },
+    Command { names: &["HELLO"], usage: ["HELLO v1 [caps=json,color,utf8,delta,confirm,bot] [lang=pt|en]", "HELLO v1 [caps=json,color,utf8,delta,confirm,bot] [lang=pt|en]"], summary: ["negocia a versão do protocolo e os recursos do cliente", "negotiates the protocol version and the client features"] },
+    Command { names: &["MODE"], usage: ["MODE json|text", "MODE json|text"], summary: ["escolhe o formato das mensagens", "chooses the message format"] },
+    Command { names: &["COLOR"], usage: ["COLOR on|off", "COLOR on|off"], summary: ["liga ou desliga as cores do tabuleiro", "turns the board colors on or off"] },
+    Command { names: &["/bell"], usage: ["/bell on|off", "/bell on|off"], summary: ["toca um aviso quando for sua vez ou o oponente entrar", "rings a bell on your turn or when the opponent joins"] },
+    Command { names: &["LANG"], usage: ["LANG pt|en", "LANG pt|en"], summary: ["escolhe o idioma das mensagens", "chooses the message language"] },
+    Command { names: &["ADMIN"], usage: ["ADMIN [senha]", "ADMIN [password]"], summary: ["abre o console de administração, se o servidor permitir", "opens the administration console, if the server allows it"] },
+];
+
+const ROOM_COMMANDS: &[Command] = &[
+    Command { names: &[], usage: ["<coluna>", "<column>"], summary: ["joga na coluna, contando a partir de 1 (no reversi: <linha> <coluna>)", "plays in the column, counting from 1 (in reversi: <row> <column>)"] },
+    Command { names: &["/say"], usage: ["/say <mensagem>", "/say <message>"], summary: ["envia uma mensagem no chat da sala", "sends a message to the room chat"] },
+    Command { names: &["/team"], usage: ["/team <mensagem>", "/team <message>"], summary: ["envia uma mensagem só para o parceiro, nas salas de duplas", "sends a message to your teammate only, in team rooms"] },
+    Command { names: &["/vote"], usage: ["/vote <coluna>", "/vote <column>"], summary: ["vota na próxima jogada, na sala da multidão; vale também só o número", "votes on the next move, in the crowd room; the number alone also works"] },
+    Command { names: &["/undo"], usage: ["/undo", "/undo"], summary: ["pede ao oponente para desfazer sua última jogada", "asks the opponent to undo your last move"] },
+    Command { names: &["/abort"], usage: ["/abort", "/abort"], summary: ["anula a partida sem resultado e volta ao lobby; depois da primeira jogada de cada lado, só se o oponente aceitar", "cancels the game with no result and returns to the lobby; after each side's first move, only if the opponent accepts"] },
+    Command { names: &["/draw"], usage: ["/draw", "/draw"], summary: ["propõe empate ao oponente; a proposta vale até a próxima jogada", "offers the opponent a draw; the offer stands until the next move"] },
+    Command { names: &["/accept", "/decline"], usage: ["/accept | /decline", "/accept | /decline"], summary: ["responde ao pedido para desfazer ou anular, ou à proposta de empate", "answers the undo or abort request, or the draw offer"] },
+    Command { names: &["/swap"], usage: ["/swap", "/swap"], summary: ["fica com o lado do adversário depois da primeira jogada, se a sala permitir", "takes over the opponent's side after the first move, if the room allows it"] },
+    Command { names: &["/pop"], usage: ["/pop <coluna>", "/pop <column>"], summary: ["retira uma peça sua da base da coluna, se a sala jogar o Pop Out", "removes your own piece from the bottom of the column, if the room plays Pop Out"] },
+    Command { names: &["/wild"], usage: ["/wild <coluna>", "/wild <column>"], summary: ["joga o seu coringa na coluna, se a sala jogar o Power Up", "plays your wildcard in the column, if the room plays Power Up"] },
+    Command { names: &["/hint"], usage: ["/hint", "/hint"], summary: ["sugere a melhor coluna; 3 por partida, fora das partidas ranqueadas", "suggests the best column; 3 per game, outside rated games"] },
+    Command { names: &["/eval"], usage: ["/eval", "/eval"], summary: ["valor teórico da posição no lig4 7x6; na partida, só para quem assiste", "theoretical value of the position in 7x6 lig4; during the game, spectators only"] },
+    Command { names: &["/threats"], usage: ["/threats on|off", "/threats on|off"], summary: ["destaca no lig4 as casas que vencem na próxima jogada", "highlights the lig4 cells that win on the next move"] },
+    Command { names: &["/bell"], usage: ["/bell on|off", "/bell on|off"], summary: ["toca um aviso quando for sua vez", "rings a bell when it is your turn"] },
+    Command { names: &["/pause"], usage: ["/pause", "/pause"], summary: ["pede uma pausa; a partida para quando os dois pedirem", "asks for a break; the game stops when both ask"] },
+    Command { names: &["/resume"], usage: ["/resume", "/resume"], summary: ["retoma a partida pausada", "resumes the paused game"] },
+    Command { names: &["/confirm", "/cancel"], usage: ["/confirm | /cancel", "/confirm | /cancel"], summary: ["confirma ou descarta a jogada proposta, com a capacidade confirm", "confirms or discards the proposed move, with the confirm capability"] },
+    Command { names: &["resign", "desistir"], usage: ["resign | desistir", "resign | desistir"], summary: ["desiste da partida; conta como derrota por W.O.", "resigns the game; counts as a loss by forfeit"] },
+    Command { names: &["board"], usage: ["board", "board"], summary: ["redesenha o tabuleiro inteiro", "redraws the whole board"] },
+    Command { names: &["help"], usage: ["help [comando]", "help [command]"], summary: ["mostra esta ajuda, ou só a de um comando", "shows this help, or only a command's"] },
+    Command { names: &["quit"], usage: ["quit", "quit"], summary: ["sai da sala e desconecta", "leaves the room and disconnects"] },
+];
+
+// Outros nomes aceitos na sala, em português, e o comando que cada um chama
+const ROOM_ALIASES: &[(&str, &str)] = &[
+    ("/anular", "/abort"),
+    ("/empate", "/draw"),
+    ("/troca", "/swap"),
+    ("/dica", "/hint"),
+    ("/avaliar", "/eval"),
+    ("/pausa", "/pause"),
+    ("/continuar", "/resume"),
+    ("/confirmar", "/confirm"),
+    ("/cancelar", "/cancel"),
+    ("/coringa", "/wild"),
+    ("/ameacas", "/threats"),
+    ("/sino", "/bell"),
+    ("/votar", "/vote"),
+    ("/dupla", "/team"),
+];
+
+impl Context {
+    fn commands(self) -> &'static [Command] {
+        match self {
+            Context::Lobby => LOBBY_COMMANDS,
+            Context::Room => ROOM_COMMANDS,
+        }
+    }
+
+    fn aliases(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Context::Lobby => &[],
+            Context::Room => ROOM_ALIASES,
+        }
+    }
+
+    // Largura da coluna de uso na ajuda; usos maiores ficam sozinhos na linha
+    fn width(self) -> usize {
+        match self {
+            Context::Lobby => 30,
+            Context::Room => 22,
+        }
+    }
+
+    fn header(self) -> [&'static str; 2] {
+        match self {
+            Context::Lobby => ["Comandos do lobby:", "Lobby commands:"],
+            Context::Room => ["Comandos na sala:", "Room commands:"],
+        }
+    }
+
+    fn footer(self) -> Option<[&'static str; 2]> {
+        match self {
+            Context::Lobby => Some([
+                "Dentro de uma sala, digite help para ver os comandos da partida.",
+                "Inside a room, type help to see the game commands.",
+            ]),
+            Context::Room => None,
+        }
+    }
+
+    // Forma usada para comparar nomes: sem maiúsculas e, na sala, sem a barra
+    fn normalize(self, name: &str) -> String {
+        let name = name.to_lowercase();
+        match self {
+            Context::Room => name.trim_start_matches('/').to_string(),
+            Context::Lobby => name,
+        }
+    }
+}
+
+// Separa a linha em argumentos. Aspas duplas ou simples no começo de um argumento o
+// estendem até a aspa correspondente, para nomes com espaços (/create "sala do gil");
+// dentro delas, \ protege a própria aspa e a barra invertida.
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else {
+            return Ok(words);
+        };
+        let mut word = String::new();
+        if first == '"' || first == '\'' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some(c) if c == first => break,
+                    Some('\\') => match chars.next() {
+                        Some(c) if c == first || c == '\\' => word.push(c),
+                        Some(c) => {
+                            word.push('\\');
+                            word.push(c);
+                        }
+                        None => return Err("Aspas sem fechar na linha.\n".to_string()),
+                    },
+                    Some(c) => word.push(c),
+                    None => return Err("Aspas sem fechar na linha.\n".to_string()),
+                }
+            }
+        }
+        // O que vem colado depois das aspas, ou a palavra inteira, vai até o próximo espaço
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            word.push(c);
+        }
+        words.push(word);
+    }
+}
+
+// Só números: a coluna, ou linha e coluna no reversi
+pub fn is_move(text: &str) -> bool {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    matches!(words.len(), 1 | 2) && words.iter().all(|word| word.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+// Encontra o comando pelo nome, sem diferenciar maiúsculas. Comandos com barra também
+// aceitam o começo do nome, se só um comando começar assim (/hist para /history).
+// Devolve o nome como está na tabela, Ok(None) para o que não é comando e Err quando a
+// abreviação serve para mais de um.
+pub fn resolve(context: Context, word: &str) -> Result<Option<&'static str>, String> {
+    let wanted = context.normalize(word);
+    let names = context
+        .commands()
+        .iter()
+        .flat_map(|command| command.names.iter().map(|&name| (name, name)))
+        .chain(context.aliases().iter().copied());
+
+    let mut candidates: Vec<&'static str> = Vec::new();
+    for (name, canonical) in names {
+        let normalized = context.normalize(name);
+        if normalized == wanted {
+            return Ok(Some(canonical));
+        }
+        let abbreviated = word.len() > 1 && word.starts_with('/') && normalized.starts_with(&wanted);
+        if abbreviated && !candidates.contains(&canonical) {
+            candidates.push(canonical);
+        }
+    }
+    match candidates.as_slice() {
+        [] => Ok(None),
+        [name] => Ok(Some(name)),
+        _ => Err(format!("O comando {} é ambíguo: {}.\n", word, candidates.join(", "))),
+    }
+}
+
+// Entrada já separada entre jogada, comando e texto livre, como as respostas à revanche
+pub enum Input<'a> {
+    Move(&'a str),
+    Command(&'static str, &'a str), // Nome como está na tabela e o resto da linha, sem alterar
+    Text(&'a str),
+}
+
+pub fn classify(context: Context, line: &str) -> Result<Input<'_>, String> {
+    let trimmed = line.trim();
+    if is_move(trimmed) {
+        return Ok(Input::Move(trimmed));
+    }
+    let (word, rest) = trimmed.split_once(char::is_whitespace).unwrap_or((trimmed, ""));
+    match resolve(context, word)? {
+        Some(name) => Ok(Input::Command(name, rest)),
+        None if word.starts_with('/') => Err(unknown(word)),
+        None => Ok(Input::Text(line)),
+    }
+}
+
+pub fn unknown(word: &str) -> String {
+    format!("Comando desconhecido: {}. Digite help para ver os comandos.\n", word)
+}
+
+// Ajuda completa do lugar, em português; a conexão traduz na hora de enviar
+pub fn help(context: Context) -> String {
+    render_help(context, Lang::Pt)
+}
+
+// Ajuda de um só comando, com todas as suas formas. Procura primeiro no lugar em que o
+// jogador está e depois no outro, para que a ajuda da sala explique também o lobby.
+pub fn usage(context: Context, topic: &str) -> Option<String> {
+    render_usage(context, topic, Lang::Pt)
+}
+
+fn render_help(context: Context, lang: Lang) -> String {
+    let index = lang_index(lang);
+    let mut text = format!("{}\n", context.header()[index]);
+    for command in context.commands() {
+        render_row(&mut text, context, command, index);
+    }
+    if let Some(footer) = context.footer() {
+        text.push_str(footer[index]);
+        text.push('\n');
+    }
+    text
+}
+
+fn render_usage(context: Context, topic: &str, lang: Lang) -> Option<String> {
+    let other = match context {
+        Context::Lobby => Context::Room,
+        Context::Room => Context::Lobby,
+    };
+    // O tópico vale com ou sem a barra: "/help join" e "/help /join"
+    let slashed = format!("/{}", topic.trim_start_matches('/'));
+    [context, other].into_iter().find_map(|context| {
+        let name = [topic, slashed.as_str()].into_iter().find_map(|topic| resolve(context, topic).ok().flatten())?;
+        let index = lang_index(lang);
+        let header = ["Uso de {}:", "Usage of {}:"][index].replace("{}", name);
+        let mut text = format!("{}\n", header);
+        for command in context.commands().iter().filter(|command| command.names.contains(&name)) {
+            render_row(&mut text, context, command, index);
+        }
+        Some(text)
+    })
+}
+
+fn render_row(text: &mut String, context: Context, command: &Command, index: usize) {
+    let width = context.width();
+    let (usage, summary) = (command.usage[index], command.summary[index]);
+    if usage.chars().count() < width {
+        text.push_str(&format!("  {:<width$}{}\n", usage, summary));
+    } else {
+        text.push_str(&format!("  {}\n  {:width$}{}\n", usage, "", summary));
+    }
+}
+
+fn lang_index(lang: Lang) -> usize {
+    match lang {
+        Lang::Pt => 0,
+        Lang::En => 1,
+    }
+}
+
+// Traduz uma ajuda montada aqui, que não está no catálogo por ser gerada da tabela
+pub fn translate_help(lang: Lang, text: &str) -> Option<String> {
+    for context in [Context::Lobby, Context::Room] {
+        if text == help(context) {
+            return Some(render_help(context, lang));
+        }
+    }
+    let topic = text.strip_prefix("Uso de ")?.split_once(":\n")?.0;
+    [Context::Lobby, Context::Room]
+        .into_iter()
+        .find(|&context| usage(context, topic).as_deref() == Some(text))
+        .and_then(|context| render_usage(context, topic, lang))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_group_words_and_unclosed_quotes_are_refused() {
+        assert_eq!(tokenize(r#"/create "sala do gil"  bo3"#).unwrap(), ["/create", "sala do gil", "bo3"]);
+        assert_eq!(tokenize(r#"NICK 'd\'água' "a\\b""#).unwrap(), ["NICK", "d'água", r"a\b"]);
+        assert_eq!(tokenize("/join copa d'água").unwrap(), ["/join", "copa", "d'água"]);
+        assert!(tokenize(r#"/join "sala"#).is_err());
+    }
+
+    #[test]
+    fn names_resolve_by_unique_prefix() {
+        assert_eq!(resolve(Context::Lobby, "/hist"), Ok(Some("/history")));
+        assert_eq!(resolve(Context::Lobby, "nick"), Ok(Some("NICK")));
+        assert_eq!(resolve(Context::Lobby, "/friend"), Ok(Some("/friend")));
+        assert!(resolve(Context::Lobby, "/cr").is_err());
+        assert_eq!(resolve(Context::Lobby, "/nada"), Ok(None));
+        assert_eq!(resolve(Context::Room, "dica"), Ok(Some("/hint")));
+        assert_eq!(resolve(Context::Room, "/desi"), Ok(Some("desistir")));
+        assert!(matches!(classify(Context::Room, " 3 4 "), Ok(Input::Move("3 4"))));
+        assert!(matches!(classify(Context::Room, "sim"), Ok(Input::Text("sim"))));
+    }
+
+    #[test]
+    fn every_help_translates_whole() {
+        for context in [Context::Lobby, Context::Room] {
+            assert_eq!(translate_help(Lang::En, &help(context)), Some(render_help(context, Lang::En)));
+            for command in context.commands() {
+                for name in command.names {
+                    let usage = usage(context, name).unwrap();
+                    assert!(translate_help(Lang::En, &usage).is_some_and(|en| en.lines().count() == usage.lines().count()), "{}", name);
+                }
+            }
+        }
+    }
+}
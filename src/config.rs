@@ -0,0 +1,586 @@
+use clap::Parser;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+use reversi_egui::game::BoardConfig;
+
+use crate::filter::{FilterLevel, WordFilter};
+use crate::input::InputLimits;
+use crate::tournament::Schedule;
+
+// Arquivo lido automaticamente, se existir no diretório atual
+const DEFAULT_CONFIG_FILE: &str = "server.toml";
+
+// Opções de linha de comando; têm prioridade sobre o arquivo de configuração
+#[derive(Parser, Debug)]
+#[command(name = "server", about = "Servidor de Lig 4 e Reversi")]
+pub struct Args {
+    /// Arquivo de configuração TOML (padrão: server.toml, se existir)
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Endereço em que o servidor escuta
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Porta em que o servidor escuta
+    #[arg(short, long)]
+    port: Option<u16>,
+
+    /// Endereço completo da porta TCP, como 0.0.0.0:8080 ou [::]:8080; pode ser repetido
+    /// e substitui --host e --port para a porta TCP
+    #[arg(long)]
+    listen: Vec<String>,
+
+    /// Porta WebSocket para clientes no navegador (0 desliga)
+    #[arg(long)]
+    websocket_port: Option<u16>,
+
+    /// Porta telnet, com negociação de eco e tamanho da janela (0 desliga)
+    #[arg(long)]
+    telnet_port: Option<u16>,
+
+    /// Porta TLS com o mesmo protocolo da porta TCP (0 desliga; exige --tls-cert e --tls-key)
+    #[arg(long)]
+    tls_port: Option<u16>,
+
+    /// Certificado do servidor em PEM, com a cadeia intermediária, se houver
+    #[arg(long)]
+    tls_cert: Option<String>,
+
+    /// Chave privada do certificado em PEM
+    #[arg(long)]
+    tls_key: Option<String>,
+
+    /// Porta HTTP com as métricas do Prometheus em /metrics (0 desliga)
+    #[arg(long)]
+    metrics_port: Option<u16>,
+
+    /// Porta da API HTTP em JSON, só de leitura (0 desliga)
+    #[arg(long)]
+    api_port: Option<u16>,
+
+    /// Número máximo de salas abertas ao mesmo tempo
+    #[arg(long)]
+    max_rooms: Option<usize>,
+
+    /// Número máximo de conexões abertas ao mesmo tempo (0 desliga)
+    #[arg(long)]
+    max_connections: Option<usize>,
+
+    /// Número máximo de conexões abertas ao mesmo tempo vindas de um mesmo endereço (0 desliga)
+    #[arg(long)]
+    max_connections_per_ip: Option<usize>,
+
+    /// Limite de tempo de cada jogada, em segundos
+    #[arg(long)]
+    turn_timeout: Option<u64>,
+
+    /// Limite de tempo de cada jogada nas partidas ranqueadas, em segundos
+    #[arg(long)]
+    rated_turn_timeout: Option<u64>,
+
+    /// Tempo, em segundos, para um jogador que caiu voltar com RESUME (0 desliga)
+    #[arg(long)]
+    reconnect_timeout: Option<u64>,
+
+    /// Linhas do tabuleiro padrão do lig4
+    #[arg(long)]
+    rows: Option<usize>,
+
+    /// Colunas do tabuleiro padrão do lig4
+    #[arg(long)]
+    cols: Option<usize>,
+
+    /// Peças em linha necessárias para vencer no lig4
+    #[arg(long)]
+    win_length: Option<usize>,
+
+    /// Intervalo, em segundos, do keepalive TCP e dos pings WebSocket (0 desliga)
+    #[arg(long)]
+    keepalive: Option<u64>,
+
+    /// Tempo, em segundos, sem nenhum comando no lobby antes de a conexão ser encerrada (0 desliga)
+    #[arg(long)]
+    idle_timeout: Option<u64>,
+
+    /// Tempo, em segundos, sem nenhum comando numa partida antes de o jogador ser avisado como ausente (0 desliga)
+    #[arg(long)]
+    afk_timeout: Option<u64>,
+
+    /// Intervalo, em segundos, entre as cópias das partidas em andamento guardadas no banco (0 desliga)
+    #[arg(long)]
+    snapshot_interval: Option<u64>,
+
+    /// Semente fixa para o sorteio de quem começa em todas as salas, para testes reproduzíveis
+    #[arg(long)]
+    first_player_seed: Option<u32>,
+
+    /// Tamanho máximo de uma linha enviada pelo cliente, em bytes
+    #[arg(long)]
+    max_line_length: Option<usize>,
+
+    /// Linhas por segundo aceitas de cada conexão antes de ela ser encerrada (0 desliga)
+    #[arg(long)]
+    rate_limit: Option<u32>,
+
+    /// Nível de log (error, warn, info, debug ou trace)
+    #[arg(long)]
+    log_level: Option<String>,
+
+    /// Banco SQLite do histórico de partidas
+    #[arg(long)]
+    database: Option<String>,
+
+    /// Diretório onde cada partida encerrada é exportada como partida-<número>.pgn
+    #[arg(long)]
+    export_dir: Option<String>,
+
+    /// Senha do comando ADMIN, que dá acesso ao console de administração (vazia desliga)
+    #[arg(long)]
+    admin_password: Option<String>,
+
+    /// Arquivo com a mensagem do dia, mostrada a cada conexão e com /motd (vazio desliga)
+    #[arg(long)]
+    motd_file: Option<String>,
+
+    /// Rigor do filtro de palavras no chat das salas que não escolhem outro (off, normal ou strict)
+    #[arg(long)]
+    chat_filter: Option<String>,
+
+    /// Arquivo com palavras proibidas no chat e nos apelidos, uma por linha, somadas às de blocked_words
+    #[arg(long)]
+    blocked_words_file: Option<String>,
+
+    /// Horário de um torneio automático, em UTC, como "sáb 20:00" ou "18:30 reversi"; pode ser repetido
+    #[arg(long = "tournament")]
+    tournaments: Vec<String>,
+
+    /// Quanto tempo, em segundos, antes do início de cada torneio as inscrições abrem
+    #[arg(long)]
+    tournament_registration: Option<u64>,
+
+    /// Desliga as cores ANSI por padrão, para terminais simples
+    #[arg(long)]
+    no_color: bool,
+}
+
+// Configuração do servidor; campos ausentes no arquivo usam os valores padrão
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub host: String,
+    pub port: u16,
+    pub listen: Vec<String>, // Endereços da porta TCP; vazio usa host e port
+    pub websocket_port: u16, // 0 desliga o WebSocket
+    pub telnet_port: u16,    // 0 desliga o telnet
+    pub tls_port: u16,       // 0 desliga o TLS
+    pub tls_cert: String,
+    pub tls_key: String,
+    pub metrics_port: u16,   // 0 desliga o /metrics
+    pub api_port: u16,       // 0 desliga a API HTTP
+    pub max_rooms: usize,
+    pub max_connections: usize,        // 0 desliga o limite do servidor todo
+    pub max_connections_per_ip: usize, // 0 desliga o limite por endereço
+    pub turn_timeout_secs: u64,
+    pub rated_turn_timeout_secs: u64, // Mais curto: nas ranqueadas não há tempo para consultar ninguém
+    pub reconnect_timeout_secs: u64, // 0 desliga a pausa por queda de conexão
+    pub keepalive_secs: u64,         // 0 desliga o keepalive e os pings
+    pub idle_timeout_secs: u64,      // 0 desliga o encerramento por inatividade
+    pub afk_timeout_secs: u64,       // 0 desliga a detecção de jogadores ausentes
+    pub snapshot_interval_secs: u64, // 0 desliga as cópias periódicas; as partidas só são salvas ao encerrar
+    pub first_player_seed: Option<u32>, // Sem valor, cada sala sorteia a sua semente
+    pub max_line_length: usize,
+    pub rate_limit_lines: u32, // Linhas por segundo de cada conexão; 0 desliga o limite
+    pub rate_limit_burst: u32, // Linhas aceitas de uma vez antes de o limite valer
+    pub rows: usize,
+    pub cols: usize,
+    pub win_length: usize,
+    pub log_level: String,
+    pub database: String,
+    pub export_dir: String, // Vazio desliga a exportação das partidas para arquivos
+    pub admin_password: String, // Vazia desliga o console de administração
+    pub motd_file: String,      // Vazio desliga a mensagem do dia
+    pub chat_filter: String,    // off, normal ou strict
+    pub blocked_words: Vec<String>, // Vazia desliga o filtro do chat e dos apelidos
+    pub blocked_words_file: String, // Mais palavras, uma por linha; linhas com # são comentários
+    pub tournaments: Vec<String>, // Horários dos torneios automáticos; vazia desliga os torneios
+    pub tournament_registration_secs: u64,
+    pub color: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let board = BoardConfig::default();
+        Config {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            listen: Vec::new(),
+            websocket_port: 8081,
+            telnet_port: 2323,
+            tls_port: 0,
+            tls_cert: String::new(),
+            tls_key: String::new(),
+            metrics_port: 0,
+            api_port: 0,
+            max_rooms: 100,
+            max_connections: 1000,
+            max_connections_per_ip: 20,
+            turn_timeout_secs: 60,
+            rated_turn_timeout_secs: 30,
+            reconnect_timeout_secs: 60,
+            keepalive_secs: 30,
+            idle_timeout_secs: 600,
+            afk_timeout_secs: 300,
+            snapshot_interval_secs: 30,
+            first_player_seed: None,
+            max_line_length: 1024,
+            rate_limit_lines: 20,
+            rate_limit_burst: 40,
+            rows: board.rows,
+            cols: board.cols,
+            win_length: board.win_length,
+            log_level: "info".to_string(),
+            database: crate::storage::DEFAULT_DATABASE.to_string(),
+            export_dir: String::new(),
+            admin_password: String::new(),
+            motd_file: String::new(),
+            chat_filter: "normal".to_string(),
+            blocked_words: Vec::new(),
+            blocked_words_file: String::new(),
+            tournaments: Vec::new(),
+            tournament_registration_secs: 30 * 60,
+            color: true,
+        }
+    }
+}
+
+impl Config {
+    // Lê o arquivo de configuração (se houver) e aplica as opções da linha de comando
+    pub fn load() -> Result<Self, String> {
+        let args = Args::parse();
+
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => Config::default(),
+        };
+
+        if let Some(host) = args.host {
+            config.host = host;
+        }
+        if let Some(port) = args.port {
+            config.port = port;
+        }
+        if !args.listen.is_empty() {
+            config.listen = args.listen;
+        }
+        if let Some(websocket_port) = args.websocket_port {
+            config.websocket_port = websocket_port;
+        }
+        if let Some(telnet_port) = args.telnet_port {
+            config.telnet_port = telnet_port;
+        }
+        if let Some(tls_port) = args.tls_port {
+            config.tls_port = tls_port;
+        }
+        if let Some(tls_cert) = args.tls_cert {
+            config.tls_cert = tls_cert;
+        }
+        if let Some(tls_key) = args.tls_key {
+            config.tls_key = tls_key;
+        }
+        if let Some(metrics_port) = args.metrics_port {
+            config.metrics_port = metrics_port;
+        }
+        if let Some(api_port) = args.api_port {
+            config.api_port = api_port;
+        }
+        if let Some(max_rooms) = args.max_rooms {
+            config.max_rooms = max_rooms;
+        }
+        if let Some(max_connections) = args.max_connections {
+            config.max_connections = max_connections;
+        }
+        if let Some(max_connections_per_ip) = args.max_connections_per_ip {
+            config.max_connections_per_ip = max_connections_per_ip;
+        }
+        if let Some(turn_timeout) = args.turn_timeout {
+            config.turn_timeout_secs = turn_timeout;
+        }
+        if let Some(rated_turn_timeout) = args.rated_turn_timeout {
+            config.rated_turn_timeout_secs = rated_turn_timeout;
+        }
+        if let Some(reconnect_timeout) = args.reconnect_timeout {
+            config.reconnect_timeout_secs = reconnect_timeout;
+        }
+        if let Some(keepalive) = args.keepalive {
+            config.keepalive_secs = keepalive;
+        }
+        if let Some(idle_timeout) = args.idle_timeout {
+            config.idle_timeout_secs = idle_timeout;
+        }
+        if let Some(afk_timeout) = args.afk_timeout {
+            config.afk_timeout_secs = afk_timeout;
+        }
+        if let Some(snapshot_interval) = args.snapshot_interval {
+            config.snapshot_interval_secs = snapshot_interval;
+        }
+        if let Some(seed) = args.first_player_seed {
+            config.first_player_seed = Some(seed);
+        }
+        if let Some(max_line_length) = args.max_line_length {
+            config.max_line_length = max_line_length;
+        }
+        if let Some(rate_limit) = args.rate_limit {
+            config.rate_limit_lines = rate_limit;
+        }
+        if let Some(rows) = args.rows {
+            config.rows = rows;
+        }
+        if let Some(cols) = args.cols {
+            config.cols = cols;
+        }
+        if let Some(win_length) = args.win_length {
+            config.win_length = win_length;
+        }
+        if let Some(log_level) = args.log_level {
+            config.log_level = log_level;
+        }
+        if let Some(database) = args.database {
+            config.database = database;
+        }
+        if let Some(export_dir) = args.export_dir {
+            config.export_dir = export_dir;
+        }
+        if let Some(admin_password) = args.admin_password {
+            config.admin_password = admin_password;
+        }
+        if let Some(motd_file) = args.motd_file {
+            config.motd_file = motd_file;
+        }
+        if let Some(chat_filter) = args.chat_filter {
+            config.chat_filter = chat_filter;
+        }
+        if let Some(blocked_words_file) = args.blocked_words_file {
+            config.blocked_words_file = blocked_words_file;
+        }
+        if !args.tournaments.is_empty() {
+            config.tournaments = args.tournaments;
+        }
+        if let Some(tournament_registration) = args.tournament_registration {
+            config.tournament_registration_secs = tournament_registration;
+        }
+        if args.no_color {
+            config.color = false;
+        }
+
+        // Valida o tabuleiro padrão com as mesmas regras do /create
+        config.board()?;
+        if config.turn_timeout_secs == 0 {
+            return Err("turn_timeout_secs precisa ser maior que zero".to_string());
+        }
+        if config.rated_turn_timeout_secs == 0 {
+            return Err("rated_turn_timeout_secs precisa ser maior que zero".to_string());
+        }
+        if config.tls_port != 0 && (config.tls_cert.is_empty() || config.tls_key.is_empty()) {
+            return Err("tls_port exige tls_cert e tls_key".to_string());
+        }
+        if config.max_line_length == 0 {
+            return Err("max_line_length precisa ser maior que zero".to_string());
+        }
+        config.tournament_schedules()?;
+        if FilterLevel::parse(&config.chat_filter).is_none() {
+            return Err("chat_filter precisa ser off, normal ou strict".to_string());
+        }
+        if !config.blocked_words_file.is_empty() {
+            let path = Path::new(&config.blocked_words_file);
+            let text = std::fs::read_to_string(path)
+                .map_err(|err| format!("não foi possível ler {}: {}", path.display(), err))?;
+            let words = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+            config.blocked_words.extend(words.map(str::to_string));
+        }
+        Ok(config)
+    }
+
+    // Opções que mudaram na configuração relida mas só valem depois de reiniciar o servidor:
+    // as portas já abertas, o banco e o filtro de palavras, que as conexões já carregam
+    pub fn restart_required(&self, new: &Config) -> Vec<&'static str> {
+        let changes = [
+            ("listen", self.listen_addresses() != new.listen_addresses()),
+            ("websocket_port", self.websocket_address() != new.websocket_address()),
+            ("telnet_port", self.telnet_address() != new.telnet_address()),
+            ("tls_port", self.tls_address() != new.tls_address() || self.tls_cert != new.tls_cert || self.tls_key != new.tls_key),
+            ("metrics_port", self.metrics_address() != new.metrics_address()),
+            ("api_port", self.api_address() != new.api_address()),
+            ("database", self.database != new.database),
+            ("export_dir", self.export_dir != new.export_dir),
+            ("snapshot_interval_secs", self.snapshot_interval_secs != new.snapshot_interval_secs),
+            ("chat_filter", self.chat_filter != new.chat_filter),
+            ("blocked_words", self.blocked_words != new.blocked_words),
+            ("tournaments", self.tournaments != new.tournaments),
+        ];
+        changes.into_iter().filter(|(_, changed)| *changed).map(|(name, _)| name).collect()
+    }
+
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("não foi possível ler {}: {}", path.display(), err))?;
+        toml::from_str(&text).map_err(|err| format!("erro em {}: {}", path.display(), err))
+    }
+
+    // Endereços da porta TCP, todos atendidos pelo mesmo lobby
+    pub fn listen_addresses(&self) -> Vec<String> {
+        if self.listen.is_empty() {
+            vec![self.address(self.port)]
+        } else {
+            self.listen.clone()
+        }
+    }
+
+    pub fn websocket_address(&self) -> Option<String> {
+        (self.websocket_port != 0).then(|| self.address(self.websocket_port))
+    }
+
+    pub fn telnet_address(&self) -> Option<String> {
+        (self.telnet_port != 0).then(|| self.address(self.telnet_port))
+    }
+
+    pub fn tls_address(&self) -> Option<String> {
+        (self.tls_port != 0).then(|| self.address(self.tls_port))
+    }
+
+    pub fn metrics_address(&self) -> Option<String> {
+        (self.metrics_port != 0).then(|| self.address(self.metrics_port))
+    }
+
+    pub fn api_address(&self) -> Option<String> {
+        (self.api_port != 0).then(|| self.address(self.api_port))
+    }
+
+    // Endereços IPv6 vão entre colchetes antes da porta
+    fn address(&self, port: u16) -> String {
+        if self.host.contains(':') && !self.host.starts_with('[') {
+            format!("[{}]:{}", self.host, port)
+        } else {
+            format!("{}:{}", self.host, port)
+        }
+    }
+
+    pub fn turn_timeout(&self) -> Duration {
+        Duration::from_secs(self.turn_timeout_secs)
+    }
+
+    // Limite por jogada de uma sala, em segundos; as ranqueadas têm o seu
+    pub fn turn_timeout_secs_for(&self, rated: bool) -> u64 {
+        if rated {
+            self.rated_turn_timeout_secs
+        } else {
+            self.turn_timeout_secs
+        }
+    }
+
+    pub fn reconnect_timeout(&self) -> Duration {
+        Duration::from_secs(self.reconnect_timeout_secs)
+    }
+
+    pub fn export_dir(&self) -> Option<PathBuf> {
+        (!self.export_dir.is_empty()).then(|| PathBuf::from(&self.export_dir))
+    }
+
+    pub fn admin_password(&self) -> Option<&str> {
+        (!self.admin_password.is_empty()).then_some(self.admin_password.as_str())
+    }
+
+    pub fn motd_file(&self) -> Option<PathBuf> {
+        (!self.motd_file.is_empty()).then(|| PathBuf::from(&self.motd_file))
+    }
+
+    pub fn chat_filter(&self) -> FilterLevel {
+        FilterLevel::parse(&self.chat_filter).unwrap_or_default()
+    }
+
+    pub fn word_filter(&self) -> WordFilter {
+        WordFilter::new(&self.blocked_words)
+    }
+
+    pub fn keepalive(&self) -> Option<Duration> {
+        (self.keepalive_secs != 0).then(|| Duration::from_secs(self.keepalive_secs))
+    }
+
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout_secs != 0).then(|| Duration::from_secs(self.idle_timeout_secs))
+    }
+
+    pub fn afk_timeout(&self) -> Option<Duration> {
+        (self.afk_timeout_secs != 0).then(|| Duration::from_secs(self.afk_timeout_secs))
+    }
+
+    pub fn snapshot_interval(&self) -> Option<Duration> {
+        (self.snapshot_interval_secs != 0).then(|| Duration::from_secs(self.snapshot_interval_secs))
+    }
+
+    pub fn tournament_schedules(&self) -> Result<Vec<Schedule>, String> {
+        self.tournaments.iter().map(|text| Schedule::parse(text)).collect()
+    }
+
+    pub fn tournament_registration(&self) -> Duration {
+        Duration::from_secs(self.tournament_registration_secs)
+    }
+
+    pub fn input_limits(&self) -> InputLimits {
+        InputLimits {
+            max_line_length: self.max_line_length,
+            lines_per_sec: self.rate_limit_lines,
+            burst: self.rate_limit_burst,
+        }
+    }
+
+    // Tabuleiro padrão do lig4
+    pub fn board(&self) -> Result<BoardConfig, String> {
+        BoardConfig::new(self.rows, self.cols, self.win_length).map_err(|err| err.trim_end().to_string())
+    }
+}
+
+// Pedido para reler a configuração, com o canal da resposta: o relatório do que mudou ou o
+// motivo de nada ter mudado
+pub type ReloadRequest = oneshot::Sender<Result<String, String>>;
+
+// Encaminha os pedidos de recarga (do console e do SIGHUP) para o laço principal, que é
+// quem guarda os limites de conexão e o nível do log
+#[derive(Clone)]
+pub struct Reloader(mpsc::UnboundedSender<ReloadRequest>);
+
+impl Reloader {
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<ReloadRequest>) {
+        let (sender, requests) = mpsc::unbounded_channel();
+        (Reloader(sender), requests)
+    }
+
+    pub async fn reload(&self) -> Result<String, String> {
+        let closing = || "O servidor está encerrando e não pode recarregar a configuração.\n".to_string();
+        let (reply, result) = oneshot::channel();
+        self.0.send(reply).map_err(|_| closing())?;
+        result.await.unwrap_or_else(|_| Err(closing()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listen_list_replaces_host_and_port() {
+        let config: Config = toml::from_str("port = 9000\nlisten = [\"0.0.0.0:8080\", \"[::]:8080\"]\n").unwrap();
+        assert_eq!(config.listen_addresses(), ["0.0.0.0:8080", "[::]:8080"]);
+    }
+
+    #[test]
+    fn without_listen_the_tcp_port_uses_host_and_port() {
+        let config: Config = toml::from_str("host = \"::1\"\nport = 9000\n").unwrap();
+        assert_eq!(config.listen_addresses(), ["[::1]:9000"]);
+        assert_eq!(Config::default().listen_addresses(), ["127.0.0.1:8080"]);
+    }
+}
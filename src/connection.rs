@@ -0,0 +1,642 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{broadcast, mpsc, watch, Notify};
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::warn;
+
+use reversi_egui::error::GameError;
+use reversi_egui::protocol::{ErrorCode, GameSummary, LegalMove, RatingEntry, RoomInfo, ServerMessage};
+use reversi_egui::game::{winning_cells, GameState};
+use reversi_egui::render::{self, Mark};
+use crate::i18n::{self, Lang};
+use crate::metrics::METRICS;
+use crate::moderation::Moderation;
+use crate::room::{line_marks, Game, GameClock, Hint, MovePreview, RoomStatus, RoomView};
+use crate::transport::{EchoControl, Terminal, WindowSize};
+use crate::ClientWriter;
+
+// Altura suposta do terminal quando o cliente não informa o tamanho
+const DEFAULT_HEIGHT: usize = 24;
+
+// Mensagens esperando a tarefa de escrita antes de a conexão esperar por ela
+const OUTPUT_QUEUE: usize = 256;
+
+// Tempo máximo de uma escrita no socket; um cliente que não lê por mais que isso é dado
+// como caído
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Fila de saída de uma conexão, esvaziada pela tarefa de escrita
+pub struct Output {
+    sender: mpsc::Sender<String>,
+    stalled: Arc<Notify>, // Acionado quando a tarefa de escrita desiste do cliente
+}
+
+// Cria a tarefa que escreve no socket o que a conexão põe na fila. Assim nenhuma tarefa
+// espera o socket com uma sala ou o lobby nas mãos, e um cliente lento só atrasa a própria
+// fila. A tarefa termina quando a conexão solta a fila, depois de escrever o que restou.
+pub fn spawn_writer(mut writer: ClientWriter) -> (Output, JoinHandle<()>) {
+    let (sender, mut receiver) = mpsc::channel::<String>(OUTPUT_QUEUE);
+    let stalled = Arc::new(Notify::new());
+    let notify = Arc::clone(&stalled);
+    let task = tokio::spawn(async move {
+        while let Some(text) = receiver.recv().await {
+            match time::timeout(WRITE_TIMEOUT, writer.write_all(text.as_bytes())).await {
+                Ok(Ok(())) => {}
+                // A conexão caiu; a leitura também vai perceber
+                Ok(Err(_)) => break,
+                Err(_) => {
+                    warn!("cliente parou de ler; conexão encerrada");
+                    METRICS.slow_client_dropped();
+                    notify.notify_one();
+                    break;
+                }
+            }
+        }
+        let _ = writer.shutdown().await;
+    });
+    (Output { sender, stalled }, task)
+}
+
+// Lado de escrita de uma conexão, ciente do formato negociado com o cliente
+pub struct Connection {
+    output: Output,
+    pub json: bool,  // true depois que o cliente envia "MODE json"
+    pub color: bool, // Cores ANSI no tabuleiro em modo texto
+    pub utf8: bool,  // false para clientes que negociaram só ASCII no HELLO
+    pub unicode_board: bool, // Tabuleiro com moldura e discos (● ○); só para quem pediu utf8 no HELLO
+    pub lang: Lang,  // Idioma das mensagens, escolhido no HELLO ou com LANG
+    pub delta: bool, // O cliente JSON aceita só as casas que mudaram, negociado no HELLO
+    pub confirm_moves: bool, // Jogadas em dois passos: prévia e depois /confirm, negociado no HELLO
+    pub threats: bool, // Destaca no lig4 as casas que vencem na próxima jogada, ligado com /threats
+    pub bell: bool,    // Aviso sonoro na vez do jogador e quando o oponente entra, ligado com /bell
+    pub bot: bool,     // Programa que joga sozinho: recebe um your_turn a cada vez, negociado no HELLO
+    last_frame: Option<String>, // Último quadro em texto, base para enviar só as diferenças
+    lines_since_frame: usize,   // Linhas escritas abaixo do último quadro
+    last_board: Option<(Vec<Vec<i32>>, i32, Vec<LegalMove>)>, // Último tabuleiro, vez e jogadas possíveis enviados em JSON
+    window: watch::Receiver<Option<WindowSize>>, // Tamanho do terminal, quando o cliente informa
+    echo: EchoControl, // Esconde o que o cliente digita, como uma senha, onde o transporte permite
+    idle_timeout: Option<Duration>, // Tempo máximo esperando um comando no lobby
+    shutdown: watch::Receiver<bool>, // Passa a true quando o servidor começa a encerrar
+    presence: Presence, // Como o lobby alcança esta conexão
+    notices: mpsc::UnboundedReceiver<Notice>, // Avisos só para esta conexão, como desafios, e o chat do lobby
+    announcements: broadcast::Receiver<String>, // Avisos de um administrador para todo o servidor
+    moderation: Moderation, // Consultada no chat, para barrar quem foi silenciado
+}
+
+// O que o lobby guarda de cada apelido conectado para alcançar a conexão
+#[derive(Clone)]
+pub struct Presence {
+    pub kick: Arc<Notify>, // Acionado quando um administrador derruba a conexão
+    pub replaced: Arc<Notify>, // Acionado quando o jogador retoma a sessão em outra conexão
+    notices: mpsc::UnboundedSender<Notice>,
+}
+
+// O que o lobby leva a uma conexão pela presença
+enum Notice {
+    Text(String),
+    Chat { from: String, text: String, time: String },
+}
+
+impl Presence {
+    // Mostra um aviso na conexão, esteja ela no lobby ou numa sala
+    pub fn notify(&self, text: String) {
+        // Se a conexão já terminou, o aviso se perde
+        let _ = self.notices.send(Notice::Text(text));
+    }
+
+    // Entrega uma mensagem do chat do lobby
+    pub fn chat(&self, from: &str, text: &str, time: &str) {
+        let _ = self.notices.send(Notice::Chat { from: from.to_string(), text: text.to_string(), time: time.to_string() });
+    }
+}
+
+// Motivo para interromper a espera por entrada do cliente
+pub enum Interrupt {
+    Shutdown,             // O servidor está encerrando
+    Kicked,               // Um administrador derrubou a conexão
+    Replaced,             // O jogador retomou a sessão em outra conexão, talvez por outro transporte
+    Announcement(String), // Aviso para todo o servidor; depois dele a espera continua
+    Notice(String),       // Aviso só para esta conexão; depois dele a espera continua
+    Chat { from: String, text: String, time: String }, // Mensagem do chat do lobby; a espera também continua
+    Stalled,              // O cliente parou de ler e a tarefa de escrita desistiu dele
+}
+
+impl Connection {
+    pub fn new(
+        output: Output,
+        color: bool,
+        terminal: Terminal,
+        idle_timeout: Option<Duration>,
+        shutdown: watch::Receiver<bool>,
+        announcements: broadcast::Receiver<String>,
+        moderation: Moderation,
+    ) -> Self {
+        let (notices_sender, notices) = mpsc::unbounded_channel();
+        Connection {
+            output,
+            json: false,
+            color,
+            utf8: true,
+            unicode_board: false,
+            lang: Lang::default(),
+            delta: false,
+            confirm_moves: false,
+            threats: false,
+            bell: false,
+            bot: false,
+            last_frame: None,
+            lines_since_frame: 0,
+            last_board: None,
+            window: terminal.window,
+            echo: terminal.echo,
+            idle_timeout,
+            shutdown,
+            presence: Presence { kick: Arc::new(Notify::new()), replaced: Arc::new(Notify::new()), notices: notices_sender },
+            notices,
+            announcements,
+            moderation,
+        }
+    }
+
+    // Liga ou desliga a entrada secreta: o cliente telnet passa a ver asteriscos no lugar
+    // do que digita. Nos outros transportes não muda nada.
+    pub fn set_echo_masked(&self, masked: bool) {
+        self.echo.set_masked(masked);
+    }
+
+    pub fn moderation(&self) -> &Moderation {
+        &self.moderation
+    }
+
+    // Presença que o lobby guarda junto do apelido para derrubar ou avisar esta conexão
+    pub fn presence(&self) -> Presence {
+        self.presence.clone()
+    }
+
+    // Termina quando algo de fora interrompe a conexão; usado nos select! das esperas por entrada
+    pub async fn interrupted(&mut self) -> Interrupt {
+        loop {
+            tokio::select! {
+                _ = self.shutdown.wait_for(|closing| *closing) => return Interrupt::Shutdown,
+                _ = self.presence.kick.notified() => return Interrupt::Kicked,
+                _ = self.presence.replaced.notified() => return Interrupt::Replaced,
+                _ = self.output.stalled.notified() => return Interrupt::Stalled,
+                Some(notice) = self.notices.recv() => {
+                    return match notice {
+                        Notice::Text(text) => Interrupt::Notice(text),
+                        Notice::Chat { from, text, time } => Interrupt::Chat { from, text, time },
+                    };
+                }
+                // Avisos perdidos por atraso são descartados
+                announcement = self.announcements.recv() => {
+                    if let Ok(text) = announcement {
+                        return Interrupt::Announcement(text);
+                    }
+                }
+            }
+        }
+    }
+
+    // Termina quando o cliente passa do limite de inatividade; sem limite, nunca termina.
+    // O futuro não segura a conexão, para que ela possa ser usada nos outros ramos do select!
+    pub fn idle_expired(&self) -> impl Future<Output = ()> {
+        let timeout = self.idle_timeout;
+        async move {
+            match timeout {
+                Some(timeout) => time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    // Escreve uma mensagem do servidor, traduzida para o idioma da conexão
+    async fn write_text(&mut self, text: &str) {
+        let text = i18n::translate(self.lang, text).into_owned();
+        self.write(&text).await;
+    }
+
+    async fn write(&mut self, text: &str) {
+        let text = if self.utf8 { text.to_string() } else { to_ascii(text) };
+        self.lines_since_frame += text.matches('\n').count();
+        // Com a fila cheia, só esta conexão espera. Se a tarefa de escrita já terminou, o
+        // texto se perde e a conexão percebe pelo interrupted ou pela próxima leitura.
+        if let Err(TrySendError::Full(text)) = self.output.sender.try_send(text) {
+            METRICS.output_queue_full();
+            let _ = self.output.sender.send(text).await;
+        }
+    }
+
+    // O próximo tabuleiro vai inteiro, como no comando board ou ao entrar numa sala
+    pub fn full_redraw(&mut self) {
+        self.last_frame = None;
+        self.last_board = None;
+    }
+
+    // Envia um quadro do tabuleiro, centralizado se o terminal informou a largura. Quando
+    // o quadro anterior ainda está na tela, vão só as casas que mudaram.
+    async fn write_board(&mut self, frame: &str) {
+        let (width, height) = match *self.window.borrow() {
+            Some(size) => (Some(size.width as usize), size.height as usize),
+            None => (None, DEFAULT_HEIGHT),
+        };
+        let frame = match width {
+            Some(width) => render::center(frame, width),
+            None => frame.to_string(),
+        };
+
+        // Se o texto escrito depois do quadro rolou a tela, as posições não valem mais
+        let diff = self
+            .last_frame
+            .as_deref()
+            .filter(|last| last.lines().count() + self.lines_since_frame < height)
+            .and_then(|last| render::diff_frames(last, &frame));
+        match diff {
+            Some(diff) => self.write(&diff).await,
+            None => self.write(&frame).await,
+        }
+        // Sem limpeza de tela (terminais sem cores) não há como posicionar o cursor
+        self.last_frame = frame.starts_with(render::CLEAR_SCREEN).then_some(frame);
+        self.lines_since_frame = 0;
+    }
+
+    // Envia o estado em JSON; com a capacidade "delta", só as casas e a vez que mudaram
+    async fn send_board_state(&mut self, message: ServerMessage) {
+        let ServerMessage::State { board, current_turn, legal_moves, .. } = &message else {
+            return self.send_message(&message).await;
+        };
+        let current = (board.clone(), *current_turn, legal_moves.clone());
+
+        let same_size = |last: &Vec<Vec<i32>>| {
+            last.len() == board.len() && last.iter().zip(board).all(|(old, new)| old.len() == new.len())
+        };
+        match self.last_board.take().filter(|(last, _, _)| self.delta && same_size(last)) {
+            Some((last, last_turn, last_legal)) => {
+                for (row, (old, new)) in last.iter().zip(board).enumerate() {
+                    for (col, (old, new)) in old.iter().zip(new).enumerate() {
+                        if old != new {
+                            self.send_message(&ServerMessage::Delta { row, col, val: *new }).await;
+                        }
+                    }
+                }
+                // Com a mesma vez (passe no reversi, jogada extra), as jogadas possíveis ainda mudam
+                if last_turn != *current_turn || last_legal != *legal_moves {
+                    let turn = ServerMessage::Turn { current_turn: *current_turn, legal_moves: legal_moves.clone() };
+                    self.send_message(&turn).await;
+                }
+            }
+            None => self.send_message(&message).await,
+        }
+        self.last_board = Some(current);
+    }
+
+    // Envia uma mensagem estruturada como uma linha JSON
+    pub async fn send_message(&mut self, message: &ServerMessage) {
+        if let Ok(mut line) = serde_json::to_string(message) {
+            line.push('\n');
+            self.write(&line).await;
+        }
+    }
+
+    pub async fn send_info(&mut self, text: &str) {
+        if self.json {
+            let text = i18n::translate(self.lang, text);
+            let message = ServerMessage::Info { message: text.trim_end().to_string() };
+            self.send_message(&message).await;
+        } else {
+            self.write_text(text).await;
+        }
+    }
+
+    // Erro em texto livre; o código vem da tabela de trechos conhecidos
+    pub async fn send_error(&mut self, text: &str) {
+        self.send_error_with_code(text, error_code(text)).await;
+    }
+
+    // Erro de uma jogada, com o código da própria variante
+    pub async fn send_game_error(&mut self, err: &GameError) {
+        match err {
+            GameError::Other(message) => self.send_error(message).await,
+            _ => self.send_error_with_code(&err.message(), err.code()).await,
+        }
+    }
+
+    async fn send_error_with_code(&mut self, text: &str, code: ErrorCode) {
+        if self.json {
+            let code = Some(code);
+            let text = i18n::translate(self.lang, text);
+            let message = ServerMessage::Error { message: text.trim_end().to_string(), code };
+            self.send_message(&message).await;
+        } else {
+            self.write_text(text).await;
+        }
+    }
+
+    // Resposta ao HELLO com o que foi combinado
+    pub async fn send_hello(&mut self, version: u32, caps: Vec<String>, lang: &str) {
+        if self.json {
+            let message = ServerMessage::Hello { version, caps, lang: lang.to_string() };
+            self.send_message(&message).await;
+        } else {
+            self.write(&format!("HELLO v{} caps={} lang={}\n", version, caps.join(","), lang)).await;
+        }
+    }
+
+    pub async fn send_rooms(&mut self, rooms: Vec<RoomInfo>) {
+        if self.json {
+            self.send_message(&ServerMessage::Rooms { rooms }).await;
+        } else {
+            self.write_text(&format_room_list(&rooms)).await;
+        }
+    }
+
+    pub async fn send_history(&mut self, player: &str, games: Vec<GameSummary>) {
+        if self.json {
+            let message = ServerMessage::History { player: player.to_string(), games };
+            self.send_message(&message).await;
+        } else {
+            self.write_text(&format_history(player, &games)).await;
+        }
+    }
+
+    pub async fn send_export(&mut self, id: i64, text: &str) {
+        if self.json {
+            self.send_message(&ServerMessage::Export { id, text: text.to_string() }).await;
+        } else {
+            self.write(text).await;
+        }
+    }
+
+    pub async fn send_leaderboard(&mut self, entries: Vec<RatingEntry>) {
+        if self.json {
+            self.send_message(&ServerMessage::Leaderboard { entries }).await;
+        } else {
+            self.write_text(&format_leaderboard(&entries)).await;
+        }
+    }
+
+    // Envia uma posição fora de uma sala, como nos passos de um replay
+    pub async fn send_position(&mut self, game: &Game, last_move: Option<(usize, usize)>) {
+        if self.json {
+            self.send_board_state(game.state_message(0)).await;
+        } else {
+            self.write_board(&game.render(last_move, self.color, self.unicode_board)).await;
+        }
+    }
+
+    pub async fn send_state(&mut self, view: &RoomView, player_symbol: i32) {
+        if self.json {
+            let mut message = view.game.state_message(player_symbol);
+            // Enquanto a sala espera jogadores ninguém joga; na vez de quem recebe, as colunas
+            // que a vantagem bloqueia também ficam de fora
+            if let ServerMessage::State { current_turn, legal_moves, .. } = &mut message {
+                if view.status == RoomStatus::Waiting {
+                    legal_moves.clear();
+                } else if *current_turn == player_symbol {
+                    legal_moves.retain(|legal_move| !view.blocked_columns.contains(&legal_move.col));
+                }
+            }
+            self.send_board_state(message).await;
+            if let Some(clock) = &view.clock {
+                let [x_ms, o_ms] = clock.remaining.map(|remaining| remaining.as_millis() as u64);
+                self.send_message(&ServerMessage::Clock { x_ms, o_ms }).await;
+            }
+            if self.bot && view.status == RoomStatus::Playing && view.game.current_turn() == player_symbol && !view.partner_turn {
+                let mut legal_moves = view.game.legal_moves();
+                legal_moves.retain(|legal_move| !view.blocked_columns.contains(&legal_move.col));
+                self.send_message(&ServerMessage::YourTurn { you: player_symbol, legal_moves }).await;
+            }
+        } else {
+            let frame = match &view.game {
+                Game::ConnectFour(game_state) if self.threats => {
+                    let mut marks = line_marks(&view.game.winning_line());
+                    marks.extend(threat_marks(game_state, player_symbol));
+                    render::render_connect_four(&game_state.board(), view.last_move, &marks, self.color, self.unicode_board)
+                }
+                game => game.render(view.last_move, self.color, self.unicode_board),
+            };
+            let frame = match &view.clock {
+                Some(clock) => render::with_header(&frame, &clock_line(clock, view.game.current_turn(), view.status)),
+                None => frame,
+            };
+            self.write_board(&frame).await;
+        }
+    }
+
+    // Toca o aviso sonoro, se o jogador ligou com /bell: o BEL do terminal no modo texto
+    pub async fn ring_bell(&mut self) {
+        if !self.bell {
+            return;
+        }
+        if self.json {
+            self.send_message(&ServerMessage::Bell).await;
+        } else {
+            self.write("\x07").await;
+        }
+    }
+
+    // Mostra a jogada proposta como ficaria no tabuleiro, à espera do /confirm
+    pub async fn send_preview(&mut self, preview: &MovePreview) {
+        let (row, col) = preview.cell;
+        if self.json {
+            if let ServerMessage::State { board, .. } = preview.game.state_message(0) {
+                self.send_message(&ServerMessage::Preview { row, col, board }).await;
+            }
+        } else {
+            self.write_board(&preview.game.render(Some(preview.cell), self.color, self.unicode_board)).await;
+            self.send_info("Jogada proposta. Confirme com /confirm ou desista com /cancel.\n").await;
+        }
+    }
+
+    // Mostra a coluna sugerida por /hint, com a casa onde a peça cairia destacada no tabuleiro
+    pub async fn send_hint(&mut self, hint: &Hint, col: usize) {
+        if !self.json {
+            let marks: Vec<_> = hint.position.landing_row(col).map(|row| (row, col, Mark::Hint)).into_iter().collect();
+            let board = hint.position.board();
+            self.write_board(&render::render_connect_four(&board, hint.last_move, &marks, self.color, self.unicode_board)).await;
+        }
+        self.send_info(&format!("Dica: jogue na coluna {}. Dicas restantes nesta partida: {}.\n", col + 1, hint.remaining)).await;
+    }
+
+    pub async fn send_chat(&mut self, from: &str, message: &str, time: &str) {
+        if self.json {
+            let message = ServerMessage::Chat {
+                from: from.to_string(),
+                message: message.to_string(),
+                time: time.to_string(),
+            };
+            self.send_message(&message).await;
+        } else {
+            self.write(&format!("[{}] {}: {}\n", time, from, message)).await;
+        }
+    }
+
+    pub async fn send_game_over(&mut self, game: &Game, winner: i32, player_symbol: i32) {
+        let mode = game.mode();
+        if self.json {
+            self.send_message(&game.game_over_message(winner, player_symbol)).await;
+        } else if winner == 0 {
+            self.write_text("Empate!\n").await;
+        } else if player_symbol == 0 {
+            // Espectadores não têm lado: só recebem quem venceu
+            self.write_text(&format!("O {} venceu!\n", mode.glyph(winner))).await;
+        } else if winner == player_symbol {
+            self.write_text("Você venceu!\n").await;
+        } else {
+            self.write_text("Você perdeu!\n").await;
+        }
+    }
+}
+
+// Cabeçalho com o relógio de xadrez: "[X 4:32] | O 5:01", com o relógio que corre entre colchetes
+fn clock_line(clock: &GameClock, current_turn: i32, status: RoomStatus) -> String {
+    let side = |symbol: i32| {
+        let secs = clock.remaining(symbol).as_secs();
+        let glyph = if symbol == 1 { 'X' } else { 'O' };
+        let text = format!("{} {}:{:02}", glyph, secs / 60, secs % 60);
+        if status == RoomStatus::Playing && symbol == current_turn {
+            format!("[{}]", text)
+        } else {
+            text
+        }
+    };
+    format!("{} | {}", side(1), side(-1))
+}
+
+// Casas de vitória na próxima jogada, do ponto de vista do jogador: as dele e as do oponente.
+// Espectadores veem pelo lado de quem está com a vez.
+fn threat_marks(game_state: &GameState, player_symbol: i32) -> Vec<(usize, usize, Mark)> {
+    let symbol = if player_symbol == 0 { game_state.current_turn } else { player_symbol };
+    let wins = winning_cells(game_state, symbol).into_iter().map(|(row, col)| (row, col, Mark::Win));
+    let threats = winning_cells(game_state, -symbol).into_iter().map(|(row, col)| (row, col, Mark::Threat));
+    wins.chain(threats).collect()
+}
+
+// Códigos dos erros enviados em JSON, pelo trecho da mensagem em português. Os mais
+// específicos vêm antes; o que não estiver aqui vai como "error". Os erros das jogadas
+// trazem o próprio código (send_game_error).
+const ERROR_CODES: [(&str, ErrorCode); 18] = [
+    ("Não é seu turno", ErrorCode::NotYourTurn),
+    ("Coluna inválida", ErrorCode::InvalidColumn),
+    ("Coluna cheia", ErrorCode::ColumnFull),
+    ("Casa inválida", ErrorCode::InvalidCell),
+    ("Jogada inválida", ErrorCode::InvalidMove),
+    ("Jogada JSON inválida", ErrorCode::InvalidMove),
+    ("Entrada não reconhecida", ErrorCode::InvalidMove),
+    ("A partida ainda não começou", ErrorCode::GameNotStarted),
+    ("A partida já terminou", ErrorCode::GameFinished),
+    ("A partida está pausada", ErrorCode::GamePaused),
+    ("Espectadores não podem jogar", ErrorCode::Spectator),
+    ("Escolha um apelido primeiro", ErrorCode::NickRequired),
+    ("já está em uso", ErrorCode::NickTaken),
+    ("já está cheia", ErrorCode::RoomFull),
+    ("não encontrada. Use /list", ErrorCode::RoomNotFound),
+    ("Comando desconhecido", ErrorCode::UnknownCommand),
+    ("Mensagens demais", ErrorCode::RateLimited),
+    ("Linha longa demais", ErrorCode::LineTooLong),
+];
+
+fn error_code(text: &str) -> ErrorCode {
+    ERROR_CODES.iter().find(|(fragment, _)| text.contains(fragment)).map_or(ErrorCode::Error, |(_, code)| *code)
+}
+
+// Troca as letras acentuadas do português pelas sem acento; o resto fora do ASCII vira '?'
+fn to_ascii(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' => 'a',
+            'Á' | 'À' | 'Â' | 'Ã' => 'A',
+            'é' | 'ê' => 'e',
+            'É' | 'Ê' => 'E',
+            'í' => 'i',
+            'Í' => 'I',
+            'ó' | 'ô' | 'õ' => 'o',
+            'Ó' | 'Ô' | 'Õ' => 'O',
+            'ú' | 'ü' => 'u',
+            'Ú' | 'Ü' => 'U',
+            'ç' => 'c',
+            'Ç' => 'C',
+            c if c.is_ascii() => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+fn format_room_list(rooms: &[RoomInfo]) -> String {
+    if rooms.is_empty() {
+        return "Nenhuma sala aberta. Use /create <nome> para criar uma.\n".to_string();
+    }
+
+    let mut list = String::from("Salas abertas:\n");
+    for room in rooms {
+        list.push_str(&format!(
+            "  {} [{}] ({}/{}, {}) {}",
+            room.name,
+            room.mode,
+            room.players.len(),
+            room.seats,
+            room.status,
+            room.players.join(", ")
+        ));
+        if room.spectators > 0 {
+            list.push_str(&format!(" +{} assistindo", room.spectators));
+        }
+        list.push('\n');
+    }
+    list
+}
+
+fn format_history(player: &str, games: &[GameSummary]) -> String {
+    if games.is_empty() {
+        return format!("Nenhuma partida encontrada para {}.\n", player);
+    }
+
+    let mut list = format!("Últimas partidas de {}:\n", player);
+    for game in games {
+        let result = match (game.winner, game.forfeit) {
+            (1, false) => "venceu X",
+            (-1, false) => "venceu O",
+            (1, true) => "venceu X por W.O.",
+            (-1, true) => "venceu O por W.O.",
+            _ => "empate",
+        };
+        // Numa série, o placar vem junto do resultado e as jogadas são as do último jogo
+        let result = match game.series {
+            Some(series) => format!("{} (série melhor de {}: {} x {})", result, series.best_of, series.wins_x, series.wins_o),
+            None => result.to_string(),
+        };
+        list.push_str(&format!(
+            "  #{} [{}] X: {} / O: {}, {}, {} jogadas em {}m{:02}s\n",
+            game.id,
+            game.mode,
+            game.player_x,
+            game.player_o,
+            result,
+            game.moves,
+            game.duration_secs / 60,
+            game.duration_secs % 60
+        ));
+    }
+    list
+}
+
+fn format_leaderboard(entries: &[RatingEntry]) -> String {
+    if entries.is_empty() {
+        return "Ninguém no ranking ainda. Termine uma partida entre jogadores com apelido.\n".to_string();
+    }
+
+    let mut list = String::from("Ranking:\n");
+    for (position, entry) in entries.iter().enumerate() {
+        list.push_str(&format!(
+            "  {:>2}. {:<16} {:>5}  ({} partidas)\n",
+            position + 1,
+            entry.nick,
+            entry.rating,
+            entry.games
+        ));
+    }
+    list
+}
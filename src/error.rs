@@ -0,0 +1,90 @@
+use std::fmt;
+
+use crate::protocol::ErrorCode;
+
+// Erro de uma jogada. Cada variante tem o seu código para os clientes JSON e uma mensagem
+// em português, que o servidor traduz para o idioma da conexão como as demais.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    InvalidColumn { cols: usize }, // Fora do tabuleiro, que tem `cols` colunas
+    ColumnFull,
+    NoFlips,        // Casa do reversi que não vira peças do adversário
+    NotYourTurn,
+    GameNotStarted, // A sala ainda espera o oponente
+    GameFinished,
+    GamePaused,     // Um jogador caiu e a sala espera que ele volte
+    GameOnHold,     // Pausa combinada com /pause
+    CannotPop,      // Pop Out sem uma peça própria na base da coluna
+    WildUsed,       // Power Up com o coringa já usado
+    Other(String),  // Regras de uma sala em particular, como as duplas e a vantagem
+}
+
+impl GameError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            GameError::InvalidColumn { .. } => ErrorCode::InvalidColumn,
+            GameError::ColumnFull => ErrorCode::ColumnFull,
+            GameError::NotYourTurn => ErrorCode::NotYourTurn,
+            GameError::GameNotStarted => ErrorCode::GameNotStarted,
+            GameError::GameFinished => ErrorCode::GameFinished,
+            GameError::GamePaused | GameError::GameOnHold => ErrorCode::GamePaused,
+            GameError::NoFlips | GameError::CannotPop | GameError::WildUsed => ErrorCode::InvalidMove,
+            GameError::Other(_) => ErrorCode::Error,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            GameError::InvalidColumn { cols } => format!("Coluna inválida. Escolha de 1 a {}.\n", cols),
+            GameError::ColumnFull => "Coluna cheia. Escolha outra coluna.\n".to_string(),
+            GameError::NoFlips => "Jogada inválida. Escolha uma casa vazia que vire peças do adversário.\n".to_string(),
+            GameError::NotYourTurn => "Não é seu turno. Aguardando oponente...\n".to_string(),
+            GameError::GameNotStarted => "A partida ainda não começou. Aguardando oponente...\n".to_string(),
+            GameError::GameFinished => "A partida já terminou.\n".to_string(),
+            GameError::GamePaused => "A partida está pausada até o oponente voltar.\n".to_string(),
+            GameError::GameOnHold => "A partida está pausada. Use /resume para continuar.\n".to_string(),
+            GameError::CannotPop => "Só é possível retirar uma peça sua da base da coluna.\n".to_string(),
+            GameError::WildUsed => "Você já usou o seu coringa nesta partida.\n".to_string(),
+            GameError::Other(message) => message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+// Para quem só mostra o texto, como os clientes e as mensagens de sala em português
+impl From<GameError> for String {
+    fn from(err: GameError) -> Self {
+        err.message()
+    }
+}
+
+impl From<String> for GameError {
+    fn from(message: String) -> Self {
+        GameError::Other(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ServerMessage;
+
+    #[test]
+    fn error_codes_round_trip_and_unknown_codes_fall_back() {
+        let err = GameError::ColumnFull;
+        let message = ServerMessage::Error { message: err.message(), code: Some(err.code()) };
+        let json = serde_json::to_string(&message).unwrap();
+        assert!(json.contains(r#""code":"column_full""#), "{}", json);
+
+        let future = r#"{"type":"error","message":"?","code":"something_new"}"#;
+        match serde_json::from_str::<ServerMessage>(future).unwrap() {
+            ServerMessage::Error { code, .. } => assert_eq!(code, Some(ErrorCode::Error)),
+            other => panic!("{:?}", other),
+        }
+    }
+}
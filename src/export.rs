@@ -0,0 +1,162 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::room::{GameMode, PlayedMove};
+use crate::storage::GameRecord;
+
+// Notação curta de uma jogada: no lig4, a coluna contada a partir de 1, com um p na frente
+// quando a peça foi retirada no Pop Out (ex: p4) e um * quando foi o coringa do Power Up
+// (ex: *4); no reversi, a casa em notação algébrica, com a coluna em letra e a linha em
+// número (ex: d3)
+pub fn move_notation(mode: GameMode, played_move: &PlayedMove) -> String {
+    match mode {
+        GameMode::ConnectFour if played_move.pop => format!("p{}", played_move.col + 1),
+        GameMode::ConnectFour if played_move.wild => format!("*{}", played_move.col + 1),
+        GameMode::ConnectFour | GameMode::Ring(_) => (played_move.col + 1).to_string(),
+        GameMode::Reversi => format!("{}{}", (b'a' + played_move.col as u8) as char, played_move.row + 1),
+    }
+}
+
+// Jogadas numeradas em pares, como no PGN do xadrez. Quando um jogador passa a vez no
+// reversi, a jogada dele aparece como "--".
+pub fn movetext(mode: GameMode, moves: &[PlayedMove]) -> String {
+    let mut turns: Vec<String> = Vec::new();
+    let mut expected = moves.first().map_or(1, |played_move| played_move.symbol);
+    for played_move in moves {
+        if played_move.symbol != expected {
+            turns.push("--".to_string());
+        }
+        turns.push(move_notation(mode, played_move));
+        expected = -played_move.symbol;
+    }
+
+    turns
+        .chunks(2)
+        .enumerate()
+        .map(|(index, pair)| format!("{}. {}", index + 1, pair.join(" ")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Resultado no formato do PGN: 1-0 para o X, 0-1 para o O e 1/2-1/2 para empate
+fn result_tag(winner: i32) -> &'static str {
+    match winner {
+        1 => "1-0",
+        -1 => "0-1",
+        _ => "1/2-1/2",
+    }
+}
+
+// Partida completa como texto, com cabeçalho de etiquetas e as jogadas
+pub fn export(record: &GameRecord) -> String {
+    let mode = GameMode::parse(&record.mode).unwrap_or(GameMode::ConnectFour);
+    let config = record.board_config;
+    let result = result_tag(record.winner);
+
+    let mut text = String::new();
+    text.push_str(&format!("[Event \"Partida #{}\"]\n", record.id));
+    text.push_str(&format!("[Game \"{}\"]\n", record.mode));
+    if mode == GameMode::ConnectFour {
+        text.push_str(&format!("[Board \"{}x{}, {} em linha\"]\n", config.rows, config.cols, config.win_length));
+    }
+    text.push_str(&format!("[Date \"{}\"]\n", utc_date(record.started_at)));
+    text.push_str(&format!("[X \"{}\"]\n", record.player_x));
+    text.push_str(&format!("[O \"{}\"]\n", record.player_o));
+    text.push_str(&format!("[Result \"{}\"]\n", result));
+    if let Some(series) = record.series {
+        text.push_str(&format!("[Series \"{}-{}, melhor de {}\"]\n", series.wins_x, series.wins_o, series.best_of));
+    }
+    if record.forfeit {
+        text.push_str("[Termination \"W.O.\"]\n");
+    }
+    // Semente do sorteio de quem começou, para reproduzir a partida
+    if let Some(seed) = record.seed {
+        text.push_str(&format!("[Seed \"{}\"]\n", seed));
+    }
+    text.push('\n');
+
+    let moves = movetext(mode, &record.moves);
+    if moves.is_empty() {
+        text.push_str(result);
+    } else {
+        text.push_str(&format!("{} {}", moves, result));
+    }
+    text.push('\n');
+    text
+}
+
+// Grava a partida em <dir>/partida-<número>.pgn e retorna o caminho do arquivo
+pub fn write_to_dir(dir: &Path, record: &GameRecord) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("partida-{}.pgn", record.id));
+    std::fs::write(&path, export(record))?;
+    Ok(path)
+}
+
+// Data UTC no formato AAAA.MM.DD, a partir dos segundos desde a época Unix
+pub fn utc_date(secs: u64) -> String {
+    // Conversão de dias para data do calendário gregoriano (algoritmo de Howard Hinnant)
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reversi_egui::game::BoardConfig;
+
+    fn played(symbol: i32, row: usize, col: usize) -> PlayedMove {
+        PlayedMove { symbol, row, col, pop: false, wild: false }
+    }
+
+    #[test]
+    fn connect_four_moves_are_one_based_columns() {
+        let moves = [played(1, 5, 3), played(-1, 4, 3), played(1, 5, 0)];
+        assert_eq!(movetext(GameMode::ConnectFour, &moves), "1. 4 4 2. 1");
+    }
+
+    #[test]
+    fn pop_out_moves_are_prefixed() {
+        let pop = PlayedMove { pop: true, ..played(1, 5, 3) };
+        let wild = PlayedMove { wild: true, ..played(-1, 5, 3) };
+        assert_eq!(movetext(GameMode::ConnectFour, &[played(1, 5, 3), played(-1, 5, 0), pop, wild]), "1. 4 1 2. p4 *4");
+    }
+
+    #[test]
+    fn reversi_passes_are_marked() {
+        // O X joga duas vezes seguidas porque o O ficou sem jogada
+        let moves = [played(1, 2, 3), played(-1, 2, 2), played(1, 2, 1), played(1, 0, 7)];
+        assert_eq!(movetext(GameMode::Reversi, &moves), "1. d3 c3 2. b3 -- 3. h1");
+    }
+
+    #[test]
+    fn export_has_tags_and_result() {
+        let record = GameRecord {
+            id: 7,
+            mode: "lig4".to_string(),
+            board_config: BoardConfig::default(),
+            player_x: "ana".to_string(),
+            player_o: "bia".to_string(),
+            winner: -1,
+            forfeit: true,
+            moves: vec![played(1, 5, 3)],
+            started_at: 1_700_000_000,
+            duration_secs: 30,
+            series: None,
+            seed: Some(42),
+        };
+        let text = export(&record);
+        assert!(text.contains("[Event \"Partida #7\"]"));
+        assert!(text.contains("[Date \"2023.11.14\"]"));
+        assert!(text.contains("[Termination \"W.O.\"]\n[Seed \"42\"]"));
+        assert!(text.ends_with("1. 4 0-1\n"));
+    }
+}
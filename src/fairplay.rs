@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use reversi_egui::ai;
+use reversi_egui::game::{GameState, Move};
+use reversi_egui::solver::Solver;
+
+use crate::room::{Game, GameMode, PlayedMove};
+
+// Prazo do solucionador para a partida inteira
+pub const ANALYSIS_TIME_LIMIT: Duration = Duration::from_secs(10);
+// Tempo da busca em cada posição que o solucionador não resolveu
+const ENGINE_BUDGET: Duration = Duration::from_millis(100);
+// Com menos jogadas analisadas, a porcentagem ainda não diz nada
+const MIN_ANALYZED: usize = 8;
+// Semelhança a partir da qual o relatório chama a atenção para o jogador
+const SUSPICIOUS_SIMILARITY: u32 = 90;
+
+// O que um jogador fez numa partida ranqueada, comparado com o motor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlayerReport {
+    pub nick: String,
+    pub symbol: i32,
+    pub analyzed: usize,    // Jogadas em posições onde havia escolha: nem todas as colunas davam no mesmo
+    pub matches: usize,     // Dessas, as que estavam entre as melhores do motor
+    pub think_ms: Vec<u64>, // Tempo de cada jogada, em ordem; fica de fora o das jogadas de antes de uma retomada
+}
+
+impl PlayerReport {
+    // Porcentagem das jogadas analisadas que coincidiram com o motor
+    pub fn similarity(&self) -> Option<u32> {
+        (self.analyzed > 0).then(|| (self.matches * 100 / self.analyzed) as u32)
+    }
+
+    pub fn mean_ms(&self) -> u64 {
+        self.think_ms.iter().sum::<u64>() / (self.think_ms.len() as u64).max(1)
+    }
+
+    // Desvio padrão dos tempos. Quem repassa jogadas costuma gastar sempre o mesmo tanto,
+    // em qualquer posição.
+    pub fn spread_ms(&self) -> u64 {
+        if self.think_ms.is_empty() {
+            return 0;
+        }
+        let mean = self.mean_ms() as f64;
+        let variance = self.think_ms.iter().map(|ms| (*ms as f64 - mean).powi(2)).sum::<f64>() / self.think_ms.len() as f64;
+        variance.sqrt().round() as u64
+    }
+
+    pub fn flagged(&self) -> bool {
+        self.analyzed >= MIN_ANALYZED && self.similarity().is_some_and(|similarity| similarity >= SUSPICIOUS_SIMILARITY)
+    }
+}
+
+// Relatório gravado de uma partida, para o console de administração
+#[derive(Debug, Clone, PartialEq)]
+pub struct FairPlayReport {
+    pub game_id: i64,
+    pub mode: String,
+    pub created_at: u64,
+    pub players: Vec<PlayerReport>,
+}
+
+impl FairPlayReport {
+    // Só o lig4 tem motor para comparar; nos outros modos o relatório traz apenas os tempos,
+    // e as zero jogadas analisadas não querem dizer jogo limpo
+    pub fn compared(&self) -> bool {
+        self.mode == GameMode::ConnectFour.name()
+    }
+}
+
+// Refaz a partida desde `start` e compara cada jogada de lig4 com as melhores do motor.
+// `think_times` anda junto com `moves`; `players` são os apelidos do X e do O. Depois do
+// prazo, o solucionador dá lugar à busca.
+pub fn analyze(start: Game, moves: &[PlayedMove], think_times: &[Option<u64>], players: &[String; 2], deadline: Instant) -> Vec<PlayerReport> {
+    let mut reports: Vec<PlayerReport> = [1, -1]
+        .into_iter()
+        .zip(players)
+        .map(|(symbol, nick)| PlayerReport { nick: nick.clone(), symbol, analyzed: 0, matches: 0, think_ms: Vec::new() })
+        .collect();
+
+    // As posições antes de cada jogada comum de lig4; retiradas e coringas não têm comparação
+    let mut game = start;
+    let mut positions = Vec::new();
+    for (played, think_time) in moves.iter().zip(think_times.iter().chain(std::iter::repeat(&None))) {
+        if let Some(report) = reports.iter_mut().find(|report| report.symbol == played.symbol) {
+            report.think_ms.extend(think_time);
+        }
+        if let Game::ConnectFour(game_state) = &game {
+            if !played.pop && !played.wild {
+                positions.push((played.symbol, played.col, game_state.clone()));
+            }
+        }
+        game.replay(played);
+    }
+
+    // De trás para a frente, como no /eval: as posições do fim saem na hora e preenchem a
+    // tabela do solucionador para as anteriores
+    let mut solver = Solver::new(deadline);
+    for (symbol, col, position) in positions.iter().rev() {
+        let legal = position.legal_moves().len();
+        let choices = engine_choices(&mut solver, position);
+        if legal < 2 || choices.is_empty() || choices.len() == legal {
+            continue;
+        }
+        if let Some(report) = reports.iter_mut().find(|report| report.symbol == *symbol) {
+            report.analyzed += 1;
+            report.matches += usize::from(choices.contains(col));
+        }
+    }
+    reports
+}
+
+// Colunas que o motor jogaria. Com o solucionador, são todas as que mantêm o valor da
+// posição; fora do tabuleiro padrão ou depois do prazo, é a coluna da busca.
+fn engine_choices(solver: &mut Solver, position: &GameState) -> Vec<usize> {
+    let cols: Vec<usize> = position.legal_moves().iter().map(|legal| legal.col).collect();
+    let values: Option<Vec<i32>> = cols
+        .iter()
+        .map(|col| {
+            let mut child = position.clone();
+            child.apply_move(&Move { col: *col }).ok()?;
+            solver.value(&child).map(|value| -value)
+        })
+        .collect();
+    match values {
+        Some(values) => {
+            let best = values.iter().copied().max().unwrap_or_default();
+            cols.iter().zip(&values).filter(|(_, value)| **value == best).map(|(col, _)| *col).collect()
+        }
+        None => ai::best_move_within(position, ENGINE_BUDGET).into_iter().collect(),
+    }
+}
+
+// Tempo em segundos com uma casa, como "2.4s"
+fn seconds(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1000.0)
+}
+
+// Os relatórios recentes, com uma linha por jogador, para o comando fairplay sem argumento
+pub fn format_reports(reports: &[FairPlayReport]) -> String {
+    if reports.is_empty() {
+        return "Nenhum relatório de jogo limpo ainda.\n".to_string();
+    }
+    let mut list = String::from("Relatórios de jogo limpo, do mais recente:\n");
+    for report in reports {
+        list.push_str(&format!("  #{} {}:\n", report.game_id, report.mode));
+        for player in &report.players {
+            match player.similarity() {
+                _ if !report.compared() => list.push_str(&format!("    {}: sem comparação; o motor só analisa o lig4\n", player.nick)),
+                Some(similarity) if player.flagged() => {
+                    list.push_str(&format!("    {}: {}% de {} jogadas (suspeito)\n", player.nick, similarity, player.analyzed))
+                }
+                Some(similarity) => list.push_str(&format!("    {}: {}% de {} jogadas\n", player.nick, similarity, player.analyzed)),
+                None => list.push_str(&format!("    {}: sem jogadas analisadas\n", player.nick)),
+            }
+        }
+    }
+    list
+}
+
+// Relatório completo de uma partida, para o comando fairplay <partida>
+pub fn describe_report(report: &FairPlayReport) -> String {
+    let mut text = format!("Relatório de jogo limpo da partida #{} ({}):\n", report.game_id, report.mode);
+    for player in &report.players {
+        let glyph = if player.symbol == 1 { 'X' } else { 'O' };
+        match player.similarity() {
+            _ if !report.compared() => {
+                text.push_str(&format!("  {} ({}): só os tempos; o motor só analisa partidas de lig4.\n", player.nick, glyph))
+            }
+            Some(similarity) => text.push_str(&format!(
+                "  {} ({}): {} de {} jogadas entre as melhores do motor ({}%).\n",
+                player.nick, glyph, player.matches, player.analyzed, similarity
+            )),
+            None => text.push_str(&format!("  {} ({}): nenhuma jogada comparada com o motor.\n", player.nick, glyph)),
+        }
+        if !player.think_ms.is_empty() {
+            let times: Vec<String> = player.think_ms.iter().map(|ms| seconds(*ms)).collect();
+            text.push_str(&format!(
+                "    Tempo por jogada: média de {}, desvio de {}. {}\n",
+                seconds(player.mean_ms()),
+                seconds(player.spread_ms()),
+                times.join(" ")
+            ));
+        }
+        if player.flagged() {
+            text.push_str(&format!("    Atenção: semelhança acima de {}% com o motor.\n", SUSPICIOUS_SIMILARITY));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reversi_egui::game::BoardConfig;
+
+    fn played(symbol: i32, col: usize) -> PlayedMove {
+        PlayedMove { symbol, row: 0, col, pop: false, wild: false }
+    }
+
+    #[test]
+    fn engine_matches_count_only_real_choices() {
+        // A partida começa com três do X na coluna 0 e dois do O na 6. O O precisa bloquear a
+        // coluna 0 e não bloqueia; o X fecha a quarta. As duas posições só têm uma boa jogada.
+        let mut start = Game::new(GameMode::ConnectFour, BoardConfig::default(), 1);
+        for (symbol, col) in [(1, 0), (-1, 6), (1, 0), (-1, 6), (1, 0)] {
+            start.replay(&played(symbol, col));
+        }
+        let moves = [played(-1, 6), played(1, 0)];
+        let think_times = [Some(400), None];
+        let players = ["luna".to_string(), "mauro".to_string()];
+        // Com o prazo já vencido, toda posição vai para a busca, que escolhe uma só coluna
+        let reports = analyze(start, &moves, &think_times, &players, Instant::now());
+
+        let (luna, mauro) = (&reports[0], &reports[1]);
+        assert_eq!((luna.analyzed, luna.matches), (1, 1));
+        assert_eq!((mauro.analyzed, mauro.matches), (1, 0));
+        assert_eq!((luna.similarity(), mauro.similarity()), (Some(100), Some(0)));
+        // A jogada sem tempo, de antes de uma retomada, fica fora das médias
+        assert!(luna.think_ms.is_empty());
+        assert_eq!((mauro.mean_ms(), mauro.spread_ms()), (400, 0));
+        assert!(!luna.flagged());
+    }
+
+    #[test]
+    fn reports_of_other_modes_say_the_engine_did_not_compare_them() {
+        let player = PlayerReport { nick: "luna".to_string(), symbol: 1, analyzed: 0, matches: 0, think_ms: vec![1500, 2500] };
+        let report = FairPlayReport { game_id: 7, mode: GameMode::Reversi.name().to_string(), created_at: 0, players: vec![player] };
+        assert!(!report.compared());
+        let text = describe_report(&report);
+        assert!(text.contains("luna (X): só os tempos"), "{}", text);
+        assert!(text.contains("média de 2.0s, desvio de 0.5s"), "{}", text);
+        assert!(format_reports(&[report]).contains("luna: sem comparação"));
+    }
+}
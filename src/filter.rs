@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+
+// Rigor do filtro de palavras no chat de uma sala
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FilterLevel {
+    Off,     // As mensagens passam como foram escritas
+    #[default]
+    Normal,  // Esconde as palavras da lista, mesmo com acentos, maiúsculas ou números no lugar de letras
+    Strict,  // Esconde também as palavras que contêm uma da lista, como em compostos e emendas
+}
+
+impl FilterLevel {
+    // Nome usado no arquivo de configuração
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "off" => Some(FilterLevel::Off),
+            "normal" => Some(FilterLevel::Normal),
+            "strict" => Some(FilterLevel::Strict),
+            _ => None,
+        }
+    }
+
+    // Opção do /create que escolhe o filtro da sala
+    pub fn parse_option(option: &str) -> Option<Self> {
+        match option.to_lowercase().as_str() {
+            "nofilter" | "semfiltro" => Some(FilterLevel::Off),
+            "filter" | "filtro" => Some(FilterLevel::Normal),
+            "strict" | "rigoroso" => Some(FilterLevel::Strict),
+            _ => None,
+        }
+    }
+}
+
+// Lista de palavras proibidas no chat e nos apelidos. As palavras são guardadas já
+// normalizadas, para que variações como "PaLaVrA", "palávra" ou "p4l4vr4" também casem.
+#[derive(Debug, Default)]
+pub struct WordFilter {
+    words: Vec<String>,
+}
+
+impl WordFilter {
+    pub fn new(words: &[String]) -> Self {
+        let mut words: Vec<String> = words.iter().map(|word| normalize(word)).filter(|word| !word.is_empty()).collect();
+        words.sort();
+        words.dedup();
+        WordFilter { words }
+    }
+
+    // Troca por asteriscos as palavras proibidas da mensagem, conforme o rigor da sala
+    pub fn censor<'a>(&self, text: &'a str, level: FilterLevel) -> Cow<'a, str> {
+        if level == FilterLevel::Off || self.words.is_empty() {
+            return Cow::Borrowed(text);
+        }
+        let mut censored = String::with_capacity(text.len());
+        let mut changed = false;
+        let mut rest = text;
+        while let Some(start) = rest.find(is_word_char) {
+            censored.push_str(&rest[..start]);
+            let word = &rest[start..];
+            let end = word.find(|c: char| !is_word_char(c)).unwrap_or(word.len());
+            let (word, after) = word.split_at(end);
+            if self.blocks(word, level) {
+                censored.extend(std::iter::repeat_n('*', word.chars().count()));
+                changed = true;
+            } else {
+                censored.push_str(word);
+            }
+            rest = after;
+        }
+        censored.push_str(rest);
+        if changed { Cow::Owned(censored) } else { Cow::Borrowed(text) }
+    }
+
+    // Apelidos são conferidos sempre com o rigor máximo, e inteiros: "_" e "-" não separam
+    pub fn allows_nick(&self, nick: &str) -> bool {
+        !self.blocks(nick, FilterLevel::Strict)
+    }
+
+    fn blocks(&self, word: &str, level: FilterLevel) -> bool {
+        let word = normalize(word);
+        match level {
+            FilterLevel::Off => false,
+            FilterLevel::Normal => self.words.binary_search(&word).is_ok(),
+            FilterLevel::Strict => self.words.iter().any(|blocked| word.contains(blocked.as_str())),
+        }
+    }
+}
+
+// Letras, números e os símbolos que costumam substituir letras fazem parte da palavra
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '@' | '$')
+}
+
+// Minúsculas sem acentos, com números e símbolos trocados pelas letras que imitam e sem
+// letras repetidas em seguida ("merdaaa" vira "merda"); o resto é descartado
+fn normalize(word: &str) -> String {
+    let mut normalized = String::with_capacity(word.len());
+    for c in word.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' | '4' | '@' => 'a',
+            'é' | 'è' | 'ê' | 'ë' | '3' => 'e',
+            'í' | 'ì' | 'î' | 'ï' | '1' | '!' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' | '0' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            '5' | '$' => 's',
+            '7' => 't',
+            c if c.is_alphanumeric() => c,
+            _ => continue,
+        };
+        if !normalized.ends_with(c) {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> WordFilter {
+        WordFilter::new(&["Bobo".to_string(), "chato".to_string()])
+    }
+
+    #[test]
+    fn normal_level_masks_whole_words_and_their_disguises() {
+        let filter = filter();
+        assert_eq!(filter.censor("que jogada BOBA, seu b0bo", FilterLevel::Normal), "que jogada BOBA, seu ****");
+        assert_eq!(filter.censor("chaaato!", FilterLevel::Normal), "*******!");
+        assert_eq!(filter.censor("bobalhão", FilterLevel::Normal), "bobalhão");
+        assert!(matches!(filter.censor("boa partida", FilterLevel::Normal), Cow::Borrowed(_)));
+        assert_eq!(filter.censor("bobo", FilterLevel::Off), "bobo");
+    }
+
+    #[test]
+    fn strict_level_and_nicks_catch_words_inside_others() {
+        let filter = filter();
+        assert_eq!(filter.censor("superbobo ok", FilterLevel::Strict), "********* ok");
+        assert!(!filter.allows_nick("o_Ch4to"));
+        assert!(filter.allows_nick("rita"));
+    }
+}
@@ -0,0 +1,682 @@
+use serde::{Serialize, Deserialize};
+
+use crate::error::GameError;
+
+// Tabuleiro clássico do lig4
+pub const ROWS: usize = 6;
+pub const COLS: usize = 7;
+pub const WIN_LENGTH: usize = 4;
+
+// Limites aceitos para tabuleiros personalizados
+pub const MIN_SIZE: usize = 4;
+pub const MAX_ROWS: usize = 12;
+pub const MAX_COLS: usize = 10;
+pub const MIN_WIN_LENGTH: usize = 3;
+
+// Conteúdo de uma casa. A peça coringa (variante Power Up) continua sendo de quem a jogou,
+// mas conta como peça dos dois jogadores na detecção de sequências.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cell {
+    #[default]
+    Empty,
+    Piece(i32), // 1 para X, -1 para O
+    Wild(i32),  // Coringa, com o símbolo de quem o jogou
+}
+
+impl Cell {
+    // Símbolo de quem jogou a peça: 1 para X, -1 para O, 0 para vazia
+    pub fn symbol(self) -> i32 {
+        match self {
+            Cell::Empty => 0,
+            Cell::Piece(symbol) | Cell::Wild(symbol) => symbol,
+        }
+    }
+
+    // Se a casa conta para a sequência do jogador
+    pub fn counts_for(self, player_symbol: i32) -> bool {
+        match self {
+            Cell::Empty => false,
+            Cell::Piece(symbol) => symbol == player_symbol,
+            Cell::Wild(_) => true,
+        }
+    }
+
+    // Valor da casa na grade do protocolo: 1 e -1 para as peças, 2 e -2 para os coringas
+    // do X e do O e 0 para vazia
+    pub fn value(self) -> i32 {
+        match self {
+            Cell::Empty => 0,
+            Cell::Piece(symbol) => symbol,
+            Cell::Wild(symbol) => 2 * symbol,
+        }
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Cell::Empty,
+            2 | -2 => Cell::Wild(value / 2),
+            symbol => Cell::Piece(symbol.signum()),
+        }
+    }
+}
+
+// Dimensões do tabuleiro e quantas peças em linha são necessárias para vencer
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BoardConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub win_length: usize,
+}
+
+impl BoardConfig {
+    // Valida um tabuleiro personalizado
+    pub fn new(rows: usize, cols: usize, win_length: usize) -> Result<Self, String> {
+        if !(MIN_SIZE..=MAX_ROWS).contains(&rows) || !(MIN_SIZE..=MAX_COLS).contains(&cols) {
+            return Err(format!(
+                "Tamanho inválido. Use de {} a {} linhas e de {} a {} colunas.\n",
+                MIN_SIZE, MAX_ROWS, MIN_SIZE, MAX_COLS
+            ));
+        }
+        if win_length < MIN_WIN_LENGTH || win_length > rows.max(cols) {
+            return Err(format!(
+                "Sequência inválida. Use de {} a {} peças em linha.\n",
+                MIN_WIN_LENGTH,
+                rows.max(cols)
+            ));
+        }
+        Ok(BoardConfig { rows, cols, win_length })
+    }
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        BoardConfig {
+            rows: ROWS,
+            cols: COLS,
+            win_length: WIN_LENGTH,
+        }
+    }
+}
+
+// Estado do lig4 em bitboards: um inteiro de 128 bits por jogador, um bit por casa. O bit
+// da casa é col * rows + altura, com a altura contada de baixo para cima, de modo que
+// cada coluna ocupa bits seguidos. Cabe o maior tabuleiro aceito (12 x 10 = 120 casas).
+// Copiar o estado é barato e detectar uma sequência custa alguns deslocamentos, o que
+// importa para a busca do computador.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GameState {
+    pieces: [u128; 2], // Casas do X e do O
+    #[serde(default)]
+    wild: u128, // Casas com coringa; cada uma também está nas casas de quem o jogou
+    #[serde(default)]
+    wild_used: [bool; 2], // Quem do X e do O já gastou o coringa da partida
+    rows: usize,
+    cols: usize,
+    pub current_turn: i32, // 1 para jogador 1 (X), -1 para jogador 2 (O)
+    pub win_length: usize, // Peças em linha necessárias para vencer
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Move {
+    pub col: usize, // Coluna onde a peça será jogada
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        Self::with_config(BoardConfig::default())
+    }
+
+    pub fn with_config(config: BoardConfig) -> Self {
+        GameState {
+            pieces: [0, 0],
+            wild: 0,
+            wild_used: [false, false],
+            rows: config.rows,
+            cols: config.cols,
+            current_turn: 1,
+            win_length: config.win_length,
+        }
+    }
+
+    // Monta o estado a partir de uma grade com as linhas de cima para baixo, com as casas
+    // codificadas como na grade do protocolo
+    pub fn from_grid(grid: &[Vec<i32>], current_turn: i32, win_length: usize) -> Self {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        let mut game_state = GameState {
+            pieces: [0, 0],
+            wild: 0,
+            wild_used: [false, false],
+            rows,
+            cols,
+            current_turn,
+            win_length,
+        };
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                game_state.set_cell(row, col, *cell);
+            }
+        }
+        game_state
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn config(&self) -> BoardConfig {
+        BoardConfig {
+            rows: self.rows(),
+            cols: self.cols(),
+            win_length: self.win_length,
+        }
+    }
+
+    // Bit da casa; row conta de cima para baixo, como na grade exibida
+    fn bit(&self, row: usize, col: usize) -> u128 {
+        1 << (col * self.rows + (self.rows - 1 - row))
+    }
+
+    fn slot(player_symbol: i32) -> usize {
+        if player_symbol == 1 { 0 } else { 1 }
+    }
+
+    // Bitboard com as casas de um jogador, inclusive o coringa dele
+    pub fn pieces(&self, player_symbol: i32) -> u128 {
+        self.pieces[Self::slot(player_symbol)]
+    }
+
+    // Bitboard com as casas de coringa, que contam para os dois jogadores
+    pub fn wild(&self) -> u128 {
+        self.wild
+    }
+
+    // Se o jogador ainda não usou o coringa nesta partida
+    pub fn has_wild(&self, player_symbol: i32) -> bool {
+        !self.wild_used[Self::slot(player_symbol)]
+    }
+
+    fn occupied(&self) -> u128 {
+        self.pieces[0] | self.pieces[1]
+    }
+
+    pub fn piece_count(&self) -> usize {
+        self.occupied().count_ones() as usize
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        let bit = self.bit(row, col);
+        let symbol = if self.pieces[0] & bit != 0 {
+            1
+        } else if self.pieces[1] & bit != 0 {
+            -1
+        } else {
+            return Cell::Empty;
+        };
+        if self.wild & bit != 0 {
+            Cell::Wild(symbol)
+        } else {
+            Cell::Piece(symbol)
+        }
+    }
+
+    // Muda uma casa diretamente, sem a gravidade nem a troca de turno
+    pub fn set_cell(&mut self, row: usize, col: usize, cell: impl Into<Cell>) {
+        let bit = self.bit(row, col);
+        self.pieces[0] &= !bit;
+        self.pieces[1] &= !bit;
+        self.wild &= !bit;
+        let cell = cell.into();
+        if cell != Cell::Empty {
+            self.pieces[Self::slot(cell.symbol())] |= bit;
+        }
+        if let Cell::Wild(_) = cell {
+            self.wild |= bit;
+        }
+    }
+
+    // Grade para exibição e para o protocolo, com as linhas de cima para baixo e as casas
+    // codificadas por Cell::value
+    pub fn board(&self) -> Vec<Vec<i32>> {
+        (0..self.rows)
+            .map(|row| (0..self.cols).map(|col| self.cell(row, col).value()).collect())
+            .collect()
+    }
+
+    // Linha em que uma peça jogada na coluna vai parar: a casa vazia mais baixa.
+    // None se a coluna não existe ou está cheia.
+    pub fn landing_row(&self, col: usize) -> Option<usize> {
+        if col >= self.cols() {
+            return None;
+        }
+        let column = self.occupied() >> (col * self.rows);
+        let height = column.trailing_ones() as usize;
+        (height < self.rows).then(|| self.rows - 1 - height)
+    }
+
+    // Colunas onde o jogador da vez pode jogar, da esquerda para a direita; nenhuma com a
+    // partida encerrada
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.result().is_some() {
+            return Vec::new();
+        }
+        (0..self.cols()).filter(|col| self.landing_row(*col).is_some()).map(|col| Move { col }).collect()
+    }
+
+    // Solta a peça do jogador da vez na coluna, sem passar o turno. Retorna a linha onde ela caiu.
+    pub fn drop_piece(&mut self, col: usize) -> Result<usize, GameError> {
+        if col >= self.cols() {
+            return Err(GameError::InvalidColumn { cols: self.cols() });
+        }
+        let row = self.landing_row(col).ok_or(GameError::ColumnFull)?;
+        self.set_cell(row, col, Cell::Piece(self.current_turn));
+        Ok(row)
+    }
+
+    // Power Up: joga o coringa do jogador da vez na coluna e passa o turno. Cada jogador
+    // tem um só por partida. Retorna a linha onde ele caiu.
+    pub fn apply_wild(&mut self, player_move: &Move) -> Result<usize, GameError> {
+        if !self.has_wild(self.current_turn) {
+            return Err(GameError::WildUsed);
+        }
+        let row = self.drop_piece(player_move.col)?;
+        self.set_cell(row, player_move.col, Cell::Wild(self.current_turn));
+        self.wild_used[Self::slot(self.current_turn)] = true;
+        self.current_turn = -self.current_turn;
+        Ok(row)
+    }
+
+    // Atualiza o estado do jogo com base na jogada. Retorna a linha onde a peça caiu.
+    pub fn apply_move(&mut self, player_move: &Move) -> Result<usize, GameError> {
+        let row = self.drop_piece(player_move.col)?;
+
+        // Alterna o turno
+        self.current_turn = -self.current_turn;
+
+        Ok(row)
+    }
+
+    // Peça do jogador da vez na base da coluna, que ele pode retirar no Pop Out
+    pub fn can_pop(&self, col: usize) -> bool {
+        col < self.cols() && self.pieces(self.current_turn) & (1 << (col * self.rows)) != 0
+    }
+
+    // Pop Out: retira a peça do jogador da vez da base da coluna; as de cima descem uma casa.
+    // Passa o turno, como uma jogada comum.
+    pub fn pop_piece(&mut self, col: usize) -> Result<(), GameError> {
+        if col >= self.cols() {
+            return Err(GameError::InvalidColumn { cols: self.cols() });
+        }
+        if !self.can_pop(col) {
+            return Err(GameError::CannotPop);
+        }
+        let column = ((1u128 << self.rows) - 1) << (col * self.rows);
+        let bottom = 1u128 << (col * self.rows);
+        for pieces in self.pieces.iter_mut().chain([&mut self.wild]) {
+            // Sem a base, o deslocamento não passa para a coluna anterior
+            let shifted = (*pieces & column & !bottom) >> 1;
+            *pieces = (*pieces & !column) | shifted;
+        }
+        self.current_turn = -self.current_turn;
+        Ok(())
+    }
+
+    // Resultado da partida: Some(1) ou Some(-1) para o vencedor, Some(0) para empate
+    pub fn result(&self) -> Option<i32> {
+        check_winner(self).map(|outcome| outcome.winner.symbol())
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Verificar se uma jogada é válida
+pub fn is_valid_move(game_state: &GameState, player_move: &Move) -> bool {
+    game_state.landing_row(player_move.col).is_some()
+}
+
+// Casas onde o jogador venceria com a próxima peça: uma por coluna, na altura em que ela
+// cairia. Serve para destacar vitórias e ameaças no tabuleiro.
+pub fn winning_cells(game_state: &GameState, player_symbol: i32) -> Vec<(usize, usize)> {
+    (0..game_state.cols())
+        .filter_map(|col| {
+            let row = game_state.landing_row(col)?;
+            let mut next = game_state.clone();
+            next.set_cell(row, col, player_symbol);
+            has_line(&next, player_symbol).then_some((row, col))
+        })
+        .collect()
+}
+
+// Empate: todas as casas estão ocupadas, então nenhuma coluna aceita mais peças
+pub fn check_draw(game_state: &GameState) -> bool {
+    game_state.piece_count() == game_state.rows() * game_state.cols()
+}
+
+// Quem venceu a partida
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner {
+    X,
+    O,
+    Draw,
+}
+
+impl Winner {
+    // Símbolo do vencedor: 1 para X, -1 para O, 0 para empate
+    pub fn symbol(self) -> i32 {
+        match self {
+            Winner::X => 1,
+            Winner::O => -1,
+            Winner::Draw => 0,
+        }
+    }
+
+    fn of(player_symbol: i32) -> Winner {
+        if player_symbol == 1 { Winner::X } else { Winner::O }
+    }
+}
+
+// Fim de partida: o vencedor e as casas da sequência que decidiu, como (linha, coluna),
+// a partir da ponta de baixo à esquerda. No empate não há sequência.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub winner: Winner,
+    pub line: Vec<(usize, usize)>,
+}
+
+// Direções de uma sequência, como (altura, coluna): vertical, horizontal e as duas diagonais
+const LINE_DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+
+// Função para verificar se a partida terminou, olhando o tabuleiro inteiro. Quem acabou de
+// jogar é verificado primeiro: no Pop Out, retirar uma peça pode completar sequências dos
+// dois jogadores de uma vez, assim como um coringa no Power Up, e nesse caso vence quem fez
+// a jogada.
+pub fn check_winner(game_state: &GameState) -> Option<Outcome> {
+    [-game_state.current_turn, game_state.current_turn]
+        .into_iter()
+        .find_map(|symbol| {
+            let line = winning_line(game_state, symbol)?;
+            Some(Outcome { winner: Winner::of(symbol), line })
+        })
+        .or_else(|| check_draw(game_state).then(|| Outcome { winner: Winner::Draw, line: Vec::new() }))
+}
+
+// Se o jogador tem uma sequência completa, sem montar a lista de casas; é o que a busca usa
+pub fn has_line(game_state: &GameState, player_symbol: i32) -> bool {
+    line_start(game_state, player_symbol).is_some()
+}
+
+// Casas da sequência completa do jogador, na ordem da direção em que ela foi encontrada
+fn winning_line(game_state: &GameState, player_symbol: i32) -> Option<Vec<(usize, usize)>> {
+    let (start, (d_height, d_col)) = line_start(game_state, player_symbol)?;
+    let rows = game_state.rows();
+    // O bit de índice col * rows + altura; a altura conta de baixo para cima
+    let (col, height) = ((start / rows) as isize, (start % rows) as isize);
+    let line = (0..game_state.win_length as isize)
+        .map(|k| (rows - 1 - (height + k * d_height) as usize, (col + k * d_col) as usize))
+        .collect();
+    Some(line)
+}
+
+// Bit onde começa uma sequência completa do jogador e a direção dela. Para cada direção, o
+// bitboard deslocado k casas na direção marca quem tem peça k casas adiante; a interseção
+// dos deslocamentos marca o início de cada sequência completa. A máscara descarta
+// sequências que sairiam do tabuleiro e dariam a volta para a coluna seguinte.
+// Os coringas entram nas casas dos dois jogadores.
+fn line_start(game_state: &GameState, player_symbol: i32) -> Option<(usize, (isize, isize))> {
+    let pieces = game_state.pieces(player_symbol) | game_state.wild();
+    let rows = game_state.rows();
+    let length = game_state.win_length;
+    if length == 0 || pieces.count_ones() < length as u32 {
+        return None;
+    }
+
+    // Um bit na base de cada coluna; multiplicado por um padrão de alturas, repete o padrão em
+    // todas as colunas sem vai-um, já que o padrão cabe em `rows` bits
+    let bottom = (0..game_state.cols()).fold(0u128, |mask, col| mask | 1 << (col * rows));
+
+    LINE_DIRECTIONS.iter().find_map(|&(d_height, d_col)| {
+        let shift = (d_col * rows as isize + d_height) as usize;
+        // Na horizontal o deslocamento anda colunas inteiras e nada passa de uma coluna para
+        // outra. Nas demais direções, só valem as alturas de onde a sequência cabe na coluna.
+        // Colunas de início perto da borda direita não precisam de máscara: as casas além
+        // do tabuleiro são sempre zero.
+        let mut starts = match start_heights(rows, length, d_height) {
+            Some(column) => pieces & (column * bottom),
+            None => pieces,
+        };
+        for k in 1..length {
+            if starts == 0 {
+                return None;
+            }
+            // Sequências longas em tabuleiros altos podem pedir deslocamentos além de 128 bits
+            starts &= pieces.checked_shr((k * shift) as u32).unwrap_or(0);
+        }
+        (starts != 0).then(|| (starts.trailing_zeros() as usize, (d_height, d_col)))
+    })
+}
+
+// Alturas de onde uma sequência na direção dada cabe na coluna, como máscara de `rows` bits.
+// None na horizontal, em que a altura não muda.
+fn start_heights(rows: usize, length: usize, d_height: isize) -> Option<u128> {
+    let span = length - 1;
+    let count = rows.saturating_sub(span) as u32;
+    let heights = 1u128.checked_shl(count).map_or(u128::MAX, |bit| bit - 1);
+    match d_height {
+        1 => Some(heights),
+        -1 => Some(heights << span),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Enche a coluna até sobrar o número de casas vazias pedido, alternando os jogadores
+    fn fill_column(game_state: &mut GameState, col: usize, empty: usize) {
+        for _ in 0..game_state.rows() - empty {
+            game_state.apply_move(&Move { col }).unwrap();
+        }
+    }
+
+    #[test]
+    fn piece_lands_on_bottom_row_of_empty_column() {
+        let mut game_state = GameState::new();
+        assert_eq!(game_state.drop_piece(3), Ok(ROWS - 1));
+        assert_eq!(game_state.cell(ROWS - 1, 3).symbol(), 1);
+    }
+
+    #[test]
+    fn pieces_stack_upwards() {
+        let mut game_state = GameState::new();
+        for expected in (0..ROWS).rev() {
+            assert_eq!(game_state.apply_move(&Move { col: 0 }), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn drop_piece_does_not_change_turn() {
+        let mut game_state = GameState::new();
+        game_state.drop_piece(2).unwrap();
+        assert_eq!(game_state.current_turn, 1);
+        game_state.apply_move(&Move { col: 2 }).unwrap();
+        assert_eq!(game_state.current_turn, -1);
+    }
+
+    #[test]
+    fn nearly_full_column_accepts_piece_on_top_row() {
+        let mut game_state = GameState::new();
+        fill_column(&mut game_state, 4, 1);
+        assert_eq!(game_state.landing_row(4), Some(0));
+        assert!(is_valid_move(&game_state, &Move { col: 4 }));
+
+        let symbol = game_state.current_turn;
+        assert_eq!(game_state.drop_piece(4), Ok(0));
+        assert_eq!(game_state.cell(0, 4).symbol(), symbol);
+    }
+
+    #[test]
+    fn full_column_is_rejected_without_overwriting() {
+        let mut game_state = GameState::new();
+        fill_column(&mut game_state, 5, 0);
+        let before = game_state.clone();
+
+        assert_eq!(game_state.landing_row(5), None);
+        assert!(!is_valid_move(&game_state, &Move { col: 5 }));
+        assert!(game_state.drop_piece(5).is_err());
+        assert!(game_state.apply_move(&Move { col: 5 }).is_err());
+        assert_eq!(game_state, before);
+    }
+
+    #[test]
+    fn rejected_move_keeps_turn() {
+        let mut game_state = GameState::new();
+        fill_column(&mut game_state, 1, 0);
+        let turn = game_state.current_turn;
+        assert!(game_state.apply_move(&Move { col: 1 }).is_err());
+        assert_eq!(game_state.current_turn, turn);
+    }
+
+    #[test]
+    fn column_out_of_range_is_rejected() {
+        let mut game_state = GameState::new();
+        assert_eq!(game_state.landing_row(COLS), None);
+        assert!(!is_valid_move(&game_state, &Move { col: COLS }));
+        assert!(game_state.drop_piece(COLS).is_err());
+        assert!(game_state.drop_piece(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn landing_row_and_validation_agree_on_every_fill_level() {
+        let config = BoardConfig::new(MAX_ROWS, MIN_SIZE, MIN_WIN_LENGTH).unwrap();
+        for empty in 0..=config.rows {
+            let mut game_state = GameState::with_config(config);
+            fill_column(&mut game_state, 0, empty);
+            let landing = game_state.landing_row(0);
+            assert_eq!(landing, empty.checked_sub(1));
+            assert_eq!(is_valid_move(&game_state, &Move { col: 0 }), landing.is_some());
+            assert_eq!(game_state.drop_piece(0).ok(), landing);
+        }
+    }
+
+    #[test]
+    fn pop_shifts_the_column_down() {
+        let mut game_state = GameState::new();
+        for col in [2, 2, 2] {
+            game_state.apply_move(&Move { col }).unwrap();
+        }
+        // X, O, X de baixo para cima; é a vez do O, que não tem peça na base
+        assert!(game_state.pop_piece(2).is_err());
+        game_state.apply_move(&Move { col: 0 }).unwrap();
+
+        assert!(game_state.pop_piece(2).is_ok());
+        assert_eq!(game_state.cell(ROWS - 1, 2).symbol(), -1);
+        assert_eq!(game_state.cell(ROWS - 2, 2).symbol(), 1);
+        assert_eq!(game_state.cell(ROWS - 3, 2).symbol(), 0);
+        assert_eq!(game_state.cell(ROWS - 1, 1).symbol(), 0);
+        assert_eq!(game_state.current_turn, -1);
+    }
+
+    #[test]
+    fn pop_completing_both_lines_wins_for_the_popper() {
+        // Retirar o X da base da coluna 3 fecha a linha de baixo do O e a de cima do X
+        let rows = [".......", ".......", ".......", "...X...", "XXXO...", "OOOX..."];
+        let grid: Vec<Vec<i32>> = rows
+            .iter()
+            .map(|row| row.chars().map(|cell| match cell { 'X' => 1, 'O' => -1, _ => 0 }).collect())
+            .collect();
+        let mut game_state = GameState::from_grid(&grid, 1, 4);
+        assert_eq!(game_state.result(), None);
+
+        game_state.pop_piece(3).unwrap();
+        assert!(has_line(&game_state, 1));
+        assert!(has_line(&game_state, -1));
+        assert_eq!(check_winner(&game_state).map(|outcome| outcome.winner), Some(Winner::X));
+    }
+
+    #[test]
+    fn winner_and_line_come_from_the_whole_board() {
+        let rows = ["......", "...O..", "..OX..", ".OXX..", "OXXO.."];
+        let grid: Vec<Vec<i32>> = rows
+            .iter()
+            .map(|row| row.chars().map(|cell| match cell { 'X' => 1, 'O' => -1, _ => 0 }).collect())
+            .collect();
+        let game_state = GameState::from_grid(&grid, 1, 4);
+        let outcome = Outcome { winner: Winner::O, line: vec![(4, 0), (3, 1), (2, 2), (1, 3)] };
+        assert_eq!(check_winner(&game_state), Some(outcome));
+
+        // Numa posição carregada, a sequência vale mesmo sendo a vez de quem a fez
+        let mut grid = vec![vec![0; COLS]; ROWS];
+        grid[0] = vec![0, 0, 0, 1, 1, 1, 1];
+        let game_state = GameState::from_grid(&grid, 1, 4);
+        let outcome = Outcome { winner: Winner::X, line: vec![(0, 3), (0, 4), (0, 5), (0, 6)] };
+        assert_eq!(check_winner(&game_state), Some(outcome));
+        assert_eq!(check_winner(&GameState::new()), None);
+    }
+
+    #[test]
+    fn legal_moves_skip_full_columns_and_finished_games() {
+        let mut game_state = GameState::new();
+        fill_column(&mut game_state, 2, 0);
+        let cols: Vec<usize> = game_state.legal_moves().iter().map(|player_move| player_move.col).collect();
+        assert_eq!(cols, [0, 1, 3, 4, 5, 6]);
+
+        let mut grid = vec![vec![0; COLS]; ROWS];
+        grid[ROWS - 1] = vec![1, 1, 1, 1, 0, 0, 0];
+        assert!(GameState::from_grid(&grid, -1, 4).legal_moves().is_empty());
+    }
+
+    #[test]
+    fn wild_piece_counts_for_both_players_once_per_game() {
+        let mut grid = vec![vec![0; COLS]; ROWS];
+        grid[ROWS - 1] = vec![-1, -1, -1, 0, 0, 0, 1];
+        let mut game_state = GameState::from_grid(&grid, 1, 4);
+
+        // O coringa do X também conta para o O, que fecha a linha de baixo
+        assert_eq!(game_state.apply_wild(&Move { col: 3 }), Ok(ROWS - 1));
+        assert_eq!(game_state.cell(ROWS - 1, 3), Cell::Wild(1));
+        assert!(has_line(&game_state, -1));
+        assert!(!has_line(&game_state, 1));
+        assert_eq!(game_state.result(), Some(-1));
+
+        // Um coringa por jogador em cada partida
+        assert!(!game_state.has_wild(1));
+        assert!(game_state.has_wild(-1));
+        game_state.current_turn = 1;
+        assert!(game_state.apply_wild(&Move { col: 4 }).is_err());
+        assert_eq!(Cell::from(Cell::Wild(-1).value()), Cell::Wild(-1));
+    }
+
+    #[test]
+    fn piece_lands_above_gap_free_stack_only() {
+        // Uma casa ocupada no meio da coluna não pode ser sobrescrita
+        let mut game_state = GameState::new();
+        game_state.set_cell(ROWS - 1, 6, -1);
+        game_state.set_cell(ROWS - 2, 6, 1);
+        assert_eq!(game_state.drop_piece(6), Ok(ROWS - 3));
+        assert_eq!(game_state.cell(ROWS - 2, 6).symbol(), 1);
+    }
+
+    #[test]
+    fn winning_cells_are_the_playable_ones_that_complete_a_line() {
+        let mut grid = vec![vec![0; COLS]; ROWS];
+        grid[ROWS - 1] = vec![0, 1, 1, 1, 0, -1, -1];
+        grid[ROWS - 2] = vec![0, 0, 0, 0, 0, -1, -1];
+        grid[ROWS - 3] = vec![0, 0, 0, 0, 0, -1, 0];
+        let game_state = GameState::from_grid(&grid, 1, 4);
+
+        assert_eq!(winning_cells(&game_state, 1), vec![(ROWS - 1, 0), (ROWS - 1, 4)]);
+        // A vertical do O fecha em cima da coluna 5; na coluna 6 ele ainda teria só três
+        assert_eq!(winning_cells(&game_state, -1), vec![(ROWS - 4, 5)]);
+    }
+}
@@ -0,0 +1,112 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time;
+
+use crate::lobby::Lobby;
+use crate::metrics::METRICS;
+
+// Tempo para o lobby responder; além disso, ele é dado como travado
+const LOBBY_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Portas abertas pelo servidor e se o laço principal está aceitando conexões, para os
+// testes de vida (/healthz) e de prontidão (/readyz) dos orquestradores
+pub struct Health {
+    listeners: std::sync::Mutex<Vec<Listener>>,
+    accepting: AtomicBool, // Ligado quando o laço de aceitação começa, desligado no encerramento
+}
+
+pub static HEALTH: Health = Health::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Listener {
+    pub transport: String,
+    pub address: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    status: &'static str, // ok ou unavailable, como o código HTTP
+    accepting: bool,
+    listeners: Vec<Listener>,
+    lobby: &'static str,    // ok ou timeout
+    database: &'static str, // ok, disabled (sem histórico), error ou unknown, se o lobby não respondeu
+    #[serde(skip_serializing_if = "Option::is_none")]
+    database_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rooms: Option<RoomCounts>, // Sem valor se o lobby não respondeu a tempo
+    connections: u64,
+    uptime_secs: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct RoomCounts {
+    total: usize,
+    playing: usize,
+    waiting: usize,
+    paused: usize,
+}
+
+impl Health {
+    const fn new() -> Self {
+        Health { listeners: std::sync::Mutex::new(Vec::new()), accepting: AtomicBool::new(false) }
+    }
+
+    pub fn listening(&self, transport: &str, address: String) {
+        let mut listeners = self.listeners.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        listeners.push(Listener { transport: transport.to_string(), address });
+    }
+
+    pub fn set_accepting(&self, accepting: bool) {
+        self.accepting.store(accepting, Ordering::Relaxed);
+    }
+
+    // Resposta de /healthz (vivo: o lobby responde) ou de /readyz (pronto: além disso, aceita
+    // conexões e o banco, se houver, responde). O corpo em JSON detalha cada parte nos dois.
+    pub async fn check(&self, lobby: &Arc<Mutex<Lobby>>, readiness: bool) -> (&'static str, String) {
+        let snapshot = time::timeout(LOBBY_TIMEOUT, async {
+            let lobby = lobby.lock().await;
+            (lobby.stats().await, lobby.check_database())
+        })
+        .await;
+
+        let accepting = self.accepting.load(Ordering::Relaxed);
+        let mut report = Report {
+            status: "ok",
+            accepting,
+            listeners: self.listeners.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone(),
+            lobby: "ok",
+            database: "unknown",
+            database_error: None,
+            rooms: None,
+            connections: METRICS.open_connections(),
+            uptime_secs: 0,
+        };
+        let healthy = match snapshot {
+            Ok((stats, database)) => {
+                report.rooms = Some(RoomCounts { total: stats.rooms, playing: stats.playing, waiting: stats.waiting, paused: stats.paused });
+                report.uptime_secs = stats.uptime.as_secs();
+                match database {
+                    None => report.database = "disabled",
+                    Some(Ok(())) => report.database = "ok",
+                    Some(Err(err)) => {
+                        report.database = "error";
+                        report.database_error = Some(err);
+                    }
+                }
+                !readiness || (accepting && report.database != "error")
+            }
+            Err(_) => {
+                report.lobby = "timeout";
+                false
+            }
+        };
+        let status = if healthy { "200 OK" } else { "503 Service Unavailable" };
+        if !healthy {
+            report.status = "unavailable";
+        }
+        (status, serde_json::to_string(&report).unwrap_or_default())
+    }
+}
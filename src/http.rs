@@ -0,0 +1,66 @@
+use std::io;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+
+// Tamanho máximo lido de um pedido HTTP; os pedidos atendidos aqui não têm corpo
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+// O pouco de HTTP que as portas de métricas e da API precisam: um pedido GET por conexão,
+// respondido de uma vez (ou como fluxo de eventos) e fechado em seguida
+pub struct Request {
+    pub method: String,
+    pub path: String, // Sem a query string
+}
+
+// Lê a linha do pedido e descarta os cabeçalhos. Retorna o pedido e o lado de escrita da conexão.
+pub async fn read_request(stream: TcpStream) -> io::Result<(Request, OwnedWriteHalf)> {
+    let (reader, writer) = stream.into_split();
+    let mut reader = BufReader::new(reader.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    // Os cabeçalhos não importam, mas são lidos até a linha em branco antes de responder
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    Ok((Request { method, path }, writer))
+}
+
+// Envia a resposta inteira e fecha a conexão. Tudo aqui é de leitura pública, então
+// qualquer página pode fazer o pedido.
+pub async fn respond(mut writer: OwnedWriteHalf, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    writer.write_all(response.as_bytes()).await?;
+    writer.shutdown().await
+}
+
+// Cabeçalho de um fluxo de Server-Sent Events: sem Content-Length, o corpo segue até a
+// conexão fechar
+pub async fn start_event_stream(writer: &mut OwnedWriteHalf) -> io::Result<()> {
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n";
+    writer.write_all(head.as_bytes()).await
+}
+
+// Um evento do fluxo; os dados ficam em uma linha só, como os JSON do protocolo
+pub async fn send_event(writer: &mut OwnedWriteHalf, event: &str, data: &str) -> io::Result<()> {
+    writer.write_all(format!("event: {}\ndata: {}\n\n", event, data).as_bytes()).await
+}
+
+// Comentário do fluxo, ignorado pelo navegador; serve para notar quem já foi embora
+pub async fn send_comment(writer: &mut OwnedWriteHalf, text: &str) -> io::Result<()> {
+    writer.write_all(format!(": {}\n\n", text).as_bytes()).await
+}
@@ -0,0 +1,647 @@
+use std::borrow::Cow;
+
+// Idioma das mensagens de uma conexão. O servidor escreve tudo em português; para os
+// outros idiomas a conexão traduz cada mensagem pelo catálogo na hora de enviar, assim
+// jogadores de idiomas diferentes podem dividir a mesma sala.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Lang {
+    #[default]
+    Pt,
+    En,
+}
+
+impl Lang {
+    pub fn parse(code: &str) -> Option<Self> {
+        match code.to_lowercase().replace('_', "-").as_str() {
+            "pt" | "pt-br" => Some(Lang::Pt),
+            "en" | "en-us" | "en-gb" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::Pt => "pt",
+            Lang::En => "en",
+        }
+    }
+}
+
+// Traduz uma mensagem do servidor. Textos inteiros, como as ajudas, são procurados
+// primeiro; depois cada linha, e por fim cada frase de uma linha que não foi encontrada.
+// O que não está no catálogo segue em português.
+pub fn translate(lang: Lang, text: &str) -> Cow<'_, str> {
+    if lang == Lang::Pt {
+        return Cow::Borrowed(text);
+    }
+    if let Some((_, en)) = CATALOG.iter().find(|(pt, _)| *pt == text) {
+        return Cow::Borrowed(en);
+    }
+    if let Some(help) = crate::command::translate_help(lang, text) {
+        return Cow::Owned(help);
+    }
+
+    let mut translated = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (body, ending) = match line.strip_suffix('\n') {
+            Some(body) => (body, "\n"),
+            None => (line, ""),
+        };
+        match translate_line(body, 0) {
+            Some(body) => translated.push_str(&body),
+            None => translated.push_str(&translate_sentences(body)),
+        }
+        translated.push_str(ending);
+    }
+    Cow::Owned(translated)
+}
+
+// Os argumentos de um modelo também são traduzidos, até esta profundidade, para
+// mensagens que embutem outras (como o placar de uma série dentro do resultado)
+const MAX_NESTING: usize = 3;
+
+// Tradução de uma linha inteira por um modelo do catálogo, em que {} marca os argumentos
+fn translate_line(line: &str, depth: usize) -> Option<String> {
+    CATALOG.iter().find_map(|(pt, en)| {
+        let args = match_template(pt, line)?;
+        let args: Vec<String> = args
+            .into_iter()
+            .map(|arg| match depth < MAX_NESTING {
+                true => translate_line(arg, depth + 1).unwrap_or_else(|| arg.to_string()),
+                false => arg.to_string(),
+            })
+            .collect();
+        Some(fill_template(en, &args))
+    })
+}
+
+// Linhas com várias frases montadas em partes, como o status do replay
+fn translate_sentences(line: &str) -> String {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (index, _) in line.match_indices(['.', '!', '?']) {
+        if line[index + 1..].starts_with(' ') {
+            sentences.push(&line[start..=index]);
+            start = index + 2;
+        }
+    }
+    sentences.push(&line[start..]);
+    sentences
+        .into_iter()
+        .map(|sentence| translate_line(sentence, 0).unwrap_or_else(|| sentence.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Argumentos da linha, se ela segue o modelo. Cada trecho fixo é procurado na primeira
+// ocorrência depois do anterior, e o último precisa fechar a linha.
+fn match_template<'a>(template: &str, line: &'a str) -> Option<Vec<&'a str>> {
+    let mut pieces = template.split("{}");
+    let first = pieces.next().unwrap_or("");
+    let mut rest = line.strip_prefix(first)?;
+    let pieces: Vec<&str> = pieces.collect();
+    let mut args = Vec::with_capacity(pieces.len());
+    for (index, piece) in pieces.iter().enumerate() {
+        if index + 1 == pieces.len() {
+            let arg = rest.strip_suffix(piece)?;
+            if arg.is_empty() {
+                return None;
+            }
+            args.push(arg);
+            return Some(args);
+        }
+        let end = rest.find(piece).filter(|end| *end > 0)?;
+        args.push(&rest[..end]);
+        rest = &rest[end + piece.len()..];
+    }
+    // Modelo sem argumentos: só vale a linha idêntica
+    rest.is_empty().then_some(args)
+}
+
+fn fill_template(template: &str, args: &[String]) -> String {
+    let mut filled = String::with_capacity(template.len() + args.iter().map(String::len).sum::<usize>());
+    let mut args = args.iter();
+    let mut pieces = template.split("{}");
+    filled.push_str(pieces.next().unwrap_or(""));
+    for piece in pieces {
+        filled.push_str(args.next().map_or("", String::as_str));
+        filled.push_str(piece);
+    }
+    filled
+}
+
+const ADMIN_HELP_EN: &str = "Administration console:\n  rooms                  lists the open rooms\n  players                lists the connected players and where they are\n  kick <nickname>        drops the player's connection\n  ban <nickname|ip> [duration]\n                         bans the nickname or the address, forever or for 30s, 15m, 2h, 7d\n  unban <nickname|ip>    lifts the ban\n  mute <nickname> [duration]\n                         forbids the player from using the chat\n  unmute <nickname>      gives the chat back to the player\n  bans                   lists the bans and mutes in force\n  close <room>           closes the room and disconnects everyone in it\n  announce <message>     sends a notice to every connection\n  tournament [lig4|reversi]\n                         opens registration for a tournament now\n  fairplay [game]        lists the fair play reports of rated games, or shows one game's report\n  motd reload            reloads the message of the day file\n  reload                 reloads the configuration without dropping anyone, like SIGHUP\n  stats                  shows the server numbers\n  help                   shows this help\n  quit                   leaves the console\n";
+
+// Português e inglês. Modelos mais específicos vêm antes dos mais gerais que também
+// os aceitariam.
+const CATALOG: &[(&str, &str)] = &[
+    // Ajudas, traduzidas inteiras; as do lobby e da sala vêm da tabela de comandos
+    (crate::admin::ADMIN_HELP, ADMIN_HELP_EN),
+    // Conexão e lobby
+    ("Aspas sem fechar na linha.", "Unclosed quotes in the line."),
+    ("O comando {} é ambíguo: {}.", "The command {} is ambiguous: {}."),
+    ("Comando desconhecido: {}. Digite help para ver os comandos.", "Unknown command: {}. Type help to see the commands."),
+    ("Não há ajuda para {}. Digite help para ver os comandos.", "There is no help for {}. Type help to see the commands."),
+    ("Jogadas só valem dentro de uma sala. Entre em uma com /join <nome>, /queue ou /solo.", "Moves only count inside a room. Join one with /join <name>, /queue or /solo."),
+    ("Servidor encerrando. Partidas em andamento foram salvas. Até logo!", "Server shutting down. Games in progress were saved. Goodbye!"),
+    ("Conexão encerrada por inatividade.", "Connection closed for inactivity."),
+    ("Sua sessão continuou em outra conexão. Esta será encerrada.", "Your session continued on another connection. This one will be closed."),
+    ("A outra conexão com este apelido ainda não saiu. Tente o RESUME de novo.", "The other connection with this nickname has not left yet. Try RESUME again."),
+    ("Conexão encerrada por um administrador.", "Connection closed by an administrator."),
+    ("Mensagens demais em pouco tempo. Conexão encerrada.", "Too many messages in a short time. Connection closed."),
+    ("Linha longa demais (máximo de {} bytes); ela foi ignorada.", "Line too long (at most {} bytes); it was ignored."),
+    ("Linha com texto inválido; use UTF-8. Ela foi ignorada.", "Line with invalid text; use UTF-8. It was ignored."),
+    ("Escolha um apelido primeiro com NICK <apelido> [senha].", "Choose a nickname first with NICK <nickname> [password]."),
+    ("Olá, {}!", "Hello, {}!"),
+    ("Bem-vindo de volta, {}! A partida na sala '{}' continua.", "Welcome back, {}! The game in room '{}' continues."),
+    ("Senha de administração incorreta.", "Wrong administration password."),
+    ("A administração remota está desativada neste servidor.", "Remote administration is disabled on this server."),
+    ("Versão de protocolo v{} não suportada. Este servidor fala v{} a v{}.", "Protocol version v{} is not supported. This server speaks v{} to v{}."),
+    ("Use o formato: HELLO v1 caps=json,color,utf8 lang=pt", "Use the format: HELLO v1 caps=json,color,utf8 lang=en"),
+    ("Modo JSON ativado.", "JSON mode enabled."),
+    ("Modo texto ativado.", "Text mode enabled."),
+    ("Formato desconhecido. Use MODE json ou MODE text.", "Unknown format. Use MODE json or MODE text."),
+    ("Cores ativadas.", "Colors enabled."),
+    ("Cores desativadas.", "Colors disabled."),
+    ("Use COLOR on ou COLOR off.", "Use COLOR on or COLOR off."),
+    ("Idioma alterado para português.", "Language set to Portuguese."),
+    ("Idioma alterado para inglês.", "Language set to English."),
+    ("Idioma desconhecido. Use LANG pt ou LANG en.", "Unknown language. Use LANG pt or LANG en."),
+    ("Modo '{}' desconhecido. Use lig4 ou reversi.", "Unknown mode '{}'. Use lig4 or reversi."),
+    ("Modo '{}' desconhecido. Use lig4, reversi ou anel.", "Unknown mode '{}'. Use lig4, reversi or anel."),
+    ("O modo anel aceita de {} a {} jogadores.", "Ring mode takes {} to {} players."),
+    ("Só o modo anel aceita o número de jogadores.", "Only ring mode takes a number of players."),
+    ("Modo anel, experimental: a partida começa com {} jogadores e vence quem fizer {} em linha primeiro.", "Ring mode, experimental: the game starts with {} players and the first to get {} in a row wins."),
+    ("O modo anel usa o tabuleiro padrão dele e não joga séries.", "Ring mode uses its own default board and does not play series."),
+    ("O modo anel não tem fila. Crie uma sala com /create <nome> anel.", "Ring mode has no queue. Create a room with /create <name> anel."),
+    ("Sala '{}' criada contra o computador ({}). Você é o X. Cada jogada tem limite de {}s.", "Room '{}' created against the computer ({}). You are X. Each move has a {}s limit."),
+    ("Sala '{}' criada. Você é o X. Cada jogada tem limite de {}s.", "Room '{}' created. You are X. Each move has a {}s limit."),
+    ("Sala '{}' criada. Você é o X. Cada jogador tem {} min no relógio, com {}s de acréscimo por jogada.", "Room '{}' created. You are X. Each player has {} min on the clock, with a {}s increment per move."),
+    ("O relógio de xadrez não vale no modo anel.", "The chess clock does not apply to ring mode."),
+    ("A sala joga uma série melhor de {}.", "The room plays a best-of-{} series."),
+    ("A sala usa a regra da troca: o O pode responder à primeira jogada com /swap.", "The room uses the swap rule: O may answer the first move with /swap."),
+    ("A regra da troca só vale no lig4.", "The swap rule only applies to lig4."),
+    ("A sala joga o Pop Out: na sua vez, /pop <coluna> retira uma peça sua da base.", "The room plays Pop Out: on your turn, /pop <column> removes your own piece from the bottom."),
+    ("A variante Pop Out só vale no lig4.", "The Pop Out variant only applies to lig4."),
+    ("A sala joga o Power Up: cada jogador tem um coringa por partida, jogado com /wild <coluna>.", "The room plays Power Up: each player has one wildcard per game, played with /wild <column>."),
+    ("A variante Power Up só vale no lig4.", "The Power Up variant only applies to lig4."),
+    ("Use o formato: /create <nome> <linhas> <colunas> <sequência> (ex: /create grande 8 9 5)", "Use the format: /create <name> <rows> <columns> <line length> (e.g. /create big 8 9 5)"),
+    ("Use /history <jogador> ou escolha um apelido com NICK.", "Use /history <player> or choose a nickname with NICK."),
+    ("Use o formato: /export <número da partida>", "Use the format: /export <game number>"),
+    ("Use /export <número> ou escolha um apelido com NICK.", "Use /export <number> or choose a nickname with NICK."),
+    ("Use o formato: /replay <número da partida>", "Use the format: /replay <game number>"),
+    ("Você entrou na sala '{}'. Cada jogada tem limite de {}s.", "You joined room '{}'. Each move has a {}s limit."),
+    ("Você entrou na sala '{}'. Cada jogador tem {} min no relógio, com {}s de acréscimo por jogada.", "You joined room '{}'. Each player has {} min on the clock, with a {}s increment per move."),
+    ("Procurando oponente... Use /cancel para sair da fila.", "Looking for an opponent... Use /cancel to leave the queue."),
+    ("Aguardando oponente. Use /cancel para desistir da espera.", "Waiting for an opponent. Use /cancel to stop waiting."),
+    ("Você saiu da fila.", "You left the queue."),
+    ("Oponente encontrado: {}. Sala '{}', você é o {}. Cada jogada tem limite de {}s.", "Opponent found: {}. Room '{}', you are {}. Each move has a {}s limit."),
+    ("Nível desconhecido. Use /solo easy, medium, hard ou impossible.", "Unknown level. Use /solo easy, medium, hard or impossible."),
+    ("Você está assistindo a sala '{}'.", "You are watching room '{}'."),
+    ("[Aviso do servidor] {}", "[Server notice] {}"),
+    ("Código de reconexão: {}. Se a conexão cair, use RESUME {} em até {}s.", "Reconnection code: {}. If the connection drops, use RESUME {} within {}s."),
+    ("O apelido '{}' já está em uso.", "The nickname '{}' is already in use."),
+    ("O apelido '{}' é protegido por senha. Use NICK {} <senha>.", "The nickname '{}' is password protected. Use NICK {} <password>."),
+    ("Não foi possível acessar as contas.", "Could not access the accounts."),
+    ("Senha incorreta.", "Wrong password."),
+    ("Senha de administração: ", "Administration password: "),
+    ("Senha de {}: ", "Password for {}: "),
+    ("Não foi possível registrar o apelido.", "Could not register the nickname."),
+    ("Apelido inválido. Use de 2 a 16 letras, números, _ ou -.", "Invalid nickname. Use 2 to 16 letters, digits, _ or -."),
+    ("Código de reconexão inválido ou expirado.", "Invalid or expired reconnection code."),
+    ("Já existe uma sala chamada '{}'.", "There is already a room called '{}'."),
+    ("Sala '{}' já está cheia. Use /watch {} para assistir.", "Room '{}' is already full. Use /watch {} to watch."),
+    ("Sala '{}' não encontrada. Use /list para ver as salas.", "Room '{}' not found. Use /list to see the rooms."),
+    ("O servidor atingiu o limite de {} salas. Tente novamente mais tarde.", "The server reached its limit of {} rooms. Try again later."),
+    ("Você já está na fila.", "You are already in the queue."),
+    ("computador ({})", "computer ({})"),
+    ("multidão", "the crowd"),
+    ("fácil", "easy"),
+    ("médio", "medium"),
+    ("difícil", "hard"),
+    ("impossível", "impossible"),
+    // Histórico, ranking e replay
+    ("O histórico de partidas está desativado neste servidor.", "Game history is disabled on this server."),
+    ("Não foi possível ler o histórico de partidas.", "Could not read the game history."),
+    ("O ranking está desativado neste servidor.", "The ranking is disabled on this server."),
+    ("Não foi possível ler o ranking.", "Could not read the ranking."),
+    ("Partida #{} não encontrada. Use /history para ver as partidas.", "Game #{} not found. Use /history to see the games."),
+    ("Nenhuma partida encontrada para {}.", "No games found for {}."),
+    ("Últimas partidas de {}:", "Latest games of {}:"),
+    ("  #{} [{}] X: {} / O: {}, {}, {} jogadas em {}m{}s", "  #{} [{}] X: {} / O: {}, {}, {} moves in {}m{}s"),
+    ("{} (série melhor de {}: {} x {})", "{} (best-of-{} series: {} x {})"),
+    ("venceu X por W.O.", "X won by forfeit"),
+    ("venceu O por W.O.", "O won by forfeit"),
+    ("venceu X", "X won"),
+    ("venceu O", "O won"),
+    ("empate", "draw"),
+    ("Ninguém no ranking ainda. Termine uma partida entre jogadores com apelido.", "Nobody in the ranking yet. Finish a game between players with nicknames."),
+    ("{} ({} partidas)", "{} ({} games)"),
+    ("Partida #{}: jogada {}/{} ({}).", "Game #{}: move {}/{} ({})."),
+    ("Partida #{}: jogada {}/{}.", "Game #{}: move {}/{}."),
+    ("Vitória do X.", "X wins."),
+    ("Vitória do O.", "O wins."),
+    ("Empate.", "Draw."),
+    ("n = próxima, p = anterior, q = sair", "n = next, p = previous, q = quit"),
+    ("Use n para avançar, p para voltar ou q para sair.", "Use n to go forward, p to go back or q to quit."),
+    // Lista de salas
+    ("Nenhuma sala aberta. Use /create <nome> para criar uma.", "No open rooms. Use /create <name> to create one."),
+    ("Salas abertas:", "Open rooms:"),
+    ("  {} [{}] ({}/{}, {}) {} +{} assistindo", "  {} [{}] ({}/{}, {}) {} +{} watching"),
+    ("  {} [{}] ({}/{}, {}) {}", "  {} [{}] ({}/{}, {}) {}"),
+    ("{}, melhor de {}: {} x {}", "{}, best of {}: {} x {}"),
+    ("{} {}x{}, {} em linha", "{} {}x{}, {} in a row"),
+    ("{}, com troca", "{}, with swap"),
+    ("{}, pop out", "{}, pop out"),
+    ("{}, com coringa", "{}, with wildcard"),
+    ("{}, relógio {}", "{}, clock {}"),
+    ("{}, ranqueada", "{}, rated"),
+    ("{}, vantagem: {}", "{}, handicap: {}"),
+    ("{}, chat sem filtro", "{}, unfiltered chat"),
+    ("{}, chat com filtro", "{}, filtered chat"),
+    ("{}, chat com filtro rigoroso", "{}, strictly filtered chat"),
+    ("bloqueio de 1 coluna", "1 column blocked"),
+    ("bloqueio de {} colunas", "{} columns blocked"),
+    ("jogada extra", "extra move"),
+    ("{}, exibição com {}s entre jogadas", "{}, exhibition with {}s between moves"),
+    ("{} de {} jogadores", "{} for {} players"),
+    ("aguardando", "waiting"),
+    ("em andamento", "playing"),
+    ("pausada", "paused"),
+    ("em pausa", "on hold"),
+    ("encerrada", "finished"),
+    // Sala e partida
+    ("Oponente conectado: {}. Você é o {} e começa.", "Opponent connected: {}. You are {} and go first."),
+    ("Oponente conectado: {}. Você é o {}; o {} começa.", "Opponent connected: {}. You are {}; {} goes first."),
+    ("Quem começa foi sorteado (semente {}).", "The first player was drawn at random (seed {})."),
+    ("Partida iniciada: {} (X) contra {} (O). O {} começa.", "Game started: {} (X) against {} (O). {} goes first."),
+    ("Partida iniciada: {}. O {} começa.", "Game started: {}. {} goes first."),
+    ("Partida iniciada: {}. Você é o {} e começa.", "Game started: {}. You are {} and go first."),
+    ("Partida iniciada: {}. Você é o {}; o {} começa.", "Game started: {}. You are {}; {} goes first."),
+    ("{} entrou como {}. Aguardando jogadores ({}/{}).", "{} joined as {}. Waiting for players ({}/{})."),
+    ("Aguardando jogadores ({}/{})...", "Waiting for players ({}/{})..."),
+    ("Vez do {}...", "{}'s turn..."),
+    ("Série melhor de {}: vence quem ganhar {} jogos.", "Best-of-{} series: the first to win {} games wins."),
+    ("Aguardando segundo jogador...", "Waiting for the second player..."),
+    ("Aguardando oponente...", "Waiting for the opponent..."),
+    ("Sua vez, escolha a coluna (1-{}):", "Your turn, choose the column (1-{}):"),
+    ("Sua vez, escolha a casa (linha coluna):", "Your turn, choose the square (row column):"),
+    ("Não é seu turno. Aguardando oponente...", "It is not your turn. Waiting for the opponent..."),
+    ("A partida ainda não começou. Aguardando oponente...", "The game has not started yet. Waiting for the opponent..."),
+    ("Jogada inválida nesta sala.", "Invalid move in this room."),
+    ("Jogada proposta. Confirme com /confirm ou desista com /cancel.", "Proposed move. Confirm with /confirm or drop it with /cancel."),
+    ("Não há jogada para confirmar.", "There is no move to confirm."),
+    ("Não há jogada para cancelar.", "There is no move to cancel."),
+    ("Jogada cancelada.", "Move cancelled."),
+    ("Jogada JSON inválida. Use o formato: {\"col\": 0}", "Invalid JSON move. Use the format: {\"col\": 0}"),
+    ("Jogada JSON inválida. Use o formato: {\"row\": 2, \"col\": 3}", "Invalid JSON move. Use the format: {\"row\": 2, \"col\": 3}"),
+    ("Entrada não reconhecida. Digite o número da coluna (ex: 4) ou help para ver os comandos.", "Unrecognized input. Type the column number (e.g. 4) or help to see the commands."),
+    ("Entrada não reconhecida. Digite linha e coluna (ex: 3 4) ou help para ver os comandos.", "Unrecognized input. Type row and column (e.g. 3 4) or help to see the commands."),
+    ("Casa inválida. Use o formato: linha coluna, a partir de 1 (ex: 3 4)", "Invalid square. Use the format: row column, starting at 1 (e.g. 3 4)"),
+    ("Tamanho inválido. Use de {} a {} linhas e de {} a {} colunas.", "Invalid size. Use {} to {} rows and {} to {} columns."),
+    ("Sequência inválida. Use de {} a {} peças em linha.", "Invalid line length. Use {} to {} pieces in a row."),
+    ("Coluna inválida. Escolha de 1 a {}.", "Invalid column. Choose from 1 to {}."),
+    ("Coluna cheia. Escolha outra coluna.", "Column full. Choose another column."),
+    ("Jogada inválida. Escolha uma casa vazia que vire peças do adversário.", "Invalid move. Choose an empty square that flips opponent pieces."),
+    ("Jogada inválida. No reversi informe linha e coluna.", "Invalid move. In reversi give the row and column."),
+    ("Jogada inválida. No lig4 informe apenas a coluna.", "Invalid move. In lig4 give only the column."),
+    ("O jogador {} não tem jogadas e passa a vez.", "Player {} has no moves and passes."),
+    ("Espectadores não podem jogar. Digite help para ver os comandos.", "Spectators cannot play. Type help to see the commands."),
+    ("Sua conexão não acompanhou a sala e foi encerrada.", "Your connection could not keep up with the room and was closed."),
+    ("Uso: /say <mensagem>", "Usage: /say <message>"),
+    ("Tempo esgotado! O {} perdeu por tempo.", "Time is up! {} lost on time."),
+    ("O {} não voltou a tempo e perdeu por W.O.", "{} did not come back in time and lost by forfeit."),
+    ("Oponente desconectou: {}.", "Opponent disconnected: {}."),
+    ("Oponente saiu da sala: {}.", "Opponent left the room: {}."),
+    ("O {} perdeu a conexão. A partida está pausada; ele tem {}s para voltar.", "{} lost the connection. The game is paused; they have {}s to come back."),
+    ("Não há partida pausada esperando por você.", "There is no paused game waiting for you."),
+    ("O {} voltou. A partida continua.", "{} is back. The game continues."),
+    ("A partida está pausada até o oponente voltar.", "The game is paused until the opponent comes back."),
+    ("O {} saiu da sala; a partida continua pausada.", "{} left the room; the game stays paused."),
+    ("O {} voltou à sala. A partida está pausada; use /resume para continuar.", "{} is back in the room. The game is paused; use /resume to continue."),
+    ("Só é possível pausar durante a partida.", "You can only pause during the game."),
+    ("Partidas contra o computador não podem ser pausadas.", "Games against the computer cannot be paused."),
+    ("Este servidor não guarda partidas pausadas.", "This server does not keep paused games."),
+    ("Você já pediu a pausa. Aguarde o oponente concordar.", "You already asked for a break. Wait for the opponent to agree."),
+    ("O {} pediu uma pausa. Digite /pause para concordar.", "{} asked for a break. Type /pause to agree."),
+    ("Partida pausada. O relógio parou; use /resume para continuar. Se sair, volte depois com o código de reconexão.", "Game paused. The clock stopped; use /resume to continue. If you leave, come back later with the reconnection code."),
+    ("A partida não está pausada.", "The game is not paused."),
+    ("O oponente ainda não voltou à sala.", "The opponent has not come back to the room yet."),
+    ("O {} retomou a partida.", "{} resumed the game."),
+    ("A partida está pausada. Use /resume para continuar.", "The game is paused. Use /resume to continue."),
+    ("Esta sala não usa a regra da troca.", "This room does not use the swap rule."),
+    ("A troca só vale como resposta à primeira jogada da partida.", "The swap is only allowed as the answer to the first move of the game."),
+    ("Troca! {} fica com o X e a primeira jogada; {} passa a ser o O e joga agora.", "Swap! {} takes X and the first move; {} becomes O and plays now."),
+    ("Ou digite /swap para ficar com o lado do adversário e a jogada dele.", "Or type /swap to take over the opponent's side and their move."),
+    ("Ou digite /pop <coluna> para retirar uma peça sua da base.", "Or type /pop <column> to remove your own piece from the bottom."),
+    ("Esta sala não usa a variante Pop Out.", "This room does not use the Pop Out variant."),
+    ("Só é possível retirar uma peça sua da base da coluna.", "You can only remove your own piece from the bottom of the column."),
+    ("Uso: /pop <coluna>", "Usage: /pop <column>"),
+    ("Ou digite /wild <coluna> para jogar o seu coringa.", "Or type /wild <column> to play your wildcard."),
+    ("Esta sala não usa a variante Power Up.", "This room does not use the Power Up variant."),
+    ("Você já usou o seu coringa nesta partida.", "You have already used your wildcard in this game."),
+    ("Partidas do modo anel não podem ser pausadas.", "Ring mode games cannot be paused."),
+    ("Só é possível pedir dicas durante a partida.", "Hints can only be requested during the game."),
+    ("As dicas só estão disponíveis no lig4.", "Hints are only available in lig4."),
+    ("Só é possível pedir dicas na sua vez.", "Hints can only be requested on your turn."),
+    ("Não há dicas em partidas ranqueadas.", "There are no hints in rated games."),
+    ("Não é possível desfazer jogadas em partidas ranqueadas.", "Moves cannot be undone in rated games."),
+    ("Partidas ranqueadas não podem ser pausadas.", "Rated games cannot be paused."),
+    ("Partidas de torneio não podem ser pausadas.", "Tournament games cannot be paused."),
+    ("Não há dicas em partidas de torneio.", "There are no hints in tournament games."),
+    ("Não é possível desfazer jogadas em partidas de torneio.", "Moves cannot be undone in tournament games."),
+    ("Partidas de torneio não podem ser anuladas. Para sair da partida, use resign.", "Tournament games cannot be aborted. To leave the game, use resign."),
+    ("Partidas do modo anel não valem para o ranking.", "Ring mode games do not count for the ranking."),
+    ("A sala é privada e não aparece no /list. Passe o código {} para quem vai jogar: /join code {}", "The room is private and does not show up in /list. Give the code {} to your opponent: /join code {}"),
+    ("A sala '{}' é privada. Entre com /join code <código>.", "Room '{}' is private. Join with /join code <code>."),
+    ("Nenhuma sala com o código {}.", "No room with the code {}."),
+    ("A partida é ranqueada: o resultado muda o ELO e não há /undo, /hint nem /pause.", "The game is rated: the result changes ELO and there is no /undo, /hint or /pause."),
+    ("Você dá vantagem: a coluna do meio fica bloqueada para você nas suas primeiras {} jogadas.", "You give a handicap: the middle column is blocked for you during your first {} moves."),
+    ("Você dá vantagem: as duas colunas do meio ficam bloqueadas para você nas suas primeiras {} jogadas.", "You give a handicap: the two middle columns are blocked for you during your first {} moves."),
+    ("Você dá vantagem: quem entrar faz duas jogadas seguidas na primeira vez dele.", "You give a handicap: whoever joins makes two moves in a row on their first turn."),
+    ("Vantagem: {} ({}) não joga na coluna {} nas primeiras {} jogadas dele.", "Handicap: {} ({}) cannot play in column {} during their first {} moves."),
+    ("Vantagem: {} ({}) não joga nas colunas {} nas primeiras {} jogadas dele.", "Handicap: {} ({}) cannot play in columns {} during their first {} moves."),
+    ("Vantagem: {} ({}) faz duas jogadas seguidas na primeira vez dele.", "Handicap: {} ({}) makes two moves in a row on their first turn."),
+    ("A coluna {} está bloqueada para você nas suas primeiras {} jogadas.", "Column {} is blocked for you during your first {} moves."),
+    ("Colunas bloqueadas pela vantagem: {}.", "Columns blocked by the handicap: {}."),
+    ("A vantagem combinada só vale no lig4.", "Handicaps only apply to lig4."),
+    ("As duplas valem no lig4 e no reversi.", "Team rooms are available for lig4 and reversi."),
+    ("As duplas não valem junto com a regra da troca nem com a vantagem combinada.", "Team rooms cannot be combined with the swap rule or a handicap."),
+    ("A sala é de duplas: {} jogadores, dois de cada lado, e os parceiros se alternam nas jogadas.", "This is a team room: {} players, two per side, and teammates alternate moves."),
+    ("Partida em duplas: {} (X) contra {} (O). O {} começa.", "Team game: {} (X) against {} (O). {} goes first."),
+    ("Você joga pelo {}, alternando as jogadas com o parceiro. /team <mensagem> fala só com ele.", "You play for {}, alternating moves with your teammate. /team <message> talks to them only."),
+    ("Esta jogada do seu lado é do seu parceiro, {}.", "This move for your side belongs to your teammate, {}."),
+    ("Vez do seu parceiro, {}...", "Your teammate's turn, {}..."),
+    ("Partidas em duplas não podem ser pausadas.", "Team games cannot be paused."),
+    ("O chat da dupla só vale para quem joga em uma sala de duplas.", "Team chat is only for players in a team room."),
+    ("Uso: /team <mensagem>", "Usage: /team <message>"),
+    ("Use o formato: /crowd [nome] easy|medium|hard|impossible [segundos] (ex: /crowd torcida hard 20)", "Use the format: /crowd [name] easy|medium|hard|impossible [seconds] (e.g. /crowd fans hard 20)"),
+    ("Sala '{}' criada: a multidão contra o computador ({}). Chame mais gente com /watch {}.", "Room '{}' created: the crowd against the computer ({}). Bring more people with /watch {}."),
+    ("Quem assiste vota nas jogadas do {}: a cada vez, a coluna mais votada em {}s é jogada, e no empate vale a mais perto do centro.", "Spectators vote on {}'s moves: each turn, the column with the most votes in {}s is played, and ties go to the one closest to the center."),
+    ("{}, multidão contra o computador, votação de {}s", "{}, crowd against the computer, {}s votes"),
+    ("Vez da multidão, vote na coluna (1-{}):", "The crowd's turn, vote for a column (1-{}):"),
+    ("Votação aberta: digite a coluna (1-{}) da próxima jogada. Ela fecha em {}s.", "Voting is open: type the column (1-{}) for the next move. It closes in {}s."),
+    ("Faltam {}s para fechar a votação. {}", "{}s left to vote. {}"),
+    ("Votos por coluna: {}.", "Votes per column: {}."),
+    ("Ainda não há votos.", "No votes yet."),
+    ("Ninguém votou; a votação recomeça.", "Nobody voted; voting starts again."),
+    ("Votação encerrada: a multidão joga na coluna {} ({} de {} votos).", "Voting closed: the crowd plays column {} ({} of {} votes)."),
+    ("Voto registrado: coluna {}.", "Vote recorded: column {}."),
+    ("Use o formato: /vote <coluna>", "Use the format: /vote <column>"),
+    ("Esta sala não tem votação.", "This room has no voting."),
+    ("A votação abre na vez da multidão.", "Voting opens on the crowd's turn."),
+    ("Coluna inválida ou cheia. Vote em outra.", "Invalid or full column. Vote for another one."),
+    ("A vantagem combinada não vale junto com a regra da troca.", "Handicaps cannot be combined with the swap rule."),
+    ("Você já usou as {} dicas desta partida.", "You have already used the {} hints of this game."),
+    ("Dica: jogue na coluna {}. Dicas restantes nesta partida: {}.", "Hint: play in column {}. Hints left in this game: {}."),
+    ("Não há jogadas para sugerir.", "There are no moves to suggest."),
+    ("Destaque de ameaças ativado: ! vence para você, # vence para o oponente.", "Threat highlighting on: ! wins for you, # wins for the opponent."),
+    ("Destaque de ameaças desativado.", "Threat highlighting off."),
+    ("Aviso sonoro ativado: toca quando for sua vez e quando o oponente entrar.", "Bell on: it rings when it is your turn and when the opponent joins."),
+    ("Aviso sonoro desativado.", "Bell off."),
+    ("Use /bell on ou /bell off.", "Use /bell on or /bell off."),
+    ("Use o formato: /exhibition [nome] <X> <O> [segundos], com easy, medium, hard, impossible ou bot em cada lugar (ex: /exhibition demo hard easy 2)", "Use the format: /exhibition [name] <X> <O> [seconds], with easy, medium, hard, impossible or bot in each seat (e.g. /exhibition demo hard easy 2)"),
+    ("Sala de exibição '{}' criada, com {}s entre as jogadas. Você está assistindo.", "Exhibition room '{}' created, with {}s between moves. You are watching."),
+    ("A sala '{}' é uma exibição: só bots entram para jogar. Use /watch {} para assistir.", "Room '{}' is an exhibition: only bots can join to play. Use /watch {} to watch."),
+    ("Sua jogada já está guardada; ela vale quando o atraso da sala passar.", "Your move is already held; it counts once the room's delay has passed."),
+    ("Quebra-cabeças de {}: {} resolvidos em {} tentativas, {} de {} diferentes.", "Puzzles of {}: {} solved in {} attempts, {} of {} different ones."),
+    ("Use /puzzle, /puzzle <número de 1 a {}> ou /puzzle stats [jogador].", "Use /puzzle, /puzzle <number from 1 to {}> or /puzzle stats [player]."),
+    ("{}: {} joga e vence em {}. Digite a coluna de cada jogada, ou q para desistir.", "{}: {} to play and win in {}. Type the column of each move, or q to give up."),
+    ("Quebra-cabeça #{}", "Puzzle #{}"),
+    ("Desafio do dia {}", "Daily challenge {}"),
+    ("Você desistiu. Uma solução é jogar na coluna {}.", "You gave up. One solution is to play in column {}."),
+    ("Resolvido! Você venceu em {} jogadas.", "Solved! You won in {} moves."),
+    ("Você já resolveu o desafio de hoje, em {} tentativas e {}. Veja /daily top.", "You have already solved today's challenge, in {} attempts and {}. See /daily top."),
+    ("Desafio do dia resolvido na tentativa {}, em {}. Veja /daily top.", "Daily challenge solved on attempt {}, in {}. See /daily top."),
+    ("Ninguém resolveu o desafio de hoje ainda. Tente com /daily.", "Nobody has solved today's challenge yet. Try it with /daily."),
+    ("Desafio do dia {}:", "Daily challenge {}:"),
+    ("  {}. {} {}  ({} tentativas)", "  {}. {} {}  ({} attempts)"),
+    ("O ranking do desafio diário está desativado neste servidor.", "The daily challenge ranking is disabled on this server."),
+    ("Não foi possível ler o ranking do desafio diário.", "Could not read the daily challenge ranking."),
+    ("Essa jogada deixa a vitória escapar. Uma solução era jogar na coluna {}.", "That move lets the win slip away. One solution was to play in column {}."),
+    ("O adversário respondeu na coluna {}. Você ainda vence em {}.", "The opponent answered in column {}. You still win in {}."),
+    ("As estatísticas de quebra-cabeças estão desativadas neste servidor.", "Puzzle statistics are disabled on this server."),
+    ("Não foi possível ler as estatísticas de quebra-cabeças.", "Could not read the puzzle statistics."),
+    ("A avaliação só está disponível no lig4.", "Evaluation is only available in lig4."),
+    ("A avaliação só vale no lig4 padrão, de 7x6 e sem variantes.", "Evaluation only works in standard 7x6 lig4 without variants."),
+    ("Durante a partida, o /eval é só para quem assiste. Os jogadores podem usá-lo depois do fim.", "During the game, /eval is for spectators only. Players can use it after the game ends."),
+    ("Resolvendo a posição; pode levar até {}s.", "Solving the position; this may take up to {}s."),
+    ("A posição ainda está aberta demais para resolver em {}s.", "The position is still too open to solve in {}s."),
+    ("Com o melhor jogo dos dois lados, {}. A jogada que mantém o resultado é a coluna {}.", "With perfect play from both sides, {}. The move that keeps this result is column {}."),
+    ("o X vence", "X wins"),
+    ("o O vence", "O wins"),
+    ("a partida empata", "the game is a draw"),
+    ("vitória do {}", "win for {}"),
+    ("Análise da partida, com o melhor jogo a partir de cada posição:", "Game analysis, with perfect play from each position:"),
+    ("  Jogada {}, {} na coluna {}: de {} para {}.", "  Move {}, {} in column {}: from {} to {}."),
+    ("  Nenhuma jogada mudou o resultado teórico: {}.", "  No move changed the theoretical result: {}."),
+    ("  As primeiras {} jogadas ficaram de fora: as posições ainda estavam abertas demais para resolver em {}s.", "  The first {} moves were left out: the positions were still too open to solve in {}s."),
+    ("Use /threats on ou /threats off.", "Use /threats on or /threats off."),
+    ("Não é possível desfazer jogadas no modo anel.", "Moves cannot be undone in ring mode."),
+    ("Uso: /wild <coluna>", "Usage: /wild <column>"),
+    ("Nenhuma partida em andamento para assistir.", "No games in progress to watch."),
+    ("TV: sala '{}'. Digite n para a próxima partida ou q para sair.", "TV: room '{}'. Type n for the next game or q to leave."),
+    ("Use n para a próxima partida ou q para sair.", "Use n for the next game or q to leave."),
+    ("Não há partida em andamento para desistir.", "There is no game in progress to resign."),
+    ("O {} desistiu da partida.", "{} resigned the game."),
+    ("A partida já terminou.", "The game is already over."),
+    ("Só é possível desfazer jogadas durante a partida.", "Moves can only be undone during the game."),
+    ("Você ainda não tem jogadas para desfazer.", "You have no moves to undo yet."),
+    ("Já existe um pedido para desfazer aguardando resposta.", "There is already an undo request waiting for an answer."),
+    ("O {} pediu para desfazer a última jogada. Responda com /accept ou /decline.", "{} asked to undo their last move. Answer with /accept or /decline."),
+    ("Não há pedido nem proposta aguardando sua resposta.", "There is no request or offer waiting for your answer."),
+    ("O pedido para desfazer foi recusado.", "The undo request was declined."),
+    ("Só é possível anular a partida enquanto ela está em andamento.", "The game can only be aborted while it is in progress."),
+    ("Não é possível anular a partida no modo anel.", "Games cannot be aborted in ring mode."),
+    ("O {} anulou a partida. Ela não vale para o histórico.", "{} aborted the game. It does not count for the history."),
+    ("Partida anulada de comum acordo. Ela não vale para o histórico.", "Game aborted by mutual agreement. It does not count for the history."),
+    ("Você já pediu para anular a partida. Aguarde a resposta do oponente.", "You already asked to abort the game. Wait for the opponent's answer."),
+    ("O {} pediu para anular a partida, sem resultado. Responda com /accept ou /decline.", "{} asked to abort the game, with no result. Answer with /accept or /decline."),
+    ("O pedido para anular a partida foi recusado.", "The abort request was declined."),
+    ("Só é possível propor empate durante a partida.", "A draw can only be offered during the game."),
+    ("Não é possível propor empate no modo anel.", "Draws cannot be offered in ring mode."),
+    ("O computador não aceita empate.", "The computer does not accept draws."),
+    ("Você já propôs empate. Aguarde a resposta do oponente.", "You already offered a draw. Wait for the opponent's answer."),
+    ("O {} propõe empate. Responda com /accept ou /decline; a proposta vale até a próxima jogada.", "{} offers a draw. Answer with /accept or /decline; the offer stands until the next move."),
+    ("Empate de comum acordo.", "Draw by mutual agreement."),
+    ("A proposta de empate deixou de valer com a nova jogada.", "The draw offer lapsed with the new move."),
+    ("O {} recusou o empate.", "{} declined the draw."),
+    ("De volta ao lobby. Digite help para ver os comandos.", "Back in the lobby. Type help to see the commands."),
+    ("Jogada desfeita.", "Move undone."),
+    ("{} Série melhor de {}: {}. Próximo jogo: o {} começa.", "{} Best-of-{} series: {}. Next game: {} goes first."),
+    ("{} venceu a série melhor de {}! Placar final: {}.", "{} won the best-of-{} series! Final score: {}."),
+    ("{} venceu o jogo.", "{} won the game."),
+    ("Jogo empatado.", "The game was a draw."),
+    ("Nova partida! O {} começa.", "New game! {} goes first."),
+    ("Jogar novamente? (s/n)", "Play again? (y/n)"),
+    ("Aguardando resposta do oponente...", "Waiting for the opponent's answer..."),
+    ("Responda s ou n.", "Answer y or n."),
+    ("A sala foi encerrada.", "The room was closed."),
+    ("Até logo!", "Goodbye!"),
+    ("Empate!", "Draw!"),
+    ("Você venceu!", "You won!"),
+    ("Você perdeu!", "You lost!"),
+    ("O {} venceu!", "{} won!"),
+
+    // Banimentos, silêncios e filtro de palavras (antes do console, cujos modelos também casariam)
+    ("O apelido '{}' está banido deste servidor.", "The nickname '{}' is banned from this server."),
+    ("O apelido '{}' está banido deste servidor por mais {}.", "The nickname '{}' is banned from this server for another {}."),
+    ("Você está silenciado e não pode usar o chat.", "You are muted and cannot use the chat."),
+    ("Você está silenciado por mais {} e não pode usar o chat.", "You are muted for another {} and cannot use the chat."),
+    ("O apelido '{}' não é permitido neste servidor. Escolha outro.", "The nickname '{}' is not allowed on this server. Choose another one."),
+    // Console de administração
+    ("{} foi desconectado.", "{} was disconnected."),
+    ("Sala '{}' fechada.", "Room '{}' closed."),
+    ("Aviso enviado para {} conexões.", "Notice sent to {} connections."),
+    ("Mensagem do dia recarregada: o arquivo está vazio, e ela foi retirada.", "Message of the day reloaded: the file is empty, so it was removed."),
+    ("Mensagem do dia recarregada ({} linhas).", "Message of the day reloaded ({} lines)."),
+    ("Não foi possível ler a mensagem do dia em {}: {}", "Could not read the message of the day from {}: {}"),
+    ("O servidor não tem um arquivo de mensagem do dia (motd_file).", "The server has no message of the day file (motd_file)."),
+    ("Mensagem do dia:", "Message of the day:"),
+    ("Configuração recarregada.", "Configuration reloaded."),
+    ("Só valem depois de reiniciar o servidor: {}.", "Only take effect after restarting the server: {}."),
+    ("Configuração inválida, nada foi mudado: {}", "Invalid configuration, nothing was changed: {}"),
+    ("O servidor está encerrando e não pode recarregar a configuração.", "The server is shutting down and cannot reload the configuration."),
+    ("Este servidor não recarrega a configuração.", "This server does not reload its configuration."),
+    ("Não há mensagem do dia.", "There is no message of the day."),
+    ("Jogadores conectados: {}", "Connected players: {}"),
+    ("Salas: {} ({} em andamento, {} aguardando, {} pausadas)", "Rooms: {} ({} playing, {} waiting, {} paused)"),
+    ("Na fila: {}", "In the queue: {}"),
+    ("No ar há {}h{}m{}s", "Up for {}h{}m{}s"),
+    ("Comando desconhecido ou incompleto. Digite help para ver os comandos.", "Unknown or incomplete command. Type help to see the commands."),
+    ("Nenhum jogador conectado.", "No players connected."),
+    ("Jogadores conectados:", "Connected players:"),
+    ("  {} (sala {})", "  {} (room {})"),
+    ("  {} (no lobby)", "  {} (in the lobby)"),
+    ("Duração inválida. Use, por exemplo, 30s, 15m, 2h ou 7d.", "Invalid duration. Use, for example, 30s, 15m, 2h or 7d."),
+    ("{} está banido por {}.", "{} is banned for {}."),
+    ("{} está banido para sempre.", "{} is banned forever."),
+    ("O banimento de {} foi retirado.", "The ban on {} was lifted."),
+    ("{} não está banido.", "{} is not banned."),
+    ("{} está silenciado por {}.", "{} is muted for {}."),
+    ("{} está silenciado para sempre.", "{} is muted forever."),
+    ("{} pode voltar a usar o chat.", "{} can use the chat again."),
+    ("{} não está silenciado.", "{} is not muted."),
+    ("Nenhuma punição em vigor.", "No bans or mutes in force."),
+    ("Punições em vigor:", "Bans and mutes in force:"),
+    ("  {}: {}, {}", "  {}: {}, {}"),
+    ("apelido banido", "banned nickname"),
+    ("endereço banido", "banned address"),
+    ("silenciado", "muted"),
+    ("falta {}", "{} left"),
+    ("para sempre", "forever"),
+
+    // Jogadores ausentes
+    ("O {} está ausente há {}s. Se não enviar nenhum comando em {}s, {}.", "{} has been away for {}s. Without any command in {}s, {}."),
+    ("perde por W.O.", "they lose by forfeit"),
+    ("a partida é cancelada", "the game is cancelled"),
+    ("O {} voltou.", "{} is back."),
+    ("O {} continuou ausente e perdeu por W.O.", "{} stayed away and lost by forfeit."),
+    ("O {} continuou ausente e a partida foi cancelada. Ela não vale para o histórico.", "{} stayed away and the game was cancelled. It does not count for the history."),
+
+    // Amigos e desafios
+    ("{} desafiou você para uma partida de {}. Responda com /accept {} ou /decline {}.", "{} challenged you to a game of {}. Answer with /accept {} or /decline {}."),
+    ("{} retirou o desafio.", "{} withdrew the challenge."),
+    ("Você não pode desafiar a si mesmo.", "You cannot challenge yourself."),
+    ("O modo anel não tem desafios. Crie uma sala com /create <nome> anel.", "Ring mode has no challenges. Create a room with /create <name> anel."),
+    ("{} não está conectado.", "{} is not connected."),
+    ("Você já tem um desafio aguardando resposta.", "You already have a challenge waiting for an answer."),
+    ("Nenhum desafio de {} aguardando resposta.", "No challenge from {} waiting for an answer."),
+    ("Desafio enviado a {}. Aguardando a resposta... Use /cancel para retirar o desafio.", "Challenge sent to {}. Waiting for the answer... Use /cancel to withdraw the challenge."),
+    ("Você retirou o desafio.", "You withdrew the challenge."),
+    ("{} não aceitou o desafio.", "{} did not accept the challenge."),
+    ("{} aceitou o desafio. Sala privada '{}', você é o X. Cada jogada tem limite de {}s.", "{} accepted the challenge. Private room '{}', you are X. Each move has a {}s limit."),
+    ("Você aceitou o desafio de {}. Sala privada '{}', você é o O. Cada jogada tem limite de {}s.", "You accepted {}'s challenge. Private room '{}', you are O. Each move has a {}s limit."),
+    ("Você recusou o desafio de {}.", "You declined {}'s challenge."),
+    ("A lista de amigos está desativada neste servidor.", "The friend list is disabled on this server."),
+    ("Você não pode se adicionar como amigo.", "You cannot add yourself as a friend."),
+    ("Nenhum jogador com o apelido '{}'.", "No player with the nickname '{}'."),
+    ("Não foi possível guardar a lista de amigos.", "Could not save the friend list."),
+    ("Não foi possível ler a lista de amigos.", "Could not read the friend list."),
+    ("{} agora está na sua lista de amigos.", "{} is now on your friend list."),
+    ("{} já está na sua lista de amigos.", "{} is already on your friend list."),
+    ("Sua lista de amigos está vazia. Use /friend add <apelido>.", "Your friend list is empty. Use /friend add <nickname>."),
+    ("Amigos:", "Friends:"),
+    ("  {} (desconectado)", "  {} (offline)"),
+    ("  {} (na sala '{}')", "  {} (in room '{}')"),
+    ("  {} (em uma sala privada)", "  {} (in a private room)"),
+    ("Ninguém está conectado com apelido.", "Nobody with a nickname is online."),
+    ("Conectados:", "Online:"),
+    ("  {} (na fila)", "  {} (in the queue)"),
+    ("  {} (no torneio)", "  {} (in the tournament)"),
+    // Torneios automáticos
+    ("Já há um torneio com inscrições abertas ou em andamento.", "There is already a tournament open for registration or in progress."),
+    ("Inscrições abertas para o torneio de {} que começa {} (UTC): {} inscritos. Participe com /tournament join.", "Registration is open for the {} tournament starting {} (UTC): {} registered. Join with /tournament join."),
+    ("Inscrições abertas para o torneio de {} que começa {} (UTC). Participe com /tournament join.", "Registration is open for the {} tournament starting {} (UTC). Join with /tournament join."),
+    ("Inscrições abertas; o torneio começa {} (UTC).", "Registration is open; the tournament starts {} (UTC)."),
+    ("O torneio já começou; as inscrições estão fechadas.", "The tournament has already started; registration is closed."),
+    ("Não há torneio com inscrições abertas. Veja o próximo com /tournament.", "No tournament is open for registration. See the next one with /tournament."),
+    ("Você já está inscrito no torneio.", "You are already registered for the tournament."),
+    ("Você está inscrito no torneio, que começa {} (UTC). Espere aqui pela sua partida; use /cancel para desistir.", "You are registered for the tournament, which starts {} (UTC). Wait here for your game; use /cancel to withdraw."),
+    ("Você saiu do torneio.", "You left the tournament."),
+    ("Partida do torneio contra {}. Sala '{}', você é o {}.", "Tournament game against {}. Room '{}', you are {}."),
+    ("A partida do torneio terminou. De volta ao lobby.", "The tournament game is over. Back to the lobby."),
+    ("Torneio, rodada {}:", "Tournament, round {}:"),
+    ("  {} x {}", "  {} vs {}"),
+    ("  {} passa direto", "  {} gets a bye"),
+    ("Você foi eliminado do torneio na rodada {}.", "You were knocked out of the tournament in round {}."),
+    ("{} é o campeão do torneio de {}!", "{} is the champion of the {} tournament!"),
+    ("O torneio foi cancelado: não houve inscritos suficientes.", "The tournament was cancelled: not enough players registered."),
+    ("A partida do torneio entre {} e {} terminou sem resultado e será jogada de novo.", "The tournament game between {} and {} ended without a result and will be replayed."),
+    ("Torneio de {} em andamento: {} jogadores ainda na disputa.", "{} tournament in progress: {} players still in contention."),
+    ("Próximo torneio: {} {} (UTC). As inscrições abrem {} min antes.", "Next tournament: {} {} (UTC). Registration opens {} min before."),
+    ("Não há torneios marcados neste servidor.", "There are no tournaments scheduled on this server."),
+    ("O histórico de torneios está desativado neste servidor.", "Tournament history is disabled on this server."),
+    ("Não foi possível ler o histórico de torneios.", "Could not read the tournament history."),
+    ("Nenhum torneio foi disputado ainda.", "No tournament has been played yet."),
+    ("Últimos torneios:", "Latest tournaments:"),
+    ("  #{} {}, {} jogadores: campeão {}", "  #{} {}, {} players: champion {}"),
+    ("  #{} {}, {} jogadores: sem campeão", "  #{} {}, {} players: no champion"),
+    ("Modo desconhecido. Use tournament lig4 ou tournament reversi.", "Unknown mode. Use tournament lig4 or tournament reversi."),
+    ("Número de partida inválido. Use fairplay ou fairplay <partida>.", "Invalid game number. Use fairplay or fairplay <game>."),
+    ("Essa partida não tem relatório de jogo limpo. Só as ranqueadas têm.", "That game has no fair play report. Only rated games have one."),
+    ("Não foi possível ler os relatórios de jogo limpo.", "Could not read the fair play reports."),
+    ("Nenhum relatório de jogo limpo ainda.", "No fair play reports yet."),
+    ("Relatórios de jogo limpo, do mais recente:", "Fair play reports, most recent first:"),
+    ("    {}: {}% de {} jogadas (suspeito)", "    {}: {}% of {} moves (suspicious)"),
+    ("    {}: {}% de {} jogadas", "    {}: {}% of {} moves"),
+    ("    {}: sem jogadas analisadas", "    {}: no moves analyzed"),
+    ("    {}: sem comparação; o motor só analisa o lig4", "    {}: not compared; the engine only analyzes lig4"),
+    ("  {} ({}): só os tempos; o motor só analisa partidas de lig4.", "  {} ({}): times only; the engine only analyzes lig4 games."),
+    ("Relatório de jogo limpo da partida #{} ({}):", "Fair play report for game #{} ({}):"),
+    ("  {} ({}): {} de {} jogadas entre as melhores do motor ({}%).", "  {} ({}): {} of {} moves among the engine's best ({}%)."),
+    ("  {} ({}): nenhuma jogada comparada com o motor.", "  {} ({}): no moves compared with the engine."),
+    ("    Tempo por jogada: média de {}, desvio de {}. {}", "    Time per move: average {}, deviation {}. {}"),
+    ("    Atenção: semelhança acima de {}% com o motor.", "    Warning: similarity with the engine above {}%."),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_translation_keeps_the_arguments() {
+        for (pt, en) in CATALOG {
+            assert_eq!(pt.matches("{}").count(), en.matches("{}").count(), "{}", pt);
+        }
+    }
+
+    #[test]
+    fn translates_messages_with_arguments_and_nested_terms() {
+        assert_eq!(translate(Lang::En, "Olá, ana!\n"), "Hello, ana!\n");
+        assert_eq!(
+            translate(Lang::En, "  mesa [lig4 8x9, 5 em linha, melhor de 3: 1 x 0] (2/2, em andamento) X: ana, O: bia\n"),
+            "  mesa [lig4 8x9, 5 in a row, best of 3: 1 x 0] (2/2, playing) X: ana, O: bia\n"
+        );
+        assert_eq!(translate(Lang::Pt, "Olá, ana!\n"), "Olá, ana!\n");
+    }
+
+    #[test]
+    fn translates_sentence_by_sentence_and_keeps_unknown_text() {
+        assert_eq!(
+            translate(Lang::En, "Partida #3: jogada 7/7 (4). Vitória do X. n = próxima, p = anterior, q = sair\n"),
+            "Game #3: move 7/7 (4). X wins. n = next, p = previous, q = quit\n"
+        );
+        assert_eq!(translate(Lang::En, "Rating: ana 1516, bia 1484\n"), "Rating: ana 1516, bia 1484\n");
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+use tokio::io::{self, AsyncBufReadExt, BufReader, ReadHalf};
+use tokio::time::Instant;
+
+use crate::telnet::{DONT, IAC, SB, SE, WILL};
+use crate::transport::ClientStream;
+
+// Limites de entrada de cada conexão
+#[derive(Debug, Clone, Copy)]
+pub struct InputLimits {
+    pub max_line_length: usize, // Em bytes, sem contar o \n
+    pub lines_per_sec: u32,     // 0 desliga o limite de linhas
+    pub burst: u32,             // Linhas aceitas de uma vez antes de o limite valer
+}
+
+// Problema com o que o cliente enviou. Os erros fatais encerram a conexão;
+// os outros só descartam a linha e o cliente é avisado.
+#[derive(Debug)]
+pub enum InputError {
+    Io(io::Error),
+    Flooding,
+    LineTooLong { max: usize },
+    InvalidEncoding,
+}
+
+impl InputError {
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, InputError::Io(_) | InputError::Flooding)
+    }
+
+    // Aviso para o cliente; erros de leitura não têm, porque a conexão provavelmente já caiu
+    pub fn client_message(&self) -> Option<String> {
+        match self {
+            InputError::Io(_) => None,
+            InputError::Flooding => Some("Mensagens demais em pouco tempo. Conexão encerrada.\n".to_string()),
+            InputError::LineTooLong { max } => Some(format!("Linha longa demais (máximo de {} bytes); ela foi ignorada.\n", max)),
+            InputError::InvalidEncoding => Some("Linha com texto inválido; use UTF-8. Ela foi ignorada.\n".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Io(err) => write!(f, "erro de leitura: {}", err),
+            InputError::Flooding => write!(f, "limite de linhas por segundo excedido"),
+            InputError::LineTooLong { max } => write!(f, "linha com mais de {} bytes", max),
+            InputError::InvalidEncoding => write!(f, "linha fora do UTF-8"),
+        }
+    }
+}
+
+// Balde de fichas: cada linha gasta uma ficha, e as fichas voltam no ritmo permitido
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    per_sec: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_sec: u32, burst: u32) -> Self {
+        let capacity = burst.max(1) as f64;
+        TokenBucket { tokens: capacity, capacity, per_sec: per_sec as f64, updated: Instant::now() }
+    }
+
+    fn take(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated);
+        self.updated = now;
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.per_sec).min(self.capacity);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+// Camada de entrada de cada conexão: separa as linhas sem deixar nenhuma crescer além do
+// limite, aplica o limite de linhas por segundo e entrega só texto limpo para o lobby e as salas
+pub struct LineReader {
+    reader: BufReader<ReadHalf<ClientStream>>,
+    buffer: Vec<u8>,
+    discarding: bool, // A linha atual passou do limite e o resto dela é jogado fora
+    max_line_length: usize,
+    bucket: Option<TokenBucket>,
+}
+
+impl LineReader {
+    pub fn new(reader: ReadHalf<ClientStream>, limits: InputLimits) -> Self {
+        LineReader {
+            reader: BufReader::new(reader),
+            buffer: Vec::new(),
+            discarding: false,
+            max_line_length: limits.max_line_length,
+            bucket: (limits.lines_per_sec != 0).then(|| TokenBucket::new(limits.lines_per_sec, limits.burst)),
+        }
+    }
+
+    // Próxima linha, já limpa; None quando o cliente fechou a conexão.
+    // Uma linha incompleta é guardada se a leitura for cancelada por um select!.
+    pub async fn next_line(&mut self) -> Result<Option<String>, InputError> {
+        loop {
+            let available = self.reader.fill_buf().await.map_err(InputError::Io)?;
+            if available.is_empty() {
+                // Fim da conexão; um resto sem \n ainda vale como linha
+                if self.buffer.is_empty() || self.discarding {
+                    return Ok(None);
+                }
+                return self.finish_line().map(Some);
+            }
+
+            let (chunk, complete) = match available.iter().position(|byte| *byte == b'\n') {
+                Some(end) => (&available[..=end], true),
+                None => (available, false),
+            };
+            if !self.discarding {
+                self.buffer.extend_from_slice(chunk);
+            }
+            let used = chunk.len();
+            self.reader.consume(used);
+
+            // Folga para o \r\n do fim da linha
+            if self.buffer.len() > self.max_line_length + 2 {
+                self.buffer = Vec::new();
+                self.discarding = true;
+            }
+            if complete {
+                return self.finish_line().map(Some);
+            }
+        }
+    }
+
+    fn finish_line(&mut self) -> Result<String, InputError> {
+        let bytes = std::mem::take(&mut self.buffer);
+        if let Some(bucket) = &mut self.bucket {
+            if !bucket.take() {
+                return Err(InputError::Flooding);
+            }
+        }
+        if std::mem::take(&mut self.discarding) {
+            return Err(InputError::LineTooLong { max: self.max_line_length });
+        }
+        let line = sanitize(&bytes)?;
+        if line.len() > self.max_line_length {
+            return Err(InputError::LineTooLong { max: self.max_line_length });
+        }
+        Ok(line)
+    }
+}
+
+// Tira da linha as sequências de comando telnet e os caracteres de controle, inclusive
+// o \r\n do fim. Tabulações viram espaços.
+pub fn sanitize(bytes: &[u8]) -> Result<String, InputError> {
+    let text = String::from_utf8(strip_telnet(bytes)).map_err(|_| InputError::InvalidEncoding)?;
+    Ok(text
+        .chars()
+        .filter_map(|c| match c {
+            '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect())
+}
+
+// Clientes telnet na porta TCP comum mandam negociações IAC no meio do texto
+fn strip_telnet(bytes: &[u8]) -> Vec<u8> {
+    let mut text = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().copied();
+    while let Some(byte) = bytes.next() {
+        if byte != IAC {
+            text.push(byte);
+            continue;
+        }
+        match bytes.next() {
+            // IAC WILL/WONT/DO/DONT <opção>
+            Some(WILL..=DONT) => {
+                bytes.next();
+            }
+            // IAC SB ... IAC SE
+            Some(SB) => {
+                while let Some(byte) = bytes.next() {
+                    if byte == IAC && bytes.next() == Some(SE) {
+                        break;
+                    }
+                }
+            }
+            // Comandos de dois bytes, como NOP e Go Ahead
+            _ => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_line_ending_and_control_characters() {
+        assert_eq!(sanitize(b"/join\tmesa\x07\r\n").unwrap(), "/join mesa");
+        assert_eq!(sanitize("olá\u{1b}[31m!\n".as_bytes()).unwrap(), "olá[31m!");
+    }
+
+    #[test]
+    fn strips_telnet_negotiation() {
+        let line = [&[IAC, WILL, 31][..], b"NICK ", &[IAC, SB, 31, 0, 80, 0, 24, IAC, SE], b"ana", &[IAC, 241], b"\r\n"].concat();
+        assert_eq!(sanitize(&line).unwrap(), "NICK ana");
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        assert!(matches!(sanitize(b"caf\xe9\n"), Err(InputError::InvalidEncoding)));
+    }
+}
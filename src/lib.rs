@@ -0,0 +1,12 @@
+// Regras dos jogos e protocolo compartilhados entre o servidor e os clientes
+pub mod ai;
+pub mod book;
+pub mod error;
+pub mod solver;
+pub mod game;
+pub mod protocol;
+pub mod puzzle;
+pub mod rating;
+pub mod render;
+pub mod reversi;
+pub mod ring;
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Limites de conexões abertas ao mesmo tempo: no servidor todo e por endereço, para que um
+// só computador não ocupe todas as salas nem esgote os descritores de arquivo. Zero desliga
+// o limite correspondente.
+pub struct ConnectionLimits {
+    max_total: AtomicUsize,
+    max_per_ip: AtomicUsize,
+    open: Arc<Mutex<OpenConnections>>,
+}
+
+#[derive(Default)]
+struct OpenConnections {
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+// Vaga de uma conexão aceita; ela é devolvida quando a vaga sai de cena
+pub struct ConnectionSlot {
+    ip: IpAddr,
+    open: Arc<Mutex<OpenConnections>>,
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        let mut open = self.open.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        open.total -= 1;
+        if let Some(count) = open.per_ip.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                open.per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
+
+impl ConnectionLimits {
+    pub fn new(max_total: usize, max_per_ip: usize) -> Self {
+        ConnectionLimits { max_total: AtomicUsize::new(max_total), max_per_ip: AtomicUsize::new(max_per_ip), open: Arc::default() }
+    }
+
+    // Novos limites, da configuração relida; quem já está conectado acima deles continua
+    pub fn set_limits(&self, max_total: usize, max_per_ip: usize) {
+        self.max_total.store(max_total, Ordering::Relaxed);
+        self.max_per_ip.store(max_per_ip, Ordering::Relaxed);
+    }
+
+    // Reserva uma vaga para a conexão vinda do endereço, ou explica por que não há
+    pub fn admit(&self, ip: IpAddr) -> Result<ConnectionSlot, String> {
+        // IPv4 chegando pelo socket IPv6 (::ffff:a.b.c.d) conta como o próprio IPv4
+        let ip = ip.to_canonical();
+        let (max_total, max_per_ip) = (self.max_total.load(Ordering::Relaxed), self.max_per_ip.load(Ordering::Relaxed));
+        let mut open = self.open.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if max_total > 0 && open.total >= max_total {
+            return Err(format!("Servidor cheio: {} conexões abertas. Tente novamente mais tarde.\n", max_total));
+        }
+        let from_ip = open.per_ip.get(&ip).copied().unwrap_or(0);
+        if max_per_ip > 0 && from_ip >= max_per_ip {
+            return Err(format!(
+                "Seu endereço já tem {} conexões abertas, o máximo permitido. Feche uma delas e tente novamente.\n",
+                max_per_ip
+            ));
+        }
+        open.total += 1;
+        *open.per_ip.entry(ip).or_insert(0) += 1;
+        Ok(ConnectionSlot { ip, open: Arc::clone(&self.open) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_counted_per_address_and_returned_on_drop() {
+        let limits = ConnectionLimits::new(3, 2);
+        let home: IpAddr = "10.0.0.1".parse().unwrap();
+        let mapped: IpAddr = "::ffff:10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let first = limits.admit(home).unwrap();
+        let _second = limits.admit(mapped).unwrap();
+        assert!(limits.admit(home).is_err());
+        let _third = limits.admit(other).unwrap();
+        assert!(limits.admit("10.0.0.3".parse().unwrap()).is_err());
+
+        drop(first);
+        assert!(limits.admit(home).is_ok());
+    }
+
+    #[test]
+    fn zero_disables_a_limit() {
+        let limits = ConnectionLimits::new(0, 0);
+        let home: IpAddr = "10.0.0.1".parse().unwrap();
+        let slots: Vec<ConnectionSlot> = (0..10).map(|_| limits.admit(home).unwrap()).collect();
+        assert_eq!(slots.len(), 10);
+    }
+}
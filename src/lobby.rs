@@ -0,0 +1,1654 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, oneshot};
+use tracing::{error, info, info_span};
+
+use reversi_egui::ai::Difficulty;
+use reversi_egui::game::BoardConfig;
+use reversi_egui::puzzle::PUZZLES;
+use reversi_egui::protocol::{GameSummary, PlayerStats, RatingEntry, RoomInfo};
+use reversi_egui::rating::DEFAULT_RATING;
+use reversi_egui::ring::{RING_COLS, RING_ROWS, RING_WIN_LENGTH};
+use crate::config::{Config, Reloader};
+use crate::connection::Presence;
+use crate::fairplay::FairPlayReport;
+use crate::moderation::Moderation;
+use crate::storage::{hash_password, password_matches, Account, DailyEntry, DailyStatus, GameRecord, PuzzleStats, SavedSession, Storage, TournamentRecord};
+use crate::tournament::{self, Round};
+use crate::room::{GameMode, GameRoom, Player, RoomHandle, RoomOptions, Role, RoomStatus, CROWD_NICK};
+
+// Quantas partidas o /history mostra
+const HISTORY_LIMIT: usize = 10;
+
+// Quantos jogadores o /top mostra
+const LEADERBOARD_LIMIT: usize = 10;
+
+// Torneios mostrados no /tournament history
+const TOURNAMENT_HISTORY_LIMIT: usize = 5;
+
+// Relatórios de jogo limpo listados no console de administração
+const FAIR_PLAY_LIMIT: usize = 10;
+
+// Códigos das salas privadas: 6 letras e números, sem os que se confundem (0 e O, 1 e I)
+const ROOM_CODE_LENGTH: usize = 6;
+const ROOM_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+// Partida formada pela fila: a sala nova e o lugar do jogador nela
+pub struct Match {
+    pub room_name: String,
+    pub room: RoomHandle,
+    pub player: Player,
+    pub opponent: String, // Apelido e rating do oponente, para o aviso
+}
+
+// Lugar de uma sala de exibição: o computador em um dos níveis ou um bot conectado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExhibitionSeat {
+    Ai(Difficulty),
+    Bot,
+}
+
+impl ExhibitionSeat {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "bot" => Some(ExhibitionSeat::Bot),
+            level => Difficulty::parse(level).map(ExhibitionSeat::Ai),
+        }
+    }
+}
+
+// Resultado de entrar na fila
+pub enum Queued {
+    Matched(Match),
+    Waiting(oneshot::Receiver<Match>), // Recebe a partida quando outro jogador entrar
+}
+
+// Vaga de um jogador em uma sala, recuperável com RESUME <código>
+#[derive(Debug, Clone)]
+struct Session {
+    room_name: String,
+    nick: String,
+}
+
+// Resultado do RESUME
+pub enum Resume {
+    Seated(String, RoomHandle, Player), // De volta à vaga, na sala com esse nome
+    // O apelido ainda está com outra conexão, que foi avisada para sair; o aviso chega quando
+    // ela liberar o apelido, e então o RESUME pode ser repetido
+    HandingOver(oneshot::Receiver<()>),
+}
+
+// Jogador aguardando oponente na fila do /queue
+struct QueuedPlayer {
+    nick: String,
+    mode: GameMode,
+    rating: i32,
+    matched: oneshot::Sender<Match>,
+}
+
+// Desafio direto de um jogador a outro, aguardando o /accept ou o /decline
+struct Challenge {
+    from: String,
+    to: String,
+    mode: GameMode,
+    matched: oneshot::Sender<Match>, // Leva a partida a quem desafiou; descartado se o desafio cair
+}
+
+// Onde está um amigo, para o /friends
+pub enum FriendStatus {
+    Offline,
+    Lobby,
+    Room(String),
+    PrivateRoom, // O nome das salas privadas não aparece
+}
+
+// O que um jogador conectado está fazendo, para o /who e o chat do lobby
+#[derive(Clone, PartialEq)]
+pub enum Activity {
+    Idle,            // No lobby, sem esperar nada
+    Queued,          // Na fila, esperando oponente
+    Room(String),    // Na sala com este nome, jogando ou assistindo
+    PrivateRoom,     // Na lista do /who, o nome das salas privadas não aparece
+    Tournament,      // Inscrito no torneio, esperando a próxima partida
+}
+
+// Cada apelido em uso: como alcançar a conexão e o que o jogador está fazendo
+struct Online {
+    presence: Presence,
+    activity: Activity,
+}
+
+// Torneio automático, das inscrições até a final
+struct Tournament {
+    mode: GameMode,
+    starts_at: u64,
+    id: Option<i64>, // Número no banco, gravado quando as inscrições fecham
+    entrants: Vec<Entrant>, // Em ordem de inscrição; os eliminados saem
+}
+
+// Inscrito no torneio
+struct Entrant {
+    nick: String,
+    waiting: Option<oneshot::Sender<Match>>, // Presente enquanto o jogador espera no lobby
+    pending: Option<Match>, // Partida formada antes de o jogador voltar ao lobby
+    withdrawn: bool, // Saiu com /cancel ou caiu; perde as próximas partidas por W.O.
+}
+
+// Números do servidor para o console de administração
+pub struct Stats {
+    pub online: usize, // Conexões com apelido
+    pub rooms: usize,
+    pub playing: usize,
+    pub waiting: usize,
+    pub paused: usize,
+    pub queued: usize,
+    pub uptime: Duration,
+}
+
+// Guarda todas as salas abertas no servidor, indexadas pelo nome
+pub struct Lobby {
+    rooms: HashMap<String, RoomHandle>,
+    next_room_id: usize,
+    turn_timeout: Duration,
+    rated_turn_timeout: Duration,
+    reconnect_timeout: Duration,
+    afk_timeout: Option<Duration>,
+    first_player_seed: Option<u32>, // Semente fixa do sorteio de quem começa, para testes
+    default_board: BoardConfig, // Tabuleiro do lig4 quando a sala não escolhe outro
+    max_rooms: usize,
+    storage: Option<Arc<Storage>>,
+    online: HashMap<String, Online>, // Apelidos em uso, com o jeito de derrubar ou avisar cada conexão
+    queue: Vec<QueuedPlayer>, // Em ordem de chegada
+    challenges: Vec<Challenge>, // Desafios aguardando resposta, em ordem de chegada
+    sessions: HashMap<String, Session>, // Códigos de reconexão das partidas abertas
+    handovers: HashMap<String, Vec<oneshot::Sender<()>>>, // RESUMEs esperando a conexão antiga liberar o apelido
+    private_rooms: HashMap<String, String>, // Código de entrada de cada sala privada, pelo nome da sala
+    exhibitions: Vec<String>, // Salas de exibição, onde só bots entram para jogar
+    announcements: broadcast::Sender<String>, // Avisos do administrador para todas as conexões
+    moderation: Moderation, // Banimentos e silêncios aplicados pelo administrador
+    motd_file: Option<PathBuf>,
+    motd: Option<String>, // Mensagem do dia, já com o título, mostrada a cada conexão
+    reloader: Option<Reloader>, // Caminho até o laço principal para o reload do console
+    tournament: Option<Tournament>, // Com inscrições abertas ou em andamento; um de cada vez
+    next_tournament: Option<(u64, GameMode)>, // Próximo da agenda, para o /tournament
+    tournament_registration: Duration, // Quanto tempo antes do início as inscrições abrem
+    started_at: Instant,
+}
+
+impl Lobby {
+    pub fn new(config: &Config, storage: Option<Arc<Storage>>) -> Self {
+        let mut lobby = Lobby {
+            rooms: HashMap::new(),
+            next_room_id: 1,
+            turn_timeout: config.turn_timeout(),
+            rated_turn_timeout: Duration::from_secs(config.rated_turn_timeout_secs),
+            reconnect_timeout: config.reconnect_timeout(),
+            afk_timeout: config.afk_timeout(),
+            first_player_seed: config.first_player_seed,
+            default_board: config.board().unwrap_or_default(),
+            max_rooms: config.max_rooms,
+            moderation: Moderation::load(storage.clone(), config.word_filter(), config.chat_filter()),
+            storage,
+            online: HashMap::new(),
+            queue: Vec::new(),
+            challenges: Vec::new(),
+            sessions: HashMap::new(),
+            handovers: HashMap::new(),
+            private_rooms: HashMap::new(),
+            exhibitions: Vec::new(),
+            announcements: broadcast::channel(16).0,
+            motd_file: config.motd_file(),
+            motd: None,
+            reloader: None,
+            tournament: None,
+            next_tournament: None,
+            tournament_registration: config.tournament_registration(),
+            started_at: Instant::now(),
+        };
+        // Sem o arquivo, o servidor sobe sem mensagem do dia; o motd reload tenta de novo
+        if lobby.motd_file.is_some() {
+            if let Err(err) = lobby.reload_motd() {
+                error!(%err, "erro ao ler a mensagem do dia");
+            }
+        }
+        lobby
+    }
+
+    // Canal dos avisos do administrador, assinado por cada conexão ao abrir
+    pub fn subscribe_announcements(&self) -> broadcast::Receiver<String> {
+        self.announcements.subscribe()
+    }
+
+    pub fn set_reloader(&mut self, reloader: Reloader) {
+        self.reloader = Some(reloader);
+    }
+
+    // Limite por jogada das salas novas; as ranqueadas usam o mais curto da configuração
+    fn turn_timeout_for(&self, rated: bool) -> Duration {
+        if rated {
+            self.rated_turn_timeout
+        } else {
+            self.turn_timeout
+        }
+    }
+
+    pub fn reloader(&self) -> Option<Reloader> {
+        self.reloader.clone()
+    }
+
+    // Aplica a configuração relida ao que o lobby guarda. Os novos valores valem para as
+    // salas criadas daqui em diante; as abertas continuam com os tempos que tinham.
+    pub fn apply_config(&mut self, config: &Config) -> Result<(), String> {
+        self.turn_timeout = config.turn_timeout();
+        self.rated_turn_timeout = Duration::from_secs(config.rated_turn_timeout_secs);
+        self.reconnect_timeout = config.reconnect_timeout();
+        self.afk_timeout = config.afk_timeout();
+        self.first_player_seed = config.first_player_seed;
+        self.default_board = config.board().unwrap_or_default();
+        self.max_rooms = config.max_rooms;
+        self.motd_file = config.motd_file();
+        if self.motd_file.is_none() {
+            self.motd = None;
+            return Ok(());
+        }
+        self.reload_motd().map(|_| ())
+    }
+
+    pub fn motd(&self) -> Option<String> {
+        self.motd.clone()
+    }
+
+    // Relê o arquivo da mensagem do dia e retorna quantas linhas ela tem. Um arquivo vazio
+    // tira a mensagem; se a leitura falhar, a mensagem anterior continua valendo.
+    pub fn reload_motd(&mut self) -> Result<usize, String> {
+        let path = self.motd_file.as_ref().ok_or("O servidor não tem um arquivo de mensagem do dia (motd_file).\n")?;
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Não foi possível ler a mensagem do dia em {}: {}\n", path.display(), err))?;
+        let text = text.trim_end();
+        let lines = if text.trim().is_empty() { 0 } else { text.lines().count() };
+        self.motd = (lines > 0).then(|| format!("Mensagem do dia:\n{}\n", text));
+        info!(lines, "mensagem do dia carregada");
+        Ok(lines)
+    }
+
+    // Banimentos e silêncios, consultados também fora do lobby: na conexão aceita e no chat
+    pub fn moderation(&self) -> Moderation {
+        self.moderation.clone()
+    }
+
+    // Identifica a conexão pelo apelido. Apelidos registrados com senha exigem a senha;
+    // os demais são registrados (ou protegidos, se vier uma senha) no primeiro uso.
+    // `presence` derruba a conexão no /kick do administrador e leva a ela os avisos de desafios.
+    // true se o login com este apelido vai pedir senha, para o servidor perguntá-la antes
+    pub fn needs_password(&self, nick: &str, current: Option<&str>) -> bool {
+        if current == Some(nick) || self.online.contains_key(nick) {
+            return false;
+        }
+        let Some(storage) = &self.storage else {
+            return false;
+        };
+        matches!(storage.find_account(nick), Ok(Some(account)) if account.password_hash.is_some())
+    }
+
+    pub fn login(&mut self, nick: &str, password: Option<&str>, current: Option<&str>, presence: Presence) -> Result<(), String> {
+        validate_nick(nick)?;
+        if current == Some(nick) {
+            return Ok(());
+        }
+        if let Some(rejection) = self.moderation.nick_rejection(nick) {
+            return Err(rejection);
+        }
+        if !self.moderation.allows_nick(nick) {
+            return Err(format!("O apelido '{}' não é permitido neste servidor. Escolha outro.\n", nick));
+        }
+        if self.online.contains_key(nick) {
+            return Err(format!("O apelido '{}' já está em uso.\n", nick));
+        }
+
+        if let Some(storage) = &self.storage {
+            let account = storage
+                .find_account(nick)
+                .map_err(|_| "Não foi possível acessar as contas.\n".to_string())?;
+            let password_hash = password.map(|password| hash_password(nick, password));
+
+            match account.and_then(|account| account.password_hash) {
+                Some(_) if password_hash.is_none() => {
+                    return Err(format!("O apelido '{}' é protegido por senha. Use NICK {} <senha>.\n", nick, nick));
+                }
+                Some(stored) if !password_hash.as_deref().is_some_and(|password_hash| password_matches(&stored, password_hash)) => {
+                    return Err("Senha incorreta.\n".to_string());
+                }
+                Some(_) => {}
+                None => {
+                    let account = Account { nick: nick.to_string(), password_hash };
+                    storage
+                        .save_account(&account)
+                        .map_err(|_| "Não foi possível registrar o apelido.\n".to_string())?;
+                }
+            }
+        }
+
+        if let Some(current) = current {
+            self.release_nick(current);
+        }
+        self.online.insert(nick.to_string(), Online { presence, activity: Activity::Idle });
+        info!(nick, "apelido em uso");
+        Ok(())
+    }
+
+    // Libera o apelido quando a conexão termina; os desafios feitos a ele caem
+    pub fn release_nick(&mut self, nick: &str) {
+        self.online.remove(nick);
+        self.challenges.retain(|challenge| challenge.to != nick);
+        for waiting in self.handovers.remove(nick).unwrap_or_default() {
+            let _ = waiting.send(());
+        }
+    }
+
+    // Gera o código com que o jogador recupera a vaga na sala se a conexão cair
+    pub fn issue_token(&mut self, room_name: &str, player: &Player) -> String {
+        let token = generate_token();
+        let session = Session {
+            room_name: room_name.to_string(),
+            nick: player.nick.clone(),
+        };
+        if let Some(storage) = &self.storage {
+            let saved = SavedSession {
+                token: token.clone(),
+                room_name: session.room_name.clone(),
+                nick: session.nick.clone(),
+            };
+            if let Err(err) = storage.save_session(&saved) {
+                error!(%err, "erro ao guardar o código de reconexão");
+            }
+        }
+        self.sessions.insert(token.clone(), session);
+        token
+    }
+
+    // Reabre as partidas guardadas no banco, pausadas até os jogadores voltarem com os
+    // códigos de reconexão. Partidas sem código válido não têm como ser retomadas e são
+    // descartadas. Retorna quantas salas foram reabertas.
+    pub fn restore_games(&mut self) -> usize {
+        let storage = match &self.storage {
+            Some(storage) => Arc::clone(storage),
+            None => return 0,
+        };
+        let (games, sessions) = match (storage.saved_games(), storage.sessions()) {
+            (Ok(games), Ok(sessions)) => (games, sessions),
+            (Err(err), _) | (_, Err(err)) => {
+                error!(%err, "erro ao ler as partidas guardadas");
+                return 0;
+            }
+        };
+
+        let mut restored = 0;
+        for saved in &games {
+            let resumable = sessions.iter().any(|session| session.room_name == saved.room_name);
+            let mode = match GameMode::parse(&saved.mode) {
+                Some(mode) if resumable => mode,
+                _ => continue,
+            };
+            let room = GameRoom::restore(saved, mode, self.turn_timeout_for(saved.rated), self.reconnect_timeout, self.afk_timeout, Some(Arc::clone(&storage)));
+            let room = RoomHandle::spawn(room, Vec::new(), info_span!("room", room = %saved.room_name));
+            self.rooms.insert(saved.room_name.clone(), room);
+            info!(room = %saved.room_name, x = %saved.player_x, o = %saved.player_o, "partida guardada reaberta");
+            restored += 1;
+        }
+
+        for session in sessions {
+            if self.rooms.contains_key(&session.room_name) {
+                let SavedSession { token, room_name, nick } = session;
+                self.sessions.insert(token, Session { room_name, nick });
+            } else if let Err(err) = storage.discard_room(&session.room_name) {
+                error!(%err, "erro ao apagar códigos de reconexão antigos");
+            }
+        }
+        for saved in games.iter().filter(|saved| !self.rooms.contains_key(&saved.room_name)) {
+            if let Err(err) = storage.discard_room(&saved.room_name) {
+                error!(%err, "erro ao apagar a partida guardada");
+            }
+        }
+        restored
+    }
+
+    // Volta para a partida pausada com o código de reconexão. A conexão assume o apelido
+    // de quem caiu, no lugar do que estiver usando.
+    pub async fn resume(&mut self, token: &str, current: Option<&str>, presence: Presence) -> Result<Resume, String> {
+        let session = match self.sessions.get(token) {
+            Some(session) => session.clone(),
+            None => return Err("Código de reconexão inválido ou expirado.\n".to_string()),
+        };
+        if let Some(rejection) = self.moderation.nick_rejection(&session.nick) {
+            return Err(rejection);
+        }
+        // O código prova que é o mesmo jogador, então a vaga não fica presa à conexão antiga,
+        // que pode ser de outro transporte ou ter caído sem o servidor perceber: ela sai como
+        // numa queda, a sala pausa, e a vaga passa para esta conexão
+        if current != Some(session.nick.as_str()) {
+            if let Some(holder) = self.online.get(&session.nick) {
+                info!(nick = %session.nick, "sessão retomada em outra conexão; a antiga será encerrada");
+                holder.presence.replaced.notify_one();
+                let (released, handover) = oneshot::channel();
+                self.handovers.entry(session.nick.clone()).or_default().push(released);
+                return Ok(Resume::HandingOver(handover));
+            }
+        }
+        let room = match self.rooms.get(&session.room_name) {
+            Some(room) => room.clone(),
+            None => return Err("Código de reconexão inválido ou expirado.\n".to_string()),
+        };
+
+        let player = room.resume(&session.nick).await?;
+        if let Some(current) = current {
+            self.release_nick(current);
+        }
+        self.online.insert(session.nick.clone(), Online { presence, activity: Activity::Idle });
+        Ok(Resume::Seated(session.room_name, room, player))
+    }
+
+    // Cria uma nova sala e coloca o criador como jogador X
+    pub fn create_room(
+        &mut self,
+        name: Option<&str>,
+        mode: GameMode,
+        board_config: Option<BoardConfig>,
+        options: RoomOptions,
+        nick: &str,
+    ) -> Result<(String, RoomHandle, Player), String> {
+        self.check_room_limit()?;
+        if options.swap && mode != GameMode::ConnectFour {
+            return Err("A regra da troca só vale no lig4.\n".to_string());
+        }
+        if options.pop_out && mode != GameMode::ConnectFour {
+            return Err("A variante Pop Out só vale no lig4.\n".to_string());
+        }
+        if options.power_up && mode != GameMode::ConnectFour {
+            return Err("A variante Power Up só vale no lig4.\n".to_string());
+        }
+        if options.handicap.is_some() && mode != GameMode::ConnectFour {
+            return Err("A vantagem combinada só vale no lig4.\n".to_string());
+        }
+        if options.teams && !matches!(mode, GameMode::ConnectFour | GameMode::Reversi) {
+            return Err("As duplas valem no lig4 e no reversi.\n".to_string());
+        }
+        // A troca e a vantagem mudam os lados de um jogador só; em duplas, os lados são das duplas
+        if options.teams && (options.swap || options.handicap.is_some()) {
+            return Err("As duplas não valem junto com a regra da troca nem com a vantagem combinada.\n".to_string());
+        }
+        // Com a troca, quem dá a vantagem poderia ficar com a jogada de quem a recebeu
+        if options.handicap.is_some() && options.swap {
+            return Err("A vantagem combinada não vale junto com a regra da troca.\n".to_string());
+        }
+        // O anel tem tabuleiro próprio e não joga séries nem usa o relógio de xadrez, que são de dois jogadores
+        let board_config = match mode {
+            GameMode::Ring(_) if options.clock.is_some() => {
+                return Err("O relógio de xadrez não vale no modo anel.\n".to_string());
+            }
+            GameMode::Ring(_) if options.rated => {
+                return Err("Partidas do modo anel não valem para o ranking.\n".to_string());
+            }
+            GameMode::Ring(_) if board_config.is_some() || options.best_of.is_some() => {
+                return Err("O modo anel usa o tabuleiro padrão dele e não joga séries.\n".to_string());
+            }
+            GameMode::Ring(_) => BoardConfig { rows: RING_ROWS, cols: RING_COLS, win_length: RING_WIN_LENGTH },
+            _ => board_config.unwrap_or(self.default_board),
+        };
+        let name = self.claim_room_name(name)?;
+
+        let player = Player {
+            symbol: 1,
+            nick: nick.to_string(),
+            role: Role::Player,
+        };
+        let mut room = GameRoom::new(mode, board_config, self.turn_timeout_for(options.rated), self.reconnect_timeout, self.afk_timeout, self.storage.clone());
+        room.draw_first_turn(self.room_seed());
+        room.players.push(player.clone());
+        room.apply_options(options);
+
+        let room = RoomHandle::spawn(room, Vec::new(), info_span!("room", room = %name));
+        self.rooms.insert(name.clone(), room.clone());
+        info!(room = %name, mode = mode.name(), nick, "sala criada");
+        Ok((name, room, player))
+    }
+
+    // Nome da sala nova: o pedido, se estiver livre, ou um gerado
+    fn claim_room_name(&mut self, name: Option<&str>) -> Result<String, String> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => self.generate_room_name(),
+        };
+        if self.rooms.contains_key(&name) {
+            return Err(format!("Já existe uma sala chamada '{}'.\n", name));
+        }
+        Ok(name)
+    }
+
+    // Cria uma sala de exibição de lig4, com o computador ou bots conectados nos dois lugares e
+    // um atraso entre as jogadas para quem assiste. Quem cria fica assistindo. Com o computador
+    // nos dois lugares a partida começa na hora; lugares de bot esperam um /join.
+    pub fn create_exhibition(
+        &mut self,
+        name: Option<&str>,
+        seats: [ExhibitionSeat; 2],
+        move_delay: Duration,
+        nick: &str,
+    ) -> Result<(String, RoomHandle, Player), String> {
+        self.check_room_limit()?;
+        let name = self.claim_room_name(name)?;
+
+        let mut room = GameRoom::new(GameMode::ConnectFour, self.default_board, self.turn_timeout, self.reconnect_timeout, self.afk_timeout, self.storage.clone());
+        room.draw_first_turn(self.room_seed());
+        room.make_exhibition(move_delay);
+        let mut ais = Vec::new();
+        for (symbol, seat) in [1, -1].into_iter().zip(seats) {
+            if let ExhibitionSeat::Ai(difficulty) = seat {
+                room.players.push(Player {
+                    symbol,
+                    nick: format!("computador ({})", difficulty.name()),
+                    role: Role::Ai,
+                });
+                ais.push((symbol, difficulty));
+            }
+        }
+        let spectator = Player {
+            symbol: 0,
+            nick: nick.to_string(),
+            role: Role::Spectator,
+        };
+        room.players.push(spectator.clone());
+        if ais.len() == seats.len() {
+            room.start_game();
+        }
+
+        let room = RoomHandle::spawn(room, ais, info_span!("room", room = %name));
+        self.rooms.insert(name.clone(), room.clone());
+        self.exhibitions.push(name.clone());
+        info!(room = %name, nick, ?seats, "sala de exibição criada");
+        Ok((name, room, spectator))
+    }
+
+    // Cria a sala da multidão contra o computador. A multidão fica com o X, num lugar que
+    // ninguém ocupa, e quem cria a sala entra como o primeiro espectador. Como na exibição,
+    // ninguém entra para jogar, e a sala fecha quando o último espectador sai.
+    pub fn create_crowd_room(
+        &mut self,
+        name: Option<&str>,
+        difficulty: Difficulty,
+        window: Duration,
+        nick: &str,
+    ) -> Result<(String, RoomHandle, Player), String> {
+        self.check_room_limit()?;
+        let name = self.claim_room_name(name)?;
+
+        let mut room = GameRoom::new(GameMode::ConnectFour, self.default_board, self.turn_timeout, self.reconnect_timeout, self.afk_timeout, self.storage.clone());
+        room.draw_first_turn(self.room_seed());
+        room.make_crowd(1, window);
+        room.players.push(Player {
+            symbol: 1,
+            nick: CROWD_NICK.to_string(),
+            role: Role::Ai,
+        });
+        room.players.push(Player {
+            symbol: -1,
+            nick: format!("computador ({})", difficulty.name()),
+            role: Role::Ai,
+        });
+        let spectator = Player {
+            symbol: 0,
+            nick: nick.to_string(),
+            role: Role::Spectator,
+        };
+        room.players.push(spectator.clone());
+        room.start_game();
+
+        let room = RoomHandle::spawn(room, vec![(-1, difficulty)], info_span!("room", room = %name));
+        self.rooms.insert(name.clone(), room.clone());
+        self.exhibitions.push(name.clone());
+        info!(room = %name, nick, ?difficulty, ?window, "sala da multidão criada");
+        Ok((name, room, spectator))
+    }
+
+    // Torna a sala privada: ela some do /list e só se entra nela com o código gerado aqui
+    pub fn make_private(&mut self, name: &str) -> String {
+        let taken: Vec<&String> = self.private_rooms.values().collect();
+        let code = loop {
+            let code = generate_room_code();
+            if !taken.contains(&&code) {
+                break code;
+            }
+        };
+        self.private_rooms.insert(name.to_string(), code.clone());
+        info!(room = %name, "sala privada");
+        code
+    }
+
+    // Entra em uma sala existente como jogador O; no anel, com o próximo símbolo livre da roda.
+    // Nas exibições só bots entram para jogar.
+    pub async fn join_room(&mut self, name: &str, nick: &str, bot: bool) -> Result<(RoomHandle, Player), String> {
+        if self.private_rooms.contains_key(name) {
+            return Err(format!("A sala '{}' é privada. Entre com /join code <código>.\n", name));
+        }
+        if !bot && self.exhibitions.iter().any(|exhibition| exhibition == name) {
+            return Err(format!("A sala '{}' é uma exibição: só bots entram para jogar. Use /watch {} para assistir.\n", name, name));
+        }
+        self.seat_player(name, nick).await
+    }
+
+    // Entra em uma sala privada pelo código recebido de quem a criou. Retorna também o nome da sala.
+    pub async fn join_by_code(&mut self, code: &str, nick: &str) -> Result<(String, RoomHandle, Player), String> {
+        let name = self
+            .private_rooms
+            .iter()
+            .find(|(_, room_code)| room_code.eq_ignore_ascii_case(code))
+            .map(|(name, _)| name.clone())
+            .ok_or_else(|| format!("Nenhuma sala com o código {}.\n", code.to_uppercase()))?;
+        let (room, player) = self.seat_player(&name, nick).await?;
+        Ok((name, room, player))
+    }
+
+    async fn seat_player(&mut self, name: &str, nick: &str) -> Result<(RoomHandle, Player), String> {
+        let room = self.find_room(name)?;
+
+        let mut player = Player {
+            symbol: -1,
+            nick: nick.to_string(),
+            role: Role::Player,
+        };
+        player.symbol = match room.join(player.clone()).await {
+            Some(symbol) => symbol,
+            None => return Err(format!("Sala '{}' já está cheia. Use /watch {} para assistir.\n", name, name)),
+        };
+
+        Ok((room, player))
+    }
+
+    // Cria uma sala de lig4 contra o computador, que joga de O
+    pub fn create_solo_room(&mut self, difficulty: Difficulty, nick: &str) -> Result<(String, RoomHandle, Player), String> {
+        self.check_room_limit()?;
+        let name = self.generate_room_name();
+
+        let player = Player {
+            symbol: 1,
+            nick: nick.to_string(),
+            role: Role::Player,
+        };
+        let computer = Player {
+            symbol: -1,
+            nick: format!("computador ({})", difficulty.name()),
+            role: Role::Ai,
+        };
+        let mut room = GameRoom::new(GameMode::ConnectFour, self.default_board, self.turn_timeout, self.reconnect_timeout, self.afk_timeout, self.storage.clone());
+        room.draw_first_turn(self.room_seed());
+        room.players.push(player.clone());
+        room.players.push(computer);
+        room.start_game();
+
+        let room = RoomHandle::spawn(room, vec![(-1, difficulty)], info_span!("room", room = %name));
+        self.rooms.insert(name.clone(), room.clone());
+        info!(room = %name, nick, difficulty = difficulty.name(), "partida contra o computador iniciada");
+
+        Ok((name, room, player))
+    }
+
+    // Coloca o jogador na fila. Se já houver alguém esperando pelo mesmo jogo, forma a
+    // partida na hora com o oponente de ELO mais próximo; quem esperava joga de X.
+    pub fn enqueue(&mut self, nick: &str, mode: GameMode) -> Result<Queued, String> {
+        if self.queue.iter().any(|queued| queued.nick == nick) {
+            return Err("Você já está na fila.\n".to_string());
+        }
+        // A fila forma pares; o anel precisa de uma mesa inteira
+        if let GameMode::Ring(_) = mode {
+            return Err("O modo anel não tem fila. Crie uma sala com /create <nome> anel.\n".to_string());
+        }
+        self.check_room_limit()?;
+
+        let rating = self.rating(nick);
+        // Quem desconectou sem sair da fila é descartado
+        self.queue.retain(|queued| !queued.matched.is_closed());
+
+        let opponent = self
+            .queue
+            .iter()
+            .enumerate()
+            .filter(|(_, queued)| queued.mode == mode)
+            .min_by_key(|(_, queued)| (queued.rating - rating).abs())
+            .map(|(index, _)| index);
+
+        let opponent = match opponent {
+            Some(index) => self.queue.remove(index),
+            None => {
+                let (matched, receiver) = oneshot::channel();
+                self.queue.push(QueuedPlayer {
+                    nick: nick.to_string(),
+                    mode,
+                    rating,
+                    matched,
+                });
+                self.set_activity(nick, Activity::Queued);
+                return Ok(Queued::Waiting(receiver));
+            }
+        };
+
+        // Partidas da fila são sempre ranqueadas: a fila já escolhe o oponente pelo ELO
+        let options = RoomOptions { rated: true, ..RoomOptions::default() };
+        let (room_name, room, player_x, player_o) = self.start_match(mode, options, &opponent.nick, nick);
+        info!(room = %room_name, x = %opponent.nick, o = nick, "partida formada pela fila");
+
+        let _ = opponent.matched.send(Match {
+            room_name: room_name.clone(),
+            room: room.clone(),
+            player: player_x,
+            opponent: format!("{} ({})", nick, rating),
+        });
+        Ok(Queued::Matched(Match {
+            room_name,
+            room,
+            player: player_o,
+            opponent: format!("{} ({})", opponent.nick, opponent.rating),
+        }))
+    }
+
+    // Abre uma sala com os dois jogadores sentados e a partida já começando
+    fn start_match(&mut self, mode: GameMode, options: RoomOptions, nick_x: &str, nick_o: &str) -> (String, RoomHandle, Player, Player) {
+        let room_name = self.generate_room_name();
+        let player_x = Player {
+            symbol: 1,
+            nick: nick_x.to_string(),
+            role: Role::Player,
+        };
+        let player_o = Player {
+            symbol: -1,
+            nick: nick_o.to_string(),
+            role: Role::Player,
+        };
+        let mut room = GameRoom::new(mode, self.default_board, self.turn_timeout_for(options.rated), self.reconnect_timeout, self.afk_timeout, self.storage.clone());
+        room.draw_first_turn(self.room_seed());
+        room.apply_options(options);
+        room.players.push(player_x.clone());
+        room.players.push(player_o.clone());
+        room.start_game();
+
+        let room = RoomHandle::spawn(room, Vec::new(), info_span!("room", room = %room_name));
+        self.rooms.insert(room_name.clone(), room.clone());
+        (room_name, room, player_x, player_o)
+    }
+
+    // Tira o jogador da fila e retira o desafio que ele fez. Retorna false se ele não
+    // estava mais esperando (a partida já foi formada).
+    pub fn stop_waiting(&mut self, nick: &str) -> bool {
+        let before = self.queue.len() + self.challenges.len();
+        self.queue.retain(|queued| queued.nick != nick);
+        if let Some(online) = self.online.get_mut(nick).filter(|online| matches!(online.activity, Activity::Queued | Activity::Tournament)) {
+            online.activity = Activity::Idle;
+        }
+        // No torneio, quem sai da espera desiste: antes do início sai da lista, depois perde por W.O.
+        let mut left_tournament = false;
+        if let Some(tournament) = &mut self.tournament {
+            if let Some(entrant) = tournament.entrants.iter_mut().find(|entrant| entrant.nick == nick) {
+                left_tournament = entrant.waiting.take().is_some();
+                entrant.withdrawn |= left_tournament;
+            }
+            if tournament.id.is_none() {
+                tournament.entrants.retain(|entrant| entrant.nick != nick);
+            }
+        }
+        if left_tournament {
+            return true;
+        }
+        let (withdrawn, challenges) = std::mem::take(&mut self.challenges).into_iter().partition(|challenge| challenge.from == nick);
+        self.challenges = challenges;
+        for challenge in withdrawn {
+            self.notify(&challenge.to, format!("{} retirou o desafio.\n", nick));
+        }
+        self.queue.len() + self.challenges.len() < before
+    }
+
+    // Mostra um aviso na conexão do jogador, se ele estiver conectado
+    fn notify(&self, nick: &str, text: String) {
+        if let Some(online) = self.online.get(nick) {
+            online.presence.notify(text);
+        }
+    }
+
+    // Anota o que o jogador está fazendo agora; quem já desconectou é ignorado
+    pub fn set_activity(&mut self, nick: &str, activity: Activity) {
+        if let Some(online) = self.online.get_mut(nick) {
+            online.activity = match activity {
+                Activity::Room(room) if self.private_rooms.contains_key(&room) => Activity::PrivateRoom,
+                activity => activity,
+            };
+        }
+    }
+
+    // Apelidos conectados, em ordem alfabética, com o que cada um está fazendo
+    pub fn who(&self) -> Vec<(String, Activity)> {
+        let mut players: Vec<(String, Activity)> =
+            self.online.iter().map(|(nick, online)| (nick.clone(), online.activity.clone())).collect();
+        players.sort_by(|a, b| a.0.cmp(&b.0));
+        players
+    }
+
+    // Manda uma mensagem ao chat do lobby. Só recebe quem está no lobby ou esperando nele:
+    // quem está numa sala fica com o chat dela.
+    pub fn lobby_chat(&self, from: &str, text: &str, time: &str) {
+        for online in self.in_lobby() {
+            online.presence.chat(from, text, time);
+        }
+    }
+
+    fn in_lobby(&self) -> impl Iterator<Item = &Online> {
+        self.online
+            .values()
+            .filter(|online| matches!(online.activity, Activity::Idle | Activity::Queued | Activity::Tournament))
+    }
+
+    // Aviso para quem está no lobby, como os do torneio
+    pub fn tell_lobby(&self, text: &str) {
+        info!(text = text.trim_end(), "aviso no lobby");
+        for online in self.in_lobby() {
+            online.presence.notify(text.to_string());
+        }
+    }
+
+    pub fn tournament_registration(&self) -> Duration {
+        self.tournament_registration
+    }
+
+    // Anota o próximo torneio da agenda, mostrado no /tournament até as inscrições abrirem
+    pub fn schedule_tournament(&mut self, starts_at: u64, mode: GameMode) {
+        self.next_tournament = Some((starts_at, mode));
+    }
+
+    // Abre as inscrições de um torneio que começa em `starts_at` e avisa o lobby
+    pub fn open_tournament(&mut self, mode: GameMode, starts_at: u64) -> Result<(), String> {
+        if self.tournament.is_some() {
+            return Err("Já há um torneio com inscrições abertas ou em andamento.\n".to_string());
+        }
+        if self.next_tournament.is_some_and(|(next, _)| next == starts_at) {
+            self.next_tournament = None;
+        }
+        self.tournament = Some(Tournament { mode, starts_at, id: None, entrants: Vec::new() });
+        info!(mode = mode.name(), starts_at, "inscrições do torneio abertas");
+        self.tell_lobby(&format!(
+            "Inscrições abertas para o torneio de {} que começa {} (UTC). Participe com /tournament join.\n",
+            mode.name(),
+            tournament::describe(starts_at)
+        ));
+        Ok(())
+    }
+
+    // Inscreve o jogador. Ele espera no lobby com o recebedor retornado, que traz a primeira partida.
+    pub fn join_tournament(&mut self, nick: &str) -> Result<(u64, oneshot::Receiver<Match>), String> {
+        let tournament = match &mut self.tournament {
+            Some(tournament) if tournament.id.is_none() => tournament,
+            Some(_) => return Err("O torneio já começou; as inscrições estão fechadas.\n".to_string()),
+            None => return Err("Não há torneio com inscrições abertas. Veja o próximo com /tournament.\n".to_string()),
+        };
+        if tournament.entrants.iter().any(|entrant| entrant.nick == nick) {
+            return Err("Você já está inscrito no torneio.\n".to_string());
+        }
+        let (sender, receiver) = oneshot::channel();
+        tournament.entrants.push(Entrant { nick: nick.to_string(), waiting: Some(sender), pending: None, withdrawn: false });
+        let starts_at = tournament.starts_at;
+        self.set_activity(nick, Activity::Tournament);
+        Ok((starts_at, receiver))
+    }
+
+    // De volta ao lobby depois de uma partida, o jogador que segue no torneio espera a próxima.
+    // None se ele foi eliminado ou o torneio acabou.
+    pub fn tournament_next(&mut self, nick: &str) -> Option<oneshot::Receiver<Match>> {
+        let entrant = self.tournament.as_mut()?.entrants.iter_mut().find(|entrant| entrant.nick == nick && !entrant.withdrawn)?;
+        let (sender, receiver) = oneshot::channel();
+        match entrant.pending.take() {
+            Some(found) => {
+                let _ = sender.send(found);
+            }
+            None => entrant.waiting = Some(sender),
+        }
+        self.set_activity(nick, Activity::Tournament);
+        Some(receiver)
+    }
+
+    // Fecha as inscrições e grava o torneio. Retorna os inscritos que seguem esperando, do
+    // maior ELO para o menor: a ordem de cabeça de chave.
+    pub fn begin_tournament(&mut self) -> Vec<String> {
+        let Some(tournament) = &mut self.tournament else {
+            return Vec::new();
+        };
+        tournament.entrants.retain(|entrant| entrant.waiting.as_ref().is_some_and(|waiting| !waiting.is_closed()));
+        let mut players: Vec<String> = tournament.entrants.iter().map(|entrant| entrant.nick.clone()).collect();
+        let mode = tournament.mode;
+        players.sort_by_key(|nick| std::cmp::Reverse(self.rating(nick)));
+        if players.len() >= 2 {
+            let id = self.storage.as_ref().and_then(|storage| {
+                storage
+                    .start_tournament(mode.name(), &players, tournament::unix_now())
+                    .map_err(|err| error!(%err, "erro ao gravar o torneio"))
+                    .ok()
+            });
+            if let Some(tournament) = &mut self.tournament {
+                // Sem banco, o torneio roda do mesmo jeito, só sem histórico
+                tournament.id = Some(id.unwrap_or(0));
+            }
+            info!(players = players.len(), "torneio começou");
+        }
+        players
+    }
+
+    pub fn announce_round(&self, round: usize, pairing: &Round) {
+        let mut text = format!("Torneio, rodada {}:\n", round);
+        for (player_x, player_o) in &pairing.games {
+            text.push_str(&format!("  {} x {}\n", player_x, player_o));
+        }
+        if let Some(bye) = &pairing.bye {
+            text.push_str(&format!("  {} passa direto\n", bye));
+        }
+        self.tell_lobby(&text);
+    }
+
+    // Abre a sala de uma partida do torneio e leva a partida aos dois jogadores. Retorna
+    // também quem desistiu, para a sala dar o W.O.
+    pub fn start_tournament_game(&mut self, nick_x: &str, nick_o: &str) -> (RoomHandle, Vec<String>) {
+        let mode = self.tournament.as_ref().map_or(GameMode::ConnectFour, |tournament| tournament.mode);
+        let options = RoomOptions { tournament: true, ..RoomOptions::default() };
+        let (room_name, room, player_x, player_o) = self.start_match(mode, options, nick_x, nick_o);
+        info!(room = %room_name, x = nick_x, o = nick_o, "partida do torneio");
+
+        let mut absent = Vec::new();
+        for (player, opponent) in [(player_x, nick_o), (player_o, nick_x)] {
+            let found = Match {
+                room_name: room_name.clone(),
+                room: room.clone(),
+                opponent: format!("{} ({})", opponent, self.rating(opponent)),
+                player,
+            };
+            let nick = found.player.nick.clone();
+            let Some(entrant) = self.tournament.as_mut().and_then(|tournament| tournament.entrants.iter_mut().find(|entrant| entrant.nick == nick)) else {
+                continue;
+            };
+            // Quem caiu durante a partida anterior também não volta mais para esta
+            match entrant.waiting.take() {
+                _ if entrant.withdrawn || !self.online.contains_key(&nick) => absent.push(nick),
+                Some(waiting) => {
+                    if waiting.send(found).is_err() {
+                        absent.push(nick);
+                    }
+                }
+                None => entrant.pending = Some(found),
+            }
+        }
+        (room, absent)
+    }
+
+    pub fn record_tournament_game(&self, round: usize, player_x: &str, player_o: &str, winner: &str) {
+        let id = self.tournament.as_ref().and_then(|tournament| tournament.id).filter(|id| *id > 0);
+        if let (Some(storage), Some(id)) = (&self.storage, id) {
+            if let Err(err) = storage.record_tournament_game(id, round, player_x, player_o, winner) {
+                error!(%err, "erro ao gravar a partida do torneio");
+            }
+        }
+    }
+
+    // Tira o perdedor do torneio; se ele estiver esperando no lobby, a espera acaba
+    pub fn eliminate(&mut self, nick: &str, round: usize) {
+        if let Some(tournament) = &mut self.tournament {
+            tournament.entrants.retain(|entrant| entrant.nick != nick);
+        }
+        self.notify(nick, format!("Você foi eliminado do torneio na rodada {}.\n", round));
+    }
+
+    // Encerra o torneio com o campeão e avisa o lobby
+    pub fn crown(&mut self, champion: &str) {
+        let Some(tournament) = &self.tournament else {
+            return;
+        };
+        let id = tournament.id.filter(|id| *id > 0);
+        if let (Some(storage), Some(id)) = (&self.storage, id) {
+            if let Err(err) = storage.finish_tournament(id, champion) {
+                error!(%err, "erro ao gravar o campeão do torneio");
+            }
+        }
+        let text = format!("{} é o campeão do torneio de {}!\n", champion, tournament.mode.name());
+        self.end_tournament(text);
+    }
+
+    // Fecha o torneio; quem ainda esperava volta ao lobby. Os inscritos que restam recebem o
+    // aviso onde estiverem: o campeão pode ainda não ter saído da sala da final.
+    pub fn end_tournament(&mut self, text: String) {
+        let entrants = self.tournament.take().map(|tournament| tournament.entrants).unwrap_or_default();
+        for entrant in &entrants {
+            if let Some(online) = self.online.get_mut(&entrant.nick).filter(|online| online.activity == Activity::Tournament) {
+                online.activity = Activity::Idle;
+            }
+        }
+        info!(text = text.trim_end(), "aviso no lobby");
+        for (nick, online) in &self.online {
+            let entrant = entrants.iter().any(|entrant| entrant.nick == *nick);
+            if entrant || matches!(online.activity, Activity::Idle | Activity::Queued | Activity::Tournament) {
+                online.presence.notify(text.clone());
+            }
+        }
+    }
+
+    // O /tournament: o torneio aberto ou em andamento, ou o próximo da agenda
+    pub fn tournament_status(&self) -> String {
+        match (&self.tournament, self.next_tournament) {
+            (Some(tournament), _) if tournament.id.is_some() => {
+                format!("Torneio de {} em andamento: {} jogadores ainda na disputa.\n", tournament.mode.name(), tournament.entrants.len())
+            }
+            (Some(tournament), _) => format!(
+                "Inscrições abertas para o torneio de {} que começa {} (UTC): {} inscritos. Participe com /tournament join.\n",
+                tournament.mode.name(),
+                tournament::describe(tournament.starts_at),
+                tournament.entrants.len()
+            ),
+            (None, Some((starts_at, mode))) => format!(
+                "Próximo torneio: {} {} (UTC). As inscrições abrem {} min antes.\n",
+                mode.name(),
+                tournament::describe(starts_at),
+                self.tournament_registration.as_secs() / 60
+            ),
+            (None, None) => "Não há torneios marcados neste servidor.\n".to_string(),
+        }
+    }
+
+    // Últimos torneios gravados, para o /tournament history
+    pub fn tournament_history(&self) -> Result<Vec<TournamentRecord>, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O histórico de torneios está desativado neste servidor.\n".to_string()),
+        };
+        storage
+            .recent_tournaments(TOURNAMENT_HISTORY_LIMIT)
+            .map_err(|_| "Não foi possível ler o histórico de torneios.\n".to_string())
+    }
+
+    // Relatórios de jogo limpo para o console de administração: os mais recentes, ou o de
+    // uma partida
+    pub fn fair_play_reports(&self, game: Option<i64>) -> Result<Vec<FairPlayReport>, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O histórico de partidas está desativado neste servidor.\n".to_string()),
+        };
+        let reports = match game {
+            Some(game) => storage.fair_play_report(game).map(|report| report.into_iter().collect()),
+            None => storage.recent_fair_play_reports(FAIR_PLAY_LIMIT),
+        };
+        reports.map_err(|_| "Não foi possível ler os relatórios de jogo limpo.\n".to_string())
+    }
+
+    // Desafia um jogador conectado. Quem desafia espera a resposta com o recebedor
+    // retornado, que traz a partida se o desafio for aceito.
+    pub fn challenge(&mut self, from: &str, to: &str, mode: GameMode) -> Result<oneshot::Receiver<Match>, String> {
+        if from == to {
+            return Err("Você não pode desafiar a si mesmo.\n".to_string());
+        }
+        if let GameMode::Ring(_) = mode {
+            return Err("O modo anel não tem desafios. Crie uma sala com /create <nome> anel.\n".to_string());
+        }
+        if !self.online.contains_key(to) {
+            return Err(format!("{} não está conectado.\n", to));
+        }
+        // Quem desconectou esperando a resposta não desafia mais ninguém
+        self.challenges.retain(|challenge| !challenge.matched.is_closed());
+        if self.challenges.iter().any(|challenge| challenge.from == from) {
+            return Err("Você já tem um desafio aguardando resposta.\n".to_string());
+        }
+
+        let (matched, receiver) = oneshot::channel();
+        self.challenges.push(Challenge {
+            from: from.to_string(),
+            to: to.to_string(),
+            mode,
+            matched,
+        });
+        let notice = format!("{} desafiou você para uma partida de {}. Responda com /accept {} ou /decline {}.\n", from, mode.name(), from, from);
+        self.notify(to, notice);
+        info!(from, to, mode = mode.name(), "desafio feito");
+        Ok(receiver)
+    }
+
+    // Aceita o desafio: abre uma sala privada e amistosa em que quem desafiou joga de X
+    pub fn accept_challenge(&mut self, nick: &str, from: &str) -> Result<Match, String> {
+        let index = self
+            .challenges
+            .iter()
+            .position(|challenge| challenge.to == nick && challenge.from == from && !challenge.matched.is_closed())
+            .ok_or_else(|| format!("Nenhum desafio de {} aguardando resposta.\n", from))?;
+        self.check_room_limit()?;
+        let challenge = self.challenges.remove(index);
+
+        let (room_name, room, player_x, player_o) = self.start_match(challenge.mode, RoomOptions::default(), from, nick);
+        self.make_private(&room_name);
+        info!(room = %room_name, x = from, o = nick, "desafio aceito");
+
+        let _ = challenge.matched.send(Match {
+            room_name: room_name.clone(),
+            room: room.clone(),
+            player: player_x,
+            opponent: nick.to_string(),
+        });
+        Ok(Match {
+            room_name,
+            room,
+            player: player_o,
+            opponent: from.to_string(),
+        })
+    }
+
+    // Recusa o desafio; quem desafiou para de esperar
+    pub fn decline_challenge(&mut self, nick: &str, from: &str) -> Result<(), String> {
+        let index = self
+            .challenges
+            .iter()
+            .position(|challenge| challenge.to == nick && challenge.from == from)
+            .ok_or_else(|| format!("Nenhum desafio de {} aguardando resposta.\n", from))?;
+        self.challenges.remove(index);
+        Ok(())
+    }
+
+    // Adiciona um jogador registrado à lista de amigos
+    pub fn add_friend(&self, nick: &str, friend: &str) -> Result<bool, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("A lista de amigos está desativada neste servidor.\n".to_string()),
+        };
+        if nick == friend {
+            return Err("Você não pode se adicionar como amigo.\n".to_string());
+        }
+        match storage.find_account(friend) {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(format!("Nenhum jogador com o apelido '{}'.\n", friend)),
+            Err(_) => return Err("Não foi possível acessar as contas.\n".to_string()),
+        }
+        storage
+            .add_friend(nick, friend)
+            .map_err(|_| "Não foi possível guardar a lista de amigos.\n".to_string())
+    }
+
+    // Amigos do jogador, com onde cada um está agora
+    pub async fn friends(&self, nick: &str) -> Result<Vec<(String, FriendStatus)>, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("A lista de amigos está desativada neste servidor.\n".to_string()),
+        };
+        let friends = storage
+            .friends(nick)
+            .map_err(|_| "Não foi possível ler a lista de amigos.\n".to_string())?;
+
+        let mut seats: HashMap<String, String> = HashMap::new();
+        for (name, room) in &self.rooms {
+            if let Some(summary) = room.summary().await {
+                for player in summary.players {
+                    seats.insert(player.nick, name.clone());
+                }
+            }
+        }
+        Ok(friends
+            .into_iter()
+            .map(|friend| {
+                let status = match seats.get(&friend) {
+                    _ if !self.online.contains_key(&friend) => FriendStatus::Offline,
+                    Some(room) if self.private_rooms.contains_key(room) => FriendStatus::PrivateRoom,
+                    Some(room) => FriendStatus::Room(room.clone()),
+                    None => FriendStatus::Lobby,
+                };
+                (friend, status)
+            })
+            .collect())
+    }
+
+    fn rating(&self, nick: &str) -> i32 {
+        self.storage
+            .as_ref()
+            .and_then(|storage| storage.rating(nick).ok())
+            .unwrap_or(DEFAULT_RATING)
+    }
+
+    // Entra em uma sala como espectador, sem ocupar vaga de jogador
+    pub async fn watch_room(&mut self, name: &str, nick: &str) -> Result<(RoomHandle, Player), String> {
+        let room = self.find_room(name)?;
+
+        let player = Player {
+            symbol: 0,
+            nick: nick.to_string(),
+            role: Role::Spectator,
+        };
+        room.watch(player.clone()).await;
+
+        Ok((room, player))
+    }
+
+    // Próxima sala com partida em andamento para o modo TV, em ordem de nome e voltando ao
+    // começo depois da última. Sem sala atual, sorteia uma.
+    pub async fn live_room(&self, current: Option<&str>) -> Option<(String, RoomHandle)> {
+        let mut live = Vec::new();
+        for (name, room) in self.rooms.iter().filter(|(name, _)| !self.private_rooms.contains_key(*name)) {
+            let status = room.summary().await.map(|summary| summary.status);
+            if matches!(status, Some(RoomStatus::Playing | RoomStatus::Paused)) {
+                live.push((name.clone(), room.clone()));
+            }
+        }
+        if live.is_empty() {
+            return None;
+        }
+        live.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let index = match current {
+            Some(current) => live.iter().position(|(name, _)| name.as_str() > current).unwrap_or(0),
+            None => random_below(live.len()),
+        };
+        Some(live.swap_remove(index))
+    }
+
+    // Sala listada no /list, para quem acompanha de fora do jogo; as privadas ficam de fora
+    pub fn public_room(&self, name: &str) -> Result<RoomHandle, String> {
+        match self.private_rooms.contains_key(name) {
+            true => Err(format!("Sala '{}' não encontrada. Use /list para ver as salas.\n", name)),
+            false => self.find_room(name),
+        }
+    }
+
+    // Semente do sorteio de quem começa numa sala nova: a fixada na configuração ou uma aleatória
+    fn room_seed(&self) -> u32 {
+        self.first_player_seed.unwrap_or_else(|| random_u64() as u32)
+    }
+
+    fn find_room(&self, name: &str) -> Result<RoomHandle, String> {
+        match self.rooms.get(name) {
+            Some(room) => Ok(room.clone()),
+            None => Err(format!("Sala '{}' não encontrada. Use /list para ver as salas.\n", name)),
+        }
+    }
+
+    // Remove a sala depois que a partida terminou ou que todos os jogadores saíram. A
+    // exibição continua enquanto alguém estiver jogando ou assistindo.
+    pub async fn close_room_if_done(&mut self, name: &str) {
+        let exhibition = self.exhibitions.iter().any(|exhibition| exhibition == name);
+        let done = match self.rooms.get(name) {
+            Some(room) => match room.summary().await {
+                Some(summary) if exhibition => summary.players.iter().all(|player| player.role == Role::Ai),
+                Some(summary) => summary.humans == 0 || matches!(summary.status, RoomStatus::Finished(_)),
+                None => true, // A tarefa da sala já terminou
+            },
+            None => return,
+        };
+
+        if done {
+            self.rooms.remove(name);
+            self.forget_room(name);
+            info!(room = %name, "sala fechada");
+        }
+    }
+
+    // Quem assiste não fecha as salas comuns, mas a exibição só acaba quando a última
+    // pessoa sai
+    pub async fn close_exhibition_if_empty(&mut self, name: &str) {
+        if self.exhibitions.iter().any(|exhibition| exhibition == name) {
+            self.close_room_if_done(name).await;
+        }
+    }
+
+    // Descarta os códigos de reconexão e de entrada e a partida guardada de uma sala que fechou
+    fn forget_room(&mut self, name: &str) {
+        self.sessions.retain(|_, session| session.room_name != name);
+        self.private_rooms.remove(name);
+        self.exhibitions.retain(|exhibition| exhibition != name);
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.discard_room(name) {
+                error!(room = %name, %err, "erro ao apagar os dados guardados da sala");
+            }
+        }
+    }
+
+    // Lista as salas com a ocupação de cada uma. As privadas só aparecem para o administrador.
+    pub async fn room_infos(&self, include_private: bool) -> Vec<RoomInfo> {
+        let mut names: Vec<&String> = self
+            .rooms
+            .keys()
+            .filter(|name| include_private || !self.private_rooms.contains_key(*name))
+            .collect();
+        names.sort();
+
+        let mut rooms = Vec::new();
+        for name in names {
+            let room = match self.rooms[name].summary().await {
+                Some(room) => room,
+                None => continue,
+            };
+            let status = match room.status {
+                RoomStatus::Waiting => "aguardando",
+                RoomStatus::Playing => "em andamento",
+                RoomStatus::Paused => "pausada",
+                RoomStatus::OnHold => "em pausa",
+                RoomStatus::Finished(_) => "encerrada",
+            };
+            rooms.push(RoomInfo {
+                name: name.clone(),
+                mode: room.mode_label,
+                players: room
+                    .players
+                    .iter()
+                    .filter(|player| player.role != Role::Spectator)
+                    .map(|player| format!("{}: {}", room.mode.glyph(player.symbol), player.nick))
+                    .collect(),
+                seats: room.seats,
+                spectators: room.players.iter().filter(|player| player.role == Role::Spectator).count(),
+                status: status.to_string(),
+            });
+        }
+        rooms
+    }
+
+    // Suspende e salva as partidas em andamento antes de o servidor encerrar.
+    // Retorna quantas partidas foram salvas.
+    pub async fn suspend_games(&mut self) -> usize {
+        let mut saved = 0;
+        for (name, room) in &self.rooms {
+            let game = match room.suspend(name).await {
+                Some(game) => game,
+                None => continue,
+            };
+            if let Some(storage) = &self.storage {
+                match storage.save_in_progress(&game) {
+                    Ok(()) => saved += 1,
+                    Err(err) => error!(room = %name, %err, "erro ao salvar a partida"),
+                }
+            }
+        }
+        saved
+    }
+
+    // Guarda uma cópia das partidas em andamento sem interrompê-las, para que uma queda do
+    // servidor não as perca. As salas sem partida em andamento, como as que acabaram de
+    // terminar, perdem a cópia anterior. Retorna quantas partidas foram guardadas.
+    pub async fn snapshot_games(&self) -> usize {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return 0,
+        };
+        let mut saved = 0;
+        for (name, room) in &self.rooms {
+            let result = match room.snapshot(name).await {
+                Some(game) => storage.save_in_progress(&game).map(|()| saved += 1),
+                None => storage.discard_saved_game(name),
+            };
+            if let Err(err) = result {
+                error!(room = %name, %err, "erro ao guardar a cópia da partida");
+            }
+        }
+        saved
+    }
+
+    // Partidas recentes do jogador, lidas do histórico
+    pub fn history(&self, player: &str) -> Result<Vec<GameSummary>, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O histórico de partidas está desativado neste servidor.\n".to_string()),
+        };
+
+        let records = storage
+            .recent_games(player, HISTORY_LIMIT)
+            .map_err(|_| "Não foi possível ler o histórico de partidas.\n".to_string())?;
+        Ok(records
+            .into_iter()
+            .map(|record| GameSummary {
+                id: record.id,
+                mode: record.mode,
+                player_x: record.player_x,
+                player_o: record.player_o,
+                winner: record.winner,
+                forfeit: record.forfeit,
+                moves: record.moves.len(),
+                started_at: record.started_at,
+                duration_secs: record.duration_secs,
+                series: record.series,
+            })
+            .collect())
+    }
+
+    // Número de um quebra-cabeça sorteado para o /puzzle, de 1 em diante
+    pub fn random_puzzle(&self) -> usize {
+        random_below(PUZZLES.len()) + 1
+    }
+
+    // Guarda o resultado de um quebra-cabeça; sem o histórico, ele não é guardado
+    pub fn record_puzzle(&self, nick: &str, number: usize, solved: bool) {
+        if let Some(storage) = &self.storage {
+            let played_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+            if let Err(err) = storage.record_puzzle(nick, number, solved, played_at) {
+                error!(%err, "erro ao gravar o quebra-cabeça");
+            }
+        }
+    }
+
+    pub fn puzzle_stats(&self, nick: &str) -> Result<PuzzleStats, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("As estatísticas de quebra-cabeças estão desativadas neste servidor.\n".to_string()),
+        };
+
+        storage
+            .puzzle_stats(nick)
+            .map_err(|_| "Não foi possível ler as estatísticas de quebra-cabeças.\n".to_string())
+    }
+
+    // Tentativas do jogador no desafio do dia; sem o histórico, ninguém tentou ainda
+    pub fn daily_status(&self, nick: &str, day: u64) -> DailyStatus {
+        let Some(storage) = &self.storage else {
+            return DailyStatus::default();
+        };
+        storage.daily_status(nick, day).unwrap_or_else(|err| {
+            error!(%err, "erro ao ler o desafio diário");
+            DailyStatus::default()
+        })
+    }
+
+    pub fn record_daily(&self, nick: &str, day: u64, solved: bool, duration: Duration) {
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.record_daily(nick, day, solved, duration.as_millis() as u64) {
+                error!(%err, "erro ao gravar o desafio diário");
+            }
+        }
+    }
+
+    pub fn daily_leaderboard(&self, day: u64) -> Result<Vec<DailyEntry>, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O ranking do desafio diário está desativado neste servidor.\n".to_string()),
+        };
+
+        storage
+            .daily_leaderboard(day, LEADERBOARD_LIMIT)
+            .map_err(|_| "Não foi possível ler o ranking do desafio diário.\n".to_string())
+    }
+
+    // Ranking dos jogadores pelo ELO
+    pub fn leaderboard(&self) -> Result<Vec<RatingEntry>, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O ranking está desativado neste servidor.\n".to_string()),
+        };
+
+        storage
+            .leaderboard(LEADERBOARD_LIMIT)
+            .map_err(|_| "Não foi possível ler o ranking.\n".to_string())
+    }
+
+    // Números do jogador no histórico, para a API HTTP
+    pub fn player_stats(&self, nick: &str) -> Result<PlayerStats, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O histórico de partidas está desativado neste servidor.\n".to_string()),
+        };
+
+        match storage.player_stats(nick) {
+            Ok(Some(stats)) => Ok(stats),
+            Ok(None) => Err(format!("Nenhum jogador com o apelido '{}'.\n", nick)),
+            Err(_) => Err("Não foi possível ler o histórico de partidas.\n".to_string()),
+        }
+    }
+
+    // Partida gravada, para o /replay
+    pub fn find_game(&self, id: i64) -> Result<GameRecord, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O histórico de partidas está desativado neste servidor.\n".to_string()),
+        };
+
+        match storage.find_game(id) {
+            Ok(Some(record)) => Ok(record),
+            Ok(None) => Err(format!("Partida #{} não encontrada. Use /history para ver as partidas.\n", id)),
+            Err(_) => Err("Não foi possível ler o histórico de partidas.\n".to_string()),
+        }
+    }
+
+    // Última partida gravada do jogador, para o /export sem número
+    pub fn latest_game(&self, player: &str) -> Result<GameRecord, String> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Err("O histórico de partidas está desativado neste servidor.\n".to_string()),
+        };
+
+        match storage.recent_games(player, 1) {
+            Ok(records) => records
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("Nenhuma partida encontrada para {}.\n", player)),
+            Err(_) => Err("Não foi possível ler o histórico de partidas.\n".to_string()),
+        }
+    }
+
+    // Apelidos conectados, em ordem alfabética, com a sala em que cada um está
+    pub async fn players(&self) -> Vec<(String, Option<String>)> {
+        let mut seats: HashMap<String, String> = HashMap::new();
+        for (name, room) in &self.rooms {
+            if let Some(summary) = room.summary().await {
+                for player in summary.players {
+                    seats.insert(player.nick, name.clone());
+                }
+            }
+        }
+
+        let mut players: Vec<(String, Option<String>)> =
+            self.online.keys().map(|nick| (nick.clone(), seats.get(nick).cloned())).collect();
+        players.sort();
+        players
+    }
+
+    // Derruba a conexão do jogador; numa partida, é o mesmo que sair da sala
+    pub fn kick(&mut self, nick: &str) -> Result<(), String> {
+        match self.online.get(nick) {
+            Some(online) => {
+                online.presence.kick.notify_one();
+                info!(nick, "conexão derrubada pelo administrador");
+                Ok(())
+            }
+            None => Err(format!("Ninguém conectado com o apelido '{}'.\n", nick)),
+        }
+    }
+
+    // Fecha a sala na hora, sem gravar a partida em andamento; os jogadores são desconectados
+    pub async fn close_room(&mut self, name: &str) -> Result<(), String> {
+        let room = self.find_room(name)?;
+        self.rooms.remove(name);
+        self.forget_room(name);
+        room.close().await;
+        info!(room = %name, "sala fechada pelo administrador");
+        Ok(())
+    }
+
+    // Envia um aviso a todas as conexões. Retorna quantas receberam.
+    pub fn announce(&self, text: &str) -> usize {
+        info!(text, "aviso do administrador");
+        self.announcements.send(text.to_string()).unwrap_or(0)
+    }
+
+    // Pedidos na fila de cada sala, para as métricas
+    pub fn queue_depths(&self) -> Vec<(String, usize)> {
+        let mut depths: Vec<(String, usize)> = self.rooms.iter().map(|(name, room)| (name.clone(), room.queue_depth())).collect();
+        depths.sort();
+        depths
+    }
+
+    // Sem histórico, None; com ele, se o banco respondeu a uma consulta simples
+    pub fn check_database(&self) -> Option<Result<(), String>> {
+        let storage = self.storage.as_ref()?;
+        Some(storage.ping().map_err(|err| err.to_string()))
+    }
+
+    pub async fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            online: self.online.len(),
+            rooms: self.rooms.len(),
+            playing: 0,
+            waiting: 0,
+            paused: 0,
+            queued: self.queue.len(),
+            uptime: self.started_at.elapsed(),
+        };
+        for room in self.rooms.values() {
+            match room.summary().await.map(|summary| summary.status) {
+                Some(RoomStatus::Playing) => stats.playing += 1,
+                Some(RoomStatus::Waiting) => stats.waiting += 1,
+                Some(RoomStatus::Paused | RoomStatus::OnHold) => stats.paused += 1,
+                Some(RoomStatus::Finished(_)) | None => {}
+            }
+        }
+        stats
+    }
+
+    fn check_room_limit(&self) -> Result<(), String> {
+        if self.rooms.len() >= self.max_rooms {
+            return Err(format!("O servidor atingiu o limite de {} salas. Tente novamente mais tarde.\n", self.max_rooms));
+        }
+        Ok(())
+    }
+
+    fn generate_room_name(&mut self) -> String {
+        loop {
+            let name = format!("sala{}", self.next_room_id);
+            self.next_room_id += 1;
+            if !self.rooms.contains_key(&name) {
+                return name;
+            }
+        }
+    }
+}
+
+// Código de reconexão aleatório, com 128 bits em hexadecimal
+fn generate_token() -> String {
+    format!("{:016x}{:016x}", random_u64(), random_u64())
+}
+
+// Cada RandomState nasce com chaves aleatórias, então o hash de um valor fixo já é imprevisível
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u8(0);
+    hasher.finish()
+}
+
+fn random_below(limit: usize) -> usize {
+    (random_u64() % limit as u64) as usize
+}
+
+fn generate_room_code() -> String {
+    (0..ROOM_CODE_LENGTH)
+        .map(|_| ROOM_CODE_ALPHABET[random_below(ROOM_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+// Apelidos curtos, só com letras, números, _ e -
+fn validate_nick(nick: &str) -> Result<(), String> {
+    let length = nick.chars().count();
+    if (2..=16).contains(&length) && nick.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+        Ok(())
+    } else {
+        Err("Apelido inválido. Use de 2 a 16 letras, números, _ ou -.\n".to_string())
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt::Write as _;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::time;
+use tracing::{debug, warn};
+
+use crate::health::HEALTH;
+use crate::http;
+use crate::lobby::Lobby;
+
+// Tempo máximo para ler o pedido e enviar a resposta
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Contadores do servidor, expostos no formato de texto do Prometheus em /metrics.
+// Salas e filas são lidas do lobby na hora da coleta.
+pub struct Metrics {
+    connections: AtomicU64, // Conexões abertas agora
+    games_completed: AtomicU64,
+    moves: AtomicU64,
+    game_duration_millis: AtomicU64, // Soma das durações das partidas encerradas
+    events_lagged: AtomicU64,  // Vezes em que alguém ficou para trás nos eventos de uma sala
+    events_skipped: AtomicU64, // Eventos descartados por esses atrasos
+    output_full: AtomicU64,    // Vezes em que uma conexão achou a própria fila de saída cheia
+    slow_disconnects: AtomicU64, // Conexões encerradas por não acompanhar o servidor
+    connections_rejected: AtomicU64, // Conexões recusadas pelos limites do servidor ou por endereço, ou vindas de endereços banidos
+}
+
+pub static METRICS: Metrics = Metrics::new();
+
+// Mantém a conexão contada enquanto existir
+pub struct ConnectionGuard;
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        METRICS.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Metrics {
+            connections: AtomicU64::new(0),
+            games_completed: AtomicU64::new(0),
+            moves: AtomicU64::new(0),
+            game_duration_millis: AtomicU64::new(0),
+            events_lagged: AtomicU64::new(0),
+            events_skipped: AtomicU64::new(0),
+            output_full: AtomicU64::new(0),
+            slow_disconnects: AtomicU64::new(0),
+            connections_rejected: AtomicU64::new(0),
+        }
+    }
+
+    pub fn connection_opened(&'static self) -> ConnectionGuard {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard
+    }
+
+    pub fn open_connections(&self) -> u64 {
+        self.connections.load(Ordering::Relaxed)
+    }
+
+    pub fn connection_rejected(&self) {
+        self.connections_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn move_played(&self) {
+        self.moves.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn game_completed(&self, duration: Duration) {
+        self.games_completed.fetch_add(1, Ordering::Relaxed);
+        self.game_duration_millis.fetch_add(duration.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    // Um assinante perdeu `skipped` eventos de uma sala e vai redesenhar tudo
+    pub fn events_lagged(&self, skipped: u64) {
+        self.events_lagged.fetch_add(1, Ordering::Relaxed);
+        self.events_skipped.fetch_add(skipped, Ordering::Relaxed);
+    }
+
+    pub fn output_queue_full(&self) {
+        self.output_full.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn slow_client_dropped(&self) {
+        self.slow_disconnects.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// Atende os pedidos HTTP na porta de métricas até o servidor encerrar
+pub async fn serve(listener: TcpListener, lobby: Arc<Mutex<Lobby>>) {
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                warn!(%err, "erro ao aceitar conexão de métricas");
+                continue;
+            }
+        };
+        let lobby = Arc::clone(&lobby);
+        tokio::spawn(async move {
+            match time::timeout(REQUEST_TIMEOUT, respond(stream, &lobby)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => debug!(%peer, %err, "erro ao responder as métricas"),
+                Err(_) => debug!(%peer, "pedido de métricas demorou demais"),
+            }
+        });
+    }
+}
+
+async fn respond(stream: TcpStream, lobby: &Arc<Mutex<Lobby>>) -> io::Result<()> {
+    let (request, writer) = http::read_request(stream).await?;
+    let (status, body) = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/metrics") => ("200 OK", render(lobby).await),
+        ("GET", "/healthz") => return respond_health(writer, lobby, false).await,
+        ("GET", "/readyz") => return respond_health(writer, lobby, true).await,
+        ("GET", _) => ("404 Not Found", "Use /metrics, /healthz ou /readyz\n".to_string()),
+        _ => ("405 Method Not Allowed", "Use GET /metrics\n".to_string()),
+    };
+    http::respond(writer, status, "text/plain; version=0.0.4; charset=utf-8", &body).await
+}
+
+async fn respond_health(writer: OwnedWriteHalf, lobby: &Arc<Mutex<Lobby>>, readiness: bool) -> io::Result<()> {
+    let (status, body) = HEALTH.check(lobby, readiness).await;
+    http::respond(writer, status, "application/json; charset=utf-8", &body).await
+}
+
+// Texto no formato de exposição do Prometheus
+async fn render(lobby: &Arc<Mutex<Lobby>>) -> String {
+    let queue_depths = lobby.lock().await.queue_depths();
+    let games = METRICS.games_completed.load(Ordering::Relaxed);
+    let duration_secs = METRICS.game_duration_millis.load(Ordering::Relaxed) as f64 / 1000.0;
+    let average_secs = if games == 0 { 0.0 } else { duration_secs / games as f64 };
+
+    let mut text = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+        let _ = write!(text, "# HELP {} {}\n# TYPE {} {}\n{} {}\n", name, help, name, kind, name, value);
+    };
+    metric("reversi_connections", "gauge", "Conexões abertas.", METRICS.connections.load(Ordering::Relaxed).to_string());
+    metric("reversi_connections_rejected_total", "counter", "Conexões recusadas pelos limites de conexões ou por banimento.", METRICS.connections_rejected.load(Ordering::Relaxed).to_string());
+    metric("reversi_rooms", "gauge", "Salas abertas.", queue_depths.len().to_string());
+    metric("reversi_games_completed_total", "counter", "Partidas encerradas.", games.to_string());
+    metric("reversi_moves_total", "counter", "Jogadas aceitas, inclusive as do computador.", METRICS.moves.load(Ordering::Relaxed).to_string());
+    metric("reversi_game_duration_seconds_sum", "counter", "Soma das durações das partidas encerradas.", duration_secs.to_string());
+    metric("reversi_game_duration_average_seconds", "gauge", "Duração média das partidas encerradas.", average_secs.to_string());
+    metric("reversi_events_lagged_total", "counter", "Vezes em que uma conexão ficou para trás nos eventos de uma sala.", METRICS.events_lagged.load(Ordering::Relaxed).to_string());
+    metric("reversi_events_skipped_total", "counter", "Eventos de sala descartados para quem ficou para trás.", METRICS.events_skipped.load(Ordering::Relaxed).to_string());
+    metric("reversi_output_queue_full_total", "counter", "Vezes em que a fila de saída de uma conexão estava cheia.", METRICS.output_full.load(Ordering::Relaxed).to_string());
+    metric("reversi_slow_disconnects_total", "counter", "Conexões encerradas por não acompanhar o servidor.", METRICS.slow_disconnects.load(Ordering::Relaxed).to_string());
+
+    text.push_str("# HELP reversi_room_queue_depth Pedidos aguardando a tarefa de cada sala.\n");
+    text.push_str("# TYPE reversi_room_queue_depth gauge\n");
+    for (room, depth) in queue_depths {
+        let _ = writeln!(text, "reversi_room_queue_depth{{room=\"{}\"}} {}", escape_label(&room), depth);
+    }
+    text
+}
+
+// Aspas e barras invertidas precisam de escape dentro do valor de um rótulo
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
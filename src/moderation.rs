@@ -0,0 +1,275 @@
+use std::borrow::Cow;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info};
+
+use crate::filter::{FilterLevel, WordFilter};
+use crate::storage::Storage;
+
+// Punição aplicada pelo administrador
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SanctionKind {
+    BanNick, // O apelido não entra mais no servidor
+    BanIp,   // Nenhuma conexão vinda do endereço é aceita
+    Mute,    // O jogador continua jogando, mas não usa o chat
+}
+
+impl SanctionKind {
+    // Nome guardado no banco
+    pub fn name(&self) -> &'static str {
+        match self {
+            SanctionKind::BanNick => "ban_nick",
+            SanctionKind::BanIp => "ban_ip",
+            SanctionKind::Mute => "mute",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ban_nick" => Some(SanctionKind::BanNick),
+            "ban_ip" => Some(SanctionKind::BanIp),
+            "mute" => Some(SanctionKind::Mute),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            SanctionKind::BanNick => "apelido banido",
+            SanctionKind::BanIp => "endereço banido",
+            SanctionKind::Mute => "silenciado",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sanction {
+    pub kind: SanctionKind,
+    pub target: String,
+    pub expires_at: Option<u64>, // Segundos desde a época Unix; sem valor, vale para sempre
+}
+
+impl Sanction {
+    // Tempo que falta, ou None se ela vale para sempre
+    fn remaining(&self) -> Option<Duration> {
+        self.expires_at.map(|expires_at| Duration::from_secs(expires_at.saturating_sub(now())))
+    }
+
+    fn expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now())
+    }
+}
+
+// Banimentos e silêncios em vigor. A lista fica em memória, para ser consultada a cada
+// conexão aceita e a cada mensagem de chat sem passar pelo lobby, e é copiada para o banco
+// a cada mudança, para valer depois de o servidor reiniciar. As vencidas somem na consulta.
+// O filtro de palavras do chat e dos apelidos também fica aqui, ao alcance das conexões.
+#[derive(Clone)]
+pub struct Moderation {
+    storage: Option<Arc<Storage>>,
+    sanctions: Arc<Mutex<Vec<Sanction>>>,
+    words: Arc<WordFilter>,
+    chat_filter: FilterLevel, // Rigor do filtro nas salas que não escolhem outro
+}
+
+impl Moderation {
+    pub fn load(storage: Option<Arc<Storage>>, words: WordFilter, chat_filter: FilterLevel) -> Self {
+        let sanctions = match &storage {
+            Some(storage) => storage.sanctions().unwrap_or_else(|err| {
+                error!(%err, "erro ao ler os banimentos guardados");
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        let sanctions = sanctions.into_iter().filter(|sanction| !sanction.expired()).collect();
+        Moderation { storage, sanctions: Arc::new(Mutex::new(sanctions)), words: Arc::new(words), chat_filter }
+    }
+
+    // Mensagem de chat com as palavras proibidas escondidas, no rigor da sala ou no do servidor
+    pub fn censor<'a>(&self, text: &'a str, room_filter: Option<FilterLevel>) -> Cow<'a, str> {
+        self.words.censor(text, room_filter.unwrap_or(self.chat_filter))
+    }
+
+    // Apelidos com palavras proibidas não podem ser usados, qualquer que seja o rigor do chat
+    pub fn allows_nick(&self, nick: &str) -> bool {
+        self.words.allows_nick(nick)
+    }
+
+    // Bane um apelido ou, se o alvo for um endereço IP, o endereço. Sem duração, para sempre.
+    pub fn ban(&self, target: &str, duration: Option<Duration>) -> Sanction {
+        match target.parse::<IpAddr>() {
+            Ok(ip) => self.apply(SanctionKind::BanIp, &ip.to_canonical().to_string(), duration),
+            Err(_) => self.apply(SanctionKind::BanNick, target, duration),
+        }
+    }
+
+    pub fn mute(&self, nick: &str, duration: Option<Duration>) -> Sanction {
+        self.apply(SanctionKind::Mute, nick, duration)
+    }
+
+    // Retira o banimento do apelido ou do endereço. Retorna false se não havia nenhum.
+    pub fn unban(&self, target: &str) -> bool {
+        match target.parse::<IpAddr>() {
+            Ok(ip) => self.lift(SanctionKind::BanIp, &ip.to_canonical().to_string()),
+            Err(_) => self.lift(SanctionKind::BanNick, target),
+        }
+    }
+
+    pub fn unmute(&self, nick: &str) -> bool {
+        self.lift(SanctionKind::Mute, nick)
+    }
+
+    // Punições em vigor, na ordem em que foram aplicadas
+    pub fn active(&self) -> Vec<Sanction> {
+        let mut sanctions = self.sanctions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        sanctions.retain(|sanction| !sanction.expired());
+        sanctions.clone()
+    }
+
+    // Aviso para a conexão vinda de um endereço banido
+    pub fn ip_rejection(&self, ip: IpAddr) -> Option<String> {
+        let sanction = self.find(SanctionKind::BanIp, &ip.to_canonical().to_string())?;
+        Some(match sanction.remaining() {
+            Some(remaining) => format!("Seu endereço está banido deste servidor por mais {}.\n", format_duration(remaining)),
+            None => "Seu endereço está banido deste servidor.\n".to_string(),
+        })
+    }
+
+    // Erro do NICK ou do RESUME com um apelido banido
+    pub fn nick_rejection(&self, nick: &str) -> Option<String> {
+        let sanction = self.find(SanctionKind::BanNick, nick)?;
+        Some(match sanction.remaining() {
+            Some(remaining) => format!("O apelido '{}' está banido deste servidor por mais {}.\n", nick, format_duration(remaining)),
+            None => format!("O apelido '{}' está banido deste servidor.\n", nick),
+        })
+    }
+
+    // Erro do chat para um jogador silenciado
+    pub fn mute_notice(&self, nick: &str) -> Option<String> {
+        let sanction = self.find(SanctionKind::Mute, nick)?;
+        Some(match sanction.remaining() {
+            Some(remaining) => format!("Você está silenciado por mais {} e não pode usar o chat.\n", format_duration(remaining)),
+            None => "Você está silenciado e não pode usar o chat.\n".to_string(),
+        })
+    }
+
+    fn find(&self, kind: SanctionKind, target: &str) -> Option<Sanction> {
+        self.active().into_iter().find(|sanction| sanction.kind == kind && sanction.target == target)
+    }
+
+    // Uma nova punição para o mesmo alvo substitui a anterior, com o prazo novo
+    fn apply(&self, kind: SanctionKind, target: &str, duration: Option<Duration>) -> Sanction {
+        let sanction = Sanction {
+            kind,
+            target: target.to_string(),
+            expires_at: duration.map(|duration| now() + duration.as_secs()),
+        };
+        {
+            let mut sanctions = self.sanctions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            sanctions.retain(|existing| existing.kind != kind || existing.target != target);
+            sanctions.push(sanction.clone());
+        }
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.save_sanction(&sanction) {
+                error!(%err, "erro ao guardar o banimento");
+            }
+        }
+        info!(kind = kind.name(), target, ?duration, "punição aplicada pelo administrador");
+        sanction
+    }
+
+    fn lift(&self, kind: SanctionKind, target: &str) -> bool {
+        let active = self.find(kind, target).is_some();
+        {
+            let mut sanctions = self.sanctions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            sanctions.retain(|existing| existing.kind != kind || existing.target != target);
+        }
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.remove_sanction(kind, target) {
+                error!(%err, "erro ao apagar o banimento");
+            }
+        }
+        if active {
+            info!(kind = kind.name(), target, "punição retirada pelo administrador");
+        }
+        active
+    }
+}
+
+// Lista do comando bans do console
+pub fn format_sanctions(sanctions: &[Sanction]) -> String {
+    if sanctions.is_empty() {
+        return "Nenhuma punição em vigor.\n".to_string();
+    }
+    let mut list = String::from("Punições em vigor:\n");
+    for sanction in sanctions {
+        let remaining = match sanction.remaining() {
+            Some(remaining) => format!("falta {}", format_duration(remaining)),
+            None => "para sempre".to_string(),
+        };
+        list.push_str(&format!("  {}: {}, {}\n", sanction.kind.label(), sanction.target, remaining));
+    }
+    list
+}
+
+// Duração escrita como 30s, 15m, 2h ou 7d
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let unit = text.chars().last()?;
+    let amount: u64 = text[..text.len() - unit.len_utf8()].parse().ok().filter(|amount| *amount > 0)?;
+    let secs = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    amount.checked_mul(secs).map(Duration::from_secs)
+}
+
+// Tempo restante com as duas maiores unidades, como 2d03h, 1h05m ou 4m30s
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        3600..=86_399 => format!("{}h{:02}m", secs / 3600, secs / 60 % 60),
+        _ => format!("{}d{:02}h", secs / 86_400, secs / 3600 % 24),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_use_a_single_unit() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("15m"), Some(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(2 * 3600)));
+        assert_eq!(parse_duration("7d"), Some(Duration::from_secs(7 * 86_400)));
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("2x"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 5 * 60)), "3h05m");
+    }
+
+    #[test]
+    fn bans_tell_nicks_from_addresses_and_can_be_lifted() {
+        let moderation = Moderation::load(None, WordFilter::default(), FilterLevel::Normal);
+        moderation.ban("troll", None);
+        moderation.ban("::ffff:10.0.0.7", Some(Duration::from_secs(3600)));
+
+        assert!(moderation.nick_rejection("troll").is_some());
+        assert!(moderation.ip_rejection("10.0.0.7".parse().unwrap()).unwrap().contains("por mais"));
+        assert!(moderation.ip_rejection("10.0.0.8".parse().unwrap()).is_none());
+        assert!(moderation.mute_notice("troll").is_none());
+
+        assert!(moderation.unban("troll"));
+        assert!(!moderation.unban("troll"));
+        assert!(moderation.nick_rejection("troll").is_none());
+    }
+}
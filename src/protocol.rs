@@ -0,0 +1,289 @@
+use serde::{Serialize, Deserialize};
+
+// Versão do protocolo falada por este servidor e pelos clientes do projeto
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// Capacidades que o servidor sabe negociar no HELLO. "bot" é para programas que jogam sozinhos:
+// só JSON, sem cores e com um your_turn a cada vez do jogador.
+pub const CAPABILITIES: [&str; 6] = ["json", "color", "utf8", "delta", "confirm", "bot"];
+
+// Idiomas das mensagens do servidor; o primeiro é o padrão
+pub const LANGUAGES: [&str; 2] = ["pt", "en"];
+
+// Código estável de um erro, para que os programas reajam sem ler o texto (como destacar a
+// coluna cheia). Vai no JSON em snake_case: not_your_turn, invalid_column, column_full...
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotYourTurn,
+    InvalidColumn,
+    ColumnFull,
+    InvalidCell,
+    InvalidMove,
+    GameNotStarted,
+    GameFinished,
+    GamePaused,
+    Spectator,
+    NickRequired,
+    NickTaken,
+    RoomNotFound,
+    RoomFull,
+    UnknownCommand,
+    RateLimited,
+    LineTooLong,
+    // Os demais erros, e os códigos que esta versão ainda não conhece
+    #[serde(other)]
+    Error,
+}
+
+// Mensagens enviadas pelo servidor no modo JSON, uma por linha
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Info {
+        message: String,
+    },
+    Error {
+        message: String,
+        // Código estável do erro, para programas; a mensagem vem no idioma da conexão
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<ErrorCode>,
+    },
+    Rooms {
+        rooms: Vec<RoomInfo>,
+    },
+    State {
+        mode: String,
+        board: Vec<Vec<i32>>, // Casas como em game::Cell::value; 2 e -2 são coringas do Power Up
+        current_turn: i32,
+        you: i32, // Símbolo do jogador que recebe a mensagem
+        // Casas onde quem está com a vez pode jogar, para o cliente desabilitar o resto; no
+        // lig4, uma por coluna com espaço. Vazia com a partida encerrada ou à espera de jogadores.
+        #[serde(default)]
+        legal_moves: Vec<LegalMove>,
+    },
+    // Com a capacidade "delta", só as casas que mudaram desde o último estado enviado
+    Delta {
+        row: usize,
+        col: usize,
+        val: i32,
+    },
+    // Com a capacidade "confirm", a jogada proposta: a casa onde a peça fica e o tabuleiro como
+    // ficaria. Nada muda até o cliente enviar /confirm; /cancel descarta a proposta.
+    Preview {
+        row: usize,
+        col: usize,
+        board: Vec<Vec<i32>>,
+    },
+    // Com a capacidade "bot", enviado depois do estado sempre que a vez é do jogador, com as
+    // casas onde ele pode jogar. Cada uma pode voltar como está, como a jogada em JSON.
+    YourTurn {
+        you: i32,
+        legal_moves: Vec<LegalMove>,
+    },
+    // Troca de vez que acompanha os deltas, com as jogadas possíveis como no estado
+    Turn {
+        current_turn: i32,
+        #[serde(default)]
+        legal_moves: Vec<LegalMove>,
+    },
+    // Relógio de xadrez, enviado depois do estado quando a sala usa um. O tempo de quem está
+    // com a vez continua correndo a partir daqui.
+    Clock {
+        x_ms: u64,
+        o_ms: u64,
+    },
+    GameOver {
+        winner: i32, // 1, -1 ou 0 para empate
+        you: i32,
+        // Casas da sequência que decidiu a partida; vazia no empate, no reversi e na desistência
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        line: Vec<LegalMove>,
+    },
+    Chat {
+        from: String, // Apelido de quem enviou
+        message: String,
+        time: String, // Horário UTC, HH:MM:SS
+    },
+    History {
+        player: String,
+        games: Vec<GameSummary>,
+    },
+    Leaderboard {
+        entries: Vec<RatingEntry>,
+    },
+    Export {
+        id: i64,
+        text: String, // Partida em texto, no formato do /export
+    },
+    // Com /bell on, avisa que chegou a vez do jogador ou que o oponente entrou; o cliente toca
+    // um som ou pisca a janela
+    Bell,
+    Hello {
+        version: u32,      // Versão combinada, a menor entre a do cliente e a do servidor
+        caps: Vec<String>, // Capacidades pedidas que o servidor aceitou
+        lang: String,
+    },
+}
+
+// Casa de uma jogada possível, ou da sequência vencedora; no lig4, row é a linha onde a peça cairia
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct LegalMove {
+    pub row: usize,
+    pub col: usize,
+}
+
+// Apresentação do cliente ao conectar: HELLO v1 caps=json,color,utf8,delta,confirm lang=pt.
+// caps e lang são opcionais; sem caps o cliente recebe texto simples, sem cores e em ASCII.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hello {
+    pub version: u32,
+    pub caps: Vec<String>,
+    pub lang: Option<String>,
+}
+
+impl Hello {
+    pub fn parse(line: &str) -> Result<Hello, String> {
+        const USAGE: &str = "Use o formato: HELLO v1 caps=json,color,utf8 lang=pt\n";
+        let mut parts = line.split_whitespace();
+        if parts.next() != Some("HELLO") {
+            return Err(USAGE.to_string());
+        }
+        let version = parts
+            .next()
+            .and_then(|version| version.strip_prefix('v'))
+            .and_then(|version| version.parse::<u32>().ok())
+            .ok_or_else(|| USAGE.to_string())?;
+
+        let mut hello = Hello { version, caps: Vec::new(), lang: None };
+        for part in parts {
+            match part.split_once('=') {
+                Some(("caps", caps)) => {
+                    hello.caps = caps.split(',').filter(|cap| !cap.is_empty()).map(|cap| cap.to_lowercase()).collect();
+                }
+                Some(("lang", lang)) => hello.lang = Some(lang.to_lowercase()),
+                // Campos desconhecidos são ignorados, para que versões novas continuem conversando
+                _ => {}
+            }
+        }
+        Ok(hello)
+    }
+
+    pub fn has(&self, cap: &str) -> bool {
+        self.caps.iter().any(|own| own == cap)
+    }
+
+    // Linha enviada pelo cliente
+    pub fn to_line(&self) -> String {
+        let mut line = format!("HELLO v{}", self.version);
+        if !self.caps.is_empty() {
+            line.push_str(&format!(" caps={}", self.caps.join(",")));
+        }
+        if let Some(lang) = &self.lang {
+            line.push_str(&format!(" lang={}", lang));
+        }
+        line.push('\n');
+        line
+    }
+}
+
+// Resumo de uma sala para a listagem do lobby
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoomInfo {
+    pub name: String,
+    pub mode: String,
+    pub players: Vec<String>,
+    #[serde(default = "two_seats")]
+    pub seats: usize, // Lugares da mesa; o modo anel tem mais de dois
+    pub spectators: usize,
+    pub status: String,
+}
+
+fn two_seats() -> usize {
+    2
+}
+
+// Resumo de uma partida encerrada para o comando /history
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameSummary {
+    pub id: i64,
+    pub mode: String,
+    pub player_x: String,
+    pub player_o: String,
+    pub winner: i32, // 1, -1 ou 0 para empate
+    #[serde(default)]
+    pub forfeit: bool, // Decidida por desistência ou W.O.
+    pub moves: usize,
+    pub started_at: u64, // Segundos desde a época Unix
+    pub duration_secs: u64,
+    #[serde(default)]
+    pub series: Option<SeriesScore>, // Placar final, quando a partida foi uma série melhor de N
+}
+
+// Placar de uma série melhor de N: leva a série quem vencer mais da metade dos jogos.
+// Empates não contam para nenhum dos lados.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SeriesScore {
+    pub best_of: u32,
+    pub wins_x: u32,
+    pub wins_o: u32,
+}
+
+impl SeriesScore {
+    pub fn new(best_of: u32) -> Self {
+        SeriesScore { best_of, wins_x: 0, wins_o: 0 }
+    }
+
+    // Vitórias necessárias para levar a série
+    pub fn needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    // Conta o resultado de um jogo: 1 ou -1 para o vencedor, 0 para empate
+    pub fn add_result(&mut self, winner: i32) {
+        match winner {
+            1 => self.wins_x += 1,
+            -1 => self.wins_o += 1,
+            _ => {}
+        }
+    }
+
+    // Dá a série ao jogador, como quando o oponente abandona a sala no meio dela
+    pub fn concede(&mut self, winner: i32) {
+        if winner == 1 {
+            self.wins_x = self.needed();
+        } else {
+            self.wins_o = self.needed();
+        }
+    }
+
+    // Vencedor da série, quando ela já está decidida
+    pub fn winner(&self) -> Option<i32> {
+        if self.wins_x >= self.needed() {
+            Some(1)
+        } else if self.wins_o >= self.needed() {
+            Some(-1)
+        } else {
+            None
+        }
+    }
+}
+
+// Linha do ranking mostrado pelo /top
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RatingEntry {
+    pub nick: String,
+    pub rating: i32,
+    pub games: usize,
+}
+
+// Números de um jogador no histórico, servidos pela API HTTP
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlayerStats {
+    pub nick: String,
+    pub rating: i32,
+    pub games: usize, // Partidas gravadas no histórico, ranqueadas ou não
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
@@ -0,0 +1,207 @@
+use crate::game::{has_line, is_valid_move, GameState, Move, COLS};
+
+// Quebra-cabeças do lig4 padrão: posições de partida em que quem joga vence em um número
+// certo de jogadas suas, contra qualquer defesa. A solução não é guardada; cada jogada do
+// jogador é conferida por uma busca completa até a profundidade do quebra-cabeça, então
+// qualquer caminho que vença a tempo vale.
+pub struct Puzzle {
+    pub moves: u32, // Jogadas de quem joga até vencer, contando a última
+    rows: [&'static str; 6], // Linhas de cima para baixo: X, O ou . para casa vazia
+}
+
+impl Puzzle {
+    // Posição inicial. O X começa, como nas partidas comuns: com o mesmo número de peças
+    // joga o X, e com uma peça a mais do X joga o O.
+    pub fn position(&self) -> GameState {
+        let grid: Vec<Vec<i32>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|cell| match cell {
+                        'X' => 1,
+                        'O' => -1,
+                        _ => 0,
+                    })
+                    .collect()
+            })
+            .collect();
+        let count = |symbol: i32| grid.iter().flatten().filter(|cell| **cell == symbol).count();
+        let current_turn = if count(1) > count(-1) { -1 } else { 1 };
+        GameState::from_grid(&grid, current_turn, crate::game::WIN_LENGTH)
+    }
+}
+
+// Posições tiradas de partidas sorteadas, todas com uma única primeira jogada que vence a tempo
+pub const PUZZLES: [Puzzle; 12] = [
+    Puzzle {
+        moves: 2,
+        rows: [".......", ".......", ".......", ".....X.", "..OXOX.", "O.XOXO."],
+    },
+    Puzzle {
+        moves: 2,
+        rows: ["...X...", "...O...", "...X.X.", "..OXOX.", ".OXXOO.", ".OXOOX."],
+    },
+    Puzzle {
+        moves: 2,
+        rows: ["...XX..", "...XX..", "...OO..", "..OXOO.", ".XOOXX.", ".OXOXOX"],
+    },
+    Puzzle {
+        moves: 2,
+        rows: ["...X...", "...X...", "...O...", "...X...", "...XOO.", ".O.XOX."],
+    },
+    Puzzle {
+        moves: 2,
+        rows: [".......", "..X....", "..O....", "..XOO.O", "..OXX.X", "..OXXXO"],
+    },
+    Puzzle {
+        moves: 2,
+        rows: [".......", ".......", "...OO..", "...XX..", "..OXOX.", "..OXOX."],
+    },
+    Puzzle {
+        moves: 3,
+        rows: ["...X.X.", "...X.X.", "..OXXX.", "..OOXO.", ".OXOOXO", "OOXOXXO"],
+    },
+    Puzzle {
+        moves: 3,
+        rows: [".......", "..X....", "..X....", "..O...X", "..X.O.O", ".XOOXOX"],
+    },
+    Puzzle {
+        moves: 3,
+        rows: [".......", ".......", ".....X.", "..O..O.", ".XXO.X.", ".XXOXOO"],
+    },
+    Puzzle {
+        moves: 3,
+        rows: [".......", "..OO...", "..XO...", "..XX...", "..OXO..", "..XOXXO"],
+    },
+    Puzzle {
+        moves: 3,
+        rows: [".......", ".......", "..X.O..", "..O.X..", ".OO.X..", "XOX.OXX"],
+    },
+    Puzzle {
+        moves: 3,
+        rows: [".......", "...O...", "...O...", "...XX..", "...OXO.", ".OXXOX."],
+    },
+];
+
+// Jogadas do desafio diário: um pouco mais difícil que a maioria dos quebra-cabeças fixos
+pub const DAILY_MOVES: u32 = 3;
+
+// Quebra-cabeça sorteado a partir da semente, como os fixos foram achados: posições de
+// partidas aleatórias, com preferência pelo centro, até uma em que quem joga vence em
+// exatamente `moves` jogadas e só uma primeira jogada serve. A mesma semente dá sempre a
+// mesma posição, em qualquer máquina.
+pub fn generate(seed: u64, moves: u32) -> GameState {
+    // Xorshift: simples, sem dependências, e o mesmo em toda plataforma
+    let mut state = seed | 1;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    loop {
+        let mut game_state = GameState::new();
+        let pieces = 10 + (next() % 16) as usize;
+        while game_state.piece_count() < pieces && game_state.result().is_none() {
+            let col = (next() % COLS as u64 + next() % COLS as u64).div_ceil(2) as usize;
+            let _ = game_state.apply_move(&Move { col });
+        }
+        if game_state.result().is_some() || wins_within(&game_state, moves - 1) {
+            continue;
+        }
+        let solutions = column_order(&game_state).filter(|col| move_wins_within(&game_state, *col, moves)).count();
+        if solutions == 1 {
+            return game_state;
+        }
+    }
+}
+
+// Se quem joga vence em até `moves` jogadas suas, contra qualquer defesa
+pub fn wins_within(game_state: &GameState, moves: u32) -> bool {
+    winning_move(game_state, moves).is_some()
+}
+
+// Uma jogada que vence em até `moves` jogadas, do centro para as bordas
+pub fn winning_move(game_state: &GameState, moves: u32) -> Option<usize> {
+    column_order(game_state).find(|col| move_wins_within(game_state, *col, moves))
+}
+
+// Se jogar na coluna vence em até `moves` jogadas, contando esta
+pub fn move_wins_within(game_state: &GameState, col: usize, moves: u32) -> bool {
+    let mut next = game_state.clone();
+    if moves == 0 || next.apply_move(&Move { col }).is_err() {
+        return false;
+    }
+    if has_line(&next, game_state.current_turn) {
+        return true;
+    }
+    if moves == 1 || next.result().is_some() {
+        return false;
+    }
+    // Toda resposta do adversário ainda perde
+    column_order(&next).all(|reply| {
+        let mut after = next.clone();
+        after.apply_move(&Move { col: reply }).is_ok()
+            && !has_line(&after, next.current_turn)
+            && wins_within(&after, moves - 1)
+    })
+}
+
+// Resposta do adversário: a que mais adia a derrota, ou a que escapa dela se o jogador
+// errou. None se não há jogadas.
+pub fn defend(game_state: &GameState, moves: u32) -> Option<usize> {
+    column_order(game_state).max_by_key(|col| {
+        let mut after = game_state.clone();
+        let _ = after.apply_move(&Move { col: *col });
+        if has_line(&after, game_state.current_turn) {
+            return moves + 2;
+        }
+        // Jogadas que o jogador ainda precisa para vencer; sem vitória a tempo, o máximo
+        (1..=moves).find(|needed| wins_within(&after, *needed)).unwrap_or(moves + 1)
+    })
+}
+
+// Colunas válidas, do centro para as bordas
+fn column_order(game_state: &GameState) -> std::vec::IntoIter<usize> {
+    let cols = game_state.cols();
+    let mut order: Vec<usize> = (0..cols).filter(|col| is_valid_move(game_state, &Move { col: *col })).collect();
+    order.sort_by_key(|col| (2 * *col).abs_diff(cols - 1));
+    order.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_puzzle_needs_exactly_its_number_of_moves() {
+        for (index, puzzle) in PUZZLES.iter().enumerate() {
+            let position = puzzle.position();
+            assert!(position.result().is_none(), "quebra-cabeça {}", index + 1);
+            assert!(wins_within(&position, puzzle.moves), "quebra-cabeça {}", index + 1);
+            assert!(!wins_within(&position, puzzle.moves - 1), "quebra-cabeça {}", index + 1);
+        }
+    }
+
+    #[test]
+    fn generated_puzzles_depend_only_on_the_seed() {
+        let position = generate(20261015, DAILY_MOVES);
+        assert_eq!(position, generate(20261015, DAILY_MOVES));
+        assert!(wins_within(&position, DAILY_MOVES));
+        assert!(!wins_within(&position, DAILY_MOVES - 1));
+        assert_ne!(position, generate(20261016, DAILY_MOVES));
+    }
+
+    #[test]
+    fn the_defence_makes_the_player_use_every_move() {
+        let puzzle = &PUZZLES[PUZZLES.len() - 1];
+        let mut position = puzzle.position();
+        let col = winning_move(&position, puzzle.moves).unwrap();
+        position.apply_move(&Move { col }).unwrap();
+        let reply = defend(&position, puzzle.moves - 1).unwrap();
+        position.apply_move(&Move { col: reply }).unwrap();
+        assert!(wins_within(&position, puzzle.moves - 1));
+        assert!(!wins_within(&position, puzzle.moves - 2));
+    }
+}
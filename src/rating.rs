@@ -0,0 +1,69 @@
+// Cálculo do rating ELO usado no ranking dos jogadores
+
+pub const DEFAULT_RATING: i32 = 1200;
+
+// Quanto uma única partida pode mover o rating
+const K_FACTOR: f64 = 32.0;
+
+// Probabilidade esperada de vitória de quem tem `rating` contra `opponent`
+pub fn expected_score(rating: i32, opponent: i32) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) as f64 / 400.0))
+}
+
+// Novos ratings de X e O depois de uma partida. O resultado segue o resto do
+// servidor: 1 para vitória do X, -1 para vitória do O e 0 para empate.
+pub fn updated_ratings(rating_x: i32, rating_o: i32, winner: i32) -> (i32, i32) {
+    updated_ratings_with_handicap(rating_x, rating_o, winner, 0)
+}
+
+// Como updated_ratings, numa partida com vantagem: `handicap_x` pontos que o X deu ao O,
+// negativos se foi o O quem deu. A expectativa de quem dá a vantagem cai na mesma medida,
+// então vencer dando vantagem rende mais e perder custa menos.
+pub fn updated_ratings_with_handicap(rating_x: i32, rating_o: i32, winner: i32, handicap_x: i32) -> (i32, i32) {
+    let score_x = match winner {
+        1 => 1.0,
+        -1 => 0.0,
+        _ => 0.5,
+    };
+
+    let change = (K_FACTOR * (score_x - expected_score(rating_x - handicap_x, rating_o))).round() as i32;
+    (rating_x + change, rating_o - change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_ratings_move_by_half_the_k_factor() {
+        assert_eq!(updated_ratings(1200, 1200, 1), (1216, 1184));
+        assert_eq!(updated_ratings(1200, 1200, -1), (1184, 1216));
+        assert_eq!(updated_ratings(1200, 1200, 0), (1200, 1200));
+    }
+
+    #[test]
+    fn the_favourite_gains_little_and_loses_a_lot() {
+        // O X, 200 pontos acima, tem cerca de 76% de expectativa
+        assert_eq!(updated_ratings(1400, 1200, 1), (1408, 1192));
+        assert_eq!(updated_ratings(1400, 1200, -1), (1376, 1224));
+        // No empate, o favorito perde pontos
+        assert_eq!(updated_ratings(1400, 1200, 0), (1392, 1208));
+        assert_eq!(updated_ratings(1200, 1400, 0), (1208, 1392));
+    }
+
+    #[test]
+    fn handicap_raises_the_reward_of_whoever_gives_it() {
+        assert_eq!(updated_ratings_with_handicap(1200, 1200, 1, 200), (1224, 1176));
+        assert_eq!(updated_ratings_with_handicap(1200, 1200, -1, 200), (1192, 1208));
+    }
+
+    #[test]
+    fn what_one_player_gains_the_other_loses() {
+        for (rating_x, rating_o) in [(1200, 1200), (1523, 987), (900, 2100)] {
+            for winner in [1, 0, -1] {
+                let (new_x, new_o) = updated_ratings(rating_x, rating_o, winner);
+                assert_eq!(new_x - rating_x, rating_o - new_o, "{} x {}, resultado {}", rating_x, rating_o, winner);
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+// Desenho do tabuleiro em texto, com cores ANSI opcionais. Em UTF-8 o tabuleiro ganha
+// bordas e as peças viram discos (● ○); sem isso, fica em ASCII com X, O e +.
+
+use crate::ring;
+
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+const RESET: &str = "\x1b[0m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const MAGENTA: &str = "\x1b[1;35m";
+const CYAN: &str = "\x1b[1;36m";
+const GREEN: &str = "\x1b[1;32m";
+const DIM: &str = "\x1b[2m";
+const INVERSE: &str = "\x1b[7m";
+const BLINK: &str = "\x1b[5m";
+
+// Destaque de uma casa no tabuleiro do lig4
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mark {
+    Hint,   // Coluna sugerida por /hint
+    Win,    // O jogador vence jogando aqui
+    Threat, // O oponente vence jogando aqui
+    Line,   // Peça da sequência que decidiu a partida; mantém a peça e pisca
+}
+
+impl Mark {
+    // As casas vazias ganham o símbolo da marca; a da sequência mantém a peça
+    fn paint(self, cell: i32, color: bool, unicode: bool) -> String {
+        let (glyph, style) = match self {
+            Mark::Hint => ("o", GREEN),
+            Mark::Win => ("!", GREEN),
+            Mark::Threat => ("#", MAGENTA),
+            Mark::Line => return line_cell_str(cell, color, unicode),
+        };
+        paint(glyph, &format!("{}{}", style, INVERSE), color)
+    }
+}
+
+// Tabuleiro do lig4, com os números das colunas (a partir de 1) embaixo. As casas marcadas
+// aparecem em destaque; se uma casa tem mais de uma marca, vale a primeira da lista.
+pub fn render_connect_four<R: AsRef<[i32]>>(
+    board: &[R],
+    last_move: Option<(usize, usize)>,
+    marks: &[(usize, usize, Mark)],
+    color: bool,
+    unicode: bool,
+) -> String {
+    let mut out = screen_start(color);
+    let cols = board.first().map_or(0, |row| row.as_ref().len());
+
+    push_border(&mut out, "", cols, '┌', '┐', unicode);
+    for (i, row) in board.iter().enumerate() {
+        push_side(&mut out, "", unicode);
+        for (j, cell) in row.as_ref().iter().enumerate() {
+            out.push(' ');
+            if let Some((_, _, mark)) = marks.iter().find(|(row, col, _)| (*row, *col) == (i, j)) {
+                out.push_str(&mark.paint(*cell, color, unicode));
+                continue;
+            }
+            out.push_str(&cell_str(*cell, last_move == Some((i, j)), color, unicode));
+        }
+        push_side(&mut out, " ", unicode);
+        out.push('\n');
+    }
+    push_border(&mut out, "", cols, '└', '┘', unicode);
+
+    // Colunas numeradas a partir de 1, como o jogador digita
+    if unicode {
+        out.push(' ');
+    }
+    for col in 1..=cols {
+        out.push_str(&format!("{:>2}", col));
+    }
+    out.push('\n');
+    out
+}
+
+// Tabuleiro do modo anel: como o do lig4, com a letra de cada jogador da roda. As casas da
+// sequência vencedora piscam em destaque, ou ficam em minúsculas sem cores.
+pub fn render_ring<R: AsRef<[i32]>>(
+    board: &[R],
+    last_move: Option<(usize, usize)>,
+    line: &[(usize, usize)],
+    color: bool,
+    unicode: bool,
+) -> String {
+    let mut out = screen_start(color);
+    let cols = board.first().map_or(0, |row| row.as_ref().len());
+
+    push_border(&mut out, "", cols, '┌', '┐', unicode);
+    for (i, row) in board.iter().enumerate() {
+        push_side(&mut out, "", unicode);
+        for (j, cell) in row.as_ref().iter().enumerate() {
+            out.push(' ');
+            let style = match cell {
+                0 => {
+                    out.push_str(&paint(empty_glyph(unicode), DIM, color));
+                    continue;
+                }
+                1 => RED,
+                2 => YELLOW,
+                3 => CYAN,
+                _ => GREEN,
+            };
+            let glyph = ring::glyph(*cell).to_string();
+            if line.contains(&(i, j)) && color {
+                out.push_str(&format!("{}{}{}{}{}", style, INVERSE, BLINK, glyph, RESET));
+            } else if line.contains(&(i, j)) {
+                out.push_str(&glyph.to_lowercase());
+            } else if last_move == Some((i, j)) && color {
+                out.push_str(&format!("{}{}{}{}", style, INVERSE, glyph, RESET));
+            } else {
+                out.push_str(&paint(&glyph, style, color));
+            }
+        }
+        push_side(&mut out, " ", unicode);
+        out.push('\n');
+    }
+    push_border(&mut out, "", cols, '└', '┘', unicode);
+
+    if unicode {
+        out.push(' ');
+    }
+    for col in 1..=cols {
+        out.push_str(&format!("{:>2}", col));
+    }
+    out.push('\n');
+    out
+}
+
+// Tabuleiro do reversi, com linhas e colunas numeradas a partir de 1 e as jogadas possíveis
+// marcadas com '.' (◦ em UTF-8)
+pub fn render_reversi<R: AsRef<[i32]>>(
+    board: &[R],
+    last_move: Option<(usize, usize)>,
+    legal_moves: &[(usize, usize)],
+    color: bool,
+    unicode: bool,
+) -> String {
+    let mut out = screen_start(color);
+    let cols = board.first().map_or(0, |row| row.as_ref().len());
+
+    out.push_str(if unicode { "  " } else { " " });
+    for col in 1..=cols {
+        out.push_str(&format!(" {}", col));
+    }
+    out.push('\n');
+
+    push_border(&mut out, " ", cols, '┌', '┐', unicode);
+    for (i, row) in board.iter().enumerate() {
+        out.push_str(&(i + 1).to_string());
+        push_side(&mut out, "", unicode);
+        for (j, cell) in row.as_ref().iter().enumerate() {
+            out.push(' ');
+            if *cell == 0 && legal_moves.contains(&(i, j)) {
+                out.push_str(&paint(if unicode { "◦" } else { "." }, YELLOW, color));
+            } else {
+                out.push_str(&cell_str(*cell, last_move == Some((i, j)), color, unicode));
+            }
+        }
+        push_side(&mut out, " ", unicode);
+        out.push('\n');
+    }
+    push_border(&mut out, " ", cols, '└', '┘', unicode);
+    out
+}
+
+// Linha de cima ou de baixo da moldura em UTF-8, depois da margem dada
+fn push_border(out: &mut String, margin: &str, cols: usize, left: char, right: char, unicode: bool) {
+    if unicode {
+        out.push_str(&format!("{}{}{}{}\n", margin, left, "─".repeat(cols * 2 + 1), right));
+    }
+}
+
+// Lateral da moldura em UTF-8, depois do recuo dado
+fn push_side(out: &mut String, indent: &str, unicode: bool) {
+    if unicode {
+        out.push_str(indent);
+        out.push('│');
+    }
+}
+
+// Com cores, limpa a tela; sem cores (terminais simples) apenas separa os quadros
+fn screen_start(color: bool) -> String {
+    if color {
+        CLEAR_SCREEN.to_string()
+    } else {
+        "\n\n".to_string()
+    }
+}
+
+// Põe uma linha de cabeçalho, como o relógio da partida, logo acima do tabuleiro: depois da
+// limpeza de tela ou das linhas em branco que separam o quadro do texto anterior
+pub fn with_header(frame: &str, header: &str) -> String {
+    let body = frame.strip_prefix(CLEAR_SCREEN).unwrap_or(frame).trim_start_matches('\n');
+    let start = &frame[..frame.len() - body.len()];
+    format!("{}{}\n{}", start, header, body)
+}
+
+// Centraliza o quadro em um terminal com a largura informada, sem contar as
+// sequências ANSI. A limpeza de tela, se houver, continua no começo.
+pub fn center(frame: &str, width: usize) -> String {
+    let (clear, body) = match frame.strip_prefix(CLEAR_SCREEN) {
+        Some(body) => (CLEAR_SCREEN, body),
+        None => ("", frame),
+    };
+    let frame_width = body.lines().map(visible_width).max().unwrap_or(0);
+    if frame_width >= width {
+        return frame.to_string();
+    }
+
+    let padding = " ".repeat((width - frame_width) / 2);
+    let mut out = clear.to_string();
+    for line in body.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            out.push_str(line);
+        } else {
+            out.push_str(&padding);
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+// Atualização de um quadro para o seguinte só com as casas que mudaram, posicionando o
+// cursor em cada trecho alterado. A posição do cursor é salva e restaurada, para o texto
+// que vem depois do quadro continuar onde estava. None quando os quadros não podem ser
+// comparados (sem limpeza de tela ou com outro número de linhas): é preciso redesenhar.
+pub fn diff_frames(old: &str, new: &str) -> Option<String> {
+    let old_lines: Vec<&str> = old.strip_prefix(CLEAR_SCREEN)?.lines().collect();
+    let new_lines: Vec<&str> = new.strip_prefix(CLEAR_SCREEN)?.lines().collect();
+    if old_lines.len() != new_lines.len() {
+        return None;
+    }
+
+    let mut out = String::new();
+    for (row, (old_line, new_line)) in old_lines.iter().zip(&new_lines).enumerate() {
+        if old_line == new_line {
+            continue;
+        }
+        let old_cells = cells(old_line);
+        let new_cells = cells(new_line);
+        // Linha com outra largura, como o placar do reversi: reescreve ela inteira
+        if old_cells.len() != new_cells.len() {
+            out.push_str(&format!("\x1b[{};1H{}\x1b[K", row + 1, new_line));
+            continue;
+        }
+
+        let mut col = 0;
+        while col < new_cells.len() {
+            if old_cells[col] == new_cells[col] {
+                col += 1;
+                continue;
+            }
+            out.push_str(&format!("\x1b[{};{}H", row + 1, col + 1));
+            while col < new_cells.len() && old_cells[col] != new_cells[col] {
+                out.push_str(new_cells[col]);
+                col += 1;
+            }
+        }
+    }
+
+    if out.is_empty() {
+        return Some(out);
+    }
+    Some(format!("\x1b7{}\x1b8", out))
+}
+
+// Divide a linha nas posições da tela: cada trecho tem as sequências de cor que vêm
+// antes do caractere visível e o RESET logo depois dele, se houver
+fn cells(line: &str) -> Vec<&str> {
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut in_escape = false;
+    for (index, ch) in line.char_indices() {
+        if in_escape {
+            in_escape = !ch.is_ascii_alphabetic();
+        } else if ch == '\x1b' {
+            in_escape = true;
+        } else {
+            let mut end = index + ch.len_utf8();
+            if line[end..].starts_with(RESET) {
+                end += RESET.len();
+            }
+            cells.push(&line[start..end]);
+            start = end;
+        }
+    }
+    cells
+}
+
+// Largura de uma linha na tela, ignorando as sequências de cor
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for ch in line.chars() {
+        if in_escape {
+            in_escape = !ch.is_ascii_alphabetic();
+        } else if ch == '\x1b' {
+            in_escape = true;
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+fn cell_str(cell: i32, highlight: bool, color: bool, unicode: bool) -> String {
+    let (glyph, style) = match (cell, unicode) {
+        (1, false) => ("X", RED),
+        (1, true) => ("●", RED),
+        (-1, false) => ("O", YELLOW),
+        (-1, true) => ("○", YELLOW),
+        // Coringa do Power Up, de qualquer jogador
+        (2 | -2, false) => ("*", MAGENTA),
+        (2 | -2, true) => ("◆", MAGENTA),
+        _ => (empty_glyph(unicode), DIM),
+    };
+
+    if highlight && color {
+        format!("{}{}{}{}", style, INVERSE, glyph, RESET)
+    } else {
+        paint(glyph, style, color)
+    }
+}
+
+// Peça da sequência vencedora: em destaque e piscando, onde o terminal pisca. Sem cores,
+// a peça muda de desenho (x e o minúsculos, ◉ e ◎ em UTF-8).
+fn line_cell_str(cell: i32, color: bool, unicode: bool) -> String {
+    if color {
+        let style = match cell {
+            1 => RED,
+            -1 => YELLOW,
+            _ => MAGENTA,
+        };
+        return format!("{}{}{}{}{}", style, INVERSE, BLINK, cell_str(cell, false, false, unicode), RESET);
+    }
+    match (cell, unicode) {
+        (1, false) => "x".to_string(),
+        (1, true) => "◉".to_string(),
+        (-1, false) => "o".to_string(),
+        (-1, true) => "◎".to_string(),
+        _ => cell_str(cell, false, false, unicode),
+    }
+}
+
+fn empty_glyph(unicode: bool) -> &'static str {
+    if unicode { "·" } else { "+" }
+}
+
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", style, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_touches_only_the_changed_cells() {
+        let empty = [[0; 4]; 3];
+        let mut played = empty;
+        played[2][1] = 1;
+        let old = render_connect_four(&empty, None, &[], true, false);
+        let new = render_connect_four(&played, Some((2, 1)), &[], true, false);
+
+        let diff = diff_frames(&old, &new).unwrap();
+        assert!(diff.starts_with("\x1b7\x1b[3;4H"));
+        assert!(diff.ends_with(&format!("{}{}X{}\x1b8", RED, INVERSE, RESET)));
+        assert_eq!(diff_frames(&new, &new).as_deref(), Some(""));
+
+        // Outro tamanho de tabuleiro, ou quadros sem cor, pedem o quadro inteiro
+        assert!(diff_frames(&old, &render_connect_four(&[[0; 4]; 4], None, &[], true, false)).is_none());
+        assert!(diff_frames(&render_connect_four(&empty, None, &[], false, false), &old).is_none());
+    }
+
+    #[test]
+    fn marked_cells_show_without_colors() {
+        let marks = [(1, 2, Mark::Hint), (1, 0, Mark::Win), (1, 2, Mark::Threat)];
+        let frame = render_connect_four(&[[0; 3]; 2], None, &marks, false, false);
+        assert!(frame.ends_with(" + + +\n ! + o\n 1 2 3\n"));
+        let frame = render_connect_four(&[[0; 3]; 2], None, &[(0, 1, Mark::Threat)], false, false);
+        assert!(frame.ends_with(" + # +\n + + +\n 1 2 3\n"));
+
+        // A sequência vencedora mantém as peças e pisca com cores
+        let board = [[0, 0, 0], [1, 1, -1]];
+        let line = [(1, 0, Mark::Line), (1, 1, Mark::Line)];
+        assert!(render_connect_four(&board, None, &line, false, false).ends_with(" x x O\n 1 2 3\n"));
+        let frame = render_connect_four(&board, None, &line, true, false);
+        assert_eq!(frame.matches(&format!("{}{}{}X{}", RED, INVERSE, BLINK, RESET)).count(), 2);
+    }
+
+    #[test]
+    fn unicode_boards_have_frames_and_discs() {
+        let board = [[0, 0, 0], [1, -1, 0]];
+        let frame = render_connect_four(&board, None, &[], false, true);
+        assert!(frame.ends_with("┌───────┐\n│ · · · │\n│ ● ○ · │\n└───────┘\n  1 2 3\n"), "{}", frame);
+        let frame = render_reversi(&board, None, &[(0, 2)], false, true);
+        assert!(frame.ends_with("   1 2 3\n ┌───────┐\n1│ · · ◦ │\n2│ ● ○ · │\n └───────┘\n"), "{}", frame);
+
+        // A moldura não atrapalha a atualização só das casas que mudaram
+        let mut played = board;
+        played[0][2] = 1;
+        let old = render_connect_four(&board, None, &[], true, true);
+        let new = render_connect_four(&played, Some((0, 2)), &[], true, true);
+        assert!(diff_frames(&old, &new).unwrap().starts_with("\x1b7\x1b[2;7H"));
+    }
+}
@@ -0,0 +1,228 @@
+use serde::{Serialize, Deserialize};
+
+use crate::error::GameError;
+
+pub const REVERSI_SIZE: usize = 8;
+
+// As oito direções em que uma jogada pode cercar peças do adversário
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1), (-1, 0), (-1, 1),
+    (0, -1),           (0, 1),
+    (1, -1),  (1, 0),  (1, 1),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReversiState {
+    pub board: [[i32; REVERSI_SIZE]; REVERSI_SIZE], // Tabuleiro 8x8
+    pub current_turn: i32, // 1 para jogador 1 (X), -1 para jogador 2 (O)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReversiMove {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl ReversiState {
+    pub fn new() -> Self {
+        let mut board = [[0; REVERSI_SIZE]; REVERSI_SIZE];
+        // Posição inicial: X ocupa a diagonal d5/e4 e começa jogando
+        board[3][3] = -1;
+        board[3][4] = 1;
+        board[4][3] = 1;
+        board[4][4] = -1;
+
+        ReversiState {
+            board,
+            current_turn: 1,
+        }
+    }
+
+    // Retorna as peças do adversário que seriam viradas pela jogada
+    pub fn flips(&self, player_move: &ReversiMove, player_symbol: i32) -> Vec<(usize, usize)> {
+        let mut flipped = Vec::new();
+        if player_move.row >= REVERSI_SIZE
+            || player_move.col >= REVERSI_SIZE
+            || self.board[player_move.row][player_move.col] != 0
+        {
+            return flipped;
+        }
+
+        for (d_row, d_col) in DIRECTIONS {
+            let mut line = Vec::new();
+            let mut row = player_move.row as isize + d_row;
+            let mut col = player_move.col as isize + d_col;
+
+            while in_bounds(row, col) && self.board[row as usize][col as usize] == -player_symbol {
+                line.push((row as usize, col as usize));
+                row += d_row;
+                col += d_col;
+            }
+
+            // Só vira se a linha de peças adversárias termina em uma peça do jogador
+            if !line.is_empty() && in_bounds(row, col) && self.board[row as usize][col as usize] == player_symbol {
+                flipped.extend(line);
+            }
+        }
+
+        flipped
+    }
+
+    // Lista todas as jogadas que viram pelo menos uma peça
+    pub fn legal_moves(&self, player_symbol: i32) -> Vec<ReversiMove> {
+        let mut moves = Vec::new();
+        for row in 0..REVERSI_SIZE {
+            for col in 0..REVERSI_SIZE {
+                let player_move = ReversiMove { row, col };
+                if !self.flips(&player_move, player_symbol).is_empty() {
+                    moves.push(player_move);
+                }
+            }
+        }
+        moves
+    }
+
+    pub fn has_legal_move(&self, player_symbol: i32) -> bool {
+        !self.legal_moves(player_symbol).is_empty()
+    }
+
+    // Aplica a jogada do jogador da vez. Retorna true se o adversário
+    // ficou sem jogadas e teve que passar a vez.
+    pub fn apply_move(&mut self, player_move: &ReversiMove) -> Result<bool, GameError> {
+        let flipped = self.flips(player_move, self.current_turn);
+        if flipped.is_empty() {
+            return Err(GameError::NoFlips);
+        }
+
+        self.board[player_move.row][player_move.col] = self.current_turn;
+        for (row, col) in flipped {
+            self.board[row][col] = self.current_turn;
+        }
+
+        // O adversário só joga se tiver alguma jogada; senão a vez volta
+        if self.has_legal_move(-self.current_turn) {
+            self.current_turn = -self.current_turn;
+            Ok(false)
+        } else {
+            Ok(self.has_legal_move(self.current_turn))
+        }
+    }
+
+    // O jogo termina quando nenhum dos dois jogadores tem jogada
+    pub fn is_over(&self) -> bool {
+        !self.has_legal_move(1) && !self.has_legal_move(-1)
+    }
+
+    pub fn count(&self, player_symbol: i32) -> usize {
+        self.board
+            .iter()
+            .flatten()
+            .filter(|cell| **cell == player_symbol)
+            .count()
+    }
+
+    // Vencedor pela contagem de peças: Some(1), Some(-1) ou Some(0) para empate
+    pub fn final_result(&self) -> Option<i32> {
+        if !self.is_over() {
+            return None;
+        }
+
+        let x = self.count(1);
+        let o = self.count(-1);
+        Some(if x > o { 1 } else if o > x { -1 } else { 0 })
+    }
+}
+
+impl Default for ReversiState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn in_bounds(row: isize, col: isize) -> bool {
+    row >= 0 && row < REVERSI_SIZE as isize && col >= 0 && col < REVERSI_SIZE as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Tabuleiro vazio, com as peças pedidas e a vez de quem começa
+    fn board_with(pieces: &[(usize, usize, i32)], current_turn: i32) -> ReversiState {
+        let mut board = [[0; REVERSI_SIZE]; REVERSI_SIZE];
+        for (row, col, symbol) in pieces {
+            board[*row][*col] = *symbol;
+        }
+        ReversiState { board, current_turn }
+    }
+
+    #[test]
+    fn opening_position_has_four_moves() {
+        let reversi_state = ReversiState::new();
+        let moves: Vec<_> = reversi_state.legal_moves(1).iter().map(|legal| (legal.row, legal.col)).collect();
+        assert_eq!(moves, [(2, 3), (3, 2), (4, 5), (5, 4)]);
+        assert_eq!(reversi_state.final_result(), None);
+    }
+
+    #[test]
+    fn move_flips_lines_in_all_eight_directions() {
+        // Um anel de O em volta da casa vazia (3, 3) e um anel de X logo depois dele
+        let mut pieces = Vec::new();
+        for (d_row, d_col) in DIRECTIONS {
+            pieces.push(((3 + d_row) as usize, (3 + d_col) as usize, -1));
+            pieces.push(((3 + 2 * d_row) as usize, (3 + 2 * d_col) as usize, 1));
+        }
+        let mut reversi_state = board_with(&pieces, 1);
+        assert_eq!(reversi_state.flips(&ReversiMove { row: 3, col: 3 }, 1).len(), 8);
+
+        reversi_state.apply_move(&ReversiMove { row: 3, col: 3 }).unwrap();
+        assert_eq!((reversi_state.count(1), reversi_state.count(-1)), (17, 0));
+    }
+
+    #[test]
+    fn lines_without_a_closing_piece_are_not_flipped() {
+        // O O da borda não tem nada depois dele, e os dois do meio terminam numa casa vazia
+        let reversi_state = board_with(&[(0, 1, -1), (3, 3, -1), (3, 4, -1), (6, 6, 1)], 1);
+        assert!(reversi_state.flips(&ReversiMove { row: 0, col: 2 }, 1).is_empty());
+        assert!(reversi_state.flips(&ReversiMove { row: 3, col: 5 }, 1).is_empty());
+        assert!(reversi_state.legal_moves(1).is_empty());
+        // Com um X do outro lado, a mesma linha vira inteira
+        let closed = board_with(&[(3, 2, 1), (3, 3, -1), (3, 4, -1)], 1);
+        assert_eq!(closed.flips(&ReversiMove { row: 3, col: 5 }, 1), [(3, 4), (3, 3)]);
+    }
+
+    #[test]
+    fn illegal_moves_are_refused_and_keep_the_board() {
+        let mut reversi_state = ReversiState::new();
+        // Casa ocupada, casa que não vira nada e casa fora do tabuleiro
+        for (row, col) in [(3, 3), (0, 0), (REVERSI_SIZE, 2)] {
+            assert_eq!(reversi_state.apply_move(&ReversiMove { row, col }).unwrap_err(), GameError::NoFlips);
+        }
+        assert_eq!(reversi_state.board, ReversiState::new().board);
+        assert_eq!(reversi_state.current_turn, 1);
+    }
+
+    #[test]
+    fn player_without_moves_passes_the_turn() {
+        // Depois de o X jogar em (0, 2) o O fica só com a peça de (5, 6), que não cerca nada
+        let mut reversi_state = board_with(&[(0, 0, 1), (0, 1, -1), (5, 6, -1), (5, 7, 1)], 1);
+        assert_eq!(reversi_state.apply_move(&ReversiMove { row: 0, col: 2 }), Ok(true));
+        assert_eq!(reversi_state.current_turn, 1);
+        assert!(!reversi_state.is_over());
+
+        // O X vira a última peça do O e ninguém mais tem jogada
+        assert_eq!(reversi_state.apply_move(&ReversiMove { row: 5, col: 5 }), Ok(false));
+        assert!(reversi_state.is_over());
+        assert_eq!(reversi_state.final_result(), Some(1));
+    }
+
+    #[test]
+    fn finished_game_is_won_by_the_most_discs() {
+        let o_ahead = board_with(&[(0, 0, -1), (0, 1, -1), (7, 7, 1)], 1);
+        assert!(o_ahead.is_over());
+        assert_eq!(o_ahead.final_result(), Some(-1));
+
+        let tied = board_with(&[(0, 0, 1), (7, 7, -1)], 1);
+        assert_eq!(tied.final_result(), Some(0));
+    }
+}
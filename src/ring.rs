@@ -0,0 +1,185 @@
+use serde::{Serialize, Deserialize};
+
+use crate::error::GameError;
+use crate::game::Move;
+
+// Modo anel, experimental: lig4 para três ou quatro jogadores em um tabuleiro mais largo.
+// Os jogadores são numerados de 1 a N e jogam em roda, na ordem dos números.
+pub const RING_ROWS: usize = 9;
+pub const RING_COLS: usize = 10;
+pub const RING_WIN_LENGTH: usize = 4;
+pub const MIN_PLAYERS: usize = 3;
+pub const MAX_PLAYERS: usize = 4;
+
+// Letra de cada jogador, na ordem da roda
+const GLYPHS: [char; MAX_PLAYERS] = ['X', 'O', 'A', 'V'];
+
+// Direções de uma sequência: horizontal, vertical e as duas diagonais
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+
+// Letra do jogador no tabuleiro e nos avisos; '?' para símbolos fora da roda
+pub fn glyph(player_symbol: i32) -> char {
+    usize::try_from(player_symbol - 1)
+        .ok()
+        .and_then(|index| GLYPHS.get(index).copied())
+        .unwrap_or('?')
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RingState {
+    pub board: Vec<Vec<i32>>, // Linhas de cima para baixo; 0 para vazia, 1 a N para os jogadores
+    pub players: usize,
+    pub current_turn: i32,
+    pub win_length: usize,
+    out: Vec<i32>, // Jogadores eliminados por desistência ou tempo; a vez deles é pulada
+}
+
+impl RingState {
+    pub fn new(players: usize) -> Self {
+        RingState {
+            board: vec![vec![0; RING_COLS]; RING_ROWS],
+            players: players.clamp(MIN_PLAYERS, MAX_PLAYERS),
+            current_turn: 1,
+            win_length: RING_WIN_LENGTH,
+            out: Vec::new(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.board.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.board.first().map_or(0, |row| row.len())
+    }
+
+    // Linha em que uma peça jogada na coluna vai parar; None se a coluna não existe ou está cheia
+    pub fn landing_row(&self, col: usize) -> Option<usize> {
+        if col >= self.cols() {
+            return None;
+        }
+        (0..self.rows()).rev().find(|row| self.board[*row][col] == 0)
+    }
+
+    // Colunas onde o jogador da vez pode jogar; nenhuma com a partida encerrada
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.result().is_some() {
+            return Vec::new();
+        }
+        (0..self.cols()).filter(|col| self.landing_row(*col).is_some()).map(|col| Move { col }).collect()
+    }
+
+    // Solta a peça do jogador da vez e passa a vez ao próximo da roda. Retorna a linha onde ela caiu.
+    pub fn apply_move(&mut self, player_move: &Move) -> Result<usize, GameError> {
+        if player_move.col >= self.cols() {
+            return Err(GameError::InvalidColumn { cols: self.cols() });
+        }
+        let row = self.landing_row(player_move.col).ok_or(GameError::ColumnFull)?;
+        self.board[row][player_move.col] = self.current_turn;
+        self.current_turn = self.next_after(self.current_turn);
+        Ok(row)
+    }
+
+    // Próximo jogador da roda depois do símbolo dado, pulando os eliminados
+    fn next_after(&self, player_symbol: i32) -> i32 {
+        let players = self.players as i32;
+        (1..=players)
+            .map(|step| (player_symbol - 1 + step).rem_euclid(players) + 1)
+            .find(|symbol| !self.out.contains(symbol))
+            .unwrap_or(player_symbol)
+    }
+
+    // Tira o jogador da roda; as peças dele ficam no tabuleiro. Se era a vez dele, ela passa adiante.
+    pub fn eliminate(&mut self, player_symbol: i32) {
+        if !self.out.contains(&player_symbol) {
+            self.out.push(player_symbol);
+        }
+        if self.current_turn == player_symbol {
+            self.current_turn = self.next_after(player_symbol);
+        }
+    }
+
+    // Jogadores ainda na roda, em ordem
+    pub fn remaining(&self) -> Vec<i32> {
+        (1..=self.players as i32).filter(|symbol| !self.out.contains(symbol)).collect()
+    }
+
+    // Se o jogador tem uma sequência completa em alguma direção
+    pub fn check_winner(&self, player_symbol: i32) -> bool {
+        self.winning_line(player_symbol).is_some()
+    }
+
+    // Casas da primeira sequência completa do jogador, como (linha, coluna)
+    pub fn winning_line(&self, player_symbol: i32) -> Option<Vec<(usize, usize)>> {
+        let (rows, cols) = (self.rows() as isize, self.cols() as isize);
+        let length = self.win_length as isize;
+        let cells = |row: isize, col: isize, (d_row, d_col): (isize, isize)| (0..length).map(move |i| (row + i * d_row, col + i * d_col));
+        (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col))).find_map(|(row, col)| {
+            DIRECTIONS
+                .iter()
+                .find(|&&direction| {
+                    cells(row, col, direction).all(|(r, c)| {
+                        (0..rows).contains(&r) && (0..cols).contains(&c) && self.board[r as usize][c as usize] == player_symbol
+                    })
+                })
+                .map(|&direction| cells(row, col, direction).map(|(r, c)| (r as usize, c as usize)).collect())
+        })
+    }
+
+    // Resultado da partida: o símbolo do vencedor, Some(0) para empate ou None se continua.
+    // Com um só jogador restante na roda, ele vence.
+    pub fn result(&self) -> Option<i32> {
+        if let Some(winner) = (1..=self.players as i32).find(|symbol| self.check_winner(*symbol)) {
+            return Some(winner);
+        }
+        if let [last] = self.remaining()[..] {
+            return Some(last);
+        }
+        self.board.iter().flatten().all(|cell| *cell != 0).then_some(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_cycle_through_every_player() {
+        let mut ring_state = RingState::new(4);
+        for (col, symbol) in [(0, 1), (1, 2), (2, 3), (3, 4), (4, 1)] {
+            assert_eq!(ring_state.current_turn, symbol);
+            ring_state.apply_move(&Move { col }).unwrap();
+        }
+        assert_eq!(ring_state.board[RING_ROWS - 1][3], 4);
+        assert_eq!(ring_state.current_turn, 2);
+    }
+
+    #[test]
+    fn eliminated_players_are_skipped_and_the_last_one_wins() {
+        let mut ring_state = RingState::new(3);
+        ring_state.eliminate(1);
+        assert_eq!(ring_state.current_turn, 2);
+        ring_state.apply_move(&Move { col: 0 }).unwrap();
+        ring_state.apply_move(&Move { col: 0 }).unwrap();
+        assert_eq!(ring_state.current_turn, 2);
+        assert_eq!(ring_state.result(), None);
+
+        ring_state.eliminate(3);
+        assert_eq!(ring_state.result(), Some(2));
+    }
+
+    #[test]
+    fn line_wins_for_any_player() {
+        let mut ring_state = RingState::new(3);
+        for col in 2..2 + RING_WIN_LENGTH {
+            ring_state.board[RING_ROWS - 1 - (col - 2)][col] = 3;
+        }
+        assert!(ring_state.check_winner(3));
+        assert!(!ring_state.check_winner(1));
+        let line = ring_state.winning_line(3).unwrap();
+        assert_eq!(line.len(), RING_WIN_LENGTH);
+        assert_eq!(line[0], (RING_ROWS - 1, 2));
+        assert_eq!(ring_state.result(), Some(3));
+        assert_eq!(glyph(3), 'A');
+    }
+}
@@ -0,0 +1,2790 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+use tracing::{error, info, Instrument, Span};
+
+use reversi_egui::ai::{self, Difficulty};
+use reversi_egui::error::GameError;
+use reversi_egui::game::{self, is_valid_move, BoardConfig, GameState, Move};
+use reversi_egui::protocol::{LegalMove, SeriesScore, ServerMessage};
+use reversi_egui::render;
+use reversi_egui::reversi::{ReversiMove, ReversiState};
+use reversi_egui::ring::{self, RingState};
+use crate::export;
+use crate::fairplay;
+use crate::filter::FilterLevel;
+use crate::metrics::METRICS;
+use crate::storage::{GameRecord, SavedGame, Storage};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Player,
+    Ai,        // Vaga ocupada pelo computador no modo solo
+    Spectator, // Só assiste: recebe o tabuleiro, mas não joga
+}
+
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub symbol: i32, // 1 para "X", -1 para "O", 0 para espectadores; no modo anel, de 1 a N
+    pub nick: String, // Apelido do jogador; no modo solo, o nome do computador
+    pub role: Role,
+}
+
+// Modo de jogo escolhido na criação da sala
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameMode {
+    ConnectFour,
+    Reversi,
+    Ring(usize), // Experimental: lig4 para o número de jogadores dado, jogando em roda
+}
+
+impl GameMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "lig4" | "connect4" => Some(GameMode::ConnectFour),
+            "reversi" | "othello" => Some(GameMode::Reversi),
+            "anel" | "ring" => Some(GameMode::Ring(ring::MIN_PLAYERS)),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::ConnectFour => "lig4",
+            GameMode::Reversi => "reversi",
+            GameMode::Ring(_) => "anel",
+        }
+    }
+
+    // Letra do jogador no tabuleiro e nos avisos
+    pub fn glyph(&self, player_symbol: i32) -> char {
+        match self {
+            GameMode::Ring(_) => ring::glyph(player_symbol),
+            _ if player_symbol == 1 => 'X',
+            _ => 'O',
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Game {
+    ConnectFour(GameState),
+    Reversi(Box<ReversiState>), // Tabuleiro fixo de 8x8, bem maior que o estado do lig4
+    Ring(RingState),
+}
+
+// Jogada registrada no histórico da partida; no lig4, row é a linha onde a peça caiu
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PlayedMove {
+    pub symbol: i32,
+    pub row: usize,
+    pub col: usize,
+    #[serde(default)]
+    pub pop: bool, // Pop Out: a peça foi retirada da base da coluna, e não jogada nela
+    #[serde(default)]
+    pub wild: bool, // Power Up: a peça jogada foi o coringa
+}
+
+// Eventos enviados a todos os jogadores de uma sala
+#[derive(Debug, Clone)]
+pub enum RoomEvent {
+    StateChanged,   // O tabuleiro ou o turno mudou
+    GameOver,       // A partida terminou; o resultado fica em GameRoom::status
+    OpponentJoined, // O segundo jogador entrou e a partida começou
+    Notice(String), // Aviso exibido para todos os jogadores da sala
+    Chat { from: String, text: String, time: String, team: i32 }, // team: lado da conversa de dupla; 0 é para todos
+    Closed, // A sala foi fechada pelo administrador
+    Aborted, // A partida foi anulada sem resultado; a sala fecha e os jogadores voltam para o lobby
+    SidesSwapped, // Regra da troca: os dois jogadores trocaram de símbolo
+}
+
+// Ciclo de vida de uma sala
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoomStatus {
+    Waiting,       // Aguardando o segundo jogador
+    Playing,
+    Paused,        // Um jogador caiu e a sala espera que ele volte com RESUME
+    OnHold,        // Os jogadores combinaram uma pausa com /pause; o relógio para até o /resume
+    Finished(i32), // Vencedor: 1, -1 ou 0 para empate
+}
+
+// Opções escolhidas no /create, além do modo e do tabuleiro
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoomOptions {
+    pub best_of: Option<u32>, // Série melhor de N
+    pub swap: bool,           // Regra da troca: o segundo jogador pode ficar com a primeira jogada
+    pub pop_out: bool,        // Pop Out: na vez, o jogador pode retirar uma peça sua da base de uma coluna
+    pub power_up: bool,       // Power Up: cada jogador tem um coringa por partida, que conta para os dois
+    pub clock: Option<GameClock>, // Relógio de xadrez, no lugar do limite por jogada
+    pub rated: bool,          // Partida ranqueada: vale para o ELO, sem desfazer, dicas nem pausa combinada
+    pub handicap: Option<HandicapKind>, // Vantagem que quem cria a sala dá a quem entrar
+    pub teams: bool,          // Duplas: dois jogadores de cada lado, que se alternam nas jogadas
+    pub chat_filter: Option<FilterLevel>, // Rigor do filtro do chat; sem valor, o do servidor
+    pub tournament: bool,     // Partida de torneio: acabou, os jogadores voltam para o lobby
+}
+
+// Relógio de xadrez: cada jogador tem um tempo total para a partida, que só corre na vez
+// dele, e ganha o acréscimo (Fischer) a cada jogada feita. Quem zera o relógio perde.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GameClock {
+    pub initial: Duration,
+    pub increment: Duration,
+    pub remaining: [Duration; 2], // Tempo restante do X e do O, sem contar o turno em andamento
+}
+
+impl GameClock {
+    pub fn new(initial: Duration, increment: Duration) -> Self {
+        GameClock { initial, increment, remaining: [initial; 2] }
+    }
+
+    fn slot(player_symbol: i32) -> usize {
+        if player_symbol == 1 { 0 } else { 1 }
+    }
+
+    pub fn remaining(&self, player_symbol: i32) -> Duration {
+        self.remaining[Self::slot(player_symbol)]
+    }
+
+    // Desconta o tempo gasto no turno
+    fn spend(&mut self, player_symbol: i32, elapsed: Duration) {
+        let remaining = &mut self.remaining[Self::slot(player_symbol)];
+        *remaining = remaining.saturating_sub(elapsed);
+    }
+
+    // Fecha o turno de quem jogou: desconta o tempo gasto e soma o acréscimo
+    fn complete_move(&mut self, player_symbol: i32, elapsed: Duration) {
+        self.spend(player_symbol, elapsed);
+        self.remaining[Self::slot(player_symbol)] += self.increment;
+    }
+
+    fn reset(&mut self) {
+        self.remaining = [self.initial; 2];
+    }
+
+    // "5+3": minutos de tempo total e segundos de acréscimo
+    pub fn label(&self) -> String {
+        format!("{}+{}", self.initial.as_secs() / 60, self.increment.as_secs())
+    }
+}
+
+// Vantagem combinada no /create entre jogadores de níveis diferentes. Quem cria a sala se
+// declara o mais forte e quem entra aceita a vantagem junto com a sala.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HandicapKind {
+    Blocked(usize), // Colunas do meio, uma ou duas, proibidas ao mais forte nas primeiras jogadas dele
+    ExtraMove,      // O mais fraco faz duas jogadas seguidas na primeira vez dele
+}
+
+// Jogadas do mais forte durante as quais as colunas ficam bloqueadas
+pub const HANDICAP_BLOCKED_MOVES: usize = 4;
+
+impl HandicapKind {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.to_lowercase().as_str() {
+            "block1" | "bloqueio1" => Some(HandicapKind::Blocked(1)),
+            "block2" | "bloqueio2" => Some(HandicapKind::Blocked(2)),
+            "extra" => Some(HandicapKind::ExtraMove),
+            _ => None,
+        }
+    }
+
+    // Quanto a vantagem vale em pontos de rating: no ELO, quem dá a vantagem joga como se
+    // tivesse esses pontos a menos
+    pub fn rating_points(self) -> i32 {
+        match self {
+            HandicapKind::Blocked(count) => 100 * count as i32,
+            HandicapKind::ExtraMove => 250,
+        }
+    }
+
+    // Colunas bloqueadas: as mais perto do centro, a da esquerda primeiro no empate
+    pub fn blocked_columns(self, cols: usize) -> Vec<usize> {
+        let HandicapKind::Blocked(count) = self else {
+            return Vec::new();
+        };
+        let mut order: Vec<usize> = (0..cols).collect();
+        order.sort_by_key(|col| (2 * *col).abs_diff(cols - 1));
+        let mut blocked: Vec<usize> = order.into_iter().take(count).collect();
+        blocked.sort();
+        blocked
+    }
+
+    fn label(self) -> String {
+        match self {
+            HandicapKind::Blocked(1) => "bloqueio de 1 coluna".to_string(),
+            HandicapKind::Blocked(count) => format!("bloqueio de {} colunas", count),
+            HandicapKind::ExtraMove => "jogada extra".to_string(),
+        }
+    }
+}
+
+// Vantagem da sala, com o lado de quem a dá. O símbolo acompanha o jogador na regra da troca.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Handicap {
+    pub kind: HandicapKind,
+    pub giver: i32,
+}
+
+// Jogador que perdeu a conexão no meio da partida e ainda pode voltar
+#[derive(Debug, Clone, Copy)]
+struct Away {
+    symbol: i32,
+    deadline: Instant,     // Até quando ele pode voltar antes de perder por W.O.
+    turn_elapsed: Duration, // Tempo já gasto no turno quando a partida pausou
+}
+
+// Jogada já interpretada, conforme o modo da sala
+#[derive(Clone)]
+pub enum ParsedMove {
+    ConnectFour(Move),
+    PopOut(usize), // Coluna de onde o jogador retira a própria peça, no Pop Out
+    Wild(usize),   // Coluna onde o jogador solta o coringa, no Power Up
+    Reversi(ReversiMove),
+}
+
+// Pedidos das conexões para a tarefa da sala; os que esperam resposta levam o canal dela
+enum RoomCommand {
+    Join { player: Player, reply: oneshot::Sender<Option<i32>> },
+    Watch { player: Player },
+    Resume { nick: String, reply: oneshot::Sender<Result<Player, String>> },
+    View { symbol: i32, nick: String, reply: oneshot::Sender<RoomView> },
+    Play { symbol: i32, nick: String, player_move: ParsedMove, reply: oneshot::Sender<Result<Option<String>, GameError>> },
+    Preview { symbol: i32, nick: String, player_move: ParsedMove, reply: oneshot::Sender<Result<MovePreview, GameError>> },
+    Resign { symbol: i32, reply: oneshot::Sender<Result<(), String>> },
+    RequestUndo { symbol: i32, reply: oneshot::Sender<Result<(), String>> },
+    RequestAbort { symbol: i32, reply: oneshot::Sender<Result<(), String>> },
+    OfferDraw { symbol: i32, reply: oneshot::Sender<Result<(), String>> },
+    Answer { symbol: i32, accepted: bool, reply: oneshot::Sender<Result<(), String>> },
+    Swap { symbol: i32, reply: oneshot::Sender<Result<(), String>> },
+    Hint { symbol: i32, reply: oneshot::Sender<Result<Hint, String>> },
+    Eval { symbol: i32, reply: oneshot::Sender<Result<Evaluation, String>> },
+    Pause { symbol: i32, room_name: String, reply: oneshot::Sender<Result<(), String>> },
+    ResumeGame { symbol: i32, room_name: String, reply: oneshot::Sender<Result<(), String>> },
+    Rematch { symbol: i32, reply: oneshot::Sender<bool> },
+    Vote { nick: String, col: usize, reply: oneshot::Sender<Result<(), String>> },
+    Leave { nick: String },
+    Activity { nick: String }, // O jogador enviou alguma coisa; ele não está ausente
+    Disconnect { nick: String, reply: oneshot::Sender<bool> },
+    Suspend { room_name: String, reply: oneshot::Sender<Option<SavedGame>> },
+    Snapshot { room_name: String, reply: oneshot::Sender<Option<SavedGame>> },
+    Summary { reply: oneshot::Sender<RoomSummary> },
+    Close, // Encerra a tarefa da sala; as conexões percebem e saem
+}
+
+// Retrato da sala do ponto de vista de um jogador, usado para desenhar a tela dele
+pub struct RoomView {
+    pub game: Game,
+    pub last_move: Option<(usize, usize)>, // Casa da última jogada, destacada no tabuleiro
+    pub status: RoomStatus,
+    pub seated: usize, // Jogadores sentados, sem contar os espectadores
+    pub seats: usize,  // Lugares da mesa: dois, ou o número de jogadores do anel
+    pub humans: usize, // Jogadores humanos sentados
+    pub prompt: Option<String>, // De quem é a vez e o que digitar
+    pub start_notice: String,
+    pub clock: Option<GameClock>, // Relógio de xadrez com o turno em andamento descontado
+    pub exhibition: bool, // Sala de exibição, que segue aberta para quem assiste mesmo sem ninguém jogando
+    pub blocked_columns: Vec<usize>, // Colunas que a vantagem combinada ainda proíbe a quem vê
+    pub partner_turn: bool, // Em duplas, a vez é do lado de quem vê, mas quem joga é o parceiro
+    pub teams: bool,
+    pub crowd: bool, // Multidão contra o computador: quem assiste vota nas jogadas
+    pub chat_filter: Option<FilterLevel>,
+    pub tournament: bool,
+    pub turn_timeout: Duration, // Limite por jogada, mais curto nas ranqueadas
+}
+
+// Dica pedida com /hint: a posição para a busca do computador, que roda fora da sala
+pub struct Hint {
+    pub position: GameState,
+    pub last_move: Option<(usize, usize)>,
+    pub remaining: usize, // Dicas que o jogador ainda pode pedir nesta partida
+}
+
+// Avaliação pedida com /eval: as posições da partida, da inicial à atual, para o
+// solucionador, que roda fora da sala
+pub struct Evaluation {
+    pub positions: Vec<GameState>,
+    pub moves: Vec<PlayedMove>,
+    pub finished: bool,
+}
+
+// Jogada proposta por um cliente que confirma antes de jogar: o jogo como ficaria depois dela
+// e a casa onde a peça fica
+pub struct MovePreview {
+    pub game: Game,
+    pub cell: (usize, usize),
+}
+
+// Resumo da sala para o lobby: a lista de salas e o fechamento das que acabaram
+pub struct RoomSummary {
+    pub status: RoomStatus,
+    pub mode: GameMode,
+    pub seats: usize,
+    pub mode_label: String,
+    pub players: Vec<Player>,
+    pub humans: usize,
+}
+
+// Endereço da tarefa da sala. As conexões enviam pedidos pelo canal de comandos e recebem
+// as mudanças pelo canal de eventos; a sala em si só é tocada pela própria tarefa.
+#[derive(Clone)]
+pub struct RoomHandle {
+    commands: mpsc::Sender<RoomCommand>,
+    events: broadcast::Sender<RoomEvent>,
+    mode: GameMode,
+}
+
+// Pedidos aguardando a tarefa da sala antes de a conexão esperar
+const COMMAND_QUEUE: usize = 32;
+
+// Dicas que cada jogador pode pedir por partida
+pub const HINTS_PER_GAME: usize = 3;
+
+// Lugares de uma sala de duplas
+pub const TEAM_SEATS: usize = 4;
+
+// Nome do lado jogado pela multidão
+pub const CROWD_NICK: &str = "multidão";
+
+// Avisos da votação da multidão: quanto falta para ela fechar
+const CROWD_COUNTDOWNS: [Duration; 2] = [Duration::from_secs(10), Duration::from_secs(5)];
+
+pub struct GameRoom {
+    game: Game,
+    board_config: BoardConfig, // Tabuleiro do lig4 escolhido na criação da sala
+    status: RoomStatus,
+    pub players: Vec<Player>,
+    events: broadcast::Sender<RoomEvent>,
+    first_turn: i32,          // Quem começou a partida atual; alterna a cada revanche
+    seed: u32,                // Semente do sorteio de quem começa a primeira partida da sala
+    rematch_votes: Vec<i32>,  // Jogadores que já aceitaram jogar novamente
+    turn_timeout: Duration,
+    turn_started_at: Instant, // Início do turno atual, usado pelo relógio da sala
+    last_move: Option<(usize, usize)>, // Casa da última jogada, destacada no tabuleiro
+    moves: Vec<PlayedMove>,   // Jogadas da partida atual, em ordem
+    think_times: Vec<Option<u64>>, // Tempo de cada jogada em ms, junto com moves; só nas ranqueadas
+    undo_request: Option<i32>, // Jogador que pediu para desfazer e aguarda a resposta do oponente
+    abort_request: Option<i32>, // Lado que pediu para anular a partida e aguarda a resposta do outro
+    draw_offer: Option<i32>, // Lado que propôs empate; a proposta vale até a próxima jogada
+    aborted: bool, // A partida foi anulada e a tarefa da sala termina sem gravar nada
+    storage: Option<Arc<Storage>>, // Onde as partidas encerradas são gravadas, se o histórico estiver ativo
+    match_started_at: SystemTime,
+    match_players: [String; 2], // X e O da partida atual, guardados mesmo que alguém saia
+    reconnect_timeout: Duration, // Zero desliga a pausa: quem cai perde na hora
+    away: Option<Away>,
+    series: Option<SeriesScore>, // Placar da série, quando a sala joga uma melhor de N
+    pause_request: Option<i32>, // Jogador que pediu a pausa e aguarda o oponente concordar
+    held_turn_elapsed: Duration, // Tempo já gasto no turno quando a pausa combinada começou
+    absent: Vec<i32>, // Lugares de quem saiu durante a pausa combinada e ainda pode voltar
+    swap_rule: bool, // Depois da primeira jogada, o segundo jogador pode trocar de lado
+    pop_out: bool,   // Variante Pop Out: vale retirar a própria peça da base de uma coluna
+    power_up: bool,  // Variante Power Up: cada jogador tem um coringa por partida
+    hints_used: Vec<i32>, // Uma entrada por dica pedida na partida atual, com o símbolo de quem pediu
+    clock: Option<GameClock>, // Relógio de xadrez; sem ele vale o limite por jogada
+    rated: bool, // Ranqueada: o resultado muda o ELO; amistosa, só entra no histórico
+    afk_timeout: Option<Duration>, // Tempo sem nenhum comando até o jogador ser dado como ausente
+    last_input: HashMap<String, Instant>, // Último comando de cada jogador humano na partida
+    afk: HashMap<String, Instant>, // Jogadores avisados como ausentes, com a hora do aviso
+    exhibition: bool, // Jogam o computador ou bots conectados; as pessoas só assistem
+    move_delay: Duration, // Exibição: tempo mínimo entre as jogadas, para quem assiste acompanhar
+    held_move: Option<HeldMove>, // Jogada de um bot feita antes do atraso, já conferida
+    handicap: Option<Handicap>, // Vantagem combinada entre jogadores de níveis diferentes
+    teams: bool, // Duplas: quatro jogadores, dois de cada lado
+    team_members: [Vec<String>; 2], // Parceiros do X e do O, na ordem em que se sentaram
+    crowd: Option<CrowdVote>, // Multidão contra o computador: quem assiste vota nas jogadas de um lado
+    chat_filter: Option<FilterLevel>, // Filtro do chat escolhido no /create; sem valor, o do servidor
+    tournament: bool, // Partida de um torneio automático, sem revanche
+}
+
+// Votação da multidão. A cada vez dela, quem assiste vota em uma coluna; quando o tempo
+// acaba, a mais votada é jogada.
+struct CrowdVote {
+    symbol: i32,      // Lado jogado pela multidão
+    window: Duration, // Duração de cada votação
+    votes: Vec<(String, usize)>, // Apelido e coluna; cada um tem um voto, e vale o último
+    countdowns_sent: usize, // Avisos de contagem regressiva já enviados nesta votação
+}
+
+// Jogada guardada até o atraso da exibição passar; a resposta vai quando ela for feita
+struct HeldMove {
+    symbol: i32,
+    nick: String,
+    player_move: ParsedMove,
+    reply: oneshot::Sender<Result<Option<String>, GameError>>,
+}
+
+impl Game {
+    pub fn new(mode: GameMode, board_config: BoardConfig, first_turn: i32) -> Self {
+        match mode {
+            GameMode::ConnectFour => {
+                let mut game_state = GameState::with_config(board_config);
+                game_state.current_turn = first_turn;
+                Game::ConnectFour(game_state)
+            }
+            GameMode::Reversi => {
+                let mut reversi_state = ReversiState::new();
+                reversi_state.current_turn = first_turn;
+                Game::Reversi(Box::new(reversi_state))
+            }
+            GameMode::Ring(players) => {
+                let mut ring_state = RingState::new(players);
+                ring_state.current_turn = first_turn;
+                Game::Ring(ring_state)
+            }
+        }
+    }
+
+    // Reaplica uma jogada do histórico
+    pub fn replay(&mut self, played_move: &PlayedMove) {
+        match self {
+            Game::ConnectFour(game_state) => {
+                // A vez vem da própria jogada: com a jogada extra, o mesmo lado joga duas vezes
+                game_state.current_turn = played_move.symbol;
+                let _ = if played_move.pop {
+                    game_state.pop_piece(played_move.col)
+                } else if played_move.wild {
+                    game_state.apply_wild(&Move { col: played_move.col }).map(|_| ())
+                } else {
+                    game_state.apply_move(&Move { col: played_move.col }).map(|_| ())
+                };
+            }
+            Game::Reversi(reversi_state) => {
+                let _ = reversi_state.apply_move(&ReversiMove { row: played_move.row, col: played_move.col });
+            }
+            Game::Ring(ring_state) => {
+                let _ = ring_state.apply_move(&Move { col: played_move.col });
+            }
+        }
+    }
+
+    pub fn mode(&self) -> GameMode {
+        match self {
+            Game::ConnectFour(_) => GameMode::ConnectFour,
+            Game::Reversi(_) => GameMode::Reversi,
+            Game::Ring(ring_state) => GameMode::Ring(ring_state.players),
+        }
+    }
+
+    pub fn current_turn(&self) -> i32 {
+        match self {
+            Game::ConnectFour(game_state) => game_state.current_turn,
+            Game::Reversi(reversi_state) => reversi_state.current_turn,
+            Game::Ring(ring_state) => ring_state.current_turn,
+        }
+    }
+
+    // Casas onde o jogador da vez pode jogar, como as regras de cada modo listam; no lig4 e
+    // no anel, uma por coluna com espaço, na linha onde a peça cairia
+    pub fn legal_moves(&self) -> Vec<LegalMove> {
+        match self {
+            Game::ConnectFour(game_state) => game_state
+                .legal_moves()
+                .iter()
+                .filter_map(|player_move| game_state.landing_row(player_move.col).map(|row| LegalMove { row, col: player_move.col }))
+                .collect(),
+            Game::Reversi(reversi_state) => reversi_state
+                .legal_moves(reversi_state.current_turn)
+                .iter()
+                .map(|player_move| LegalMove { row: player_move.row, col: player_move.col })
+                .collect(),
+            Game::Ring(ring_state) => ring_state
+                .legal_moves()
+                .iter()
+                .filter_map(|player_move| ring_state.landing_row(player_move.col).map(|row| LegalMove { row, col: player_move.col }))
+                .collect(),
+        }
+    }
+
+    // Estado do jogo como mensagem do protocolo JSON
+    pub fn state_message(&self, player_symbol: i32) -> ServerMessage {
+        let board = match self {
+            Game::ConnectFour(game_state) => game_state.board(),
+            Game::Reversi(reversi_state) => reversi_state.board.iter().map(|row| row.to_vec()).collect(),
+            Game::Ring(ring_state) => ring_state.board.clone(),
+        };
+
+        ServerMessage::State {
+            mode: self.mode().name().to_string(),
+            board,
+            current_turn: self.current_turn(),
+            you: player_symbol,
+            legal_moves: self.legal_moves(),
+        }
+    }
+
+    // Casas da sequência que decidiu a partida, como (linha, coluna). Vazio enquanto ela
+    // continua, no reversi e nas vitórias sem sequência, como a do último que resta no anel.
+    pub fn winning_line(&self) -> Vec<(usize, usize)> {
+        let line = match self {
+            Game::ConnectFour(game_state) => game::check_winner(game_state).map(|outcome| outcome.line),
+            Game::Reversi(_) => None,
+            Game::Ring(ring_state) => ring_state.result().filter(|winner| *winner != 0).and_then(|winner| ring_state.winning_line(winner)),
+        };
+        line.unwrap_or_default()
+    }
+
+    // Resultado como mensagem do protocolo JSON, com a sequência vencedora
+    pub fn game_over_message(&self, winner: i32, player_symbol: i32) -> ServerMessage {
+        let line = self.winning_line().into_iter().map(|(row, col)| LegalMove { row, col }).collect();
+        ServerMessage::GameOver { winner, you: player_symbol, line }
+    }
+
+    // Tabuleiro em texto, com a última jogada e a sequência vencedora destacadas
+    pub fn render(&self, last_move: Option<(usize, usize)>, color: bool, unicode: bool) -> String {
+        match self {
+            Game::ConnectFour(game_state) => {
+                let marks = line_marks(&self.winning_line());
+                render::render_connect_four(&game_state.board(), last_move, &marks, color, unicode)
+            }
+            Game::Reversi(reversi_state) => {
+                let legal_moves: Vec<(usize, usize)> = reversi_state
+                    .legal_moves(reversi_state.current_turn)
+                    .iter()
+                    .map(|player_move| (player_move.row, player_move.col))
+                    .collect();
+                let mut out = render::render_reversi(&reversi_state.board, last_move, &legal_moves, color, unicode);
+                out.push_str(&format!("X: {}  O: {}\n", reversi_state.count(1), reversi_state.count(-1)));
+                out
+            }
+            Game::Ring(ring_state) => render::render_ring(&ring_state.board, last_move, &self.winning_line(), color, unicode),
+        }
+    }
+}
+
+// Marcas do tabuleiro para as casas da sequência vencedora
+pub fn line_marks(line: &[(usize, usize)]) -> Vec<(usize, usize, render::Mark)> {
+    line.iter().map(|&(row, col)| (row, col, render::Mark::Line)).collect()
+}
+
+impl GameRoom {
+    pub fn new(
+        mode: GameMode,
+        board_config: BoardConfig,
+        turn_timeout: Duration,
+        reconnect_timeout: Duration,
+        afk_timeout: Option<Duration>,
+        storage: Option<Arc<Storage>>,
+    ) -> Self {
+        let (events, _) = broadcast::channel(16);
+
+        GameRoom {
+            game: Game::new(mode, board_config, 1),
+            board_config,
+            status: RoomStatus::Waiting,
+            players: Vec::new(),
+            events,
+            first_turn: 1,
+            seed: 0,
+            rematch_votes: Vec::new(),
+            turn_timeout,
+            turn_started_at: Instant::now(),
+            last_move: None,
+            moves: Vec::new(),
+            think_times: Vec::new(),
+            undo_request: None,
+            abort_request: None,
+            draw_offer: None,
+            aborted: false,
+            storage,
+            match_started_at: SystemTime::now(),
+            match_players: [String::new(), String::new()],
+            reconnect_timeout,
+            away: None,
+            series: None,
+            pause_request: None,
+            held_turn_elapsed: Duration::ZERO,
+            absent: Vec::new(),
+            swap_rule: false,
+            pop_out: false,
+            power_up: false,
+            hints_used: Vec::new(),
+            clock: None,
+            rated: false,
+            afk_timeout,
+            last_input: HashMap::new(),
+            afk: HashMap::new(),
+            exhibition: false,
+            move_delay: Duration::ZERO,
+            held_move: None,
+            handicap: None,
+            teams: false,
+            team_members: [Vec::new(), Vec::new()],
+            crowd: None,
+            chat_filter: None,
+            tournament: false,
+        }
+    }
+
+    // Reabre uma partida guardada antes de o servidor reiniciar. Ela volta pausada, com os
+    // dois lugares esperando os jogadores voltarem com o código de reconexão.
+    pub fn restore(
+        saved: &SavedGame,
+        mode: GameMode,
+        turn_timeout: Duration,
+        reconnect_timeout: Duration,
+        afk_timeout: Option<Duration>,
+        storage: Option<Arc<Storage>>,
+    ) -> Self {
+        let mut room = GameRoom::new(mode, saved.board_config, turn_timeout, reconnect_timeout, afk_timeout, storage);
+        room.first_turn = saved.first_turn;
+        room.seed = saved.seed;
+        room.game = Game::new(mode, saved.board_config, saved.first_turn);
+        for played_move in &saved.moves {
+            room.game.replay(played_move);
+        }
+        room.moves = saved.moves.clone();
+        // O tempo das jogadas de antes da retomada se perdeu
+        room.think_times = vec![None; saved.moves.len()];
+        room.last_move = saved.moves.last().map(|played_move| (played_move.row, played_move.col));
+        room.match_players = [saved.player_x.clone(), saved.player_o.clone()];
+        room.pop_out = saved.pop_out;
+        room.power_up = saved.power_up;
+        room.clock = saved.clock;
+        room.rated = saved.rated;
+        room.handicap = saved.handicap;
+        room.grant_extra_move();
+        for (symbol, nick) in [(1, &saved.player_x), (-1, &saved.player_o)] {
+            room.players.push(Player { symbol, nick: nick.clone(), role: Role::Player });
+        }
+        room.status = RoomStatus::OnHold;
+        room.absent = vec![1, -1];
+        room
+    }
+
+    // Aplica as opções do /create: série melhor de N, regra da troca e as variantes
+    pub fn apply_options(&mut self, options: RoomOptions) {
+        self.series = options.best_of.map(SeriesScore::new);
+        self.swap_rule = options.swap;
+        self.pop_out = options.pop_out;
+        self.power_up = options.power_up;
+        self.clock = options.clock;
+        self.rated = options.rated;
+        // Quem cria a sala é sempre o X
+        self.handicap = options.handicap.map(|kind| Handicap { kind, giver: 1 });
+        self.teams = options.teams;
+        self.chat_filter = options.chat_filter;
+        self.tournament = options.tournament;
+    }
+
+    // Sala de exibição: cada jogada só vale depois do atraso, contado do início do turno
+    pub fn make_exhibition(&mut self, move_delay: Duration) {
+        self.exhibition = true;
+        self.move_delay = move_delay;
+    }
+
+    // Sala da multidão: as jogadas do lado dela saem da votação de quem assiste
+    pub fn make_crowd(&mut self, symbol: i32, window: Duration) {
+        self.crowd = Some(CrowdVote { symbol, window, votes: Vec::new(), countdowns_sent: 0 });
+    }
+
+    // A votação em andamento, se for a vez da multidão
+    fn open_vote(&self) -> Option<&CrowdVote> {
+        self.crowd
+            .as_ref()
+            .filter(|crowd| self.status == RoomStatus::Playing && self.current_turn() == crowd.symbol)
+    }
+
+    // Registra o voto de quem assiste; um novo voto troca o anterior
+    fn vote(&mut self, nick: &str, col: usize) -> Result<(), String> {
+        if self.crowd.is_none() {
+            return Err("Esta sala não tem votação.\n".to_string());
+        }
+        self.ensure_not_finished()?;
+        if self.open_vote().is_none() {
+            return Err("A votação abre na vez da multidão.\n".to_string());
+        }
+        let valid = matches!(&self.game, Game::ConnectFour(game_state) if is_valid_move(game_state, &Move { col }));
+        if !valid {
+            return Err("Coluna inválida ou cheia. Vote em outra.\n".to_string());
+        }
+        if let Some(crowd) = &mut self.crowd {
+            crowd.votes.retain(|(voter, _)| voter != nick);
+            crowd.votes.push((nick.to_string(), col));
+        }
+        Ok(())
+    }
+
+    // Colunas votadas, da mais votada para a menos. No empate fica na frente a mais perto do
+    // centro, que entra em mais sequências, e depois a da esquerda.
+    fn tally(&self) -> Vec<(usize, usize)> {
+        let Some(crowd) = &self.crowd else {
+            return Vec::new();
+        };
+        let mut tally: Vec<(usize, usize)> = Vec::new();
+        for (_, col) in &crowd.votes {
+            match tally.iter_mut().find(|(voted, _)| voted == col) {
+                Some((_, count)) => *count += 1,
+                None => tally.push((*col, 1)),
+            }
+        }
+        let cols = self.board_config.cols;
+        tally.sort_by_key(|(col, count)| (std::cmp::Reverse(*count), (2 * col).abs_diff(cols - 1), *col));
+        tally
+    }
+
+    fn tally_text(&self) -> String {
+        let tally: Vec<String> = self.tally().iter().map(|(col, count)| format!("{} ({})", col + 1, count)).collect();
+        if tally.is_empty() {
+            "Ainda não há votos.".to_string()
+        } else {
+            format!("Votos por coluna: {}.", tally.join(", "))
+        }
+    }
+
+    // Próximo aviso de contagem regressiva da votação em andamento
+    pub fn countdown_deadline(&self) -> Option<Instant> {
+        let crowd = self.open_vote()?;
+        let mark = CROWD_COUNTDOWNS.iter().filter(|mark| **mark < crowd.window).nth(crowd.countdowns_sent)?;
+        Some(self.turn_started_at + crowd.window - *mark)
+    }
+
+    pub fn announce_countdown(&mut self) {
+        let Some(crowd) = &mut self.crowd else {
+            return;
+        };
+        let Some(mark) = CROWD_COUNTDOWNS.iter().filter(|mark| **mark < crowd.window).nth(crowd.countdowns_sent) else {
+            return;
+        };
+        crowd.countdowns_sent += 1;
+        let notice = format!("Faltam {}s para fechar a votação. {}\n", mark.as_secs(), self.tally_text());
+        let _ = self.events.send(RoomEvent::Notice(notice));
+    }
+
+    // Abre a votação quando a vez passa para a multidão
+    fn open_crowd_vote(&mut self) {
+        let Some(crowd) = &mut self.crowd else {
+            return;
+        };
+        crowd.votes.clear();
+        crowd.countdowns_sent = 0;
+        let window = crowd.window;
+        if self.open_vote().is_some() {
+            let notice = format!(
+                "Votação aberta: digite a coluna (1-{}) da próxima jogada. Ela fecha em {}s.\n",
+                self.board_config.cols,
+                window.as_secs()
+            );
+            let _ = self.events.send(RoomEvent::Notice(notice));
+        }
+    }
+
+    // A votação fechou: joga a coluna mais votada. Sem votos, ela recomeça, e a partida
+    // espera quem assiste.
+    fn close_crowd_vote(&mut self) {
+        let Some(&(col, count)) = self.tally().first() else {
+            let _ = self.events.send(RoomEvent::Notice("Ninguém votou; a votação recomeça.\n".to_string()));
+            self.turn_started_at = Instant::now();
+            self.open_crowd_vote();
+            return;
+        };
+        let total = self.crowd.as_ref().map_or(0, |crowd| crowd.votes.len());
+        info!(col, count, total, "votação da multidão encerrada");
+        let notice = format!("Votação encerrada: a multidão joga na coluna {} ({} de {} votos).\n", col + 1, count, total);
+        let _ = self.events.send(RoomEvent::Notice(notice));
+        if self.update_game_state(Move { col }).is_ok() {
+            self.notify_state_changed();
+        }
+    }
+
+    // Quando a próxima jogada pode ser feita; sem atraso, já
+    fn next_move_at(&self) -> Instant {
+        self.turn_started_at + self.move_delay
+    }
+
+    fn held_move_deadline(&self) -> Option<Instant> {
+        self.held_move.as_ref().map(|_| self.next_move_at())
+    }
+
+    // Faz a jogada guardada e responde a quem jogou
+    fn release_held_move(&mut self) {
+        if let Some(held) = self.held_move.take() {
+            let _ = held.reply.send(self.play(held.symbol, &held.nick, held.player_move));
+        }
+    }
+
+    // Sorteia quem começa a primeira partida da sala. A semente vai para o histórico, e a
+    // mesma semente sempre dá o mesmo resultado; as revanches continuam alternando.
+    pub fn draw_first_turn(&mut self, seed: u32) {
+        self.seed = seed;
+        self.first_turn = first_turn_for(seed, self.mode());
+        self.game = Game::new(self.mode(), self.board_config, self.first_turn);
+    }
+
+    // Começa a partida quando o segundo jogador entra
+    pub fn start_game(&mut self) {
+        self.status = RoomStatus::Playing;
+        self.turn_started_at = Instant::now();
+        self.reset_activity();
+        self.begin_match();
+        let _ = self.events.send(RoomEvent::OpponentJoined);
+        self.notify_state_changed();
+    }
+
+    // Aviso de início de partida do ponto de vista de cada um: quem é o oponente e quem começa
+    pub fn start_notice(&self, player_symbol: i32) -> String {
+        let mut notice = match self.game {
+            Game::Ring(_) => self.ring_start_notice(player_symbol),
+            _ if self.teams => self.team_start_notice(player_symbol),
+            _ => self.duel_start_notice(player_symbol),
+        };
+        notice.push_str(&format!("Quem começa foi sorteado (semente {}).\n", self.seed));
+        notice
+    }
+
+    fn duel_start_notice(&self, player_symbol: i32) -> String {
+        let [player_x, player_o] = &self.match_players;
+        let first = if self.current_turn() == 1 { 'X' } else { 'O' };
+        let mut notice = match player_symbol {
+            1 | -1 => {
+                let (opponent, symbol) = if player_symbol == 1 { (player_o, 'X') } else { (player_x, 'O') };
+                if self.current_turn() == player_symbol {
+                    format!("Oponente conectado: {}. Você é o {} e começa.\n", opponent, symbol)
+                } else {
+                    format!("Oponente conectado: {}. Você é o {}; o {} começa.\n", opponent, symbol, first)
+                }
+            }
+            _ => format!("Partida iniciada: {} (X) contra {} (O). O {} começa.\n", player_x, player_o, first),
+        };
+        if let Some(series) = self.series {
+            notice.push_str(&format!("Série melhor de {}: vence quem ganhar {} jogos.\n", series.best_of, series.needed()));
+        }
+        if let Some(handicap) = self.handicap {
+            notice.push_str(&self.handicap_notice(handicap));
+        }
+        if let Some(crowd) = &self.crowd {
+            notice.push_str(&format!(
+                "Quem assiste vota nas jogadas do {}: a cada vez, a coluna mais votada em {}s é jogada, e no empate vale a mais perto do centro.\n",
+                self.glyph(crowd.symbol),
+                crowd.window.as_secs()
+            ));
+        }
+        notice
+    }
+
+    // A vantagem combinada, com o apelido de quem ela afeta
+    fn handicap_notice(&self, handicap: Handicap) -> String {
+        let nick_of = |symbol: i32| &self.match_players[if symbol == 1 { 0 } else { 1 }];
+        match handicap.kind {
+            HandicapKind::Blocked(_) => {
+                let cols: Vec<String> = handicap
+                    .kind
+                    .blocked_columns(self.board_config.cols)
+                    .iter()
+                    .map(|col| (col + 1).to_string())
+                    .collect();
+                let giver = nick_of(handicap.giver);
+                let glyph = self.glyph(handicap.giver);
+                match cols.as_slice() {
+                    [col] => format!(
+                        "Vantagem: {} ({}) não joga na coluna {} nas primeiras {} jogadas dele.\n",
+                        giver, glyph, col, HANDICAP_BLOCKED_MOVES
+                    ),
+                    cols => format!(
+                        "Vantagem: {} ({}) não joga nas colunas {} nas primeiras {} jogadas dele.\n",
+                        giver,
+                        glyph,
+                        cols.join(", "),
+                        HANDICAP_BLOCKED_MOVES
+                    ),
+                }
+            }
+            HandicapKind::ExtraMove => format!(
+                "Vantagem: {} ({}) faz duas jogadas seguidas na primeira vez dele.\n",
+                nick_of(-handicap.giver),
+                self.glyph(-handicap.giver)
+            ),
+        }
+    }
+
+    // Colunas que a vantagem ainda proíbe ao jogador: só a quem a dá, nas primeiras jogadas dele
+    fn blocked_columns(&self, player_symbol: i32) -> Vec<usize> {
+        match self.handicap {
+            Some(handicap) if handicap.giver == player_symbol && self.moves_by(player_symbol) < HANDICAP_BLOCKED_MOVES => {
+                handicap.kind.blocked_columns(self.board_config.cols)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn moves_by(&self, player_symbol: i32) -> usize {
+        self.moves.iter().filter(|played_move| played_move.symbol == player_symbol).count()
+    }
+
+    fn ensure_not_blocked(&self, player_symbol: i32, col: usize) -> Result<(), String> {
+        if self.blocked_columns(player_symbol).contains(&col) {
+            return Err(format!(
+                "A coluna {} está bloqueada para você nas suas primeiras {} jogadas.\n",
+                col + 1,
+                HANDICAP_BLOCKED_MOVES
+            ));
+        }
+        Ok(())
+    }
+
+    // Jogada extra: depois da primeira jogada de quem recebe a vantagem, a vez continua com ele
+    fn grant_extra_move(&mut self) {
+        let Some(Handicap { kind: HandicapKind::ExtraMove, giver }) = self.handicap else {
+            return;
+        };
+        let receiver = -giver;
+        let first_move = self.moves.last().is_some_and(|played_move| played_move.symbol == receiver) && self.moves_by(receiver) == 1;
+        if let Game::ConnectFour(game_state) = &mut self.game {
+            if first_move && game_state.result().is_none() {
+                game_state.current_turn = receiver;
+            }
+        }
+    }
+
+    // Aviso de início em duplas; o apelido de cada dupla junta os dois parceiros
+    fn team_start_notice(&self, player_symbol: i32) -> String {
+        let [team_x, team_o] = &self.match_players;
+        let first = self.glyph(self.current_turn());
+        let mut notice = format!("Partida em duplas: {} (X) contra {} (O). O {} começa.\n", team_x, team_o, first);
+        if player_symbol != 0 {
+            notice.push_str(&format!(
+                "Você joga pelo {}, alternando as jogadas com o parceiro. /team <mensagem> fala só com ele.\n",
+                self.glyph(player_symbol)
+            ));
+        }
+        notice
+    }
+
+    // Aviso de início no anel, com a letra de cada jogador da roda
+    fn ring_start_notice(&self, player_symbol: i32) -> String {
+        let mut seated: Vec<&Player> = self.players.iter().filter(|player| player.role == Role::Player).collect();
+        seated.sort_by_key(|player| player.symbol);
+        let names: Vec<String> = seated
+            .iter()
+            .map(|player| format!("{} ({})", player.nick, ring::glyph(player.symbol)))
+            .collect();
+        let first = ring::glyph(self.current_turn());
+        match player_symbol {
+            0 => format!("Partida iniciada: {}. O {} começa.\n", names.join(", "), first),
+            symbol if symbol == self.current_turn() => {
+                format!("Partida iniciada: {}. Você é o {} e começa.\n", names.join(", "), ring::glyph(symbol))
+            }
+            symbol => format!(
+                "Partida iniciada: {}. Você é o {}; o {} começa.\n",
+                names.join(", "),
+                ring::glyph(symbol),
+                first
+            ),
+        }
+    }
+
+    // Lugares da mesa: dois jogadores, ou os do anel
+    pub fn seats(&self) -> usize {
+        match self.mode() {
+            GameMode::Ring(players) => players,
+            _ if self.teams => TEAM_SEATS,
+            _ => 2,
+        }
+    }
+
+    fn glyph(&self, player_symbol: i32) -> char {
+        self.mode().glyph(player_symbol)
+    }
+
+    // Placar da série com os apelidos, como "ana 1 x 0 bia"
+    fn series_score(&self, series: &SeriesScore) -> String {
+        let [player_x, player_o] = &self.match_players;
+        format!("{} {} x {} {}", player_x, series.wins_x, series.wins_o, player_o)
+    }
+
+    // Anota quem joga e quando a partida começou, para o histórico. Em duplas, o nome de
+    // cada lado junta os dois parceiros.
+    fn begin_match(&mut self) {
+        self.match_started_at = SystemTime::now();
+        for (slot, symbol) in [(0, 1), (1, -1)] {
+            let members: Vec<String> = self
+                .players
+                .iter()
+                .filter(|player| player.symbol == symbol)
+                .map(|player| player.nick.clone())
+                .collect();
+            if let Some(first) = members.first() {
+                self.match_players[slot] = if self.teams { members.join(" & ") } else { first.clone() };
+            }
+            self.team_members[slot] = members;
+        }
+    }
+
+    // Em duplas, quem joga agora pelo lado: os parceiros se revezam a cada jogada do lado
+    fn team_mover(&self, player_symbol: i32) -> Option<&str> {
+        if !self.teams || self.status != RoomStatus::Playing {
+            return None;
+        }
+        let members: Vec<&Player> = self
+            .players
+            .iter()
+            .filter(|player| player.symbol == player_symbol && player.role == Role::Player)
+            .collect();
+        let mover = members.get(self.moves_by(player_symbol) % members.len().max(1))?;
+        Some(&mover.nick)
+    }
+
+    // Prazo do relógio da sala: o do jogador da vez ou, com a partida pausada, o da reconexão
+    pub fn turn_deadline(&self) -> Option<Instant> {
+        match self.status {
+            RoomStatus::Playing => Some(self.turn_started_at + self.turn_limit()),
+            RoomStatus::Paused => self.away.map(|away| away.deadline),
+            _ => None,
+        }
+    }
+
+    // A contagem de ausência recomeça para todos quando a partida começa ou é retomada
+    fn reset_activity(&mut self) {
+        let now = Instant::now();
+        self.last_input = self
+            .players
+            .iter()
+            .filter(|player| player.role == Role::Player)
+            .map(|player| (player.nick.clone(), now))
+            .collect();
+        self.afk.clear();
+    }
+
+    // Registra um comando do jogador; quem tinha sido avisado como ausente volta
+    fn record_activity(&mut self, nick: &str) {
+        if let Some(last_input) = self.last_input.get_mut(nick) {
+            *last_input = Instant::now();
+        }
+        if self.afk.remove(nick).is_some() && self.status == RoomStatus::Playing {
+            if let Some(symbol) = self.symbol_of(nick) {
+                let _ = self.events.send(RoomEvent::Notice(format!("O {} voltou.\n", self.glyph(symbol))));
+            }
+        }
+    }
+
+    fn symbol_of(&self, nick: &str) -> Option<i32> {
+        self.players
+            .iter()
+            .find(|player| player.nick == nick && player.role == Role::Player)
+            .map(|player| player.symbol)
+    }
+
+    // Próximo momento em que alguém passa a contar como ausente ou esgota o aviso de ausência
+    pub fn afk_deadline(&self) -> Option<Instant> {
+        let timeout = self.afk_timeout?;
+        if self.status != RoomStatus::Playing {
+            return None;
+        }
+        self.last_input
+            .iter()
+            .map(|(nick, last_input)| match self.afk.get(nick) {
+                Some(warned_at) => *warned_at + timeout,
+                None => *last_input + timeout,
+            })
+            .min()
+    }
+
+    // Avisa a sala de quem ficou ausente. Quem continua ausente depois do aviso perde por W.O.
+    // nas partidas ranqueadas; nas amistosas a partida é cancelada, sem ir para o histórico.
+    // Retorna true se a sala deve ser fechada.
+    pub fn expire_afk(&mut self) -> bool {
+        let timeout = match self.afk_timeout {
+            Some(timeout) if self.status == RoomStatus::Playing => timeout,
+            _ => return false,
+        };
+        let now = Instant::now();
+        let expired: Vec<String> = self
+            .last_input
+            .iter()
+            .filter(|(nick, last_input)| match self.afk.get(*nick) {
+                Some(warned_at) => now >= *warned_at + timeout,
+                None => now >= **last_input + timeout,
+            })
+            .map(|(nick, _)| nick.clone())
+            .collect();
+
+        for nick in expired {
+            let symbol = match self.symbol_of(&nick) {
+                Some(symbol) => symbol,
+                None => {
+                    self.last_input.remove(&nick);
+                    continue;
+                }
+            };
+            let glyph = self.glyph(symbol);
+            if let Entry::Vacant(warning) = self.afk.entry(nick.clone()) {
+                info!(nick = %nick, "jogador ausente");
+                warning.insert(now);
+                let consequence = if self.rated || self.tournament { "perde por W.O." } else { "a partida é cancelada" };
+                let _ = self.events.send(RoomEvent::Notice(format!(
+                    "O {} está ausente há {}s. Se não enviar nenhum comando em {}s, {}.\n",
+                    glyph,
+                    timeout.as_secs(),
+                    timeout.as_secs(),
+                    consequence
+                )));
+                continue;
+            }
+
+            self.afk.remove(&nick);
+            self.last_input.remove(&nick);
+            // No anel, cancelar a partida de todos por causa de um ausente seria injusto: ele só sai da
+            // roda. No torneio, a partida cancelada não diria quem avança.
+            if self.rated || self.tournament || matches!(self.game, Game::Ring(_)) {
+                info!(nick = %nick, "jogador ausente perdeu por W.O.");
+                let _ = self.events.send(RoomEvent::Notice(format!("O {} continuou ausente e perdeu por W.O.\n", glyph)));
+                self.concede(symbol, true);
+            } else {
+                info!(nick = %nick, "partida cancelada por ausência");
+                let _ = self.events.send(RoomEvent::Notice(format!(
+                    "O {} continuou ausente e a partida foi cancelada. Ela não vale para o histórico.\n",
+                    glyph
+                )));
+                return true;
+            }
+            if self.status != RoomStatus::Playing {
+                break;
+            }
+        }
+        false
+    }
+
+    // O prazo do relógio passou: quem estava sendo esperado perde a partida
+    pub fn expire_deadline(&mut self) {
+        match self.away.take() {
+            Some(away) => self.expire_reconnect(away),
+            // Na vez da multidão o prazo é o da votação
+            None if self.open_vote().is_some() => self.close_crowd_vote(),
+            None => self.expire_turn(),
+        }
+    }
+
+    // Tempo do jogador da vez: o que resta no relógio de xadrez ou o limite por jogada
+    fn turn_limit(&self) -> Duration {
+        if let Some(crowd) = self.open_vote() {
+            return crowd.window;
+        }
+        match &self.clock {
+            Some(clock) => clock.remaining(self.current_turn()),
+            // O atraso da exibição não sai do tempo de quem joga
+            None => self.turn_timeout + self.move_delay,
+        }
+    }
+
+    // Relógio com o turno em andamento já descontado, para mostrar e para guardar a partida
+    fn clock_now(&self) -> Option<GameClock> {
+        let mut clock = self.clock?;
+        let elapsed = match self.status {
+            RoomStatus::Playing => self.turn_started_at.elapsed(),
+            RoomStatus::Paused => self.away.map_or(Duration::ZERO, |away| away.turn_elapsed),
+            RoomStatus::OnHold => self.held_turn_elapsed,
+            RoomStatus::Waiting | RoomStatus::Finished(_) => Duration::ZERO,
+        };
+        clock.spend(self.current_turn(), elapsed);
+        Some(clock)
+    }
+
+    // Fecha o turno de quem acabou de jogar no relógio de xadrez, se a sala usa um
+    fn charge_clock(&mut self, player_symbol: i32) {
+        let elapsed = self.turn_started_at.elapsed();
+        if let Some(clock) = &mut self.clock {
+            clock.complete_move(player_symbol, elapsed);
+        }
+    }
+
+    // O jogador da vez estourou o tempo e perde a partida
+    fn expire_turn(&mut self) {
+        let loser = self.current_turn();
+        // A bandeira caiu: o relógio de quem perdeu fica zerado
+        if let Some(clock) = &mut self.clock {
+            clock.spend(loser, Duration::MAX);
+        }
+        info!(loser, "tempo esgotado");
+        let symbol = self.glyph(loser);
+        let _ = self.events.send(RoomEvent::Notice(format!("Tempo esgotado! O {} perdeu por tempo.\n", symbol)));
+        self.concede(loser, false);
+    }
+
+    // O jogador que caiu não voltou a tempo e perde por W.O.
+    fn expire_reconnect(&mut self, away: Away) {
+        info!(symbol = away.symbol, "jogador não voltou a tempo");
+        self.players.retain(|player| player.symbol != away.symbol);
+        if let Some(series) = &mut self.series {
+            series.concede(-away.symbol);
+        }
+        let symbol = self.glyph(away.symbol);
+        let _ = self.events.send(RoomEvent::Notice(format!("O {} não voltou a tempo e perdeu por W.O.\n", symbol)));
+        self.concede(away.symbol, true);
+    }
+
+    // Quem desiste, estoura o tempo ou abandona a partida perde. No anel ele só sai da roda,
+    // com as peças no tabuleiro, e a partida segue até alguém fechar uma sequência ou sobrar
+    // um só jogador.
+    fn concede(&mut self, loser: i32, forfeit: bool) {
+        let ring_state = match &mut self.game {
+            Game::Ring(ring_state) => ring_state,
+            _ => return self.finish(-loser, forfeit),
+        };
+        let turn = ring_state.current_turn;
+        ring_state.eliminate(loser);
+        if ring_state.current_turn != turn {
+            self.turn_started_at = Instant::now();
+        }
+        match self.result() {
+            Some(winner) => self.finish(winner, forfeit),
+            None => self.notify_state_changed(),
+        }
+    }
+
+    // Avisa as tarefas dos jogadores que o estado da sala mudou
+    pub fn notify_state_changed(&self) {
+        // Sem receptores inscritos o envio falha, o que não é um problema
+        let _ = self.events.send(RoomEvent::StateChanged);
+    }
+
+    // Encerra a partida e avisa todos os jogadores da sala. forfeit indica vitória por
+    // desistência ou W.O., sem decisão no tabuleiro. Numa série ainda em aberto, só o jogo
+    // termina: o placar é anunciado e o próximo jogo começa com o outro jogador.
+    fn finish(&mut self, winner: i32, forfeit: bool) {
+        info!(winner, forfeit, moves = self.moves.len(), "partida encerrada");
+        if let Some(mut series) = self.series {
+            if series.winner().is_none() {
+                series.add_result(winner);
+                self.series = Some(series);
+            }
+            if series.winner().is_none() {
+                self.next_series_game(winner, &series);
+                return;
+            }
+            let [player_x, player_o] = &self.match_players;
+            let champion = if winner == 1 { player_x } else { player_o };
+            let _ = self.events.send(RoomEvent::Notice(format!(
+                "{} venceu a série melhor de {}! Placar final: {}.\n",
+                champion,
+                series.best_of,
+                self.series_score(&series)
+            )));
+        }
+        self.status = RoomStatus::Finished(winner);
+        METRICS.game_completed(self.match_started_at.elapsed().unwrap_or_default());
+        self.record_game(winner, forfeit);
+        let _ = self.events.send(RoomEvent::GameOver);
+    }
+
+    // Grava a partida encerrada no histórico
+    fn record_game(&self, winner: i32, forfeit: bool) {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return,
+        };
+        // O histórico e o ranking são de partidas entre dois jogadores
+        if let Game::Ring(_) = self.game {
+            return;
+        }
+
+        // Em duplas, cada parceiro do X fica com um registro contra o parceiro do O sentado na
+        // mesma ordem, para que o histórico e as estatísticas de todos contem a partida
+        let pairs: Vec<(String, String)> = if self.teams {
+            self.team_members[0].iter().cloned().zip(self.team_members[1].iter().cloned()).collect()
+        } else {
+            vec![(self.match_players[0].clone(), self.match_players[1].clone())]
+        };
+        let started_at = self.match_started_at.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+        let mut records = Vec::new();
+        for (player_x, player_o) in pairs {
+            let record = GameRecord {
+                id: 0, // Definido pelo banco
+                mode: self.mode().name().to_string(),
+                board_config: self.board_config,
+                player_x,
+                player_o,
+                winner,
+                forfeit,
+                moves: self.moves.clone(),
+                started_at,
+                duration_secs: self.match_started_at.elapsed().map_or(0, |elapsed| elapsed.as_secs()),
+                series: self.series,
+                seed: Some(self.seed),
+            };
+            let id = match storage.record_game(&record) {
+                Ok(id) => {
+                    if let Some(dir) = storage.export_dir() {
+                        let record = GameRecord { id, ..record.clone() };
+                        if let Err(err) = export::write_to_dir(dir, &record) {
+                            error!(%err, "erro ao exportar a partida");
+                        }
+                    }
+                    id
+                }
+                Err(err) => {
+                    error!(%err, "erro ao gravar a partida no histórico");
+                    0
+                }
+            };
+            records.push(GameRecord { id, ..record });
+        }
+
+        // Partidas amistosas ficam no histórico, mas não mexem no ranking
+        if !self.rated {
+            return;
+        }
+        if self.teams {
+            match storage.update_team_ratings(&self.team_members[0], &self.team_members[1], winner) {
+                Ok(Some(ratings)) => {
+                    let ratings: Vec<String> = ratings.iter().map(|(nick, rating)| format!("{} {}", nick, rating)).collect();
+                    let _ = self.events.send(RoomEvent::Notice(format!("Rating: {}\n", ratings.join(", "))));
+                }
+                Ok(None) => {}
+                Err(err) => error!(%err, "erro ao atualizar o ranking"),
+            }
+            return;
+        }
+        let Some(record) = records.pop() else {
+            return;
+        };
+        let handicap_x = self.handicap.map_or(0, |handicap| handicap.kind.rating_points() * handicap.giver);
+        match storage.update_ratings(&record.player_x, &record.player_o, winner, handicap_x) {
+            Ok(Some((rating_x, rating_o))) => {
+                let _ = self.events.send(RoomEvent::Notice(format!(
+                    "Rating: {} {}, {} {}\n",
+                    record.player_x, rating_x, record.player_o, rating_o
+                )));
+            }
+            Ok(None) => {}
+            Err(err) => error!(%err, "erro ao atualizar o ranking"),
+        }
+        if record.id != 0 {
+            self.report_fair_play(storage, record.id);
+        }
+    }
+
+    // Compara as jogadas da partida ranqueada com as do motor e grava o relatório para os
+    // administradores. Roda fora da sala, porque o solucionador pode levar alguns segundos.
+    fn report_fair_play(&self, storage: &Arc<Storage>, game_id: i64) {
+        let start = Game::new(self.mode(), self.board_config, self.first_turn);
+        let moves = self.moves.clone();
+        let think_times = self.think_times.clone();
+        let players = self.match_players.clone();
+        let storage = Arc::clone(storage);
+        tokio::task::spawn_blocking(move || {
+            let deadline = std::time::Instant::now() + fairplay::ANALYSIS_TIME_LIMIT;
+            let report = fairplay::analyze(start, &moves, &think_times, &players, deadline);
+            let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+            if let Err(err) = storage.save_fair_play_report(game_id, &report, created_at) {
+                error!(%err, "erro ao gravar o relatório de jogo limpo");
+            }
+        });
+    }
+
+    // Jogadores sentados à mesa, sem contar os espectadores
+    pub fn seated_count(&self) -> usize {
+        self.players.iter().filter(|player| player.role != Role::Spectator).count()
+    }
+
+    // Jogadores humanos sentados; sem eles a sala não tem mais motivo para existir
+    pub fn human_count(&self) -> usize {
+        self.players.iter().filter(|player| player.role == Role::Player).count()
+    }
+
+    // Interrompe a partida em andamento para o encerramento do servidor. A sala volta a
+    // aguardar, de modo que a saída dos jogadores não conte como W.O.
+    pub fn suspend(&mut self, room_name: &str) -> Option<SavedGame> {
+        if !matches!(self.status, RoomStatus::Playing | RoomStatus::Paused | RoomStatus::OnHold) {
+            return None;
+        }
+        self.status = RoomStatus::Waiting;
+        self.away = None;
+        // Partidas do anel não são guardadas: a sala só sabe reabrir partidas de dois jogadores
+        if let Game::Ring(_) = self.game {
+            return None;
+        }
+        Some(self.saved_game(room_name))
+    }
+
+    // Cópia da partida em andamento para guardar no banco, sem interromper o jogo. Se o
+    // servidor cair, ela é reaberta como as partidas suspensas no encerramento.
+    pub fn snapshot(&self, room_name: &str) -> Option<SavedGame> {
+        let in_progress = matches!(self.status, RoomStatus::Playing | RoomStatus::Paused | RoomStatus::OnHold);
+        if !in_progress || matches!(self.game, Game::Ring(_)) || self.teams || self.crowd.is_some() {
+            return None;
+        }
+        Some(self.saved_game(room_name))
+    }
+
+    // A partida atual como fica guardada no banco
+    fn saved_game(&self, room_name: &str) -> SavedGame {
+        SavedGame {
+            room_name: room_name.to_string(),
+            mode: self.mode().name().to_string(),
+            board_config: self.board_config,
+            player_x: self.match_players[0].clone(),
+            player_o: self.match_players[1].clone(),
+            first_turn: self.first_turn,
+            seed: self.seed,
+            moves: self.moves.clone(),
+            pop_out: self.pop_out,
+            power_up: self.power_up,
+            clock: self.clock_now(),
+            rated: self.rated,
+            handicap: self.handicap,
+            saved_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+        }
+    }
+
+    // Retira um jogador da sala; com a partida em andamento, o oponente vence por W.O.
+    pub fn remove_player(&mut self, nick: &str) {
+        // Na pausa combinada a vaga fica guardada até o jogador voltar com o código de reconexão
+        let held = self.players.iter().find(|player| player.nick == nick && player.role == Role::Player);
+        if let Some(player) = held.filter(|_| self.status == RoomStatus::OnHold) {
+            let player_symbol = player.symbol;
+            if !self.absent.contains(&player_symbol) {
+                self.absent.push(player_symbol);
+            }
+            info!(nick, symbol = player_symbol, "jogador saiu durante a pausa");
+            let symbol = self.glyph(player_symbol);
+            let _ = self.events.send(RoomEvent::Notice(format!("O {} saiu da sala; a partida continua pausada.\n", symbol)));
+            return;
+        }
+
+        let player = match self.players.iter().position(|player| player.nick == nick) {
+            Some(index) => self.players.remove(index),
+            None => return,
+        };
+
+        // A saída de um espectador não afeta a partida
+        if player.role == Role::Spectator {
+            return;
+        }
+
+        let player_symbol = player.symbol;
+        self.rematch_votes.retain(|symbol| *symbol != player_symbol);
+
+        // Sair com a partida pausada também é W.O., mesmo que o outro jogador ainda não tenha voltado.
+        // No meio de uma série, quem sai perde a série inteira.
+        if matches!(self.status, RoomStatus::Playing | RoomStatus::Paused) {
+            self.away = None;
+            if let Some(series) = &mut self.series {
+                series.concede(-player_symbol);
+            }
+            info!(nick, symbol = player_symbol, "jogador saiu durante a partida");
+            let _ = self.events.send(RoomEvent::Notice(format!("Oponente desconectou: {}.\n", nick)));
+            self.concede(player_symbol, true);
+        } else {
+            let _ = self.events.send(RoomEvent::Notice(format!("Oponente saiu da sala: {}.\n", nick)));
+        }
+    }
+
+    // A conexão do jogador caiu. Com a partida em andamento, a sala pausa e guarda a vaga
+    // até o prazo de reconexão; fora disso, é o mesmo que sair. Retorna true se pausou.
+    pub fn disconnect_player(&mut self, nick: &str) -> bool {
+        let player = match self.players.iter().find(|player| player.nick == nick) {
+            Some(player) => player.clone(),
+            None => return false,
+        };
+        // No anel e em duplas a partida não pausa por um jogador: quem cai sai da mesa
+        let ring = matches!(self.game, Game::Ring(_)) || self.teams;
+        if player.role != Role::Player || self.status != RoomStatus::Playing || self.reconnect_timeout.is_zero() || ring {
+            self.remove_player(nick);
+            return false;
+        }
+
+        info!(nick, symbol = player.symbol, "jogador caiu; partida pausada");
+        self.status = RoomStatus::Paused;
+        self.undo_request = None;
+        self.abort_request = None;
+        self.draw_offer = None;
+        self.away = Some(Away {
+            symbol: player.symbol,
+            deadline: Instant::now() + self.reconnect_timeout,
+            turn_elapsed: self.turn_started_at.elapsed(),
+        });
+        let symbol = self.glyph(player.symbol);
+        let _ = self.events.send(RoomEvent::Notice(format!(
+            "O {} perdeu a conexão. A partida está pausada; ele tem {}s para voltar.\n",
+            symbol,
+            self.reconnect_timeout.as_secs()
+        )));
+        true
+    }
+
+    // Devolve a vaga a quem caiu e retoma a partida de onde parou
+    // O lugar é achado pelo apelido, porque a regra da troca pode ter trocado os símbolos
+    pub fn resume_player(&mut self, nick: &str) -> Result<Player, String> {
+        let player = match self.players.iter().find(|player| player.nick == nick && player.role == Role::Player) {
+            Some(player) => player.clone(),
+            None => return Err("Não há partida pausada esperando por você.\n".to_string()),
+        };
+        let symbol = player.symbol;
+        if self.status == RoomStatus::OnHold && self.absent.contains(&symbol) {
+            return self.return_to_hold(player);
+        }
+        let away = match self.away {
+            Some(away) if away.symbol == symbol && self.status == RoomStatus::Paused => away,
+            _ => return Err("Não há partida pausada esperando por você.\n".to_string()),
+        };
+
+        // O relógio do turno continua de onde parou
+        self.away = None;
+        self.status = RoomStatus::Playing;
+        self.turn_started_at = Instant::now() - away.turn_elapsed;
+        self.reset_activity();
+        info!(nick = %player.nick, symbol, "jogador voltou; partida retomada");
+
+        let symbol = self.glyph(symbol);
+        let _ = self.events.send(RoomEvent::Notice(format!("O {} voltou. A partida continua.\n", symbol)));
+        self.notify_state_changed();
+        Ok(player)
+    }
+
+    // Quem saiu durante a pausa combinada volta ao seu lugar; a partida segue pausada
+    fn return_to_hold(&mut self, player: Player) -> Result<Player, String> {
+        let symbol = player.symbol;
+        self.absent.retain(|absent| *absent != symbol);
+        info!(nick = %player.nick, symbol, "jogador voltou à partida pausada");
+
+        let symbol = if symbol == 1 { 'X' } else { 'O' };
+        let _ = self.events.send(RoomEvent::Notice(format!(
+            "O {} voltou à sala. A partida está pausada; use /resume para continuar.\n",
+            symbol
+        )));
+        self.notify_state_changed();
+        Ok(player)
+    }
+
+    // A troca vale só como resposta à primeira jogada de cada partida
+    fn swap_available(&self) -> bool {
+        self.swap_rule && self.status == RoomStatus::Playing && self.moves.len() == 1
+    }
+
+    // Regra da troca: em vez de responder à primeira jogada, o segundo jogador fica com o
+    // lado de quem a fez, e quem abriu a partida passa a jogar do outro lado, já na vez dele.
+    // O tabuleiro não muda; só os símbolos dos jogadores são trocados.
+    pub fn swap_sides(&mut self, player_symbol: i32) -> Result<(), String> {
+        if !self.swap_rule {
+            return Err("Esta sala não usa a regra da troca.\n".to_string());
+        }
+        if !self.swap_available() || self.current_turn() != player_symbol {
+            return Err("A troca só vale como resposta à primeira jogada da partida.\n".to_string());
+        }
+
+        for player in self.players.iter_mut().filter(|player| player.role != Role::Spectator) {
+            player.symbol = -player.symbol;
+        }
+        self.match_players.swap(0, 1);
+        for symbol in &mut self.hints_used {
+            *symbol = -*symbol;
+        }
+        if let Some(handicap) = &mut self.handicap {
+            handicap.giver = -handicap.giver;
+        }
+        // O relógio acompanha o jogador: quem fez a primeira jogada leva o tempo dela para o O
+        if let Some(clock) = &mut self.clock {
+            clock.remaining.swap(0, 1);
+        }
+        if let Some(series) = &mut self.series {
+            std::mem::swap(&mut series.wins_x, &mut series.wins_o);
+        }
+        self.rematch_votes.clear();
+        self.undo_request = None;
+        self.pause_request = None;
+        // Propostas de empate e de anulação seguem com quem as fez, que agora joga do outro lado
+        self.draw_offer = self.draw_offer.map(|symbol| -symbol);
+        self.abort_request = self.abort_request.map(|symbol| -symbol);
+        self.turn_started_at = Instant::now();
+        info!(symbol = player_symbol, "regra da troca: jogadores trocaram de lado");
+
+        let _ = self.events.send(RoomEvent::SidesSwapped);
+        let [player_x, player_o] = &self.match_players;
+        let _ = self.events.send(RoomEvent::Notice(format!(
+            "Troca! {} fica com o X e a primeira jogada; {} passa a ser o O e joga agora.\n",
+            player_x, player_o
+        )));
+        self.notify_state_changed();
+        Ok(())
+    }
+
+    // Pede uma pausa; a partida só para quando o oponente também pedir. O relógio do turno
+    // fica parado e a partida é guardada, para sobreviver a um reinício do servidor.
+    pub fn request_pause(&mut self, player_symbol: i32, room_name: &str) -> Result<(), String> {
+        if self.status != RoomStatus::Playing {
+            return Err("Só é possível pausar durante a partida.\n".to_string());
+        }
+        if self.players.iter().any(|player| player.role == Role::Ai) {
+            return Err("Partidas contra o computador não podem ser pausadas.\n".to_string());
+        }
+        if let Game::Ring(_) = self.game {
+            return Err("Partidas do modo anel não podem ser pausadas.\n".to_string());
+        }
+        if self.teams {
+            return Err("Partidas em duplas não podem ser pausadas.\n".to_string());
+        }
+        // Na ranqueada o relógio não para: a pausa combinada daria tempo extra para pensar
+        if self.rated {
+            return Err("Partidas ranqueadas não podem ser pausadas.\n".to_string());
+        }
+        // O chaveamento espera todas as partidas da rodada acabarem
+        if self.tournament {
+            return Err("Partidas de torneio não podem ser pausadas.\n".to_string());
+        }
+        // Sem códigos de reconexão ninguém conseguiria voltar a uma partida pausada
+        if self.reconnect_timeout.is_zero() {
+            return Err("Este servidor não guarda partidas pausadas.\n".to_string());
+        }
+
+        let symbol = if player_symbol == 1 { 'X' } else { 'O' };
+        match self.pause_request {
+            Some(requester) if requester == player_symbol => {
+                return Err("Você já pediu a pausa. Aguarde o oponente concordar.\n".to_string());
+            }
+            Some(_) => {}
+            None => {
+                self.pause_request = Some(player_symbol);
+                let _ = self.events.send(RoomEvent::Notice(format!(
+                    "O {} pediu uma pausa. Digite /pause para concordar.\n",
+                    symbol
+                )));
+                return Ok(());
+            }
+        }
+
+        self.pause_request = None;
+        self.undo_request = None;
+        self.status = RoomStatus::OnHold;
+        self.held_turn_elapsed = self.turn_started_at.elapsed();
+        info!("partida pausada em comum acordo");
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.save_in_progress(&self.saved_game(room_name)) {
+                error!(%err, "erro ao guardar a partida pausada");
+            }
+        }
+
+        let _ = self.events.send(RoomEvent::Notice(
+            "Partida pausada. O relógio parou; use /resume para continuar. Se sair, volte depois com o código de reconexão.\n"
+                .to_string(),
+        ));
+        self.notify_state_changed();
+        Ok(())
+    }
+
+    // Retoma a partida pausada com /pause, com o relógio do turno de onde parou
+    pub fn resume_game(&mut self, player_symbol: i32, room_name: &str) -> Result<(), String> {
+        if self.status != RoomStatus::OnHold {
+            return Err("A partida não está pausada.\n".to_string());
+        }
+        if !self.absent.is_empty() {
+            return Err("O oponente ainda não voltou à sala.\n".to_string());
+        }
+
+        self.status = RoomStatus::Playing;
+        self.turn_started_at = Instant::now() - self.held_turn_elapsed;
+        self.reset_activity();
+        info!(symbol = player_symbol, "partida retomada");
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.discard_saved_game(room_name) {
+                error!(%err, "erro ao apagar a partida guardada");
+            }
+        }
+
+        let symbol = if player_symbol == 1 { 'X' } else { 'O' };
+        let _ = self.events.send(RoomEvent::Notice(format!("O {} retomou a partida.\n", symbol)));
+        self.notify_state_changed();
+        Ok(())
+    }
+
+    // O jogador desiste e o oponente vence
+    pub fn resign(&mut self, player_symbol: i32) -> Result<(), String> {
+        if !matches!(self.status, RoomStatus::Playing | RoomStatus::Paused | RoomStatus::OnHold) {
+            return Err("Não há partida em andamento para desistir.\n".to_string());
+        }
+        self.away = None;
+        // Quem saiu durante a pausa não volta mais para esta partida
+        let absent = std::mem::take(&mut self.absent);
+        self.players.retain(|player| !absent.contains(&player.symbol));
+        info!(symbol = player_symbol, "jogador desistiu");
+        let symbol = self.glyph(player_symbol);
+        let _ = self.events.send(RoomEvent::Notice(format!("O {} desistiu da partida.\n", symbol)));
+        self.concede(player_symbol, true);
+        Ok(())
+    }
+
+    // Registra que o jogador quer a revanche. Retorna true se a nova partida começou.
+    pub fn accept_rematch(&mut self, player_symbol: i32) -> bool {
+        if !self.rematch_votes.contains(&player_symbol) {
+            self.rematch_votes.push(player_symbol);
+        }
+
+        // O computador sempre aceita a revanche, então basta o voto dos humanos. Em duplas,
+        // os votos são por lado, e basta um parceiro de cada.
+        let needed = if self.teams { 2 } else { self.human_count() };
+        if self.rematch_votes.len() < needed {
+            return false;
+        }
+
+        // Todos aceitaram: o tabuleiro é zerado e quem começa é invertido. Numa sala de
+        // séries, a revanche é uma série nova.
+        self.rematch_votes.clear();
+        if let Some(series) = &mut self.series {
+            *series = SeriesScore::new(series.best_of);
+        }
+        self.reset_board();
+        self.begin_match();
+
+        let first = self.glyph(self.first_turn);
+        let _ = self.events.send(RoomEvent::Notice(format!("Nova partida! O {} começa.\n", first)));
+        self.notify_state_changed();
+        true
+    }
+
+    // Próximo jogo de uma série, anunciado com o resultado do anterior e o placar
+    fn next_series_game(&mut self, winner: i32, series: &SeriesScore) {
+        let result = match winner {
+            1 => format!("{} venceu o jogo.", self.match_players[0]),
+            -1 => format!("{} venceu o jogo.", self.match_players[1]),
+            _ => "Jogo empatado.".to_string(),
+        };
+        self.reset_board();
+        let first = if self.first_turn == 1 { 'X' } else { 'O' };
+        let _ = self.events.send(RoomEvent::Notice(format!(
+            "{} Série melhor de {}: {}. Próximo jogo: o {} começa.\n",
+            result,
+            series.best_of,
+            self.series_score(series),
+            first
+        )));
+        self.notify_state_changed();
+    }
+
+    // Tabuleiro novo para o próximo jogo, com quem começa invertido; no anel, a vez de
+    // começar passa ao próximo da roda
+    fn reset_board(&mut self) {
+        self.first_turn = match self.mode() {
+            GameMode::Ring(players) => self.first_turn % players as i32 + 1,
+            _ => -self.first_turn,
+        };
+        self.game = Game::new(self.mode(), self.board_config, self.first_turn);
+        self.status = RoomStatus::Playing;
+        self.turn_started_at = Instant::now();
+        self.reset_activity();
+        self.last_move = None;
+        self.moves.clear();
+        self.think_times.clear();
+        self.undo_request = None;
+        self.abort_request = None;
+        self.draw_offer = None;
+        self.pause_request = None;
+        self.hints_used.clear();
+        if let Some(clock) = &mut self.clock {
+            clock.reset();
+        }
+    }
+
+    // Reserva uma dica para o jogador da vez e entrega a posição a analisar. As dicas são
+    // poucas por partida e não valem quando o resultado mexe no ranking.
+    pub fn take_hint(&mut self, player_symbol: i32) -> Result<Hint, String> {
+        if self.status != RoomStatus::Playing {
+            return Err("Só é possível pedir dicas durante a partida.\n".to_string());
+        }
+        let game_state = match &self.game {
+            Game::ConnectFour(game_state) => game_state,
+            _ => return Err("As dicas só estão disponíveis no lig4.\n".to_string()),
+        };
+        if game_state.current_turn != player_symbol {
+            return Err("Só é possível pedir dicas na sua vez.\n".to_string());
+        }
+        if self.rated {
+            return Err("Não há dicas em partidas ranqueadas.\n".to_string());
+        }
+        if self.tournament {
+            return Err("Não há dicas em partidas de torneio.\n".to_string());
+        }
+        let used = self.hints_used.iter().filter(|symbol| **symbol == player_symbol).count();
+        if used >= HINTS_PER_GAME {
+            return Err(format!("Você já usou as {} dicas desta partida.\n", HINTS_PER_GAME));
+        }
+
+        let hint = Hint {
+            position: game_state.clone(),
+            last_move: self.last_move,
+            remaining: HINTS_PER_GAME - used - 1,
+        };
+        self.hints_used.push(player_symbol);
+        info!(symbol = player_symbol, "dica pedida");
+        Ok(hint)
+    }
+
+    // Entrega as posições da partida para o /eval. Enquanto a partida corre, só quem assiste
+    // pode pedir, para a avaliação não virar dica; depois do fim, todos na sala.
+    pub fn evaluation(&self, player_symbol: i32) -> Result<Evaluation, String> {
+        if !matches!(self.game, Game::ConnectFour(_)) {
+            return Err("A avaliação só está disponível no lig4.\n".to_string());
+        }
+        if self.board_config != BoardConfig::default() || self.pop_out || self.power_up || self.handicap.is_some() {
+            return Err("A avaliação só vale no lig4 padrão, de 7x6 e sem variantes.\n".to_string());
+        }
+        let finished = matches!(self.status, RoomStatus::Finished(_));
+        if !finished && player_symbol != 0 {
+            return Err("Durante a partida, o /eval é só para quem assiste. Os jogadores podem usá-lo depois do fim.\n".to_string());
+        }
+
+        let mut position = GameState::with_config(self.board_config);
+        position.current_turn = self.first_turn;
+        let mut positions = vec![position.clone()];
+        for played in &self.moves {
+            if position.apply_move(&Move { col: played.col }).is_err() {
+                break;
+            }
+            positions.push(position.clone());
+        }
+        Ok(Evaluation { positions, moves: self.moves.clone(), finished })
+    }
+
+    // Pede para desfazer a última jogada do jogador; o oponente precisa aprovar.
+    // O computador sempre aprova.
+    pub fn request_undo(&mut self, player_symbol: i32) -> Result<(), String> {
+        if self.status != RoomStatus::Playing {
+            return Err("Só é possível desfazer jogadas durante a partida.\n".to_string());
+        }
+        if let Game::Ring(_) = self.game {
+            return Err("Não é possível desfazer jogadas no modo anel.\n".to_string());
+        }
+        if self.rated {
+            return Err("Não é possível desfazer jogadas em partidas ranqueadas.\n".to_string());
+        }
+        if self.tournament {
+            return Err("Não é possível desfazer jogadas em partidas de torneio.\n".to_string());
+        }
+        if !self.moves.iter().any(|played_move| played_move.symbol == player_symbol) {
+            return Err("Você ainda não tem jogadas para desfazer.\n".to_string());
+        }
+        if self.undo_request.is_some() {
+            return Err("Já existe um pedido para desfazer aguardando resposta.\n".to_string());
+        }
+
+        if self.players.iter().any(|player| player.role == Role::Ai) {
+            self.undo_moves(player_symbol);
+            return Ok(());
+        }
+
+        self.undo_request = Some(player_symbol);
+        let symbol = self.glyph(player_symbol);
+        let _ = self.events.send(RoomEvent::Notice(format!(
+            "O {} pediu para desfazer a última jogada. Responda com /accept ou /decline.\n",
+            symbol
+        )));
+        Ok(())
+    }
+
+    // Pede para anular a partida, que acaba sem resultado e sem ir para o histórico, ao
+    // contrário da desistência. Vale na hora antes de cada lado jogar uma vez ou contra o
+    // computador; depois disso, o oponente precisa aprovar.
+    pub fn request_abort(&mut self, player_symbol: i32) -> Result<(), String> {
+        if self.status != RoomStatus::Playing {
+            return Err("Só é possível anular a partida enquanto ela está em andamento.\n".to_string());
+        }
+        if let Game::Ring(_) = self.game {
+            return Err("Não é possível anular a partida no modo anel.\n".to_string());
+        }
+        // A partida anulada não diria quem avança no chaveamento
+        if self.tournament {
+            return Err("Partidas de torneio não podem ser anuladas. Para sair da partida, use resign.\n".to_string());
+        }
+        let glyph = self.glyph(player_symbol);
+        if self.moves.len() < 2 || self.players.iter().any(|player| player.role == Role::Ai) {
+            self.abort(format!("O {} anulou a partida. Ela não vale para o histórico.\n", glyph));
+            return Ok(());
+        }
+        match self.abort_request {
+            // Os dois lados pediram: é o mesmo que aceitar o pedido do outro
+            Some(requester) if requester != player_symbol => {
+                self.abort("Partida anulada de comum acordo. Ela não vale para o histórico.\n".to_string());
+                Ok(())
+            }
+            Some(_) => Err("Você já pediu para anular a partida. Aguarde a resposta do oponente.\n".to_string()),
+            None => {
+                self.abort_request = Some(player_symbol);
+                let _ = self.events.send(RoomEvent::Notice(format!(
+                    "O {} pediu para anular a partida, sem resultado. Responda com /accept ou /decline.\n",
+                    glyph
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    // A partida acaba sem vencedor nem registro; a tarefa da sala termina logo depois
+    fn abort(&mut self, notice: String) {
+        info!(moves = self.moves.len(), "partida anulada");
+        self.aborted = true;
+        self.abort_request = None;
+        let _ = self.events.send(RoomEvent::Notice(notice));
+        let _ = self.events.send(RoomEvent::Aborted);
+    }
+
+    // Propõe empate ao oponente. A proposta vale até a próxima jogada de qualquer lado ou até
+    // ser recusada; se o oponente também propuser, é o mesmo que aceitar.
+    pub fn offer_draw(&mut self, player_symbol: i32) -> Result<(), String> {
+        if self.status != RoomStatus::Playing {
+            return Err("Só é possível propor empate durante a partida.\n".to_string());
+        }
+        if let Game::Ring(_) = self.game {
+            return Err("Não é possível propor empate no modo anel.\n".to_string());
+        }
+        if self.players.iter().any(|player| player.role == Role::Ai) {
+            return Err("O computador não aceita empate.\n".to_string());
+        }
+        match self.draw_offer {
+            Some(offerer) if offerer != player_symbol => {
+                self.agree_draw();
+                Ok(())
+            }
+            Some(_) => Err("Você já propôs empate. Aguarde a resposta do oponente.\n".to_string()),
+            None => {
+                self.draw_offer = Some(player_symbol);
+                let glyph = self.glyph(player_symbol);
+                let _ = self.events.send(RoomEvent::Notice(format!(
+                    "O {} propõe empate. Responda com /accept ou /decline; a proposta vale até a próxima jogada.\n",
+                    glyph
+                )));
+                Ok(())
+            }
+        }
+    }
+
+    // Empate aceito: conta como qualquer empate, no histórico e no ranking
+    fn agree_draw(&mut self) {
+        self.draw_offer = None;
+        info!("empate de comum acordo");
+        let _ = self.events.send(RoomEvent::Notice("Empate de comum acordo.\n".to_string()));
+        self.finish(0, false);
+    }
+
+    // A jogada feita responde à proposta de empate pendente, que deixa de valer
+    fn withdraw_draw_offer(&mut self) {
+        if self.draw_offer.take().is_some() {
+            let _ = self.events.send(RoomEvent::Notice("A proposta de empate deixou de valer com a nova jogada.\n".to_string()));
+        }
+    }
+
+    // Resposta do oponente a um pedido pendente: o de anular a partida, a proposta de empate
+    // ou o pedido para desfazer, nessa ordem
+    pub fn answer(&mut self, player_symbol: i32, accepted: bool) -> Result<(), String> {
+        let draw_offer = self.draw_offer.filter(|offerer| *offerer != player_symbol);
+        if let (None, Some(_), RoomStatus::Playing) = (self.abort_request, draw_offer, self.status) {
+            if accepted {
+                self.agree_draw();
+            } else {
+                self.draw_offer = None;
+                let glyph = self.glyph(player_symbol);
+                let _ = self.events.send(RoomEvent::Notice(format!("O {} recusou o empate.\n", glyph)));
+            }
+            return Ok(());
+        }
+        match self.abort_request {
+            Some(requester) if requester != player_symbol && self.status == RoomStatus::Playing => {
+                self.abort_request = None;
+                if accepted {
+                    self.abort("Partida anulada de comum acordo. Ela não vale para o histórico.\n".to_string());
+                } else {
+                    let _ = self.events.send(RoomEvent::Notice("O pedido para anular a partida foi recusado.\n".to_string()));
+                }
+                Ok(())
+            }
+            _ => self.answer_undo(player_symbol, accepted),
+        }
+    }
+
+    // Resposta do oponente ao pedido para desfazer
+    fn answer_undo(&mut self, player_symbol: i32, accepted: bool) -> Result<(), String> {
+        let requester = match self.undo_request {
+            Some(requester) if requester != player_symbol => requester,
+            _ => return Err("Não há pedido nem proposta aguardando sua resposta.\n".to_string()),
+        };
+        self.undo_request = None;
+
+        if accepted {
+            self.undo_moves(requester);
+        } else {
+            let _ = self.events.send(RoomEvent::Notice("O pedido para desfazer foi recusado.\n".to_string()));
+        }
+        Ok(())
+    }
+
+    // Desfaz as jogadas até a última de quem pediu, inclusive. Se o oponente já respondeu,
+    // a resposta também é desfeita e a vez volta para quem pediu.
+    fn undo_moves(&mut self, player_symbol: i32) {
+        self.draw_offer = None;
+        while let Some(played_move) = self.moves.pop() {
+            if played_move.symbol == player_symbol {
+                break;
+            }
+        }
+        self.think_times.truncate(self.moves.len());
+
+        // A partida é refeita desde o início, o que também acerta o turno quando houve passe no reversi
+        self.game = Game::new(self.mode(), self.board_config, self.first_turn);
+        for played_move in &self.moves {
+            self.game.replay(played_move);
+        }
+        self.grant_extra_move();
+        self.last_move = self.moves.last().map(|played_move| (played_move.row, played_move.col));
+        self.turn_started_at = Instant::now();
+
+        let _ = self.events.send(RoomEvent::Notice("Jogada desfeita.\n".to_string()));
+        self.notify_state_changed();
+    }
+
+    fn ensure_not_finished(&self) -> Result<(), GameError> {
+        match self.status {
+            // Ninguém joga antes de todos sentarem; o relógio e a vez só começam com a sala cheia
+            RoomStatus::Waiting => Err(GameError::GameNotStarted),
+            RoomStatus::Finished(_) => Err(GameError::GameFinished),
+            RoomStatus::Paused => Err(GameError::GamePaused),
+            RoomStatus::OnHold => Err(GameError::GameOnHold),
+            RoomStatus::Playing => Ok(()),
+        }
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.game.mode()
+    }
+
+    // Nome do modo para a lista de salas, com as dimensões quando o tabuleiro não é o padrão
+    // e o placar quando a sala joga uma série
+    pub fn mode_label(&self) -> String {
+        let label = match self.mode() {
+            GameMode::ConnectFour if self.board_config != BoardConfig::default() => format!(
+                "{} {}x{}, {} em linha",
+                self.mode().name(),
+                self.board_config.rows,
+                self.board_config.cols,
+                self.board_config.win_length
+            ),
+            GameMode::Ring(players) => format!("{} de {} jogadores", self.mode().name(), players),
+            mode => mode.name().to_string(),
+        };
+        let label = if self.swap_rule { format!("{}, com troca", label) } else { label };
+        let label = if self.pop_out { format!("{}, pop out", label) } else { label };
+        let label = if self.power_up { format!("{}, com coringa", label) } else { label };
+        let label = match &self.clock {
+            Some(clock) => format!("{}, relógio {}", label, clock.label()),
+            None => label,
+        };
+        let label = if self.rated { format!("{}, ranqueada", label) } else { label };
+        let label = match &self.handicap {
+            Some(handicap) => format!("{}, vantagem: {}", label, handicap.kind.label()),
+            None => label,
+        };
+        let label = match self.chat_filter {
+            Some(FilterLevel::Off) => format!("{}, chat sem filtro", label),
+            Some(FilterLevel::Normal) => format!("{}, chat com filtro", label),
+            Some(FilterLevel::Strict) => format!("{}, chat com filtro rigoroso", label),
+            None => label,
+        };
+        let label = if self.exhibition { format!("{}, exibição com {}s entre jogadas", label, self.move_delay.as_secs()) } else { label };
+        let label = match &self.crowd {
+            Some(crowd) => format!("{}, multidão contra o computador, votação de {}s", label, crowd.window.as_secs()),
+            None => label,
+        };
+        match self.series {
+            Some(series) => format!("{}, melhor de {}: {} x {}", label, series.best_of, series.wins_x, series.wins_o),
+            None => label,
+        }
+    }
+
+    pub fn current_turn(&self) -> i32 {
+        self.game.current_turn()
+    }
+
+    // Atualiza o estado do jogo com base na jogada
+    pub fn update_game_state(&mut self, player_move: Move) -> Result<(), GameError> {
+        self.ensure_not_finished()?;
+        let (symbol, row) = match &mut self.game {
+            Game::ConnectFour(game_state) => (game_state.current_turn, game_state.apply_move(&player_move)?),
+            Game::Ring(ring_state) => (ring_state.current_turn, ring_state.apply_move(&player_move)?),
+            Game::Reversi(_) => return Err(GameError::Other("Jogada inválida. No reversi informe linha e coluna.\n".to_string())),
+        };
+        self.record_move(PlayedMove { symbol, row, col: player_move.col, pop: false, wild: false });
+        Ok(())
+    }
+
+    // Power Up: joga o coringa do jogador da vez na coluna
+    pub fn play_wild(&mut self, col: usize) -> Result<(), GameError> {
+        self.ensure_not_finished()?;
+        let game_state = match &mut self.game {
+            Game::ConnectFour(game_state) if self.power_up => game_state,
+            _ => return Err(GameError::Other("Esta sala não usa a variante Power Up.\n".to_string())),
+        };
+
+        let symbol = game_state.current_turn;
+        let row = game_state.apply_wild(&Move { col })?;
+        self.record_move(PlayedMove { symbol, row, col, pop: false, wild: true });
+        Ok(())
+    }
+
+    // Pop Out: retira a peça do jogador da vez da base da coluna
+    pub fn pop_piece(&mut self, col: usize) -> Result<(), GameError> {
+        self.ensure_not_finished()?;
+        let game_state = match &mut self.game {
+            Game::ConnectFour(game_state) if self.pop_out => game_state,
+            _ => return Err(GameError::Other("Esta sala não usa a variante Pop Out.\n".to_string())),
+        };
+
+        let symbol = game_state.current_turn;
+        game_state.pop_piece(col)?;
+        let row = game_state.rows() - 1;
+        self.record_move(PlayedMove { symbol, row, col, pop: true, wild: false });
+        Ok(())
+    }
+
+    // Guarda quanto o jogador da vez pensou, para o relatório de jogo limpo das ranqueadas
+    fn record_think_time(&mut self) {
+        let elapsed = self.turn_started_at.elapsed().as_millis() as u64;
+        self.think_times.push(self.rated.then_some(elapsed));
+    }
+
+    // Registra uma jogada do lig4 já aplicada ao tabuleiro e encerra a partida se houve
+    // vitória ou se o tabuleiro encheu. Depois de um pop ou de um coringa, o resultado considera
+    // os dois jogadores, já que a jogada pode completar sequências de qualquer um.
+    fn record_move(&mut self, played_move: PlayedMove) {
+        self.charge_clock(played_move.symbol);
+        self.record_think_time();
+        self.turn_started_at = Instant::now();
+        self.last_move = Some((played_move.row, played_move.col));
+        self.moves.push(played_move);
+        METRICS.move_played();
+        info!(
+            symbol = played_move.symbol,
+            row = played_move.row,
+            col = played_move.col,
+            pop = played_move.pop,
+            wild = played_move.wild,
+            "jogada"
+        );
+        self.undo_request = None;
+        self.withdraw_draw_offer();
+
+        if let Some(winner) = self.result() {
+            self.finish(winner, false);
+        } else {
+            self.grant_extra_move();
+            self.open_crowd_vote();
+        }
+    }
+
+    // Aplica uma jogada de reversi. Retorna o aviso de passe de vez, se houver.
+    pub fn update_reversi_state(&mut self, player_move: ReversiMove) -> Result<Option<String>, GameError> {
+        self.ensure_not_finished()?;
+        let reversi_state = match &mut self.game {
+            Game::Reversi(reversi_state) => reversi_state,
+            Game::ConnectFour(_) | Game::Ring(_) => {
+                return Err(GameError::Other("Jogada inválida. No lig4 informe apenas a coluna.\n".to_string()))
+            }
+        };
+
+        let symbol = reversi_state.current_turn;
+        let passed = reversi_state.apply_move(&player_move)?;
+        let next_turn = reversi_state.current_turn;
+        self.charge_clock(symbol);
+        self.record_think_time();
+        self.turn_started_at = Instant::now();
+        self.last_move = Some((player_move.row, player_move.col));
+        self.moves.push(PlayedMove { symbol, row: player_move.row, col: player_move.col, pop: false, wild: false });
+        METRICS.move_played();
+        info!(symbol, row = player_move.row, col = player_move.col, "jogada");
+        self.undo_request = None;
+        self.withdraw_draw_offer();
+
+        if let Some(winner) = self.result() {
+            self.finish(winner, false);
+        } else if passed {
+            let symbol = if next_turn == 1 { 'O' } else { 'X' };
+            return Ok(Some(format!("O jogador {} não tem jogadas e passa a vez.\n", symbol)));
+        }
+        Ok(None)
+    }
+
+    // Resultado da partida: Some(1) ou Some(-1) para o vencedor, Some(0) para empate
+    pub fn result(&self) -> Option<i32> {
+        match &self.game {
+            Game::ConnectFour(game_state) => game_state.result(),
+            Game::Reversi(reversi_state) => reversi_state.final_result(),
+            Game::Ring(ring_state) => ring_state.result(),
+        }
+    }
+
+    // Linha que orienta o jogador depois de cada tabuleiro: de quem é a vez e o que digitar
+    pub fn turn_prompt(&self, player_symbol: i32) -> Option<String> {
+        match self.status {
+            RoomStatus::Waiting if self.seats() > 2 => {
+                return Some(format!("Aguardando jogadores ({}/{})...\n", self.seated_count(), self.seats()));
+            }
+            RoomStatus::Waiting => return Some("Aguardando segundo jogador...\n".to_string()),
+            RoomStatus::Playing => {}
+            RoomStatus::Paused | RoomStatus::OnHold | RoomStatus::Finished(_) => return None,
+        }
+        if self.current_turn() != player_symbol && self.seats() > 2 {
+            return Some(format!("Vez do {}...\n", self.glyph(self.current_turn())));
+        }
+        if self.current_turn() != player_symbol {
+            return Some("Aguardando oponente...\n".to_string());
+        }
+        let prompt = match self.mode() {
+            GameMode::ConnectFour | GameMode::Ring(_) => format!("Sua vez, escolha a coluna (1-{}):\n", self.board_config.cols),
+            GameMode::Reversi => "Sua vez, escolha a casa (linha coluna):\n".to_string(),
+        };
+        let mut prompt = prompt;
+        // Na resposta à primeira jogada, lembra que a sala permite a troca
+        if self.swap_available() {
+            prompt.push_str("Ou digite /swap para ficar com o lado do adversário e a jogada dele.\n");
+        }
+        if self.pop_out {
+            prompt.push_str("Ou digite /pop <coluna> para retirar uma peça sua da base.\n");
+        }
+        if self.power_up && matches!(&self.game, Game::ConnectFour(game_state) if game_state.has_wild(player_symbol)) {
+            prompt.push_str("Ou digite /wild <coluna> para jogar o seu coringa.\n");
+        }
+        let blocked: Vec<String> = self.blocked_columns(player_symbol).iter().map(|col| (col + 1).to_string()).collect();
+        if !blocked.is_empty() {
+            prompt.push_str(&format!("Colunas bloqueadas pela vantagem: {}.\n", blocked.join(", ")));
+        }
+        Some(prompt)
+    }
+
+    // Põe mais um jogador à mesa e começa a partida quando ela enche. No anel, quem entra
+    // fica com o primeiro símbolo livre da roda. Retorna o símbolo, ou None se a sala já está cheia.
+    fn seat_player(&mut self, mut player: Player) -> Option<i32> {
+        if self.status != RoomStatus::Waiting || self.seated_count() >= self.seats() {
+            return None;
+        }
+        let taken = |symbol: &i32| self.players.iter().any(|seated| seated.symbol == *symbol && seated.role != Role::Spectator);
+        player.symbol = match self.mode() {
+            GameMode::Ring(players) => (1..=players as i32).find(|symbol| !taken(symbol))?,
+            // Em duplas, quem entra completa o lado com menos gente, o X primeiro no empate
+            _ if self.teams => {
+                let count = |symbol: i32| self.players.iter().filter(|seated| seated.symbol == symbol && seated.role != Role::Spectator).count();
+                if count(1) <= count(-1) { 1 } else { -1 }
+            }
+            // Quem entra é o O, a não ser que o O já seja do computador, como nas exibições
+            _ => [-1, 1].into_iter().find(|symbol| !taken(symbol))?,
+        };
+        let symbol = player.symbol;
+        self.players.push(player.clone());
+        if self.seated_count() < self.seats() {
+            info!(nick = %player.nick, symbol, "jogador entrou na roda");
+            let _ = self.events.send(RoomEvent::Notice(format!(
+                "{} entrou como {}. Aguardando jogadores ({}/{}).\n",
+                player.nick,
+                self.glyph(symbol),
+                self.seated_count(),
+                self.seats()
+            )));
+            self.notify_state_changed();
+            return Some(symbol);
+        }
+        info!(nick = %player.nick, "partida iniciada");
+        self.start_game();
+        Some(symbol)
+    }
+
+    // Jogada de uma conexão; só o jogador da vez pode jogar
+    fn play(&mut self, player_symbol: i32, nick: &str, player_move: ParsedMove) -> Result<Option<String>, GameError> {
+        self.ensure_not_finished()?;
+        if self.current_turn() != player_symbol {
+            return Err(GameError::NotYourTurn);
+        }
+        self.ensure_team_mover(player_symbol, nick)?;
+        if let ParsedMove::ConnectFour(Move { col }) | ParsedMove::Wild(col) = player_move {
+            self.ensure_not_blocked(player_symbol, col)?;
+        }
+        let notice = match player_move {
+            ParsedMove::ConnectFour(player_move) => self.update_game_state(player_move).map(|_| None)?,
+            ParsedMove::PopOut(col) => self.pop_piece(col).map(|_| None)?,
+            ParsedMove::Wild(col) => self.play_wild(col).map(|_| None)?,
+            ParsedMove::Reversi(player_move) => self.update_reversi_state(player_move)?,
+        };
+        self.notify_state_changed();
+        Ok(notice)
+    }
+
+    // Prévia de uma jogada, com as mesmas checagens do play, aplicada a uma cópia do jogo
+    fn preview(&self, player_symbol: i32, nick: &str, player_move: &ParsedMove) -> Result<MovePreview, GameError> {
+        self.ensure_not_finished()?;
+        if self.current_turn() != player_symbol {
+            return Err(GameError::NotYourTurn);
+        }
+        self.ensure_team_mover(player_symbol, nick)?;
+        if let ParsedMove::ConnectFour(Move { col }) | ParsedMove::Wild(col) = player_move {
+            self.ensure_not_blocked(player_symbol, *col)?;
+        }
+        let mut game = self.game.clone();
+        let cell = match (&mut game, player_move) {
+            (Game::ConnectFour(game_state), ParsedMove::ConnectFour(player_move)) => {
+                (game_state.apply_move(player_move)?, player_move.col)
+            }
+            (Game::Ring(ring_state), ParsedMove::ConnectFour(player_move)) => (ring_state.apply_move(player_move)?, player_move.col),
+            (Game::ConnectFour(game_state), ParsedMove::Wild(col)) if self.power_up => {
+                (game_state.apply_wild(&Move { col: *col })?, *col)
+            }
+            (Game::ConnectFour(game_state), ParsedMove::PopOut(col)) if self.pop_out => {
+                game_state.pop_piece(*col)?;
+                (game_state.rows() - 1, *col)
+            }
+            (Game::Reversi(reversi_state), ParsedMove::Reversi(player_move)) => {
+                reversi_state.apply_move(player_move)?;
+                (player_move.row, player_move.col)
+            }
+            _ => return Err(GameError::Other("Jogada inválida nesta sala.\n".to_string())),
+        };
+        Ok(MovePreview { game, cell })
+    }
+
+    fn ensure_team_mover(&self, player_symbol: i32, nick: &str) -> Result<(), String> {
+        match self.team_mover(player_symbol) {
+            Some(mover) if mover != nick => Err(format!("Esta jogada do seu lado é do seu parceiro, {}.\n", mover)),
+            _ => Ok(()),
+        }
+    }
+
+    // Posição a analisar quando é a vez do computador
+    fn ai_position(&self, symbol: i32) -> Option<GameState> {
+        match &self.game {
+            Game::ConnectFour(game_state) if self.status == RoomStatus::Playing && game_state.current_turn == symbol => {
+                Some(game_state.clone())
+            }
+            _ => None,
+        }
+    }
+
+    // Jogada do computador, aplicada só se a posição analisada ainda é a do tabuleiro. Um
+    // /undo ou o fim da partida durante a busca a torna obsoleta.
+    fn play_ai_move(&mut self, position: &GameState, col: usize) {
+        let current = matches!(&self.game, Game::ConnectFour(game_state) if game_state == position);
+        if current && self.status == RoomStatus::Playing && self.update_game_state(Move { col }).is_ok() {
+            self.notify_state_changed();
+        }
+    }
+
+    fn view(&self, player_symbol: i32, nick: &str) -> RoomView {
+        let partner = self.team_mover(player_symbol).filter(|mover| *mover != nick && self.current_turn() == player_symbol);
+        let prompt = match partner {
+            Some(partner) => Some(format!("Vez do seu parceiro, {}...\n", partner)),
+            None if player_symbol == 0 && self.open_vote().is_some() => {
+                Some(format!("Vez da multidão, vote na coluna (1-{}):\n", self.board_config.cols))
+            }
+            None => self.turn_prompt(player_symbol),
+        };
+        RoomView {
+            game: self.game.clone(),
+            last_move: self.last_move,
+            status: self.status,
+            seated: self.seated_count(),
+            seats: self.seats(),
+            humans: self.human_count(),
+            prompt,
+            start_notice: self.start_notice(player_symbol),
+            clock: self.clock_now(),
+            // Na sala da multidão também só se assiste, mesmo sem ninguém sentado
+            exhibition: self.exhibition || self.crowd.is_some(),
+            blocked_columns: self.blocked_columns(player_symbol),
+            partner_turn: partner.is_some(),
+            teams: self.teams,
+            crowd: self.crowd.is_some(),
+            chat_filter: self.chat_filter,
+            tournament: self.tournament,
+            turn_timeout: self.turn_timeout,
+        }
+    }
+
+    fn summary(&self) -> RoomSummary {
+        RoomSummary {
+            status: self.status,
+            mode: self.mode(),
+            seats: self.seats(),
+            mode_label: self.mode_label(),
+            players: self.players.clone(),
+            humans: self.human_count(),
+        }
+    }
+
+    // Atende um pedido. Se quem pediu já desistiu de esperar, a resposta é descartada.
+    fn handle(&mut self, command: RoomCommand) {
+        match command {
+            RoomCommand::Join { player, reply } => {
+                let _ = reply.send(self.seat_player(player));
+            }
+            RoomCommand::Watch { player } => self.players.push(player),
+            RoomCommand::Resume { nick, reply } => {
+                let _ = reply.send(self.resume_player(&nick));
+            }
+            RoomCommand::Vote { nick, col, reply } => {
+                let _ = reply.send(self.vote(&nick, col));
+            }
+            RoomCommand::View { symbol, nick, reply } => {
+                let _ = reply.send(self.view(symbol, &nick));
+            }
+            // Na exibição, a jogada feita antes do atraso é conferida e espera a hora dela
+            RoomCommand::Play { symbol, nick, player_move, reply } if Instant::now() < self.next_move_at() => {
+                let checked = match self.held_move {
+                    Some(_) => Err(GameError::Other("Sua jogada já está guardada; ela vale quando o atraso da sala passar.\n".to_string())),
+                    None => self.preview(symbol, &nick, &player_move).map(|_| ()),
+                };
+                match checked {
+                    Ok(()) => self.held_move = Some(HeldMove { symbol, nick, player_move, reply }),
+                    Err(msg) => {
+                        let _ = reply.send(Err(msg));
+                    }
+                }
+            }
+            RoomCommand::Play { symbol, nick, player_move, reply } => {
+                let _ = reply.send(self.play(symbol, &nick, player_move));
+            }
+            RoomCommand::Preview { symbol, nick, player_move, reply } => {
+                let _ = reply.send(self.preview(symbol, &nick, &player_move));
+            }
+            RoomCommand::Resign { symbol, reply } => {
+                let _ = reply.send(self.resign(symbol));
+            }
+            RoomCommand::RequestUndo { symbol, reply } => {
+                let _ = reply.send(self.request_undo(symbol));
+            }
+            RoomCommand::RequestAbort { symbol, reply } => {
+                let _ = reply.send(self.request_abort(symbol));
+            }
+            RoomCommand::OfferDraw { symbol, reply } => {
+                let _ = reply.send(self.offer_draw(symbol));
+            }
+            RoomCommand::Answer { symbol, accepted, reply } => {
+                let _ = reply.send(self.answer(symbol, accepted));
+            }
+            RoomCommand::Swap { symbol, reply } => {
+                let _ = reply.send(self.swap_sides(symbol));
+            }
+            RoomCommand::Hint { symbol, reply } => {
+                let _ = reply.send(self.take_hint(symbol));
+            }
+            RoomCommand::Eval { symbol, reply } => {
+                let _ = reply.send(self.evaluation(symbol));
+            }
+            RoomCommand::Pause { symbol, room_name, reply } => {
+                let _ = reply.send(self.request_pause(symbol, &room_name));
+            }
+            RoomCommand::ResumeGame { symbol, room_name, reply } => {
+                let _ = reply.send(self.resume_game(symbol, &room_name));
+            }
+            RoomCommand::Rematch { symbol, reply } => {
+                let _ = reply.send(self.accept_rematch(symbol));
+            }
+            RoomCommand::Leave { nick } => self.remove_player(&nick),
+            RoomCommand::Activity { nick } => self.record_activity(&nick),
+            RoomCommand::Disconnect { nick, reply } => {
+                let _ = reply.send(self.disconnect_player(&nick));
+            }
+            RoomCommand::Suspend { room_name, reply } => {
+                let _ = reply.send(self.suspend(&room_name));
+            }
+            RoomCommand::Snapshot { room_name, reply } => {
+                let _ = reply.send(self.snapshot(&room_name));
+            }
+            RoomCommand::Summary { reply } => {
+                let _ = reply.send(self.summary());
+            }
+            // Tratado pela própria tarefa, que termina
+            RoomCommand::Close => {}
+        }
+    }
+}
+
+impl RoomHandle {
+    // Entrega a sala a uma tarefa própria. O computador joga com cada símbolo de `ais`, no
+    // nível dado; nas exibições, com os dois.
+    pub fn spawn(room: GameRoom, ais: Vec<(i32, Difficulty)>, span: Span) -> Self {
+        let (commands, receiver) = mpsc::channel(COMMAND_QUEUE);
+        let handle = RoomHandle {
+            commands,
+            events: room.events.clone(),
+            mode: room.mode(),
+        };
+        tokio::spawn(run_room(room, receiver, ais).instrument(span));
+        handle
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    // Pedidos enviados que a tarefa da sala ainda não atendeu
+    pub fn queue_depth(&self) -> usize {
+        self.commands.max_capacity() - self.commands.capacity()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RoomEvent> {
+        self.events.subscribe()
+    }
+
+    // Repassa um evento a todos na sala sem passar pela tarefa, como as mensagens de chat
+    pub fn broadcast(&self, event: RoomEvent) {
+        // Sem receptores inscritos o envio falha, o que não é um problema
+        let _ = self.events.send(event);
+    }
+
+    // Envia o pedido e espera a resposta. None se a tarefa da sala já terminou.
+    async fn request<T>(&self, command: impl FnOnce(oneshot::Sender<T>) -> RoomCommand) -> Option<T> {
+        let (reply, receiver) = oneshot::channel();
+        self.commands.send(command(reply)).await.ok()?;
+        receiver.await.ok()
+    }
+
+    // Senta o jogador à mesa; retorna o símbolo que ele recebeu, ou None se a sala está cheia
+    pub async fn join(&self, player: Player) -> Option<i32> {
+        self.request(|reply| RoomCommand::Join { player, reply }).await.flatten()
+    }
+
+    pub async fn watch(&self, player: Player) {
+        let _ = self.commands.send(RoomCommand::Watch { player }).await;
+    }
+
+    pub async fn resume(&self, nick: &str) -> Result<Player, String> {
+        let nick = nick.to_string();
+        self.request(|reply| RoomCommand::Resume { nick, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn view(&self, symbol: i32) -> Option<RoomView> {
+        self.view_for(symbol, "").await
+    }
+
+    // Retrato para um jogador em particular; em duplas, o apelido separa os parceiros
+    pub async fn view_for(&self, symbol: i32, nick: &str) -> Option<RoomView> {
+        let nick = nick.to_string();
+        self.request(|reply| RoomCommand::View { symbol, nick, reply }).await
+    }
+
+    pub async fn play(&self, symbol: i32, nick: &str, player_move: ParsedMove) -> Result<Option<String>, GameError> {
+        let nick = nick.to_string();
+        self.request(|reply| RoomCommand::Play { symbol, nick, player_move, reply })
+            .await
+            .unwrap_or_else(|| room_closed().map_err(GameError::from))
+    }
+
+    pub async fn preview(&self, symbol: i32, nick: &str, player_move: ParsedMove) -> Result<MovePreview, GameError> {
+        let nick = nick.to_string();
+        self.request(|reply| RoomCommand::Preview { symbol, nick, player_move, reply })
+            .await
+            .unwrap_or_else(|| room_closed().map_err(GameError::from))
+    }
+
+    pub async fn vote(&self, nick: &str, col: usize) -> Result<(), String> {
+        let nick = nick.to_string();
+        self.request(|reply| RoomCommand::Vote { nick, col, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn resign(&self, symbol: i32) -> Result<(), String> {
+        self.request(|reply| RoomCommand::Resign { symbol, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn request_undo(&self, symbol: i32) -> Result<(), String> {
+        self.request(|reply| RoomCommand::RequestUndo { symbol, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn request_abort(&self, symbol: i32) -> Result<(), String> {
+        self.request(|reply| RoomCommand::RequestAbort { symbol, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn offer_draw(&self, symbol: i32) -> Result<(), String> {
+        self.request(|reply| RoomCommand::OfferDraw { symbol, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    // Resposta ao pedido pendente do oponente: anular a partida, empatar ou desfazer a jogada
+    pub async fn answer(&self, symbol: i32, accepted: bool) -> Result<(), String> {
+        self.request(|reply| RoomCommand::Answer { symbol, accepted, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn swap_sides(&self, symbol: i32) -> Result<(), String> {
+        self.request(|reply| RoomCommand::Swap { symbol, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn hint(&self, symbol: i32) -> Result<Hint, String> {
+        self.request(|reply| RoomCommand::Hint { symbol, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn evaluation(&self, symbol: i32) -> Result<Evaluation, String> {
+        self.request(|reply| RoomCommand::Eval { symbol, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn pause(&self, symbol: i32, room_name: &str) -> Result<(), String> {
+        let room_name = room_name.to_string();
+        self.request(|reply| RoomCommand::Pause { symbol, room_name, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    pub async fn resume_game(&self, symbol: i32, room_name: &str) -> Result<(), String> {
+        let room_name = room_name.to_string();
+        self.request(|reply| RoomCommand::ResumeGame { symbol, room_name, reply })
+            .await
+            .unwrap_or_else(room_closed)
+    }
+
+    // Retorna true se a nova partida começou
+    pub async fn accept_rematch(&self, symbol: i32) -> bool {
+        self.request(|reply| RoomCommand::Rematch { symbol, reply }).await.unwrap_or(false)
+    }
+
+    pub async fn leave(&self, nick: &str) {
+        let _ = self.commands.send(RoomCommand::Leave { nick: nick.to_string() }).await;
+    }
+
+    // Avisa a sala de que o jogador enviou alguma coisa, para a contagem de ausência
+    pub async fn record_activity(&self, nick: &str) {
+        let _ = self.commands.send(RoomCommand::Activity { nick: nick.to_string() }).await;
+    }
+
+    // Retorna true se a partida pausou esperando o jogador voltar
+    pub async fn disconnect(&self, nick: &str) -> bool {
+        let nick = nick.to_string();
+        self.request(|reply| RoomCommand::Disconnect { nick, reply }).await.unwrap_or(false)
+    }
+
+    pub async fn suspend(&self, room_name: &str) -> Option<SavedGame> {
+        let room_name = room_name.to_string();
+        self.request(|reply| RoomCommand::Suspend { room_name, reply }).await.flatten()
+    }
+
+    pub async fn snapshot(&self, room_name: &str) -> Option<SavedGame> {
+        let room_name = room_name.to_string();
+        self.request(|reply| RoomCommand::Snapshot { room_name, reply }).await.flatten()
+    }
+
+    pub async fn summary(&self) -> Option<RoomSummary> {
+        self.request(|reply| RoomCommand::Summary { reply }).await
+    }
+
+    pub async fn close(&self) {
+        let _ = self.commands.send(RoomCommand::Close).await;
+    }
+}
+
+// Quem começa a primeira partida para uma semente. Os bits da semente são misturados
+// (splitmix64) para que sementes vizinhas não deem sempre o mesmo lado.
+fn first_turn_for(seed: u32, mode: GameMode) -> i32 {
+    let mut mixed = u64::from(seed).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    mixed ^= mixed >> 31;
+    match mode {
+        GameMode::Ring(players) => (mixed % players as u64) as i32 + 1,
+        _ if mixed % 2 == 0 => 1,
+        _ => -1,
+    }
+}
+
+fn room_closed<T>() -> Result<T, String> {
+    Err("A sala foi encerrada.\n".to_string())
+}
+
+// Busca do computador rodando fora das tarefas assíncronas, com a posição analisada
+type AiSearch = (GameState, JoinHandle<Option<usize>>);
+
+// Tarefa dona da sala: atende os pedidos um de cada vez, encerra a partida se o jogador da
+// vez passar do prazo ou se quem caiu não voltar a tempo, e faz as jogadas do computador.
+// Termina quando não sobra nenhum RoomHandle, isto é, quando o lobby e todas as conexões
+// largaram a sala.
+async fn run_room(mut room: GameRoom, mut commands: mpsc::Receiver<RoomCommand>, ais: Vec<(i32, Difficulty)>) {
+    let mut search: Option<AiSearch> = None;
+    let mut ready: Option<(GameState, usize)> = None; // Jogada do computador esperando o atraso da exibição
+
+    loop {
+        if search.is_none() && ready.is_none() {
+            // A busca é pesada no nível difícil, então roda em uma thread própria
+            search = ais.iter().find_map(|(symbol, difficulty)| {
+                let difficulty = *difficulty;
+                room.ai_position(*symbol).map(|position| {
+                    let game_state = position.clone();
+                    (position, tokio::task::spawn_blocking(move || ai::best_move(&game_state, difficulty)))
+                })
+            });
+        }
+        let deadline = room.turn_deadline();
+        let afk_deadline = room.afk_deadline();
+        let held_deadline = room.held_move_deadline();
+        let ready_deadline = ready.as_ref().map(|_| room.next_move_at());
+        let countdown = room.countdown_deadline();
+
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(RoomCommand::Close) => {
+                    info!("sala encerrada");
+                    // Acorda as conexões, que ao pedir o estado descobrem que a sala acabou
+                    let _ = room.events.send(RoomEvent::Closed);
+                    break;
+                }
+                Some(command) => {
+                    room.handle(command);
+                    // Partida anulada: a sala fecha sem gravar nada, como na ausência de uma amistosa
+                    if room.aborted {
+                        break;
+                    }
+                }
+                None => break,
+            },
+            _ = sleep_until(deadline) => room.expire_deadline(),
+            _ = sleep_until(afk_deadline) => {
+                if room.expire_afk() {
+                    // Partida amistosa cancelada: a sala fecha sem gravar nada
+                    let _ = room.events.send(RoomEvent::Closed);
+                    break;
+                }
+            }
+            col = search_result(&mut search) => {
+                if let (Some((position, _)), Some(col)) = (search.take(), col) {
+                    if Instant::now() < room.next_move_at() {
+                        ready = Some((position, col));
+                    } else {
+                        room.play_ai_move(&position, col);
+                    }
+                }
+            }
+            _ = sleep_until(ready_deadline) => {
+                if let Some((position, col)) = ready.take() {
+                    room.play_ai_move(&position, col);
+                }
+            }
+            _ = sleep_until(held_deadline) => room.release_held_move(),
+            _ = sleep_until(countdown) => room.announce_countdown(),
+        }
+    }
+}
+
+// Espera o prazo do relógio; sem prazo, nunca termina
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => future::pending().await,
+    }
+}
+
+// Espera a coluna escolhida pelo computador; sem busca em andamento, nunca termina
+async fn search_result(search: &mut Option<AiSearch>) -> Option<usize> {
+    match search {
+        Some((_, task)) => task.await.ok().flatten(),
+        None => future::pending().await,
+    }
+}
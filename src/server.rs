@@ -1,243 +1,2540 @@
-use tokio::{net::TcpListener, io::{self, AsyncWriteExt, AsyncBufReadExt}};
-use serde::{Serialize, Deserialize};
+use tokio::{net::{TcpListener, TcpStream}, io};
+use tokio::io::AsyncWriteExt;
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, oneshot, watch, Mutex};
+use tokio::task::JoinSet;
+use tokio::time;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+use tracing_subscriber::EnvFilter;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct GameState {
-    board: [[i32; 7]; 6],  // Tabuleiro 6x7
-    current_turn: i32, // 1 para jogador 1 (X), -1 para jogador 2 (O)
+use reversi_egui::ai::{self, Difficulty};
+use reversi_egui::game::{has_line, BoardConfig, GameState, Move};
+use reversi_egui::protocol::{Hello, CAPABILITIES, PROTOCOL_VERSION};
+use reversi_egui::puzzle::{self, PUZZLES};
+use reversi_egui::reversi::ReversiMove;
+use reversi_egui::ring;
+use reversi_egui::solver::{self, Solver};
+
+mod admin;
+mod api;
+mod command;
+mod config;
+mod connection;
+mod export;
+mod fairplay;
+mod filter;
+mod health;
+mod http;
+mod i18n;
+mod input;
+mod limits;
+mod lobby;
+mod metrics;
+mod moderation;
+mod room;
+mod storage;
+mod telnet;
+mod tls;
+mod tournament;
+mod transport;
+
+use command::{Context, Input};
+use config::{Config, Reloader};
+use connection::{Connection, Interrupt, Output};
+use filter::FilterLevel;
+use i18n::Lang;
+use input::{InputError, LineReader};
+use limits::{ConnectionLimits, ConnectionSlot};
+use lobby::{Activity, ExhibitionSeat, FriendStatus, Lobby, Match, Queued, Resume};
+use storage::{DailyEntry, GameRecord, Storage};
+use room::{Evaluation, Game, GameClock, GameMode, HandicapKind, ParsedMove, Player, Role, RoomEvent, RoomHandle, RoomOptions, RoomStatus};
+use transport::{ClientStream, Protocol, Terminal};
+
+type ClientLines = LineReader;
+type ClientWriter = io::WriteHalf<ClientStream>;
+
+const SHUTDOWN_MESSAGE: &str = "Servidor encerrando. Partidas em andamento foram salvas. Até logo!\n";
+
+const IDLE_MESSAGE: &str = "Conexão encerrada por inatividade.\n";
+
+const KICK_MESSAGE: &str = "Conexão encerrada por um administrador.\n";
+
+const REPLACED_MESSAGE: &str = "Sua sessão continuou em outra conexão. Esta será encerrada.\n";
+
+// Quanto tempo o modo TV mostra o resultado antes de passar para a próxima partida
+const TV_RESULT_DELAY: Duration = Duration::from_secs(5);
+
+// Quem assiste e perde eventos da sala de novo antes deste prazo não acompanha o ritmo dela
+// e é desconectado; a memória de cada conexão fica limitada à fila dos eventos
+const SPECTATOR_LAG_GRACE: Duration = Duration::from_secs(10);
+
+// Limites do relógio de xadrez pedido no /create
+const MAX_CLOCK_MINUTES: u64 = 180;
+const MAX_CLOCK_INCREMENT_SECS: u64 = 60;
+
+// Atraso entre as jogadas das exibições, quando o /exhibition não diz, e o maior aceito
+const DEFAULT_EXHIBITION_DELAY_SECS: u64 = 2;
+const MAX_EXHIBITION_DELAY_SECS: u64 = 30;
+
+// Duração de cada votação da sala da multidão, quando o /crowd não diz, e os limites aceitos
+const DEFAULT_CROWD_VOTE_SECS: u64 = 20;
+const MIN_CROWD_VOTE_SECS: u64 = 5;
+const MAX_CROWD_VOTE_SECS: u64 = 120;
+
+// Embaralha o número do dia na semente do desafio diário, para dias vizinhos darem
+// posições bem diferentes
+const DAILY_SEED_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+// Prazo do solucionador no /eval; as posições do começo da partida costumam não caber nele
+const EVAL_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+// Clientes com versão menor que esta são recusados no HELLO
+const MIN_PROTOCOL_VERSION: u32 = 1;
+
+// Tempo que o servidor espera as conexões terminarem antes de sair
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Tempo para o handshake e o aviso de uma conexão recusada pelos limites
+const REJECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Pausa depois de um erro do accept, para não girar em falso enquanto faltam descritores
+const ACCEPT_ERROR_PAUSE: Duration = Duration::from_millis(100);
+
+// Tempo que o RESUME espera a conexão antiga liberar a vaga
+const HANDOVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Comandos que colocam o jogador em uma sala e por isso exigem um apelido
+const NICK_REQUIRED: [&str; 16] = ["/say", "/tournament", "/create", "/join", "/solo", "/exhibition", "/crowd", "/watch", "/queue", "/challenge", "/accept", "/decline", "/friend", "/friends", "/puzzle", "/daily"];
+
+async fn handle_connection(
+    stream: TcpStream,
+    protocol: Protocol,
+    lobby: Arc<Mutex<Lobby>>,
+    configs: watch::Receiver<Arc<Config>>,
+    shutdown: watch::Receiver<bool>,
+    _slot: ConnectionSlot, // Vaga nos limites de conexões, devolvida quando a conexão termina
+) {
+    let _counted = metrics::METRICS.connection_opened();
+    // O keepalive e os limites de leitura ficam como estavam quando a conexão abriu
+    let config = Arc::clone(&configs.borrow());
+
+    // Clientes que caíram sem fechar a conexão são percebidos pelo keepalive e tratados
+    // como uma desconexão comum
+    if let Some(interval) = config.keepalive() {
+        if let Err(err) = transport::set_keepalive(&stream, interval) {
+            warn!(%err, "não foi possível ligar o keepalive");
+        }
+    }
+
+    // O handshake roda na tarefa da conexão para não travar o laço de aceitação
+    let client = match protocol.accept(stream, config.keepalive()).await {
+        Ok(client) => client,
+        Err(err) => {
+            warn!(%err, "handshake falhou");
+            return;
+        }
+    };
+
+    // A leitura fica com a tarefa da conexão; a escrita vai para uma tarefa própria, para que
+    // um cliente que não lê só atrase a si mesmo
+    let (reader, writer) = io::split(client.stream);
+    let lines = LineReader::new(reader, config.input_limits());
+    let (output, writing) = connection::spawn_writer(writer);
+
+    let mut nick = None;
+    serve_lobby(lines, output, client.terminal, &lobby, &configs, shutdown, &mut nick).await;
+
+    // Quando a conexão termina, o apelido fica livre para outra pessoa
+    if let Some(nick) = nick {
+        lobby.lock().await.release_nick(&nick);
+    }
+    // O que ainda está na fila, como o aviso de encerramento do servidor, chega ao cliente
+    // antes de a conexão fechar
+    let _ = writing.await;
+    info!("conexão encerrada");
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Move {
-    col: usize, // Coluna onde a peça será jogada
+// Conexão acima do limite: faz o handshake, para que o aviso chegue no formato do
+// transporte, explica o motivo e fecha, sem passar pelo lobby
+async fn reject_connection(stream: TcpStream, protocol: Protocol, msg: String) {
+    let rejected = time::timeout(REJECT_TIMEOUT, async {
+        let mut client = protocol.accept(stream, None).await.ok()?;
+        client.stream.write_all(msg.as_bytes()).await.ok()?;
+        client.stream.shutdown().await.ok()
+    })
+    .await;
+    if !matches!(rejected, Ok(Some(()))) {
+        debug!("conexão recusada caiu antes do aviso");
+    }
 }
 
-#[derive(Debug, Clone)]
-struct Player {
-    symbol: i32, // 1 para "X", -1 para "O"
-    address: String,
+// Atende a conexão no lobby até o jogador criar ou entrar em uma sala. Se a partida for
+// anulada com /abort, a conexão volta para o lobby e o atendimento continua.
+async fn serve_lobby(
+    mut lines: ClientLines,
+    output: Output,
+    terminal: Terminal,
+    lobby: &Arc<Mutex<Lobby>>,
+    configs: &watch::Receiver<Arc<Config>>,
+    shutdown: watch::Receiver<bool>,
+    nick: &mut Option<String>,
+) {
+    let config = Arc::clone(&configs.borrow());
+    let (announcements, moderation, motd) = {
+        let lobby = lobby.lock().await;
+        (lobby.subscribe_announcements(), lobby.moderation(), lobby.motd())
+    };
+    let mut conn = Connection::new(output, config.color, terminal, config.idle_timeout(), shutdown, announcements, moderation);
+
+    if let Some(motd) = motd {
+        conn.send_info(&motd).await;
+    }
+    conn.send_info(&command::help(Context::Lobby)).await;
+
+    loop {
+        let line = match read_line(&mut lines, &mut conn).await {
+            Some(line) => line,
+            None => return,
+        };
+        // A configuração pode ter sido relida desde o último comando
+        let current = Arc::clone(&configs.borrow());
+        let config: &Config = &current;
+
+        // Jogadas só valem dentro de uma sala
+        if command::is_move(&line) {
+            conn.send_error("Jogadas só valem dentro de uma sala. Entre em uma com /join <nome>, /queue ou /solo.\n").await;
+            continue;
+        }
+        let words = match command::tokenize(&line) {
+            Ok(words) => words,
+            Err(msg) => {
+                conn.send_error(&msg).await;
+                continue;
+            }
+        };
+        let mut parts: Vec<&str> = words.iter().map(String::as_str).collect();
+        // O nome vale abreviado (/hist) e, nos comandos sem barra, em minúsculas (nick)
+        if let Some(first) = parts.first_mut() {
+            match command::resolve(Context::Lobby, first) {
+                Ok(Some(name)) => *first = name,
+                Ok(None) => {}
+                Err(msg) => {
+                    conn.send_error(&msg).await;
+                    continue;
+                }
+            }
+        }
+        // O modo TV só acompanha as salas, sem entrar nelas, e dispensa o apelido
+        let needs_nick = parts.first().is_some_and(|command| NICK_REQUIRED.contains(command)) && parts != ["/watch", "random"];
+        if nick.is_none() && needs_nick {
+            conn.send_error("Escolha um apelido primeiro com NICK <apelido> [senha].\n").await;
+            continue;
+        }
+        let player_nick = nick.clone().unwrap_or_default();
+
+        // /create private [nome] [...] cria uma sala privada, com código de entrada
+        let private = parts.len() > 1 && parts[0] == "/create" && matches!(parts[1].to_lowercase().as_str(), "private" | "privada");
+        if private {
+            parts.remove(1);
+        }
+
+        // Os últimos argumentos do /create podem pedir uma série melhor de N (bo3 ou bo5),
+        // a regra da troca (swap), as variantes Pop Out (popout) e Power Up (powerup), um
+        // relógio de xadrez (5+3), a partida ranqueada (rated), uma vantagem de quem cria a
+        // sala (block1, block2 ou extra), as duplas (2v2) e o rigor do filtro do chat
+        // (nofilter, filter ou strict), em qualquer ordem. Sem rated, a sala é amistosa.
+        let mut options = RoomOptions::default();
+        while parts.len() > 2 && parts[0] == "/create" {
+            let last = parts[parts.len() - 1];
+            if let Some(best_of) = parse_best_of(last) {
+                options.best_of = Some(best_of);
+            } else if matches!(last.to_lowercase().as_str(), "swap" | "troca") {
+                options.swap = true;
+            } else if matches!(last.to_lowercase().as_str(), "popout" | "pop") {
+                options.pop_out = true;
+            } else if matches!(last.to_lowercase().as_str(), "powerup" | "coringa") {
+                options.power_up = true;
+            } else if let Some(clock) = parse_clock(last) {
+                options.clock = Some(clock);
+            } else if matches!(last.to_lowercase().as_str(), "rated" | "ranqueada") {
+                options.rated = true;
+            } else if matches!(last.to_lowercase().as_str(), "casual" | "amistosa") {
+                options.rated = false;
+            } else if let Some(handicap) = HandicapKind::parse(last) {
+                options.handicap = Some(handicap);
+            } else if matches!(last.to_lowercase().as_str(), "2v2" | "duplas") {
+                options.teams = true;
+            } else if let Some(filter) = FilterLevel::parse_option(last) {
+                options.chat_filter = Some(filter);
+            } else {
+                break;
+            }
+            parts.pop();
+        }
+
+        match parts.as_slice() {
+            ["NICK", name] | ["NICK", name, _] => {
+                let mut password = parts.get(2).map(|password| password.to_string());
+                // Em modo texto, a senha de um apelido protegido é perguntada à parte, sem
+                // aparecer na tela nem no histórico do terminal
+                if password.is_none() && !conn.json && lobby.lock().await.needs_password(name, nick.as_deref()) {
+                    match prompt_secret(&mut lines, &mut conn, &format!("Senha de {}: ", name)).await {
+                        Some(secret) => password = Some(secret),
+                        None => return,
+                    }
+                }
+                let result = lobby.lock().await.login(name, password.as_deref(), nick.as_deref(), conn.presence());
+                match result {
+                    Ok(()) => {
+                        Span::current().record("nick", name);
+                        *nick = Some(name.to_string());
+                        conn.send_info(&format!("Olá, {}!\n", name)).await;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["RESUME", token] => {
+                let mut result = lobby.lock().await.resume(token, nick.as_deref(), conn.presence()).await;
+                // A vaga ainda está com outra conexão; espera ela sair e tenta de novo
+                if let Ok(Resume::HandingOver(released)) = result {
+                    let _ = time::timeout(HANDOVER_TIMEOUT, released).await;
+                    result = lobby.lock().await.resume(token, nick.as_deref(), conn.presence()).await;
+                }
+                match result {
+                    Ok(Resume::Seated(name, game_room, player)) => {
+                        Span::current().record("nick", player.nick.as_str());
+                        *nick = Some(player.nick.clone());
+                        let msg = format!("Bem-vindo de volta, {}! A partida na sala '{}' continua.\n", player.nick, name);
+                        conn.send_info(&msg).await;
+                        match play_in_room(lines, conn, lobby, config, game_room, player, &name, None).await {
+                            Some(back) => (lines, conn) = back,
+                            None => return,
+                        }
+                    }
+                    Ok(Resume::HandingOver(_)) => {
+                        conn.send_error("A outra conexão com este apelido ainda não saiu. Tente o RESUME de novo.\n").await;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["ADMIN"] | ["ADMIN", _] => match config.admin_password() {
+                Some(expected) => {
+                    // Sem a senha na linha, ela é perguntada à parte, sem eco no telnet
+                    let password = match parts.get(1) {
+                        Some(password) => password.to_string(),
+                        None => match prompt_secret(&mut lines, &mut conn, "Senha de administração: ").await {
+                            Some(secret) => secret,
+                            None => return,
+                        },
+                    };
+                    // Uma senha errada encerra a conexão, para atrasar quem tenta adivinhar
+                    if !storage::password_matches(expected, &password) {
+                        warn!("senha de administração incorreta");
+                        conn.send_error("Senha de administração incorreta.\n").await;
+                        return;
+                    }
+                    info!("console de administração aberto");
+                    admin::serve_admin(&mut lines, &mut conn, lobby).await;
+                    return;
+                }
+                None => conn.send_error("A administração remota está desativada neste servidor.\n").await,
+            },
+            ["HELLO", ..] => match Hello::parse(&line) {
+                Ok(hello) if hello.version < MIN_PROTOCOL_VERSION => {
+                    warn!(version = hello.version, "versão de protocolo incompatível");
+                    let msg = format!("Versão de protocolo v{} não suportada. Este servidor fala v{} a v{}.\n", hello.version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION);
+                    conn.send_error(&msg).await;
+                    return;
+                }
+                Ok(hello) => negotiate(&mut conn, &hello).await,
+                Err(msg) => conn.send_error(&msg).await,
+            },
+            ["MODE", format] => match format.to_lowercase().as_str() {
+                "json" => {
+                    conn.json = true;
+                    conn.send_info("Modo JSON ativado.\n").await;
+                }
+                "text" => {
+                    conn.json = false;
+                    conn.send_info("Modo texto ativado.\n").await;
+                }
+                _ => conn.send_error("Formato desconhecido. Use MODE json ou MODE text.\n").await,
+            },
+            ["COLOR", setting] => match setting.to_lowercase().as_str() {
+                "on" => {
+                    conn.color = true;
+                    conn.send_info("Cores ativadas.\n").await;
+                }
+                "off" => {
+                    conn.color = false;
+                    conn.send_info("Cores desativadas.\n").await;
+                }
+                _ => conn.send_error("Use COLOR on ou COLOR off.\n").await,
+            },
+            ["/bell", setting] => set_bell(&mut conn, setting).await,
+            ["LANG", code] => match Lang::parse(code) {
+                Some(lang) => {
+                    conn.lang = lang;
+                    let msg = match lang {
+                        Lang::Pt => "Idioma alterado para português.\n",
+                        Lang::En => "Idioma alterado para inglês.\n",
+                    };
+                    conn.send_info(msg).await;
+                }
+                None => conn.send_error("Idioma desconhecido. Use LANG pt ou LANG en.\n").await,
+            },
+            ["/list"] => {
+                let rooms = lobby.lock().await.room_infos(false).await;
+                conn.send_rooms(rooms).await;
+            }
+            ["/create"] | ["/create", _] | ["/create", _, _] | ["/create", _, _, _] | ["/create", _, _, _, _] => {
+                // Com quatro argumentos, a sala é de lig4 com tabuleiro personalizado;
+                // com três, é do modo anel com o número de jogadores
+                let board_config = match parts.as_slice() {
+                    [_, _, rows, cols, win_length] => match parse_board_config(rows, cols, win_length) {
+                        Ok(board_config) => Some(board_config),
+                        Err(msg) => {
+                            conn.send_error(&msg).await;
+                            continue;
+                        }
+                    },
+                    _ => None,
+                };
+                let mode = match parts.get(2).filter(|_| parts.len() <= 4) {
+                    Some(mode) => match GameMode::parse(mode) {
+                        Some(mode) => mode,
+                        None => {
+                            let msg = format!("Modo '{}' desconhecido. Use lig4, reversi ou anel.\n", mode);
+                            conn.send_error(&msg).await;
+                            continue;
+                        }
+                    },
+                    None => GameMode::ConnectFour,
+                };
+                let mode = match (mode, parts.get(3)) {
+                    (GameMode::Ring(_), Some(players)) => match players.parse::<usize>() {
+                        Ok(players) if (ring::MIN_PLAYERS..=ring::MAX_PLAYERS).contains(&players) => GameMode::Ring(players),
+                        _ => {
+                            let msg = format!("O modo anel aceita de {} a {} jogadores.\n", ring::MIN_PLAYERS, ring::MAX_PLAYERS);
+                            conn.send_error(&msg).await;
+                            continue;
+                        }
+                    },
+                    (_, Some(_)) => {
+                        conn.send_error("Só o modo anel aceita o número de jogadores.\n").await;
+                        continue;
+                    }
+                    (mode, None) => mode,
+                };
+
+                let result = {
+                    let mut lobby = lobby.lock().await;
+                    lobby
+                        .create_room(parts.get(1).copied(), mode, board_config, options, &player_nick)
+                        .map(|(name, game_room, player)| {
+                            let code = private.then(|| lobby.make_private(&name));
+                            (name, game_room, player, code)
+                        })
+                };
+                match result {
+                    Ok((name, game_room, player, code)) => {
+                        let mut msg = match &options.clock {
+                            Some(clock) => format!(
+                                "Sala '{}' criada. Você é o X. Cada jogador tem {} min no relógio, com {}s de acréscimo por jogada.\n",
+                                name,
+                                clock.initial.as_secs() / 60,
+                                clock.increment.as_secs()
+                            ),
+                            None => format!(
+                                "Sala '{}' criada. Você é o X. Cada jogada tem limite de {}s.\n",
+                                name,
+                                config.turn_timeout_secs_for(options.rated)
+                            ),
+                        };
+                        if let Some(best_of) = options.best_of {
+                            msg.push_str(&format!("A sala joga uma série melhor de {}.\n", best_of));
+                        }
+                        if options.swap {
+                            msg.push_str("A sala usa a regra da troca: o O pode responder à primeira jogada com /swap.\n");
+                        }
+                        if options.pop_out {
+                            msg.push_str("A sala joga o Pop Out: na sua vez, /pop <coluna> retira uma peça sua da base.\n");
+                        }
+                        if options.power_up {
+                            msg.push_str("A sala joga o Power Up: cada jogador tem um coringa por partida, jogado com /wild <coluna>.\n");
+                        }
+                        if options.rated {
+                            msg.push_str("A partida é ranqueada: o resultado muda o ELO e não há /undo, /hint nem /pause.\n");
+                        }
+                        match options.handicap {
+                            Some(HandicapKind::Blocked(1)) => msg.push_str(&format!(
+                                "Você dá vantagem: a coluna do meio fica bloqueada para você nas suas primeiras {} jogadas.\n",
+                                room::HANDICAP_BLOCKED_MOVES
+                            )),
+                            Some(HandicapKind::Blocked(_)) => msg.push_str(&format!(
+                                "Você dá vantagem: as duas colunas do meio ficam bloqueadas para você nas suas primeiras {} jogadas.\n",
+                                room::HANDICAP_BLOCKED_MOVES
+                            )),
+                            Some(HandicapKind::ExtraMove) => {
+                                msg.push_str("Você dá vantagem: quem entrar faz duas jogadas seguidas na primeira vez dele.\n")
+                            }
+                            None => {}
+                        }
+                        if options.teams {
+                            msg.push_str(&format!(
+                                "A sala é de duplas: {} jogadores, dois de cada lado, e os parceiros se alternam nas jogadas.\n",
+                                room::TEAM_SEATS
+                            ));
+                        }
+                        if let Some(code) = code {
+                            msg.push_str(&format!("A sala é privada e não aparece no /list. Passe o código {} para quem vai jogar: /join code {}\n", code, code));
+                        }
+                        if let GameMode::Ring(players) = mode {
+                            msg.push_str(&format!("Modo anel, experimental: a partida começa com {} jogadores e vence quem fizer {} em linha primeiro.\n", players, ring::RING_WIN_LENGTH));
+                        }
+                        conn.send_info(&msg).await;
+                        match play_in_room(lines, conn, lobby, config, game_room, player, &name, None).await {
+                            Some(back) => (lines, conn) = back,
+                            None => return,
+                        }
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/history"] | ["/history", _] => {
+                let player = match parts.get(1).copied().or(nick.as_deref()) {
+                    Some(player) => player.to_string(),
+                    None => {
+                        conn.send_error("Use /history <jogador> ou escolha um apelido com NICK.\n").await;
+                        continue;
+                    }
+                };
+                let result = lobby.lock().await.history(&player);
+                match result {
+                    Ok(games) => conn.send_history(&player, games).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/top"] => {
+                let result = lobby.lock().await.leaderboard();
+                match result {
+                    Ok(entries) => conn.send_leaderboard(entries).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/puzzle", "stats"] | ["/puzzle", "stats", _] => {
+                let player = parts.get(2).copied().unwrap_or(&player_nick).to_string();
+                let result = lobby.lock().await.puzzle_stats(&player);
+                match result {
+                    Ok(stats) => {
+                        let msg = format!(
+                            "Quebra-cabeças de {}: {} resolvidos em {} tentativas, {} de {} diferentes.\n",
+                            player,
+                            stats.solved,
+                            stats.attempts,
+                            stats.distinct_solved,
+                            PUZZLES.len()
+                        );
+                        conn.send_info(&msg).await;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/puzzle"] | ["/puzzle", _] => {
+                let number = match parts.get(1).map(|number| number.trim_start_matches('#').parse::<usize>()) {
+                    Some(Ok(number)) if (1..=PUZZLES.len()).contains(&number) => number,
+                    Some(_) => {
+                        let msg = format!("Use /puzzle, /puzzle <número de 1 a {}> ou /puzzle stats [jogador].\n", PUZZLES.len());
+                        conn.send_error(&msg).await;
+                        continue;
+                    }
+                    None => lobby.lock().await.random_puzzle(),
+                };
+                let puzzle = &PUZZLES[number - 1];
+                let title = format!("Quebra-cabeça #{}", number);
+                let solved = match solve_puzzle(&mut lines, &mut conn, puzzle.position(), puzzle.moves, &title).await {
+                    Some(solved) => solved,
+                    None => return,
+                };
+                lobby.lock().await.record_puzzle(&player_nick, number, solved);
+                conn.send_info(&command::help(Context::Lobby)).await;
+            }
+            ["/daily"] => {
+                let day = utc_day();
+                let status = lobby.lock().await.daily_status(&player_nick, day);
+                if let Some(ms) = status.solved_in_ms {
+                    let msg = format!(
+                        "Você já resolveu o desafio de hoje, em {} tentativas e {}. Veja /daily top.\n",
+                        status.attempts,
+                        format_millis(ms)
+                    );
+                    conn.send_info(&msg).await;
+                    continue;
+                }
+                let seed = day.wrapping_mul(DAILY_SEED_MULTIPLIER);
+                let position = tokio::task::spawn_blocking(move || puzzle::generate(seed, puzzle::DAILY_MOVES)).await;
+                let Ok(position) = position else {
+                    continue;
+                };
+                let title = format!("Desafio do dia {}", export::utc_date(day * SECS_PER_DAY));
+                let started = Instant::now();
+                let solved = match solve_puzzle(&mut lines, &mut conn, position, puzzle::DAILY_MOVES, &title).await {
+                    Some(solved) => solved,
+                    None => return,
+                };
+                let elapsed = started.elapsed();
+                lobby.lock().await.record_daily(&player_nick, day, solved, elapsed);
+                if solved {
+                    let msg = format!(
+                        "Desafio do dia resolvido na tentativa {}, em {}. Veja /daily top.\n",
+                        status.attempts + 1,
+                        format_millis(elapsed.as_millis() as u64)
+                    );
+                    conn.send_info(&msg).await;
+                }
+                conn.send_info(&command::help(Context::Lobby)).await;
+            }
+            ["/daily", "top"] => {
+                let day = utc_day();
+                let result = lobby.lock().await.daily_leaderboard(day);
+                match result {
+                    Ok(entries) => conn.send_info(&format_daily_leaderboard(day, &entries)).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/export"] | ["/export", _] => {
+                let result = match parts.get(1) {
+                    Some(id) => match id.trim_start_matches('#').parse::<i64>() {
+                        Ok(id) => lobby.lock().await.find_game(id),
+                        Err(_) => Err("Use o formato: /export <número da partida>\n".to_string()),
+                    },
+                    None => match nick.as_deref() {
+                        Some(player) => lobby.lock().await.latest_game(player),
+                        None => Err("Use /export <número> ou escolha um apelido com NICK.\n".to_string()),
+                    },
+                };
+                match result {
+                    Ok(record) => conn.send_export(record.id, &export::export(&record)).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/replay", id] => {
+                let id = match id.trim_start_matches('#').parse::<i64>() {
+                    Ok(id) => id,
+                    Err(_) => {
+                        conn.send_error("Use o formato: /replay <número da partida>\n").await;
+                        continue;
+                    }
+                };
+                let result = lobby.lock().await.find_game(id);
+                match result {
+                    Ok(record) => {
+                        if !replay_game(&mut lines, &mut conn, &record).await {
+                            return;
+                        }
+                        conn.send_info(&command::help(Context::Lobby)).await;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/join", _] | ["/join", "code" | "codigo", _] => {
+                let result = match parts.as_slice() {
+                    [_, _, code] => lobby.lock().await.join_by_code(code, &player_nick).await,
+                    _ => {
+                        let name = parts[1].to_string();
+                        let result = lobby.lock().await.join_room(&name, &player_nick, conn.bot).await;
+                        result.map(|(game_room, player)| (name, game_room, player))
+                    }
+                };
+                match result {
+                    Ok((name, game_room, player)) => {
+                        // A partida começou antes de o O se inscrever nos eventos da sala, então o
+                        // aviso de início vai junto com os da entrada
+                        let view = game_room.view(player.symbol).await;
+                        let msg = match view.as_ref().and_then(|view| view.clock) {
+                            Some(clock) => format!(
+                                "Você entrou na sala '{}'. Cada jogador tem {} min no relógio, com {}s de acréscimo por jogada.\n",
+                                name,
+                                clock.initial.as_secs() / 60,
+                                clock.increment.as_secs()
+                            ),
+                            None => format!(
+                                "Você entrou na sala '{}'. Cada jogada tem limite de {}s.\n",
+                                name,
+                                view.as_ref().map_or(config.turn_timeout_secs, |view| view.turn_timeout.as_secs())
+                            ),
+                        };
+                        conn.send_info(&msg).await;
+                        let start_notice = view.filter(|view| view.status != RoomStatus::Waiting).map(|view| view.start_notice);
+                        match play_in_room(lines, conn, lobby, config, game_room, player, &name, start_notice).await {
+                            Some(back) => (lines, conn) = back,
+                            None => return,
+                        }
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/queue"] | ["/queue", _] => {
+                let mode = match parts.get(1) {
+                    Some(mode) => match GameMode::parse(mode) {
+                        Some(mode) => mode,
+                        None => {
+                            let msg = format!("Modo '{}' desconhecido. Use lig4 ou reversi.\n", mode);
+                            conn.send_error(&msg).await;
+                            continue;
+                        }
+                    },
+                    None => GameMode::ConnectFour,
+                };
+
+                let result = lobby.lock().await.enqueue(&player_nick, mode);
+                let found = match result {
+                    Ok(Queued::Matched(found)) => found,
+                    Ok(Queued::Waiting(receiver)) => {
+                        conn.send_info("Procurando oponente... Use /cancel para sair da fila.\n").await;
+                        match wait_for_match(&mut lines, &mut conn, lobby, &player_nick, receiver).await {
+                            QueueWait::Found(found) => found,
+                            QueueWait::Cancelled | QueueWait::Refused => {
+                                conn.send_info("Você saiu da fila.\n").await;
+                                continue;
+                            }
+                            QueueWait::Disconnected => return,
+                        }
+                    }
+                    Err(msg) => {
+                        conn.send_error(&msg).await;
+                        continue;
+                    }
+                };
+
+                let symbol = if found.player.symbol == 1 { 'X' } else { 'O' };
+                let msg = format!(
+                    "Oponente encontrado: {}. Sala '{}', você é o {}. Cada jogada tem limite de {}s.\n",
+                    found.opponent,
+                    found.room_name,
+                    symbol,
+                    // As partidas da fila são ranqueadas
+                    config.rated_turn_timeout_secs
+                );
+                conn.send_info(&msg).await;
+                match play_in_room(lines, conn, lobby, config, found.room, found.player, &found.room_name, None).await {
+                    Some(back) => (lines, conn) = back,
+                    None => return,
+                }
+            }
+            ["/challenge", opponent] | ["/challenge", opponent, _] => {
+                let mode = match parts.get(2) {
+                    Some(mode) => match GameMode::parse(mode) {
+                        Some(mode) => mode,
+                        None => {
+                            let msg = format!("Modo '{}' desconhecido. Use lig4 ou reversi.\n", mode);
+                            conn.send_error(&msg).await;
+                            continue;
+                        }
+                    },
+                    None => GameMode::ConnectFour,
+                };
+
+                let result = lobby.lock().await.challenge(&player_nick, opponent, mode);
+                let receiver = match result {
+                    Ok(receiver) => receiver,
+                    Err(msg) => {
+                        conn.send_error(&msg).await;
+                        continue;
+                    }
+                };
+                conn.send_info(&format!("Desafio enviado a {}. Aguardando a resposta... Use /cancel para retirar o desafio.\n", opponent)).await;
+                let found = match wait_for_match(&mut lines, &mut conn, lobby, &player_nick, receiver).await {
+                    QueueWait::Found(found) => found,
+                    QueueWait::Cancelled => {
+                        conn.send_info("Você retirou o desafio.\n").await;
+                        continue;
+                    }
+                    QueueWait::Refused => {
+                        conn.send_info(&format!("{} não aceitou o desafio.\n", opponent)).await;
+                        continue;
+                    }
+                    QueueWait::Disconnected => return,
+                };
+                let msg = format!(
+                    "{} aceitou o desafio. Sala privada '{}', você é o X. Cada jogada tem limite de {}s.\n",
+                    found.opponent, found.room_name, config.turn_timeout_secs
+                );
+                conn.send_info(&msg).await;
+                match play_in_room(lines, conn, lobby, config, found.room, found.player, &found.room_name, None).await {
+                    Some(back) => (lines, conn) = back,
+                    None => return,
+                }
+            }
+            ["/accept", challenger] => {
+                let result = lobby.lock().await.accept_challenge(&player_nick, challenger);
+                match result {
+                    Ok(found) => {
+                        let msg = format!(
+                            "Você aceitou o desafio de {}. Sala privada '{}', você é o O. Cada jogada tem limite de {}s.\n",
+                            found.opponent, found.room_name, config.turn_timeout_secs
+                        );
+                        conn.send_info(&msg).await;
+                        match play_in_room(lines, conn, lobby, config, found.room, found.player, &found.room_name, None).await {
+                            Some(back) => (lines, conn) = back,
+                            None => return,
+                        }
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/decline", challenger] => {
+                let result = lobby.lock().await.decline_challenge(&player_nick, challenger);
+                match result {
+                    Ok(()) => conn.send_info(&format!("Você recusou o desafio de {}.\n", challenger)).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/friend", "add", friend] => {
+                let result = lobby.lock().await.add_friend(&player_nick, friend);
+                match result {
+                    Ok(true) => conn.send_info(&format!("{} agora está na sua lista de amigos.\n", friend)).await,
+                    Ok(false) => conn.send_info(&format!("{} já está na sua lista de amigos.\n", friend)).await,
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/motd"] => {
+                let motd = lobby.lock().await.motd();
+                match motd {
+                    Some(motd) => conn.send_info(&motd).await,
+                    None => conn.send_info("Não há mensagem do dia.\n").await,
+                }
+            }
+            ["/tournament"] => {
+                let status = lobby.lock().await.tournament_status();
+                conn.send_info(&status).await;
+            }
+            ["/tournament", "history"] => {
+                let result = lobby.lock().await.tournament_history();
+                match result {
+                    Ok(records) if records.is_empty() => conn.send_info("Nenhum torneio foi disputado ainda.\n").await,
+                    Ok(records) => {
+                        let mut msg = String::from("Últimos torneios:\n");
+                        for record in records {
+                            let result = match record.champion {
+                                Some(champion) => format!("campeão {}", champion),
+                                None => "sem campeão".to_string(),
+                            };
+                            msg.push_str(&format!("  #{} {}, {} jogadores: {}\n", record.id, record.mode, record.players, result));
+                        }
+                        conn.send_info(&msg).await;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/tournament", "join"] => {
+                let result = lobby.lock().await.join_tournament(&player_nick);
+                let mut receiver = match result {
+                    Ok((starts_at, receiver)) => {
+                        let msg = format!(
+                            "Você está inscrito no torneio, que começa {} (UTC). Espere aqui pela sua partida; use /cancel para desistir.\n",
+                            tournament::describe(starts_at)
+                        );
+                        conn.send_info(&msg).await;
+                        receiver
+                    }
+                    Err(msg) => {
+                        conn.send_error(&msg).await;
+                        continue;
+                    }
+                };
+                // Cada vitória traz o jogador de volta para esperar a partida da rodada seguinte
+                loop {
+                    let found = match wait_for_match(&mut lines, &mut conn, lobby, &player_nick, receiver).await {
+                        QueueWait::Found(found) => found,
+                        QueueWait::Cancelled => {
+                            conn.send_info("Você saiu do torneio.\n").await;
+                            break;
+                        }
+                        QueueWait::Refused => break,
+                        QueueWait::Disconnected => return,
+                    };
+                    let symbol = if found.player.symbol == 1 { 'X' } else { 'O' };
+                    let msg = format!("Partida do torneio contra {}. Sala '{}', você é o {}.\n", found.opponent, found.room_name, symbol);
+                    conn.send_info(&msg).await;
+                    match play_in_room(lines, conn, lobby, config, found.room, found.player, &found.room_name, None).await {
+                        Some(back) => (lines, conn) = back,
+                        None => return,
+                    }
+                    receiver = match lobby.lock().await.tournament_next(&player_nick) {
+                        Some(receiver) => receiver,
+                        None => break,
+                    };
+                }
+            }
+            ["/who"] => {
+                let players = lobby.lock().await.who();
+                if players.is_empty() {
+                    conn.send_info("Ninguém está conectado com apelido.\n").await;
+                    continue;
+                }
+                let mut msg = String::from("Conectados:\n");
+                for (player, activity) in players {
+                    let activity = match activity {
+                        Activity::Idle => "no lobby".to_string(),
+                        Activity::Queued => "na fila".to_string(),
+                        Activity::Room(room) => format!("na sala '{}'", room),
+                        Activity::PrivateRoom => "em uma sala privada".to_string(),
+                        Activity::Tournament => "no torneio".to_string(),
+                    };
+                    msg.push_str(&format!("  {} ({})\n", player, activity));
+                }
+                conn.send_info(&msg).await;
+            }
+            ["/say", ..] => {
+                // O texto vai como foi digitado, sem passar pela separação das palavras
+                let text = line.trim().split_once(char::is_whitespace).map_or("", |(_, text)| text);
+                if text.trim().is_empty() {
+                    conn.send_error("Uso: /say <mensagem>\n").await;
+                    continue;
+                }
+                if let Some(notice) = conn.moderation().mute_notice(&player_nick) {
+                    conn.send_error(&notice).await;
+                    continue;
+                }
+                let text = conn.moderation().censor(text.trim(), None).into_owned();
+                lobby.lock().await.lobby_chat(&player_nick, &text, &utc_time());
+            }
+            ["/friends"] => {
+                let result = lobby.lock().await.friends(&player_nick).await;
+                match result {
+                    Ok(friends) if friends.is_empty() => {
+                        conn.send_info("Sua lista de amigos está vazia. Use /friend add <apelido>.\n").await;
+                    }
+                    Ok(friends) => {
+                        let mut msg = String::from("Amigos:\n");
+                        for (friend, status) in friends {
+                            let status = match status {
+                                FriendStatus::Offline => "desconectado".to_string(),
+                                FriendStatus::Lobby => "no lobby".to_string(),
+                                FriendStatus::Room(room) => format!("na sala '{}'", room),
+                                FriendStatus::PrivateRoom => "em uma sala privada".to_string(),
+                            };
+                            msg.push_str(&format!("  {} ({})\n", friend, status));
+                        }
+                        conn.send_info(&msg).await;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/solo", level] => {
+                let difficulty = match Difficulty::parse(level) {
+                    Some(difficulty) => difficulty,
+                    None => {
+                        conn.send_error("Nível desconhecido. Use /solo easy, medium, hard ou impossible.\n").await;
+                        continue;
+                    }
+                };
+
+                let result = lobby.lock().await.create_solo_room(difficulty, &player_nick);
+                let (name, game_room, player) = match result {
+                    Ok(seat) => seat,
+                    Err(msg) => {
+                        conn.send_error(&msg).await;
+                        continue;
+                    }
+                };
+                let msg = format!("Sala '{}' criada contra o computador ({}). Você é o X. Cada jogada tem limite de {}s.\n", name, difficulty.name(), config.turn_timeout_secs);
+                conn.send_info(&msg).await;
+                match play_in_room(lines, conn, lobby, config, game_room, player, &name, None).await {
+                    Some(back) => (lines, conn) = back,
+                    None => return,
+                }
+            }
+            ["/tv"] | ["/watch", "random"] => {
+                if !watch_tv(&mut lines, &mut conn, lobby).await {
+                    return;
+                }
+                conn.send_info(&command::help(Context::Lobby)).await;
+            }
+            ["/watch", name] => {
+                let result = lobby.lock().await.watch_room(name, &player_nick).await;
+                match result {
+                    Ok((game_room, spectator)) => {
+                        let msg = format!("Você está assistindo a sala '{}'.\n", name);
+                        conn.send_info(&msg).await;
+                        lobby.lock().await.set_activity(&player_nick, Activity::Room(name.to_string()));
+                        handle_client(lines, conn, game_room, spectator, name, Vec::new()).await;
+                        lobby.lock().await.close_exhibition_if_empty(name).await;
+                        return;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/exhibition", args @ ..] => {
+                let Some((name, seats, move_delay)) = parse_exhibition(args) else {
+                    conn.send_error("Use o formato: /exhibition [nome] <X> <O> [segundos], com easy, medium, hard, impossible ou bot em cada lugar (ex: /exhibition demo hard easy 2)\n").await;
+                    continue;
+                };
+                let result = lobby.lock().await.create_exhibition(name, seats, move_delay, &player_nick);
+                match result {
+                    Ok((name, game_room, spectator)) => {
+                        let msg = format!("Sala de exibição '{}' criada, com {}s entre as jogadas. Você está assistindo.\n", name, move_delay.as_secs());
+                        conn.send_info(&msg).await;
+                        // Com o computador nos dois lugares, a partida já começou
+                        let notices = match game_room.view(0).await {
+                            Some(view) if view.status == RoomStatus::Playing => vec![view.start_notice],
+                            _ => Vec::new(),
+                        };
+                        lobby.lock().await.set_activity(&player_nick, Activity::Room(name.clone()));
+                        handle_client(lines, conn, game_room, spectator, &name, notices).await;
+                        lobby.lock().await.close_exhibition_if_empty(&name).await;
+                        return;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            ["/crowd", args @ ..] => {
+                let Some((name, difficulty, window)) = parse_crowd(args) else {
+                    conn.send_error("Use o formato: /crowd [nome] easy|medium|hard|impossible [segundos] (ex: /crowd torcida hard 20)\n").await;
+                    continue;
+                };
+                let result = lobby.lock().await.create_crowd_room(name, difficulty, window, &player_nick);
+                match result {
+                    Ok((name, game_room, spectator)) => {
+                        let msg = format!(
+                            "Sala '{}' criada: a multidão contra o computador ({}). Chame mais gente com /watch {}.\n",
+                            name,
+                            difficulty.name(),
+                            name
+                        );
+                        conn.send_info(&msg).await;
+                        let notices = match game_room.view(0).await {
+                            Some(view) => vec![view.start_notice],
+                            None => Vec::new(),
+                        };
+                        lobby.lock().await.set_activity(&player_nick, Activity::Room(name.clone()));
+                        handle_client(lines, conn, game_room, spectator, &name, notices).await;
+                        lobby.lock().await.close_exhibition_if_empty(&name).await;
+                        return;
+                    }
+                    Err(msg) => conn.send_error(&msg).await,
+                }
+            }
+            [] | ["help"] => conn.send_info(&command::help(Context::Lobby)).await,
+            ["/help" | "help", topic @ ..] => send_help(&mut conn, Context::Lobby, &topic.join(" ")).await,
+            // Comando conhecido com argumentos errados: mostra só como usá-lo
+            [name, ..] => match command::usage(Context::Lobby, name) {
+                Some(usage) => conn.send_error(&usage).await,
+                None => conn.send_error(&command::unknown(name)).await,
+            },
+        }
+    }
 }
 
-struct GameRoom {
-    game_state: GameState,
-    game_started: bool,
-    players: Vec<Player>,
+// Combina com o cliente a versão do protocolo, as capacidades e o idioma. Versões mais
+// novas que a do servidor caem para a dele; capacidades desconhecidas são ignoradas.
+async fn negotiate(conn: &mut Connection, hello: &Hello) {
+    let version = hello.version.min(PROTOCOL_VERSION);
+    let bot = hello.has("bot");
+    let caps: Vec<String> = CAPABILITIES
+        .iter()
+        .filter(|cap| hello.has(cap) && !(bot && matches!(**cap, "color" | "confirm")))
+        .map(|cap| cap.to_string())
+        .collect();
+    let lang = hello.lang.as_deref().and_then(Lang::parse).unwrap_or_default();
+    info!(version, ?caps, lang = lang.code(), "protocolo negociado");
+
+    // Bots recebem só JSON, sem cores e sem prévia das jogadas, peçam o que pedirem
+    conn.bot = bot;
+    conn.json = hello.has("json") || conn.bot;
+    conn.color = hello.has("color") && !conn.bot;
+    conn.utf8 = hello.has("utf8");
+    conn.unicode_board = hello.has("utf8") && !conn.bot;
+    conn.delta = hello.has("delta");
+    conn.confirm_moves = hello.has("confirm") && !conn.bot;
+    conn.lang = lang;
+    conn.send_hello(version, caps, lang.code()).await;
+    if let Some(requested) = hello.lang.as_deref().filter(|requested| Lang::parse(requested).is_none()) {
+        conn.send_info(&format!("Idioma '{}' indisponível; as mensagens seguem em português.\n", requested)).await;
+    }
 }
 
-impl GameRoom {
-    fn new() -> Self {
-        GameRoom {
-            game_state: GameState {
-                board: [[0; 7]; 6], 
-                current_turn: 1, 
+// Lê a próxima linha do cliente. Retorna None se ele desconectou, se ficou parado além do
+// limite de inatividade ou se o servidor está encerrando.
+async fn read_line(lines: &mut ClientLines, conn: &mut Connection) -> Option<String> {
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(line) => return line,
+                Err(err) => {
+                    if report_input_error(conn, &err).await {
+                        return None;
+                    }
+                }
             },
-            game_started: false,
-            players: Vec::new(),
+            _ = conn.idle_expired() => {
+                info!("conexão ociosa encerrada");
+                conn.send_info(IDLE_MESSAGE).await;
+                return None;
+            }
+            interrupt = conn.interrupted() => {
+                if handle_interrupt(conn, interrupt).await {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+// Pede uma entrada secreta, como uma senha. No telnet o eco mostra asteriscos enquanto o
+// cliente digita; nos outros transportes o eco é do próprio cliente e não muda.
+async fn prompt_secret(lines: &mut ClientLines, conn: &mut Connection, prompt: &str) -> Option<String> {
+    conn.set_echo_masked(true);
+    conn.send_info(prompt).await;
+    let secret = read_line(lines, conn).await;
+    conn.set_echo_masked(false);
+    secret.map(|secret| secret.trim().to_string())
+}
+
+// Avisa o cliente sobre a linha recusada. Retorna true se a conexão deve terminar.
+async fn report_input_error(conn: &mut Connection, err: &InputError) -> bool {
+    match err {
+        InputError::Io(_) => debug!(%err, "erro ao ler mensagem do cliente"),
+        InputError::Flooding => warn!(%err, "conexão encerrada pelo limite de entrada"),
+        _ => debug!(%err, "linha recusada"),
+    }
+    if let Some(text) = err.client_message() {
+        conn.send_error(&text).await;
+    }
+    err.is_fatal()
+}
+
+// Mostra o aviso ou a mensagem de despedida. Retorna true se a conexão deve terminar.
+async fn handle_interrupt(conn: &mut Connection, interrupt: Interrupt) -> bool {
+    match interrupt {
+        Interrupt::Announcement(text) => {
+            conn.send_info(&format!("[Aviso do servidor] {}\n", text)).await;
+            false
+        }
+        Interrupt::Notice(text) => {
+            conn.send_info(&text).await;
+            false
+        }
+        Interrupt::Chat { from, text, time } => {
+            conn.send_chat(&from, &text, &time).await;
+            false
+        }
+        Interrupt::Shutdown => {
+            conn.send_info(SHUTDOWN_MESSAGE).await;
+            true
         }
+        Interrupt::Kicked => {
+            conn.send_info(KICK_MESSAGE).await;
+            true
+        }
+        Interrupt::Replaced => {
+            conn.send_info(REPLACED_MESSAGE).await;
+            true
+        }
+        // O cliente parou de ler e a escrita desistiu; não há como avisá-lo
+        Interrupt::Stalled => true,
     }
+}
 
+// Como terminou a espera na fila
+enum QueueWait {
+    Found(Match),
+    Cancelled,
+    Refused, // O desafio foi recusado ou caiu
+    Disconnected,
+}
 
-    // Atualiza o estado do jogo com base na jogada
-    pub fn update_game_state(&mut self, player_move: Move) -> Result<(), String> {
-        if !is_valid_move(&self.game_state, &player_move) {
-            return Err("Jogada inválida. Escolha uma coluna vazia.".to_string());
+// Espera um oponente na fila ou a resposta a um desafio, ainda lendo a conexão para
+// aceitar /cancel e perceber desconexões
+async fn wait_for_match(
+    lines: &mut ClientLines,
+    conn: &mut Connection,
+    lobby: &Arc<Mutex<Lobby>>,
+    nick: &str,
+    mut receiver: oneshot::Receiver<Match>,
+) -> QueueWait {
+    loop {
+        tokio::select! {
+            found = &mut receiver => {
+                return match found {
+                    Ok(found) => QueueWait::Found(found),
+                    Err(_) => QueueWait::Refused,
+                };
+            }
+            interrupt = conn.interrupted() => {
+                if handle_interrupt(conn, interrupt).await {
+                    lobby.lock().await.stop_waiting(nick);
+                    return QueueWait::Disconnected;
+                }
+            }
+            line = lines.next_line() => {
+                let cancel = match line {
+                    Ok(Some(line)) if line.trim() == "/cancel" => true,
+                    Ok(Some(_)) => {
+                        conn.send_error("Aguardando oponente. Use /cancel para desistir da espera.\n").await;
+                        false
+                    }
+                    Err(err) if !report_input_error(conn, &err).await => false,
+                    Ok(None) | Err(_) => {
+                        // Se a partida já tinha sido formada, o oponente vence por W.O.
+                        if !lobby.lock().await.stop_waiting(nick) {
+                            if let Ok(found) = receiver.try_recv() {
+                                found.room.leave(nick).await;
+                            }
+                        }
+                        return QueueWait::Disconnected;
+                    }
+                };
+
+                // Se a partida foi formada enquanto o pedido chegava, ela vale
+                if cancel && !lobby.lock().await.stop_waiting(nick) {
+                    if let Ok(found) = receiver.await {
+                        return QueueWait::Found(found);
+                    }
+                    return QueueWait::Cancelled;
+                }
+                if cancel {
+                    return QueueWait::Cancelled;
+                }
+            }
         }
+    }
+}
+
+// Mostra uma partida gravada passo a passo: n avança, p volta e q sai.
+// Retorna false se o jogador desconectou durante o replay.
+async fn replay_game(lines: &mut ClientLines, conn: &mut Connection, record: &GameRecord) -> bool {
+    let mode = GameMode::parse(&record.mode).unwrap_or(GameMode::ConnectFour);
+    let first_turn = record.moves.first().map_or(1, |played_move| played_move.symbol);
+    let total = record.moves.len();
+    let mut step = 0;
+    conn.full_redraw();
 
-        // Encontra a linha disponível para a jogada
-        let mut row = 5; // Começa da última linha
-        while row > 0 && self.game_state.board[row][player_move.col] != 0 {
-            row -= 1;
+    loop {
+        // A posição é refeita desde o início, como no /undo
+        let mut game = Game::new(mode, record.board_config, first_turn);
+        for played_move in &record.moves[..step] {
+            game.replay(played_move);
         }
-        
-        // Coloca a peça na linha e coluna corretas
-        self.game_state.board[row][player_move.col] = self.game_state.current_turn;
+        let last_move = step.checked_sub(1).map(|index| (record.moves[index].row, record.moves[index].col));
+        conn.send_position(&game, last_move).await;
 
-        // Alterna o turno
-        self.game_state.current_turn = -self.game_state.current_turn;
+        let mut status = format!("Partida #{}: jogada {}/{}", record.id, step, total);
+        match step.checked_sub(1) {
+            Some(index) => status.push_str(&format!(" ({}).", export::move_notation(mode, &record.moves[index]))),
+            None => status.push('.'),
+        }
+        if step == total {
+            let result = match record.winner {
+                1 => " Vitória do X.",
+                -1 => " Vitória do O.",
+                _ => " Empate.",
+            };
+            status.push_str(result);
+        }
+        status.push_str(" n = próxima, p = anterior, q = sair\n");
+        conn.send_info(&status).await;
 
-        Ok(())
+        let line = match read_line(lines, conn).await {
+            Some(line) => line,
+            None => return false,
+        };
+        match line.trim().to_lowercase().as_str() {
+            "n" | "" => step = (step + 1).min(total),
+            "p" => step = step.saturating_sub(1),
+            "q" => return true,
+            _ => conn.send_error("Use n para avançar, p para voltar ou q para sair.\n").await,
+        }
     }
+}
 
-    // Retorna o estado atual do jogo como uma string em formato bonito
-    pub fn get_game_state(&self) -> String {
-        let mut chars = Vec::new();
-        for i in 1..50 {
-            chars.push('\n');
+// Quebra-cabeça do /puzzle e do /daily: o jogador joga o lado da vez e o computador
+// defende, até a vitória, um erro ou a desistência com q. Retorna se ele resolveu, ou None
+// se a conexão caiu.
+async fn solve_puzzle(lines: &mut ClientLines, conn: &mut Connection, mut position: GameState, moves: u32, title: &str) -> Option<bool> {
+    let player = position.current_turn;
+    let mut remaining = moves;
+    let mut last_move = None;
+    let mut status = format!(
+        "{}: {} joga e vence em {}. Digite a coluna de cada jogada, ou q para desistir.\n",
+        title,
+        GameMode::ConnectFour.glyph(player),
+        moves
+    );
+    conn.full_redraw();
+
+    loop {
+        conn.send_position(&Game::ConnectFour(position.clone()), last_move).await;
+        conn.send_info(&status).await;
+        let line = read_line(lines, conn).await?;
+        // A solução mostrada a quem erra ou desiste parte da posição atual
+        let solution = puzzle::winning_move(&position, remaining).map_or(0, |col| col + 1);
+        if line.trim().eq_ignore_ascii_case("q") {
+            conn.send_info(&format!("Você desistiu. Uma solução é jogar na coluna {}.\n", solution)).await;
+            return Some(false);
         }
-        for row in &self.game_state.board {
-            for cell in row {
-                if *cell == 0 { chars.push('+'); }
-                else if *cell == 1 { chars.push('X'); }
-                else if *cell == -1 { chars.push('O'); }
+        let col = match parse_column(&line) {
+            Ok(col) => col,
+            Err(msg) => {
+                conn.send_error(&msg).await;
+                continue;
             }
-            chars.push('\n');
+        };
+        let mut next = position.clone();
+        let row = match next.apply_move(&Move { col }) {
+            Ok(row) => row,
+            Err(err) => {
+                conn.send_game_error(&err).await;
+                continue;
+            }
+        };
+        let keeps_win = puzzle::move_wins_within(&position, col, remaining);
+        position = next;
+        last_move = Some((row, col));
+        if has_line(&position, player) {
+            conn.send_position(&Game::ConnectFour(position), last_move).await;
+            conn.send_info(&format!("Resolvido! Você venceu em {} jogadas.\n", moves)).await;
+            return Some(true);
+        }
+        if !keeps_win {
+            conn.send_position(&Game::ConnectFour(position), last_move).await;
+            conn.send_info(&format!("Essa jogada deixa a vitória escapar. Uma solução era jogar na coluna {}.\n", solution)).await;
+            return Some(false);
         }
-        let s = chars.into_iter().collect();
-        s
+
+        remaining -= 1;
+        // Sempre há resposta: a jogada que mantém a vitória não encerra a partida
+        let reply = puzzle::defend(&position, remaining).unwrap_or_default();
+        let Ok(row) = position.apply_move(&Move { col: reply }) else {
+            return Some(false);
+        };
+        last_move = Some((row, reply));
+        status = format!("O adversário respondeu na coluna {}. Você ainda vence em {}.\n", reply + 1, remaining);
     }
 }
 
-async fn handle_client(mut stream: tokio::net::TcpStream, game_room: Arc<Mutex<GameRoom>>, player_symbol: i32) {
-    let (reader, mut writer) = io::split(stream);
-    let mut reader = io::BufReader::new(reader);
-    let mut buffer = String::new();
+// Como terminou a transmissão de uma sala no modo TV
+enum Channel {
+    Next,         // Próxima partida, pedida pelo cliente ou porque esta terminou
+    Quit,         // Volta ao lobby
+    Disconnected, // A conexão caiu ou o servidor está encerrando
+}
 
+// Modo TV: assiste às partidas em andamento sem ocupar lugar nas salas, só acompanhando os
+// eventos delas. Começa por uma partida sorteada, n passa para a próxima e q volta ao lobby;
+// quando uma partida termina, a seguinte entra sozinha. Retorna false se a conexão caiu.
+async fn watch_tv(lines: &mut ClientLines, conn: &mut Connection, lobby: &Arc<Mutex<Lobby>>) -> bool {
+    let mut current: Option<String> = None;
     loop {
-        // Bloqueia o estado do jogo para acessar a lógica de controle de turno e estado
-        let mut game_room_lock = game_room.lock().await;
-        let game_state_str = game_room_lock.get_game_state();
-        
-        // Envia o estado atual do jogo
-        let _ = writer.write_all(game_state_str.as_bytes()).await;
+        let next = lobby.lock().await.live_room(current.as_deref()).await;
+        let (name, game_room) = match next {
+            Some(next) => next,
+            None => {
+                conn.send_info("Nenhuma partida em andamento para assistir.\n").await;
+                return true;
+            }
+        };
+
+        match tune_in(lines, conn, &game_room, &name).await {
+            Channel::Next => current = Some(name),
+            Channel::Quit => return true,
+            Channel::Disconnected => return false,
+        }
+    }
+}
+
+// Transmite uma sala para o modo TV até a partida acabar ou o cliente pedir outra
+async fn tune_in(lines: &mut ClientLines, conn: &mut Connection, game_room: &RoomHandle, name: &str) -> Channel {
+    let mut events = game_room.subscribe();
+    let mut redraw = true;
+    let mut next_at = None; // Com a partida encerrada, quando passar para a próxima
+    let mut announced = false;
+    conn.full_redraw();
 
-        // Verifica se é o turno do jogador, caso contrário, apenas aguarda
-        if game_room_lock.game_state.current_turn != player_symbol {
-            drop(game_room_lock); // Libera o bloqueio, para que outros jogadores possam jogar
-            continue; // Se não é o turno, continua esperando
+    loop {
+        if redraw {
+            let view = match game_room.view(0).await {
+                Some(view) => view,
+                None => return Channel::Next,
+            };
+            conn.send_state(&view, 0).await;
+            // O aviso vem depois do primeiro quadro, que limpa a tela
+            if !announced {
+                conn.send_info(&format!("TV: sala '{}'. Digite n para a próxima partida ou q para sair.\n", name)).await;
+                announced = true;
+            }
+            if let RoomStatus::Finished(winner) = view.status {
+                conn.send_game_over(&view.game, winner, 0).await;
+                next_at = Some(time::Instant::now() + TV_RESULT_DELAY);
+            }
+            redraw = false;
         }
 
-        // Jogador tem permissão para jogar
-        buffer.clear();
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(RoomEvent::Notice(text)) => conn.send_info(&text).await,
+                Ok(RoomEvent::Chat { from, text, time, team: 0 }) => conn.send_chat(&from, &text, &time).await,
+                Ok(RoomEvent::Chat { .. }) => {}
+                Ok(RoomEvent::Closed | RoomEvent::Aborted) | Err(broadcast::error::RecvError::Closed) => return Channel::Next,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    metrics::METRICS.events_lagged(skipped);
+                    redraw = next_at.is_none();
+                }
+                Ok(_) => redraw = next_at.is_none(),
+            },
+            _ = time::sleep_until(next_at.unwrap_or_else(time::Instant::now)), if next_at.is_some() => return Channel::Next,
+            interrupt = conn.interrupted() => {
+                if handle_interrupt(conn, interrupt).await {
+                    return Channel::Disconnected;
+                }
+            }
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => match line.trim().trim_start_matches('/').to_lowercase().as_str() {
+                    "n" | "next" => return Channel::Next,
+                    "q" | "quit" => return Channel::Quit,
+                    "board" => {
+                        conn.full_redraw();
+                        redraw = true;
+                    }
+                    _ => conn.send_error("Use n para a próxima partida ou q para sair.\n").await,
+                },
+                Ok(None) => return Channel::Disconnected,
+                Err(err) => {
+                    if report_input_error(conn, &err).await {
+                        return Channel::Disconnected;
+                    }
+                }
+            },
+        }
+    }
+}
+
+// Joga na sala até sair. Antes, entrega ao jogador o aviso de entrada, se houver, e o código
+// de reconexão; se a conexão cair com a partida em andamento, a sala fica aberta até ele
+// voltar ou perder o prazo.
+#[allow(clippy::too_many_arguments)]
+async fn play_in_room(
+    lines: ClientLines,
+    conn: Connection,
+    lobby: &Arc<Mutex<Lobby>>,
+    config: &Config,
+    game_room: RoomHandle,
+    player: Player,
+    room_name: &str,
+    greeting: Option<String>,
+) -> Option<(ClientLines, Connection)> {
+    let mut notices: Vec<String> = greeting.into_iter().collect();
+    lobby.lock().await.set_activity(&player.nick, Activity::Room(room_name.to_string()));
+    if config.reconnect_timeout_secs > 0 {
+        let token = lobby.lock().await.issue_token(room_name, &player);
+        notices.push(format!(
+            "Código de reconexão: {}. Se a conexão cair, use RESUME {} em até {}s.\n",
+            token, token, config.reconnect_timeout_secs
+        ));
+    }
+
+    let player_nick = player.nick.clone();
+    match handle_client(lines, conn, game_room.clone(), player, room_name, notices).await {
+        RoomExit::Dropped => {
+            let task = close_after_pause(Arc::clone(lobby), game_room, room_name.to_string());
+            tokio::spawn(task.in_current_span());
+            None
+        }
+        RoomExit::Left => {
+            lobby.lock().await.close_room_if_done(room_name).await;
+            None
+        }
+        RoomExit::Lobby(back) => {
+            let mut lobby = lobby.lock().await;
+            lobby.close_room_if_done(room_name).await;
+            lobby.set_activity(&player_nick, Activity::Idle);
+            Some(*back)
+        }
+    }
+}
 
-        // Lê a entrada do jogador
-        if reader.read_line(&mut buffer).await.is_err() {
-            println!("Erro ao ler mensagem do cliente");
+// Como a conexão saiu da sala
+enum RoomExit {
+    Left,    // O jogador saiu, foi desligado ou a sala fechou
+    Dropped, // A conexão caiu e a partida ficou pausada esperando o jogador voltar
+    Lobby(Box<(ClientLines, Connection)>), // A partida foi anulada e o jogador volta para o lobby
+}
+
+// Espera a pausa terminar, com a volta do jogador ou com o W.O., e fecha a sala se ela
+// acabou. No modo solo não sobra outra conexão para fazer isso.
+async fn close_after_pause(lobby: Arc<Mutex<Lobby>>, game_room: RoomHandle, room_name: String) {
+    let mut events = game_room.subscribe();
+    while game_room.summary().await.is_some_and(|summary| summary.status == RoomStatus::Paused) {
+        if let Err(broadcast::error::RecvError::Closed) = events.recv().await {
             break;
         }
+    }
+    lobby.lock().await.close_room_if_done(&room_name).await;
+}
+
+// Atende o jogador dentro da sala. `notice` é exibido logo depois do primeiro tabuleiro.
+#[tracing::instrument(name = "room", skip_all, fields(room = %room_name, symbol = player.symbol))]
+async fn handle_client(
+    mut lines: ClientLines,
+    mut conn: Connection,
+    game_room: RoomHandle,
+    player: Player,
+    room_name: &str,
+    notices: Vec<String>,
+) -> RoomExit {
+    info!("entrou na sala");
+    let mut player_symbol = player.symbol; // Muda se os jogadores trocarem de lado
+    let mut events = game_room.subscribe();
+    let mode = game_room.mode();
+    let mut redraw = true;
+    let mut prompt = false; // O tabuleiro mudou e o jogador precisa saber de quem é a vez
+    let mut messages: Vec<RoomEvent> = notices.into_iter().map(RoomEvent::Notice).collect();
+    let mut game_over_sent = false;
+    let mut dropped = false; // A conexão caiu, em vez de o jogador sair da sala
+    let mut last_lag: Option<Instant> = None; // Quando a conexão perdeu eventos da sala pela última vez
+    let mut proposed: Option<ParsedMove> = None; // Jogada à espera do /confirm
+    let mut my_turn = false; // Para o aviso sonoro tocar só quando a vez chega
+    let mut to_lobby = false; // A partida foi anulada, ou a do torneio acabou, e quem jogava volta para o lobby
+    conn.full_redraw();
+
+    loop {
+        // Consome os eventos pendentes antes de olhar o estado da sala
+        while let Ok(event) = events.try_recv() {
+            apply_event(event, &mut redraw, &mut messages, &mut player_symbol);
+        }
+
+        // Retrato da sala feito pela tarefa dela; os eventos que chegarem depois pedem outro
+        let view = match game_room.view_for(player_symbol, &player.nick).await {
+            Some(view) => view,
+            None => {
+                // O último aviso da sala explica por que ela fechou
+                while let Ok(event) = events.try_recv() {
+                    apply_event(event, &mut redraw, &mut messages, &mut player_symbol);
+                }
+                for message in messages.drain(..) {
+                    match message {
+                        RoomEvent::Notice(notice) => conn.send_info(&notice).await,
+                        RoomEvent::Aborted => to_lobby = player.role == Role::Player,
+                        _ => {}
+                    }
+                }
+                if to_lobby {
+                    conn.send_info("De volta ao lobby. Digite help para ver os comandos.\n").await;
+                } else {
+                    conn.send_info("A sala foi encerrada.\n").await;
+                }
+                break;
+            }
+        };
+
+        if redraw {
+            // Envia o estado atual do jogo
+            conn.send_state(&view, player_symbol).await;
+            redraw = false;
+            prompt = true;
+        }
 
-        // Processa a entrada (jogada do jogador)
-        let parts: Vec<&str> = buffer.trim().split_whitespace().collect();
-        if parts.len() == 1 {
-            if let Ok(col) = parts[0].parse::<usize>() {
-                let player_move = Move { col };
+        // Avisos e mensagens de chat vêm depois do tabuleiro para não serem apagados por ele
+        let mut ring = false;
+        for message in messages.drain(..) {
+            match message {
+                RoomEvent::Notice(notice) => conn.send_info(&notice).await,
+                RoomEvent::Chat { from, text, time, team: 0 } => conn.send_chat(&from, &text, &time).await,
+                // O chat da dupla só chega ao lado dela
+                RoomEvent::Chat { from, text, time, team } if team == player_symbol => {
+                    conn.send_chat(&format!("{} (dupla)", from), &text, &time).await
+                }
+                RoomEvent::Chat { .. } => {}
+                RoomEvent::OpponentJoined => {
+                    conn.send_info(&view.start_notice).await;
+                    ring = true;
+                }
+                RoomEvent::StateChanged | RoomEvent::GameOver | RoomEvent::Closed | RoomEvent::SidesSwapped | RoomEvent::Aborted => {}
+            }
+        }
+        let turn_now = player.role == Role::Player
+            && view.status == RoomStatus::Playing
+            && view.game.current_turn() == player_symbol
+            && !view.partner_turn;
+        if ring || (turn_now && !my_turn) {
+            conn.ring_bell().await;
+        }
+        my_turn = turn_now;
 
-                match game_room_lock.update_game_state(player_move) {
-                    Ok(_) => {
-                        // Se a jogada foi bem-sucedida, verifica se alguém venceu
-                        let game_state_str = game_room_lock.get_game_state();
-                        let _ = writer.write_all(game_state_str.as_bytes()).await;
+        // Em modo texto, a última linha diz de quem é a vez; no JSON o estado já informa
+        if prompt && !conn.json && player.role == Role::Player {
+            if let Some(text) = &view.prompt {
+                conn.send_info(text).await;
+            }
+        }
+        prompt = false;
 
-                        // Verifica se o jogador venceu
-                        if check_winner(&game_room_lock.game_state, player_symbol) {
-                            let msg = "Você venceu!\n";
-                            let _ = writer.write_all(msg.as_bytes()).await;
+        // Com a partida encerrada, cada jogador recebe o resultado do seu ponto de vista
+        // e decide se quer a revanche
+        let finished = matches!(view.status, RoomStatus::Finished(_));
+        if let RoomStatus::Finished(winner) = view.status {
+            if !game_over_sent {
+                conn.send_game_over(&view.game, winner, player_symbol).await;
+                game_over_sent = true;
+                // No torneio não há revanche: o chaveamento segue no lobby
+                if player.role == Role::Player && view.tournament {
+                    conn.send_info("A partida do torneio terminou. De volta ao lobby.\n").await;
+                    to_lobby = true;
+                    break;
+                }
+                if player.role == Role::Player && view.seated == view.seats {
+                    conn.send_info("Jogar novamente? (s/n)\n").await;
+                }
+            }
+
+            // Com a mesa incompleta não há revanche
+            if view.seated < view.seats {
+                break;
+            }
+        } else {
+            game_over_sent = false;
+        }
+
+        // Todos os jogadores saíram antes de a partida começar. Na exibição as pessoas só
+        // assistem, então a sala segue.
+        if view.humans == 0 && !view.exhibition {
+            break;
+        }
+
+        // Espera uma mudança na sala ou uma linha do jogador, o que vier primeiro.
+        // A leitura continua mesmo fora do turno para perceber desconexões.
+        tokio::select! {
+            event = events.recv() => {
+                match event {
+                    Ok(event) => apply_event(event, &mut redraw, &mut messages, &mut player_symbol),
+                    // Os eventos perdidos são descartados e o tabuleiro vai inteiro de novo. Jogadores
+                    // sempre se recuperam assim; quem assiste e volta a atrasar logo é desligado.
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        metrics::METRICS.events_lagged(skipped);
+                        let lagging = last_lag.is_some_and(|at| at.elapsed() < SPECTATOR_LAG_GRACE);
+                        if player.role == Role::Spectator && lagging {
+                            warn!(skipped, "espectador não acompanha a sala; conexão encerrada");
+                            metrics::METRICS.slow_client_dropped();
+                            conn.send_error("Sua conexão não acompanhou a sala e foi encerrada.\n").await;
                             break;
                         }
+                        last_lag = Some(Instant::now());
+                        redraw = true;
                     }
-                    Err(msg) => {
-                        // Se a jogada foi inválida, envia a mensagem de erro
-                        let _ = writer.write_all(msg.as_bytes()).await;
-                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+                // A prévia mostrada não vale mais para o tabuleiro novo
+                if redraw {
+                    proposed = None;
                 }
-            } else {
-                // Envia mensagem se a entrada for inválida
-                let msg = "Coluna inválida. Use o formato: número da coluna (ex: 1)\n";
-                let _ = writer.write_all(msg.as_bytes()).await;
             }
-        } else {
-            // Envia mensagem se a jogada não tiver o formato esperado
-            let msg = "Formato de jogada inválido. Use o formato: número da coluna (ex: 1)\n";
-            let _ = writer.write_all(msg.as_bytes()).await;
+            interrupt = conn.interrupted() => {
+                // Um cliente que parou de ler conta como uma queda: a partida pausa à espera dele.
+                // A sessão retomada em outra conexão também, para que a vaga passe para ela.
+                dropped = matches!(interrupt, Interrupt::Stalled | Interrupt::Replaced);
+                if handle_interrupt(&mut conn, interrupt).await {
+                    break;
+                }
+            }
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => {
+                    // Qualquer linha conta como sinal de vida para a detecção de ausentes
+                    if player.role == Role::Player {
+                        game_room.record_activity(&player.nick).await;
+                    }
+                    match parse_room_input(&line) {
+                        RoomInput::Chat(text) => send_chat(&game_room, &mut conn, &player, text, 0, view.chat_filter).await,
+                        RoomInput::TeamChat(_) if !view.teams || player.role != Role::Player => {
+                            conn.send_error("O chat da dupla só vale para quem joga em uma sala de duplas.\n").await;
+                        }
+                        RoomInput::TeamChat(text) => send_chat(&game_room, &mut conn, &player, text, player_symbol, view.chat_filter).await,
+                        RoomInput::Help(topic) => send_help(&mut conn, Context::Room, topic.trim()).await,
+                        RoomInput::Invalid(msg) => conn.send_error(&msg).await,
+                        RoomInput::Board => {
+                            conn.full_redraw();
+                            redraw = true;
+                        }
+                        RoomInput::Bell(setting) => set_bell(&mut conn, setting.trim()).await,
+                        RoomInput::Threats(setting) => match setting.trim().to_lowercase().as_str() {
+                            "on" => {
+                                conn.threats = true;
+                                conn.send_info("Destaque de ameaças ativado: ! vence para você, # vence para o oponente.\n").await;
+                                redraw = true;
+                            }
+                            "off" => {
+                                conn.threats = false;
+                                conn.send_info("Destaque de ameaças desativado.\n").await;
+                                redraw = true;
+                            }
+                            _ => conn.send_error("Use /threats on ou /threats off.\n").await,
+                        },
+                        // Vale para quem assiste e também depois do fim da partida
+                        RoomInput::Eval => match game_room.evaluation(player_symbol).await {
+                            Ok(evaluation) => {
+                                conn.send_info(&format!("Resolvendo a posição; pode levar até {}s.\n", EVAL_TIME_LIMIT.as_secs())).await;
+                                let report = tokio::task::spawn_blocking(move || eval_report(&evaluation));
+                                if let Ok(report) = report.await {
+                                    conn.send_info(&report).await;
+                                }
+                            }
+                            Err(msg) => conn.send_error(&msg).await,
+                        },
+                        RoomInput::Quit => {
+                            conn.send_info("Até logo!\n").await;
+                            break;
+                        }
+                        // Na sala da multidão, quem assiste vota com /vote ou só com o número da coluna
+                        RoomInput::Vote(text) | RoomInput::Other(text) if view.crowd && player.role == Role::Spectator => {
+                            match parse_position(text.trim()) {
+                                Some(col) => match game_room.vote(&player.nick, col).await {
+                                    Ok(()) => conn.send_info(&format!("Voto registrado: coluna {}.\n", col + 1)).await,
+                                    Err(msg) => conn.send_error(&msg).await,
+                                },
+                                None => conn.send_error("Use o formato: /vote <coluna>\n").await,
+                            }
+                        }
+                        RoomInput::Vote(_) => conn.send_error("Esta sala não tem votação.\n").await,
+                        _ if player.role == Role::Spectator => {
+                            conn.send_error("Espectadores não podem jogar. Digite help para ver os comandos.\n").await;
+                        }
+                        _ if finished => {
+                            if !answer_rematch(&game_room, &mut conn, &line, player_symbol).await {
+                                break;
+                            }
+                        }
+                        RoomInput::Undo => {
+                            let result = game_room.request_undo(player_symbol).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Abort => {
+                            let result = game_room.request_abort(player_symbol).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Draw => {
+                            let result = game_room.offer_draw(player_symbol).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Answer(accepted) => {
+                            let result = game_room.answer(player_symbol, accepted).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Pop(text) => match parse_position(text.trim()) {
+                            None => conn.send_error("Uso: /pop <coluna>\n").await,
+                            Some(col) => {
+                                let player_move = ParsedMove::PopOut(col);
+                                prompt = submit_move(&game_room, &mut conn, &mut events, &mut proposed, player_move, player_symbol, &player.nick).await;
+                            }
+                        },
+                        RoomInput::Wild(text) => match parse_position(text.trim()) {
+                            None => conn.send_error("Uso: /wild <coluna>\n").await,
+                            Some(col) => {
+                                let player_move = ParsedMove::Wild(col);
+                                prompt = submit_move(&game_room, &mut conn, &mut events, &mut proposed, player_move, player_symbol, &player.nick).await;
+                            }
+                        },
+                        RoomInput::Swap => {
+                            let result = game_room.swap_sides(player_symbol).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Hint => match game_room.hint(player_symbol).await {
+                            Ok(hint) => {
+                                // A busca leva meio segundo, então roda fora da tarefa da conexão
+                                let position = hint.position.clone();
+                                let search = tokio::task::spawn_blocking(move || ai::best_move(&position, Difficulty::Medium));
+                                match search.await.ok().flatten() {
+                                    Some(col) => conn.send_hint(&hint, col).await,
+                                    None => conn.send_error("Não há jogadas para sugerir.\n").await,
+                                }
+                            }
+                            Err(msg) => conn.send_error(&msg).await,
+                        },
+                        RoomInput::Pause => {
+                            let result = game_room.pause(player_symbol, room_name).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Resume => {
+                            let result = game_room.resume_game(player_symbol, room_name).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Resign => {
+                            let result = game_room.resign(player_symbol).await;
+                            if let Err(msg) = result {
+                                conn.send_error(&msg).await;
+                            }
+                        }
+                        RoomInput::Confirm => match proposed.take() {
+                            Some(player_move) => {
+                                prompt = play_move(&game_room, &mut conn, &mut events, player_move, player_symbol, &player.nick).await;
+                            }
+                            None => conn.send_error("Não há jogada para confirmar.\n").await,
+                        },
+                        RoomInput::Cancel => match proposed.take() {
+                            Some(_) => {
+                                conn.send_info("Jogada cancelada.\n").await;
+                                conn.full_redraw();
+                                redraw = true;
+                            }
+                            None => conn.send_error("Não há jogada para cancelar.\n").await,
+                        },
+                        RoomInput::Other(text) => match parse_move(mode, text) {
+                            Err(msg) => conn.send_error(&msg).await,
+                            Ok(player_move) => {
+                                prompt = submit_move(&game_room, &mut conn, &mut events, &mut proposed, player_move, player_symbol, &player.nick).await;
+                            }
+                        },
+                    }
+                }
+                Ok(None) => {
+                    dropped = true;
+                    break;
+                }
+                Err(err) => {
+                    if report_input_error(&mut conn, &err).await {
+                        dropped = true;
+                        break;
+                    }
+                }
+            },
+        }
+    }
+
+    // Ao sair da sala o jogador libera a vaga; se a conexão caiu no meio da partida, ela fica guardada.
+    // Da partida anulada não sobra vaga: a sala já fechou. A do torneio acabou e fecha em seguida.
+    if to_lobby {
+        RoomExit::Lobby(Box::new((lines, conn)))
+    } else if dropped && game_room.disconnect(&player.nick).await {
+        RoomExit::Dropped
+    } else {
+        if !dropped {
+            game_room.leave(&player.nick).await;
         }
+        RoomExit::Left
     }
 }
 
+// Entrada do jogador dentro da sala
+enum RoomInput<'a> {
+    Chat(&'a str),
+    TeamChat(&'a str), // /team <mensagem>, só para o parceiro
+    Help(&'a str),     // help [comando]
+    Board,
+    Quit,
+    Resign,
+    Undo,
+    Abort,          // /abort, que anula a partida sem resultado
+    Draw,           // /draw, que propõe empate
+    Answer(bool),   // /accept ou /decline, aos pedidos para desfazer, anular ou empatar
+    Pop(&'a str),   // /pop <coluna>, no Pop Out
+    Wild(&'a str),  // /wild <coluna>, no Power Up
+    Threats(&'a str), // /threats on|off
+    Bell(&'a str),    // /bell on|off
+    Vote(&'a str),    // /vote <coluna>, na sala da multidão
+    Swap,
+    Hint,
+    Eval,
+    Pause,
+    Resume,
+    Confirm, // /confirm, para a jogada proposta por quem joga em dois passos
+    Cancel,  // /cancel, que descarta a jogada proposta
+    Other(&'a str), // Jogada ou resposta ao convite de revanche
+    Invalid(String), // Comando desconhecido ou abreviação ambígua
+}
 
-// Verificar se uma jogada é válida
-fn is_valid_move(game_state: &GameState, player_move: &Move) -> bool {
-    player_move.col < 7 && game_state.board[0][player_move.col] == 0
+// Os comandos da sala valem com ou sem a barra ("help" e "/help" são o mesmo) e abreviados
+// com a barra (/pa para /pause). Números sozinhos são jogadas.
+fn parse_room_input(line: &str) -> RoomInput<'_> {
+    let (name, argument) = match command::classify(Context::Room, line) {
+        Ok(Input::Command(name, argument)) => (name.trim_start_matches('/'), argument),
+        Ok(Input::Move(text)) | Ok(Input::Text(text)) => return RoomInput::Other(text),
+        Err(msg) => return RoomInput::Invalid(msg),
+    };
+    match name {
+        "say" => RoomInput::Chat(argument),
+        "team" => RoomInput::TeamChat(argument),
+        "pop" => RoomInput::Pop(argument),
+        "wild" => RoomInput::Wild(argument),
+        "threats" => RoomInput::Threats(argument),
+        "bell" => RoomInput::Bell(argument),
+        "vote" => RoomInput::Vote(argument),
+        "help" => RoomInput::Help(argument),
+        "board" => RoomInput::Board,
+        "quit" => RoomInput::Quit,
+        "resign" | "desistir" => RoomInput::Resign,
+        "undo" => RoomInput::Undo,
+        "abort" => RoomInput::Abort,
+        "draw" => RoomInput::Draw,
+        "accept" => RoomInput::Answer(true),
+        "decline" => RoomInput::Answer(false),
+        "swap" => RoomInput::Swap,
+        "hint" => RoomInput::Hint,
+        "eval" => RoomInput::Eval,
+        "pause" => RoomInput::Pause,
+        "resume" => RoomInput::Resume,
+        "confirm" => RoomInput::Confirm,
+        "cancel" => RoomInput::Cancel,
+        _ => RoomInput::Other(line),
+    }
 }
 
-// Função para verificar se algum jogador venceu
-fn check_winner(game_state: &GameState, player_symbol: i32) -> bool {
-    for row in 0..6 {
-        for col in 0..4 {
-            if game_state.board[row][col] == player_symbol
-                && game_state.board[row][col + 1] == player_symbol
-                && game_state.board[row][col + 2] == player_symbol
-                && game_state.board[row][col + 3] == player_symbol
-            {
-                return true;
-            }
+// Ajuda completa do lugar ou, com um tópico, só a do comando pedido
+async fn send_help(conn: &mut Connection, context: Context, topic: &str) {
+    if topic.is_empty() {
+        conn.send_info(&command::help(context)).await;
+        return;
+    }
+    match command::usage(context, topic) {
+        Some(usage) => conn.send_info(&usage).await,
+        None => conn.send_error(&format!("Não há ajuda para {}. Digite help para ver os comandos.\n", topic)).await,
+    }
+}
+
+// Liga ou desliga o aviso sonoro da conexão; vale no lobby e dentro das salas
+async fn set_bell(conn: &mut Connection, setting: &str) {
+    match setting.to_lowercase().as_str() {
+        "on" => {
+            conn.bell = true;
+            conn.send_info("Aviso sonoro ativado: toca quando for sua vez e quando o oponente entrar.\n").await;
+        }
+        "off" => {
+            conn.bell = false;
+            conn.send_info("Aviso sonoro desativado.\n").await;
         }
+        _ => conn.send_error("Use /bell on ou /bell off.\n").await,
     }
+}
 
-    for col in 0..7 {
-        for row in 0..3 {
-            if game_state.board[row][col] == player_symbol
-                && game_state.board[row + 1][col] == player_symbol
-                && game_state.board[row + 2][col] == player_symbol
-                && game_state.board[row + 3][col] == player_symbol
-            {
-                return true;
-            }
+fn apply_event(event: RoomEvent, redraw: &mut bool, messages: &mut Vec<RoomEvent>, player_symbol: &mut i32) {
+    match event {
+        // Espectadores têm o símbolo 0, que não muda com a troca
+        RoomEvent::SidesSwapped => {
+            *player_symbol = -*player_symbol;
+            *redraw = true;
         }
+        RoomEvent::StateChanged | RoomEvent::GameOver | RoomEvent::Closed => *redraw = true,
+        RoomEvent::Notice(_) | RoomEvent::Chat { .. } | RoomEvent::OpponentJoined | RoomEvent::Aborted => messages.push(event),
+    }
+}
+
+// Repassa uma mensagem de chat para todos na sala, identificada pelo apelido de quem enviou.
+// Com `team`, só o lado da dupla recebe.
+async fn send_chat(game_room: &RoomHandle, conn: &mut Connection, player: &Player, text: &str, team: i32, filter: Option<FilterLevel>) {
+    let text = text.trim();
+    if text.is_empty() {
+        let usage = if team == 0 { "Uso: /say <mensagem>\n" } else { "Uso: /team <mensagem>\n" };
+        conn.send_error(usage).await;
+        return;
+    }
+    if let Some(notice) = conn.moderation().mute_notice(&player.nick) {
+        conn.send_error(&notice).await;
+        return;
     }
 
-    for row in 0..3 {
-        for col in 0..4 {
-            if game_state.board[row][col] == player_symbol
-                && game_state.board[row + 1][col + 1] == player_symbol
-                && game_state.board[row + 2][col + 2] == player_symbol
-                && game_state.board[row + 3][col + 3] == player_symbol
-            {
-                return true;
+    let event = RoomEvent::Chat {
+        from: player.nick.clone(),
+        text: conn.moderation().censor(text, filter).into_owned(),
+        time: utc_time(),
+        team,
+    };
+    game_room.broadcast(event);
+}
+
+// Horário atual (UTC) no formato HH:MM:SS
+// Dias desde a época Unix, em UTC: o desafio diário muda à meia-noite de Greenwich
+fn utc_day() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()) / SECS_PER_DAY
+}
+
+fn format_millis(ms: u64) -> String {
+    format!("{}.{}s", ms / 1000, ms % 1000 / 100)
+}
+
+fn format_daily_leaderboard(day: u64, entries: &[DailyEntry]) -> String {
+    if entries.is_empty() {
+        return "Ninguém resolveu o desafio de hoje ainda. Tente com /daily.\n".to_string();
+    }
+    let mut list = format!("Desafio do dia {}:\n", export::utc_date(day * SECS_PER_DAY));
+    for (position, entry) in entries.iter().enumerate() {
+        list.push_str(&format!(
+            "  {:>2}. {:<16} {:>7}  ({} tentativas)\n",
+            position + 1,
+            entry.nick,
+            format_millis(entry.duration_ms),
+            entry.attempts
+        ));
+    }
+    list
+}
+
+fn utc_time() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    format!("{:02}:{:02}:{:02}", secs / 3600 % 24, secs / 60 % 60, secs % 60)
+}
+
+// Trata a resposta ao convite de revanche. Retorna false se o jogador deixou a sala.
+async fn answer_rematch(game_room: &RoomHandle, conn: &mut Connection, line: &str, player_symbol: i32) -> bool {
+    match line.trim().to_lowercase().as_str() {
+        // s/n em português, y/n para quem joga em inglês
+        "s" | "sim" | "y" | "yes" => {
+            let started = game_room.accept_rematch(player_symbol).await;
+            if !started {
+                conn.send_info("Aguardando resposta do oponente...\n").await;
             }
+            true
+        }
+        "n" | "nao" | "não" | "no" => false,
+        _ => {
+            conn.send_error("Responda s ou n.\n").await;
+            true
         }
     }
+}
 
-    for row in 3..6 {
-        for col in 0..4 {
-            if game_state.board[row][col] == player_symbol
-                && game_state.board[row - 1][col + 1] == player_symbol
-                && game_state.board[row - 2][col + 2] == player_symbol
-                && game_state.board[row - 3][col + 3] == player_symbol
-            {
-                return true;
+// Processa a entrada do jogador da vez como uma jogada. Retorna true se ela foi aceita.
+async fn play_move(
+    game_room: &RoomHandle,
+    conn: &mut Connection,
+    events: &mut broadcast::Receiver<RoomEvent>,
+    player_move: ParsedMove,
+    player_symbol: i32,
+    nick: &str,
+) -> bool {
+    // A sala avisa todos quando a jogada é aceita; o fim da partida é tratado no laço principal
+    match game_room.play(player_symbol, nick, player_move).await {
+        Ok(notice) => {
+            // O próprio evento é descartado: o tabuleiro já é enviado aqui
+            while events.try_recv().is_ok() {}
+            if let Some(view) = game_room.view_for(player_symbol, nick).await {
+                conn.send_state(&view, player_symbol).await;
             }
+
+            if let Some(notice) = notice {
+                conn.send_info(&notice).await;
+            }
+            true
+        }
+        Err(err) => {
+            // Se a jogada foi inválida, envia a mensagem de erro
+            debug!(%err, "jogada inválida");
+            conn.send_game_error(&err).await;
+            false
         }
     }
+}
 
+// Clientes que negociaram "confirm" recebem só a prévia da jogada, que fica guardada até o
+// /confirm; os demais jogam direto. Retorna true se a jogada foi feita.
+async fn submit_move(
+    game_room: &RoomHandle,
+    conn: &mut Connection,
+    events: &mut broadcast::Receiver<RoomEvent>,
+    proposed: &mut Option<ParsedMove>,
+    player_move: ParsedMove,
+    player_symbol: i32,
+    nick: &str,
+) -> bool {
+    if !conn.confirm_moves {
+        return play_move(game_room, conn, events, player_move, player_symbol, nick).await;
+    }
+    match game_room.preview(player_symbol, nick, player_move.clone()).await {
+        Ok(preview) => {
+            conn.send_preview(&preview).await;
+            *proposed = Some(player_move);
+        }
+        Err(err) => conn.send_game_error(&err).await,
+    }
     false
 }
 
+// Texto do /eval. Durante a partida, o valor da posição atual e a jogada que o mantém;
+// depois do fim, as jogadas que mudaram o resultado teórico.
+fn eval_report(evaluation: &Evaluation) -> String {
+    let deadline = Instant::now() + EVAL_TIME_LIMIT;
+    let too_open = format!("A posição ainda está aberta demais para resolver em {}s.\n", EVAL_TIME_LIMIT.as_secs());
+    if !evaluation.finished {
+        let Some(position) = evaluation.positions.last() else {
+            return too_open;
+        };
+        return match Solver::new(deadline).best_move(position) {
+            Some((col, value)) => format!(
+                "Com o melhor jogo dos dois lados, {}. A jogada que mantém o resultado é a coluna {}.\n",
+                outcome_phrase(value * position.current_turn),
+                col + 1
+            ),
+            None => too_open,
+        };
+    }
+
+    // Cada valor vem da posição depois da jogada de mesmo número; o primeiro é o da inicial
+    let values = solver::game_values(&evaluation.positions, deadline);
+    let Some(first) = values.iter().position(Option::is_some) else {
+        return too_open;
+    };
+    let mut report = String::from("Análise da partida, com o melhor jogo a partir de cada posição:\n");
+    let mut changed = false;
+    for (index, played) in evaluation.moves.iter().enumerate().skip(first) {
+        let (Some(Some(before)), Some(Some(after))) = (values.get(index), values.get(index + 1)) else {
+            continue;
+        };
+        if before != after {
+            let glyph = GameMode::ConnectFour.glyph(played.symbol);
+            report.push_str(&format!(
+                "  Jogada {}, {} na coluna {}: de {} para {}.\n",
+                index + 1,
+                glyph,
+                played.col + 1,
+                outcome_noun(*before),
+                outcome_noun(*after)
+            ));
+            changed = true;
+        }
+    }
+    if !changed {
+        let value = values[first].unwrap_or_default();
+        report.push_str(&format!("  Nenhuma jogada mudou o resultado teórico: {}.\n", outcome_noun(value)));
+    }
+    if first > 0 {
+        report.push_str(&format!(
+            "  As primeiras {} jogadas ficaram de fora: as posições ainda estavam abertas demais para resolver em {}s.\n",
+            first,
+            EVAL_TIME_LIMIT.as_secs()
+        ));
+    }
+    report
+}
+
+// Valor do ponto de vista do X: 1 o X vence, -1 o O vence, 0 empate
+fn outcome_phrase(value: i32) -> &'static str {
+    match value {
+        1 => "o X vence",
+        -1 => "o O vence",
+        _ => "a partida empata",
+    }
+}
+
+fn outcome_noun(value: i32) -> &'static str {
+    match value {
+        1 => "vitória do X",
+        -1 => "vitória do O",
+        _ => "empate",
+    }
+}
+
+fn parse_move(mode: GameMode, line: &str) -> Result<ParsedMove, String> {
+    match mode {
+        GameMode::ConnectFour | GameMode::Ring(_) => parse_column(line).map(|col| ParsedMove::ConnectFour(Move { col })),
+        GameMode::Reversi => parse_cell(line).map(ParsedMove::Reversi),
+    }
+}
+
+// Converte um número digitado, contado a partir de 1, para o índice do tabuleiro
+fn parse_position(text: &str) -> Option<usize> {
+    text.parse::<usize>().ok().and_then(|number| number.checked_sub(1))
+}
+
+// Interpreta a entrada do lig4: o número da coluna, a partir de 1, ou um Move em JSON.
+// No JSON a coluna continua contando a partir de 0, como no protocolo.
+fn parse_column(line: &str) -> Result<usize, String> {
+    if line.trim_start().starts_with('{') {
+        return serde_json::from_str::<Move>(line)
+            .map(|player_move| player_move.col)
+            .map_err(|_| "Jogada JSON inválida. Use o formato: {\"col\": 0}\n".to_string());
+    }
+
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    match parts.as_slice() {
+        [col] => parse_position(col)
+            .ok_or_else(|| "Entrada não reconhecida. Digite o número da coluna (ex: 4) ou help para ver os comandos.\n".to_string()),
+        _ => Err("Entrada não reconhecida. Digite o número da coluna (ex: 4) ou help para ver os comandos.\n".to_string()),
+    }
+}
+
+// Tamanho da série pedido no /create: bo3 ou bo5
+fn parse_best_of(text: &str) -> Option<u32> {
+    match text.to_lowercase().as_str() {
+        "bo3" => Some(3),
+        "bo5" => Some(5),
+        _ => None,
+    }
+}
+
+// Interpreta o /exhibition: o nome, opcional, o que joga de X e de O e o atraso em segundos
+fn parse_exhibition<'a>(args: &[&'a str]) -> Option<(Option<&'a str>, [ExhibitionSeat; 2], Duration)> {
+    let (name, args) = match args {
+        [first, rest @ ..] if ExhibitionSeat::parse(first).is_none() => (Some(*first), rest),
+        _ => (None, args),
+    };
+    let (x, o, delay) = match args {
+        [x, o] => (x, o, None),
+        [x, o, delay] => (x, o, Some(delay)),
+        _ => return None,
+    };
+    let seats = [ExhibitionSeat::parse(x)?, ExhibitionSeat::parse(o)?];
+    let delay = match delay {
+        Some(delay) => delay.parse().ok().filter(|delay| *delay <= MAX_EXHIBITION_DELAY_SECS)?,
+        None => DEFAULT_EXHIBITION_DELAY_SECS,
+    };
+    Some((name, seats, Duration::from_secs(delay)))
+}
+
+// Interpreta o /crowd: o nome, opcional, o nível do computador e a duração de cada votação
+fn parse_crowd<'a>(args: &[&'a str]) -> Option<(Option<&'a str>, Difficulty, Duration)> {
+    let (name, args) = match args {
+        [first, rest @ ..] if Difficulty::parse(first).is_none() => (Some(*first), rest),
+        _ => (None, args),
+    };
+    let (difficulty, window) = match args {
+        [difficulty] => (difficulty, None),
+        [difficulty, window] => (difficulty, Some(window)),
+        _ => return None,
+    };
+    let difficulty = Difficulty::parse(difficulty)?;
+    let window = match window {
+        Some(window) => window.parse().ok().filter(|window| (MIN_CROWD_VOTE_SECS..=MAX_CROWD_VOTE_SECS).contains(window))?,
+        None => DEFAULT_CROWD_VOTE_SECS,
+    };
+    Some((name, difficulty, Duration::from_secs(window)))
+}
+
+// Relógio de xadrez pedido no /create: minutos por jogador e segundos de acréscimo, como 5+3
+fn parse_clock(text: &str) -> Option<GameClock> {
+    let (minutes, increment) = text.split_once('+')?;
+    let minutes: u64 = minutes.parse().ok().filter(|minutes| (1..=MAX_CLOCK_MINUTES).contains(minutes))?;
+    let increment: u64 = increment.parse().ok().filter(|increment| *increment <= MAX_CLOCK_INCREMENT_SECS)?;
+    Some(GameClock::new(Duration::from_secs(minutes * 60), Duration::from_secs(increment)))
+}
+
+// Interpreta as dimensões de um tabuleiro de lig4 personalizado
+fn parse_board_config(rows: &str, cols: &str, win_length: &str) -> Result<BoardConfig, String> {
+    match (rows.parse(), cols.parse(), win_length.parse()) {
+        (Ok(rows), Ok(cols), Ok(win_length)) => BoardConfig::new(rows, cols, win_length),
+        _ => Err("Use o formato: /create <nome> <linhas> <colunas> <sequência> (ex: /create grande 8 9 5)\n".to_string()),
+    }
+}
+
+// Interpreta a entrada do reversi: linha e coluna, a partir de 1, ou um ReversiMove em JSON
+fn parse_cell(line: &str) -> Result<ReversiMove, String> {
+    if line.trim_start().starts_with('{') {
+        return serde_json::from_str::<ReversiMove>(line)
+            .map_err(|_| "Jogada JSON inválida. Use o formato: {\"row\": 2, \"col\": 3}\n".to_string());
+    }
+
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    match parts.as_slice() {
+        [row, col] => match (parse_position(row), parse_position(col)) {
+            (Some(row), Some(col)) => Ok(ReversiMove { row, col }),
+            _ => Err("Casa inválida. Use o formato: linha coluna, a partir de 1 (ex: 3 4)\n".to_string()),
+        },
+        _ => Err("Entrada não reconhecida. Digite linha e coluna (ex: 3 4) ou help para ver os comandos.\n".to_string()),
+    }
+}
+
 #[tokio::main]
 async fn main() {
-    let addr = "127.0.0.1:8080";
-    let listener = TcpListener::bind(addr).await.unwrap();
-    println!("Servidor iniciado na porta 8080");
-
-    let game_room = Arc::new(Mutex::new(GameRoom::new()));
-
-    while let Ok((stream, _)) = listener.accept().await {
-        let game_room_clone = Arc::clone(&game_room);
-        let mut game_room_lock = game_room_clone.lock().await;
-
-        if game_room_lock.players.len() < 2 {
-            let player_symbol = if game_room_lock.players.is_empty() {
-                game_room_lock.players.push(Player {
-                    symbol: 1,
-                    address: stream.peer_addr().unwrap().to_string(),
-                });
-                1
-            } else {
-                game_room_lock.players.push(Player {
-                    symbol: -1,
-                    address: stream.peer_addr().unwrap().to_string(),
-                });
-                -1
-            };
+    let config = match Config::load() {
+        Ok(config) => Arc::new(config),
+        Err(err) => {
+            eprintln!("Configuração inválida: {}", err);
+            std::process::exit(2);
+        }
+    };
 
-            drop(game_room_lock);
+    // RUST_LOG, se definido, tem prioridade sobre o nível da configuração
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.log_level));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter).with_filter_reloading();
+    let log_filter = subscriber.reload_handle();
+    subscriber.init();
 
-            tokio::spawn(async move {
-                handle_client(stream, game_room_clone, player_symbol).await;
-            });
-        } else {
-            let (_, mut writer) = io::split(stream);
-            let _ = writer.write_all(b"Jogo j\xE1 cheio, aguarde uma nova partida!\n").await;
+    // Com vários endereços, como 0.0.0.0:8080 e [::]:8080, o IPv6 não pode tomar também o IPv4
+    let addresses = config.listen_addresses();
+    let mut listeners = Vec::new();
+    for addr in &addresses {
+        match transport::bind(addr, addresses.len() > 1).await {
+            Ok(listener) => {
+                health::HEALTH.listening("tcp", addr.clone());
+                listeners.push(listener);
+            }
+            Err(err) => {
+                error!(%addr, %err, "não foi possível escutar no endereço");
+                std::process::exit(1);
+            }
+        }
+        info!(%addr, "servidor iniciado");
+    }
+
+    // Portas extras com o mesmo protocolo: WebSocket para navegadores e telnet com negociação
+    let ws_listener = bind_optional(config.websocket_address(), "WebSocket").await;
+    let telnet_listener = bind_optional(config.telnet_address(), "telnet").await;
+    let tls = match config.tls_address() {
+        Some(addr) => match tls::load_acceptor(Path::new(&config.tls_cert), Path::new(&config.tls_key)) {
+            Ok(acceptor) => bind_optional(Some(addr), "TLS").await.map(|listener| (listener, acceptor)),
+            Err(err) => {
+                error!(%err, "não foi possível carregar o certificado TLS");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    // Sem o banco, o servidor continua funcionando, apenas sem histórico
+    let storage = match Storage::open(&config.database) {
+        Ok(storage) => Some(Arc::new(storage.with_export_dir(config.export_dir()))),
+        Err(err) => {
+            warn!(%err, "histórico de partidas desativado");
+            None
+        }
+    };
+    let lobby = Arc::new(Mutex::new(Lobby::new(&config, storage)));
+    let restored = lobby.lock().await.restore_games();
+    if restored > 0 {
+        info!(restored, "partidas guardadas reabertas, pausadas até os jogadores voltarem");
+    }
+    if let Some(interval) = config.snapshot_interval() {
+        tokio::spawn(snapshot_periodically(Arc::clone(&lobby), interval));
+    }
+    // Os horários já foram validados ao ler a configuração
+    let schedules = config.tournament_schedules().unwrap_or_default();
+    if !schedules.is_empty() {
+        tokio::spawn(tournament::run_scheduler(Arc::clone(&lobby), schedules));
+    }
+    if let Some(listener) = bind_optional(config.metrics_address(), "métricas").await {
+        tokio::spawn(metrics::serve(listener, Arc::clone(&lobby)));
+    }
+    if let Some(listener) = bind_optional(config.api_address(), "API HTTP").await {
+        tokio::spawn(api::serve(listener, Arc::clone(&lobby)));
+    }
+
+    let (shutdown_sender, shutdown) = watch::channel(false);
+    let limits = ConnectionLimits::new(config.max_connections, config.max_connections_per_ip);
+    let moderation = lobby.lock().await.moderation();
+    // Cada conexão acompanha a configuração atual, trocada a cada recarga
+    let configs = watch::Sender::new(Arc::clone(&config));
+    let (reloader, mut reload_requests) = Reloader::channel();
+    lobby.lock().await.set_reloader(reloader.clone());
+    #[cfg(unix)]
+    tokio::spawn(reload_on_hangup(reloader));
+    let mut connections = JoinSet::new();
+    let signal = shutdown_signal();
+    tokio::pin!(signal);
+    health::HEALTH.set_accepting(true);
+
+    loop {
+        let (accepted, protocol) = tokio::select! {
+            accepted = accept_any(&listeners) => (accepted, Protocol::Tcp),
+            accepted = accept_optional(&ws_listener) => (accepted, Protocol::WebSocket),
+            accepted = accept_optional(&telnet_listener) => (accepted, Protocol::Telnet),
+            (accepted, protocol) = accept_tls(&tls) => (accepted, protocol),
+            _ = &mut signal => break,
+            Some(reply) = reload_requests.recv() => {
+                let result = reload_config(&configs, &lobby, &limits, &log_filter).await;
+                match &result {
+                    Ok(report) => info!(report = report.trim_end(), "configuração recarregada"),
+                    Err(err) => warn!(err = err.trim_end(), "configuração não recarregada"),
+                }
+                let _ = reply.send(result);
+                continue;
+            }
+        };
+        let (stream, peer) = match accepted {
+            Ok(accepted) => accepted,
+            // Falhas do accept são passageiras, como o fim dos descritores numa rajada de
+            // conexões ou um cliente que desistiu na fila; o servidor espera e segue aceitando
+            Err(err) => {
+                error!(%err, "erro ao aceitar conexão");
+                time::sleep(ACCEPT_ERROR_PAUSE).await;
+                continue;
+            }
+        };
+        let span = info_span!("conn", %peer, transport = protocol.name(), nick = tracing::field::Empty);
+        // Endereços banidos são recusados antes de ocupar uma vaga
+        if let Some(msg) = moderation.ip_rejection(peer.ip()) {
+            warn!(%peer, transport = protocol.name(), "conexão recusada: endereço banido");
+            metrics::METRICS.connection_rejected();
+            connections.spawn(reject_connection(stream, protocol, msg).instrument(span));
+            continue;
+        }
+        let slot = match limits.admit(peer.ip()) {
+            Ok(slot) => slot,
+            Err(msg) => {
+                warn!(%peer, transport = protocol.name(), "conexão recusada: limite de conexões");
+                metrics::METRICS.connection_rejected();
+                connections.spawn(reject_connection(stream, protocol, msg).instrument(span));
+                continue;
+            }
+        };
+        info!(%peer, transport = protocol.name(), "conexão aceita");
+        let lobby = Arc::clone(&lobby);
+        let configs = configs.subscribe();
+        let shutdown = shutdown.clone();
+
+        connections.spawn(handle_connection(stream, protocol, lobby, configs, shutdown, slot).instrument(span));
+        // Descarta as conexões que já terminaram
+        while connections.try_join_next().is_some() {}
+    }
+
+    // Para de aceitar conexões, salva as partidas em andamento e avisa todos os clientes
+    health::HEALTH.set_accepting(false);
+    drop(listeners);
+    drop(ws_listener);
+    drop(telnet_listener);
+    drop(tls);
+    let saved = lobby.lock().await.suspend_games().await;
+    info!(saved, "servidor encerrando; partidas em andamento salvas");
+    let _ = shutdown_sender.send(true);
+
+    let finished = time::timeout(SHUTDOWN_TIMEOUT, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if finished.is_err() {
+        warn!("algumas conexões não terminaram a tempo e foram fechadas");
+    }
+}
+
+type LogFilter = tracing_subscriber::reload::Handle<EnvFilter, tracing_subscriber::fmt::Formatter>;
+
+// Relê a configuração e aplica o que pode mudar com o servidor no ar: tempos de jogada,
+// reconexão e ausência, limites de conexões e de salas, mensagem do dia, nível do log e senha
+// do console. Os comandos seguintes de cada conexão já usam os valores novos; o keepalive e
+// os limites de leitura só mudam para as conexões novas, e as portas só reiniciando.
+async fn reload_config(
+    configs: &watch::Sender<Arc<Config>>,
+    lobby: &Mutex<Lobby>,
+    limits: &ConnectionLimits,
+    log_filter: &LogFilter,
+) -> Result<String, String> {
+    let config = Config::load().map_err(|err| format!("Configuração inválida, nada foi mudado: {}\n", err))?;
+    let mut report = String::from("Configuração recarregada.\n");
+    let restart = configs.borrow().restart_required(&config);
+    if !restart.is_empty() {
+        report.push_str(&format!("Só valem depois de reiniciar o servidor: {}.\n", restart.join(", ")));
+    }
+
+    limits.set_limits(config.max_connections, config.max_connections_per_ip);
+    // Como na abertura, o RUST_LOG tem prioridade sobre o nível da configuração
+    if std::env::var_os(EnvFilter::DEFAULT_ENV).is_none() {
+        if let Err(err) = log_filter.reload(EnvFilter::new(&config.log_level)) {
+            warn!(%err, "não foi possível trocar o nível do log");
+        }
+    }
+    if let Err(msg) = lobby.lock().await.apply_config(&config) {
+        report.push_str(&msg);
+    }
+    configs.send_replace(Arc::new(config));
+    Ok(report)
+}
+
+// Copia as partidas em andamento para o banco a cada intervalo, para que uma queda do
+// servidor não as perca; na volta, elas são reabertas como as salvas no encerramento
+async fn snapshot_periodically(lobby: Arc<Mutex<Lobby>>, interval: Duration) {
+    let mut ticker = time::interval(interval);
+    // O primeiro tique é imediato e não há o que copiar logo na abertura
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let saved = lobby.lock().await.snapshot_games().await;
+        debug!(saved, "cópia das partidas em andamento guardada");
+    }
+}
+
+// Escuta em uma porta opcional; sai do servidor se ela estiver configurada e não puder ser usada
+async fn bind_optional(addr: Option<String>, transport: &str) -> Option<TcpListener> {
+    let addr = addr?;
+    match TcpListener::bind(&addr).await {
+        Ok(listener) => {
+            info!(%addr, transport, "porta extra disponível");
+            health::HEALTH.listening(transport, addr);
+            Some(listener)
+        }
+        Err(err) => {
+            error!(%addr, transport, %err, "não foi possível escutar no endereço");
+            std::process::exit(1);
+        }
+    }
+}
+
+// Aceita a primeira conexão que chegar em qualquer um dos endereços
+async fn accept_any(listeners: &[TcpListener]) -> io::Result<(TcpStream, SocketAddr)> {
+    let accepts = listeners.iter().map(|listener| Box::pin(listener.accept()));
+    futures_util::future::select_all(accepts).await.0
+}
+
+// Aceita em uma porta opcional; sem ela, nunca retorna
+async fn accept_optional(listener: &Option<TcpListener>) -> io::Result<(TcpStream, SocketAddr)> {
+    match listener {
+        Some(listener) => listener.accept().await,
+        None => std::future::pending().await,
+    }
+}
+
+// Aceita na porta TLS, se houver, junto com o aceitador que fará o handshake
+async fn accept_tls(tls: &Option<(TcpListener, TlsAcceptor)>) -> (io::Result<(TcpStream, SocketAddr)>, Protocol) {
+    match tls {
+        Some((listener, acceptor)) => (listener.accept().await, Protocol::Tls(acceptor.clone())),
+        None => std::future::pending().await,
+    }
+}
+
+// Em sistemas Unix, o SIGHUP relê a configuração, como o reload do console
+#[cfg(unix)]
+async fn reload_on_hangup(reloader: Reloader) {
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            warn!(%err, "não foi possível escutar o SIGHUP");
+            return;
         }
+    };
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP recebido; relendo a configuração");
+        // O laço principal registra o resultado
+        let _ = reloader.reload().await;
+    }
+}
+
+// Espera Ctrl+C ou, em sistemas Unix, o SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
     }
 }
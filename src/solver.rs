@@ -0,0 +1,466 @@
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::book::{self, KEY_COLUMN_BITS};
+use crate::game::{BoardConfig, GameState, COLS, ROWS};
+
+// Solucionador do lig4 padrão (7x6): diz com certeza se quem joga vence, empata ou perde com
+// o melhor jogo dos dois lados, e qual jogada mantém esse resultado. É a busca clássica do
+// lig4 resolvido: negamax com janela nula sobre bitboards de colunas com 7 bits (as 6 casas e
+// um bit de folga), jogadas ordenadas pelas ameaças que criam e uma tabela de transposição
+// em que cada posição divide a entrada com o seu espelho.
+//
+// Perto do fim a resposta sai na hora, mas nas primeiras jogadas a árvore é enorme e a busca
+// leva minutos. Por isso o solucionador tem um prazo, e quem chama decide o que fazer quando
+// ele acaba.
+
+const CELLS: i32 = (COLS * ROWS) as i32;
+
+// Placares do negamax: quanto antes a vitória, maior o placar; 0 é empate
+const MIN_SCORE: i32 = -CELLS / 2 + 3;
+const MAX_SCORE: i32 = (CELLS + 1) / 2 - 3;
+
+const BOTTOM_MASK: u64 = bottom_mask();
+const BOARD_MASK: u64 = BOTTOM_MASK * ((1 << ROWS) - 1);
+
+// Centro primeiro, como no livro: as colunas do meio entram em mais sequências
+const MOVE_ORDER: [usize; COLS] = [3, 2, 4, 1, 5, 0, 6];
+
+// Entradas da tabela de transposição. O tamanho é primo para que os 32 bits guardados de
+// cada chave, junto com o resto da divisão, identifiquem a posição sem ambiguidade.
+const TABLE_SIZE: usize = 4_194_301;
+
+// Com as chaves de 49 bits, o menor primo que ainda as identifica é acima de 2^17. Tabela do
+// solucionador que chega com as grandes todas em uso: mais lenta, mas com menos de 1 MB.
+const SMALL_TABLE_SIZE: usize = 131_101;
+
+// Cada tabela grande ocupa uns 20 MB; o processo inteiro não passa destas, por mais partidas
+// que estejam sendo analisadas ao mesmo tempo
+const SHARED_TABLES: usize = 2;
+
+static TABLES: Mutex<TablePool> = Mutex::new(TablePool { free: Vec::new(), allocated: 0 });
+
+// A cada tantos nós a busca confere se o prazo acabou
+const DEADLINE_CHECK_NODES: u64 = 4096;
+
+const fn bottom_mask() -> u64 {
+    let mut mask = 0;
+    let mut col = 0;
+    while col < COLS {
+        mask |= 1 << (col * KEY_COLUMN_BITS);
+        col += 1;
+    }
+    mask
+}
+
+fn column_mask(col: usize) -> u64 {
+    ((1 << ROWS) - 1) << (col * KEY_COLUMN_BITS)
+}
+
+// Posição em bitboards: as peças de quem joga e as casas ocupadas
+#[derive(Clone, Copy)]
+struct Position {
+    current: u64,
+    mask: u64,
+    moves: i32,
+}
+
+impl Position {
+    fn from_state(game_state: &GameState) -> Self {
+        let current = book::key_bits(game_state.pieces(game_state.current_turn), ROWS);
+        let mask = book::key_bits(game_state.pieces(1) | game_state.pieces(-1), ROWS);
+        Position { current, mask, moves: game_state.piece_count() as i32 }
+    }
+
+    // A casa livre mais baixa de cada coluna que não está cheia
+    fn possible(&self) -> u64 {
+        (self.mask + BOTTOM_MASK) & BOARD_MASK
+    }
+
+    fn can_play(&self, col: usize) -> bool {
+        self.possible() & column_mask(col) != 0
+    }
+
+    fn play(&mut self, cell: u64) {
+        self.current ^= self.mask;
+        self.mask |= cell;
+        self.moves += 1;
+    }
+
+    fn play_col(&mut self, col: usize) {
+        self.play(self.possible() & column_mask(col));
+    }
+
+    fn is_winning_move(&self, col: usize) -> bool {
+        winning_cells(self.current, self.mask) & self.possible() & column_mask(col) != 0
+    }
+
+    fn can_win_next(&self) -> bool {
+        winning_cells(self.current, self.mask) & self.possible() != 0
+    }
+
+    // Jogadas que não entregam a vitória ao oponente na jogada seguinte. Vazio quando
+    // o oponente tem duas ameaças para completar ou quando o tabuleiro encheu.
+    fn non_losing_moves(&self) -> u64 {
+        let possible = self.possible();
+        let opponent_wins = winning_cells(self.current ^ self.mask, self.mask);
+        let forced = possible & opponent_wins;
+        let candidates = match forced {
+            0 => possible,
+            forced if forced & (forced - 1) != 0 => return 0,
+            forced => forced,
+        };
+        // Nem jogar embaixo da casa em que o oponente vence
+        candidates & !(opponent_wins >> 1)
+    }
+
+    // Ameaças que a jogada cria: casas vazias que completariam uma sequência
+    fn threats_after(&self, cell: u64) -> u32 {
+        winning_cells(self.current | cell, self.mask).count_ones()
+    }
+
+    // A mesma chave para a posição e para o espelho dela, que têm o mesmo valor
+    fn key(&self) -> u64 {
+        let key = self.current + self.mask;
+        let mirrored = book::mirror(self.current) + book::mirror(self.mask);
+        key.min(mirrored)
+    }
+
+    fn is_symmetric(&self) -> bool {
+        book::mirror(self.current) == self.current && book::mirror(self.mask) == self.mask
+    }
+}
+
+// Casas vazias que completariam quatro em linha para as peças dadas
+fn winning_cells(pieces: u64, mask: u64) -> u64 {
+    let height = KEY_COLUMN_BITS as u32;
+    // Vertical
+    let mut cells = (pieces << 1) & (pieces << 2) & (pieces << 3);
+    // Horizontal e as duas diagonais, pelo deslocamento de uma casa em cada direção
+    for step in [height, height - 1, height + 1] {
+        let pair = (pieces << step) & (pieces << (2 * step));
+        cells |= pair & (pieces << (3 * step));
+        cells |= pair & (pieces >> step);
+        let pair = (pieces >> step) & (pieces >> (2 * step));
+        cells |= pair & (pieces << step);
+        cells |= pair & (pieces >> (3 * step));
+    }
+    cells & (BOARD_MASK ^ mask)
+}
+
+// Tabela de transposição: os 32 bits de baixo da chave e o placar guardado
+#[derive(Default)]
+struct Table {
+    keys: Vec<u32>,
+    values: Vec<i8>,
+}
+
+impl Table {
+    fn new(size: usize) -> Self {
+        Table { keys: vec![0; size], values: vec![0; size] }
+    }
+}
+
+// Tabelas grandes livres e quantas já foram alocadas, em uso ou não
+struct TablePool {
+    free: Vec<Table>,
+    allocated: usize,
+}
+
+// Uma tabela grande livre, se houver ou se ainda couber mais uma; senão, uma pequena só
+// para este solucionador
+fn take_table() -> Table {
+    let mut pool = TABLES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(table) = pool.free.pop() {
+        return table;
+    }
+    if pool.allocated < SHARED_TABLES {
+        pool.allocated += 1;
+        drop(pool);
+        return Table::new(TABLE_SIZE);
+    }
+    Table::new(SMALL_TABLE_SIZE)
+}
+
+// Solucionador com prazo. A tabela de transposição vale para todas as perguntas feitas ao
+// mesmo solucionador, então analisar várias posições da mesma partida aproveita o trabalho.
+// Depois que o prazo acaba, todas as respostas são None.
+pub struct Solver {
+    table: Table,
+    deadline: Instant,
+    nodes: u64,
+    timed_out: bool,
+}
+
+impl Solver {
+    pub fn new(deadline: Instant) -> Self {
+        Solver {
+            table: take_table(),
+            deadline,
+            nodes: 0,
+            timed_out: false,
+        }
+    }
+
+    // Só o tabuleiro padrão sem coringas tem a solução conhecida
+    pub fn supports(game_state: &GameState) -> bool {
+        game_state.config() == BoardConfig::default() && game_state.wild() == 0
+    }
+
+    // Valor da posição para quem joga: 1 vence, 0 empata e -1 perde com o melhor jogo dos
+    // dois lados. None em outros tabuleiros ou se o prazo acabou.
+    pub fn value(&mut self, game_state: &GameState) -> Option<i32> {
+        if Instant::now() >= self.deadline {
+            self.timed_out = true;
+        }
+        if !Self::supports(game_state) || self.timed_out {
+            return None;
+        }
+        if let Some(winner) = game_state.result() {
+            return Some(winner * game_state.current_turn);
+        }
+        if let Some(book_move) = book::lookup(game_state) {
+            return Some(book_move.value);
+        }
+        let value = self.weak(&Position::from_state(game_state));
+        (!self.timed_out).then_some(value)
+    }
+
+    // Jogada que mantém o valor da posição, e o valor. Na posição perdida qualquer jogada
+    // perde, e vem a primeira pela ordem do centro para as bordas.
+    pub fn best_move(&mut self, game_state: &GameState) -> Option<(usize, i32)> {
+        let value = self.value(game_state)?;
+        if game_state.result().is_some() {
+            return None;
+        }
+        if let Some(book_move) = book::lookup(game_state) {
+            return Some((book_move.col, book_move.value));
+        }
+        let position = Position::from_state(game_state);
+        if let Some(col) = MOVE_ORDER.into_iter().find(|col| position.can_play(*col) && position.is_winning_move(*col)) {
+            return Some((col, 1));
+        }
+        // Na posição simétrica, cada coluna vale o mesmo que a do espelho
+        let symmetric = position.is_symmetric();
+        for col in MOVE_ORDER {
+            if (symmetric && col > COLS / 2) || !position.can_play(col) {
+                continue;
+            }
+            let after = self.value_after(&position, col);
+            if self.timed_out {
+                return None;
+            }
+            if after == value {
+                return Some((col, value));
+            }
+        }
+        None
+    }
+
+    // Valor, para quem joga, de jogar na coluna
+    fn value_after(&mut self, position: &Position, col: usize) -> i32 {
+        if position.is_winning_move(col) {
+            return 1;
+        }
+        let mut next = *position;
+        next.play_col(col);
+        -self.weak(&next)
+    }
+
+    // Só o sinal do placar: a janela de -1 a 1 basta e é muito mais rápida que o placar exato
+    fn weak(&mut self, position: &Position) -> i32 {
+        if position.can_win_next() {
+            return 1;
+        }
+        self.negamax(position, -1, 1).signum()
+    }
+
+    // Negamax com poda alfa-beta. Supõe que quem joga não vence de imediato.
+    fn negamax(&mut self, position: &Position, mut alpha: i32, mut beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(DEADLINE_CHECK_NODES) && Instant::now() >= self.deadline {
+            self.timed_out = true;
+        }
+        if self.timed_out {
+            return 0;
+        }
+
+        let next = position.non_losing_moves();
+        if next == 0 {
+            return -(CELLS - position.moves) / 2;
+        }
+        if position.moves >= CELLS - 2 {
+            return 0;
+        }
+
+        // O oponente não vence na próxima jogada, o que limita o pior placar
+        let min = -(CELLS - 2 - position.moves) / 2;
+        if alpha < min {
+            alpha = min;
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+        // A tabela guarda um limite inferior ou superior, conforme a faixa do valor
+        let mut max = (CELLS - 1 - position.moves) / 2;
+        let key = position.key();
+        match self.lookup(key) {
+            0 => {}
+            stored if stored > MAX_SCORE - MIN_SCORE + 1 => {
+                let min = stored + 2 * MIN_SCORE - MAX_SCORE - 2;
+                if alpha < min {
+                    alpha = min;
+                    if alpha >= beta {
+                        return alpha;
+                    }
+                }
+            }
+            stored => max = stored + MIN_SCORE - 1,
+        }
+        if beta > max {
+            beta = max;
+            if alpha >= beta {
+                return beta;
+            }
+        }
+
+        // Jogadas que criam mais ameaças primeiro; no empate, a ordem do centro para as bordas
+        let mut moves: Vec<(u64, u32)> = MOVE_ORDER
+            .iter()
+            .map(|col| next & column_mask(*col))
+            .filter(|cell| *cell != 0)
+            .map(|cell| (cell, position.threats_after(cell)))
+            .collect();
+        moves.sort_by_key(|(_, threats)| std::cmp::Reverse(*threats));
+
+        for (cell, _) in moves {
+            let mut child = *position;
+            child.play(cell);
+            let score = -self.negamax(&child, -beta, -alpha);
+            if score >= beta {
+                self.store(key, score + MAX_SCORE - 2 * MIN_SCORE + 2);
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        self.store(key, alpha - MIN_SCORE + 1);
+        alpha
+    }
+
+    fn lookup(&self, key: u64) -> i32 {
+        let index = (key % self.table.keys.len() as u64) as usize;
+        match self.table.keys[index] == key as u32 {
+            true => self.table.values[index] as i32,
+            false => 0,
+        }
+    }
+
+    fn store(&mut self, key: u64, value: i32) {
+        let index = (key % self.table.keys.len() as u64) as usize;
+        self.table.keys[index] = key as u32;
+        self.table.values[index] = value as i8;
+    }
+}
+
+// A tabela grande volta limpa para o próximo solucionador: depois de um prazo vencido ela
+// guarda placares de buscas interrompidas
+impl Drop for Solver {
+    fn drop(&mut self) {
+        if self.table.keys.len() != TABLE_SIZE {
+            return;
+        }
+        let mut table = std::mem::take(&mut self.table);
+        table.keys.fill(0);
+        table.values.fill(0);
+        TABLES.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).free.push(table);
+    }
+}
+
+// Valores das posições de uma partida, do ponto de vista do X: 1 o X vence, -1 o O vence e
+// 0 empata. As posições são resolvidas de trás para a frente, porque as do fim saem na hora
+// e preenchem a tabela para as anteriores; as que não couberem no prazo ficam None.
+pub fn game_values(positions: &[GameState], deadline: Instant) -> Vec<Option<i32>> {
+    let mut solver = Solver::new(deadline);
+    let mut values = vec![None; positions.len()];
+    for (value, position) in values.iter_mut().zip(positions).rev() {
+        match solver.value(position) {
+            Some(for_mover) => *value = Some(for_mover * position.current_turn),
+            None => break,
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Move;
+    use std::time::Duration;
+
+    fn play(cols: &[usize]) -> GameState {
+        let mut game_state = GameState::new();
+        for col in cols {
+            game_state.apply_move(&Move { col: *col }).unwrap();
+        }
+        game_state
+    }
+
+    fn solver() -> Solver {
+        Solver::new(Instant::now() + Duration::from_secs(60))
+    }
+
+    #[test]
+    fn finds_the_immediate_win() {
+        // X tem três na coluna 0 e joga
+        let game_state = play(&[0, 1, 0, 1, 0, 2]);
+        assert_eq!(solver().best_move(&game_state), Some((0, 1)));
+    }
+
+    #[test]
+    fn double_threats_lose_for_the_side_to_move() {
+        // X tem três na base, das colunas 1 a 3, com as duas pontas livres; o O não bloqueia as duas
+        let game_state = play(&[1, 1, 2, 2, 3]);
+        assert_eq!(solver().value(&game_state), Some(-1));
+    }
+
+    #[test]
+    fn mirrored_positions_share_the_value() {
+        let moves = [3, 3, 3, 3, 2, 4, 4, 2, 2, 2, 1, 5, 5];
+        let mirrored: Vec<usize> = moves.iter().map(|col| COLS - 1 - col).collect();
+        let mut solver = solver();
+        let value = solver.value(&play(&moves));
+        assert!(value.is_some());
+        assert_eq!(solver.value(&play(&mirrored)), value);
+    }
+
+    #[test]
+    fn game_values_are_seen_from_x() {
+        // O X completa a linha de baixo, das colunas 0 a 3
+        let moves = [3, 3, 3, 3, 2, 4, 4, 2, 2, 2, 1, 5, 5, 6, 0];
+        let positions: Vec<GameState> = (10..=moves.len()).map(|count| play(&moves[..count])).collect();
+        let values = game_values(&positions, Instant::now() + Duration::from_secs(60));
+        assert_eq!(values.len(), positions.len());
+        assert!(values.iter().all(Option::is_some));
+        assert_eq!(values.last(), Some(&Some(1)));
+    }
+
+    #[test]
+    fn solvers_beyond_the_shared_tables_still_answer() {
+        // Os outros testes também pegam tabelas; com uma a mais que o limite, pelo menos uma é pequena
+        let mut solvers: Vec<Solver> = (0..=SHARED_TABLES).map(|_| solver()).collect();
+        let small = solvers
+            .iter_mut()
+            .find(|solver| solver.table.keys.len() == SMALL_TABLE_SIZE)
+            .expect("todas as tabelas eram grandes");
+        assert_eq!(small.value(&play(&[1, 1, 2, 2, 3])), Some(-1));
+        assert_eq!(small.best_move(&play(&[0, 1, 0, 1, 0, 2])), Some((0, 1)));
+    }
+
+    #[test]
+    fn expired_deadlines_and_other_boards_have_no_answer() {
+        let mut expired = Solver::new(Instant::now());
+        assert_eq!(expired.value(&play(&[3, 3, 2, 4, 0, 6, 1, 5, 0])), None);
+        let board = BoardConfig::new(6, 8, 4).unwrap();
+        assert_eq!(solver().value(&GameState::with_config(board)), None);
+    }
+}
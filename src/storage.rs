@@ -0,0 +1,800 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use reversi_egui::game::BoardConfig;
+use reversi_egui::protocol::{PlayerStats, RatingEntry, SeriesScore};
+use reversi_egui::rating::{self, DEFAULT_RATING};
+use crate::fairplay::{FairPlayReport, PlayerReport};
+use crate::moderation::{Sanction, SanctionKind};
+use crate::room::{GameClock, Handicap, PlayedMove};
+
+// Arquivo padrão do banco de partidas
+pub const DEFAULT_DATABASE: &str = "partidas.db";
+
+// Partida encerrada, como fica gravada no banco
+#[derive(Serialize, Debug, Clone)]
+pub struct GameRecord {
+    pub id: i64,
+    pub mode: String,
+    pub board_config: BoardConfig,
+    pub player_x: String,
+    pub player_o: String,
+    pub winner: i32, // 1, -1 ou 0 para empate
+    pub forfeit: bool, // Decidida por desistência ou W.O., e não no tabuleiro
+    pub moves: Vec<PlayedMove>,
+    pub started_at: u64, // Segundos desde a época Unix
+    pub duration_secs: u64,
+    pub series: Option<SeriesScore>, // Placar final de uma série; as jogadas são as do último jogo
+    pub seed: Option<u32>, // Semente do sorteio de quem começou a sala; ausente nas partidas antigas
+}
+
+// Partida interrompida pelo encerramento do servidor ou pausada pelos jogadores, com as
+// jogadas feitas até então
+#[derive(Debug, Clone)]
+pub struct SavedGame {
+    pub room_name: String,
+    pub mode: String,
+    pub board_config: BoardConfig,
+    pub player_x: String,
+    pub player_o: String,
+    pub first_turn: i32,
+    pub seed: u32, // Semente do sorteio de quem começou, para o histórico
+    pub moves: Vec<PlayedMove>,
+    pub pop_out: bool, // A sala joga a variante Pop Out
+    pub power_up: bool, // A sala joga a variante Power Up
+    pub clock: Option<GameClock>, // Relógio de xadrez no momento em que a partida foi guardada
+    pub rated: bool, // Partida ranqueada, que muda o ELO ao terminar
+    pub handicap: Option<Handicap>, // Vantagem combinada na criação da sala
+    pub saved_at: u64, // Segundos desde a época Unix
+}
+
+// Código de reconexão de um jogador, guardado para valer também depois de um reinício
+#[derive(Debug, Clone)]
+pub struct SavedSession {
+    pub token: String,
+    pub room_name: String,
+    pub nick: String,
+}
+
+// Tentativas de quebra-cabeças de um jogador, para o /puzzle stats
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzleStats {
+    pub attempts: usize,
+    pub solved: usize,
+    pub distinct_solved: usize, // Quebra-cabeças diferentes resolvidos pelo menos uma vez
+}
+
+// Tentativas de um jogador no desafio de um dia
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DailyStatus {
+    pub attempts: usize,
+    pub solved_in_ms: Option<u64>, // Duração da tentativa que resolveu, se já resolveu
+}
+
+// Linha do /daily top: quem resolveu, com quantas tentativas e em quanto tempo
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyEntry {
+    pub nick: String,
+    pub attempts: usize,
+    pub duration_ms: u64,
+}
+
+// Torneio automático, para o /tournament history
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentRecord {
+    pub id: i64,
+    pub mode: String,
+    pub players: usize,
+    pub started_at: u64,
+    pub champion: Option<String>, // Sem campeão enquanto o torneio não acaba
+}
+
+// Conta de um apelido registrado; sem senha, qualquer um pode usar o apelido
+#[derive(Debug, Clone)]
+pub struct Account {
+    pub nick: String,
+    pub password_hash: Option<String>,
+}
+
+// Histórico de partidas em SQLite, para que os resultados sobrevivam a reinícios do servidor
+pub struct Storage {
+    conn: Mutex<Connection>,
+    export_dir: Option<PathBuf>, // Onde as partidas encerradas também são gravadas como texto
+}
+
+impl Storage {
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS games (
+                id            INTEGER PRIMARY KEY AUTOINCREMENT,
+                mode          TEXT NOT NULL,
+                rows          INTEGER NOT NULL,
+                cols          INTEGER NOT NULL,
+                win_length    INTEGER NOT NULL,
+                player_x      TEXT NOT NULL,
+                player_o      TEXT NOT NULL,
+                winner        INTEGER NOT NULL,
+                moves         TEXT NOT NULL,
+                started_at    INTEGER NOT NULL,
+                duration_secs INTEGER NOT NULL,
+                forfeit       INTEGER NOT NULL DEFAULT 0,
+                best_of       INTEGER,
+                wins_x        INTEGER,
+                wins_o        INTEGER,
+                seed          INTEGER
+            );
+            CREATE TABLE IF NOT EXISTS accounts (
+                nick          TEXT PRIMARY KEY,
+                password_hash TEXT
+            );
+            CREATE TABLE IF NOT EXISTS saved_games (
+                room_name     TEXT PRIMARY KEY,
+                mode          TEXT NOT NULL,
+                rows          INTEGER NOT NULL,
+                cols          INTEGER NOT NULL,
+                win_length    INTEGER NOT NULL,
+                player_x      TEXT NOT NULL,
+                player_o      TEXT NOT NULL,
+                first_turn    INTEGER NOT NULL,
+                moves         TEXT NOT NULL,
+                saved_at      INTEGER NOT NULL,
+                pop_out       INTEGER NOT NULL DEFAULT 0,
+                power_up      INTEGER NOT NULL DEFAULT 0,
+                clock         TEXT,
+                rated         INTEGER NOT NULL DEFAULT 0,
+                seed          INTEGER NOT NULL DEFAULT 0,
+                handicap      TEXT
+            );
+            CREATE TABLE IF NOT EXISTS sessions (
+                token     TEXT PRIMARY KEY,
+                room_name TEXT NOT NULL,
+                nick      TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS ratings (
+                nick   TEXT PRIMARY KEY REFERENCES accounts(nick),
+                rating INTEGER NOT NULL,
+                games  INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS friends (
+                nick   TEXT NOT NULL REFERENCES accounts(nick),
+                friend TEXT NOT NULL REFERENCES accounts(nick),
+                PRIMARY KEY (nick, friend)
+            );
+            CREATE TABLE IF NOT EXISTS puzzle_attempts (
+                nick      TEXT NOT NULL,
+                puzzle    INTEGER NOT NULL,
+                solved    INTEGER NOT NULL,
+                played_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS daily_attempts (
+                nick        TEXT NOT NULL,
+                day         INTEGER NOT NULL,
+                solved      INTEGER NOT NULL,
+                duration_ms INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS sanctions (
+                kind       TEXT NOT NULL,
+                target     TEXT NOT NULL,
+                expires_at INTEGER,
+                PRIMARY KEY (kind, target)
+            );
+            CREATE TABLE IF NOT EXISTS tournaments (
+                id         INTEGER PRIMARY KEY AUTOINCREMENT,
+                mode       TEXT NOT NULL,
+                players    TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                champion   TEXT
+            );
+            CREATE TABLE IF NOT EXISTS tournament_games (
+                tournament INTEGER NOT NULL REFERENCES tournaments(id),
+                round      INTEGER NOT NULL,
+                player_x   TEXT NOT NULL,
+                player_o   TEXT NOT NULL,
+                winner     TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS fair_play_reports (
+                game       INTEGER PRIMARY KEY REFERENCES games(id),
+                players    TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );",
+
+        )?;
+
+        // Bancos criados antes das colunas novas: as partidas antigas contam como decididas
+        // no tabuleiro e fora de séries
+        add_missing_column(&conn, "games", "forfeit", "INTEGER NOT NULL DEFAULT 0")?;
+        for column in ["best_of", "wins_x", "wins_o", "seed"] {
+            add_missing_column(&conn, "games", column, "INTEGER")?;
+        }
+        for column in ["pop_out", "power_up", "rated", "seed"] {
+            add_missing_column(&conn, "saved_games", column, "INTEGER NOT NULL DEFAULT 0")?;
+        }
+        for column in ["clock", "handicap"] {
+            add_missing_column(&conn, "saved_games", column, "TEXT")?;
+        }
+
+        Ok(Storage { conn: Mutex::new(conn), export_dir: None })
+    }
+
+    pub fn with_export_dir(mut self, export_dir: Option<PathBuf>) -> Self {
+        self.export_dir = export_dir;
+        self
+    }
+
+    // Consulta simples, para o /readyz saber se o banco ainda responde
+    pub fn ping(&self) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.prepare("SELECT 1 FROM games LIMIT 1")?.exists([])?;
+        Ok(())
+    }
+
+    pub fn export_dir(&self) -> Option<&Path> {
+        self.export_dir.as_deref()
+    }
+
+    // Grava uma partida encerrada e retorna o número dela
+    pub fn record_game(&self, record: &GameRecord) -> rusqlite::Result<i64> {
+        let moves = serde_json::to_string(&record.moves).unwrap_or_else(|_| "[]".to_string());
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO games
+                 (mode, rows, cols, win_length, player_x, player_o, winner, moves, started_at, duration_secs, forfeit, best_of, wins_x, wins_o, seed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            params![
+                record.mode,
+                record.board_config.rows as i64,
+                record.board_config.cols as i64,
+                record.board_config.win_length as i64,
+                record.player_x,
+                record.player_o,
+                record.winner,
+                moves,
+                record.started_at as i64,
+                record.duration_secs as i64,
+                record.forfeit,
+                record.series.map(|series| series.best_of),
+                record.series.map(|series| series.wins_x),
+                record.series.map(|series| series.wins_o),
+                record.seed,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    // Guarda uma partida em andamento; uma sala com o mesmo nome substitui a anterior
+    pub fn save_in_progress(&self, game: &SavedGame) -> rusqlite::Result<()> {
+        let moves = serde_json::to_string(&game.moves).unwrap_or_else(|_| "[]".to_string());
+        let clock = game.clock.and_then(|clock| serde_json::to_string(&clock).ok());
+        let handicap = game.handicap.and_then(|handicap| serde_json::to_string(&handicap).ok());
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO saved_games
+                 (room_name, mode, rows, cols, win_length, player_x, player_o, first_turn, moves, saved_at, pop_out, power_up, clock, rated, seed, handicap)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                game.room_name,
+                game.mode,
+                game.board_config.rows as i64,
+                game.board_config.cols as i64,
+                game.board_config.win_length as i64,
+                game.player_x,
+                game.player_o,
+                game.first_turn,
+                moves,
+                game.saved_at as i64,
+                game.pop_out,
+                game.power_up,
+                clock,
+                game.rated,
+                game.seed,
+                handicap,
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Partidas guardadas, para reabrir as salas quando o servidor volta
+    pub fn saved_games(&self) -> rusqlite::Result<Vec<SavedGame>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT room_name, mode, rows, cols, win_length, player_x, player_o, first_turn, moves, saved_at, pop_out, power_up, clock, rated, seed, handicap
+             FROM saved_games ORDER BY room_name",
+        )?;
+        let games = statement.query_map([], |row| {
+            let moves: String = row.get(8)?;
+            let clock: Option<String> = row.get(12)?;
+            let handicap: Option<String> = row.get(15)?;
+            Ok(SavedGame {
+                room_name: row.get(0)?,
+                mode: row.get(1)?,
+                board_config: BoardConfig {
+                    rows: row.get::<_, i64>(2)? as usize,
+                    cols: row.get::<_, i64>(3)? as usize,
+                    win_length: row.get::<_, i64>(4)? as usize,
+                },
+                player_x: row.get(5)?,
+                player_o: row.get(6)?,
+                first_turn: row.get(7)?,
+                seed: row.get(14)?,
+                moves: serde_json::from_str(&moves).unwrap_or_default(),
+                saved_at: row.get::<_, i64>(9)? as u64,
+                pop_out: row.get(10)?,
+                power_up: row.get(11)?,
+                clock: clock.and_then(|clock| serde_json::from_str(&clock).ok()),
+                rated: row.get(13)?,
+                handicap: handicap.and_then(|handicap| serde_json::from_str(&handicap).ok()),
+            })
+        })?;
+        games.collect()
+    }
+
+    // Esquece a partida guardada da sala, que voltou a ser jogada
+    pub fn discard_saved_game(&self, room_name: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM saved_games WHERE room_name = ?1", params![room_name])?;
+        Ok(())
+    }
+
+    pub fn save_session(&self, session: &SavedSession) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO sessions (token, room_name, nick) VALUES (?1, ?2, ?3)",
+            params![session.token, session.room_name, session.nick],
+        )?;
+        Ok(())
+    }
+
+    pub fn sessions(&self) -> rusqlite::Result<Vec<SavedSession>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT token, room_name, nick FROM sessions")?;
+        let sessions = statement.query_map([], |row| {
+            Ok(SavedSession {
+                token: row.get(0)?,
+                room_name: row.get(1)?,
+                nick: row.get(2)?,
+            })
+        })?;
+        sessions.collect()
+    }
+
+    // Apaga o que estava guardado de uma sala fechada: a partida e os códigos de reconexão
+    pub fn discard_room(&self, room_name: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM saved_games WHERE room_name = ?1", params![room_name])?;
+        conn.execute("DELETE FROM sessions WHERE room_name = ?1", params![room_name])?;
+        Ok(())
+    }
+
+    // Partidas mais recentes em que o jogador participou
+    pub fn recent_games(&self, player: &str, limit: usize) -> rusqlite::Result<Vec<GameRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT id, mode, rows, cols, win_length, player_x, player_o, winner, moves, started_at, duration_secs, forfeit, best_of, wins_x, wins_o, seed
+             FROM games WHERE player_x = ?1 OR player_o = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let records = statement.query_map(params![player, limit as i64], read_record)?;
+        records.collect()
+    }
+
+    pub fn find_game(&self, id: i64) -> rusqlite::Result<Option<GameRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT id, mode, rows, cols, win_length, player_x, player_o, winner, moves, started_at, duration_secs, forfeit, best_of, wins_x, wins_o, seed
+             FROM games WHERE id = ?1",
+        )?;
+        let mut records = statement.query_map(params![id], read_record)?;
+        records.next().transpose()
+    }
+
+    pub fn find_account(&self, nick: &str) -> rusqlite::Result<Option<Account>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT nick, password_hash FROM accounts WHERE nick = ?1",
+            params![nick],
+            |row| {
+                Ok(Account {
+                    nick: row.get(0)?,
+                    password_hash: row.get(1)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    // Cria a conta ou atualiza a senha de uma conta existente
+    pub fn save_account(&self, account: &Account) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO accounts (nick, password_hash) VALUES (?1, ?2)
+             ON CONFLICT(nick) DO UPDATE SET password_hash = excluded.password_hash",
+            params![account.nick, account.password_hash],
+        )?;
+        Ok(())
+    }
+
+    // Atualiza o ELO dos dois jogadores. Partidas contra quem não tem conta, como o
+    // computador, não contam. `handicap_x` são os pontos da vantagem que o X deu ao O,
+    // negativos se foi o O quem deu. Retorna os ratings novos de X e O.
+    pub fn update_ratings(&self, player_x: &str, player_o: &str, winner: i32, handicap_x: i32) -> rusqlite::Result<Option<(i32, i32)>> {
+        let mut conn = self.conn.lock().unwrap();
+        let transaction = conn.transaction()?;
+
+        let registered = |nick: &str| -> rusqlite::Result<bool> {
+            transaction
+                .query_row("SELECT 1 FROM accounts WHERE nick = ?1", params![nick], |_| Ok(()))
+                .optional()
+                .map(|found| found.is_some())
+        };
+        if !registered(player_x)? || !registered(player_o)? {
+            return Ok(None);
+        }
+
+        let current = |nick: &str| -> rusqlite::Result<i32> {
+            transaction
+                .query_row("SELECT rating FROM ratings WHERE nick = ?1", params![nick], |row| row.get(0))
+                .optional()
+                .map(|rating| rating.unwrap_or(DEFAULT_RATING))
+        };
+        let (rating_x, rating_o) = rating::updated_ratings_with_handicap(current(player_x)?, current(player_o)?, winner, handicap_x);
+
+        for (nick, rating) in [(player_x, rating_x), (player_o, rating_o)] {
+            transaction.execute(
+                "INSERT INTO ratings (nick, rating, games) VALUES (?1, ?2, 1)
+                 ON CONFLICT(nick) DO UPDATE SET rating = excluded.rating, games = games + 1",
+                params![nick, rating],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(Some((rating_x, rating_o)))
+    }
+
+    // ELO de uma partida em duplas: cada dupla joga com a média dos ratings dos parceiros,
+    // e os dois parceiros ganham ou perdem os mesmos pontos. Como em update_ratings, todos
+    // precisam ter conta. Retorna os ratings novos, na ordem dos apelidos recebidos.
+    pub fn update_team_ratings(&self, team_x: &[String], team_o: &[String], winner: i32) -> rusqlite::Result<Option<Vec<(String, i32)>>> {
+        let mut conn = self.conn.lock().unwrap();
+        let transaction = conn.transaction()?;
+
+        let mut current = Vec::new();
+        for nick in team_x.iter().chain(team_o) {
+            let registered = transaction
+                .query_row("SELECT 1 FROM accounts WHERE nick = ?1", params![nick], |_| Ok(()))
+                .optional()?
+                .is_some();
+            if !registered {
+                return Ok(None);
+            }
+            let rating = transaction
+                .query_row("SELECT rating FROM ratings WHERE nick = ?1", params![nick], |row| row.get(0))
+                .optional()?
+                .unwrap_or(DEFAULT_RATING);
+            current.push((nick.clone(), rating));
+        }
+        let average = |ratings: &[(String, i32)]| ratings.iter().map(|(_, rating)| rating).sum::<i32>() / ratings.len().max(1) as i32;
+        let (ratings_x, ratings_o) = current.split_at(team_x.len());
+        let (average_x, average_o) = (average(ratings_x), average(ratings_o));
+        let (new_x, _) = rating::updated_ratings(average_x, average_o, winner);
+        let change = new_x - average_x;
+
+        let mut updated = Vec::new();
+        for (index, (nick, rating)) in current.into_iter().enumerate() {
+            let rating = if index < team_x.len() { rating + change } else { rating - change };
+            transaction.execute(
+                "INSERT INTO ratings (nick, rating, games) VALUES (?1, ?2, 1)
+                 ON CONFLICT(nick) DO UPDATE SET rating = excluded.rating, games = games + 1",
+                params![nick, rating],
+            )?;
+            updated.push((nick, rating));
+        }
+        transaction.commit()?;
+        Ok(Some(updated))
+    }
+
+    pub fn rating(&self, nick: &str) -> rusqlite::Result<i32> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT rating FROM ratings WHERE nick = ?1", params![nick], |row| row.get(0))
+            .optional()
+            .map(|rating| rating.unwrap_or(DEFAULT_RATING))
+    }
+
+    // Adiciona um amigo à lista do jogador. Retorna false se ele já estava lá.
+    pub fn add_friend(&self, nick: &str, friend: &str) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let added = conn.execute("INSERT OR IGNORE INTO friends (nick, friend) VALUES (?1, ?2)", params![nick, friend])?;
+        Ok(added > 0)
+    }
+
+    // Amigos do jogador, em ordem alfabética
+    pub fn friends(&self, nick: &str) -> rusqlite::Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT friend FROM friends WHERE nick = ?1 ORDER BY friend")?;
+        let friends = statement.query_map(params![nick], |row| row.get(0))?;
+        friends.collect()
+    }
+
+    // Vitórias, derrotas e empates do jogador no histórico; None se o apelido não tem conta
+    pub fn player_stats(&self, nick: &str) -> rusqlite::Result<Option<PlayerStats>> {
+        if self.find_account(nick)?.is_none() {
+            return Ok(None);
+        }
+        let rating = self.rating(nick)?;
+        let conn = self.conn.lock().unwrap();
+        let (games, wins, draws): (i64, i64, i64) = conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM((player_x = ?1 AND winner = 1) OR (player_o = ?1 AND winner = -1)), 0),
+                    COALESCE(SUM(winner = 0), 0)
+             FROM games WHERE player_x = ?1 OR player_o = ?1",
+            params![nick],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok(Some(PlayerStats {
+            nick: nick.to_string(),
+            rating,
+            games: games as usize,
+            wins: wins as usize,
+            losses: (games - wins - draws) as usize,
+            draws: draws as usize,
+        }))
+    }
+
+    // Guarda uma tentativa de quebra-cabeça; puzzle é o número mostrado ao jogador
+    pub fn record_puzzle(&self, nick: &str, puzzle: usize, solved: bool, played_at: u64) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO puzzle_attempts (nick, puzzle, solved, played_at) VALUES (?1, ?2, ?3, ?4)",
+            params![nick, puzzle as i64, solved, played_at as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn puzzle_stats(&self, nick: &str) -> rusqlite::Result<PuzzleStats> {
+        let conn = self.conn.lock().unwrap();
+        let (attempts, solved, distinct_solved): (i64, i64, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(solved), 0), COUNT(DISTINCT CASE WHEN solved THEN puzzle END)
+             FROM puzzle_attempts WHERE nick = ?1",
+            params![nick],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok(PuzzleStats {
+            attempts: attempts as usize,
+            solved: solved as usize,
+            distinct_solved: distinct_solved as usize,
+        })
+    }
+
+    // Guarda uma tentativa do desafio diário; day conta os dias desde a época Unix
+    pub fn record_daily(&self, nick: &str, day: u64, solved: bool, duration_ms: u64) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO daily_attempts (nick, day, solved, duration_ms) VALUES (?1, ?2, ?3, ?4)",
+            params![nick, day as i64, solved, duration_ms as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn daily_status(&self, nick: &str, day: u64) -> rusqlite::Result<DailyStatus> {
+        let conn = self.conn.lock().unwrap();
+        let (attempts, solved_in_ms): (i64, Option<i64>) = conn.query_row(
+            "SELECT COUNT(*), MIN(CASE WHEN solved THEN duration_ms END)
+             FROM daily_attempts WHERE nick = ?1 AND day = ?2",
+            params![nick, day as i64],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(DailyStatus {
+            attempts: attempts as usize,
+            solved_in_ms: solved_in_ms.map(|ms| ms as u64),
+        })
+    }
+
+    // Quem resolveu o desafio do dia: menos tentativas primeiro e, no empate, o mais rápido
+    pub fn daily_leaderboard(&self, day: u64, limit: usize) -> rusqlite::Result<Vec<DailyEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT nick, COUNT(*), MIN(CASE WHEN solved THEN duration_ms END) AS best
+             FROM daily_attempts WHERE day = ?1
+             GROUP BY nick HAVING best IS NOT NULL
+             ORDER BY COUNT(*), best, nick LIMIT ?2",
+        )?;
+        let entries = statement.query_map(params![day as i64, limit as i64], |row| {
+            Ok(DailyEntry {
+                nick: row.get(0)?,
+                attempts: row.get::<_, i64>(1)? as usize,
+                duration_ms: row.get::<_, i64>(2)? as u64,
+            })
+        })?;
+        entries.collect()
+    }
+
+    // Melhores ratings, do maior para o menor
+    pub fn leaderboard(&self, limit: usize) -> rusqlite::Result<Vec<RatingEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT nick, rating, games FROM ratings ORDER BY rating DESC, nick LIMIT ?1")?;
+        let entries = statement.query_map(params![limit as i64], |row| {
+            Ok(RatingEntry {
+                nick: row.get(0)?,
+                rating: row.get(1)?,
+                games: row.get::<_, i64>(2)? as usize,
+            })
+        })?;
+        entries.collect()
+    }
+
+    // Grava o início de um torneio, com os inscritos na ordem do chaveamento, e retorna o número dele
+    pub fn start_tournament(&self, mode: &str, players: &[String], started_at: u64) -> rusqlite::Result<i64> {
+        let players = serde_json::to_string(players).unwrap_or_else(|_| "[]".to_string());
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO tournaments (mode, players, started_at) VALUES (?1, ?2, ?3)",
+            params![mode, players, started_at as i64],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    // Resultado de uma partida do chaveamento; W.O. e empates já chegam com quem avançou
+    pub fn record_tournament_game(&self, tournament: i64, round: usize, player_x: &str, player_o: &str, winner: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO tournament_games (tournament, round, player_x, player_o, winner) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![tournament, round as i64, player_x, player_o, winner],
+        )?;
+        Ok(())
+    }
+
+    pub fn finish_tournament(&self, tournament: i64, champion: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE tournaments SET champion = ?1 WHERE id = ?2", params![champion, tournament])?;
+        Ok(())
+    }
+
+    // Torneios mais recentes, do último para o primeiro
+    pub fn recent_tournaments(&self, limit: usize) -> rusqlite::Result<Vec<TournamentRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT id, mode, players, started_at, champion FROM tournaments ORDER BY id DESC LIMIT ?1")?;
+        let records = statement.query_map(params![limit as i64], |row| {
+            let players: String = row.get(2)?;
+            Ok(TournamentRecord {
+                id: row.get(0)?,
+                mode: row.get(1)?,
+                players: serde_json::from_str::<Vec<String>>(&players).map_or(0, |players| players.len()),
+                started_at: row.get::<_, i64>(3)? as u64,
+                champion: row.get(4)?,
+            })
+        })?;
+        records.collect()
+    }
+
+    // Relatório de jogo limpo de uma partida ranqueada, com os dois jogadores
+    pub fn save_fair_play_report(&self, game: i64, players: &[PlayerReport], created_at: u64) -> rusqlite::Result<()> {
+        let players = serde_json::to_string(players).unwrap_or_else(|_| "[]".to_string());
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO fair_play_reports (game, players, created_at) VALUES (?1, ?2, ?3)",
+            params![game, players, created_at as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn fair_play_report(&self, game: i64) -> rusqlite::Result<Option<FairPlayReport>> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT r.game, g.mode, r.created_at, r.players FROM fair_play_reports r JOIN games g ON g.id = r.game WHERE r.game = ?1",
+            params![game],
+            fair_play_from_row,
+        )
+        .optional()
+    }
+
+    // Relatórios mais recentes, do último para o primeiro
+    pub fn recent_fair_play_reports(&self, limit: usize) -> rusqlite::Result<Vec<FairPlayReport>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT r.game, g.mode, r.created_at, r.players FROM fair_play_reports r JOIN games g ON g.id = r.game ORDER BY r.game DESC LIMIT ?1",
+        )?;
+        let reports = statement.query_map(params![limit as i64], fair_play_from_row)?;
+        reports.collect()
+    }
+
+    // Banimento ou silêncio novo; substitui o anterior do mesmo tipo para o mesmo alvo
+    pub fn save_sanction(&self, sanction: &Sanction) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO sanctions (kind, target, expires_at) VALUES (?1, ?2, ?3)",
+            params![sanction.kind.name(), sanction.target, sanction.expires_at.map(|expires_at| expires_at as i64)],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_sanction(&self, kind: SanctionKind, target: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM sanctions WHERE kind = ?1 AND target = ?2", params![kind.name(), target])?;
+        Ok(())
+    }
+
+    // Punições guardadas, inclusive as vencidas, que quem carrega descarta
+    pub fn sanctions(&self) -> rusqlite::Result<Vec<Sanction>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT kind, target, expires_at FROM sanctions ORDER BY rowid")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<i64>>(2)?))
+        })?;
+        let mut sanctions = Vec::new();
+        for row in rows {
+            let (kind, target, expires_at) = row?;
+            // Tipos desconhecidos vêm de versões mais novas do servidor e ficam de fora
+            if let Some(kind) = SanctionKind::parse(&kind) {
+                sanctions.push(Sanction { kind, target, expires_at: expires_at.map(|expires_at| expires_at as u64) });
+            }
+        }
+        Ok(sanctions)
+    }
+}
+
+// Hash da senha; o apelido entra no cálculo para que senhas iguais gerem hashes diferentes
+pub fn hash_password(nick: &str, password: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", nick, password).as_bytes());
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Confere uma senha, ou o hash de uma, em tempo constante: os resumos têm sempre o mesmo
+// tamanho e a comparação passa por todos os bytes, acerte ou erre
+pub fn password_matches(expected: &str, given: &str) -> bool {
+    let (expected, given) = (Sha256::digest(expected.as_bytes()), Sha256::digest(given.as_bytes()));
+    expected.iter().zip(given.iter()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// Acrescenta a coluna à tabela se o banco foi criado antes dela
+fn add_missing_column(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
+
+fn read_record(row: &rusqlite::Row) -> rusqlite::Result<GameRecord> {
+    let moves: String = row.get(8)?;
+    let best_of: Option<u32> = row.get(12)?;
+    Ok(GameRecord {
+        id: row.get(0)?,
+        mode: row.get(1)?,
+        board_config: BoardConfig {
+            rows: row.get::<_, i64>(2)? as usize,
+            cols: row.get::<_, i64>(3)? as usize,
+            win_length: row.get::<_, i64>(4)? as usize,
+        },
+        player_x: row.get(5)?,
+        player_o: row.get(6)?,
+        winner: row.get(7)?,
+        moves: serde_json::from_str(&moves).unwrap_or_default(),
+        started_at: row.get::<_, i64>(9)? as u64,
+        duration_secs: row.get::<_, i64>(10)? as u64,
+        forfeit: row.get(11)?,
+        series: match best_of {
+            Some(best_of) => Some(SeriesScore {
+                best_of,
+                wins_x: row.get(13)?,
+                wins_o: row.get(14)?,
+            }),
+            None => None,
+        },
+        seed: row.get(15)?,
+    })
+}
+
+fn fair_play_from_row(row: &rusqlite::Row) -> rusqlite::Result<FairPlayReport> {
+    let players: String = row.get(3)?;
+    Ok(FairPlayReport {
+        game_id: row.get(0)?,
+        mode: row.get(1)?,
+        created_at: row.get::<_, i64>(2)? as u64,
+        players: serde_json::from_str(&players).unwrap_or_default(),
+    })
+}
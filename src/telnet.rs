@@ -0,0 +1,372 @@
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tracing::debug;
+
+use crate::transport::{Client, EchoControl, Terminal, WindowSize};
+
+// Bytes de comando do telnet (RFC 854) e opções usadas pelo servidor
+pub const IAC: u8 = 255;
+pub const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+pub const WILL: u8 = 251;
+pub const SB: u8 = 250;
+pub const SE: u8 = 240;
+
+const OPT_ECHO: u8 = 1;
+const OPT_SGA: u8 = 3; // Suppress Go Ahead: com ECHO, liga o modo caractere a caractere
+const OPT_NAWS: u8 = 31; // Negotiate About Window Size
+
+const BACKSPACE: u8 = 8;
+const DELETE: u8 = 127;
+
+// Capacidade do canal interno entre a ponte telnet e a conexão
+const BRIDGE_BUFFER: usize = 64 * 1024;
+
+// Acima disso a linha em edição é repassada mesmo sem \n, e a camada de entrada
+// da conexão a descarta por ser longa demais
+const MAX_PENDING_LINE: usize = BRIDGE_BUFFER;
+
+// Só o NAWS é interpretado, com 5 bytes; o resto de subnegociações maiores é ignorado
+const MAX_SUBNEGOTIATION: usize = 64;
+
+// Estado do interpretador de sequências IAC
+enum State {
+    Data,
+    Iac,
+    Negotiation(u8), // Depois de IAC WILL/WONT/DO/DONT, esperando a opção
+    Subnegotiation,
+    SubnegotiationIac,
+    // Depois de um CR: o LF ou NUL que o acompanha é descartado
+    CarriageReturn,
+}
+
+// Interpreta o que o cliente telnet envia. O servidor faz o eco e a edição da linha
+// (backspace), e só entrega linhas completas para o lobby e as salas.
+struct TelnetParser {
+    state: State,
+    subnegotiation: Vec<u8>,
+    line: Vec<u8>,
+    echo: bool,   // false se o cliente recusar que o servidor faça o eco
+    masked: bool, // Entrada secreta, como uma senha: o eco mostra asteriscos
+    echo_borrowed: bool, // O servidor pediu o eco só durante a entrada secreta e o devolve depois
+}
+
+// O que resulta de um bloco de bytes recebidos
+#[derive(Default)]
+struct Parsed {
+    lines: Vec<u8>, // Linhas completas, terminadas em \n, para o protocolo do jogo
+    reply: Vec<u8>, // Eco e respostas de negociação, para o cliente
+    window: Option<WindowSize>,
+}
+
+impl TelnetParser {
+    fn new() -> Self {
+        TelnetParser {
+            state: State::Data,
+            subnegotiation: Vec::new(),
+            line: Vec::new(),
+            echo: true,
+            masked: false,
+            echo_borrowed: false,
+        }
+    }
+
+    // Liga ou desliga a entrada secreta e devolve a negociação a enviar ao cliente. Se o
+    // cliente faz o próprio eco, o servidor volta a pedir o eco (IAC WILL ECHO), para que a
+    // senha não apareça, e o devolve no fim (IAC WONT ECHO).
+    fn set_masked(&mut self, masked: bool) -> Vec<u8> {
+        self.masked = masked;
+        if masked && !self.echo {
+            self.echo_borrowed = true;
+            return vec![IAC, WILL, OPT_ECHO];
+        }
+        if !masked && self.echo_borrowed {
+            self.echo_borrowed = false;
+            self.echo = false;
+            return vec![IAC, WONT, OPT_ECHO];
+        }
+        Vec::new()
+    }
+
+    fn feed(&mut self, bytes: &[u8]) -> Parsed {
+        let mut parsed = Parsed::default();
+        for &byte in bytes {
+            self.state = match std::mem::replace(&mut self.state, State::Data) {
+                State::Data => self.data(byte, &mut parsed),
+                State::CarriageReturn if byte == b'\n' || byte == 0 => State::Data,
+                State::CarriageReturn => self.data(byte, &mut parsed),
+                State::Iac => match byte {
+                    IAC => {
+                        self.push_char(IAC, &mut parsed);
+                        State::Data
+                    }
+                    WILL | WONT | DO | DONT => State::Negotiation(byte),
+                    SB => {
+                        self.subnegotiation.clear();
+                        State::Subnegotiation
+                    }
+                    // NOP, Go Ahead, Are You There e outros comandos são ignorados
+                    _ => State::Data,
+                },
+                State::Negotiation(command) => {
+                    self.negotiate(command, byte, &mut parsed);
+                    State::Data
+                }
+                State::Subnegotiation => match byte {
+                    IAC => State::SubnegotiationIac,
+                    _ => {
+                        if self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+                            self.subnegotiation.push(byte);
+                        }
+                        State::Subnegotiation
+                    }
+                },
+                State::SubnegotiationIac => match byte {
+                    SE => {
+                        self.finish_subnegotiation(&mut parsed);
+                        State::Data
+                    }
+                    _ => {
+                        // IAC IAC dentro da subnegociação é um 255 literal
+                        self.subnegotiation.push(byte);
+                        State::Subnegotiation
+                    }
+                },
+            };
+        }
+        parsed
+    }
+
+    fn data(&mut self, byte: u8, parsed: &mut Parsed) -> State {
+        match byte {
+            IAC => return State::Iac,
+            b'\r' | b'\n' => {
+                self.line.push(b'\n');
+                parsed.lines.append(&mut self.line);
+                if self.echo {
+                    parsed.reply.extend_from_slice(b"\r\n");
+                }
+                if byte == b'\r' {
+                    return State::CarriageReturn;
+                }
+            }
+            BACKSPACE | DELETE => {
+                // Apaga o último caractere inteiro, mesmo que tenha vários bytes em UTF-8
+                let mut removed = false;
+                while let Some(last) = self.line.pop() {
+                    removed = true;
+                    if last & 0xC0 != 0x80 {
+                        break;
+                    }
+                }
+                if removed && self.echo {
+                    parsed.reply.extend_from_slice(b"\x08 \x08");
+                }
+            }
+            0 => {}
+            _ => self.push_char(byte, parsed),
+        }
+        State::Data
+    }
+
+    fn push_char(&mut self, byte: u8, parsed: &mut Parsed) {
+        // Outros caracteres de controle, como setas e Ctrl+letra, não entram na linha
+        if byte < 0x20 || byte == 0x1b {
+            return;
+        }
+        self.line.push(byte);
+        if self.line.len() >= MAX_PENDING_LINE {
+            parsed.lines.append(&mut self.line);
+        }
+        if self.echo && self.masked {
+            // Um asterisco por caractere, não por byte do UTF-8
+            if byte & 0xC0 != 0x80 {
+                parsed.reply.push(b'*');
+            }
+        } else if self.echo {
+            parsed.reply.push(byte);
+            if byte == IAC {
+                parsed.reply.push(IAC);
+            }
+        }
+    }
+
+    fn negotiate(&mut self, command: u8, option: u8, parsed: &mut Parsed) {
+        match (command, option) {
+            // Respostas ao que o servidor ofereceu ou pediu na abertura ou para a entrada secreta
+            (DO, OPT_ECHO) => self.echo = true,
+            (DO, OPT_SGA) | (WILL, OPT_SGA) | (WILL, OPT_NAWS) | (WONT, OPT_NAWS) => {}
+            (DONT, OPT_ECHO) => self.echo = false,
+            // O cliente quer fazer o próprio eco; o servidor insiste em fazer, para o modo caractere
+            (WILL, OPT_ECHO) => parsed.reply.extend_from_slice(&[IAC, DONT, OPT_ECHO]),
+            (WILL, _) => parsed.reply.extend_from_slice(&[IAC, DONT, option]),
+            (DO, _) => parsed.reply.extend_from_slice(&[IAC, WONT, option]),
+            _ => {}
+        }
+    }
+
+    fn finish_subnegotiation(&mut self, parsed: &mut Parsed) {
+        if let [OPT_NAWS, w1, w2, h1, h2] = self.subnegotiation[..] {
+            parsed.window = Some(WindowSize {
+                width: u16::from_be_bytes([w1, w2]),
+                height: u16::from_be_bytes([h1, h2]),
+            });
+        }
+    }
+}
+
+// Converte a saída do servidor para o telnet: \n vira \r\n e o byte 255 é duplicado
+fn encode_output(text: &[u8], out: &mut Vec<u8>) {
+    for &byte in text {
+        match byte {
+            b'\n' => out.extend_from_slice(b"\r\n"),
+            IAC => out.extend_from_slice(&[IAC, IAC]),
+            _ => out.push(byte),
+        }
+    }
+}
+
+// Abre a negociação com um cliente telnet e devolve a conexão já sem as sequências IAC.
+// O servidor assume o eco e pede o tamanho da janela, que chega pelo canal `window`; o canal
+// `echo` liga a entrada secreta.
+pub async fn accept_telnet(mut stream: TcpStream) -> io::Result<Client> {
+    stream
+        .write_all(&[IAC, WILL, OPT_ECHO, IAC, WILL, OPT_SGA, IAC, DO, OPT_SGA, IAC, DO, OPT_NAWS])
+        .await?;
+
+    let (client_side, bridge_side) = io::duplex(BRIDGE_BUFFER);
+    let (window_sender, window) = watch::channel(None);
+    let (echo, masked) = watch::channel(false);
+    tokio::spawn(bridge(stream, bridge_side, window_sender, masked));
+    Ok(Client { stream: Box::new(client_side), terminal: Terminal { window, echo: EchoControl::new(echo) } })
+}
+
+async fn bridge(stream: TcpStream, pipe: io::DuplexStream, window: watch::Sender<Option<WindowSize>>, mut masked: watch::Receiver<bool>) {
+    let (mut socket_reader, mut socket_writer) = stream.into_split();
+    let (mut pipe_reader, mut pipe_writer) = io::split(pipe);
+    let mut parser = TelnetParser::new();
+    let mut incoming = [0u8; 1024];
+    let mut outgoing = [0u8; 4096];
+
+    loop {
+        tokio::select! {
+            read = socket_reader.read(&mut incoming) => {
+                let count = match read {
+                    Ok(0) | Err(_) => break,
+                    Ok(count) => count,
+                };
+                let parsed = parser.feed(&incoming[..count]);
+                if let Some(size) = parsed.window {
+                    debug!(width = size.width, height = size.height, "tamanho da janela");
+                    let _ = window.send(Some(size));
+                }
+                if !parsed.reply.is_empty() && socket_writer.write_all(&parsed.reply).await.is_err() {
+                    break;
+                }
+                if !parsed.lines.is_empty() && pipe_writer.write_all(&parsed.lines).await.is_err() {
+                    break;
+                }
+            }
+            // Quando a conexão termina, o canal fecha e este ramo deixa de valer
+            Ok(()) = masked.changed() => {
+                let negotiation = parser.set_masked(*masked.borrow_and_update());
+                if !negotiation.is_empty() && socket_writer.write_all(&negotiation).await.is_err() {
+                    break;
+                }
+            }
+            read = pipe_reader.read(&mut outgoing) => {
+                let count = match read {
+                    Ok(0) | Err(_) => break,
+                    Ok(count) => count,
+                };
+                let mut encoded = Vec::with_capacity(count + 16);
+                encode_output(&outgoing[..count], &mut encoded);
+                if socket_writer.write_all(&encoded).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = pipe_writer.shutdown().await;
+    let _ = socket_writer.shutdown().await;
+    debug!("ponte telnet encerrada");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequences_split_across_reads_are_still_understood() {
+        let mut parser = TelnetParser::new();
+        let first = parser.feed(&[b'o', b'i', IAC]);
+        assert!(first.lines.is_empty());
+        let second = parser.feed(&[DO, 99, b'!', b'\n']);
+        assert_eq!(second.lines, b"oi!\n");
+        // A opção desconhecida é recusada, e o resto continua sendo eco
+        assert_eq!(second.reply, [IAC, WONT, 99, b'!', b'\r', b'\n']);
+    }
+
+    #[test]
+    fn doubled_iac_is_a_literal_byte_in_data_and_subnegotiation() {
+        let mut parser = TelnetParser::new();
+        let parsed = parser.feed(&[b'a', IAC, IAC, b'b', b'\n']);
+        assert_eq!(parsed.lines, [b'a', IAC, b'b', b'\n']);
+        assert_eq!(parsed.reply, [b'a', IAC, IAC, b'b', b'\r', b'\n']);
+
+        // Largura de 255 colunas: o byte baixo vem dobrado
+        let parsed = parser.feed(&[IAC, SB, OPT_NAWS, 0, IAC, IAC, 0, 40, IAC, SE]);
+        assert_eq!(parsed.window, Some(WindowSize { width: 255, height: 40 }));
+        assert!(parsed.lines.is_empty());
+    }
+
+    #[test]
+    fn cr_lf_and_cr_nul_end_a_single_line() {
+        let mut parser = TelnetParser::new();
+        assert_eq!(parser.feed(b"um\r\ndois\r\0").lines, b"um\ndois\n");
+        // O LF pode chegar na leitura seguinte
+        assert_eq!(parser.feed(b"tres\r").lines, b"tres\n");
+        assert!(parser.feed(b"\n").lines.is_empty());
+    }
+
+    #[test]
+    fn backspace_removes_a_whole_multibyte_character() {
+        let mut parser = TelnetParser::new();
+        let typed = parser.feed("pé".as_bytes());
+        let erased = parser.feed(&[DELETE]);
+        assert_eq!(erased.reply, b"\x08 \x08");
+        let parsed = parser.feed(b"a\n");
+        assert_eq!(parsed.lines, b"pa\n");
+        assert_eq!(typed.reply, "pé".as_bytes());
+    }
+
+    #[test]
+    fn naws_reports_the_window_size() {
+        let mut parser = TelnetParser::new();
+        let parsed = parser.feed(&[IAC, WILL, OPT_NAWS, IAC, SB, OPT_NAWS, 0, 120, 0, 30, IAC, SE]);
+        assert_eq!(parsed.window, Some(WindowSize { width: 120, height: 30 }));
+        assert!(parsed.reply.is_empty());
+    }
+
+    #[test]
+    fn masked_input_borrows_the_echo_and_gives_it_back() {
+        let mut parser = TelnetParser::new();
+        parser.feed(&[IAC, DONT, OPT_ECHO]);
+        assert!(parser.feed(b"x").reply.is_empty());
+
+        assert_eq!(parser.set_masked(true), [IAC, WILL, OPT_ECHO]);
+        parser.feed(&[IAC, DO, OPT_ECHO]);
+        // Um asterisco por caractere, mesmo com acento
+        assert_eq!(parser.feed("sé".as_bytes()).reply, b"**");
+
+        assert_eq!(parser.set_masked(false), [IAC, WONT, OPT_ECHO]);
+        assert!(parser.feed(b"y").reply.is_empty());
+        // Com o eco do servidor desde o início, não há nada a negociar
+        let mut echoing = TelnetParser::new();
+        assert!(echoing.set_masked(true).is_empty());
+        assert!(echoing.set_masked(false).is_empty());
+    }
+}
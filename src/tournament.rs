@@ -0,0 +1,265 @@
+use futures::future::join_all;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, Mutex};
+use tokio::time;
+use tracing::info;
+
+use crate::lobby::Lobby;
+use crate::room::{GameMode, RoomHandle, RoomStatus};
+
+const SECS_PER_DAY: u64 = 86_400;
+
+// Dias da semana, de domingo a sábado, como aparecem nos avisos e na configuração
+const WEEKDAYS: [&str; 7] = ["dom", "seg", "ter", "qua", "qui", "sex", "sáb"];
+const WEEKDAYS_EN: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+// Horário de um torneio recorrente, em UTC, escrito como "[dia] HH:MM [lig4|reversi]".
+// Sem o dia, o torneio é diário; sem o modo, é de lig4.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub weekday: Option<u64>, // 0 é domingo
+    pub minute_of_day: u64,
+    pub mode: GameMode,
+}
+
+impl Schedule {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("horário de torneio inválido: '{}' (use [dia] HH:MM [lig4|reversi], como sáb 20:00)", text);
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let (weekday, rest) = match words.split_first() {
+            Some((first, rest)) if parse_weekday(first).is_some() => (parse_weekday(first), rest),
+            _ => (None, &words[..]),
+        };
+        let (time, mode) = match rest {
+            [time] => (*time, GameMode::ConnectFour),
+            // O anel precisa de mesas inteiras, que o chaveamento não forma
+            [time, mode] => match GameMode::parse(mode) {
+                Some(GameMode::Ring(_)) | None => return Err(invalid()),
+                Some(mode) => (*time, mode),
+            },
+            _ => return Err(invalid()),
+        };
+        let (hour, minute) = time.split_once(':').ok_or_else(invalid)?;
+        let (hour, minute): (u64, u64) = match (hour.parse(), minute.parse()) {
+            (Ok(hour), Ok(minute)) if hour < 24 && minute < 60 => (hour, minute),
+            _ => return Err(invalid()),
+        };
+        Ok(Schedule { weekday, minute_of_day: hour * 60 + minute, mode })
+    }
+
+    // Próximo início depois de `now`, em segundos desde a época Unix
+    pub fn next_start(&self, now: u64) -> u64 {
+        let today = now / SECS_PER_DAY;
+        (today..=today + 7)
+            .map(|day| (day, day * SECS_PER_DAY + self.minute_of_day * 60))
+            .find(|(day, start)| *start > now && self.weekday.is_none_or(|weekday| weekday_of(*day) == weekday))
+            .map_or(now, |(_, start)| start)
+    }
+}
+
+// Aceita o nome do dia inteiro ou abreviado, com ou sem acento: sáb, sabado, sat, saturday
+fn parse_weekday(word: &str) -> Option<u64> {
+    let word = word.to_lowercase().replace('á', "a");
+    [WEEKDAYS, WEEKDAYS_EN]
+        .iter()
+        .find_map(|names| names.iter().position(|name| word.starts_with(&name.replace('á', "a"))))
+        .map(|day| day as u64)
+}
+
+// 1º de janeiro de 1970 foi uma quinta-feira
+fn weekday_of(day: u64) -> u64 {
+    (day + 4) % 7
+}
+
+// Dia e horário de um início, como "sáb 20:00"
+pub fn describe(start: u64) -> String {
+    let day = WEEKDAYS[weekday_of(start / SECS_PER_DAY) as usize];
+    format!("{} {:02}:{:02}", day, start / 3600 % 24, start / 60 % 60)
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+// Uma rodada do mata-mata
+#[derive(Debug, PartialEq)]
+pub struct Round {
+    pub games: Vec<(String, String)>, // Cabeça de chave melhor primeiro; ele joga de X
+    pub bye: Option<String>,          // Quem passa direto quando sobra um jogador
+}
+
+// Emparelha os jogadores ainda na disputa, em ordem de cabeça de chave: o primeiro enfrenta
+// o último, o segundo o penúltimo, e assim por diante. Com número ímpar, o primeiro passa direto.
+pub fn pair_round(players: &[String]) -> Round {
+    let (bye, rest) = match players.len() % 2 {
+        1 => (players.first().cloned(), &players[1..]),
+        _ => (None, players),
+    };
+    let half = rest.len() / 2;
+    let games = rest[..half].iter().zip(rest[half..].iter().rev()).map(|(high, low)| (high.clone(), low.clone())).collect();
+    Round { games, bye }
+}
+
+// Roda os torneios da configuração, um de cada vez, para sempre
+pub async fn run_scheduler(lobby: Arc<Mutex<Lobby>>, schedules: Vec<Schedule>) {
+    loop {
+        let now = unix_now();
+        let Some((start, mode)) = schedules.iter().map(|schedule| (schedule.next_start(now), schedule.mode)).min_by_key(|(start, _)| *start) else {
+            return;
+        };
+        let registration = {
+            let mut lobby = lobby.lock().await;
+            lobby.schedule_tournament(start, mode);
+            lobby.tournament_registration().as_secs()
+        };
+        // Com o servidor ligado no meio das inscrições, elas abrem na hora
+        let opens = start.saturating_sub(registration);
+        if opens > now {
+            time::sleep(Duration::from_secs(opens - now)).await;
+        }
+
+        let opened = lobby.lock().await.open_tournament(mode, start);
+        match opened {
+            Ok(()) => play(Arc::clone(&lobby), start).await,
+            Err(msg) => info!(reason = msg.trim_end(), "torneio agendado não abriu"),
+        }
+        // O próximo horário é sempre depois deste
+        let now = unix_now();
+        if now <= start {
+            time::sleep(Duration::from_secs(start + 1 - now)).await;
+        }
+    }
+}
+
+// Espera o fim das inscrições e joga o mata-mata até sobrar o campeão
+pub async fn play(lobby: Arc<Mutex<Lobby>>, start: u64) {
+    let now = unix_now();
+    if start > now {
+        time::sleep(Duration::from_secs(start - now)).await;
+    }
+
+    let mut players = lobby.lock().await.begin_tournament();
+    if players.len() < 2 {
+        lobby.lock().await.end_tournament("O torneio foi cancelado: não houve inscritos suficientes.\n".to_string());
+        return;
+    }
+
+    let mut round = 1;
+    while players.len() > 1 {
+        let pairing = pair_round(&players);
+        lobby.lock().await.announce_round(round, &pairing);
+        let mut results = Vec::new();
+        let mut pending = pairing.games.clone();
+        while !pending.is_empty() {
+            let winners = play_games(&lobby, &pending).await;
+            let mut replays = Vec::new();
+            for ((player_x, player_o), winner) in pending.into_iter().zip(winners) {
+                match winner {
+                    Some(winner) => results.push((player_x, player_o, winner)),
+                    None => replays.push((player_x, player_o)),
+                }
+            }
+            // A sala fechada sem resultado, como pelo close do administrador, não decide
+            // nada: a partida é jogada de novo
+            for (player_x, player_o) in &replays {
+                info!(x = %player_x, o = %player_o, "partida do torneio sem resultado; jogando de novo");
+                let text = format!("A partida do torneio entre {} e {} terminou sem resultado e será jogada de novo.\n", player_x, player_o);
+                lobby.lock().await.tell_lobby(&text);
+            }
+            pending = replays;
+        }
+
+        let mut lobby = lobby.lock().await;
+        for (player_x, player_o, winner) in &results {
+            let loser = if winner == player_x { player_o } else { player_x };
+            lobby.record_tournament_game(round, player_x, player_o, winner);
+            lobby.eliminate(loser, round);
+        }
+        // A ordem de cabeça de chave se mantém entre as rodadas
+        players.retain(|player| pairing.bye.as_ref() == Some(player) || results.iter().any(|(_, _, winner)| winner == player));
+        round += 1;
+    }
+
+    let champion = players.remove(0);
+    let mut lobby = lobby.lock().await;
+    lobby.crown(&champion);
+}
+
+// Abre as salas das partidas e espera todas acabarem. Cada resultado é o vencedor, ou None
+// se a sala fechou sem resultado.
+async fn play_games(lobby: &Arc<Mutex<Lobby>>, games: &[(String, String)]) -> Vec<Option<String>> {
+    let mut winners = Vec::new();
+    let mut walkovers = Vec::new();
+    {
+        let mut lobby = lobby.lock().await;
+        for (player_x, player_o) in games {
+            let (room, absent) = lobby.start_tournament_game(player_x, player_o);
+            walkovers.push((room.clone(), absent));
+            winners.push(game_winner(room, player_x.clone(), player_o.clone()));
+        }
+    }
+    // Quem saiu do torneio perde a partida por W.O. assim que ela começa
+    for (room, absent) in walkovers {
+        for nick in absent {
+            room.leave(&nick).await;
+        }
+    }
+    join_all(winners).await
+}
+
+// Espera a partida acabar e diz quem avança. No empate avança o cabeça de chave melhor, que
+// joga de X. A sala que fecha sem resultado não tem vencedor.
+async fn game_winner(room: RoomHandle, player_x: String, player_o: String) -> Option<String> {
+    let mut events = room.subscribe();
+    loop {
+        match room.view(1).await.map(|view| view.status) {
+            Some(RoomStatus::Finished(-1)) => return Some(player_o),
+            Some(RoomStatus::Finished(_)) => return Some(player_x),
+            None => return None,
+            Some(_) => {}
+        }
+        if let Err(broadcast::error::RecvError::Closed) = events.recv().await {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nicks(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn schedules_find_the_next_start_in_utc() {
+        let saturday = Schedule::parse("sáb 20:00").unwrap();
+        assert_eq!(saturday, Schedule { weekday: Some(6), minute_of_day: 20 * 60, mode: GameMode::ConnectFour });
+        assert_eq!(Schedule::parse("sat 20:00 reversi").unwrap().mode, GameMode::Reversi);
+        assert_eq!(Schedule::parse("18:30").unwrap().weekday, None);
+        assert!(Schedule::parse("sab 25:00").is_err());
+        assert!(Schedule::parse("sab 20:00 anel").is_err());
+        assert!(Schedule::parse("amanhã").is_err());
+
+        // 1970-01-01 foi quinta: o primeiro sábado às 20:00 é o dia 3
+        let first_saturday = 2 * SECS_PER_DAY + 20 * 3600;
+        assert_eq!(saturday.next_start(0), first_saturday);
+        assert_eq!(describe(first_saturday), "sáb 20:00");
+        // No próprio horário, o próximo é o da semana seguinte
+        assert_eq!(saturday.next_start(first_saturday), first_saturday + 7 * SECS_PER_DAY);
+        assert_eq!(Schedule::parse("18:30").unwrap().next_start(19 * 3600), SECS_PER_DAY + 18 * 3600 + 30 * 60);
+    }
+
+    #[test]
+    fn rounds_pair_the_best_seed_with_the_worst() {
+        let round = pair_round(&nicks(&["a", "b", "c", "d"]));
+        assert_eq!(round.games, vec![("a".to_string(), "d".to_string()), ("b".to_string(), "c".to_string())]);
+        assert_eq!(round.bye, None);
+
+        let round = pair_round(&nicks(&["a", "b", "c"]));
+        assert_eq!(round.games, vec![("b".to_string(), "c".to_string())]);
+        assert_eq!(round.bye, Some("a".to_string()));
+    }
+}
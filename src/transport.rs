@@ -0,0 +1,204 @@
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{self, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use socket2::{Domain, SockRef, Socket, TcpKeepalive, Type};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::debug;
+
+// Qualquer fluxo de bytes em que o protocolo do jogo pode rodar: TCP direto,
+// WebSocket ou telnet. O lobby e as salas só enxergam linhas de texto e não sabem qual é.
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+pub type ClientStream = Box<dyn Transport>;
+
+// Tamanho do terminal do cliente, em caracteres
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowSize {
+    pub width: u16,
+    pub height: u16,
+}
+
+// Conexão aceita em qualquer transporte
+pub struct Client {
+    pub stream: ClientStream,
+    pub terminal: Terminal,
+}
+
+// O que o transporte sabe do terminal do cliente. `window` só recebe valores quando o
+// transporte sabe o tamanho (telnet com NAWS), e `echo` só tem efeito onde o servidor faz
+// o eco do que o cliente digita (telnet).
+pub struct Terminal {
+    pub window: watch::Receiver<Option<WindowSize>>,
+    pub echo: EchoControl,
+}
+
+// Esconde ou volta a mostrar o que o cliente digita, para entrar com senhas. Nos transportes
+// em que o eco é do próprio cliente, não faz nada.
+#[derive(Clone, Default)]
+pub struct EchoControl(Option<watch::Sender<bool>>);
+
+impl EchoControl {
+    pub fn new(masked: watch::Sender<bool>) -> Self {
+        EchoControl(Some(masked))
+    }
+
+    pub fn set_masked(&self, masked: bool) {
+        if let Some(sender) = &self.0 {
+            sender.send_replace(masked);
+        }
+    }
+}
+
+impl Client {
+    // Conexão sem informação de tamanho de janela nem controle do eco
+    pub fn plain(stream: ClientStream) -> Self {
+        let (_, window) = watch::channel(None);
+        Client { stream, terminal: Terminal { window, echo: EchoControl::default() } }
+    }
+}
+
+// Porta pela qual a conexão chegou
+#[derive(Clone)]
+pub enum Protocol {
+    Tcp,
+    WebSocket,
+    Telnet,
+    Tls(TlsAcceptor),
+}
+
+impl Protocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::WebSocket => "websocket",
+            Protocol::Telnet => "telnet",
+            Protocol::Tls(_) => "tls",
+        }
+    }
+
+    // Faz o handshake do protocolo, se houver, e devolve a conexão pronta para o lobby.
+    // `heartbeat` é o intervalo dos pings do WebSocket.
+    pub async fn accept(self, stream: TcpStream, heartbeat: Option<Duration>) -> Result<Client, String> {
+        match self {
+            Protocol::Tcp => Ok(Client::plain(Box::new(stream))),
+            Protocol::WebSocket => accept_websocket(stream, heartbeat).await,
+            Protocol::Telnet => crate::telnet::accept_telnet(stream).await.map_err(|err| err.to_string()),
+            Protocol::Tls(acceptor) => crate::tls::accept_tls(stream, acceptor).await,
+        }
+    }
+}
+
+// Escuta no endereço. Com `v6_only`, um endereço IPv6 atende só IPv6, para que
+// [::]:porta e 0.0.0.0:porta possam ser usados ao mesmo tempo
+pub async fn bind(addr: &str, v6_only: bool) -> io::Result<TcpListener> {
+    let mut last_err = io::Error::new(io::ErrorKind::InvalidInput, "o endereço não tem nenhum IP");
+    for addr in tokio::net::lookup_host(addr).await? {
+        match bind_socket(addr, v6_only) {
+            Ok(listener) => return Ok(listener),
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+fn bind_socket(addr: SocketAddr, v6_only: bool) -> io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(socket2::Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(v6_only)?;
+    }
+    // Como o TcpListener::bind do tokio, para reiniciar o servidor sem esperar o TIME_WAIT
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    TcpListener::from_std(socket.into())
+}
+
+// Liga o keepalive TCP: depois de `interval` sem tráfego o sistema sonda o cliente e, se
+// ele não responder, a conexão dá erro na próxima leitura como em uma desconexão comum
+pub fn set_keepalive(stream: &TcpStream, interval: Duration) -> io::Result<()> {
+    let keepalive = TcpKeepalive::new().with_time(interval).with_interval(interval);
+    SockRef::from(stream).set_tcp_keepalive(&keepalive)
+}
+
+// Capacidade do canal interno entre a ponte WebSocket e a conexão
+const BRIDGE_BUFFER: usize = 64 * 1024;
+
+// Faz o handshake WebSocket e devolve um fluxo de texto equivalente ao do TCP.
+// Cada mensagem de texto recebida vira uma linha do protocolo, e cada linha que o
+// servidor escreve é enviada ao navegador como uma mensagem de texto.
+async fn accept_websocket(stream: TcpStream, heartbeat: Option<Duration>) -> Result<Client, String> {
+    let socket = tokio_tungstenite::accept_async(stream)
+        .await
+        .map_err(|err| err.to_string())?;
+    let (client_side, bridge_side) = io::duplex(BRIDGE_BUFFER);
+    tokio::spawn(bridge(socket, bridge_side, heartbeat));
+    Ok(Client::plain(Box::new(client_side)))
+}
+
+async fn bridge(socket: tokio_tungstenite::WebSocketStream<TcpStream>, pipe: io::DuplexStream, heartbeat: Option<Duration>) {
+    let (mut ws_sink, mut ws_stream) = socket.split();
+    let (pipe_reader, mut pipe_writer) = io::split(pipe);
+    let mut outgoing = io::BufReader::new(pipe_reader).lines();
+    // Pings periódicos mantêm proxies sem fechar a conexão e fazem a escrita falhar se o navegador sumiu
+    let mut pings = heartbeat.map(|interval| time::interval_at(time::Instant::now() + interval, interval));
+
+    loop {
+        tokio::select! {
+            message = ws_stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Binary(bytes))) => String::from_utf8_lossy(&bytes).into_owned(),
+                    Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => continue,
+                    Some(Ok(_)) | Some(Err(_)) | None => break,
+                };
+                // Uma mensagem pode trazer várias linhas; a última sempre termina com \n
+                let mut text = text;
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                if pipe_writer.write_all(text.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+            line = outgoing.next_line() => {
+                match line {
+                    Ok(Some(line)) => {
+                        if ws_sink.send(Message::Text(line)).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ => break,
+                }
+            }
+            _ = next_ping(&mut pings) => {
+                if ws_sink.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Fecha os dois lados: a conexão vê o fim do fluxo e o navegador recebe o Close
+    let _ = pipe_writer.shutdown().await;
+    let _ = ws_sink.close().await;
+    debug!("ponte WebSocket encerrada");
+}
+
+// Próximo ping do WebSocket; sem intervalo configurado, nunca termina
+async fn next_ping(pings: &mut Option<time::Interval>) {
+    match pings {
+        Some(pings) => {
+            pings.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
@@ -0,0 +1,178 @@
+use proptest::prelude::*;
+
+use reversi_egui::game::{
+    check_draw, check_winner, has_line, is_valid_move, BoardConfig, GameState, Move, Outcome, Winner, MAX_COLS, MAX_ROWS,
+    MIN_SIZE, MIN_WIN_LENGTH,
+};
+
+// Direções de uma sequência: horizontal, vertical e as duas diagonais
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+
+// Tabuleiro válido qualquer, dentro dos limites aceitos pelo /create
+fn board_config() -> impl Strategy<Value = BoardConfig> {
+    (MIN_SIZE..=MAX_ROWS, MIN_SIZE..=MAX_COLS)
+        .prop_flat_map(|(rows, cols)| (Just(rows), Just(cols), MIN_WIN_LENGTH..=rows.min(cols)))
+        .prop_map(|(rows, cols, win_length)| BoardConfig::new(rows, cols, win_length).unwrap())
+}
+
+fn board_from(rows: &[&str]) -> GameState {
+    let board: Vec<Vec<i32>> = rows
+        .iter()
+        .map(|row| {
+            row.chars()
+                .map(|cell| match cell {
+                    'X' => 1,
+                    'O' => -1,
+                    _ => 0,
+                })
+                .collect()
+        })
+        .collect();
+    GameState::from_grid(&board, 1, 4)
+}
+
+proptest! {
+    // Uma sequência do tamanho exato, em qualquer direção e posição, vence
+    #[test]
+    fn line_of_win_length_wins_in_every_direction(
+        config in board_config(),
+        direction in 0..DIRECTIONS.len(),
+        start_row in 0..MAX_ROWS,
+        start_col in 0..MAX_COLS,
+        symbol in prop_oneof![Just(1), Just(-1)],
+    ) {
+        let (d_row, d_col) = DIRECTIONS[direction];
+        let length = config.win_length as isize;
+        let (rows, cols) = (config.rows as isize, config.cols as isize);
+        let (row, col) = (start_row as isize % rows, start_col as isize % cols);
+        let end_row = row + (length - 1) * d_row;
+        let end_col = col + (length - 1) * d_col;
+        prop_assume!((0..rows).contains(&end_row) && end_col < cols);
+
+        let mut game_state = GameState::with_config(config);
+        for i in 0..length {
+            game_state.set_cell((row + i * d_row) as usize, (col + i * d_col) as usize, symbol);
+        }
+
+        prop_assert!(has_line(&game_state, symbol));
+        prop_assert!(!has_line(&game_state, -symbol));
+        // A vez não importa: a sequência é encontrada olhando o tabuleiro inteiro
+        let outcome = check_winner(&game_state).unwrap();
+        prop_assert_eq!(outcome.winner.symbol(), symbol);
+        prop_assert_eq!(outcome.line.len(), config.win_length);
+        prop_assert!(outcome.line.iter().all(|&(row, col)| game_state.cell(row, col).symbol() == symbol));
+        prop_assert_eq!(game_state.result(), Some(symbol));
+    }
+
+    // Uma peça a menos que o necessário não vence
+    #[test]
+    fn line_one_short_does_not_win(
+        config in board_config(),
+        direction in 0..DIRECTIONS.len(),
+        start_row in 0..MAX_ROWS,
+        start_col in 0..MAX_COLS,
+    ) {
+        let (d_row, d_col) = DIRECTIONS[direction];
+        let length = config.win_length as isize - 1;
+        let (rows, cols) = (config.rows as isize, config.cols as isize);
+        let (row, col) = (start_row as isize % rows, start_col as isize % cols);
+        prop_assume!((0..rows).contains(&(row + (length - 1) * d_row)) && col + (length - 1) * d_col < cols);
+
+        let mut game_state = GameState::with_config(config);
+        for i in 0..length {
+            game_state.set_cell((row + i * d_row) as usize, (col + i * d_col) as usize, 1);
+        }
+
+        prop_assert!(!has_line(&game_state, 1));
+        prop_assert_eq!(check_winner(&game_state), None);
+    }
+
+    // Partidas aleatórias: só jogadas válidas são aceitas, o turno alterna apenas com
+    // jogadas aceitas e o resultado bate com as funções de vitória e empate
+    #[test]
+    fn random_games_stay_consistent(config in board_config(), columns in prop::collection::vec(0..MAX_COLS + 2, 1..200)) {
+        let mut game_state = GameState::with_config(config);
+        for col in columns {
+            if game_state.result().is_some() {
+                break;
+            }
+            let player_move = Move { col };
+            let valid = is_valid_move(&game_state, &player_move);
+            let turn = game_state.current_turn;
+            let pieces = game_state.piece_count();
+
+            let applied = game_state.apply_move(&player_move);
+            prop_assert_eq!(applied.is_ok(), valid);
+            let after = game_state.piece_count();
+            if valid {
+                prop_assert_eq!(game_state.current_turn, -turn);
+                prop_assert_eq!(after, pieces + 1);
+            } else {
+                prop_assert_eq!(game_state.current_turn, turn);
+                prop_assert_eq!(after, pieces);
+            }
+        }
+
+        match check_winner(&game_state) {
+            Some(Outcome { winner: Winner::Draw, line }) => prop_assert!(check_draw(&game_state) && line.is_empty()),
+            Some(Outcome { winner, .. }) => prop_assert!(has_line(&game_state, winner.symbol())),
+            None => prop_assert!(!check_draw(&game_state)),
+        }
+    }
+}
+
+#[test]
+fn full_board_without_lines_is_a_draw() {
+    let game_state = board_from(&[
+        "OOOXOOO",
+        "XXXOXXX",
+        "OOOXOOO",
+        "XXXOXXX",
+        "OOOXOOO",
+        "XXXOXXX",
+    ]);
+    assert!(check_draw(&game_state));
+    assert!(!has_line(&game_state, 1));
+    assert!(!has_line(&game_state, -1));
+    assert_eq!(check_winner(&game_state), Some(Outcome { winner: Winner::Draw, line: Vec::new() }));
+}
+
+#[test]
+fn win_on_the_last_move_beats_the_draw() {
+    // Tabuleiro cheio em que o X fecha uma linha na última casa: vitória, não empate
+    let game_state = board_from(&[
+        "XXXXOOO",
+        "XXXOXXX",
+        "OOOXOOO",
+        "XXXOXXX",
+        "OOOXOOO",
+        "XXXOXXX",
+    ]);
+    assert!(check_draw(&game_state));
+    assert_eq!(check_winner(&game_state).map(|outcome| outcome.winner), Some(Winner::X));
+}
+
+#[test]
+fn every_column_full_rejects_all_moves() {
+    let mut game_state = board_from(&[
+        "OOOXOOO",
+        "XXXOXXX",
+        "OOOXOOO",
+        "XXXOXXX",
+        "OOOXOOO",
+        "XXXOXXX",
+    ]);
+    for col in 0..game_state.cols() {
+        assert!(!is_valid_move(&game_state, &Move { col }));
+        assert!(game_state.apply_move(&Move { col }).is_err());
+    }
+}
+
+#[test]
+fn invalid_moves_are_rejected() {
+    let mut game_state = GameState::new();
+    assert!(game_state.apply_move(&Move { col: game_state.cols() }).is_err());
+    assert!(game_state.apply_move(&Move { col: usize::MAX }).is_err());
+    assert_eq!(game_state.current_turn, 1);
+    assert_eq!(game_state.piece_count(), 0);
+}
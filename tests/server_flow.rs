@@ -0,0 +1,1968 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reversi_egui::game::{is_valid_move, GameState, Move, WIN_LENGTH};
+use reversi_egui::protocol::{ErrorCode, LegalMove, ServerMessage};
+use reversi_egui::puzzle::{self, PUZZLES};
+use reversi_egui::reversi::ReversiState;
+use tokio_tungstenite::tungstenite::{self, Message};
+
+// Tempo máximo esperando uma mensagem do servidor antes de o teste falhar
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Servidor rodando em uma porta livre, encerrado quando o teste termina
+struct TestServer {
+    child: Child,
+    port: u16,
+    database: PathBuf,
+}
+
+impl TestServer {
+    fn start(name: &str) -> Self {
+        Self::start_with(name, &[])
+    }
+
+    // Inicia o servidor com opções extras de linha de comando
+    fn start_with(name: &str, extra_args: &[&str]) -> Self {
+        let port = free_port();
+        let database = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.db", name));
+        let _ = std::fs::remove_file(&database);
+
+        let child = spawn_server(port, &database, extra_args);
+        let server = TestServer { child, port, database };
+        server.wait_until_ready();
+        server
+    }
+
+    // Derruba o servidor sem aviso e sobe outro com o mesmo banco, como depois de uma queda
+    fn restart(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        self.port = free_port();
+        self.child = spawn_server(self.port, &self.database, &[]);
+        self.wait_until_ready();
+    }
+
+    fn wait_until_ready(&self) {
+        let deadline = Instant::now() + READ_TIMEOUT;
+        while TcpStream::connect(("127.0.0.1", self.port)).is_err() {
+            assert!(Instant::now() < deadline, "o servidor não abriu a porta {}", self.port);
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    // Conexão crua, sem nenhum comando enviado
+    fn open(&self) -> Client {
+        self.open_at(self.port)
+    }
+
+    // Conexão crua numa das outras portas do mesmo servidor
+    fn open_at(&self, port: u16) -> Client {
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+        Client {
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            stream,
+            min_pieces: 0,
+        }
+    }
+
+    fn connect(&self, nick: &str) -> Client {
+        let mut client = self.open();
+        client.send("MODE json");
+        client.send(&format!("NICK {}", nick));
+        client.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains(nick)));
+        client
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.database);
+    }
+}
+
+// Semente do sorteio em que o X (ou o primeiro do anel) começa, para os testes que não
+// tratam do sorteio
+const FIRST_PLAYER_SEED: &str = "20";
+
+fn spawn_server(port: u16, database: &Path, extra_args: &[&str]) -> Child {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_server"));
+    if !extra_args.contains(&"--first-player-seed") {
+        command.args(["--first-player-seed", FIRST_PLAYER_SEED]);
+    }
+    if !extra_args.contains(&"--telnet-port") {
+        command.args(["--telnet-port", "0"]);
+    }
+    if !extra_args.contains(&"--websocket-port") {
+        command.args(["--websocket-port", "0"]);
+    }
+    command
+        .args(["--port", &port.to_string()])
+        .arg("--database")
+        .arg(database)
+        .args(extra_args)
+        .env("RUST_LOG", "error")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("não foi possível iniciar o servidor")
+}
+
+// A porta é liberada antes de o servidor abrir, mas o sistema não a reutiliza tão cedo
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+// Cliente falso que fala o protocolo JSON, uma mensagem por linha
+struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+    min_pieces: usize, // Peças que o tabuleiro precisa ter na próxima vez deste jogador
+}
+
+impl Client {
+    fn send(&mut self, line: &str) {
+        self.stream.write_all(line.as_bytes()).unwrap();
+        self.stream.write_all(b"\n").unwrap();
+    }
+
+    // Próxima mensagem JSON; linhas de texto, como a ajuda do lobby, são ignoradas
+    fn next_message(&mut self) -> ServerMessage {
+        loop {
+            let mut line = String::new();
+            let read = self.reader.read_line(&mut line).expect("o servidor não respondeu a tempo");
+            assert!(read > 0, "o servidor fechou a conexão");
+            if let Ok(message) = serde_json::from_str(&line) {
+                return message;
+            }
+        }
+    }
+
+    // Descarta mensagens até chegar uma que satisfaça a condição
+    fn expect(&mut self, condition: impl Fn(&ServerMessage) -> bool) -> ServerMessage {
+        loop {
+            let message = self.next_message();
+            if condition(&message) {
+                return message;
+            }
+        }
+    }
+
+    // Espera a vez deste jogador e joga na coluna (índice a partir de 0). Estados repetidos de antes da última
+    // jogada (como o do início da partida) são ignorados pela contagem de peças, e os da sala ainda à espera
+    // do oponente, pela falta de jogadas válidas.
+    fn play(&mut self, col: usize) {
+        let min_pieces = self.min_pieces;
+        let state = self.expect(|message| {
+            matches!(message, ServerMessage::State { board, current_turn, you, legal_moves, .. }
+                if current_turn == you && pieces(board) >= min_pieces && !legal_moves.is_empty())
+        });
+        if let ServerMessage::State { board, .. } = state {
+            self.min_pieces = pieces(&board) + 1;
+        }
+        // Em modo texto as colunas são digitadas a partir de 1
+        self.send(&(col + 1).to_string());
+    }
+}
+
+// Código de reconexão entregue ao entrar na sala
+fn reconnect_code(client: &mut Client) -> String {
+    let message = client.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Código de reconexão")));
+    match message {
+        ServerMessage::Info { message } => message["Código de reconexão: ".len()..].split('.').next().unwrap().to_string(),
+        _ => unreachable!(),
+    }
+}
+
+fn pieces(board: &[Vec<i32>]) -> usize {
+    board.iter().flatten().filter(|cell| **cell != 0).count()
+}
+
+fn is_game_over(message: &ServerMessage) -> bool {
+    matches!(message, ServerMessage::GameOver { .. })
+}
+
+#[test]
+fn scripted_game_ends_with_winner_for_both_players() {
+    let server = TestServer::start("scripted_game");
+    let mut x = server.connect("ana");
+    let mut o = server.connect("bia");
+
+    x.send("/create mesa lig4");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("mesa")));
+    o.send("/join mesa");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    // X empilha na coluna 0 e vence na vertical
+    for _ in 0..3 {
+        x.play(0);
+        o.play(1);
+    }
+    x.play(0);
+
+    match x.expect(is_game_over) {
+        ServerMessage::GameOver { winner, you, line } => {
+            assert_eq!((winner, you), (1, 1));
+            // As quatro peças da coluna 0, de baixo para cima
+            let cells: Vec<_> = line.iter().map(|cell| (cell.row, cell.col)).collect();
+            assert_eq!(cells, [(5, 0), (4, 0), (3, 0), (2, 0)]);
+        }
+        _ => unreachable!(),
+    }
+    match o.expect(is_game_over) {
+        ServerMessage::GameOver { winner, you, line } => assert_eq!((winner, you, line.len()), (1, -1, 4)),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn scripted_reversi_game_is_played_to_the_disc_count() {
+    let server = TestServer::start("scripted_reversi");
+    let mut x = server.connect("caua");
+    let mut o = server.connect("dora");
+
+    x.send("/create disco reversi");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("disco")));
+    o.send("/join disco");
+
+    // Os dois jogam sempre a primeira casa válida; a partida local diz de quem é a vez,
+    // inclusive depois de um passe, e qual tabuleiro o servidor deve mostrar
+    let mut local = ReversiState::new();
+    let mut moves = 0;
+    while !local.is_over() {
+        let player_move = local.legal_moves(local.current_turn).remove(0);
+        let expected: Vec<Vec<i32>> = local.board.iter().map(|row| row.to_vec()).collect();
+        let mover = if local.current_turn == 1 { &mut x } else { &mut o };
+        mover.expect(|message| matches!(message, ServerMessage::State { board, current_turn, you, legal_moves, .. } if *board == expected && current_turn == you && !legal_moves.is_empty()));
+        mover.send(&format!("{} {}", player_move.row + 1, player_move.col + 1));
+        local.apply_move(&player_move).unwrap();
+        moves += 1;
+    }
+    assert!(moves > 4);
+
+    let expected = local.final_result().unwrap();
+    match x.expect(is_game_over) {
+        ServerMessage::GameOver { winner, you, .. } => assert_eq!((winner, you), (expected, 1)),
+        _ => unreachable!(),
+    }
+    match o.expect(is_game_over) {
+        ServerMessage::GameOver { winner, you, .. } => assert_eq!((winner, you), (expected, -1)),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn invalid_move_is_reported_and_turn_is_kept() {
+    let server = TestServer::start("invalid_move");
+    let mut x = server.connect("caio");
+    let mut o = server.connect("duda");
+
+    x.send("/create sala lig4");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("sala")));
+    o.send("/join sala");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    x.play(98);
+    x.expect(|message| matches!(message, ServerMessage::Error { .. }));
+
+    // A jogada inválida não passa a vez: X ainda joga
+    x.send("4");
+    match x.expect(|message| matches!(message, ServerMessage::State { .. })) {
+        ServerMessage::State { board, current_turn, .. } => {
+            assert_eq!(current_turn, -1);
+            assert_eq!(board.last().unwrap()[3], 1);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn moves_before_the_opponent_joins_are_refused() {
+    let server = TestServer::start("move_before_join");
+    let mut x = server.connect("lia");
+    let mut o = server.connect("rui");
+
+    x.send("/create mesa");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("mesa")));
+    x.send("4");
+    x.expect(|message| matches!(message, ServerMessage::Error { code: Some(code), .. } if *code == ErrorCode::GameNotStarted));
+
+    // O tabuleiro continua vazio quando o O entra
+    o.send("/join mesa");
+    match o.expect(|message| matches!(message, ServerMessage::State { .. })) {
+        ServerMessage::State { board, current_turn, .. } => assert_eq!((pieces(&board), current_turn), (0, 1)),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn dropped_connection_pauses_the_game() {
+    let server = TestServer::start("dropped_connection");
+    let mut x = server.connect("eva");
+    let mut o = server.connect("fabi");
+
+    x.send("/create jogo");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("jogo")));
+    o.send("/join jogo");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    drop(o);
+
+    // A queda pausa a partida em vez de dar a vitória ao X na hora
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("pausada")));
+    x.send("3");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("pausada")));
+}
+
+#[test]
+fn resume_takes_the_seat_over_from_a_connection_still_open() {
+    let server = TestServer::start("takeover");
+    let mut x = server.connect("gil");
+    let mut o = server.connect("hana");
+
+    x.send("/create balcao");
+    let code_x = reconnect_code(&mut x);
+    o.send("/join balcao");
+    x.play(2);
+    o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1));
+
+    // O jogador volta por outra conexão (no jogo real, outro cliente ou transporte) sem a
+    // antiga ter caído; ela é encerrada e a partida continua na nova
+    let mut again = server.open();
+    again.send("MODE json");
+    again.send(&format!("RESUME {}", code_x));
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("continuou em outra conexão")));
+    again.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Bem-vindo de volta, gil")));
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message == "O X voltou. A partida continua."));
+
+    o.send("4");
+    again.min_pieces = 2;
+    again.play(2);
+    o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 3));
+}
+
+#[test]
+fn game_paused_by_agreement_survives_a_restart() {
+    let mut server = TestServer::start("paused_by_agreement");
+    let mut x = server.connect("gil");
+    let mut o = server.connect("hana");
+
+    x.send("/create varanda");
+    let code_x = reconnect_code(&mut x);
+    o.send("/join varanda");
+    let code_o = reconnect_code(&mut o);
+    x.play(2);
+
+    // Só o pedido dos dois pausa a partida
+    o.send("/pause");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("pediu uma pausa")));
+    x.send("/pause");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Partida pausada")));
+    o.send("1");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("/resume")));
+
+    server.restart();
+    let mut x = server.open();
+    x.send("MODE json");
+    x.send(&format!("RESUME {}", code_x));
+    x.expect(|message| matches!(message, ServerMessage::State { .. }));
+    x.send("/resume");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("ainda não voltou")));
+
+    let mut o = server.open();
+    o.send("MODE json");
+    o.send(&format!("RESUME {}", code_o));
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("O O voltou à sala")));
+    o.send("/resume");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message == "O O retomou a partida."));
+
+    // A partida continua de onde parou, na vez do O
+    o.play(3);
+    match x.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2)) {
+        ServerMessage::State { board, current_turn, .. } => {
+            assert_eq!(current_turn, 1);
+            assert_eq!(board.last().unwrap()[2], 1);
+            assert_eq!(board.last().unwrap()[3], -1);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn tv_follows_games_without_taking_a_seat() {
+    let server = TestServer::start("tv");
+    let mut viewer = server.open();
+    viewer.send("MODE json");
+    viewer.send("/tv");
+    viewer.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Nenhuma partida")));
+
+    let mut x = server.connect("ivo");
+    let mut o = server.connect("jade");
+    x.send("/create palco");
+    x.expect(|message| matches!(message, ServerMessage::State { .. }));
+    o.send("/join palco");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    viewer.send("/watch random");
+    viewer.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("TV: sala 'palco'")));
+    x.play(4);
+    match viewer.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1)) {
+        ServerMessage::State { you, .. } => assert_eq!(you, 0),
+        _ => unreachable!(),
+    }
+
+    // Quem assiste pela TV não aparece na sala como espectador
+    viewer.send("q");
+    viewer.send("/list");
+    match viewer.expect(|message| matches!(message, ServerMessage::Rooms { .. })) {
+        ServerMessage::Rooms { rooms } => assert_eq!(rooms[0].spectators, 0),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn swap_rule_hands_the_first_move_to_the_second_player() {
+    let server = TestServer::start("swap");
+    let mut x = server.connect("kai");
+    let mut o = server.connect("lua");
+
+    x.send("/create troca lig4 swap");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("regra da troca")));
+    o.send("/join troca");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    x.play(3);
+    o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1));
+
+    o.send("/swap");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Troca! lua fica com o X")));
+    // Quem abriu a partida agora é o O e joga em seguida
+    match x.expect(|message| matches!(message, ServerMessage::State { you: -1, .. })) {
+        ServerMessage::State { board, current_turn, .. } => {
+            assert_eq!(current_turn, -1);
+            assert_eq!(board.last().unwrap()[3], 1);
+        }
+        _ => unreachable!(),
+    }
+    x.send("4");
+    o.expect(|message| matches!(message, ServerMessage::State { you: 1, current_turn: 1, .. }));
+
+    // Depois da resposta à primeira jogada a troca não vale mais
+    o.send("/swap");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("primeira jogada")));
+}
+
+#[test]
+fn pop_out_removes_the_players_own_bottom_piece() {
+    let server = TestServer::start("pop_out");
+    let mut x = server.connect("mel");
+    let mut o = server.connect("nina");
+
+    x.send("/create pop lig4 popout");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Pop Out")));
+    o.send("/join pop");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    x.play(3);
+    o.play(0);
+    x.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2));
+
+    // Só vale retirar a própria peça
+    x.send("/pop 1");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("peça sua")));
+    x.send("/pop 4");
+    match o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1)) {
+        ServerMessage::State { board, current_turn, .. } => {
+            assert_eq!(current_turn, -1);
+            assert_eq!(board.last().unwrap()[0], -1);
+            assert_eq!(board.last().unwrap()[3], 0);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn power_up_wildcard_is_played_once_per_game() {
+    let server = TestServer::start("power_up");
+    let mut x = server.connect("olga");
+    let mut o = server.connect("pia");
+
+    x.send("/create coringa lig4 powerup");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Power Up")));
+    o.send("/join coringa");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Oponente conectado")));
+    x.send("/wild 4");
+    match o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1)) {
+        ServerMessage::State { board, current_turn, .. } => {
+            assert_eq!(current_turn, -1);
+            assert_eq!(board.last().unwrap()[3], 2);
+        }
+        _ => unreachable!(),
+    }
+    o.send("1");
+    x.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2));
+    x.send("/wild 2");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("já usou")));
+}
+
+#[test]
+fn ring_room_starts_when_full_and_turns_go_around() {
+    let server = TestServer::start("ring");
+    let mut x = server.connect("rui");
+    let mut o = server.connect("sol");
+    let mut a = server.connect("tom");
+
+    x.send("/create roda anel 3");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Modo anel")));
+    o.send("/join roda");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("(2/3)")));
+    a.send("/join roda");
+    match a.expect(|message| matches!(message, ServerMessage::State { .. })) {
+        ServerMessage::State { mode, you, current_turn, board, .. } => {
+            assert_eq!((mode.as_str(), you, current_turn), ("anel", 3, 1));
+            assert_eq!((board.len(), board[0].len()), (9, 10));
+        }
+        _ => unreachable!(),
+    }
+
+    x.play(0);
+    o.play(1);
+    a.play(2);
+    match x.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 3)) {
+        ServerMessage::State { board, current_turn, .. } => {
+            assert_eq!(current_turn, 1);
+            assert_eq!(board.last().unwrap()[..3], [1, 2, 3]);
+        }
+        _ => unreachable!(),
+    }
+
+    // Quem desiste sai da roda e a partida segue com os outros
+    o.send("desistir");
+    x.play(0);
+    a.expect(|message| matches!(message, ServerMessage::State { board, current_turn, .. } if pieces(board) == 4 && *current_turn == 3));
+}
+
+#[test]
+fn hints_are_limited_per_game() {
+    let server = TestServer::start("hints");
+    let mut x = server.connect("ugo");
+    let mut o = server.connect("vera");
+
+    // Partidas ranqueadas não têm dicas nem jogadas desfeitas
+    x.send("/create dicas lig4 rated");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("ranqueada")));
+    o.send("/join dicas");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Oponente conectado")));
+    x.send("/hint");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("ranqueadas")));
+    x.send("4");
+    x.send("/undo");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("desfazer jogadas em partidas ranqueadas")));
+
+    let mut x = server.connect("wil");
+    x.send("/solo easy");
+    x.expect(|message| matches!(message, ServerMessage::State { .. }));
+    for remaining in (0..3).rev() {
+        x.send("/hint");
+        let remaining = format!("restantes nesta partida: {}.", remaining);
+        x.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Dica:") && message.contains(&remaining)));
+    }
+    x.send("/hint");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("já usou as 3 dicas")));
+}
+
+#[test]
+fn eval_is_for_spectators_while_the_game_runs() {
+    let server = TestServer::start("eval");
+    let mut x = server.connect("zeca");
+    let mut o = server.connect("alba");
+    let mut viewer = server.connect("bento");
+
+    x.send("/create avaliar");
+    o.send("/join avaliar");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Oponente conectado")));
+    x.send("/eval");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("só para quem assiste")));
+
+    // O tabuleiro vazio sai do livro: quem começa vence jogando no centro
+    viewer.send("/watch avaliar");
+    viewer.send("/eval");
+    viewer.expect(|message| {
+        matches!(message, ServerMessage::Info { message } if message.contains("o X vence") && message.contains("coluna 4"))
+    });
+
+    let mut custom = server.connect("cida");
+    custom.send("/create retirar lig4 popout");
+    custom.expect(|message| matches!(message, ServerMessage::State { .. }));
+    custom.send("/eval");
+    custom.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("lig4 padrão")));
+}
+
+#[test]
+fn puzzles_check_each_move_and_count_the_results() {
+    let server = TestServer::start("puzzle");
+    let mut x = server.connect("dora");
+
+    // Resolve o primeiro jogando como o servidor confere: a vitória a tempo contra a defesa dele
+    let puzzle = &PUZZLES[0];
+    let mut position = puzzle.position();
+    x.send("/puzzle 1");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Quebra-cabeça #1:")));
+    let col = puzzle::winning_move(&position, puzzle.moves).unwrap();
+    position.apply_move(&Move { col }).unwrap();
+    x.send(&(col + 1).to_string());
+    let reply = puzzle::defend(&position, puzzle.moves - 1).unwrap();
+    position.apply_move(&Move { col: reply }).unwrap();
+    let answer = format!("respondeu na coluna {}.", reply + 1);
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains(&answer)));
+    let col = puzzle::winning_move(&position, puzzle.moves - 1).unwrap();
+    x.send(&(col + 1).to_string());
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Resolvido!")));
+
+    // No segundo, qualquer outra primeira jogada deixa a vitória escapar
+    let position = PUZZLES[1].position();
+    let solution = puzzle::winning_move(&position, PUZZLES[1].moves).unwrap();
+    let wrong = (0..7).find(|col| *col != solution && is_valid_move(&position, &Move { col: *col })).unwrap();
+    x.send("/puzzle 2");
+    x.send(&(wrong + 1).to_string());
+    let expected = format!("Uma solução era jogar na coluna {}.", solution + 1);
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains(&expected)));
+
+    x.send("/puzzle stats");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("1 resolvidos em 2 tentativas")));
+}
+
+#[test]
+fn the_daily_challenge_is_shared_and_ranked() {
+    let server = TestServer::start("daily");
+    let mut x = server.connect("ines");
+    let mut o = server.connect("joel");
+
+    x.send("/daily top");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Ninguém resolveu")));
+
+    // Os dois recebem a mesma posição e a resolvem lendo o tabuleiro enviado
+    let first = solve_daily(&mut x);
+    assert_eq!(first, solve_daily(&mut o));
+    x.send("/daily");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("já resolveu o desafio de hoje")));
+
+    o.send("/daily top");
+    o.expect(|message| {
+        matches!(message, ServerMessage::Info { message } if message.contains("ines") && message.contains("joel"))
+    });
+}
+
+fn solve_daily(client: &mut Client) -> GameState {
+    client.send("/daily");
+    let mut position = match client.expect(|message| matches!(message, ServerMessage::State { .. })) {
+        ServerMessage::State { board, current_turn, .. } => GameState::from_grid(&board, current_turn, WIN_LENGTH),
+        _ => unreachable!(),
+    };
+    let start = position.clone();
+    for remaining in (1..=puzzle::DAILY_MOVES).rev() {
+        let col = puzzle::winning_move(&position, remaining).unwrap();
+        position.apply_move(&Move { col }).unwrap();
+        client.send(&(col + 1).to_string());
+        if remaining > 1 {
+            let reply = puzzle::defend(&position, remaining - 1).unwrap();
+            position.apply_move(&Move { col: reply }).unwrap();
+        }
+    }
+    client.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Desafio do dia resolvido")));
+    start
+}
+
+#[test]
+fn handicaps_block_columns_or_grant_an_extra_move() {
+    let server = TestServer::start("handicap");
+    let mut x = server.connect("lara");
+    let mut o = server.connect("milo");
+
+    // Com bloqueio, quem criou a sala não joga na coluna do meio no começo
+    x.send("/create bloqueada block1");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("coluna do meio fica bloqueada")));
+    o.send("/join bloqueada");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("não joga na coluna 4")));
+    x.expect(|message| matches!(message, ServerMessage::State { current_turn: 1, .. }));
+    x.send("4");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("bloqueada")));
+
+    // Com a jogada extra, quem entra joga duas vezes seguidas na primeira vez
+    let mut x = server.connect("nara");
+    let mut o = server.connect("otto");
+    x.send("/create extra extra");
+    o.send("/join extra");
+    x.play(0);
+    o.play(1);
+    o.play(2);
+    x.expect(|message| matches!(message, ServerMessage::State { board, current_turn: 1, .. } if pieces(board) == 3));
+}
+
+#[test]
+fn team_players_alternate_moves_and_chat_with_their_partner() {
+    let server = TestServer::start("teams");
+    let mut a = server.connect("ana");
+    let mut b = server.connect("beto");
+    let mut c = server.connect("caio");
+    let mut d = server.connect("dani");
+
+    a.send("/create duplas 2v2");
+    a.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("sala é de duplas")));
+    for client in [&mut b, &mut c, &mut d] {
+        client.send("/join duplas");
+        client.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Você entrou na sala")));
+    }
+    d.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("ana & caio (X) contra beto & dani (O)")));
+
+    // Cada lado reveza entre os parceiros: ana, beto, caio e dani, nessa ordem
+    a.play(0);
+    b.play(1);
+    a.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2));
+    a.send("3");
+    a.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("parceiro, caio")));
+    c.send("3");
+    d.expect(|message| matches!(message, ServerMessage::State { board, current_turn: -1, .. } if pieces(board) == 3));
+    d.send("4");
+    a.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 4));
+
+    // O chat da dupla não chega ao outro lado
+    a.send("/team vou no meio");
+    a.send("/say boa partida");
+    c.expect(|message| matches!(message, ServerMessage::Chat { from, message, .. } if from == "ana (dupla)" && message == "vou no meio"));
+    match b.expect(|message| matches!(message, ServerMessage::Chat { .. })) {
+        ServerMessage::Chat { from, message, .. } => assert_eq!((from.as_str(), message.as_str()), ("ana", "boa partida")),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn chess_clock_runs_on_the_movers_time_and_adds_the_increment() {
+    let server = TestServer::start("clock");
+    let mut x = server.connect("xavi");
+    let mut o = server.connect("yara");
+
+    x.send("/create relogio lig4 1+5");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("1 min no relógio")));
+    o.send("/join relogio");
+    // O relógio do X já corre; o do O está cheio
+    o.expect(|message| matches!(message, ServerMessage::Clock { x_ms, o_ms: 60_000 } if *x_ms <= 60_000));
+
+    // Quem joga gasta um pouco do próprio tempo e ganha os 5s de acréscimo
+    x.play(3);
+    match o.expect(|message| matches!(message, ServerMessage::Clock { x_ms, .. } if *x_ms > 60_000)) {
+        ServerMessage::Clock { x_ms, o_ms } => {
+            assert!(x_ms <= 65_000, "x_ms = {}", x_ms);
+            assert!(o_ms <= 60_000);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn resign_ends_the_game_for_both_players() {
+    let server = TestServer::start("resign");
+    let mut x = server.connect("gil");
+    let mut o = server.connect("hugo");
+
+    x.send("/create mesa");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("mesa")));
+    o.send("/join mesa");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    // Desistir vale mesmo fora da vez
+    o.send("desistir");
+    match x.expect(is_game_over) {
+        ServerMessage::GameOver { winner, you, line } => assert_eq!((winner, you, line.len()), (1, 1, 0)),
+        _ => unreachable!(),
+    }
+    match o.expect(is_game_over) {
+        ServerMessage::GameOver { winner, you, .. } => assert_eq!((winner, you), (1, -1)),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn opponent_join_is_announced_to_both_players() {
+    let server = TestServer::start("opponent_join");
+    let mut x = server.connect("iara");
+    let mut o = server.connect("joao");
+
+    x.send("/create mesa");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("mesa")));
+    o.send("/join mesa");
+
+    x.expect(|message| {
+        matches!(message, ServerMessage::Info { message } if message.contains("Oponente conectado: joao") && message.contains("começa"))
+    });
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Oponente conectado: iara")));
+}
+
+#[test]
+fn series_is_decided_by_majority_and_stored_once() {
+    let server = TestServer::start("series");
+    let mut x = server.connect("olga");
+    let mut o = server.connect("paulo");
+
+    x.send("/create mesa lig4 bo3");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("melhor de 3")));
+    o.send("/join mesa");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    // O primeiro jogo só encerra o jogo: o placar é anunciado e a série continua
+    o.send("desistir");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("olga 1 x 0 paulo")));
+    o.send("desistir");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("olga venceu a série")));
+    match x.expect(is_game_over) {
+        ServerMessage::GameOver { winner, you, line } => assert_eq!((winner, you, line.len()), (1, 1, 0)),
+        _ => unreachable!(),
+    }
+
+    // A série inteira fica como uma partida só no histórico
+    let mut reader = server.connect("quim");
+    reader.send("/history olga");
+    match reader.expect(|message| matches!(message, ServerMessage::History { .. })) {
+        ServerMessage::History { games, .. } => {
+            assert_eq!(games.len(), 1);
+            let series = games[0].series.expect("partida sem placar da série");
+            assert_eq!((series.best_of, series.wins_x, series.wins_o), (3, 2, 0));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn admin_can_announce_and_kick() {
+    let server = TestServer::start_with("admin", &["--admin-password", "segredo"]);
+    let mut player = server.connect("rita");
+    let mut admin = server.open();
+
+    admin.send("ADMIN segredo");
+    admin.send("announce Manutenção às 22h");
+    player.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Manutenção às 22h")));
+
+    admin.send("kick rita");
+    player.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("administrador")));
+    let mut line = String::new();
+    assert_eq!(player.reader.read_line(&mut line).unwrap(), 0, "a conexão derrubada continuou aberta");
+}
+
+#[test]
+fn aborted_game_is_not_recorded_and_players_return_to_the_lobby() {
+    let server = TestServer::start("abort");
+    let mut x = server.connect("lara");
+    let mut o = server.connect("nina");
+
+    x.send("/create anulada lig4");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("anulada")));
+    o.send("/join anulada");
+    x.play(0);
+    o.play(1);
+
+    // Depois da primeira jogada de cada lado, o oponente precisa aceitar
+    x.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2));
+    x.send("/abort");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("pediu para anular")));
+    o.send("/accept");
+    for client in [&mut x, &mut o] {
+        client.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("comum acordo")));
+        client.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("De volta ao lobby")));
+    }
+
+    // De volta ao lobby, os comandos dele valem de novo, e a partida não foi gravada
+    x.send("/history");
+    x.expect(|message| matches!(message, ServerMessage::History { games, .. } if games.is_empty()));
+    o.send("/create outra lig4");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Sala 'outra' criada")));
+}
+
+#[test]
+fn rated_rooms_use_the_shorter_turn_timeout() {
+    let server = TestServer::start_with("rated-timeout", &["--rated-turn-timeout", "2"]);
+    let mut x = server.connect("olga");
+    let mut o = server.connect("pedro");
+    let mut casual = server.connect("quim");
+
+    casual.send("/create amistosa");
+    casual.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("limite de 60s")));
+    x.send("/create relampago lig4 rated");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("limite de 2s")));
+    o.send("/join relampago");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("limite de 2s")));
+
+    // O X não joga e perde por tempo bem antes do limite das amistosas
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Tempo esgotado")));
+    o.expect(|message| matches!(message, ServerMessage::GameOver { winner: -1, .. }));
+}
+
+#[test]
+fn draw_offer_lapses_with_a_move_and_an_accepted_one_ends_the_game_tied() {
+    let server = TestServer::start("draw");
+    let mut x = server.connect("lara");
+    let mut o = server.connect("nina");
+
+    x.send("/create acordo lig4 rated");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("acordo")));
+    o.send("/join acordo");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    x.play(0);
+
+    // A jogada do O responde à proposta, que deixa de valer
+    x.send("/draw");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("propõe empate")));
+    o.send("2");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("deixou de valer")));
+    o.send("/accept");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("aguardando sua resposta")));
+
+    x.send("1");
+    x.send("/draw");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("propõe empate")));
+    o.send("/accept");
+    for client in [&mut x, &mut o] {
+        client.expect(|message| matches!(message, ServerMessage::Info { message } if message == "Empate de comum acordo."));
+        client.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Rating:")));
+        client.expect(|message| matches!(message, ServerMessage::GameOver { winner: 0, .. }));
+    }
+}
+
+#[test]
+fn draw_offer_stays_with_the_offerer_after_a_swap() {
+    let server = TestServer::start("draw-swap");
+    let mut x = server.connect("rui");
+    let mut o = server.connect("sara");
+
+    x.send("/create trocada lig4 rated swap");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("regra da troca")));
+    o.send("/join trocada");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    x.play(3);
+    o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1));
+
+    // Quem propôs e trocou de lado não pode aceitar a própria proposta
+    o.send("/draw");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("propõe empate")));
+    o.send("/swap");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Troca!")));
+    o.send("/accept");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("aguardando sua resposta")));
+
+    x.send("/accept");
+    for client in [&mut x, &mut o] {
+        client.expect(|message| matches!(message, ServerMessage::GameOver { winner: 0, .. }));
+    }
+}
+
+#[test]
+fn admin_bans_nicks_and_mutes_chat() {
+    let server = TestServer::start_with("moderation", &["--admin-password", "segredo"]);
+    let mut rita = server.connect("rita");
+    let mut admin = server.open();
+    admin.send("MODE json");
+    admin.send("ADMIN segredo");
+
+    // O banimento derruba quem está conectado e barra o apelido até ser retirado
+    admin.send("ban rita 2h");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("rita está banido por 2h00m")));
+    rita.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("administrador")));
+    let mut again = server.open();
+    again.send("MODE json");
+    again.send("NICK rita");
+    again.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("banido deste servidor por mais")));
+    admin.send("unban rita");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("banimento de rita foi retirado")));
+    again.send("NICK rita");
+    again.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("rita")));
+
+    // Quem foi silenciado continua na sala, mas o chat recusa as mensagens
+    let mut caio = server.connect("caio");
+    again.send("/create conversa lig4");
+    again.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("conversa")));
+    caio.send("/join conversa");
+    admin.send("mute rita");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("silenciado para sempre")));
+    again.send("/say oi");
+    again.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("silenciado")));
+    admin.send("unmute rita");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("voltar a usar o chat")));
+    again.send("/say oi");
+    caio.expect(|message| matches!(message, ServerMessage::Chat { from, message, .. } if from == "rita" && message == "oi"));
+}
+
+#[test]
+fn motd_is_shown_on_connect_and_reloaded_from_the_console() {
+    let motd = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("motd.txt");
+    std::fs::write(&motd, "Manutenção no domingo às 3h.\n").unwrap();
+    let server = TestServer::start_with("motd", &["--motd-file", motd.to_str().unwrap(), "--admin-password", "segredo"]);
+
+    // A mensagem chega antes da ajuda do lobby, ainda em texto
+    let mut guest = server.open();
+    let mut line = String::new();
+    guest.reader.read_line(&mut line).unwrap();
+    assert_eq!(line.trim_end(), "Mensagem do dia:");
+    line.clear();
+    guest.reader.read_line(&mut line).unwrap();
+    assert_eq!(line.trim_end(), "Manutenção no domingo às 3h.");
+
+    let mut admin = server.open();
+    admin.send("MODE json");
+    admin.send("ADMIN segredo");
+    std::fs::write(&motd, "Regras: nada de ofensas.\nBom jogo!\n").unwrap();
+    admin.send("motd reload");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("recarregada (2 linhas)")));
+
+    let mut rita = server.connect("rita");
+    rita.send("/motd");
+    rita.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Regras: nada de ofensas.\nBom jogo!")));
+
+    std::fs::write(&motd, "").unwrap();
+    admin.send("motd reload");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("retirada")));
+    rita.send("/motd");
+    rita.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Não há mensagem do dia")));
+}
+
+#[test]
+fn reload_applies_new_settings_to_open_connections() {
+    let config = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("reload.toml");
+    std::fs::write(&config, "turn_timeout_secs = 60\n").unwrap();
+    let server = TestServer::start_with("reload", &["--config", config.to_str().unwrap(), "--admin-password", "segredo"]);
+    let mut rita = server.connect("rita");
+    let mut admin = server.open();
+    admin.send("MODE json");
+    admin.send("ADMIN segredo");
+
+    // Um arquivo inválido não muda nada
+    std::fs::write(&config, "turn_timeout_secs = 0\n").unwrap();
+    admin.send("reload");
+    admin.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("nada foi mudado")));
+
+    std::fs::write(&config, "turn_timeout_secs = 45\nexport_dir = \"partidas\"\n").unwrap();
+    admin.send("reload");
+    admin.expect(|message| {
+        matches!(message, ServerMessage::Info { message } if message.contains("Configuração recarregada") && message.contains("reiniciar o servidor: export_dir"))
+    });
+    rita.send("/create tempo lig4");
+    rita.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("limite de 45s")));
+}
+
+#[test]
+fn word_filter_masks_chat_and_refuses_nicknames() {
+    let words = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("palavras.txt");
+    std::fs::write(&words, "# palavras proibidas\nbobo\n").unwrap();
+    let server = TestServer::start_with("filter", &["--blocked-words-file", words.to_str().unwrap()]);
+
+    let mut guest = server.open();
+    guest.send("MODE json");
+    guest.send("NICK B0b0ca");
+    guest.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("não é permitido")));
+
+    // O filtro normal esconde só a palavra inteira; a sala rigorosa esconde também as emendas
+    let mut x = server.connect("lara");
+    let mut o = server.connect("nina");
+    x.send("/create conversa lig4 strict");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("conversa")));
+    o.send("/join conversa");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Você entrou")));
+    x.send("/say que BOBO, superbobo");
+    o.expect(|message| matches!(message, ServerMessage::Chat { message, .. } if message == "que ****, *********"));
+
+    let mut y = server.connect("yara");
+    let mut z = server.connect("zeca");
+    y.send("/create papo lig4");
+    y.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("papo")));
+    z.send("/join papo");
+    z.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Você entrou")));
+    y.send("/say que BOBO, superbobo");
+    z.expect(|message| matches!(message, ServerMessage::Chat { message, .. } if message == "que ****, superbobo"));
+}
+
+#[test]
+fn metrics_report_connections_rooms_and_moves() {
+    let metrics_port = free_port();
+    let server = TestServer::start_with("metrics", &["--metrics-port", &metrics_port.to_string()]);
+    let mut x = server.connect("lara");
+    let mut o = server.connect("nina");
+
+    x.send("/create placar lig4");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("placar")));
+    o.send("/join placar");
+    for _ in 0..3 {
+        x.play(0);
+        o.play(1);
+    }
+    x.play(0);
+    x.expect(is_game_over);
+
+    let body = scrape(metrics_port);
+    for line in [
+        "reversi_connections 2",
+        "reversi_rooms 1",
+        "reversi_games_completed_total 1",
+        "reversi_moves_total 7",
+        "reversi_room_queue_depth{room=\"placar\"} 0",
+        // Clientes que leem tudo não ficam para trás
+        "reversi_events_lagged_total 0",
+        "reversi_slow_disconnects_total 0",
+    ] {
+        assert!(body.lines().any(|metric| metric == line), "faltou {:?} em:\n{}", line, body);
+    }
+}
+
+#[test]
+fn health_endpoints_report_listeners_database_and_rooms() {
+    let metrics_port = free_port();
+    let api_port = free_port();
+    let server = TestServer::start_with("health", &["--metrics-port", &metrics_port.to_string(), "--api-port", &api_port.to_string()]);
+    let mut x = server.connect("lara");
+    x.send("/create saude lig4");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("saude")));
+
+    for (port, path) in [(metrics_port, "/healthz"), (api_port, "/readyz")] {
+        let (status, body) = http_get(port, path);
+        assert!(status.contains("200"), "resposta inesperada: {} {}", status, body);
+        let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(report["status"], "ok");
+        assert_eq!(report["accepting"], true);
+        assert_eq!(report["database"], "ok");
+        assert_eq!(report["rooms"]["waiting"], 1);
+        assert!(report["listeners"].as_array().unwrap().iter().any(|listener| listener["transport"] == "tcp"));
+    }
+}
+
+// Faz um GET /metrics e devolve o corpo da resposta
+fn scrape(port: u16) -> String {
+    let (status, body) = http_get(port, "/metrics");
+    assert!(status.starts_with("HTTP/1.1 200"), "resposta inesperada: {} {}", status, body);
+    body
+}
+
+// Faz um GET e devolve a linha de status e o corpo da resposta
+fn http_get(port: u16, path: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+    stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+#[test]
+fn http_api_serves_rooms_games_leaderboard_and_player_stats() {
+    let api_port = free_port();
+    let server = TestServer::start_with("api", &["--api-port", &api_port.to_string()]);
+    let mut x = server.connect("olga");
+    let mut o = server.connect("pedro");
+
+    x.send("/create vitrine lig4 rated");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("vitrine")));
+    let (status, body) = http_get(api_port, "/api/rooms");
+    assert!(status.contains("200"));
+    let rooms: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+    assert_eq!(rooms[0]["name"], "vitrine");
+
+    o.send("/join vitrine");
+    for _ in 0..3 {
+        x.play(0);
+        o.play(1);
+    }
+    x.play(0);
+    x.expect(is_game_over);
+
+    let (_, body) = http_get(api_port, "/api/games/1");
+    let game: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!((game["player_x"].as_str(), game["winner"].as_i64()), (Some("olga"), Some(1)));
+    assert_eq!(game["moves"].as_array().unwrap().len(), 7);
+
+    let (_, body) = http_get(api_port, "/api/players/pedro/stats");
+    let stats: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!((stats["games"].as_u64(), stats["wins"].as_u64(), stats["losses"].as_u64()), (Some(1), Some(0), Some(1)));
+
+    let (_, body) = http_get(api_port, "/api/leaderboard");
+    let leaderboard: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+    assert_eq!(leaderboard[0]["nick"], "olga");
+
+    let (status, body) = http_get(api_port, "/api/games/999");
+    assert!(status.contains("404") && body.contains("\"error\""), "{} {}", status, body);
+}
+
+// Próximo evento de um fluxo SSE, pulando os comentários de keepalive
+fn next_event(events: &mut impl BufRead) -> (String, serde_json::Value) {
+    let (mut event, mut line) = (String::new(), String::new());
+    loop {
+        line.clear();
+        assert!(events.read_line(&mut line).unwrap() > 0, "o fluxo fechou");
+        if let Some(name) = line.strip_prefix("event: ") {
+            event = name.trim().to_string();
+        } else if let Some(data) = line.strip_prefix("data: ") {
+            return (event, serde_json::from_str(data).unwrap());
+        }
+    }
+}
+
+#[test]
+fn game_stream_sends_moves_and_board_deltas() {
+    let api_port = free_port();
+    let server = TestServer::start_with("stream", &["--api-port", &api_port.to_string()]);
+    let mut x = server.connect("rita");
+    let mut o = server.connect("tiago");
+
+    x.send("/create palco lig4");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("palco")));
+    o.send("/join palco");
+
+    let mut stream = TcpStream::connect(("127.0.0.1", api_port)).unwrap();
+    stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+    stream.write_all(b"GET /api/games/palco/stream HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+    let mut events = BufReader::new(stream);
+    let mut status = String::new();
+    events.read_line(&mut status).unwrap();
+    assert!(status.contains("200"), "{}", status);
+    let (event, state) = next_event(&mut events);
+    assert_eq!((event.as_str(), state["current_turn"].as_i64()), ("state", Some(1)));
+
+    x.play(0);
+    let (event, played) = next_event(&mut events);
+    assert_eq!(event, "move");
+    assert_eq!((played["row"].as_u64(), played["col"].as_u64(), played["val"].as_i64()), (Some(5), Some(0), Some(1)));
+    let (event, delta) = next_event(&mut events);
+    assert_eq!((event.as_str(), delta["type"].as_str()), ("delta", Some("delta")));
+    let (event, turn) = next_event(&mut events);
+    assert_eq!((event.as_str(), turn["current_turn"].as_i64()), ("turn", Some(-1)));
+
+    let (status, _) = http_get(api_port, "/api/games/vazio/stream");
+    assert!(status.contains("404"));
+}
+
+#[test]
+fn flooding_closes_the_connection_and_long_lines_are_ignored() {
+    let server = TestServer::start_with("flood", &["--rate-limit", "5", "--max-line-length", "64"]);
+
+    let mut flooder = server.connect("davi");
+    for _ in 0..100 {
+        flooder.send("/list");
+    }
+    flooder.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("Mensagens demais")));
+    let mut line = String::new();
+    while flooder.reader.read_line(&mut line).unwrap() > 0 {
+        line.clear();
+    }
+
+    let mut verbose = server.connect("enzo");
+    verbose.send(&"a".repeat(65));
+    verbose.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("longa demais")));
+    // A conexão continua, e controles e sequências telnet somem do texto
+    verbose.send("/create\x07 \u{1b}sala");
+    verbose.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Sala 'sala' criada")));
+}
+
+#[test]
+fn every_listen_address_reaches_the_same_lobby() {
+    let (port, second_port) = (free_port(), free_port());
+    let database = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("listen.db");
+    let _ = std::fs::remove_file(&database);
+    let listen = [format!("127.0.0.1:{}", port), format!("127.0.0.1:{}", second_port)];
+    let child = spawn_server(port, &database, &["--listen", &listen[0], "--listen", &listen[1]]);
+    let server = TestServer { child, port, database };
+    server.wait_until_ready();
+
+    // Quem cria a sala por uma porta recebe o oponente que entrou pela outra
+    let mut x = server.connect("tito");
+    let mut o = server.open_at(second_port);
+    o.send("MODE json");
+    o.send("NICK ugo");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("ugo")));
+    x.send("/create ponte");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("ponte")));
+    o.send("/join ponte");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Oponente conectado: ugo")));
+
+    // Se um dos endereços está ocupado, o servidor não sobe pela metade
+    let busy = TcpListener::bind("127.0.0.1:0").unwrap();
+    let busy_address = busy.local_addr().unwrap().to_string();
+    let free_address = format!("127.0.0.1:{}", free_port());
+    let database = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("listen_busy.db");
+    let mut child = spawn_server(free_port(), &database, &["--listen", &free_address, "--listen", &busy_address]);
+    let deadline = Instant::now() + READ_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        assert!(Instant::now() < deadline, "o servidor continuou rodando sem um dos endereços");
+        thread::sleep(Duration::from_millis(20));
+    };
+    assert!(!status.success());
+    let _ = std::fs::remove_file(&database);
+}
+
+#[test]
+fn websocket_clients_get_the_lobby_greeting() {
+    let websocket_port = free_port();
+    let _server = TestServer::start_with("websocket", &["--websocket-port", &websocket_port.to_string()]);
+
+    let deadline = Instant::now() + READ_TIMEOUT;
+    let stream = loop {
+        match TcpStream::connect(("127.0.0.1", websocket_port)) {
+            Ok(stream) => break stream,
+            Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            Err(err) => panic!("a porta WebSocket não abriu: {}", err),
+        }
+    };
+    stream.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+    let url = format!("ws://127.0.0.1:{}/", websocket_port);
+    let (mut socket, response) = tungstenite::client(url.as_str(), stream).expect("o handshake WebSocket falhou");
+    assert_eq!(response.status(), 101);
+
+    // Cada linha do protocolo vai numa mensagem de texto
+    for line in ["MODE json", "NICK rosa"] {
+        socket.write_message(Message::Text(line.to_string())).unwrap();
+    }
+    loop {
+        let text = match socket.read_message().expect("o servidor não respondeu a tempo") {
+            Message::Text(text) => text,
+            _ => continue,
+        };
+        if let Ok(ServerMessage::Info { message }) = serde_json::from_str(&text) {
+            if message.contains("Olá, rosa") {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn telnet_password_prompt_hides_what_is_typed() {
+    let telnet_port = free_port();
+    let server = TestServer::start_with("senha", &["--telnet-port", &telnet_port.to_string(), "--admin-password", "segredo"]);
+    // Registra o apelido com senha e o libera trocando de apelido na mesma conexão
+    let mut owner = server.connect("ana");
+    owner.send("NICK gil segredo");
+    owner.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("gil")));
+    owner.send("NICK bia");
+    owner.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("bia")));
+    let mut intruder = server.open();
+    intruder.send("MODE json");
+    intruder.send("NICK gil errada");
+    intruder.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("Senha incorreta")));
+
+    let deadline = Instant::now() + READ_TIMEOUT;
+    let mut telnet = loop {
+        match TcpStream::connect(("127.0.0.1", telnet_port)) {
+            Ok(stream) => break stream,
+            Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            Err(err) => panic!("a porta telnet não abriu: {}", err),
+        }
+    };
+    telnet.set_read_timeout(Some(READ_TIMEOUT)).unwrap();
+    let read_until = |telnet: &mut TcpStream, needle: &[u8]| {
+        let mut received = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while !received.windows(needle.len()).any(|window| window == needle) {
+            let count = telnet.read(&mut buffer).expect("o servidor não respondeu a tempo");
+            assert!(count > 0, "a conexão telnet fechou");
+            received.extend_from_slice(&buffer[..count]);
+        }
+        received
+    };
+
+    telnet.write_all(b"NICK gil\r\n").unwrap();
+    read_until(&mut telnet, b"Senha de gil: ");
+    telnet.write_all(b"segredo\r\n").unwrap();
+    let received = read_until(&mut telnet, "Olá, gil!".as_bytes());
+    let received = String::from_utf8_lossy(&received);
+    assert!(received.contains("*******"), "a senha não foi mascarada: {:?}", received);
+    assert!(!received.contains("segredo"), "a senha apareceu no eco: {:?}", received);
+
+    // A senha de administração também é perguntada à parte, com o mesmo eco mascarado
+    telnet.write_all(b"ADMIN\r\n").unwrap();
+    read_until(&mut telnet, "Senha de administração: ".as_bytes());
+    telnet.write_all(b"segredo\r\n").unwrap();
+    let received = read_until(&mut telnet, "Console de administração".as_bytes());
+    let received = String::from_utf8_lossy(&received);
+    assert!(received.contains("*******"), "a senha não foi mascarada: {:?}", received);
+    assert!(!received.contains("segredo"), "a senha apareceu no eco: {:?}", received);
+}
+
+#[test]
+fn commands_take_quoted_arguments_abbreviations_and_per_command_help() {
+    let server = TestServer::start("comandos");
+    let mut x = server.connect("ana");
+    let mut o = server.connect("bia");
+
+    x.send(r#"/cre "sala do gil""#);
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Sala 'sala do gil' criada")));
+    o.send("/c");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("ambíguo") && message.contains("/create")));
+    o.send("/help join");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Uso de /join:") && message.contains("/join code")));
+    o.send("4");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("dentro de uma sala")));
+    o.send(r#"/join "sala do gil""#);
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    o.send("/re");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("ambíguo")));
+    o.send("/xyz");
+    o.expect(|message| matches!(message, ServerMessage::Error { message, code } if message.contains("Comando desconhecido") && *code == Some(ErrorCode::UnknownCommand)));
+}
+
+#[test]
+fn idle_connection_is_closed() {
+    let server = TestServer::start_with("idle", &["--idle-timeout", "1"]);
+    let mut client = server.connect("kaua");
+
+    client.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("inatividade")));
+    let mut line = String::new();
+    assert_eq!(client.reader.read_line(&mut line).unwrap(), 0, "a conexão ociosa continuou aberta");
+}
+
+#[test]
+fn hello_downgrades_to_the_server_version() {
+    let server = TestServer::start("hello");
+    let mut client = server.open();
+
+    client.send("HELLO v3 caps=json,utf8,sound lang=fr");
+    match client.expect(|message| matches!(message, ServerMessage::Hello { .. })) {
+        ServerMessage::Hello { version, caps, lang } => {
+            assert_eq!(version, 1);
+            assert_eq!(caps, ["json", "utf8"]);
+            assert_eq!(lang, "pt");
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn each_connection_gets_messages_in_its_own_language() {
+    let server = TestServer::start("lang");
+    let mut english = server.open();
+    english.send("HELLO v1 caps=json lang=en");
+    english.send("NICK paul");
+    english.expect(|message| matches!(message, ServerMessage::Info { message } if message == "Hello, paul!"));
+    let mut portuguese = server.connect("rui");
+
+    english.send("/create ponte");
+    english.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Room 'ponte' created")));
+    portuguese.send("/join ponte");
+    portuguese.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Você entrou na sala 'ponte'")));
+    english.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Opponent connected: rui")));
+}
+
+#[test]
+fn delta_clients_receive_only_the_changed_cells() {
+    let server = TestServer::start("delta");
+    let mut x = server.open();
+    x.send("HELLO v1 caps=json,delta");
+    x.send("NICK lia");
+    let mut o = server.connect("teo");
+
+    x.send("/create trilho lig4");
+    x.expect(|message| matches!(message, ServerMessage::State { .. }));
+    o.send("/join trilho");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    x.send("4");
+    match x.expect(|message| matches!(message, ServerMessage::Delta { .. } | ServerMessage::State { .. })) {
+        ServerMessage::Delta { row, col, val } => assert_eq!((row, col, val), (5, 3, 1)),
+        message => panic!("esperava um delta, veio {:?}", message),
+    }
+    // A troca de vez traz as jogadas possíveis, como o estado inteiro
+    match x.expect(|message| matches!(message, ServerMessage::Turn { current_turn: -1, .. })) {
+        ServerMessage::Turn { legal_moves, .. } => assert_eq!(legal_moves.len(), 7),
+        _ => unreachable!(),
+    }
+
+    // O comando board volta a mandar o estado inteiro
+    x.send("board");
+    match x.expect(|message| matches!(message, ServerMessage::State { .. })) {
+        ServerMessage::State { board, legal_moves, .. } => {
+            assert_eq!(pieces(&board), 1);
+            assert!(legal_moves.contains(&LegalMove { row: 4, col: 3 }));
+            assert!(legal_moves.iter().filter(|legal| legal.col != 3).all(|legal| legal.row == 5));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn confirm_clients_preview_moves_before_playing() {
+    let server = TestServer::start("confirm");
+    let mut x = server.open();
+    x.send("HELLO v1 caps=json,confirm");
+    x.send("NICK nina");
+    let mut o = server.connect("caio");
+
+    x.send("/create prova lig4");
+    x.expect(|message| matches!(message, ServerMessage::State { .. }));
+    o.send("/join prova");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    x.send("4");
+    match x.expect(|message| matches!(message, ServerMessage::Preview { .. })) {
+        ServerMessage::Preview { row, col, board } => assert_eq!((row, col, board[5][3]), (5, 3, 1)),
+        _ => unreachable!(),
+    }
+    x.send("/cancel");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Jogada cancelada")));
+    x.send("/confirm");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("jogada para confirmar")));
+
+    // A jogada só vale depois do /confirm
+    x.send("2");
+    x.expect(|message| matches!(message, ServerMessage::Preview { col: 1, .. }));
+    x.send("/confirm");
+    match x.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1)) {
+        ServerMessage::State { board, current_turn, .. } => assert_eq!((board[5][1], current_turn), (1, -1)),
+        _ => unreachable!(),
+    }
+
+    // Quem não negociou a capacidade continua jogando em um passo
+    o.send("3");
+    o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2));
+}
+
+#[test]
+fn first_player_is_drawn_from_a_recorded_seed() {
+    let server = TestServer::start_with("seed", &["--first-player-seed", "0"]);
+    let mut x = server.connect("lais");
+    let mut o = server.connect("yuri");
+
+    x.send("/create sorteio");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("sorteio")));
+    o.send("/join sorteio");
+    // A semente 0 dá a primeira jogada ao O
+    x.expect(|message| {
+        matches!(message, ServerMessage::Info { message } if message.contains("o O começa") && message.contains("semente 0"))
+    });
+    for _ in 0..3 {
+        o.play(0);
+        x.play(1);
+    }
+    o.play(0);
+    o.expect(is_game_over);
+
+    let mut reader = server.connect("rui");
+    reader.send("/export 1");
+    match reader.expect(|message| matches!(message, ServerMessage::Export { .. })) {
+        ServerMessage::Export { text, .. } => assert!(text.contains("[Seed \"0\"]") && text.contains("0-1"), "{}", text),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn bell_rings_when_the_opponent_joins_and_on_each_turn() {
+    let server = TestServer::start("bell");
+    let mut x = server.connect("bela");
+    let mut o = server.connect("davi");
+
+    x.send("/bell on");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Aviso sonoro ativado")));
+    x.send("/create sino");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("sino")));
+    o.send("/join sino");
+    x.expect(|message| matches!(message, ServerMessage::Bell));
+
+    // O tabuleiro chega antes do aviso, então a jogada vai direto
+    x.send("4");
+    o.play(3);
+    x.expect(|message| matches!(message, ServerMessage::Bell));
+    x.send("/bell off");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("desativado")));
+}
+
+#[test]
+fn bots_get_your_turn_events_and_error_codes() {
+    let server = TestServer::start("bot");
+    let mut bot = server.open();
+    bot.send("HELLO v1 caps=bot,color");
+    match bot.expect(|message| matches!(message, ServerMessage::Hello { .. })) {
+        ServerMessage::Hello { caps, .. } => assert_eq!(caps, ["bot"]),
+        _ => unreachable!(),
+    }
+    bot.send("NICK robo");
+    bot.send("/create maquina");
+    let mut o = server.connect("rita");
+    o.send("/join maquina");
+    o.send("1");
+    o.expect(|message| matches!(message, ServerMessage::Error { code: Some(code), .. } if *code == ErrorCode::NotYourTurn));
+
+    let legal_moves = match bot.expect(|message| matches!(message, ServerMessage::YourTurn { .. })) {
+        ServerMessage::YourTurn { you, legal_moves } => {
+            assert_eq!(you, 1);
+            legal_moves
+        }
+        _ => unreachable!(),
+    };
+    assert_eq!(legal_moves.len(), 7);
+    assert!(legal_moves.iter().all(|cell| cell.row == 5));
+    bot.send(r#"{"col": 9}"#);
+    bot.expect(|message| matches!(message, ServerMessage::Error { code: Some(code), .. } if *code == ErrorCode::InvalidColumn));
+
+    // A casa da lista volta como está, como a jogada
+    bot.send(&serde_json::to_string(&legal_moves[3]).unwrap());
+    o.play(3);
+    match bot.expect(|message| matches!(message, ServerMessage::YourTurn { .. })) {
+        ServerMessage::YourTurn { legal_moves, .. } => assert!(legal_moves.iter().any(|cell| (cell.row, cell.col) == (3, 3))),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn exhibition_rooms_seat_bots_and_space_out_the_moves() {
+    let server = TestServer::start("exhibition");
+    let mut viewer = server.connect("vera");
+    viewer.send("/exhibition vitrine bot easy 1");
+    viewer.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("vitrine")));
+
+    // Gente entra só para assistir; o lugar do X é de um bot
+    let mut human = server.connect("hugo");
+    human.send("/join vitrine");
+    human.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("exibição")));
+    let mut bot = server.open();
+    bot.send("HELLO v1 caps=bot");
+    bot.send("NICK robo");
+    bot.send("/join vitrine");
+    let started = Instant::now();
+    match bot.expect(|message| matches!(message, ServerMessage::YourTurn { .. })) {
+        ServerMessage::YourTurn { you, legal_moves } => {
+            assert_eq!(you, 1);
+            bot.send(&serde_json::to_string(&legal_moves[3]).unwrap());
+        }
+        _ => unreachable!(),
+    }
+
+    // A jogada do bot e a resposta do computador esperam o atraso da sala
+    viewer.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1));
+    let first = started.elapsed();
+    viewer.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2));
+    assert!(first >= Duration::from_millis(900), "a primeira jogada veio em {:?}", first);
+    assert!(started.elapsed() - first >= Duration::from_millis(900));
+}
+
+#[test]
+fn the_crowd_plays_the_most_voted_column_against_the_computer() {
+    let server = TestServer::start("crowd");
+    let mut host = server.connect("vitor");
+    host.send("/crowd torcida easy 6");
+    host.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("torcida")));
+    let mut fan = server.connect("wanda");
+    fan.send("/watch torcida");
+    fan.expect(|message| matches!(message, ServerMessage::State { .. }));
+
+    // Cada um tem um voto, e vale o último
+    host.send("1");
+    host.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("coluna 1")));
+    host.send("/vote 5");
+    host.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("coluna 5")));
+    fan.send("5");
+    fan.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("coluna 5")));
+
+    fan.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Faltam 5s")));
+    fan.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("coluna 5 (2 de 2 votos)")));
+    // O computador responde, e a votação abre de novo
+    match fan.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 2)) {
+        ServerMessage::State { board, .. } => assert_eq!(board[board.len() - 1][4], 1),
+        _ => unreachable!(),
+    }
+    fan.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Votação aberta")));
+}
+
+#[test]
+fn connections_over_the_per_address_limit_are_turned_away() {
+    let server = TestServer::start_with("connection_limit", &["--max-connections-per-ip", "2"]);
+    let _first = server.connect("ana");
+    let _second = server.connect("beto");
+
+    let mut third = server.open();
+    let mut output = String::new();
+    third.reader.read_to_string(&mut output).unwrap();
+    assert!(output.contains("2 conexões abertas"), "resposta inesperada: {}", output);
+}
+
+#[test]
+fn hello_with_unsupported_version_is_rejected() {
+    let server = TestServer::start("hello_rejected");
+    let mut client = server.open();
+
+    client.send("HELLO v0");
+    let mut output = String::new();
+    client.reader.read_to_string(&mut output).unwrap();
+    assert!(output.contains("não suportada"), "resposta inesperada: {}", output);
+}
+
+#[test]
+fn finished_game_is_exported() {
+    let export_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("exported");
+    let _ = std::fs::remove_dir_all(&export_dir);
+    let server = TestServer::start_with("export", &["--export-dir", export_dir.to_str().unwrap()]);
+    let mut x = server.connect("lara");
+    let mut o = server.connect("mauro");
+
+    x.send("/create mesa");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("mesa")));
+    o.send("/join mesa");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    for _ in 0..3 {
+        x.play(0);
+        o.play(1);
+    }
+    x.play(0);
+    x.expect(is_game_over);
+
+    let mut reader = server.connect("nina");
+    reader.send("/export lara");
+    reader.expect(|message| matches!(message, ServerMessage::Error { .. }));
+    reader.send("/export 1");
+    let text = match reader.expect(|message| matches!(message, ServerMessage::Export { .. })) {
+        ServerMessage::Export { id, text } => {
+            assert_eq!(id, 1);
+            text
+        }
+        _ => unreachable!(),
+    };
+    assert!(text.contains("[X \"lara\"]"));
+    assert!(text.ends_with("1. 1 2 2. 1 2 3. 1 2 4. 1 1-0\n"), "exportação inesperada: {}", text);
+
+    // O servidor também grava a partida em arquivo
+    let saved = std::fs::read_to_string(export_dir.join("partida-1.pgn")).unwrap();
+    assert_eq!(saved, text);
+}
+
+#[test]
+fn private_rooms_are_hidden_and_joined_by_code() {
+    let server = TestServer::start("private");
+    let mut x = server.connect("zeca");
+    let mut o = server.connect("alba");
+    let mut stranger = server.connect("bia");
+
+    x.send("/create private amigos");
+    let code = match x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("/join code"))) {
+        ServerMessage::Info { message } => message.trim_end().rsplit(' ').next().unwrap().to_string(),
+        _ => unreachable!(),
+    };
+    assert_eq!(code.len(), 6);
+
+    stranger.send("/list");
+    match stranger.expect(|message| matches!(message, ServerMessage::Rooms { .. })) {
+        ServerMessage::Rooms { rooms } => assert!(rooms.is_empty()),
+        _ => unreachable!(),
+    }
+    stranger.send("/join amigos");
+    stranger.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("privada")));
+
+    o.send(&format!("/join code {}", code.to_lowercase()));
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Você entrou na sala 'amigos'")));
+}
+
+#[test]
+fn friends_and_accepted_challenges_open_a_private_room() {
+    let server = TestServer::start("challenge");
+    let mut x = server.connect("caio");
+    let mut o = server.connect("dora");
+
+    x.send("/friend add dora");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("dora agora está na sua lista")));
+    x.send("/friends");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("dora (no lobby)")));
+
+    x.send("/challenge dora");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("caio desafiou você")));
+    o.send("/decline caio");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("dora não aceitou o desafio")));
+
+    x.send("/challenge dora");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("caio desafiou você")));
+    o.send("/accept caio");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Sala privada")));
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("dora aceitou o desafio")));
+    x.send("4");
+    o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1));
+
+    let mut viewer = server.connect("edu");
+    viewer.send("/list");
+    match viewer.expect(|message| matches!(message, ServerMessage::Rooms { .. })) {
+        ServerMessage::Rooms { rooms } => assert!(rooms.is_empty()),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn lobby_chat_reaches_the_lobby_and_who_lists_everyone() {
+    let server = TestServer::start("who");
+    let mut seated = server.connect("hugo");
+    let mut queued = server.connect("iara");
+    let mut idle = server.connect("joao");
+
+    seated.send("/create mesa");
+    seated.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("mesa")));
+    queued.send("/queue");
+    queued.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Procurando oponente")));
+
+    idle.send("/who");
+    idle.expect(|message| {
+        matches!(message, ServerMessage::Info { message }
+            if message.contains("hugo (na sala 'mesa')") && message.contains("iara (na fila)") && message.contains("joao (no lobby)"))
+    });
+
+    idle.send("/say oi,  lobby");
+    match queued.expect(|message| matches!(message, ServerMessage::Chat { .. })) {
+        ServerMessage::Chat { from, message, .. } => assert_eq!((from.as_str(), message.as_str()), ("joao", "oi,  lobby")),
+        _ => unreachable!(),
+    }
+    idle.expect(|message| matches!(message, ServerMessage::Chat { from, .. } if from == "joao"));
+}
+
+#[test]
+fn tournament_runs_the_bracket_and_records_the_champion() {
+    let server = TestServer::start_with("tournament", &["--admin-password", "segredo", "--tournament-registration", "2"]);
+    let mut admin = server.open();
+    admin.send("MODE json");
+    admin.send("ADMIN segredo");
+    admin.send("tournament lig4");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Inscrições abertas;")));
+
+    let mut x = server.connect("luna");
+    let mut o = server.connect("mauro");
+    x.send("/tournament join");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Você está inscrito")));
+    o.send("/tournament join");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Você está inscrito")));
+
+    // Com o mesmo ELO, quem se inscreveu primeiro é o cabeça de chave e joga de X
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Partida do torneio contra mauro")));
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Partida do torneio contra luna")));
+    for _ in 0..3 {
+        x.play(0);
+        o.play(1);
+    }
+    x.play(0);
+
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("luna é o campeão do torneio de lig4")));
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("eliminado do torneio na rodada 1")));
+    o.send("/tournament history");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("#1 lig4, 2 jogadores: campeão luna")));
+}
+
+#[test]
+fn tournament_games_cannot_be_aborted_or_paused() {
+    let server = TestServer::start_with("tournament-abort", &["--admin-password", "segredo", "--tournament-registration", "2"]);
+    let mut admin = server.open();
+    admin.send("MODE json");
+    admin.send("ADMIN segredo");
+    admin.send("tournament lig4");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.starts_with("Inscrições abertas;")));
+
+    let mut x = server.connect("tito");
+    let mut o = server.connect("vera");
+    x.send("/tournament join");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Você está inscrito")));
+    o.send("/tournament join");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Você está inscrito")));
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Partida do torneio contra vera")));
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Partida do torneio contra tito")));
+
+    // Anular antes das duas primeiras jogadas tiraria o X do chaveamento sem jogar
+    x.send("/abort");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("não podem ser anuladas")));
+    x.send("/pause");
+    x.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("não podem ser pausadas")));
+
+    // A partida segue, e quem desiste perde, mesmo jogando de X
+    x.send("desistir");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("vera é o campeão do torneio de lig4")));
+}
+
+#[test]
+fn rated_games_get_a_fair_play_report_for_admins() {
+    let server = TestServer::start_with("fairplay", &["--admin-password", "segredo"]);
+    let mut x = server.connect("luna");
+    let mut o = server.connect("mauro");
+
+    x.send("/create limpo lig4 rated");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("ranqueada")));
+    o.send("/join limpo");
+    o.expect(|message| matches!(message, ServerMessage::State { .. }));
+    for _ in 0..3 {
+        x.play(0);
+        o.play(1);
+    }
+    x.play(0);
+    x.expect(|message| matches!(message, ServerMessage::GameOver { .. }));
+
+    let mut admin = server.open();
+    admin.send("MODE json");
+    admin.send("ADMIN segredo");
+    admin.send("fairplay 99");
+    admin.expect(|message| matches!(message, ServerMessage::Error { message, .. } if message.contains("não tem relatório")));
+
+    // A análise roda depois do fim da partida, fora da sala, e o solucionador tem até 10s
+    let report = (0..150)
+        .find_map(|_| {
+            admin.send("fairplay 1");
+            match admin.expect(|message| matches!(message, ServerMessage::Info { .. } | ServerMessage::Error { .. })) {
+                ServerMessage::Info { message } if message.contains("partida #1") => Some(message),
+                _ => {
+                    std::thread::sleep(Duration::from_millis(100));
+                    None
+                }
+            }
+        })
+        .expect("o relatório não foi gravado");
+    assert!(report.contains("luna (X):"), "{}", report);
+    assert!(report.contains("mauro (O):"), "{}", report);
+    assert!(report.contains("Tempo por jogada"), "{}", report);
+
+    admin.send("fairplay");
+    admin.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("#1 lig4:") && message.contains("mauro:")));
+}
+
+#[test]
+fn away_players_are_warned_and_casual_games_are_cancelled() {
+    let server = TestServer::start_with("afk", &["--afk-timeout", "2"]);
+    let mut x = server.connect("fabi");
+    let mut o = server.connect("gil");
+
+    x.send("/create ausente");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("criada")));
+    o.send("/join ausente");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("Oponente conectado")));
+
+    // O X segue dando sinal de vida; o O fica parado
+    std::thread::sleep(Duration::from_secs(1));
+    x.send("board");
+    o.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("O O está ausente")));
+    x.send("board");
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("a partida foi cancelada")));
+    x.expect(|message| matches!(message, ServerMessage::Info { message } if message.contains("A sala foi encerrada")));
+}
+
+#[test]
+fn periodic_snapshots_keep_games_after_a_crash() {
+    let mut server = TestServer::start_with("snapshot", &["--snapshot-interval", "1"]);
+    let mut x = server.connect("hugo");
+    let mut o = server.connect("iara");
+
+    x.send("/create queda");
+    let code_x = reconnect_code(&mut x);
+    o.send("/join queda");
+    let code_o = reconnect_code(&mut o);
+    x.play(2);
+    o.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1));
+
+    // A queda vem sem aviso: só a cópia periódica guardou a partida
+    thread::sleep(Duration::from_millis(1500));
+    server.restart();
+    let mut x = server.open();
+    x.send("MODE json");
+    x.send(&format!("RESUME {}", code_x));
+    x.expect(|message| matches!(message, ServerMessage::State { board, .. } if pieces(board) == 1));
+    let mut o = server.open();
+    o.send("MODE json");
+    o.send(&format!("RESUME {}", code_o));
+    o.expect(|message| matches!(message, ServerMessage::State { board, current_turn, .. } if pieces(board) == 1 && *current_turn == -1));
+}